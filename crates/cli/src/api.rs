@@ -0,0 +1,273 @@
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use crate::error::CliError;
+
+/// Client for the ralph-kanban server's REST API
+pub struct ApiClient {
+    client: Client,
+    base_url: String,
+}
+
+/// `ApiResponse` wrapper every endpoint responds with
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    success: bool,
+    data: Option<T>,
+    message: Option<String>,
+}
+
+/// Request body for POST /api/tasks
+#[derive(Debug, Serialize)]
+pub struct CreateTaskRequest {
+    pub project_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub assignee: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Task {
+    pub id: Uuid,
+    pub title: String,
+    pub status: String,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub has_in_progress_attempt: bool,
+    #[serde(default)]
+    pub last_attempt_failed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub branch: String,
+    pub archived: bool,
+}
+
+/// Response from GET /api/workspaces/{id}/status
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceStatus {
+    pub workspace_id: String,
+    pub status: String,
+    pub files_changed: Option<usize>,
+    pub lines_added: Option<usize>,
+    pub lines_removed: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileDiff {
+    pub path: String,
+    pub additions: usize,
+    pub deletions: usize,
+    pub diff_content: String,
+    /// committed, staged, unstaged or untracked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Previous path of a renamed or copied file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepoDiff {
+    pub repo_id: String,
+    pub repo_name: String,
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+    pub files: Vec<FileDiff>,
+}
+
+/// Response from GET /api/workspaces/{id}/diff
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceDiff {
+    pub workspace_id: String,
+    pub repos: Vec<RepoDiff>,
+}
+
+/// Response from GET /api/workspaces/{id}/transcript
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceTranscript {
+    pub workspace_id: String,
+    pub prompt: Option<String>,
+    pub summary: Option<String>,
+}
+
+/// Response from POST /api/workspaces/{id}/close
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloseResult {
+    pub workspace_id: String,
+    pub success: bool,
+    pub message: String,
+    pub merge_commit_sha: Option<String>,
+}
+
+impl ApiClient {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            client: Client::new(),
+            base_url,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
+    /// Send a request and unwrap the `ApiResponse` envelope. Error responses carry the
+    /// server's message, so the body is read whatever the status.
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, CliError> {
+        let response = request
+            .send()
+            .await
+            .map_err(|source| CliError::Connection {
+                url: self.base_url.clone(),
+                source,
+            })?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| CliError::InvalidResponse(e.to_string()))?;
+
+        let envelope: Envelope<T> = match serde_json::from_str(&body) {
+            Ok(envelope) => envelope,
+            Err(_) if !status.is_success() => {
+                return Err(CliError::Api {
+                    status: status.as_u16(),
+                    message: body.trim().to_string(),
+                });
+            }
+            Err(e) => return Err(CliError::InvalidResponse(e.to_string())),
+        };
+
+        if !status.is_success() || !envelope.success {
+            return Err(CliError::Api {
+                status: status.as_u16(),
+                message: envelope
+                    .message
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            });
+        }
+        envelope
+            .data
+            .ok_or_else(|| CliError::InvalidResponse("response has no data".to_string()))
+    }
+
+    pub async fn create_task(&self, request: &CreateTaskRequest) -> Result<Task, CliError> {
+        self.send(self.client.post(self.url("/api/tasks")).json(request))
+            .await
+    }
+
+    pub async fn list_tasks(
+        &self,
+        project_id: Uuid,
+        status: Option<&str>,
+    ) -> Result<Vec<Task>, CliError> {
+        let mut query = vec![("project_id", project_id.to_string())];
+        if let Some(status) = status {
+            query.push(("status", status.to_string()));
+        }
+        self.send(self.client.get(self.url("/api/tasks")).query(&query))
+            .await
+    }
+
+    /// Workspaces of a task, most recent first
+    pub async fn list_workspaces(&self, task_id: Uuid) -> Result<Vec<Workspace>, CliError> {
+        self.send(
+            self.client
+                .get(self.url("/api/task-attempts"))
+                .query(&[("task_id", task_id)]),
+        )
+        .await
+    }
+
+    /// Open the project's server-sent event stream, resuming after `last_event_id`
+    pub async fn project_events(
+        &self,
+        project_id: Uuid,
+        last_event_id: Option<u64>,
+    ) -> Result<reqwest::Response, CliError> {
+        let mut request = self
+            .client
+            .get(self.url(&format!("/api/projects/{project_id}/events")));
+        if let Some(id) = last_event_id {
+            request = request.header("Last-Event-ID", id.to_string());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|source| CliError::Connection {
+                url: self.base_url.clone(),
+                source,
+            })?;
+        if !response.status().is_success() {
+            return Err(CliError::Api {
+                status: response.status().as_u16(),
+                message: "could not open the event stream".to_string(),
+            });
+        }
+        Ok(response)
+    }
+
+    pub async fn workspace_status(&self, id: Uuid) -> Result<WorkspaceStatus, CliError> {
+        self.send(
+            self.client
+                .get(self.url(&format!("/api/workspaces/{id}/status"))),
+        )
+        .await
+    }
+
+    pub async fn workspace_diff(
+        &self,
+        id: Uuid,
+        repo: Option<&str>,
+    ) -> Result<WorkspaceDiff, CliError> {
+        let mut request = self
+            .client
+            .get(self.url(&format!("/api/workspaces/{id}/diff")));
+        if let Some(repo) = repo {
+            request = request.query(&[("repo", repo)]);
+        }
+        self.send(request).await
+    }
+
+    pub async fn workspace_transcript(&self, id: Uuid) -> Result<WorkspaceTranscript, CliError> {
+        self.send(
+            self.client
+                .get(self.url(&format!("/api/workspaces/{id}/transcript"))),
+        )
+        .await
+    }
+
+    pub async fn close_workspace(
+        &self,
+        id: Uuid,
+        strategy: &str,
+        force: bool,
+    ) -> Result<CloseResult, CliError> {
+        self.send(
+            self.client
+                .post(self.url(&format!("/api/workspaces/{id}/close")))
+                .query(&[("force", force)])
+                .json(&serde_json::json!({ "strategy": strategy })),
+        )
+        .await
+    }
+}
@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("Could not find a running server. Start it, or pass --server / set RALPH_SERVER_URL.")]
+    ServerNotFound,
+
+    #[error("Failed to reach the server at {url}: {source}")]
+    Connection {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("Server returned {status}: {message}")]
+    Api { status: u16, message: String },
+
+    #[error("Failed to parse server response: {0}")]
+    InvalidResponse(String),
+}
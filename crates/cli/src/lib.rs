@@ -0,0 +1,41 @@
+//! Client library for a running ralph-kanban server, shared by the `ralph` CLI and the TUI.
+
+pub mod api;
+pub mod error;
+
+use std::env;
+
+use error::CliError;
+
+/// Name the server writes its port file under in the temp directory
+const PORT_FILE_APP_NAME: &str = "vibe-kanban";
+
+/// `--server`, then `VIBE_BACKEND_URL`, then the port file the server writes on startup
+pub fn resolve_server_url(server: Option<String>) -> Result<String, CliError> {
+    if let Some(url) = server.or_else(|| env::var("VIBE_BACKEND_URL").ok()) {
+        return Ok(url);
+    }
+    let host = env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let port = match env::var("BACKEND_PORT").or_else(|_| env::var("PORT")) {
+        Ok(port) => port,
+        Err(_) => {
+            let path = env::temp_dir()
+                .join(PORT_FILE_APP_NAME)
+                .join(format!("{PORT_FILE_APP_NAME}.port"));
+            std::fs::read_to_string(path).map_err(|_| CliError::ServerNotFound)?
+        }
+    };
+    let port: u16 = port.trim().parse().map_err(|_| CliError::ServerNotFound)?;
+    Ok(format!("http://{host}:{port}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_server_url_wins() {
+        let url = resolve_server_url(Some("http://example:1234".to_string())).unwrap();
+        assert_eq!(url, "http://example:1234");
+    }
+}
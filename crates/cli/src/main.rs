@@ -0,0 +1,232 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
+use cli::{
+    api::{ApiClient, CreateTaskRequest},
+    resolve_server_url,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Parser, Debug)]
+#[command(name = "ralph")]
+#[command(about = "Command-line client for a running ralph-kanban server")]
+#[command(version)]
+struct Cli {
+    /// Server URL; defaults to the locally running server
+    #[arg(long, global = true, env = "RALPH_SERVER_URL")]
+    server: Option<String>,
+
+    /// Print the server's response as JSON instead of text
+    #[arg(long, global = true, default_value_t = false)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Create and list tasks
+    #[command(subcommand)]
+    Task(TaskCommand),
+    /// Inspect and close workspaces
+    #[command(subcommand, name = "ws")]
+    Workspace(WorkspaceCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum TaskCommand {
+    /// Create a task in a project
+    Create(CreateTaskArgs),
+    /// List a project's tasks, newest first
+    List {
+        #[arg(long, env = "RALPH_PROJECT_ID")]
+        project: Uuid,
+        /// Comma-separated statuses, e.g. `todo,inprogress`
+        #[arg(long)]
+        status: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct CreateTaskArgs {
+    #[arg(long, env = "RALPH_PROJECT_ID")]
+    project: Uuid,
+    #[arg(long)]
+    title: String,
+    #[arg(long)]
+    description: Option<String>,
+    /// low, medium, high or urgent
+    #[arg(long)]
+    priority: Option<String>,
+    /// May be repeated
+    #[arg(long = "label")]
+    labels: Vec<String>,
+    #[arg(long)]
+    assignee: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum WorkspaceCommand {
+    /// Latest agent run status and diff stats
+    Status { id: Uuid },
+    /// Unified diff of the workspace against its target branches
+    Diff {
+        id: Uuid,
+        /// Only diff this repo, by name or id
+        #[arg(long)]
+        repo: Option<String>,
+    },
+    /// Prompt and summary of the latest agent turn
+    Transcript { id: Uuid },
+    /// Merge or discard the workspace's changes and archive it
+    Close {
+        id: Uuid,
+        /// Merge the changes into the target branches
+        #[arg(long, conflicts_with = "discard", required_unless_present = "discard")]
+        merge: bool,
+        /// Throw the changes away
+        #[arg(long)]
+        discard: bool,
+        /// Merge even if required CI checks have not passed
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let client = ApiClient::new(resolve_server_url(cli.server)?);
+
+    match cli.command {
+        Command::Task(TaskCommand::Create(args)) => {
+            let request = CreateTaskRequest {
+                project_id: args.project,
+                title: args.title,
+                description: args.description,
+                priority: args.priority,
+                labels: (!args.labels.is_empty()).then_some(args.labels),
+                assignee: args.assignee,
+            };
+            let task = client.create_task(&request).await?;
+            if cli.json {
+                return print_json(&task);
+            }
+            println!("{}", task.id);
+        }
+        Command::Task(TaskCommand::List { project, status }) => {
+            let tasks = client.list_tasks(project, status.as_deref()).await?;
+            if cli.json {
+                return print_json(&tasks);
+            }
+            for task in tasks {
+                let running = if task.has_in_progress_attempt {
+                    " (running)"
+                } else if task.last_attempt_failed {
+                    " (failed)"
+                } else {
+                    ""
+                };
+                println!("{}  {:<10} {}{running}", task.id, task.status, task.title);
+            }
+        }
+        Command::Workspace(WorkspaceCommand::Status { id }) => {
+            let status = client.workspace_status(id).await?;
+            if cli.json {
+                return print_json(&status);
+            }
+            println!("status: {}", status.status);
+            if let (Some(files), Some(added), Some(removed)) = (
+                status.files_changed,
+                status.lines_added,
+                status.lines_removed,
+            ) {
+                println!("changes: {files} files, +{added} -{removed}");
+            }
+        }
+        Command::Workspace(WorkspaceCommand::Diff { id, repo }) => {
+            let diff = client.workspace_diff(id, repo.as_deref()).await?;
+            if cli.json {
+                return print_json(&diff);
+            }
+            // Written in one go so `| less` and `| git apply` see a plain patch
+            let mut stdout = std::io::stdout().lock();
+            for file in diff.repos.iter().flat_map(|repo| &repo.files) {
+                stdout.write_all(file.diff_content.as_bytes())?;
+                if !file.diff_content.ends_with('\n') {
+                    stdout.write_all(b"\n")?;
+                }
+            }
+        }
+        Command::Workspace(WorkspaceCommand::Transcript { id }) => {
+            let transcript = client.workspace_transcript(id).await?;
+            if cli.json {
+                return print_json(&transcript);
+            }
+            println!(
+                "## Prompt\n\n{}\n",
+                transcript.prompt.as_deref().unwrap_or("-")
+            );
+            println!(
+                "## Summary\n\n{}",
+                transcript.summary.as_deref().unwrap_or("-")
+            );
+        }
+        Command::Workspace(WorkspaceCommand::Close {
+            id,
+            merge,
+            discard: _,
+            force,
+        }) => {
+            let strategy = if merge { "merge" } else { "discard" };
+            let result = client.close_workspace(id, strategy, force).await?;
+            if cli.json {
+                return print_json(&result);
+            }
+            println!("{}", result.message);
+            if let Some(sha) = result.merge_commit_sha {
+                println!("merge commit: {sha}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    // Install rustls crypto provider before any TLS operations
+    rustls::crypto::aws_lc_rs::default_provider()
+        .install_default()
+        .expect("Failed to install rustls crypto provider");
+
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_requires_a_strategy() {
+        let id = Uuid::new_v4().to_string();
+        assert!(Cli::try_parse_from(["ralph", "ws", "close", &id]).is_err());
+        assert!(
+            Cli::try_parse_from(["ralph", "ws", "close", &id, "--merge", "--discard"]).is_err()
+        );
+
+        let cli = Cli::try_parse_from(["ralph", "ws", "close", &id, "--merge"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Workspace(WorkspaceCommand::Close { merge: true, .. })
+        ));
+    }
+}
@@ -0,0 +1,2 @@
+-- Require passing CI on the workspace branch head before merging into this repo
+ALTER TABLE repos ADD COLUMN require_ci_pass INTEGER NOT NULL DEFAULT 0;
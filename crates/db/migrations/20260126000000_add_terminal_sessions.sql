@@ -0,0 +1,13 @@
+-- Audit trail of interactive terminal sessions opened in workspace worktrees
+CREATE TABLE terminal_sessions (
+    id           BLOB PRIMARY KEY,
+    workspace_id BLOB NOT NULL,
+    repo_id      BLOB,
+    shell        TEXT NOT NULL,
+    working_dir  TEXT NOT NULL,
+    started_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    ended_at     TEXT,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_terminal_sessions_workspace_id ON terminal_sessions(workspace_id, started_at DESC);
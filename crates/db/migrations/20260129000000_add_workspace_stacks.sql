@@ -0,0 +1,9 @@
+-- Stacked workspaces: a child branches off its parent workspace's branch and targets it
+CREATE TABLE workspace_stacks (
+    workspace_id        BLOB PRIMARY KEY,
+    parent_workspace_id BLOB NOT NULL,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_workspace_stacks_parent_workspace_id ON workspace_stacks(parent_workspace_id);
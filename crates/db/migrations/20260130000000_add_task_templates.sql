@@ -0,0 +1,15 @@
+-- Reusable task blueprints; project_id NULL makes a template available in every project
+CREATE TABLE task_templates (
+    id                   BLOB PRIMARY KEY,
+    project_id           BLOB,
+    name                 TEXT NOT NULL,
+    title_pattern        TEXT NOT NULL,
+    description_skeleton TEXT,
+    labels               TEXT NOT NULL DEFAULT '[]',
+    priority             TEXT CHECK (priority IN ('low', 'medium', 'high', 'urgent')),
+    checklist            TEXT NOT NULL DEFAULT '[]',
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_task_templates_project_id ON task_templates(project_id);
@@ -0,0 +1,4 @@
+-- Board metadata used for swimlanes and filtering; labels is a JSON array of strings
+ALTER TABLE tasks ADD COLUMN priority TEXT CHECK (priority IN ('low', 'medium', 'high', 'urgent'));
+ALTER TABLE tasks ADD COLUMN labels TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE tasks ADD COLUMN assignee TEXT;
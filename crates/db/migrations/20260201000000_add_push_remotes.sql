@@ -0,0 +1,4 @@
+-- Remote that workspace branches are pushed to; NULL uses the git default remote.
+-- The workspace value overrides the repo value, allowing fork-based workflows.
+ALTER TABLE repos ADD COLUMN push_remote TEXT;
+ALTER TABLE workspace_repos ADD COLUMN push_remote TEXT;
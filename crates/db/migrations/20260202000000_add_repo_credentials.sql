@@ -0,0 +1,11 @@
+-- Credentials used for a repo's fetches and pushes instead of the server's ambient ones.
+-- secret holds the HTTPS token or SSH private key path, encrypted by the server.
+CREATE TABLE repo_credentials (
+    repo_id     BLOB PRIMARY KEY,
+    kind        TEXT NOT NULL CHECK (kind IN ('https_token', 'ssh_key')),
+    username    TEXT,
+    secret      TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
@@ -0,0 +1,28 @@
+-- Secrets injected into execution process environments, encrypted by the server.
+-- name is the environment variable the value is exposed as.
+CREATE TABLE secrets (
+    id           BLOB PRIMARY KEY,
+    name         TEXT NOT NULL UNIQUE,
+    value        TEXT NOT NULL,
+    description  TEXT,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+-- Secret names a project or repo may use; processes only receive allowlisted secrets.
+-- Entries name secrets rather than reference them so an allowlist can be set up first.
+CREATE TABLE secret_allowlist (
+    id           BLOB PRIMARY KEY,
+    project_id   BLOB,
+    repo_id      BLOB,
+    secret_name  TEXT NOT NULL,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    CHECK ((project_id IS NULL) <> (repo_id IS NULL)),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_secret_allowlist_project
+    ON secret_allowlist(project_id, secret_name) WHERE project_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_secret_allowlist_repo
+    ON secret_allowlist(repo_id, secret_name) WHERE repo_id IS NOT NULL;
@@ -0,0 +1,21 @@
+-- Named groups of environment variables injected into every execution process of a
+-- project's workspaces (agent runs, setup scripts, dev servers).
+CREATE TABLE project_env_sets (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    name        TEXT NOT NULL,
+    vars        TEXT NOT NULL DEFAULT '{}',
+    enabled     INTEGER NOT NULL DEFAULT 1,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    UNIQUE (project_id, name)
+);
+
+-- Per-workspace variables, taking precedence over the project's sets.
+CREATE TABLE workspace_env_overrides (
+    workspace_id  BLOB PRIMARY KEY,
+    vars          TEXT NOT NULL DEFAULT '{}',
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
@@ -0,0 +1,6 @@
+-- Server settings changeable at runtime, one row per setting with a JSON value.
+CREATE TABLE settings (
+    key         TEXT PRIMARY KEY,
+    value       TEXT NOT NULL,
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
@@ -0,0 +1,11 @@
+-- Merges and rebases of a workspace branch that stopped on conflicts, for project statistics.
+CREATE TABLE merge_conflicts (
+    id            BLOB PRIMARY KEY,
+    workspace_id  BLOB NOT NULL,
+    repo_id       BLOB NOT NULL,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_merge_conflicts_repo_id ON merge_conflicts(repo_id);
@@ -0,0 +1,36 @@
+-- History of task status changes, recorded by triggers so every code path is covered.
+-- from_status is NULL for the row written when a task is created.
+CREATE TABLE task_status_transitions (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id      BLOB NOT NULL,
+    from_status  TEXT,
+    to_status    TEXT NOT NULL,
+    changed_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_status_transitions_task_id ON task_status_transitions(task_id);
+
+CREATE TRIGGER trg_task_status_created
+AFTER INSERT ON tasks
+FOR EACH ROW
+BEGIN
+    INSERT INTO task_status_transitions (task_id, from_status, to_status)
+    VALUES (NEW.id, NULL, NEW.status);
+END;
+
+CREATE TRIGGER trg_task_status_changed
+AFTER UPDATE OF status ON tasks
+FOR EACH ROW
+WHEN OLD.status IS NOT NEW.status
+BEGIN
+    INSERT INTO task_status_transitions (task_id, from_status, to_status)
+    VALUES (NEW.id, OLD.status, NEW.status);
+END;
+
+-- Best-effort history for existing tasks: created as todo, last status change at updated_at
+INSERT INTO task_status_transitions (task_id, from_status, to_status, changed_at)
+SELECT id, NULL, 'todo', created_at FROM tasks;
+
+INSERT INTO task_status_transitions (task_id, from_status, to_status, changed_at)
+SELECT id, 'todo', status, updated_at FROM tasks WHERE status != 'todo';
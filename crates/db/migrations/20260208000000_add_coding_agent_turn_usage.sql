@@ -0,0 +1,5 @@
+-- Token usage and cost of each coding agent turn, for agents that report them.
+-- Older turns and turns of other agents keep NULLs and are left out of cost reports.
+ALTER TABLE coding_agent_turns ADD COLUMN input_tokens INTEGER;
+ALTER TABLE coding_agent_turns ADD COLUMN output_tokens INTEGER;
+ALTER TABLE coding_agent_turns ADD COLUMN cost_usd REAL;
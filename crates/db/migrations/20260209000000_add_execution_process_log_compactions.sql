@@ -0,0 +1,10 @@
+-- Execution processes whose stored output was compacted by the log retention job, so each
+-- process is compacted only once.
+CREATE TABLE execution_process_log_compactions (
+    execution_id   BLOB PRIMARY KEY,
+    policy         TEXT NOT NULL,
+    removed_bytes  INTEGER NOT NULL,
+    archive_path   TEXT,
+    compacted_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
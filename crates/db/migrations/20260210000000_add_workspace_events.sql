@@ -0,0 +1,12 @@
+-- Notable things that happened to a workspace outside of its execution processes,
+-- such as worktrees being repaired on access
+CREATE TABLE workspace_events (
+    id            BLOB PRIMARY KEY,
+    workspace_id  BLOB NOT NULL,
+    kind          TEXT NOT NULL,
+    message       TEXT NOT NULL,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_workspace_events_workspace_id_created_at
+ON workspace_events (workspace_id, created_at DESC);
@@ -0,0 +1,13 @@
+-- Machine reached over SSH on which a project's agent and script processes run, with
+-- each workspace synced to a directory under remote_dir.
+CREATE TABLE project_remote_hosts (
+    project_id     BLOB PRIMARY KEY,
+    host           TEXT NOT NULL,
+    user           TEXT,
+    port           INTEGER,
+    identity_file  TEXT,
+    remote_dir     TEXT NOT NULL,
+    created_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
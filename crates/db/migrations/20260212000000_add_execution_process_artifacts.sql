@@ -0,0 +1,15 @@
+-- Files collected from a workspace after an execution process ran, such as build outputs
+-- and coverage reports, so they outlive the worktree. Scripts declare the glob patterns
+-- to collect in project_scripts.artifacts.
+ALTER TABLE project_scripts ADD COLUMN artifacts TEXT NOT NULL DEFAULT '[]';
+
+CREATE TABLE execution_process_artifacts (
+    id                    BLOB PRIMARY KEY,
+    execution_process_id  BLOB NOT NULL,
+    path                  TEXT NOT NULL,
+    size_bytes            INTEGER NOT NULL,
+    stored_path           TEXT NOT NULL,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    UNIQUE (execution_process_id, path)
+);
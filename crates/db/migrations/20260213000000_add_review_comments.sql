@@ -0,0 +1,19 @@
+-- Review comments on lines of a workspace diff. A comment is anchored to a line of a
+-- file on the old (removed) or new (added and unchanged) side of the diff.
+CREATE TABLE review_comments (
+    id            BLOB PRIMARY KEY,
+    workspace_id  BLOB NOT NULL,
+    repo_id       BLOB NOT NULL,
+    path          TEXT NOT NULL,
+    line          INTEGER NOT NULL,
+    side          TEXT NOT NULL CHECK (side IN ('old', 'new')),
+    body          TEXT NOT NULL,
+    resolved_at   TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_review_comments_workspace_id_path
+ON review_comments (workspace_id, repo_id, path);
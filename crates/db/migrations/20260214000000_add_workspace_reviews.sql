@@ -0,0 +1,15 @@
+-- Require an approving review before a workspace of the project is merged
+ALTER TABLE projects ADD COLUMN require_merge_approval INTEGER NOT NULL DEFAULT 0;
+
+-- Review decisions on workspaces; the latest one is the workspace's review state
+CREATE TABLE workspace_reviews (
+    id            BLOB PRIMARY KEY,
+    workspace_id  BLOB NOT NULL,
+    reviewer      TEXT NOT NULL,
+    decision      TEXT NOT NULL CHECK (decision IN ('approved', 'changes_requested')),
+    comment       TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_workspace_reviews_workspace_id_created_at
+ON workspace_reviews (workspace_id, created_at DESC);
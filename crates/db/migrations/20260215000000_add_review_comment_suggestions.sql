@@ -0,0 +1,3 @@
+-- Review comments may span several lines and suggest replacement text for them
+ALTER TABLE review_comments ADD COLUMN end_line INTEGER;
+ALTER TABLE review_comments ADD COLUMN suggestion TEXT;
@@ -0,0 +1,33 @@
+-- Projects can have a coding agent review a workspace's diff before it is merged. Findings
+-- at or above the blocking severity stop the close; unset, findings are only warnings.
+CREATE TABLE project_ai_review_policies (
+    project_id          BLOB PRIMARY KEY,
+    executor_profile_id TEXT NOT NULL,
+    block_severity      TEXT CHECK (block_severity IN ('info', 'warning', 'error')),
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- One agent review of a workspace, run as a coding agent execution process
+CREATE TABLE ai_reviews (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    execution_process_id BLOB NOT NULL,
+    status               TEXT NOT NULL DEFAULT 'running'
+                            CHECK (status IN ('running', 'completed', 'failed')),
+    error                TEXT,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    completed_at         TEXT,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_ai_reviews_workspace_id ON ai_reviews (workspace_id, created_at DESC);
+CREATE INDEX idx_ai_reviews_execution_process_id ON ai_reviews (execution_process_id);
+
+-- Findings of an agent review are stored as review comments with a severity
+ALTER TABLE review_comments ADD COLUMN severity TEXT
+    CHECK (severity IN ('info', 'warning', 'error'));
+ALTER TABLE review_comments ADD COLUMN ai_review_id BLOB
+    REFERENCES ai_reviews(id) ON DELETE CASCADE;
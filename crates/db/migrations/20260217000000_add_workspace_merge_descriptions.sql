@@ -0,0 +1,12 @@
+-- Description of a workspace's changes for its merge commit or pull request. It is
+-- generated from the workspace's commits, diff stats and agent summary and can be edited
+-- before it is used.
+CREATE TABLE workspace_merge_descriptions (
+    workspace_id  BLOB PRIMARY KEY,
+    title         TEXT NOT NULL,
+    body          TEXT NOT NULL,
+    edited        INTEGER NOT NULL DEFAULT 0,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
@@ -0,0 +1,21 @@
+-- A coding agent's proposed resolution of one conflicted file in a workspace worktree. The
+-- resolution stays in the worktree unstaged until a person accepts it.
+CREATE TABLE conflict_resolutions (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    repo_id              BLOB NOT NULL,
+    execution_process_id BLOB NOT NULL,
+    path                 TEXT NOT NULL,
+    status               TEXT NOT NULL DEFAULT 'running'
+                            CHECK (status IN ('running', 'proposed', 'unresolved', 'accepted', 'rejected')),
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_conflict_resolutions_workspace_id
+    ON conflict_resolutions (workspace_id, created_at DESC);
+CREATE INDEX idx_conflict_resolutions_execution_process_id
+    ON conflict_resolutions (execution_process_id);
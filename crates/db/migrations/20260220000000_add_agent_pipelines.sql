@@ -0,0 +1,32 @@
+-- Projects can run each workspace through a pipeline of coding agent stages, e.g. plan,
+-- implement, self-review and fix. Stages are stored as a JSON array of
+-- {name, executor_profile_id, prompt_template}.
+CREATE TABLE project_agent_pipelines (
+    project_id BLOB PRIMARY KEY,
+    stages     TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- The stages of a workspace's pipeline run, copied from the project's pipeline when the run
+-- starts so editing the pipeline does not change a run in progress
+CREATE TABLE workspace_pipeline_stages (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    position             INTEGER NOT NULL,
+    name                 TEXT NOT NULL,
+    executor_profile_id  TEXT NOT NULL,
+    prompt_template      TEXT NOT NULL,
+    status               TEXT NOT NULL DEFAULT 'pending'
+                            CHECK (status IN ('pending', 'running', 'completed', 'failed', 'skipped')),
+    execution_process_id BLOB,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL,
+    UNIQUE (workspace_id, position)
+);
+
+CREATE INDEX idx_workspace_pipeline_stages_execution_process_id
+    ON workspace_pipeline_stages (execution_process_id);
@@ -0,0 +1,20 @@
+-- Projects can limit what their coding agents may do: reach the network, run programs
+-- outside an allowlist (a JSON array, NULL allows any) or write outside the workspace.
+CREATE TABLE project_agent_policies (
+    project_id                  BLOB PRIMARY KEY,
+    allow_network               BOOLEAN NOT NULL DEFAULT TRUE,
+    allowed_commands            TEXT,
+    restrict_writes_to_worktree BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- The policy a coding agent process ran under, as JSON, kept even if the project's policy
+-- changes later
+CREATE TABLE execution_process_policies (
+    execution_process_id BLOB PRIMARY KEY,
+    policy               TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
@@ -0,0 +1,14 @@
+-- Coding agent processes that were still running when the server stopped. The agent's
+-- session can be resumed from its stored session id, continuing the interrupted run.
+CREATE TABLE interrupted_executions (
+    execution_process_id         BLOB PRIMARY KEY,
+    workspace_id                 BLOB NOT NULL,
+    resumed_execution_process_id BLOB,
+    interrupted_at               TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (resumed_execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_interrupted_executions_workspace_id
+ON interrupted_executions (workspace_id, interrupted_at DESC);
@@ -0,0 +1,8 @@
+-- OS process id of each spawned execution process, the leader of its process group, so
+-- processes still marked running at startup can be checked and cleaned up
+CREATE TABLE execution_process_pids (
+    execution_process_id BLOB PRIMARY KEY,
+    pid                  INTEGER NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- A retry re-runs the action of a failed or killed process as a new process
+ALTER TABLE execution_processes ADD COLUMN retried_from BLOB
+    REFERENCES execution_processes(id) ON DELETE SET NULL;
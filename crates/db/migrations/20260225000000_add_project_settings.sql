@@ -0,0 +1,16 @@
+-- Per-project overrides of server-wide behavior. Unset columns fall back to the server's
+-- settings: the agent new workspaces start with, how their branches are named, how a
+-- close without a strategy ends, whether quality gates run before a merge, and how long
+-- idle worktrees are kept.
+CREATE TABLE project_settings (
+    project_id                         BLOB PRIMARY KEY,
+    default_executor_profile_id        TEXT,
+    branch_template                    TEXT,
+    default_merge_strategy             TEXT CHECK (default_merge_strategy IN ('merge', 'discard')),
+    run_quality_gates                  BOOLEAN NOT NULL DEFAULT TRUE,
+    workspace_retention_hours          INTEGER,
+    archived_workspace_retention_hours INTEGER,
+    created_at                         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at                         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- Branch new workspaces of the project target in this repo when the request names none;
+-- takes precedence over the repo's own default target branch
+ALTER TABLE project_repos ADD COLUMN default_target_branch TEXT;
@@ -0,0 +1,2 @@
+ALTER TABLE project_repos ADD COLUMN path_scope TEXT;
+ALTER TABLE project_repos ADD COLUMN sparse_checkout BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- How open workspaces of the project are kept up with their target branches: rebased onto
+-- them or merged with them whenever they gain commits. Unset leaves workspaces alone.
+ALTER TABLE project_settings ADD COLUMN auto_sync_strategy TEXT
+    CHECK (auto_sync_strategy IN ('rebase', 'merge'));
@@ -0,0 +1,2 @@
+-- Whether the scheduled fetch updates the repo's remote-tracking branches
+ALTER TABLE repos ADD COLUMN auto_fetch BOOLEAN NOT NULL DEFAULT TRUE;
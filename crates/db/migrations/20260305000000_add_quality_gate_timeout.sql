@@ -0,0 +1,2 @@
+-- Seconds a quality gate may run before it is stopped; NULL uses the server default.
+ALTER TABLE project_quality_gates ADD COLUMN timeout_secs INTEGER;
@@ -0,0 +1,3 @@
+-- The sandbox that enforced the policy; NULL for processes whose policy was only checked
+-- against their tool uses
+ALTER TABLE execution_process_policies ADD COLUMN sandbox TEXT;
@@ -16,6 +16,17 @@ pub struct CodingAgentTurn {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Usage recorded for a coding agent turn, with the project it was spent on
+#[derive(Debug, Clone)]
+pub struct TurnCost {
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub created_at: DateTime<Utc>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: Option<f64>,
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateCodingAgentTurn {
     pub execution_process_id: Uuid,
@@ -157,6 +168,31 @@ impl CodingAgentTurn {
         Ok(())
     }
 
+    /// Record the token usage and cost the agent reported for the turn
+    pub async fn update_usage(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        input_tokens: i64,
+        output_tokens: i64,
+        cost_usd: Option<f64>,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE coding_agent_turns
+               SET input_tokens = $1, output_tokens = $2, cost_usd = $3, updated_at = $4
+               WHERE execution_process_id = $5"#,
+            input_tokens,
+            output_tokens,
+            cost_usd,
+            now,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Mark all coding agent turns for a workspace as seen
     pub async fn mark_seen_by_workspace_id(
         pool: &SqlitePool,
@@ -249,4 +285,124 @@ impl CodingAgentTurn {
         .fetch_optional(pool)
         .await
     }
+
+    /// Summary of the workspace's most recent coding agent turn that has one, leaving out
+    /// agent reviews of the workspace
+    pub async fn find_latest_summary_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT cat.summary as "summary!"
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON cat.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               WHERE s.workspace_id = $1
+                 AND ep.run_reason = 'codingagent'
+                 AND ep.dropped = 0
+                 AND cat.summary IS NOT NULL
+                 AND NOT EXISTS (SELECT 1 FROM ai_reviews ar WHERE ar.execution_process_id = ep.id)
+               ORDER BY ep.created_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Non-dropped coding agent turns of a session, oldest first
+    pub async fn find_page_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            CodingAgentTurn,
+            r#"SELECT
+                cat.id as "id!: Uuid",
+                cat.execution_process_id as "execution_process_id!: Uuid",
+                cat.agent_session_id,
+                cat.prompt,
+                cat.summary,
+                cat.seen as "seen!: bool",
+                cat.created_at as "created_at!: DateTime<Utc>",
+                cat.updated_at as "updated_at!: DateTime<Utc>"
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON cat.execution_process_id = ep.id
+               WHERE ep.session_id = $1
+                 AND ep.dropped = 0
+               ORDER BY ep.created_at ASC
+               LIMIT $2 OFFSET $3"#,
+            session_id,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn count_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON cat.execution_process_id = ep.id
+               WHERE ep.session_id = $1 AND ep.dropped = 0"#,
+            session_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Turns with recorded usage created in `[from, to)`, optionally limited to one project
+    pub async fn find_costs_between(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<TurnCost>, sqlx::Error> {
+        sqlx::query_as!(
+            TurnCost,
+            r#"SELECT
+                p.id as "project_id!: Uuid",
+                p.name as "project_name!",
+                cat.created_at as "created_at!: DateTime<Utc>",
+                cat.input_tokens as "input_tokens!: i64",
+                cat.output_tokens as "output_tokens!: i64",
+                cat.cost_usd
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON cat.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               JOIN projects p ON t.project_id = p.id
+               WHERE cat.input_tokens IS NOT NULL
+                 AND cat.created_at >= $1 AND cat.created_at < $2
+                 AND ($3 IS NULL OR p.id = $3)
+               ORDER BY cat.created_at ASC"#,
+            from,
+            to,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Total reported cost of all turns created since `since`
+    pub async fn total_cost_since(
+        pool: &SqlitePool,
+        since: DateTime<Utc>,
+    ) -> Result<f64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(cost_usd), 0.0) as "total!: f64"
+               FROM coding_agent_turns
+               WHERE created_at >= $1"#,
+            since
+        )
+        .fetch_one(pool)
+        .await
+    }
 }
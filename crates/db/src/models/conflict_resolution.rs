@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "conflict_resolution_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolutionStatus {
+    /// The agent is still working on the file
+    Running,
+    /// The agent left the file without conflict markers, ready to be reviewed
+    Proposed,
+    /// The agent finished without resolving the file
+    Unresolved,
+    /// The resolution was accepted and staged
+    Accepted,
+    /// The resolution was rejected and the conflict markers put back
+    Rejected,
+}
+
+/// A coding agent's resolution of one conflicted file in a workspace worktree
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ConflictResolution {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    /// The coding agent process resolving the conflicts
+    pub execution_process_id: Uuid,
+    /// Path of the conflicted file, relative to the repo
+    pub path: String,
+    pub status: ConflictResolutionStatus,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ConflictResolution {
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        execution_process_id: Uuid,
+        path: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ConflictResolution,
+            r#"INSERT INTO conflict_resolutions (id, workspace_id, repo_id, execution_process_id, path)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         path,
+                         status as "status!: ConflictResolutionStatus",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            repo_id,
+            execution_process_id,
+            path
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ConflictResolution,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      path,
+                      status as "status!: ConflictResolutionStatus",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM conflict_resolutions
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Resolutions of the workspace, newest run first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ConflictResolution,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      path,
+                      status as "status!: ConflictResolutionStatus",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM conflict_resolutions
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC, path ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ConflictResolution,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      path,
+                      status as "status!: ConflictResolutionStatus",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM conflict_resolutions
+               WHERE execution_process_id = $1
+               ORDER BY path ASC"#,
+            execution_process_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn update_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: ConflictResolutionStatus,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            ConflictResolution,
+            r#"UPDATE conflict_resolutions
+               SET status = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         path,
+                         status as "status!: ConflictResolutionStatus",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            status
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
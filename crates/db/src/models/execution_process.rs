@@ -7,7 +7,7 @@ use executors::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -57,6 +57,13 @@ pub enum ExecutionProcessRunReason {
     CleanupScript,
     CodingAgent,
     DevServer,
+    QualityGate,
+    TestRun,
+    AdHocCommand,
+    ProjectScript,
+    Plan,
+    AiReview,
+    ConflictResolution,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
@@ -76,6 +83,8 @@ pub struct ExecutionProcess {
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// The failed or killed process this one re-runs, if it is a retry
+    pub retried_from: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -103,6 +112,69 @@ pub struct ExecutionContext {
     pub repos: Vec<Repo>,
 }
 
+/// Filters for [`ExecutionProcess::find_filtered`]; unset fields match every process
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionProcessFilter {
+    pub workspace_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub status: Option<ExecutionProcessStatus>,
+    pub run_reason: Option<ExecutionProcessRunReason>,
+    /// Processes started at or after this time
+    pub started_after: Option<DateTime<Utc>>,
+    /// Processes started before this time
+    pub started_before: Option<DateTime<Utc>>,
+    /// Include processes dropped from the history view
+    pub show_soft_deleted: bool,
+}
+
+impl ExecutionProcessFilter {
+    /// Append the `WHERE` clause selecting the processes that match this filter
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Sqlite>) {
+        query.push(" WHERE 1 = 1");
+        if let Some(workspace_id) = self.workspace_id {
+            query.push(" AND w.id = ").push_bind(workspace_id);
+        }
+        if let Some(project_id) = self.project_id {
+            query.push(" AND t.project_id = ").push_bind(project_id);
+        }
+        if let Some(status) = &self.status {
+            query.push(" AND ep.status = ").push_bind(status.clone());
+        }
+        if let Some(run_reason) = &self.run_reason {
+            query
+                .push(" AND ep.run_reason = ")
+                .push_bind(run_reason.clone());
+        }
+        if let Some(started_after) = self.started_after {
+            query
+                .push(" AND datetime(ep.started_at) >= datetime(")
+                .push_bind(started_after)
+                .push(")");
+        }
+        if let Some(started_before) = self.started_before {
+            query
+                .push(" AND datetime(ep.started_at) < datetime(")
+                .push_bind(started_before)
+                .push(")");
+        }
+        if !self.show_soft_deleted {
+            query.push(" AND ep.dropped = FALSE");
+        }
+    }
+}
+
+/// An execution process with the workspace it ran in and how long it took
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutionProcessSummary {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub process: ExecutionProcess,
+    pub workspace_id: Uuid,
+    /// Milliseconds from start to completion; unset while the process runs
+    #[ts(type = "number | null")]
+    pub duration_ms: Option<i64>,
+}
+
 /// Summary info about the latest execution process for a workspace
 #[derive(Debug, Clone, FromRow)]
 pub struct LatestProcessInfo {
@@ -147,7 +219,8 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.retried_from as "retried_from?: Uuid"
                FROM execution_processes ep WHERE ep.id = ?"#,
             id
         )
@@ -221,7 +294,8 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.retried_from as "retried_from?: Uuid"
                FROM execution_processes ep WHERE ep.rowid = ?"#,
             rowid
         )
@@ -248,7 +322,8 @@ impl ExecutionProcess {
                       ep.started_at      as "started_at!: DateTime<Utc>",
                       ep.completed_at    as "completed_at?: DateTime<Utc>",
                       ep.created_at      as "created_at!: DateTime<Utc>",
-                      ep.updated_at      as "updated_at!: DateTime<Utc>"
+                      ep.updated_at      as "updated_at!: DateTime<Utc>",
+                      ep.retried_from    as "retried_from?: Uuid"
                FROM execution_processes ep
                WHERE ep.session_id = ?
                  AND (? OR ep.dropped = FALSE)
@@ -260,6 +335,128 @@ impl ExecutionProcess {
         .await
     }
 
+    /// One page of [`ExecutionProcess::find_by_session_id`], oldest first
+    pub async fn find_page_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        show_soft_deleted: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT
+                      ep.id              as "id!: Uuid",
+                      ep.session_id      as "session_id!: Uuid",
+                      ep.run_reason      as "run_reason!: ExecutionProcessRunReason",
+                      ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                      ep.status          as "status!: ExecutionProcessStatus",
+                      ep.exit_code,
+                      ep.dropped as "dropped!: bool",
+                      ep.started_at      as "started_at!: DateTime<Utc>",
+                      ep.completed_at    as "completed_at?: DateTime<Utc>",
+                      ep.created_at      as "created_at!: DateTime<Utc>",
+                      ep.updated_at      as "updated_at!: DateTime<Utc>",
+                      ep.retried_from    as "retried_from?: Uuid"
+               FROM execution_processes ep
+               WHERE ep.session_id = ?
+                 AND (? OR ep.dropped = FALSE)
+               ORDER BY ep.created_at ASC
+               LIMIT ? OFFSET ?"#,
+            session_id,
+            show_soft_deleted,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn count_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        show_soft_deleted: bool,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM execution_processes
+               WHERE session_id = ? AND (? OR dropped = FALSE)"#,
+            session_id,
+            show_soft_deleted
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Processes matching `filter` across workspaces, newest first. `limit` (when set) and
+    /// `offset` select a page of them.
+    pub async fn find_filtered(
+        pool: &SqlitePool,
+        filter: &ExecutionProcessFilter,
+        limit: Option<i64>,
+        offset: i64,
+    ) -> Result<Vec<ExecutionProcessSummary>, sqlx::Error> {
+        #[derive(FromRow)]
+        struct ExecutionProcessSummaryRow {
+            #[sqlx(flatten)]
+            process: ExecutionProcess,
+            workspace_id: Uuid,
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"SELECT
+  ep.id, ep.session_id, ep.run_reason, ep.executor_action, ep.status, ep.exit_code,
+  ep.dropped, ep.started_at, ep.completed_at, ep.created_at, ep.updated_at, ep.retried_from,
+  w.id AS workspace_id
+FROM execution_processes ep
+JOIN sessions s ON ep.session_id = s.id
+JOIN workspaces w ON s.workspace_id = w.id
+JOIN tasks t ON w.task_id = t.id"#,
+        );
+        filter.push_conditions(&mut query);
+        query.push(" ORDER BY ep.started_at DESC, ep.rowid DESC");
+        if let Some(limit) = limit {
+            query
+                .push(" LIMIT ")
+                .push_bind(limit)
+                .push(" OFFSET ")
+                .push_bind(offset);
+        }
+
+        let rows = query
+            .build_query_as::<ExecutionProcessSummaryRow>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExecutionProcessSummary {
+                duration_ms: row
+                    .process
+                    .completed_at
+                    .map(|completed_at| (completed_at - row.process.started_at).num_milliseconds()),
+                process: row.process,
+                workspace_id: row.workspace_id,
+            })
+            .collect())
+    }
+
+    /// Number of processes matching `filter`
+    pub async fn count_filtered(
+        pool: &SqlitePool,
+        filter: &ExecutionProcessFilter,
+    ) -> Result<i64, sqlx::Error> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"SELECT COUNT(*)
+FROM execution_processes ep
+JOIN sessions s ON ep.session_id = s.id
+JOIN workspaces w ON s.workspace_id = w.id
+JOIN tasks t ON w.task_id = t.id"#,
+        );
+        filter.push_conditions(&mut query);
+        query.build_query_scalar::<i64>().fetch_one(pool).await
+    }
+
     /// Find running execution processes
     pub async fn find_running(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
@@ -275,13 +472,31 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.retried_from as "retried_from?: Uuid"
                FROM execution_processes ep WHERE ep.status = 'running' ORDER BY ep.created_at ASC"#,
         )
         .fetch_all(pool)
         .await
     }
 
+    /// Count coding agent runs in progress other than `except`, across all workspaces
+    pub async fn count_running_coding_agents(
+        pool: &SqlitePool,
+        except: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM execution_processes
+               WHERE status = 'running'
+                 AND run_reason = 'codingagent'
+                 AND id != $1"#,
+            except
+        )
+        .fetch_one(pool)
+        .await
+    }
+
     /// Find running dev servers for a specific project
     pub async fn find_running_dev_servers_by_project(
         pool: &SqlitePool,
@@ -291,7 +506,7 @@ impl ExecutionProcess {
             ExecutionProcess,
             r#"SELECT ep.id as "id!: Uuid", ep.session_id as "session_id!: Uuid", ep.run_reason as "run_reason!: ExecutionProcessRunReason", ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                       ep.status as "status!: ExecutionProcessStatus", ep.exit_code,
-                      ep.dropped as "dropped!: bool", ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>"
+                      ep.dropped as "dropped!: bool", ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>", ep.retried_from as "retried_from?: Uuid"
                FROM execution_processes ep
                JOIN sessions s ON ep.session_id = s.id
                JOIN workspaces w ON s.workspace_id = w.id
@@ -342,7 +557,8 @@ impl ExecutionProcess {
             ep.started_at as "started_at!: DateTime<Utc>",
             ep.completed_at as "completed_at?: DateTime<Utc>",
             ep.created_at as "created_at!: DateTime<Utc>",
-            ep.updated_at as "updated_at!: DateTime<Utc>"
+            ep.updated_at as "updated_at!: DateTime<Utc>",
+            ep.retried_from as "retried_from?: Uuid"
         FROM execution_processes ep
         JOIN sessions s ON ep.session_id = s.id
         WHERE s.workspace_id = ?
@@ -404,7 +620,8 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.retried_from as "retried_from?: Uuid"
                FROM execution_processes ep
                WHERE ep.session_id = ? AND ep.run_reason = ? AND ep.dropped = FALSE
                ORDER BY ep.created_at DESC LIMIT 1"#,
@@ -434,7 +651,8 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.retried_from as "retried_from?: Uuid"
                FROM execution_processes ep
                JOIN sessions s ON ep.session_id = s.id
                WHERE s.workspace_id = ? AND ep.run_reason = ? AND ep.dropped = FALSE
@@ -528,6 +746,22 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Link a process to the process it retries
+    pub async fn set_retried_from(
+        pool: &SqlitePool,
+        id: Uuid,
+        retried_from: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET retried_from = $1 WHERE id = $2",
+            retried_from,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub fn executor_action(&self) -> Result<&ExecutorAction, anyhow::Error> {
         match &self.executor_action.0 {
             ExecutorActionField::ExecutorAction(action) => Ok(action),
@@ -661,7 +895,8 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.retried_from as "retried_from?: Uuid"
                FROM execution_processes ep
                WHERE ep.session_id = ? AND ep.run_reason = ? AND ep.dropped = FALSE
                ORDER BY ep.created_at DESC LIMIT 1"#,
@@ -762,4 +997,34 @@ impl ExecutionProcess {
 
         Ok(rows.into_iter().collect())
     }
+
+    /// Executor actions of the coding agent runs in the project's workspaces, oldest first.
+    /// Runs whose stored action no longer parses are skipped.
+    pub async fn find_coding_agent_actions_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<(Uuid, ExecutorAction)>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT s.workspace_id as "workspace_id!: Uuid",
+                      ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>"
+               FROM execution_processes ep
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE t.project_id = $1
+                 AND ep.run_reason = 'codingagent'
+               ORDER BY ep.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| match row.executor_action.0 {
+                ExecutorActionField::ExecutorAction(action) => Some((row.workspace_id, action)),
+                ExecutorActionField::Other(_) => None,
+            })
+            .collect())
+    }
 }
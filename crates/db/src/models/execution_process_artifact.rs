@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A file kept from the workspace after an execution process ran
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutionProcessArtifact {
+    pub id: Uuid,
+    pub execution_process_id: Uuid,
+    /// Path relative to the directory the process ran in
+    pub path: String,
+    pub size_bytes: i64,
+    /// Where the copy is stored: a path on the server or an `s3://` URL
+    #[serde(skip)]
+    #[ts(skip)]
+    pub stored_path: String,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl ExecutionProcessArtifact {
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        path: &str,
+        size_bytes: i64,
+        stored_path: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ExecutionProcessArtifact,
+            r#"INSERT INTO execution_process_artifacts (id, execution_process_id, path, size_bytes, stored_path)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(execution_process_id, path) DO UPDATE SET
+                   size_bytes = excluded.size_bytes,
+                   stored_path = excluded.stored_path
+               RETURNING id as "id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         path,
+                         size_bytes,
+                         stored_path,
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            execution_process_id,
+            path,
+            size_bytes,
+            stored_path
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessArtifact,
+            r#"SELECT id as "id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      path,
+                      size_bytes,
+                      stored_path,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM execution_process_artifacts
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Artifacts of the process in path order
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessArtifact,
+            r#"SELECT id as "id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      path,
+                      size_bytes,
+                      stored_path,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM execution_process_artifacts
+               WHERE execution_process_id = $1
+               ORDER BY path ASC"#,
+            execution_process_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -5,6 +5,8 @@ use ts_rs::TS;
 use utils::log_msg::LogMsg;
 use uuid::Uuid;
 
+use super::execution_process::ExecutionProcessRunReason;
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct ExecutionProcessLogs {
     pub execution_id: Uuid,
@@ -13,6 +15,45 @@ pub struct ExecutionProcessLogs {
     pub inserted_at: DateTime<Utc>,
 }
 
+/// A stored log record with its row cursor, for incremental reads
+#[derive(Debug, Clone)]
+pub struct ExecutionProcessLogChunk {
+    /// Row id of the record; stable for the life of the record and increasing with inserts
+    pub cursor: i64,
+    pub logs: String,
+}
+
+/// A process whose stored output contains a searched string
+#[derive(Debug, Clone)]
+pub struct ExecutionProcessLogHit {
+    pub execution_id: Uuid,
+    pub session_id: Uuid,
+    pub workspace_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Row cursor and size of a stored log record
+#[derive(Debug, Clone)]
+pub struct ExecutionProcessLogRecordSize {
+    pub cursor: i64,
+    pub byte_size: i64,
+}
+
+/// Records of a process to remove when compacting its logs, and how
+#[derive(Debug, Clone)]
+pub struct LogCompaction<'a> {
+    /// Records up to and including this cursor are kept; the marker is appended to it
+    pub keep_through: Option<i64>,
+    /// Records from this cursor on are kept
+    pub keep_from: Option<i64>,
+    /// JSONL line noting what was removed, stored in place of the removed records
+    pub marker: Option<&'a str>,
+    pub policy: &'a str,
+    pub removed_bytes: i64,
+    pub archive_path: Option<&'a str>,
+}
+
 impl ExecutionProcessLogs {
     /// Find logs by execution process ID
     pub async fn find_by_execution_id(
@@ -35,6 +76,28 @@ impl ExecutionProcessLogs {
         .await
     }
 
+    /// Up to `limit` log records stored after `after_cursor` (or from the start), in order
+    pub async fn find_chunks_after(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+        after_cursor: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<ExecutionProcessLogChunk>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessLogChunk,
+            r#"SELECT rowid as "cursor!: i64", logs
+               FROM execution_process_logs
+               WHERE execution_id = $1 AND ($2 IS NULL OR rowid > $2)
+               ORDER BY rowid ASC
+               LIMIT $3"#,
+            execution_id,
+            after_cursor,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Parse JSONL logs back into Vec<LogMsg>
     pub fn parse_logs(records: &[Self]) -> Result<Vec<LogMsg>, serde_json::Error> {
         let mut messages = Vec::new();
@@ -66,4 +129,160 @@ impl ExecutionProcessLogs {
 
         Ok(())
     }
+
+    /// Finished processes with uncompacted logs that completed before `finished_before`
+    pub async fn find_ids_to_compact(
+        pool: &SqlitePool,
+        finished_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT ep.id as "id!: Uuid"
+               FROM execution_processes ep
+               WHERE ep.status != 'running'
+                 AND ep.completed_at IS NOT NULL
+                 AND ep.completed_at < $1
+                 AND EXISTS (
+                     SELECT 1 FROM execution_process_logs epl WHERE epl.execution_id = ep.id
+                 )
+                 AND NOT EXISTS (
+                     SELECT 1 FROM execution_process_log_compactions c
+                     WHERE c.execution_id = ep.id
+                 )
+               ORDER BY ep.completed_at ASC
+               LIMIT $2"#,
+            finished_before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_record_sizes(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+    ) -> Result<Vec<ExecutionProcessLogRecordSize>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessLogRecordSize,
+            r#"SELECT rowid as "cursor!: i64", byte_size
+               FROM execution_process_logs
+               WHERE execution_id = $1
+               ORDER BY rowid ASC"#,
+            execution_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Remove the records between `keep_through` and `keep_from`, leave the marker in their
+    /// place and record the compaction, all in one transaction
+    pub async fn compact(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+        compaction: &LogCompaction<'_>,
+    ) -> Result<(), sqlx::Error> {
+        let keep_through = compaction.keep_through.unwrap_or(0);
+        let keep_from = compaction.keep_from.unwrap_or(i64::MAX);
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            r#"DELETE FROM execution_process_logs
+               WHERE execution_id = $1 AND rowid > $2 AND rowid < $3"#,
+            execution_id,
+            keep_through,
+            keep_from
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(marker) = compaction.marker {
+            Self::insert_marker(&mut tx, execution_id, compaction.keep_through, marker).await?;
+        }
+
+        sqlx::query!(
+            r#"INSERT INTO execution_process_log_compactions
+                   (execution_id, policy, removed_bytes, archive_path)
+               VALUES ($1, $2, $3, $4)"#,
+            execution_id,
+            compaction.policy,
+            compaction.removed_bytes,
+            compaction.archive_path
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Append the marker to the record at `after`, or store it as its own record
+    async fn insert_marker(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        execution_id: Uuid,
+        after: Option<i64>,
+        marker: &str,
+    ) -> Result<(), sqlx::Error> {
+        let marker_size = marker.len() as i64;
+        if let Some(cursor) = after {
+            sqlx::query!(
+                r#"UPDATE execution_process_logs
+                   SET logs = logs || $1, byte_size = byte_size + $2
+                   WHERE rowid = $3"#,
+                marker,
+                marker_size,
+                cursor
+            )
+            .execute(&mut **tx)
+            .await?;
+        } else {
+            sqlx::query!(
+                r#"INSERT INTO execution_process_logs (execution_id, logs, byte_size, inserted_at)
+                   VALUES ($1, $2, $3, datetime('now', 'subsec'))"#,
+                execution_id,
+                marker,
+                marker_size
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Newest processes of the workspace and/or project with a stored record containing
+    /// `needle`, compared against the raw JSONL
+    pub async fn find_processes_containing(
+        pool: &SqlitePool,
+        workspace_id: Option<Uuid>,
+        project_id: Option<Uuid>,
+        needle: &str,
+        limit: i64,
+    ) -> Result<Vec<ExecutionProcessLogHit>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessLogHit,
+            r#"SELECT ep.id as "execution_id!: Uuid",
+                      ep.session_id as "session_id!: Uuid",
+                      s.workspace_id as "workspace_id!: Uuid",
+                      ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                      ep.created_at as "created_at!: DateTime<Utc>"
+               FROM execution_processes ep
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE ($1 IS NULL OR s.workspace_id = $1)
+                 AND ($2 IS NULL OR t.project_id = $2)
+                 AND ep.dropped = 0
+                 AND EXISTS (
+                     SELECT 1 FROM execution_process_logs epl
+                     WHERE epl.execution_id = ep.id AND instr(epl.logs, $3) > 0
+                 )
+               ORDER BY ep.created_at DESC
+               LIMIT $4"#,
+            workspace_id,
+            project_id,
+            needle,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
 }
@@ -0,0 +1,40 @@
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// OS process id of a spawned execution process, which leads its process group
+pub struct ExecutionProcessPid;
+
+impl ExecutionProcessPid {
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        pid: u32,
+    ) -> Result<(), sqlx::Error> {
+        let pid = i64::from(pid);
+        sqlx::query!(
+            r#"INSERT INTO execution_process_pids (execution_process_id, pid)
+               VALUES ($1, $2)
+               ON CONFLICT(execution_process_id) DO UPDATE SET pid = excluded.pid"#,
+            execution_process_id,
+            pid
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<u32>, sqlx::Error> {
+        let pid = sqlx::query_scalar!(
+            r#"SELECT pid as "pid!: i64"
+               FROM execution_process_pids
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(pid.and_then(|pid| u32::try_from(pid).ok()))
+    }
+}
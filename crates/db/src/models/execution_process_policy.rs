@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use executors::{policy::AgentPolicy, sandbox::Sandbox};
+use serde::Serialize;
+use sqlx::{SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// The agent policy an execution process ran under
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutionProcessPolicy {
+    pub execution_process_id: Uuid,
+    #[ts(type = "AgentPolicy")]
+    pub policy: Json<AgentPolicy>,
+    /// Sandbox the process ran in to enforce the policy; unset for processes started before
+    /// policies were sandboxed
+    pub sandbox: Option<Sandbox>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl ExecutionProcessPolicy {
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        policy: &AgentPolicy,
+        sandbox: Sandbox,
+    ) -> Result<Self, sqlx::Error> {
+        let policy = Json(policy);
+        sqlx::query_as!(
+            ExecutionProcessPolicy,
+            r#"INSERT INTO execution_process_policies (execution_process_id, policy, sandbox)
+               VALUES ($1, $2, $3)
+               RETURNING execution_process_id as "execution_process_id!: Uuid",
+                         policy as "policy!: Json<AgentPolicy>",
+                         sandbox as "sandbox: Sandbox",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            execution_process_id,
+            policy,
+            sandbox
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessPolicy,
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid",
+                      policy as "policy!: Json<AgentPolicy>",
+                      sandbox as "sandbox: Sandbox",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM execution_process_policies
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
@@ -18,6 +18,15 @@ pub struct ExecutionProcessRepoState {
     pub updated_at: DateTime<Utc>,
 }
 
+/// HEAD commits a repo was at around one execution of a workspace
+#[derive(Debug, Clone)]
+pub struct WorkspaceRepoHeads {
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub before_head_commit: Option<String>,
+    pub after_head_commit: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateExecutionProcessRepoState {
     pub repo_id: Uuid,
@@ -27,6 +36,30 @@ pub struct CreateExecutionProcessRepoState {
 }
 
 impl ExecutionProcessRepoState {
+    /// Recorded HEAD commits of every execution in the project's workspaces, oldest first
+    pub async fn find_heads_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<WorkspaceRepoHeads>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceRepoHeads,
+            r#"SELECT s.workspace_id as "workspace_id!: Uuid",
+                      rs.repo_id as "repo_id!: Uuid",
+                      rs.before_head_commit,
+                      rs.after_head_commit
+               FROM execution_process_repo_states rs
+               JOIN execution_processes ep ON rs.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE t.project_id = $1
+               ORDER BY rs.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn create_many(
         pool: &SqlitePool,
         execution_process_id: Uuid,
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A coding agent process that was still running when the server stopped
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct InterruptedExecution {
+    pub execution_process_id: Uuid,
+    pub workspace_id: Uuid,
+    /// The coding agent run that resumed the interrupted one
+    pub resumed_execution_process_id: Option<Uuid>,
+    #[ts(type = "Date")]
+    pub interrupted_at: DateTime<Utc>,
+}
+
+impl InterruptedExecution {
+    /// Record that the process was interrupted. Recording it again changes nothing.
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO interrupted_executions (execution_process_id, workspace_id)
+               VALUES ($1, $2)
+               ON CONFLICT(execution_process_id) DO NOTHING"#,
+            execution_process_id,
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The workspace's most recent interrupted run that has not been resumed
+    pub async fn find_unresumed_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            InterruptedExecution,
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      resumed_execution_process_id as "resumed_execution_process_id?: Uuid",
+                      interrupted_at as "interrupted_at!: DateTime<Utc>"
+               FROM interrupted_executions
+               WHERE workspace_id = $1 AND resumed_execution_process_id IS NULL
+               ORDER BY interrupted_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn mark_resumed(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        resumed_execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE interrupted_executions
+               SET resumed_execution_process_id = $2
+               WHERE execution_process_id = $1"#,
+            execution_process_id,
+            resumed_execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// A merge or rebase of a workspace branch that stopped on conflicts
+pub struct MergeConflict;
+
+impl MergeConflict {
+    pub async fn record(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            "INSERT INTO merge_conflicts (id, workspace_id, repo_id) VALUES ($1, $2, $3)",
+            id,
+            workspace_id,
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Number of distinct workspaces of the project that hit a conflict, per repo
+    pub async fn count_workspaces_by_repo(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<Uuid, i64>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT mc.repo_id as "repo_id!: Uuid",
+                      COUNT(DISTINCT mc.workspace_id) as "count!: i64"
+               FROM merge_conflicts mc
+               JOIN workspaces w ON mc.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE t.project_id = $1
+               GROUP BY mc.repo_id"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.repo_id, row.count))
+            .collect())
+    }
+
+    /// The project's workspaces that hit a conflict in any repo
+    pub async fn find_workspace_ids_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashSet<Uuid>, sqlx::Error> {
+        let rows: Vec<Uuid> = sqlx::query_scalar!(
+            r#"SELECT DISTINCT mc.workspace_id as "workspace_id!: Uuid"
+               FROM merge_conflicts mc
+               JOIN workspaces w ON mc.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE t.project_id = $1"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+}
@@ -1,15 +1,46 @@
+pub mod ai_review;
 pub mod coding_agent_turn;
+pub mod conflict_resolution;
 pub mod execution_process;
+pub mod execution_process_artifact;
 pub mod execution_process_logs;
+pub mod execution_process_pid;
+pub mod execution_process_policy;
 pub mod execution_process_repo_state;
 pub mod image;
+pub mod interrupted_execution;
 pub mod merge;
+pub mod merge_conflict;
+pub mod plan_turn;
 pub mod project;
+pub mod project_agent_pipeline;
+pub mod project_agent_policy;
+pub mod project_ai_review_policy;
+pub mod project_env_set;
+pub mod project_quality_gate;
+pub mod project_remote_host;
 pub mod project_repo;
+pub mod project_script;
+pub mod project_settings;
 pub mod repo;
+pub mod repo_credential;
+pub mod review_comment;
 pub mod scratch;
+pub mod secret;
+pub mod secret_allowlist;
 pub mod session;
+pub mod setting;
 pub mod tag;
 pub mod task;
+pub mod task_status_transition;
+pub mod task_template;
+pub mod terminal_session;
 pub mod workspace;
+pub mod workspace_env_override;
+pub mod workspace_event;
+pub mod workspace_merge_description;
+pub mod workspace_pipeline_stage;
 pub mod workspace_repo;
+pub mod workspace_review;
+pub mod workspace_stack;
+pub mod workspace_test_run;
@@ -0,0 +1,207 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "plan_turn_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PlanTurnStatus {
+    /// The agent is still planning
+    Running,
+    /// The agent proposed a plan, waiting to be approved or rejected
+    Proposed,
+    /// The plan run failed or was stopped before proposing a plan
+    Failed,
+    /// The plan was approved and the run carrying it out started
+    Approved,
+    /// The plan was rejected
+    Rejected,
+}
+
+/// A plan-only coding agent turn of a workspace: the agent's proposal of how it would carry
+/// out the task, made without changing or committing anything
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PlanTurn {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// The coding agent process that made the plan
+    pub execution_process_id: Uuid,
+    /// The proposed plan, the last message of the plan run
+    pub plan: Option<String>,
+    pub status: PlanTurnStatus,
+    /// The coding agent run started when the plan was approved
+    pub approved_execution_process_id: Option<Uuid>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PlanTurn {
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            PlanTurn,
+            r#"INSERT INTO plan_turns (id, workspace_id, execution_process_id)
+               VALUES ($1, $2, $3)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         plan,
+                         status as "status!: PlanTurnStatus",
+                         approved_execution_process_id as "approved_execution_process_id?: Uuid",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            execution_process_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PlanTurn,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      plan,
+                      status as "status!: PlanTurnStatus",
+                      approved_execution_process_id as "approved_execution_process_id?: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM plan_turns
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Plan turns of the workspace, newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PlanTurn,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      plan,
+                      status as "status!: PlanTurnStatus",
+                      approved_execution_process_id as "approved_execution_process_id?: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM plan_turns
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PlanTurn,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      plan,
+                      status as "status!: PlanTurnStatus",
+                      approved_execution_process_id as "approved_execution_process_id?: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM plan_turns
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record the outcome of the plan run
+    pub async fn finish(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: PlanTurnStatus,
+        plan: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            PlanTurn,
+            r#"UPDATE plan_turns
+               SET status = $2, plan = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         plan,
+                         status as "status!: PlanTurnStatus",
+                         approved_execution_process_id as "approved_execution_process_id?: Uuid",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            status,
+            plan
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn approve(
+        pool: &SqlitePool,
+        id: Uuid,
+        approved_execution_process_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            PlanTurn,
+            r#"UPDATE plan_turns
+               SET status = 'approved', approved_execution_process_id = $2,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         plan,
+                         status as "status!: PlanTurnStatus",
+                         approved_execution_process_id as "approved_execution_process_id?: Uuid",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            approved_execution_process_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn reject(pool: &SqlitePool, id: Uuid) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            PlanTurn,
+            r#"UPDATE plan_turns
+               SET status = 'rejected', updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         plan,
+                         status as "status!: PlanTurnStatus",
+                         approved_execution_process_id as "approved_execution_process_id?: Uuid",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// One stage of a project's agent pipeline
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct AgentPipelineStage {
+    pub name: String,
+    /// The agent that runs the stage
+    pub executor_profile_id: ExecutorProfileId,
+    /// Prompt of the stage. `{{task_title}}`, `{{task_description}}`, `{{stage}}` and
+    /// `{{previous_output}}`, the last message of the stage before, are filled in when it
+    /// starts.
+    pub prompt_template: String,
+}
+
+/// Runs each workspace of a project through a sequence of coding agent stages, e.g. plan,
+/// implement, self-review and fix, instead of a single agent run
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectAgentPipeline {
+    pub project_id: Uuid,
+    /// The stages, in the order they run
+    #[ts(type = "Array<AgentPipelineStage>")]
+    pub stages: Json<Vec<AgentPipelineStage>>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertProjectAgentPipeline {
+    pub stages: Vec<AgentPipelineStage>,
+}
+
+impl ProjectAgentPipeline {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectAgentPipeline,
+            r#"SELECT project_id as "project_id!: Uuid",
+                      stages as "stages!: Json<Vec<AgentPipelineStage>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_agent_pipelines
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectAgentPipeline,
+    ) -> Result<Self, sqlx::Error> {
+        let stages = Json(&data.stages);
+        sqlx::query_as!(
+            ProjectAgentPipeline,
+            r#"INSERT INTO project_agent_pipelines (project_id, stages)
+               VALUES ($1, $2)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   stages = excluded.stages,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid",
+                         stages as "stages!: Json<Vec<AgentPipelineStage>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            stages
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_agent_pipelines WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use executors::policy::AgentPolicy;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Limits on what the coding agents of a project's workspaces may do, enforced by a sandbox;
+/// see `executors::policy`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectAgentPolicy {
+    pub project_id: Uuid,
+    /// Whether agents may reach the network
+    pub allow_network: bool,
+    /// Programs agents may run in their shell; unset allows any
+    #[ts(type = "Array<string> | null")]
+    pub allowed_commands: Option<Json<Vec<String>>>,
+    /// Whether agents may only write files inside the workspace
+    pub restrict_writes_to_worktree: bool,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertProjectAgentPolicy {
+    pub allow_network: bool,
+    pub allowed_commands: Option<Vec<String>>,
+    pub restrict_writes_to_worktree: bool,
+}
+
+impl ProjectAgentPolicy {
+    /// The policy agents of the project run under
+    pub fn policy(&self) -> AgentPolicy {
+        AgentPolicy {
+            allow_network: self.allow_network,
+            allowed_commands: self.allowed_commands.as_ref().map(|c| c.0.clone()),
+            restrict_writes_to_worktree: self.restrict_writes_to_worktree,
+        }
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectAgentPolicy,
+            r#"SELECT project_id as "project_id!: Uuid",
+                      allow_network as "allow_network!: bool",
+                      allowed_commands as "allowed_commands: Json<Vec<String>>",
+                      restrict_writes_to_worktree as "restrict_writes_to_worktree!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_agent_policies
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectAgentPolicy,
+    ) -> Result<Self, sqlx::Error> {
+        let allowed_commands = data.allowed_commands.as_ref().map(Json);
+        sqlx::query_as!(
+            ProjectAgentPolicy,
+            r#"INSERT INTO project_agent_policies (project_id, allow_network, allowed_commands, restrict_writes_to_worktree)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   allow_network = excluded.allow_network,
+                   allowed_commands = excluded.allowed_commands,
+                   restrict_writes_to_worktree = excluded.restrict_writes_to_worktree,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid",
+                         allow_network as "allow_network!: bool",
+                         allowed_commands as "allowed_commands: Json<Vec<String>>",
+                         restrict_writes_to_worktree as "restrict_writes_to_worktree!: bool",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.allow_network,
+            allowed_commands,
+            data.restrict_writes_to_worktree
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_agent_policies WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::review_comment::ReviewSeverity;
+
+/// Has a coding agent review a workspace's diff before the workspace can be merged
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectAiReviewPolicy {
+    pub project_id: Uuid,
+    /// The agent that does the review
+    #[ts(type = "ExecutorProfileId")]
+    pub executor_profile_id: Json<ExecutorProfileId>,
+    /// Unresolved findings at or above this severity block the merge; lower ones are
+    /// reported as warnings. Unset, no finding blocks.
+    pub block_severity: Option<ReviewSeverity>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertProjectAiReviewPolicy {
+    pub executor_profile_id: ExecutorProfileId,
+    pub block_severity: Option<ReviewSeverity>,
+}
+
+impl ProjectAiReviewPolicy {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectAiReviewPolicy,
+            r#"SELECT project_id as "project_id!: Uuid",
+                      executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                      block_severity as "block_severity: ReviewSeverity",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_ai_review_policies
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectAiReviewPolicy,
+    ) -> Result<Self, sqlx::Error> {
+        let executor_profile_id = Json(&data.executor_profile_id);
+        sqlx::query_as!(
+            ProjectAiReviewPolicy,
+            r#"INSERT INTO project_ai_review_policies (project_id, executor_profile_id, block_severity)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   executor_profile_id = excluded.executor_profile_id,
+                   block_severity = excluded.block_severity,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid",
+                         executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                         block_severity as "block_severity: ReviewSeverity",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            executor_profile_id,
+            data.block_severity
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_ai_review_policies WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A command that must exit successfully in every worktree of a workspace
+/// before the workspace can be merged.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectQualityGate {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub command: String,
+    pub sort_order: i64,
+    /// Seconds the command may run before it is stopped and the gate fails; unset allows 30
+    /// minutes
+    pub timeout_secs: Option<i64>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateProjectQualityGate {
+    pub name: String,
+    pub command: String,
+    pub sort_order: Option<i64>,
+    #[serde(default)]
+    pub timeout_secs: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateProjectQualityGate {
+    pub name: Option<String>,
+    pub command: Option<String>,
+    pub sort_order: Option<i64>,
+    #[serde(default)]
+    pub timeout_secs: Option<u32>,
+}
+
+impl ProjectQualityGate {
+    /// Gates for a project in the order they should run
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectQualityGate,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name as "name!",
+                      command as "command!",
+                      sort_order as "sort_order!: i64",
+                      timeout_secs,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_quality_gates
+               WHERE project_id = $1
+               ORDER BY sort_order ASC, created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectQualityGate,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name as "name!",
+                      command as "command!",
+                      sort_order as "sort_order!: i64",
+                      timeout_secs,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_quality_gates
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateProjectQualityGate,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let sort_order = data.sort_order.unwrap_or(0);
+        let timeout_secs = data.timeout_secs.map(i64::from);
+        sqlx::query_as!(
+            ProjectQualityGate,
+            r#"INSERT INTO project_quality_gates (id, project_id, name, command, sort_order, timeout_secs)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name as "name!",
+                         command as "command!",
+                         sort_order as "sort_order!: i64",
+                         timeout_secs,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.name,
+            data.command,
+            sort_order,
+            timeout_secs
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateProjectQualityGate,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = data.name.as_ref().unwrap_or(&existing.name);
+        let command = data.command.as_ref().unwrap_or(&existing.command);
+        let sort_order = data.sort_order.unwrap_or(existing.sort_order);
+        let timeout_secs = data.timeout_secs.map(i64::from).or(existing.timeout_secs);
+
+        sqlx::query_as!(
+            ProjectQualityGate,
+            r#"UPDATE project_quality_gates
+               SET name = $2, command = $3, sort_order = $4, timeout_secs = $5,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name as "name!",
+                         command as "command!",
+                         sort_order as "sort_order!: i64",
+                         timeout_secs,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            command,
+            sort_order,
+            timeout_secs
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM project_quality_gates WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use executors::remote::RemoteHost;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Machine reached over SSH on which the project's execution processes run
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectRemoteHost {
+    pub project_id: Uuid,
+    pub host: String,
+    /// Defaults to the SSH configuration for the host
+    pub user: Option<String>,
+    pub port: Option<i64>,
+    pub identity_file: Option<String>,
+    /// Directory on the host under which workspaces are synced
+    pub remote_dir: String,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetProjectRemoteHost {
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<i64>,
+    pub identity_file: Option<String>,
+    pub remote_dir: String,
+}
+
+impl ProjectRemoteHost {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectRemoteHost,
+            r#"SELECT project_id as "project_id!: Uuid",
+                      host as "host!",
+                      user,
+                      port as "port: i64",
+                      identity_file,
+                      remote_dir as "remote_dir!",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_remote_hosts
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Create or replace the project's host
+    pub async fn set(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &SetProjectRemoteHost,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectRemoteHost,
+            r#"INSERT INTO project_remote_hosts (project_id, host, user, port, identity_file, remote_dir)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   host = excluded.host,
+                   user = excluded.user,
+                   port = excluded.port,
+                   identity_file = excluded.identity_file,
+                   remote_dir = excluded.remote_dir,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid",
+                         host as "host!",
+                         user,
+                         port as "port: i64",
+                         identity_file,
+                         remote_dir as "remote_dir!",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.host,
+            data.user,
+            data.port,
+            data.identity_file,
+            data.remote_dir
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_remote_hosts WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl From<ProjectRemoteHost> for RemoteHost {
+    fn from(host: ProjectRemoteHost) -> Self {
+        RemoteHost {
+            host: host.host,
+            user: host.user,
+            port: host.port.and_then(|port| u16::try_from(port).ok()),
+            identity_file: host.identity_file.map(PathBuf::from),
+            remote_dir: host.remote_dir,
+        }
+    }
+}
@@ -24,6 +24,14 @@ pub struct ProjectRepo {
     pub id: Uuid,
     pub project_id: Uuid,
     pub repo_id: Uuid,
+    /// Branch new workspaces of the project target in this repo when none is given;
+    /// unset falls back to the repo's default target branch
+    pub default_target_branch: Option<String>,
+    /// Directory of the repo the project is limited to, such as `services/billing` in a
+    /// monorepo; diffs, diff stats and quality gates of the project's workspaces only cover it
+    pub path_scope: Option<String>,
+    /// Whether workspaces only check out the path scope and the files at the repo root
+    pub sparse_checkout: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, TS)]
@@ -32,6 +40,14 @@ pub struct CreateProjectRepo {
     pub git_repo_path: String,
 }
 
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateProjectRepo {
+    pub default_target_branch: Option<String>,
+    pub path_scope: Option<String>,
+    #[serde(default)]
+    pub sparse_checkout: bool,
+}
+
 impl ProjectRepo {
     pub async fn find_by_project_id(
         pool: &SqlitePool,
@@ -41,7 +57,10 @@ impl ProjectRepo {
             ProjectRepo,
             r#"SELECT id as "id!: Uuid",
                       project_id as "project_id!: Uuid",
-                      repo_id as "repo_id!: Uuid"
+                      repo_id as "repo_id!: Uuid",
+                      default_target_branch,
+                      path_scope,
+                      sparse_checkout as "sparse_checkout!: bool"
                FROM project_repos
                WHERE project_id = $1"#,
             project_id
@@ -58,7 +77,10 @@ impl ProjectRepo {
             ProjectRepo,
             r#"SELECT id as "id!: Uuid",
                       project_id as "project_id!: Uuid",
-                      repo_id as "repo_id!: Uuid"
+                      repo_id as "repo_id!: Uuid",
+                      default_target_branch,
+                      path_scope,
+                      sparse_checkout as "sparse_checkout!: bool"
                FROM project_repos
                WHERE repo_id = $1"#,
             repo_id
@@ -82,7 +104,11 @@ impl ProjectRepo {
                       r.copy_files,
                       r.parallel_setup_script as "parallel_setup_script!: bool",
                       r.dev_server_script,
+                      r.test_script,
                       r.default_target_branch,
+                      r.require_ci_pass as "require_ci_pass!: bool",
+                      r.push_remote,
+                      r.auto_fetch as "auto_fetch!: bool",
                       r.created_at as "created_at!: DateTime<Utc>",
                       r.updated_at as "updated_at!: DateTime<Utc>"
                FROM repos r
@@ -104,7 +130,10 @@ impl ProjectRepo {
             ProjectRepo,
             r#"SELECT id as "id!: Uuid",
                       project_id as "project_id!: Uuid",
-                      repo_id as "repo_id!: Uuid"
+                      repo_id as "repo_id!: Uuid",
+                      default_target_branch,
+                      path_scope,
+                      sparse_checkout as "sparse_checkout!: bool"
                FROM project_repos
                WHERE project_id = $1 AND repo_id = $2"#,
             project_id,
@@ -143,6 +172,34 @@ impl ProjectRepo {
         Ok(repo)
     }
 
+    /// Set how the project uses the repo. None if the repo is not part of the project.
+    pub async fn update(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        repo_id: Uuid,
+        data: &UpdateProjectRepo,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectRepo,
+            r#"UPDATE project_repos
+               SET default_target_branch = $3, path_scope = $4, sparse_checkout = $5
+               WHERE project_id = $1 AND repo_id = $2
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         default_target_branch,
+                         path_scope,
+                         sparse_checkout as "sparse_checkout!: bool""#,
+            project_id,
+            repo_id,
+            data.default_target_branch,
+            data.path_scope,
+            data.sparse_checkout
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
     pub async fn remove_repo_from_project(
         pool: &SqlitePool,
         project_id: Uuid,
@@ -175,7 +232,10 @@ impl ProjectRepo {
                VALUES ($1, $2, $3)
                RETURNING id as "id!: Uuid",
                          project_id as "project_id!: Uuid",
-                         repo_id as "repo_id!: Uuid""#,
+                         repo_id as "repo_id!: Uuid",
+                         default_target_branch,
+                         path_scope,
+                         sparse_checkout as "sparse_checkout!: bool""#,
             id,
             project_id,
             repo_id
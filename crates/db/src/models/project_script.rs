@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::rust::double_option;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::execution_process::{
+    ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus, ExecutorActionField,
+};
+
+/// A named command that can be run on demand in any workspace of a project,
+/// e.g. "migrate db" or "build docs".
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectScript {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub command: String,
+    /// Repo whose worktree the command runs in. Unset runs it in the only repo of
+    /// single-repo workspaces, otherwise in the workspace directory.
+    pub repo_id: Option<Uuid>,
+    /// Extra environment variables for the command
+    #[ts(type = "Record<string, string>")]
+    pub env: sqlx::types::Json<HashMap<String, String>>,
+    /// Glob patterns, relative to where the command runs, of files kept after each run
+    #[ts(type = "Array<string>")]
+    pub artifacts: sqlx::types::Json<Vec<String>>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateProjectScript {
+    pub name: String,
+    pub command: String,
+    pub repo_id: Option<Uuid>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateProjectScript {
+    pub name: Option<String>,
+    pub command: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub repo_id: Option<Option<Uuid>>,
+    pub env: Option<HashMap<String, String>>,
+    pub artifacts: Option<Vec<String>>,
+}
+
+impl ProjectScript {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectScript,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name as "name!",
+                      command as "command!",
+                      repo_id as "repo_id: Uuid",
+                      env as "env!: sqlx::types::Json<HashMap<String, String>>",
+                      artifacts as "artifacts!: sqlx::types::Json<Vec<String>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_scripts
+               WHERE project_id = $1
+               ORDER BY name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectScript,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name as "name!",
+                      command as "command!",
+                      repo_id as "repo_id: Uuid",
+                      env as "env!: sqlx::types::Json<HashMap<String, String>>",
+                      artifacts as "artifacts!: sqlx::types::Json<Vec<String>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_scripts
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateProjectScript,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let env = sqlx::types::Json(&data.env);
+        let artifacts = sqlx::types::Json(&data.artifacts);
+        sqlx::query_as!(
+            ProjectScript,
+            r#"INSERT INTO project_scripts (id, project_id, name, command, repo_id, env, artifacts)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name as "name!",
+                         command as "command!",
+                         repo_id as "repo_id: Uuid",
+                         env as "env!: sqlx::types::Json<HashMap<String, String>>",
+                         artifacts as "artifacts!: sqlx::types::Json<Vec<String>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.name,
+            data.command,
+            data.repo_id,
+            env,
+            artifacts
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateProjectScript,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = data.name.as_ref().unwrap_or(&existing.name);
+        let command = data.command.as_ref().unwrap_or(&existing.command);
+        let repo_id = match data.repo_id {
+            Some(repo_id) => repo_id,
+            None => existing.repo_id,
+        };
+        let env = sqlx::types::Json(data.env.as_ref().unwrap_or(&existing.env.0));
+        let artifacts = sqlx::types::Json(data.artifacts.as_ref().unwrap_or(&existing.artifacts.0));
+
+        sqlx::query_as!(
+            ProjectScript,
+            r#"UPDATE project_scripts
+               SET name = $2, command = $3, repo_id = $4, env = $5, artifacts = $6,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name as "name!",
+                         command as "command!",
+                         repo_id as "repo_id: Uuid",
+                         env as "env!: sqlx::types::Json<HashMap<String, String>>",
+                         artifacts as "artifacts!: sqlx::types::Json<Vec<String>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            command,
+            repo_id,
+            env,
+            artifacts
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM project_scripts WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Record that `execution_process_id` is a run of this script in `workspace_id`
+    pub async fn record_run(
+        pool: &SqlitePool,
+        id: Uuid,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let run_id = Uuid::new_v4();
+        sqlx::query!(
+            r#"INSERT INTO project_script_runs (id, project_script_id, workspace_id, execution_process_id)
+               VALUES ($1, $2, $3, $4)"#,
+            run_id,
+            id,
+            workspace_id,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Execution processes that ran this script, most recent first, optionally limited to
+    /// one workspace
+    pub async fn find_runs(
+        pool: &SqlitePool,
+        id: Uuid,
+        workspace_id: Option<Uuid>,
+    ) -> Result<Vec<ExecutionProcess>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT
+                ep.id as "id!: Uuid",
+                ep.session_id as "session_id!: Uuid",
+                ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                ep.status as "status!: ExecutionProcessStatus",
+                ep.exit_code,
+                ep.dropped as "dropped!: bool",
+                ep.started_at as "started_at!: DateTime<Utc>",
+                ep.completed_at as "completed_at?: DateTime<Utc>",
+                ep.created_at as "created_at!: DateTime<Utc>",
+                ep.updated_at as "updated_at!: DateTime<Utc>",
+                ep.retried_from as "retried_from?: Uuid"
+               FROM project_script_runs r
+               JOIN execution_processes ep ON ep.id = r.execution_process_id
+               WHERE r.project_script_id = $1
+                 AND ($2 IS NULL OR r.workspace_id = $2)
+               ORDER BY ep.created_at DESC"#,
+            id,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
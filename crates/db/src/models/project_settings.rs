@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type, types::Json};
+use strum_macros::Display;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// How a workspace ends when it is closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS, Display)]
+#[sqlx(type_name = "merge_strategy", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Merge the workspace's branches into their target branches
+    Merge,
+    /// Remove the workspace without merging
+    Discard,
+}
+
+/// How open workspaces are brought up to date when their target branch gains commits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS, Display)]
+#[sqlx(type_name = "auto_sync_strategy", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum AutoSyncStrategy {
+    /// Rebase the workspace branch onto the target branch
+    Rebase,
+    /// Merge the target branch into the workspace branch
+    Merge,
+}
+
+/// Per-project overrides of server-wide behavior; unset fields use the server's settings
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectSettings {
+    pub project_id: Uuid,
+    /// Agent new workspaces of the project start with when none is chosen
+    #[ts(type = "ExecutorProfileId | null")]
+    pub default_executor_profile_id: Option<Json<ExecutorProfileId>>,
+    /// Branch name of new workspaces, with `{prefix}`, `{short_id}` and `{title}`
+    /// placeholders; unset uses `{prefix}/{short_id}-{title}`
+    pub branch_template: Option<String>,
+    /// Strategy a close uses when the request does not name one
+    pub default_merge_strategy: Option<MergeStrategy>,
+    /// Whether the project's quality gates run before a workspace is merged
+    pub run_quality_gates: bool,
+    /// Hours an idle workspace of an in-progress or in-review task is kept before cleanup
+    pub workspace_retention_hours: Option<i64>,
+    /// Hours an idle archived workspace, or one whose task is not active, is kept
+    pub archived_workspace_retention_hours: Option<i64>,
+    /// Keep open workspaces up with their target branches this way; unset leaves them alone
+    pub auto_sync_strategy: Option<AutoSyncStrategy>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertProjectSettings {
+    pub default_executor_profile_id: Option<ExecutorProfileId>,
+    pub branch_template: Option<String>,
+    pub default_merge_strategy: Option<MergeStrategy>,
+    pub run_quality_gates: bool,
+    pub workspace_retention_hours: Option<u32>,
+    pub archived_workspace_retention_hours: Option<u32>,
+    pub auto_sync_strategy: Option<AutoSyncStrategy>,
+}
+
+impl ProjectSettings {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectSettings,
+            r#"SELECT project_id as "project_id!: Uuid",
+                      default_executor_profile_id as "default_executor_profile_id: Json<ExecutorProfileId>",
+                      branch_template,
+                      default_merge_strategy as "default_merge_strategy: MergeStrategy",
+                      run_quality_gates as "run_quality_gates!: bool",
+                      workspace_retention_hours,
+                      archived_workspace_retention_hours,
+                      auto_sync_strategy as "auto_sync_strategy: AutoSyncStrategy",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_settings
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Settings of the project a task belongs to
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectSettings,
+            r#"SELECT ps.project_id as "project_id!: Uuid",
+                      ps.default_executor_profile_id as "default_executor_profile_id: Json<ExecutorProfileId>",
+                      ps.branch_template,
+                      ps.default_merge_strategy as "default_merge_strategy: MergeStrategy",
+                      ps.run_quality_gates as "run_quality_gates!: bool",
+                      ps.workspace_retention_hours,
+                      ps.archived_workspace_retention_hours,
+                      ps.auto_sync_strategy as "auto_sync_strategy: AutoSyncStrategy",
+                      ps.created_at as "created_at!: DateTime<Utc>",
+                      ps.updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_settings ps
+               JOIN tasks t ON t.project_id = ps.project_id
+               WHERE t.id = $1"#,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectSettings,
+    ) -> Result<Self, sqlx::Error> {
+        let default_executor_profile_id = data.default_executor_profile_id.as_ref().map(Json);
+        let workspace_retention_hours = data.workspace_retention_hours.map(i64::from);
+        let archived_workspace_retention_hours =
+            data.archived_workspace_retention_hours.map(i64::from);
+        sqlx::query_as!(
+            ProjectSettings,
+            r#"INSERT INTO project_settings (project_id, default_executor_profile_id, branch_template, default_merge_strategy, run_quality_gates, workspace_retention_hours, archived_workspace_retention_hours, auto_sync_strategy)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   default_executor_profile_id = excluded.default_executor_profile_id,
+                   branch_template = excluded.branch_template,
+                   default_merge_strategy = excluded.default_merge_strategy,
+                   run_quality_gates = excluded.run_quality_gates,
+                   workspace_retention_hours = excluded.workspace_retention_hours,
+                   archived_workspace_retention_hours = excluded.archived_workspace_retention_hours,
+                   auto_sync_strategy = excluded.auto_sync_strategy,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid",
+                         default_executor_profile_id as "default_executor_profile_id: Json<ExecutorProfileId>",
+                         branch_template,
+                         default_merge_strategy as "default_merge_strategy: MergeStrategy",
+                         run_quality_gates as "run_quality_gates!: bool",
+                         workspace_retention_hours,
+                         archived_workspace_retention_hours,
+                         auto_sync_strategy as "auto_sync_strategy: AutoSyncStrategy",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            default_executor_profile_id,
+            data.branch_template,
+            data.default_merge_strategy,
+            data.run_quality_gates,
+            workspace_retention_hours,
+            archived_workspace_retention_hours,
+            data.auto_sync_strategy
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
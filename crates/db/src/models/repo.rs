@@ -27,14 +27,23 @@ pub struct Repo {
     pub copy_files: Option<String>,
     pub parallel_setup_script: bool,
     pub dev_server_script: Option<String>,
+    /// Command run by the workspace test endpoint; output is parsed into a test report
+    pub test_script: Option<String>,
     pub default_target_branch: Option<String>,
+    /// Block merges until CI on the pushed workspace branch head has passed. Azure DevOps CI
+    /// status is not read, so repos hosted there only need the branch pushed
+    pub require_ci_pass: bool,
+    /// Remote workspace branches are pushed to and PRs opened from; `None` uses the git default
+    pub push_remote: Option<String>,
+    /// Fetch the repo's remotes on the server's fetch schedule
+    pub auto_fetch: bool,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Deserialize, TS)]
+#[derive(Debug, Clone, Default, Deserialize, TS)]
 #[ts(export)]
 pub struct UpdateRepo {
     #[serde(
@@ -85,6 +94,14 @@ pub struct UpdateRepo {
     #[ts(optional, type = "string | null")]
     pub dev_server_script: Option<Option<String>>,
 
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub test_script: Option<Option<String>>,
+
     #[serde(
         default,
         skip_serializing_if = "Option::is_none",
@@ -92,6 +109,30 @@ pub struct UpdateRepo {
     )]
     #[ts(optional, type = "string | null")]
     pub default_target_branch: Option<Option<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "boolean | null")]
+    pub require_ci_pass: Option<Option<bool>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub push_remote: Option<Option<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "boolean | null")]
+    pub auto_fetch: Option<Option<bool>>,
 }
 
 impl Repo {
@@ -109,7 +150,11 @@ impl Repo {
                       copy_files,
                       parallel_setup_script as "parallel_setup_script!: bool",
                       dev_server_script,
+                      test_script,
                       default_target_branch,
+                      require_ci_pass as "require_ci_pass!: bool",
+                      push_remote,
+                      auto_fetch as "auto_fetch!: bool",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM repos
@@ -148,7 +193,11 @@ impl Repo {
                       copy_files,
                       parallel_setup_script as "parallel_setup_script!: bool",
                       dev_server_script,
+                      test_script,
                       default_target_branch,
+                      require_ci_pass as "require_ci_pass!: bool",
+                      push_remote,
+                      auto_fetch as "auto_fetch!: bool",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM repos
@@ -204,7 +253,11 @@ impl Repo {
                          copy_files,
                          parallel_setup_script as "parallel_setup_script!: bool",
                          dev_server_script,
+                         test_script,
                          default_target_branch,
+                         require_ci_pass as "require_ci_pass!: bool",
+                         push_remote,
+                         auto_fetch as "auto_fetch!: bool",
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
             id,
@@ -239,7 +292,11 @@ impl Repo {
                       copy_files,
                       parallel_setup_script as "parallel_setup_script!: bool",
                       dev_server_script,
+                      test_script,
                       default_target_branch,
+                      require_ci_pass as "require_ci_pass!: bool",
+                      push_remote,
+                      auto_fetch as "auto_fetch!: bool",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM repos
@@ -285,10 +342,26 @@ impl Repo {
             None => existing.dev_server_script,
             Some(v) => v.clone(),
         };
+        let test_script = match &payload.test_script {
+            None => existing.test_script,
+            Some(v) => v.clone(),
+        };
         let default_target_branch = match &payload.default_target_branch {
             None => existing.default_target_branch,
             Some(v) => v.clone(),
         };
+        let require_ci_pass = match &payload.require_ci_pass {
+            None => existing.require_ci_pass,
+            Some(v) => v.unwrap_or(false),
+        };
+        let push_remote = match &payload.push_remote {
+            None => existing.push_remote,
+            Some(v) => v.clone(),
+        };
+        let auto_fetch = match &payload.auto_fetch {
+            None => existing.auto_fetch,
+            Some(v) => v.unwrap_or(true),
+        };
 
         sqlx::query_as!(
             Repo,
@@ -300,8 +373,12 @@ impl Repo {
                    parallel_setup_script = $5,
                    dev_server_script = $6,
                    default_target_branch = $7,
+                   require_ci_pass = $8,
+                   test_script = $9,
+                   push_remote = $10,
+                   auto_fetch = $11,
                    updated_at = datetime('now', 'subsec')
-               WHERE id = $8
+               WHERE id = $12
                RETURNING id as "id!: Uuid",
                          path,
                          name,
@@ -311,7 +388,11 @@ impl Repo {
                          copy_files,
                          parallel_setup_script as "parallel_setup_script!: bool",
                          dev_server_script,
+                         test_script,
                          default_target_branch,
+                         require_ci_pass as "require_ci_pass!: bool",
+                         push_remote,
+                         auto_fetch as "auto_fetch!: bool",
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
             display_name,
@@ -321,6 +402,10 @@ impl Repo {
             parallel_setup_script,
             dev_server_script,
             default_target_branch,
+            require_ci_pass,
+            test_script,
+            push_remote,
+            auto_fetch,
             id
         )
         .fetch_one(pool)
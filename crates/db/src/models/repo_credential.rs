@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "repo_credential_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RepoCredentialKind {
+    HttpsToken,
+    SshKey,
+}
+
+/// Credential for a repo's fetches and pushes. `secret` is the encrypted HTTPS token or
+/// SSH key path and is never sent to clients.
+#[derive(Debug, Clone, FromRow)]
+pub struct RepoCredential {
+    pub repo_id: Uuid,
+    pub kind: RepoCredentialKind,
+    pub username: Option<String>,
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RepoCredential {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RepoCredential,
+            r#"SELECT repo_id as "repo_id!: Uuid",
+                      kind as "kind!: RepoCredentialKind",
+                      username,
+                      secret,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM repo_credentials"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RepoCredential,
+            r#"SELECT repo_id as "repo_id!: Uuid",
+                      kind as "kind!: RepoCredentialKind",
+                      username,
+                      secret,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM repo_credentials
+               WHERE repo_id = $1"#,
+            repo_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Store the repo's credential, replacing any existing one
+    pub async fn upsert(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        kind: RepoCredentialKind,
+        username: Option<&str>,
+        secret: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            RepoCredential,
+            r#"INSERT INTO repo_credentials (repo_id, kind, username, secret)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(repo_id) DO UPDATE SET
+                   kind = excluded.kind,
+                   username = excluded.username,
+                   secret = excluded.secret,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING repo_id as "repo_id!: Uuid",
+                         kind as "kind!: RepoCredentialKind",
+                         username,
+                         secret,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            repo_id,
+            kind,
+            username,
+            secret
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, repo_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM repo_credentials WHERE repo_id = $1", repo_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,304 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Side of a diff a comment's line number refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "review_comment_side", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewCommentSide {
+    /// A line of the file before the change, for removed lines
+    Old,
+    /// A line of the file after the change, for added and unchanged lines
+    New,
+}
+
+/// How serious a review comment is, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "review_severity", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Comment on a line of a file in a workspace diff
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ReviewComment {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    /// Path of the file in the diff, relative to the repo root
+    pub path: String,
+    pub line: i64,
+    /// Last line of a comment on several lines
+    pub end_line: Option<i64>,
+    pub side: ReviewCommentSide,
+    pub body: String,
+    /// Replacement text for the commented lines, which can be applied to the worktree
+    pub suggestion: Option<String>,
+    pub severity: Option<ReviewSeverity>,
+    /// The agent review that reported the comment as a finding; unset for comments by people
+    pub ai_review_id: Option<Uuid>,
+    /// When the comment was resolved; unset while it is open
+    #[ts(type = "Date | null")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateReviewComment {
+    pub repo_id: Uuid,
+    pub path: String,
+    pub line: i64,
+    #[serde(default)]
+    #[ts(optional)]
+    pub end_line: Option<i64>,
+    pub side: ReviewCommentSide,
+    pub body: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub suggestion: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub severity: Option<ReviewSeverity>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateReviewComment {
+    pub body: Option<String>,
+    pub resolved: Option<bool>,
+}
+
+/// Number of comments on one file of a workspace
+#[derive(Debug, Clone)]
+pub struct ReviewCommentFileCount {
+    pub repo_id: Uuid,
+    pub path: String,
+    pub total: i64,
+    pub unresolved: i64,
+}
+
+impl ReviewComment {
+    /// Comments of the workspace in file and line order
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ReviewComment,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      path,
+                      line,
+                      end_line,
+                      side as "side!: ReviewCommentSide",
+                      body,
+                      suggestion,
+                      severity as "severity: ReviewSeverity",
+                      ai_review_id as "ai_review_id: Uuid",
+                      resolved_at as "resolved_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM review_comments
+               WHERE workspace_id = $1
+               ORDER BY repo_id, path, line, created_at"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Findings an agent review recorded, in file and line order
+    pub async fn find_by_ai_review_id(
+        pool: &SqlitePool,
+        ai_review_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ReviewComment,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      path,
+                      line,
+                      end_line,
+                      side as "side!: ReviewCommentSide",
+                      body,
+                      suggestion,
+                      severity as "severity: ReviewSeverity",
+                      ai_review_id as "ai_review_id: Uuid",
+                      resolved_at as "resolved_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM review_comments
+               WHERE ai_review_id = $1
+               ORDER BY repo_id, path, line, created_at"#,
+            ai_review_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ReviewComment,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      path,
+                      line,
+                      end_line,
+                      side as "side!: ReviewCommentSide",
+                      body,
+                      suggestion,
+                      severity as "severity: ReviewSeverity",
+                      ai_review_id as "ai_review_id: Uuid",
+                      resolved_at as "resolved_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM review_comments
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        data: &CreateReviewComment,
+    ) -> Result<Self, sqlx::Error> {
+        Self::insert(pool, workspace_id, None, data).await
+    }
+
+    /// Record a finding of an agent review as a comment
+    pub async fn create_for_ai_review(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        ai_review_id: Uuid,
+        data: &CreateReviewComment,
+    ) -> Result<Self, sqlx::Error> {
+        Self::insert(pool, workspace_id, Some(ai_review_id), data).await
+    }
+
+    async fn insert(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        ai_review_id: Option<Uuid>,
+        data: &CreateReviewComment,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ReviewComment,
+            r#"INSERT INTO review_comments
+                   (id, workspace_id, repo_id, path, line, end_line, side, body, suggestion,
+                    severity, ai_review_id)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         path,
+                         line,
+                         end_line,
+                         side as "side!: ReviewCommentSide",
+                         body,
+                         suggestion,
+                         severity as "severity: ReviewSeverity",
+                         ai_review_id as "ai_review_id: Uuid",
+                         resolved_at as "resolved_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            data.repo_id,
+            data.path,
+            data.line,
+            data.end_line,
+            data.side,
+            data.body,
+            data.suggestion,
+            data.severity,
+            ai_review_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Update the body and resolution of a comment. Resolving an already resolved comment
+    /// keeps its original resolution time.
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateReviewComment,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let body = data.body.as_ref().unwrap_or(&existing.body);
+        let resolved = data.resolved.unwrap_or(existing.resolved_at.is_some());
+
+        sqlx::query_as!(
+            ReviewComment,
+            r#"UPDATE review_comments
+               SET body = $2,
+                   resolved_at = CASE WHEN $3 THEN COALESCE(resolved_at, datetime('now', 'subsec')) ELSE NULL END,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         path,
+                         line,
+                         end_line,
+                         side as "side!: ReviewCommentSide",
+                         body,
+                         suggestion,
+                         severity as "severity: ReviewSeverity",
+                         ai_review_id as "ai_review_id: Uuid",
+                         resolved_at as "resolved_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            body,
+            resolved
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM review_comments WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Number of comments and of unresolved comments per file of the workspace
+    pub async fn count_by_file(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<ReviewCommentFileCount>, sqlx::Error> {
+        sqlx::query_as!(
+            ReviewCommentFileCount,
+            r#"SELECT repo_id as "repo_id!: Uuid",
+                      path as "path!",
+                      COUNT(*) as "total!: i64",
+                      COUNT(*) - COUNT(resolved_at) as "unresolved!: i64"
+               FROM review_comments
+               WHERE workspace_id = $1
+               GROUP BY repo_id, path"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
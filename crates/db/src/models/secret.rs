@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+/// Secret exposed to execution processes as the environment variable `name`. `value` is
+/// encrypted and never sent to clients.
+#[derive(Debug, Clone, FromRow)]
+pub struct Secret {
+    pub id: Uuid,
+    pub name: String,
+    pub value: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Secret {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      value,
+                      description,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               ORDER BY name ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      value,
+                      description,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_name(pool: &SqlitePool, name: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      value,
+                      description,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               WHERE name = $1"#,
+            name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Secrets allowlisted for the project or for any repo of the workspace
+    pub async fn find_allowed_for_workspace(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      value,
+                      description,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               WHERE name IN (
+                   SELECT secret_name FROM secret_allowlist
+                   WHERE project_id = $1
+                      OR repo_id IN (SELECT repo_id FROM workspace_repos WHERE workspace_id = $2)
+               )
+               ORDER BY name ASC"#,
+            project_id,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        name: &str,
+        value: &str,
+        description: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            Secret,
+            r#"INSERT INTO secrets (id, name, value, description)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         name,
+                         value,
+                         description,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            value,
+            description
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        value: &str,
+        description: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"UPDATE secrets
+               SET value = $2, description = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         name,
+                         value,
+                         description,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            value,
+            description
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM secrets WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
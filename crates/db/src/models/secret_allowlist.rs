@@ -0,0 +1,86 @@
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Names of the secrets a project or repo may pass to its execution processes
+pub struct SecretAllowlist;
+
+impl SecretAllowlist {
+    pub async fn find_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT secret_name FROM secret_allowlist
+               WHERE project_id = $1
+               ORDER BY secret_name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_for_repo(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT secret_name FROM secret_allowlist
+               WHERE repo_id = $1
+               ORDER BY secret_name ASC"#,
+            repo_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Replace the project's allowlist with `names`
+    pub async fn set_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        names: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM secret_allowlist WHERE project_id = $1",
+            project_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for name in names {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                "INSERT INTO secret_allowlist (id, project_id, secret_name) VALUES ($1, $2, $3)",
+                id,
+                project_id,
+                name
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Replace the repo's allowlist with `names`
+    pub async fn set_for_repo(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        names: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!("DELETE FROM secret_allowlist WHERE repo_id = $1", repo_id)
+            .execute(&mut *tx)
+            .await?;
+        for name in names {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                "INSERT INTO secret_allowlist (id, repo_id, secret_name) VALUES ($1, $2, $3)",
+                id,
+                repo_id,
+                name
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+}
@@ -0,0 +1,46 @@
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+/// Raw server setting row; the typed view lives in the settings service
+#[derive(Debug, Clone)]
+pub struct Setting {
+    pub key: String,
+    pub value: Value,
+}
+
+impl Setting {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT key as "key!", value as "value!: sqlx::types::Json<Value>" FROM settings"#
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| Setting {
+                key: row.key,
+                value: row.value.0,
+            })
+            .collect())
+    }
+
+    /// Store all `settings` at once, replacing existing values
+    pub async fn upsert_all(pool: &SqlitePool, settings: &[Setting]) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for setting in settings {
+            let value = sqlx::types::Json(&setting.value);
+            sqlx::query!(
+                r#"INSERT INTO settings (key, value)
+                   VALUES ($1, $2)
+                   ON CONFLICT(key) DO UPDATE SET
+                       value = excluded.value,
+                       updated_at = datetime('now', 'subsec')"#,
+                setting.key,
+                value
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+}
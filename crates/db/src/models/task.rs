@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
+use serde_with::rust::double_option;
+use sqlx::{Executor, FromRow, QueryBuilder, Sqlite, SqlitePool, Type};
 use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 use uuid::Uuid;
@@ -34,6 +35,31 @@ pub enum TaskType {
     Ralph,
 }
 
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Type,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    TS,
+    EnumString,
+    Display,
+)]
+#[sqlx(type_name = "task_priority", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TaskPriority {
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Task {
     pub id: Uuid,
@@ -46,6 +72,10 @@ pub struct Task {
     pub ralph_current_story_index: Option<i64>,
     pub ralph_auto_continue: bool,
     pub ralph_max_iterations: i64,
+    pub priority: Option<TaskPriority>,
+    #[ts(type = "Array<string>")]
+    pub labels: sqlx::types::Json<Vec<String>>,
+    pub assignee: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -91,6 +121,15 @@ pub struct CreateTask {
     pub image_ids: Option<Vec<Uuid>>,
     pub ralph_auto_continue: Option<bool>,
     pub ralph_max_iterations: Option<i64>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub labels: Option<Vec<String>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub assignee: Option<String>,
 }
 
 impl CreateTask {
@@ -109,6 +148,9 @@ impl CreateTask {
             image_ids: None,
             ralph_auto_continue: None,
             ralph_max_iterations: None,
+            priority: None,
+            labels: None,
+            assignee: None,
         }
     }
 }
@@ -120,6 +162,136 @@ pub struct UpdateTask {
     pub status: Option<TaskStatus>,
     pub parent_workspace_id: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "TaskPriority | null")]
+    pub priority: Option<Option<TaskPriority>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub labels: Option<Vec<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub assignee: Option<Option<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum TaskSortField {
+    CreatedAt,
+    UpdatedAt,
+    Title,
+    Status,
+    Priority,
+}
+
+impl TaskSortField {
+    fn order_expr(self) -> &'static str {
+        match self {
+            TaskSortField::CreatedAt => "t.created_at",
+            TaskSortField::UpdatedAt => "t.updated_at",
+            TaskSortField::Title => "t.title COLLATE NOCASE",
+            TaskSortField::Status => {
+                "CASE t.status WHEN 'todo' THEN 0 WHEN 'inprogress' THEN 1 WHEN 'inreview' THEN 2 \
+                 WHEN 'done' THEN 3 ELSE 4 END"
+            }
+            TaskSortField::Priority => {
+                "CASE t.priority WHEN 'low' THEN 1 WHEN 'medium' THEN 2 WHEN 'high' THEN 3 \
+                 WHEN 'urgent' THEN 4 ELSE 0 END"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaskSort {
+    pub field: TaskSortField,
+    pub descending: bool,
+}
+
+/// Filters for [`Task::find_filtered_with_attempt_status`]; empty or unset fields match
+/// every task
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    pub statuses: Vec<TaskStatus>,
+    /// Tasks carrying any of these labels
+    pub labels: Vec<String>,
+    pub assignee: Option<String>,
+    /// Case-insensitive substring of the title or description
+    pub text: Option<String>,
+    /// Whether the task has a workspace that is not archived
+    pub has_open_workspace: Option<bool>,
+    pub updated_since: Option<DateTime<Utc>>,
+    /// Applied in order, before the default newest-first ordering
+    pub sort: Vec<TaskSort>,
+}
+
+impl TaskFilter {
+    /// Append the `WHERE` clause selecting `project_id`'s tasks that match this filter
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Sqlite>, project_id: Uuid) {
+        query.push(" WHERE t.project_id = ").push_bind(project_id);
+
+        if !self.statuses.is_empty() {
+            query.push(" AND t.status IN (");
+            let mut statuses = query.separated(", ");
+            for status in &self.statuses {
+                statuses.push_bind(status.clone());
+            }
+            query.push(")");
+        }
+        if !self.labels.is_empty() {
+            query.push(" AND EXISTS (SELECT 1 FROM json_each(t.labels) l WHERE l.value IN (");
+            let mut labels = query.separated(", ");
+            for label in &self.labels {
+                labels.push_bind(label.clone());
+            }
+            query.push("))");
+        }
+        if let Some(assignee) = &self.assignee {
+            query.push(" AND t.assignee = ").push_bind(assignee.clone());
+        }
+        if let Some(text) = &self.text {
+            let pattern = format!("%{}%", escape_like(text));
+            query
+                .push(" AND (t.title LIKE ")
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\' OR t.description LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\')");
+        }
+        if let Some(has_open_workspace) = self.has_open_workspace {
+            query.push(if has_open_workspace {
+                " AND EXISTS"
+            } else {
+                " AND NOT EXISTS"
+            });
+            query.push(" (SELECT 1 FROM workspaces w WHERE w.task_id = t.id AND w.archived = 0)");
+        }
+        if let Some(updated_since) = self.updated_since {
+            query
+                .push(" AND datetime(t.updated_at) >= datetime(")
+                .push_bind(updated_since)
+                .push(")");
+        }
+    }
+}
+
+/// Escape `%`, `_` and `\` so `text` matches literally in a `LIKE ... ESCAPE '\'`
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
 impl Task {
@@ -151,6 +323,9 @@ impl Task {
   t.ralph_current_story_index     AS "ralph_current_story_index: i64",
   t.ralph_auto_continue           AS "ralph_auto_continue!: bool",
   t.ralph_max_iterations          AS "ralph_max_iterations!: i64",
+  t.priority                      AS "priority: TaskPriority",
+  t.labels                        AS "labels!: sqlx::types::Json<Vec<String>>",
+  t.assignee,
   t.created_at                    AS "created_at!: DateTime<Utc>",
   t.updated_at                    AS "updated_at!: DateTime<Utc>",
 
@@ -207,6 +382,9 @@ ORDER BY t.created_at DESC"#,
                     ralph_current_story_index: rec.ralph_current_story_index,
                     ralph_auto_continue: rec.ralph_auto_continue,
                     ralph_max_iterations: rec.ralph_max_iterations,
+                    priority: rec.priority,
+                    labels: rec.labels,
+                    assignee: rec.assignee,
                     created_at: rec.created_at,
                     updated_at: rec.updated_at,
                 },
@@ -219,10 +397,109 @@ ORDER BY t.created_at DESC"#,
         Ok(tasks)
     }
 
+    /// Like [`Task::find_by_project_id_with_attempt_status`], with the filters and sort keys
+    /// of `filter` applied in SQL. `limit` (when set) and `offset` select a page of them.
+    pub async fn find_filtered_with_attempt_status(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        filter: &TaskFilter,
+        limit: Option<i64>,
+        offset: i64,
+    ) -> Result<Vec<TaskWithAttemptStatus>, sqlx::Error> {
+        #[derive(FromRow)]
+        struct TaskWithAttemptStatusRow {
+            #[sqlx(flatten)]
+            task: Task,
+            has_in_progress_attempt: bool,
+            last_attempt_failed: bool,
+            executor: Option<String>,
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"SELECT
+  t.id, t.project_id, t.title, t.description, t.status, t.task_type, t.parent_workspace_id,
+  t.ralph_current_story_index, t.ralph_auto_continue, t.ralph_max_iterations,
+  t.priority, t.labels, t.assignee, t.created_at, t.updated_at,
+
+  EXISTS (
+    SELECT 1
+      FROM workspaces w
+      JOIN sessions s ON s.workspace_id = w.id
+      JOIN execution_processes ep ON ep.session_id = s.id
+     WHERE w.task_id       = t.id
+       AND ep.status        = 'running'
+       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
+  ) AS has_in_progress_attempt,
+
+  COALESCE((
+    SELECT ep.status
+      FROM workspaces w
+      JOIN sessions s ON s.workspace_id = w.id
+      JOIN execution_processes ep ON ep.session_id = s.id
+     WHERE w.task_id       = t.id
+     AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
+     ORDER BY ep.created_at DESC
+     LIMIT 1
+  ) IN ('failed','killed'), 0) AS last_attempt_failed,
+
+  ( SELECT s.executor
+      FROM workspaces w
+      JOIN sessions s ON s.workspace_id = w.id
+      WHERE w.task_id = t.id
+     ORDER BY s.created_at DESC
+      LIMIT 1
+    ) AS executor
+
+FROM tasks t"#,
+        );
+        filter.push_conditions(&mut query, project_id);
+
+        query.push(" ORDER BY ");
+        for sort in &filter.sort {
+            query
+                .push(sort.field.order_expr())
+                .push(if sort.descending { " DESC, " } else { " ASC, " });
+        }
+        query.push("t.created_at DESC");
+        if let Some(limit) = limit {
+            query
+                .push(" LIMIT ")
+                .push_bind(limit)
+                .push(" OFFSET ")
+                .push_bind(offset);
+        }
+
+        let rows = query
+            .build_query_as::<TaskWithAttemptStatusRow>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TaskWithAttemptStatus {
+                task: row.task,
+                has_in_progress_attempt: row.has_in_progress_attempt,
+                last_attempt_failed: row.last_attempt_failed,
+                executor: row.executor.unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Number of `project_id`'s tasks matching `filter`
+    pub async fn count_filtered(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        filter: &TaskFilter,
+    ) -> Result<i64, sqlx::Error> {
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM tasks t");
+        filter.push_conditions(&mut query, project_id);
+        query.build_query_scalar::<i64>().fetch_one(pool).await
+    }
+
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", priority as "priority: TaskPriority", labels as "labels!: sqlx::types::Json<Vec<String>>", assignee, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE id = $1"#,
             id
@@ -234,7 +511,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", priority as "priority: TaskPriority", labels as "labels!: sqlx::types::Json<Vec<String>>", assignee, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE rowid = $1"#,
             rowid
@@ -252,11 +529,12 @@ ORDER BY t.created_at DESC"#,
         let task_type = data.task_type.clone().unwrap_or_default();
         let ralph_auto_continue = data.ralph_auto_continue.unwrap_or(false);
         let ralph_max_iterations = data.ralph_max_iterations.unwrap_or(10);
+        let labels = sqlx::types::Json(data.labels.clone().unwrap_or_default());
         sqlx::query_as!(
             Task,
-            r#"INSERT INTO tasks (id, project_id, title, description, status, task_type, parent_workspace_id, ralph_auto_continue, ralph_max_iterations)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, task_type, parent_workspace_id, ralph_auto_continue, ralph_max_iterations, priority, labels, assignee)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", priority as "priority: TaskPriority", labels as "labels!: sqlx::types::Json<Vec<String>>", assignee, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             task_id,
             data.project_id,
             data.title,
@@ -265,7 +543,10 @@ ORDER BY t.created_at DESC"#,
             task_type,
             data.parent_workspace_id,
             ralph_auto_continue,
-            ralph_max_iterations
+            ralph_max_iterations,
+            data.priority,
+            labels,
+            data.assignee
         )
         .fetch_one(pool)
         .await
@@ -283,9 +564,10 @@ ORDER BY t.created_at DESC"#,
         sqlx::query_as!(
             Task,
             r#"UPDATE tasks
-               SET title = $3, description = $4, status = $5, parent_workspace_id = $6
+               SET title = $3, description = $4, status = $5, parent_workspace_id = $6,
+                   updated_at = datetime('now', 'subsec')
                WHERE id = $1 AND project_id = $2
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", priority as "priority: TaskPriority", labels as "labels!: sqlx::types::Json<Vec<String>>", assignee, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             project_id,
             title,
@@ -297,6 +579,27 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
+    /// Update the board fields (priority, labels, assignee) for a task
+    pub async fn update_board_fields(
+        pool: &SqlitePool,
+        id: Uuid,
+        priority: Option<TaskPriority>,
+        labels: &[String],
+        assignee: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let labels = sqlx::types::Json(labels);
+        sqlx::query!(
+            "UPDATE tasks SET priority = $2, labels = $3, assignee = $4 WHERE id = $1",
+            id,
+            priority,
+            labels,
+            assignee
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn update_status(
         pool: &SqlitePool,
         id: Uuid,
@@ -328,6 +631,49 @@ ORDER BY t.created_at DESC"#,
         Ok(())
     }
 
+    /// Distinct labels used by the tasks of a project
+    pub async fn find_labels_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT DISTINCT l.value as "label!: String"
+               FROM tasks t, json_each(t.labels) l
+               WHERE t.project_id = $1
+               ORDER BY l.value ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Move a task to another project with the labels and parent workspace it keeps there
+    pub async fn move_to_project<'e, E>(
+        executor: E,
+        id: Uuid,
+        project_id: Uuid,
+        labels: &[String],
+        parent_workspace_id: Option<Uuid>,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let labels = sqlx::types::Json(labels);
+        sqlx::query_as!(
+            Task,
+            r#"UPDATE tasks
+               SET project_id = $2, labels = $3, parent_workspace_id = $4, updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", priority as "priority: TaskPriority", labels as "labels!: sqlx::types::Json<Vec<String>>", assignee, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            labels,
+            parent_workspace_id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
     /// Nullify parent_workspace_id for all tasks that reference the given workspace ID
     /// This breaks parent-child relationships before deleting a parent task
     pub async fn nullify_children_by_workspace_id<'e, E>(
@@ -363,7 +709,7 @@ ORDER BY t.created_at DESC"#,
         // Find only child tasks that have this workspace as their parent
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", task_type as "task_type!: TaskType", parent_workspace_id as "parent_workspace_id: Uuid", ralph_current_story_index as "ralph_current_story_index: i64", ralph_auto_continue as "ralph_auto_continue!: bool", ralph_max_iterations as "ralph_max_iterations!: i64", priority as "priority: TaskPriority", labels as "labels!: sqlx::types::Json<Vec<String>>", assignee, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE parent_workspace_id = $1
                ORDER BY created_at DESC"#,
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// One change of a task's status; rows are written by database triggers
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskStatusTransition {
+    pub task_id: Uuid,
+    /// Unset for the transition recorded when the task was created
+    pub from_status: Option<TaskStatus>,
+    pub to_status: TaskStatus,
+    #[ts(type = "Date")]
+    pub changed_at: DateTime<Utc>,
+}
+
+impl TaskStatusTransition {
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskStatusTransition,
+            r#"SELECT task_id as "task_id!: Uuid",
+                      from_status as "from_status: TaskStatus",
+                      to_status as "to_status!: TaskStatus",
+                      changed_at as "changed_at!: DateTime<Utc>"
+               FROM task_status_transitions
+               WHERE task_id = $1
+               ORDER BY changed_at ASC, id ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Transitions of all the project's tasks, oldest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskStatusTransition,
+            r#"SELECT tst.task_id as "task_id!: Uuid",
+                      tst.from_status as "from_status: TaskStatus",
+                      tst.to_status as "to_status!: TaskStatus",
+                      tst.changed_at as "changed_at!: DateTime<Utc>"
+               FROM task_status_transitions tst
+               JOIN tasks t ON tst.task_id = t.id
+               WHERE t.project_id = $1
+               ORDER BY tst.changed_at ASC, tst.id ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::rust::double_option;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskPriority;
+
+/// Blueprint for creating tasks with consistent titles and descriptions.
+/// `{{name}}` placeholders in the title pattern, description skeleton and checklist are
+/// filled from variables supplied when the task is created.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskTemplate {
+    pub id: Uuid,
+    /// Project the template belongs to; unset makes it available in every project
+    pub project_id: Option<Uuid>,
+    pub name: String,
+    pub title_pattern: String,
+    pub description_skeleton: Option<String>,
+    #[ts(type = "Array<string>")]
+    pub labels: sqlx::types::Json<Vec<String>>,
+    pub priority: Option<TaskPriority>,
+    #[ts(type = "Array<string>")]
+    pub checklist: sqlx::types::Json<Vec<String>>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskTemplate {
+    pub project_id: Option<Uuid>,
+    pub name: String,
+    pub title_pattern: String,
+    pub description_skeleton: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    pub checklist: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateTaskTemplate {
+    pub name: Option<String>,
+    pub title_pattern: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub description_skeleton: Option<Option<String>>,
+    pub labels: Option<Vec<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "TaskPriority | null")]
+    pub priority: Option<Option<TaskPriority>>,
+    pub checklist: Option<Vec<String>>,
+}
+
+/// Title and description produced by [`TaskTemplate::render`]
+#[derive(Debug, Clone)]
+pub struct RenderedTaskTemplate {
+    pub title: String,
+    pub description: Option<String>,
+}
+
+impl TaskTemplate {
+    /// Templates usable in `project_id`: its own plus the global ones. `None` lists all.
+    pub async fn find_all(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id: Uuid",
+                      name as "name!",
+                      title_pattern as "title_pattern!",
+                      description_skeleton,
+                      labels as "labels!: sqlx::types::Json<Vec<String>>",
+                      priority as "priority: TaskPriority",
+                      checklist as "checklist!: sqlx::types::Json<Vec<String>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_templates
+               WHERE $1 IS NULL OR project_id IS NULL OR project_id = $1
+               ORDER BY name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id: Uuid",
+                      name as "name!",
+                      title_pattern as "title_pattern!",
+                      description_skeleton,
+                      labels as "labels!: sqlx::types::Json<Vec<String>>",
+                      priority as "priority: TaskPriority",
+                      checklist as "checklist!: sqlx::types::Json<Vec<String>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_templates
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateTaskTemplate) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let labels = sqlx::types::Json(&data.labels);
+        let checklist = sqlx::types::Json(&data.checklist);
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"INSERT INTO task_templates (id, project_id, name, title_pattern, description_skeleton, labels, priority, checklist)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id: Uuid",
+                         name as "name!",
+                         title_pattern as "title_pattern!",
+                         description_skeleton,
+                         labels as "labels!: sqlx::types::Json<Vec<String>>",
+                         priority as "priority: TaskPriority",
+                         checklist as "checklist!: sqlx::types::Json<Vec<String>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.project_id,
+            data.name,
+            data.title_pattern,
+            data.description_skeleton,
+            labels,
+            data.priority,
+            checklist
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateTaskTemplate,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = data.name.as_ref().unwrap_or(&existing.name);
+        let title_pattern = data
+            .title_pattern
+            .as_ref()
+            .unwrap_or(&existing.title_pattern);
+        let description_skeleton = match &data.description_skeleton {
+            Some(skeleton) => skeleton.as_ref(),
+            None => existing.description_skeleton.as_ref(),
+        };
+        let labels = sqlx::types::Json(data.labels.as_ref().unwrap_or(&existing.labels.0));
+        let priority = match data.priority {
+            Some(priority) => priority,
+            None => existing.priority,
+        };
+        let checklist = sqlx::types::Json(data.checklist.as_ref().unwrap_or(&existing.checklist.0));
+
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"UPDATE task_templates
+               SET name = $2, title_pattern = $3, description_skeleton = $4, labels = $5,
+                   priority = $6, checklist = $7, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id: Uuid",
+                         name as "name!",
+                         title_pattern as "title_pattern!",
+                         description_skeleton,
+                         labels as "labels!: sqlx::types::Json<Vec<String>>",
+                         priority as "priority: TaskPriority",
+                         checklist as "checklist!: sqlx::types::Json<Vec<String>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            title_pattern,
+            description_skeleton,
+            labels,
+            priority,
+            checklist
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_templates WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Fill the template's placeholders from `variables`. The description is the skeleton
+    /// followed by the checklist as markdown. On failure returns the names of placeholders
+    /// that had no value.
+    pub fn render(
+        &self,
+        variables: &HashMap<String, String>,
+    ) -> Result<RenderedTaskTemplate, Vec<String>> {
+        let mut missing = Vec::new();
+        let title = fill_placeholders(&self.title_pattern, variables, &mut missing);
+
+        let mut sections = Vec::new();
+        if let Some(skeleton) = &self.description_skeleton {
+            let skeleton = fill_placeholders(skeleton, variables, &mut missing);
+            if !skeleton.trim().is_empty() {
+                sections.push(skeleton);
+            }
+        }
+
+        if !self.checklist.is_empty() {
+            let items: Vec<String> = self
+                .checklist
+                .iter()
+                .map(|item| format!("- [ ] {}", fill_placeholders(item, variables, &mut missing)))
+                .collect();
+            sections.push(format!("## Checklist\n{}", items.join("\n")));
+        }
+
+        if !missing.is_empty() {
+            return Err(missing);
+        }
+        Ok(RenderedTaskTemplate {
+            title,
+            description: (!sections.is_empty()).then(|| sections.join("\n\n")),
+        })
+    }
+}
+
+/// Replace `{{name}}` placeholders, recording names without a value in `missing`
+pub fn fill_placeholders(
+    text: &str,
+    variables: &HashMap<String, String>,
+    missing: &mut Vec<String>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = after[..end].trim();
+        match variables.get(name) {
+            Some(value) => out.push_str(value),
+            None if !missing.iter().any(|m| m == name) => missing.push(name.to_string()),
+            None => {}
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(checklist: &[&str]) -> TaskTemplate {
+        TaskTemplate {
+            id: Uuid::new_v4(),
+            project_id: None,
+            name: "Bug".to_string(),
+            title_pattern: "Fix {{ area }}: {{summary}}".to_string(),
+            description_skeleton: Some("Reported by {{reporter}}".to_string()),
+            labels: sqlx::types::Json(Vec::new()),
+            priority: None,
+            checklist: sqlx::types::Json(checklist.iter().map(|s| s.to_string()).collect()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn render_fills_placeholders_and_appends_the_checklist() {
+        let rendered = template(&["Reproduce in {{area}}", "Add a test"])
+            .render(&variables(&[
+                ("area", "login"),
+                ("summary", "crash on empty password"),
+                ("reporter", "QA"),
+            ]))
+            .unwrap();
+
+        assert_eq!(rendered.title, "Fix login: crash on empty password");
+        assert_eq!(
+            rendered.description.as_deref(),
+            Some("Reported by QA\n\n## Checklist\n- [ ] Reproduce in login\n- [ ] Add a test")
+        );
+    }
+
+    #[test]
+    fn render_reports_each_missing_placeholder_once() {
+        let missing = template(&["Check {{area}}"])
+            .render(&variables(&[("summary", "crash")]))
+            .unwrap_err();
+
+        assert_eq!(missing, vec!["area".to_string(), "reporter".to_string()]);
+    }
+
+    #[test]
+    fn unterminated_placeholders_are_kept_verbatim() {
+        let mut missing = Vec::new();
+        assert_eq!(
+            fill_placeholders("{{a}} and {{b", &variables(&[("a", "x")]), &mut missing),
+            "x and {{b"
+        );
+        assert!(missing.is_empty());
+    }
+}
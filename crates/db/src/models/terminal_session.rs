@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Audit record of an interactive terminal opened in a workspace worktree
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TerminalSession {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// Repo whose worktree the shell was started in, if one was chosen
+    pub repo_id: Option<Uuid>,
+    pub shell: String,
+    pub working_dir: String,
+    #[ts(type = "Date")]
+    pub started_at: DateTime<Utc>,
+    /// Unset while the terminal is still open
+    #[ts(type = "Date | null")]
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub struct CreateTerminalSession {
+    pub workspace_id: Uuid,
+    pub repo_id: Option<Uuid>,
+    pub shell: String,
+    pub working_dir: String,
+}
+
+impl TerminalSession {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateTerminalSession,
+        id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TerminalSession,
+            r#"INSERT INTO terminal_sessions (id, workspace_id, repo_id, shell, working_dir)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id: Uuid",
+                         shell as "shell!",
+                         working_dir as "working_dir!",
+                         started_at as "started_at!: DateTime<Utc>",
+                         ended_at as "ended_at: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.repo_id,
+            data.shell,
+            data.working_dir
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn mark_ended(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE terminal_sessions SET ended_at = datetime('now', 'subsec') WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Terminal sessions of a workspace, most recent first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TerminalSession,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id: Uuid",
+                      shell as "shell!",
+                      working_dir as "working_dir!",
+                      started_at as "started_at!: DateTime<Utc>",
+                      ended_at as "ended_at: DateTime<Utc>"
+               FROM terminal_sessions
+               WHERE workspace_id = $1
+               ORDER BY started_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
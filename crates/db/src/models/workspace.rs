@@ -1,8 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use thiserror::Error;
 use ts_rs::TS;
+use utils::path::path_key;
 use uuid::Uuid;
 
 /// Maximum length for auto-generated workspace names (derived from first user prompt)
@@ -97,6 +98,17 @@ pub struct WorkspaceContext {
     pub workspace_repos: Vec<RepoWithTargetBranch>,
 }
 
+/// How far a workspace got, for project statistics
+#[derive(Debug, Clone)]
+pub struct WorkspaceOutcome {
+    pub id: Uuid,
+    pub archived: bool,
+    pub created_at: DateTime<Utc>,
+    /// When the first of its merges landed, directly or through a merged PR
+    pub merged_at: Option<DateTime<Utc>>,
+    pub has_closed_pr: bool,
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateWorkspace {
     pub branch: String,
@@ -159,6 +171,50 @@ impl Workspace {
         Ok(workspaces)
     }
 
+    /// One page of [`Workspace::fetch_all`], newest first
+    pub async fn fetch_page(
+        pool: &SqlitePool,
+        task_id: Option<Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, WorkspaceError> {
+        sqlx::query_as!(
+            Workspace,
+            r#"SELECT id AS "id!: Uuid",
+                      task_id AS "task_id!: Uuid",
+                      container_ref,
+                      branch,
+                      agent_working_dir,
+                      setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                      created_at AS "created_at!: DateTime<Utc>",
+                      updated_at AS "updated_at!: DateTime<Utc>",
+                      archived AS "archived!: bool",
+                      pinned AS "pinned!: bool",
+                      name
+               FROM workspaces
+               WHERE $1 IS NULL OR task_id = $1
+               ORDER BY created_at DESC
+               LIMIT $2 OFFSET $3"#,
+            task_id,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(WorkspaceError::Database)
+    }
+
+    /// Count workspaces, optionally only those of `task_id`
+    pub async fn count(pool: &SqlitePool, task_id: Option<Uuid>) -> Result<i64, WorkspaceError> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM workspaces WHERE $1 IS NULL OR task_id = $1"#,
+            task_id
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(WorkspaceError::Database)
+    }
+
     /// Load workspace with full validation - ensures workspace belongs to task and task belongs to project
     pub async fn load_context(
         pool: &SqlitePool,
@@ -309,15 +365,42 @@ impl Workspace {
         .fetch_one(pool)
         .await?;
 
-        Ok(result.exists)
+        if result.exists {
+            return Ok(true);
+        }
+        Ok(Self::find_equivalent_container_ref(pool, container_ref)
+            .await?
+            .is_some())
+    }
+
+    /// Stored container_ref naming the same directory as `path` but spelled differently,
+    /// as happens on Windows with drive letter case, separators and verbatim prefixes
+    async fn find_equivalent_container_ref(
+        pool: &SqlitePool,
+        path: &str,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let key = path_key(path);
+        let container_refs = sqlx::query_scalar!(
+            r#"SELECT container_ref as "container_ref!" FROM workspaces WHERE container_ref IS NOT NULL"#
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(container_refs
+            .into_iter()
+            .find(|container_ref| path_key(container_ref) == key))
     }
 
     /// Find workspaces that are expired and eligible for cleanup.
-    /// Uses accelerated cleanup (1 hour) for archived workspaces OR tasks not in progress/review.
-    /// Uses standard cleanup (72 hours) only for non-archived workspaces on active tasks.
+    /// Archived workspaces and those whose task is not in progress/review expire after
+    /// `archived_retention_hours`; non-archived workspaces on active tasks after `retention_hours`.
+    /// A project's settings can override either period for its workspaces.
     pub async fn find_expired_for_cleanup(
         pool: &SqlitePool,
+        retention_hours: u32,
+        archived_retention_hours: u32,
     ) -> Result<Vec<Workspace>, sqlx::Error> {
+        let archived_retention_hours = i64::from(archived_retention_hours);
+        let retention_hours = i64::from(retention_hours);
         sqlx::query_as!(
             Workspace,
             r#"
@@ -335,6 +418,7 @@ impl Workspace {
                 w.name
             FROM workspaces w
             JOIN tasks t ON w.task_id = t.id
+            LEFT JOIN project_settings ps ON ps.project_id = t.project_id
             LEFT JOIN sessions s ON w.id = s.workspace_id
             LEFT JOIN execution_processes ep ON s.id = ep.session_id AND ep.completed_at IS NOT NULL
             WHERE w.container_ref IS NOT NULL
@@ -348,8 +432,8 @@ impl Workspace {
             HAVING datetime('now', 'localtime',
                 CASE
                     WHEN w.archived = 1 OR t.status NOT IN ('inprogress', 'inreview')
-                    THEN '-1 hours'
-                    ELSE '-72 hours'
+                    THEN '-' || COALESCE(ps.archived_workspace_retention_hours, $1) || ' hours'
+                    ELSE '-' || COALESCE(ps.workspace_retention_hours, $2) || ' hours'
                 END
             ) > datetime(
                 MAX(
@@ -365,7 +449,35 @@ impl Workspace {
                     ELSE w.updated_at
                 END
             ) ASC
-            "#
+            "#,
+            archived_retention_hours,
+            retention_hours
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_outcomes_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<WorkspaceOutcome>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceOutcome,
+            r#"SELECT
+                w.id as "id!: Uuid",
+                w.archived as "archived!: bool",
+                w.created_at as "created_at!: DateTime<Utc>",
+                MIN(CASE
+                    WHEN m.merge_type = 'direct' THEN m.created_at
+                    WHEN m.pr_status = 'merged' THEN COALESCE(m.pr_merged_at, m.created_at)
+                END) as "merged_at?: DateTime<Utc>",
+                COALESCE(MAX(m.pr_status = 'closed'), FALSE) as "has_closed_pr!: bool"
+            FROM workspaces w
+            JOIN tasks t ON w.task_id = t.id
+            LEFT JOIN merges m ON m.workspace_id = w.id
+            WHERE t.project_id = $1
+            GROUP BY w.id"#,
+            project_id
         )
         .fetch_all(pool)
         .await
@@ -413,6 +525,21 @@ impl Workspace {
         pool: &SqlitePool,
         container_ref: &str,
     ) -> Result<ContainerInfo, sqlx::Error> {
+        if let Some(info) = Self::find_container_info(pool, container_ref).await? {
+            return Ok(info);
+        }
+        match Self::find_equivalent_container_ref(pool, container_ref).await? {
+            Some(stored) => Self::find_container_info(pool, &stored)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound),
+            None => Err(sqlx::Error::RowNotFound),
+        }
+    }
+
+    async fn find_container_info(
+        pool: &SqlitePool,
+        container_ref: &str,
+    ) -> Result<Option<ContainerInfo>, sqlx::Error> {
         let result = sqlx::query!(
             r#"SELECT w.id as "workspace_id!: Uuid",
                       w.task_id as "task_id!: Uuid",
@@ -423,14 +550,13 @@ impl Workspace {
             container_ref
         )
         .fetch_optional(pool)
-        .await?
-        .ok_or(sqlx::Error::RowNotFound)?;
+        .await?;
 
-        Ok(ContainerInfo {
+        Ok(result.map(|result| ContainerInfo {
             workspace_id: result.workspace_id,
             task_id: result.task_id,
             project_id: result.project_id,
-        })
+        }))
     }
 
     /// Find workspace by path, also trying the parent directory.
@@ -624,9 +750,12 @@ impl Workspace {
     }
 
     /// Delete a workspace by ID
-    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+    pub async fn delete<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let result = sqlx::query!("DELETE FROM workspaces WHERE id = $1", id)
-            .execute(pool)
+            .execute(executor)
             .await?;
         Ok(result.rows_affected())
     }
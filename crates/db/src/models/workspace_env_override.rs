@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Environment variables set for one workspace, taking precedence over the project's env sets
+pub struct WorkspaceEnvOverride;
+
+impl WorkspaceEnvOverride {
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<HashMap<String, String>, sqlx::Error> {
+        let vars = sqlx::query_scalar!(
+            r#"SELECT vars as "vars!: sqlx::types::Json<HashMap<String, String>>"
+               FROM workspace_env_overrides
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(vars.map(|vars| vars.0).unwrap_or_default())
+    }
+
+    /// Replace the workspace's overrides; an empty map removes them
+    pub async fn set(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        vars: &HashMap<String, String>,
+    ) -> Result<(), sqlx::Error> {
+        if vars.is_empty() {
+            sqlx::query!(
+                "DELETE FROM workspace_env_overrides WHERE workspace_id = $1",
+                workspace_id
+            )
+            .execute(pool)
+            .await?;
+            return Ok(());
+        }
+
+        let vars = sqlx::types::Json(vars);
+        sqlx::query!(
+            r#"INSERT INTO workspace_env_overrides (workspace_id, vars)
+               VALUES ($1, $2)
+               ON CONFLICT(workspace_id) DO UPDATE SET
+                   vars = excluded.vars,
+                   updated_at = datetime('now', 'subsec')"#,
+            workspace_id,
+            vars
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
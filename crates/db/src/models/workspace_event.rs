@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "workspace_event_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceEventKind {
+    WorktreeRepaired,
+    ProcessInterrupted,
+    FilesDiscarded,
+    TargetBranchDrifted,
+    AutoSynced,
+    AutoSyncConflicted,
+    PatchApplied,
+    PatchFailed,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorkspaceEvent {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub kind: WorkspaceEventKind,
+    pub message: String,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl WorkspaceEvent {
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        kind: WorkspaceEventKind,
+        message: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WorkspaceEvent,
+            r#"INSERT INTO workspace_events (id, workspace_id, kind, message)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         kind as "kind!: WorkspaceEventKind",
+                         message,
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            kind,
+            message
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Events of the workspace, newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceEvent,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      kind as "kind!: WorkspaceEventKind",
+                      message,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_events
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
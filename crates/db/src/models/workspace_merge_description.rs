@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Title and body used for a workspace's merge commit and as the default pull request
+/// description
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorkspaceMergeDescription {
+    pub workspace_id: Uuid,
+    pub title: String,
+    pub body: String,
+    /// Whether the description was edited since it was generated
+    pub edited: bool,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetWorkspaceMergeDescription {
+    pub title: String,
+    pub body: String,
+}
+
+impl WorkspaceMergeDescription {
+    /// The description as a commit message, body after a blank line
+    pub fn commit_message(&self) -> String {
+        if self.body.trim().is_empty() {
+            self.title.clone()
+        } else {
+            format!("{}\n\n{}", self.title, self.body.trim_end())
+        }
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceMergeDescription,
+            r#"SELECT workspace_id as "workspace_id!: Uuid",
+                      title,
+                      body,
+                      edited as "edited!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM workspace_merge_descriptions
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Store the workspace's description, replacing any previous one. `edited` marks a
+    /// description written or changed by a person rather than generated.
+    pub async fn set(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        data: &SetWorkspaceMergeDescription,
+        edited: bool,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceMergeDescription,
+            r#"INSERT INTO workspace_merge_descriptions (workspace_id, title, body, edited)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(workspace_id) DO UPDATE SET
+                   title = excluded.title,
+                   body = excluded.body,
+                   edited = excluded.edited,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING workspace_id as "workspace_id!: Uuid",
+                         title,
+                         body,
+                         edited as "edited!: bool",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            workspace_id,
+            data.title,
+            data.body,
+            edited
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, workspace_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM workspace_merge_descriptions WHERE workspace_id = $1",
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
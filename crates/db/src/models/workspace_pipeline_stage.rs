@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::project_agent_pipeline::AgentPipelineStage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "pipeline_stage_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStageStatus {
+    /// Waiting for the stages before it
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    /// Not run because a stage before it failed
+    Skipped,
+}
+
+/// A stage of a workspace's agent pipeline run
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorkspacePipelineStage {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// Zero-based place of the stage in the pipeline
+    pub position: i64,
+    pub name: String,
+    #[ts(type = "ExecutorProfileId")]
+    pub executor_profile_id: Json<ExecutorProfileId>,
+    pub prompt_template: String,
+    pub status: PipelineStageStatus,
+    /// The coding agent process of the stage, once it started
+    pub execution_process_id: Option<Uuid>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WorkspacePipelineStage {
+    /// Replace the workspace's pipeline run with pending `stages`
+    pub async fn create_run(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        stages: &[AgentPipelineStage],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM workspace_pipeline_stages WHERE workspace_id = $1",
+            workspace_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let mut created = Vec::with_capacity(stages.len());
+        for (position, stage) in stages.iter().enumerate() {
+            let id = Uuid::new_v4();
+            let position = position as i64;
+            let executor_profile_id = Json(&stage.executor_profile_id);
+            created.push(
+                sqlx::query_as!(
+                    WorkspacePipelineStage,
+                    r#"INSERT INTO workspace_pipeline_stages
+                           (id, workspace_id, position, name, executor_profile_id, prompt_template)
+                       VALUES ($1, $2, $3, $4, $5, $6)
+                       RETURNING id as "id!: Uuid",
+                                 workspace_id as "workspace_id!: Uuid",
+                                 position as "position!: i64",
+                                 name,
+                                 executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                                 prompt_template,
+                                 status as "status!: PipelineStageStatus",
+                                 execution_process_id as "execution_process_id?: Uuid",
+                                 created_at as "created_at!: DateTime<Utc>",
+                                 updated_at as "updated_at!: DateTime<Utc>""#,
+                    id,
+                    workspace_id,
+                    position,
+                    stage.name,
+                    executor_profile_id,
+                    stage.prompt_template
+                )
+                .fetch_one(&mut *tx)
+                .await?,
+            );
+        }
+
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    /// Stages of the workspace's pipeline run, in order
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspacePipelineStage,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      position as "position!: i64",
+                      name,
+                      executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                      prompt_template,
+                      status as "status!: PipelineStageStatus",
+                      execution_process_id as "execution_process_id?: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM workspace_pipeline_stages
+               WHERE workspace_id = $1
+               ORDER BY position ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspacePipelineStage,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      position as "position!: i64",
+                      name,
+                      executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                      prompt_template,
+                      status as "status!: PipelineStageStatus",
+                      execution_process_id as "execution_process_id?: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM workspace_pipeline_stages
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Mark the stage running in `execution_process_id`
+    pub async fn start(
+        pool: &SqlitePool,
+        id: Uuid,
+        execution_process_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspacePipelineStage,
+            r#"UPDATE workspace_pipeline_stages
+               SET status = 'running', execution_process_id = $2,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         position as "position!: i64",
+                         name,
+                         executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                         prompt_template,
+                         status as "status!: PipelineStageStatus",
+                         execution_process_id as "execution_process_id?: Uuid",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            execution_process_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: PipelineStageStatus,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspacePipelineStage,
+            r#"UPDATE workspace_pipeline_stages
+               SET status = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         position as "position!: i64",
+                         name,
+                         executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                         prompt_template,
+                         status as "status!: PipelineStageStatus",
+                         execution_process_id as "execution_process_id?: Uuid",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            status
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Skip the pending stages of the workspace's pipeline run
+    pub async fn skip_pending(pool: &SqlitePool, workspace_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE workspace_pipeline_stages
+               SET status = 'skipped', updated_at = datetime('now', 'subsec')
+               WHERE workspace_id = $1 AND status = 'pending'"#,
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -15,6 +15,8 @@ pub struct WorkspaceRepo {
     pub repo_id: Uuid,
     pub target_branch: String,
     pub start_from_ref: Option<String>,
+    /// Remote this workspace pushes to, overriding the repo's `push_remote`
+    pub push_remote: Option<String>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
@@ -34,6 +36,8 @@ pub struct RepoWithTargetBranch {
     #[serde(flatten)]
     pub repo: Repo,
     pub target_branch: String,
+    /// Directory of the repo the workspace's project is limited to
+    pub path_scope: Option<String>,
 }
 
 /// Repo info with copy_files configuration.
@@ -46,6 +50,11 @@ pub struct RepoWithCopyFiles {
 }
 
 impl WorkspaceRepo {
+    /// Remote to push to: the workspace override, then the repo setting; `None` uses the git default
+    pub fn effective_push_remote<'a>(&'a self, repo: &'a Repo) -> Option<&'a str> {
+        self.push_remote.as_deref().or(repo.push_remote.as_deref())
+    }
+
     pub async fn create_many(
         pool: &SqlitePool,
         workspace_id: Uuid,
@@ -72,6 +81,7 @@ impl WorkspaceRepo {
                              repo_id as "repo_id!: Uuid",
                              target_branch,
                              start_from_ref,
+                             push_remote,
                              created_at as "created_at!: DateTime<Utc>",
                              updated_at as "updated_at!: DateTime<Utc>""#,
                 id,
@@ -100,6 +110,7 @@ impl WorkspaceRepo {
                       repo_id as "repo_id!: Uuid",
                       target_branch,
                       start_from_ref,
+                      push_remote,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM workspace_repos
@@ -125,7 +136,11 @@ impl WorkspaceRepo {
                       r.copy_files,
                       r.parallel_setup_script as "parallel_setup_script!: bool",
                       r.dev_server_script,
+                      r.test_script,
                       r.default_target_branch,
+                      r.require_ci_pass as "require_ci_pass!: bool",
+                      r.push_remote,
+                      r.auto_fetch as "auto_fetch!: bool",
                       r.created_at as "created_at!: DateTime<Utc>",
                       r.updated_at as "updated_at!: DateTime<Utc>"
                FROM repos r
@@ -152,12 +167,20 @@ impl WorkspaceRepo {
                       r.copy_files,
                       r.parallel_setup_script as "parallel_setup_script!: bool",
                       r.dev_server_script,
+                      r.test_script,
                       r.default_target_branch,
+                      r.require_ci_pass as "require_ci_pass!: bool",
+                      r.push_remote,
+                      r.auto_fetch as "auto_fetch!: bool",
                       r.created_at as "created_at!: DateTime<Utc>",
                       r.updated_at as "updated_at!: DateTime<Utc>",
-                      wr.target_branch
+                      wr.target_branch,
+                      pr.path_scope as "path_scope?: String"
                FROM repos r
                JOIN workspace_repos wr ON r.id = wr.repo_id
+               JOIN workspaces w ON w.id = wr.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               LEFT JOIN project_repos pr ON pr.project_id = t.project_id AND pr.repo_id = r.id
                WHERE wr.workspace_id = $1
                ORDER BY r.display_name ASC"#,
             workspace_id
@@ -178,11 +201,16 @@ impl WorkspaceRepo {
                     copy_files: row.copy_files,
                     parallel_setup_script: row.parallel_setup_script,
                     dev_server_script: row.dev_server_script,
+                    test_script: row.test_script,
                     default_target_branch: row.default_target_branch,
+                    require_ci_pass: row.require_ci_pass,
+                    push_remote: row.push_remote,
+                    auto_fetch: row.auto_fetch,
                     created_at: row.created_at,
                     updated_at: row.updated_at,
                 },
                 target_branch: row.target_branch,
+                path_scope: row.path_scope,
             })
             .collect())
     }
@@ -199,6 +227,7 @@ impl WorkspaceRepo {
                       repo_id as "repo_id!: Uuid",
                       target_branch,
                       start_from_ref,
+                      push_remote,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM workspace_repos
@@ -227,6 +256,61 @@ impl WorkspaceRepo {
         Ok(())
     }
 
+    pub async fn delete(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM workspace_repos WHERE workspace_id = $1 AND repo_id = $2",
+            workspace_id,
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Number of the project's workspaces that include each repo
+    pub async fn count_workspaces_by_repo(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<Uuid, i64>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT wr.repo_id as "repo_id!: Uuid",
+                      COUNT(DISTINCT wr.workspace_id) as "count!: i64"
+               FROM workspace_repos wr
+               JOIN workspaces w ON wr.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE t.project_id = $1
+               GROUP BY wr.repo_id"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.repo_id, row.count))
+            .collect())
+    }
+
+    pub async fn update_push_remote(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        push_remote: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspace_repos SET push_remote = $1, updated_at = datetime('now') WHERE workspace_id = $2 AND repo_id = $3",
+            push_remote,
+            workspace_id,
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn update_target_branch_for_children_of_workspace(
         pool: &SqlitePool,
         parent_workspace_id: Uuid,
@@ -251,6 +335,32 @@ impl WorkspaceRepo {
         Ok(result.rows_affected())
     }
 
+    /// Display names of the repos each task in `project_id` has workspaces in, keyed by task
+    pub async fn find_repo_names_by_task(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<Uuid, Vec<String>>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT DISTINCT w.task_id AS "task_id!: Uuid",
+                      r.display_name AS "display_name!"
+               FROM workspaces w
+               JOIN workspace_repos wr ON wr.workspace_id = w.id
+               JOIN repos r ON r.id = wr.repo_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE t.project_id = $1
+               ORDER BY r.display_name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut repos: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for row in rows {
+            repos.entry(row.task_id).or_default().push(row.display_name);
+        }
+        Ok(repos)
+    }
+
     pub async fn find_unique_repos_for_task(
         pool: &SqlitePool,
         task_id: Uuid,
@@ -266,7 +376,11 @@ impl WorkspaceRepo {
                       r.copy_files,
                       r.parallel_setup_script as "parallel_setup_script!: bool",
                       r.dev_server_script,
+                      r.test_script,
                       r.default_target_branch,
+                      r.require_ci_pass as "require_ci_pass!: bool",
+                      r.push_remote,
+                      r.auto_fetch as "auto_fetch!: bool",
                       r.created_at as "created_at!: DateTime<Utc>",
                       r.updated_at as "updated_at!: DateTime<Utc>"
                FROM repos r
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "review_decision", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+}
+
+/// A reviewer's decision on a workspace's changes. The latest review of a workspace is its
+/// review state.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorkspaceReview {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// Who made the decision, as given by the client
+    pub reviewer: String,
+    pub decision: ReviewDecision,
+    pub comment: Option<String>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateWorkspaceReview {
+    pub reviewer: String,
+    pub decision: ReviewDecision,
+    pub comment: Option<String>,
+}
+
+impl WorkspaceReview {
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        data: &CreateWorkspaceReview,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WorkspaceReview,
+            r#"INSERT INTO workspace_reviews (id, workspace_id, reviewer, decision, comment)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         reviewer,
+                         decision as "decision!: ReviewDecision",
+                         comment,
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            data.reviewer,
+            data.decision,
+            data.comment
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Reviews of the workspace, newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceReview,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      reviewer,
+                      decision as "decision!: ReviewDecision",
+                      comment,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_reviews
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_latest_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceReview,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      reviewer,
+                      decision as "decision!: ReviewDecision",
+                      comment,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_reviews
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
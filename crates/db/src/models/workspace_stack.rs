@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::workspace::Workspace;
+
+/// Parent/child links between stacked workspaces. A stacked workspace starts from its
+/// parent's branch and targets it, so it must be retargeted when the parent closes.
+pub struct WorkspaceStack;
+
+impl WorkspaceStack {
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        parent_workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO workspace_stacks (workspace_id, parent_workspace_id) VALUES ($1, $2)",
+            workspace_id,
+            parent_workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_parent_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT parent_workspace_id as "parent_workspace_id!: Uuid"
+               FROM workspace_stacks
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Workspaces stacked directly on `parent_workspace_id` that are not archived
+    pub async fn find_active_children(
+        pool: &SqlitePool,
+        parent_workspace_id: Uuid,
+    ) -> Result<Vec<Workspace>, sqlx::Error> {
+        sqlx::query_as!(
+            Workspace,
+            r#"SELECT  w.id                AS "id!: Uuid",
+                       w.task_id           AS "task_id!: Uuid",
+                       w.container_ref,
+                       w.branch,
+                       w.agent_working_dir,
+                       w.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       w.created_at        AS "created_at!: DateTime<Utc>",
+                       w.updated_at        AS "updated_at!: DateTime<Utc>",
+                       w.archived          AS "archived!: bool",
+                       w.pinned            AS "pinned!: bool",
+                       w.name
+               FROM    workspace_stacks s
+               JOIN    workspaces w ON w.id = s.workspace_id
+               WHERE   s.parent_workspace_id = $1 AND w.archived = 0
+               ORDER BY w.created_at ASC"#,
+            parent_workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Point every child targeting `old_branch` in `repo_id` at `new_branch` instead
+    pub async fn retarget_children(
+        pool: &SqlitePool,
+        parent_workspace_id: Uuid,
+        repo_id: Uuid,
+        old_branch: &str,
+        new_branch: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE workspace_repos
+               SET target_branch = $1, updated_at = datetime('now')
+               WHERE repo_id = $2
+                 AND target_branch = $3
+                 AND workspace_id IN (
+                     SELECT workspace_id FROM workspace_stacks WHERE parent_workspace_id = $4
+                 )"#,
+            new_branch,
+            repo_id,
+            old_branch,
+            parent_workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Move the children of `parent_workspace_id` onto `new_parent_id`, or unstack them
+    /// when it is `None`
+    pub async fn reparent_children(
+        pool: &SqlitePool,
+        parent_workspace_id: Uuid,
+        new_parent_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        match new_parent_id {
+            Some(new_parent_id) => {
+                sqlx::query!(
+                    "UPDATE workspace_stacks SET parent_workspace_id = $1 WHERE parent_workspace_id = $2",
+                    new_parent_id,
+                    parent_workspace_id
+                )
+                .execute(pool)
+                .await?;
+            }
+            None => {
+                sqlx::query!(
+                    "DELETE FROM workspace_stacks WHERE parent_workspace_id = $1",
+                    parent_workspace_id
+                )
+                .execute(pool)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
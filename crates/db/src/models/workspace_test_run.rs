@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TestFramework {
+    Cargo,
+    Jest,
+    Pytest,
+    Unknown,
+}
+
+/// Parsed outcome of running one repo's test script
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RepoTestResult {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub command: String,
+    /// Execution process holding the raw test output
+    pub execution_process_id: Uuid,
+    pub exit_code: Option<i64>,
+    pub framework: TestFramework,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Names of the failing test cases, as reported by the framework
+    pub failed_cases: Vec<String>,
+}
+
+impl RepoTestResult {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0) && self.failed == 0
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceTestRun {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// True when every repo's tests passed
+    pub passed: bool,
+    #[ts(type = "Array<RepoTestResult>")]
+    pub results: sqlx::types::Json<Vec<RepoTestResult>>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl WorkspaceTestRun {
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        results: &[RepoTestResult],
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let passed = results.iter().all(RepoTestResult::succeeded);
+        let results_json = sqlx::types::Json(results);
+        sqlx::query_as!(
+            WorkspaceTestRun,
+            r#"INSERT INTO workspace_test_runs (id, workspace_id, passed, results)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         passed as "passed!: bool",
+                         results as "results!: sqlx::types::Json<Vec<RepoTestResult>>",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            passed,
+            results_json
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_latest_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceTestRun,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      passed as "passed!: bool",
+                      results as "results!: sqlx::types::Json<Vec<RepoTestResult>>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_test_runs
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
@@ -31,6 +31,7 @@ use services::services::{
     project::ProjectService,
     queued_message::QueuedMessageService,
     repo::RepoService,
+    settings::{SettingsError, SettingsService},
     worktree_manager::WorktreeError,
 };
 use sqlx::Error as SqlxError;
@@ -70,6 +71,8 @@ pub enum DeploymentError {
     Event(#[from] EventError),
     #[error(transparent)]
     Config(#[from] ConfigError),
+    #[error(transparent)]
+    Settings(#[from] SettingsError),
     #[error("Remote client not configured")]
     RemoteClientNotConfigured,
     #[error(transparent)]
@@ -110,6 +113,8 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn auth_context(&self) -> &AuthContext;
 
+    fn settings(&self) -> &SettingsService;
+
     async fn update_sentry_scope(&self) -> Result<(), DeploymentError> {
         let user_id = self.user_id();
         let config = self.config().read().await;
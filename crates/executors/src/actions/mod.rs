@@ -14,6 +14,7 @@ use crate::{
     approvals::ExecutorApprovalService,
     env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorError, SpawnedChild},
+    profile::ExecutorConfigs,
 };
 pub mod coding_agent_follow_up;
 pub mod coding_agent_initial;
@@ -69,6 +70,35 @@ impl ExecutorAction {
             ExecutorActionType::ScriptRequest(_) => None,
         }
     }
+
+    /// Directory, relative to the container, the action runs in
+    pub fn working_dir(&self) -> Option<&str> {
+        match self.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                request.working_dir.as_deref()
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                request.working_dir.as_deref()
+            }
+            ExecutorActionType::ReviewRequest(request) => request.working_dir.as_deref(),
+            ExecutorActionType::ScriptRequest(request) => request.working_dir.as_deref(),
+        }
+    }
+
+    /// Glob patterns of the files kept after the action runs: the script's own, or those
+    /// configured on the agent's profile
+    pub fn artifact_patterns(&self) -> Vec<String> {
+        let executor_profile_id = match self.typ() {
+            ExecutorActionType::ScriptRequest(request) => return request.artifacts.clone(),
+            ExecutorActionType::CodingAgentInitialRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::ReviewRequest(request) => &request.executor_profile_id,
+        };
+        ExecutorConfigs::get_cached()
+            .get_coding_agent(executor_profile_id)
+            .map(|agent| agent.artifact_patterns())
+            .unwrap_or_default()
+    }
 }
 
 #[async_trait]
@@ -1,7 +1,6 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc};
 
 use async_trait::async_trait;
-use command_group::AsyncCommandGroup;
 use serde::{Deserialize, Serialize};
 use tokio::process::Command;
 use ts_rs::TS;
@@ -25,6 +24,10 @@ pub enum ScriptContext {
     CleanupScript,
     DevServer,
     ToolInstallScript,
+    QualityGate,
+    TestRun,
+    AdHocCommand,
+    ProjectScript,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -36,6 +39,12 @@ pub struct ScriptRequest {
     /// If None, uses the container_ref directory directly.
     #[serde(default)]
     pub working_dir: Option<String>,
+    /// Extra environment variables for the script, applied over the execution environment
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Glob patterns, relative to the working directory, of files kept after the run
+    #[serde(default)]
+    pub artifacts: Vec<String>,
 }
 
 #[async_trait]
@@ -52,7 +61,11 @@ impl Executable for ScriptRequest {
             None => current_dir.to_path_buf(),
         };
 
-        let (shell_cmd, shell_arg) = get_shell_command();
+        // The local shell may not exist on a remote host
+        let (shell_cmd, shell_arg) = match env.remote {
+            Some(_) => ("sh".to_string(), "-c"),
+            None => get_shell_command(),
+        };
         let mut command = Command::new(shell_cmd);
         command
             .kill_on_drop(true)
@@ -65,8 +78,11 @@ impl Executable for ScriptRequest {
 
         // Apply environment variables
         env.apply_to_command(&mut command);
+        command.envs(&self.env);
 
-        let child = command.group_spawn()?;
+        let mut child = env.spawn(&mut command).await?;
+        // Remote processes get a piped stdin; close it as scripts read none
+        drop(child.inner().stdin.take());
 
         Ok(child.into())
     }
@@ -1,9 +1,16 @@
 use std::{collections::HashMap, path::PathBuf};
 
+use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use git::GitService;
-use tokio::process::Command;
+use tokio::{io::AsyncWriteExt, process::Command};
 
-use crate::command::CmdOverrides;
+use crate::{
+    command::{CmdOverrides, CommandBuildError},
+    executors::ExecutorError,
+    policy::AgentPolicy,
+    remote::RemoteHost,
+    sandbox::Sandbox,
+};
 
 /// Repository context for executor operations
 #[derive(Debug, Clone, Default)]
@@ -74,12 +81,32 @@ impl RepoContext {
     }
 }
 
+/// Prefix of the variables the server sets for every process
+pub const RESERVED_ENV_PREFIX: &str = "VK_";
+
+/// Whether `name` is a valid environment variable name that users may set, i.e. one that
+/// does not shadow the server's `VK_*` variables
+pub fn is_user_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.to_ascii_uppercase().starts_with(RESERVED_ENV_PREFIX)
+}
+
 /// Environment variables to inject into executor processes
 #[derive(Debug, Clone)]
 pub struct ExecutionEnv {
     pub vars: HashMap<String, String>,
     pub repo_context: RepoContext,
     pub commit_reminder: bool,
+    /// Host the processes run on instead of this machine
+    pub remote: Option<RemoteHost>,
+    /// Limits on what a coding agent may do, enforced by `sandbox`
+    pub policy: Option<AgentPolicy>,
+    /// Sandbox processes run in when there is a policy
+    pub sandbox: Option<Sandbox>,
 }
 
 impl ExecutionEnv {
@@ -88,6 +115,9 @@ impl ExecutionEnv {
             vars: HashMap::new(),
             repo_context,
             commit_reminder,
+            remote: None,
+            policy: None,
+            sandbox: None,
         }
     }
 
@@ -124,6 +154,44 @@ impl ExecutionEnv {
         }
     }
 
+    /// Spawn a command configured for this env, inside its sandbox when it has a policy and
+    /// over SSH when it has a remote host
+    pub async fn spawn(&self, command: &mut Command) -> Result<AsyncGroupChild, ExecutorError> {
+        let mut sandboxed;
+        let command = match (&self.policy, self.sandbox) {
+            (Some(policy), Some(sandbox)) => {
+                sandboxed = sandbox.wrap(command, policy, &self.writable_dirs());
+                &mut sandboxed
+            }
+            (Some(_), None) => return Err(ExecutorError::SandboxRequired),
+            (None, _) => command,
+        };
+        match &self.remote {
+            Some(remote) => {
+                let (mut remote_command, env_script) = remote
+                    .command(command, &self.repo_context.workspace_root)
+                    .map_err(CommandBuildError::from)?;
+                let mut child = remote_command.group_spawn()?;
+                if let Some(stdin) = child.inner().stdin.as_mut() {
+                    stdin
+                        .write_all(env_script.as_bytes())
+                        .await
+                        .map_err(ExecutorError::Io)?;
+                }
+                Ok(child)
+            }
+            None => Ok(command.group_spawn()?),
+        }
+    }
+
+    /// Directories a process may write to when its policy restricts writes to the worktree
+    fn writable_dirs(&self) -> Vec<PathBuf> {
+        vec![
+            self.repo_context.workspace_root.clone(),
+            std::env::temp_dir(),
+        ]
+    }
+
     pub fn contains_key(&self, key: &str) -> bool {
         self.vars.contains_key(key)
     }
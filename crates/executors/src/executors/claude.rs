@@ -12,7 +12,6 @@ use std::{
 };
 
 use async_trait::async_trait;
-use command_group::AsyncCommandGroup;
 use futures::StreamExt;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -24,7 +23,10 @@ use workspace_utils::{
 };
 
 use self::{
-    client::{AUTO_APPROVE_CALLBACK_ID, ClaudeAgentClient, STOP_GIT_CHECK_CALLBACK_ID},
+    client::{
+        AUTO_APPROVE_CALLBACK_ID, ClaudeAgentClient, POLICY_CHECK_CALLBACK_ID,
+        STOP_GIT_CHECK_CALLBACK_ID,
+    },
     protocol::ProtocolPeer,
     types::{ControlRequestType, ControlResponseType, PermissionMode},
 };
@@ -38,7 +40,7 @@ use crate::{
     },
     logs::{
         ActionType, FileChange, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
-        TodoItem, ToolStatus,
+        TodoItem, ToolStatus, TurnUsage,
         stderr_processor::normalize_stderr_logs,
         utils::{
             EntryIndexProvider,
@@ -137,7 +139,7 @@ impl ClaudeCode {
         }
     }
 
-    pub fn get_hooks(&self, commit_reminder: bool) -> Option<serde_json::Value> {
+    pub fn get_hooks(&self, commit_reminder: bool, policy: bool) -> Option<serde_json::Value> {
         let mut hooks = serde_json::Map::new();
 
         if commit_reminder {
@@ -149,30 +151,37 @@ impl ClaudeCode {
             );
         }
 
+        // Check every tool use against the agent policy; a denial wins over other hooks
+        let mut pre_tool_use = Vec::new();
+        if policy {
+            pre_tool_use.push(serde_json::json!({
+                "matcher": ".*",
+                "hookCallbackIds": [POLICY_CHECK_CALLBACK_ID],
+            }));
+        }
+
         // Add PreToolUse hooks based on plan/approvals settings
         if self.plan.unwrap_or(false) {
-            hooks.insert(
-                "PreToolUse".to_string(),
-                serde_json::json!([
-                    {
-                        "matcher": "^ExitPlanMode$",
-                        "hookCallbackIds": ["tool_approval"],
-                    },
-                    {
-                        "matcher": "^(?!ExitPlanMode$).*",
-                        "hookCallbackIds": [AUTO_APPROVE_CALLBACK_ID],
-                    }
-                ]),
-            );
+            pre_tool_use.extend([
+                serde_json::json!({
+                    "matcher": "^ExitPlanMode$",
+                    "hookCallbackIds": ["tool_approval"],
+                }),
+                serde_json::json!({
+                    "matcher": "^(?!ExitPlanMode$).*",
+                    "hookCallbackIds": [AUTO_APPROVE_CALLBACK_ID],
+                }),
+            ]);
         } else if self.approvals.unwrap_or(false) {
+            pre_tool_use.push(serde_json::json!({
+                "matcher": "^(?!(Glob|Grep|NotebookRead|Read|Task|TodoWrite)$).*",
+                "hookCallbackIds": ["tool_approval"],
+            }));
+        }
+        if !pre_tool_use.is_empty() {
             hooks.insert(
                 "PreToolUse".to_string(),
-                serde_json::json!([
-                    {
-                        "matcher": "^(?!(Glob|Grep|NotebookRead|Read|Task|TodoWrite)$).*",
-                        "hookCallbackIds": ["tool_approval"],
-                    }
-                ]),
+                serde_json::Value::Array(pre_tool_use),
             );
         }
 
@@ -312,7 +321,7 @@ impl ClaudeCode {
             tracing::info!("ANTHROPIC_API_KEY removed from environment");
         }
 
-        let mut child = command.group_spawn()?;
+        let mut child = env.spawn(&mut command).await?;
         let child_stdout = child.inner().stdout.take().ok_or_else(|| {
             ExecutorError::Io(std::io::Error::other("Claude Code missing stdout"))
         })?;
@@ -323,15 +332,17 @@ impl ClaudeCode {
 
         let new_stdout = create_stdout_pipe_writer(&mut child)?;
         let permission_mode = self.permission_mode();
-        let hooks = self.get_hooks(env.commit_reminder);
+        let hooks = self.get_hooks(env.commit_reminder, env.policy.is_some());
 
         // Create cancellation token for graceful shutdown
         let cancel = tokio_util::sync::CancellationToken::new();
+        let (input_tx, input_rx) = tokio::sync::mpsc::unbounded_channel();
 
         // Spawn task to handle the SDK client with control protocol
         let prompt_clone = combined_prompt.clone();
         let approvals_clone = self.approvals_service.clone();
         let repo_context = env.repo_context.clone();
+        let policy = env.policy.clone();
         let cancel_for_task = cancel.clone();
         tokio::spawn(async move {
             let log_writer = LogWriter::new(new_stdout);
@@ -339,10 +350,16 @@ impl ClaudeCode {
                 log_writer.clone(),
                 approvals_clone,
                 repo_context,
+                policy,
                 cancel_for_task.clone(),
             );
-            let protocol_peer =
-                ProtocolPeer::spawn(child_stdin, child_stdout, client.clone(), cancel_for_task);
+            let protocol_peer = ProtocolPeer::spawn(
+                child_stdin,
+                child_stdout,
+                client.clone(),
+                cancel_for_task,
+                input_rx,
+            );
 
             // Initialize control protocol
             if let Err(e) = protocol_peer.initialize(hooks).await {
@@ -370,6 +387,7 @@ impl ClaudeCode {
             child,
             exit_signal: None,
             cancel: Some(cancel),
+            input: Some(input_tx),
         })
     }
 }
@@ -1254,7 +1272,7 @@ impl ClaudeLogProcessor {
                         let total_tokens = input_tokens + output_tokens;
                         self.context_tokens_used = total_tokens as u32;
 
-                        patches.push(self.add_token_usage_entry(entry_index_provider));
+                        patches.push(self.add_token_usage_entry(entry_index_provider, None));
                     }
                 }
                 ClaudeStreamEvent::MessageStop => {
@@ -1267,19 +1285,32 @@ impl ClaudeLogProcessor {
             ClaudeJson::Result {
                 is_error,
                 model_usage,
+                usage,
+                total_cost_usd,
                 subtype,
                 result,
                 ..
             } => {
                 // get the real model context window and correct the context usage entry
-                if let Some(context_window) = model_usage.as_ref().and_then(|model_usage| {
+                let context_window = model_usage.as_ref().and_then(|model_usage| {
                     self.main_model_name
                         .as_ref()
                         .and_then(|name| model_usage.get(name))
                         .and_then(|usage| usage.context_window)
-                }) {
+                });
+                if let Some(context_window) = context_window {
                     self.main_model_context_window = context_window;
-                    patches.push(self.add_token_usage_entry(entry_index_provider));
+                }
+                // the result carries the totals for the whole turn, used for cost reporting
+                let turn_usage = usage.as_ref().map(|usage| TurnUsage {
+                    input_tokens: usage.input_tokens.unwrap_or(0)
+                        + usage.cache_creation_input_tokens.unwrap_or(0)
+                        + usage.cache_read_input_tokens.unwrap_or(0),
+                    output_tokens: usage.output_tokens.unwrap_or(0),
+                    cost_usd: *total_cost_usd,
+                });
+                if context_window.is_some() || turn_usage.is_some() {
+                    patches.push(self.add_token_usage_entry(entry_index_provider, turn_usage));
                 }
 
                 if matches!(self.strategy, HistoryStrategy::AmpResume) && is_error.unwrap_or(false)
@@ -1465,12 +1496,14 @@ impl ClaudeLogProcessor {
     fn add_token_usage_entry(
         &mut self,
         entry_index_provider: &EntryIndexProvider,
+        turn_usage: Option<TurnUsage>,
     ) -> json_patch::Patch {
         let entry = NormalizedEntry {
             timestamp: None,
             entry_type: NormalizedEntryType::TokenUsageInfo(crate::logs::TokenUsageInfo {
                 total_tokens: self.context_tokens_used,
                 model_context_window: self.main_model_context_window,
+                turn_usage,
             }),
             content: format!(
                 "Tokens used: {} / Context window: {}",
@@ -1723,6 +1756,8 @@ pub enum ClaudeJson {
         model_usage: Option<HashMap<String, ClaudeModelUsage>>,
         #[serde(default)]
         usage: Option<ClaudeUsage>,
+        #[serde(default, alias = "totalCostUsd")]
+        total_cost_usd: Option<f64>,
     },
     ApprovalResponse {
         call_id: String,
@@ -2288,6 +2323,7 @@ mod tests {
                 base_command_override: None,
                 additional_params: None,
                 env: None,
+                artifacts: None,
             },
             approvals_service: None,
             disable_api_key: None,
@@ -18,11 +18,13 @@ use crate::{
         },
         codex::client::LogWriter,
     },
+    policy::AgentPolicy,
 };
 
 const EXIT_PLAN_MODE_NAME: &str = "ExitPlanMode";
 pub const AUTO_APPROVE_CALLBACK_ID: &str = "AUTO_APPROVE_CALLBACK_ID";
 pub const STOP_GIT_CHECK_CALLBACK_ID: &str = "STOP_GIT_CHECK_CALLBACK_ID";
+pub const POLICY_CHECK_CALLBACK_ID: &str = "POLICY_CHECK_CALLBACK_ID";
 // Prefix for denial messages from the user, mirrors claude code CLI behavior
 const TOOL_DENY_PREFIX: &str = "The user doesn't want to proceed with this tool use. The tool use was rejected (eg. if it was a file edit, the new_string was NOT written to the file). To tell you how to proceed, the user said: ";
 
@@ -32,6 +34,7 @@ pub struct ClaudeAgentClient {
     approvals: Option<Arc<dyn ExecutorApprovalService>>,
     auto_approve: bool, // true when approvals is None
     repo_context: RepoContext,
+    policy: Option<AgentPolicy>,
     cancel: CancellationToken,
 }
 
@@ -41,6 +44,7 @@ impl ClaudeAgentClient {
         log_writer: LogWriter,
         approvals: Option<Arc<dyn ExecutorApprovalService>>,
         repo_context: RepoContext,
+        policy: Option<AgentPolicy>,
         cancel: CancellationToken,
     ) -> Arc<Self> {
         let auto_approve = approvals.is_none();
@@ -49,6 +53,7 @@ impl ClaudeAgentClient {
             approvals,
             auto_approve,
             repo_context,
+            policy,
             cancel,
         })
     }
@@ -186,6 +191,32 @@ impl ClaudeAgentClient {
             });
         }
 
+        // Policy check - denies tool uses the project's agent policy refuses, and otherwise
+        // leaves the decision to the other hooks
+        if callback_id == POLICY_CHECK_CALLBACK_ID {
+            let Some(policy) = &self.policy else {
+                return Ok(serde_json::json!({}));
+            };
+            let tool_name = input
+                .get("tool_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            let tool_input = input.get("tool_input").unwrap_or(&serde_json::Value::Null);
+            let worktree = &self.repo_context.workspace_root;
+            return Ok(
+                match policy.check_tool_use(tool_name, tool_input, worktree) {
+                    Ok(()) => serde_json::json!({}),
+                    Err(reason) => serde_json::json!({
+                        "hookSpecificOutput": {
+                            "hookEventName": "PreToolUse",
+                            "permissionDecision": "deny",
+                            "permissionDecisionReason": reason
+                        }
+                    }),
+                },
+            );
+        }
+
         if self.auto_approve {
             Ok(serde_json::json!({
                 "hookSpecificOutput": {
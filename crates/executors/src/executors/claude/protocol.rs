@@ -3,7 +3,7 @@ use std::sync::Arc;
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     process::{ChildStdin, ChildStdout},
-    sync::Mutex,
+    sync::{Mutex, mpsc},
 };
 use tokio_util::sync::CancellationToken;
 
@@ -26,11 +26,14 @@ pub struct ProtocolPeer {
 }
 
 impl ProtocolPeer {
+    /// Messages received on `input` are sent to Claude as user messages until it reports
+    /// the result of the turn.
     pub fn spawn(
         stdin: ChildStdin,
         stdout: ChildStdout,
         client: Arc<ClaudeAgentClient>,
         cancel: CancellationToken,
+        input: mpsc::UnboundedReceiver<String>,
     ) -> Self {
         let peer = Self {
             stdin: Arc::new(Mutex::new(stdin)),
@@ -38,7 +41,7 @@ impl ProtocolPeer {
 
         let reader_peer = peer.clone();
         tokio::spawn(async move {
-            if let Err(e) = reader_peer.read_loop(stdout, client, cancel).await {
+            if let Err(e) = reader_peer.read_loop(stdout, client, cancel, input).await {
                 tracing::error!("Protocol reader loop error: {}", e);
             }
         });
@@ -51,18 +54,18 @@ impl ProtocolPeer {
         stdout: ChildStdout,
         client: Arc<ClaudeAgentClient>,
         cancel: CancellationToken,
+        mut input: mpsc::UnboundedReceiver<String>,
     ) -> Result<(), ExecutorError> {
-        let mut reader = BufReader::new(stdout);
-        let mut buffer = String::new();
+        // `next_line` keeps a partly read line when sending input wins the select
+        let mut lines = BufReader::new(stdout).lines();
 
         loop {
-            buffer.clear();
             tokio::select! {
-                line_result = reader.read_line(&mut buffer) => {
+                line_result = lines.next_line() => {
                     match line_result {
-                        Ok(0) => break, // EOF
-                        Ok(_) => {
-                            let line = buffer.trim();
+                        Ok(None) => break, // EOF
+                        Ok(Some(line)) => {
+                            let line = line.trim();
                             if line.is_empty() {
                                 continue;
                             }
@@ -89,6 +92,11 @@ impl ProtocolPeer {
                         }
                     }
                 }
+                Some(message) = input.recv() => {
+                    if let Err(e) = self.send_user_message(message).await {
+                        tracing::warn!("Failed to send input to Claude: {e}");
+                    }
+                }
                 _ = cancel.cancelled() => {
                     if let Err(e) = self.interrupt().await {
                         tracing::debug!("Failed to send interrupt to Claude: {e}");
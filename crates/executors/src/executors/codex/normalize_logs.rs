@@ -1011,6 +1011,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                                             .model_context_window
                                             .unwrap_or_default()
                                             as u32,
+                                        turn_usage: None,
                                     },
                                 ),
                                 content: format!(
@@ -6,7 +6,6 @@ use std::{
 };
 
 use async_trait::async_trait;
-use command_group::AsyncCommandGroup;
 use futures::StreamExt;
 use regex::Regex;
 use schemars::JsonSchema;
@@ -127,7 +126,7 @@ impl StandardCodingAgentExecutor for Copilot {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
-        let mut child = command.group_spawn()?;
+        let mut child = env.spawn(&mut command).await?;
 
         // Write prompt to stdin
         if let Some(mut stdin) = child.inner().stdin.take() {
@@ -172,7 +171,7 @@ impl StandardCodingAgentExecutor for Copilot {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
-        let mut child = command.group_spawn()?;
+        let mut child = env.spawn(&mut command).await?;
 
         // Write comprehensive prompt to stdin
         if let Some(mut stdin) = child.inner().stdin.take() {
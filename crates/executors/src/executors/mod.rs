@@ -87,6 +87,10 @@ pub enum ExecutorError {
     SetupHelperNotSupported,
     #[error("Auth required: {0}")]
     AuthRequired(String),
+    #[error("{0} cannot run on a remote host")]
+    RemoteHostNotSupported(String),
+    #[error("The agent's policy needs a sandbox to run in")]
+    SandboxRequired,
 }
 
 #[enum_dispatch]
@@ -172,6 +176,24 @@ impl CodingAgent {
         self.default_mcp_config_path().is_some()
     }
 
+    /// Glob patterns of the files kept after the agent runs
+    pub fn artifact_patterns(&self) -> Vec<String> {
+        let cmd = match self {
+            Self::ClaudeCode(agent) => &agent.cmd,
+            Self::Amp(agent) => &agent.cmd,
+            Self::Gemini(agent) => &agent.cmd,
+            Self::Codex(agent) => &agent.cmd,
+            Self::Opencode(agent) => &agent.cmd,
+            Self::CursorAgent(agent) => &agent.cmd,
+            Self::QwenCode(agent) => &agent.cmd,
+            Self::Copilot(agent) => &agent.cmd,
+            Self::Droid(agent) => &agent.cmd,
+            #[cfg(feature = "qa-mode")]
+            Self::QaMock(_) => return Vec::new(),
+        };
+        cmd.artifacts.clone().unwrap_or_default()
+    }
+
     pub fn capabilities(&self) -> Vec<BaseAgentCapability> {
         match self {
             Self::ClaudeCode(_) => vec![
@@ -299,6 +321,10 @@ pub type ExecutorExitSignal = tokio::sync::oneshot::Receiver<ExecutorExitResult>
 /// When cancelled, the executor should attempt to cancel gracefully before being killed.
 pub type CancellationToken = tokio_util::sync::CancellationToken;
 
+/// Input for a running executor, such as a reply to a question the agent asked.
+/// The executor delivers each message the way its agent reads input mid-run.
+pub type ExecutorInputSender = tokio::sync::mpsc::UnboundedSender<String>;
+
 #[derive(Debug)]
 pub struct SpawnedChild {
     pub child: AsyncGroupChild,
@@ -306,6 +332,8 @@ pub struct SpawnedChild {
     pub exit_signal: Option<ExecutorExitSignal>,
     /// Container → Executor: signals when container wants to cancel the execution
     pub cancel: Option<CancellationToken>,
+    /// Container → Executor: input for the running agent, if it accepts any mid-run
+    pub input: Option<ExecutorInputSender>,
 }
 
 impl From<AsyncGroupChild> for SpawnedChild {
@@ -314,6 +342,7 @@ impl From<AsyncGroupChild> for SpawnedChild {
             child,
             exit_signal: None,
             cancel: None,
+            input: None,
         }
     }
 }
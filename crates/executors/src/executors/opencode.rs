@@ -87,6 +87,12 @@ impl Opencode {
         current_dir: &Path,
         env: &ExecutionEnv,
     ) -> Result<(AsyncGroupChild, ServerPassword), ExecutorError> {
+        // The server is reached on localhost, so it has to run on this machine
+        if env.remote.is_some() {
+            return Err(ExecutorError::RemoteHostNotSupported(
+                "OpenCode".to_string(),
+            ));
+        }
         let command_parts = self.build_command_builder()?.build_initial()?;
         let (program_path, args) = command_parts.into_resolved().await?;
 
@@ -109,7 +115,7 @@ impl Opencode {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
-        let child = command.group_spawn()?;
+        let child = env.spawn(&mut command).await?;
 
         Ok((child, server_password))
     }
@@ -228,6 +234,7 @@ impl Opencode {
             child,
             exit_signal: Some(exit_signal_rx),
             cancel: Some(cancel),
+            input: None,
         })
     }
 }
@@ -80,6 +80,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                             entry_type: NormalizedEntryType::TokenUsageInfo(TokenUsageInfo {
                                 total_tokens,
                                 model_context_window,
+                                turn_usage: None,
                             }),
                             content: format!(
                                 "Tokens used: {} / Context window: {}",
@@ -344,6 +344,7 @@ fn generate_mock_logs(prompt: &str) -> Vec<String> {
             session_id: Some(session_id),
             model_usage: None,
             usage: None,
+            total_cost_usd: None,
         },
     ];
 
@@ -5,5 +5,8 @@ pub mod env;
 pub mod executors;
 pub mod logs;
 pub mod mcp_config;
+pub mod policy;
 pub mod profile;
+pub mod remote;
+pub mod sandbox;
 pub mod stdout_dup;
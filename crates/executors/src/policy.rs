@@ -0,0 +1,670 @@
+//! Limits on what a coding agent process may do.
+//!
+//! Network access and writes outside the workspace are enforced by running the agent inside
+//! a sandbox of the operating system; see [`crate::sandbox`]. Limiting the programs an agent
+//! runs cannot be left to the sandbox, so agents whose policy has an allowlist must also have
+//! their tool uses checked: before each tool use web tools and network programs are refused
+//! without network access, shell commands are limited to the allowed programs, and file
+//! writes may be limited to the workspace. Shell commands are parsed on a best-effort basis,
+//! so the checks refuse early what the sandbox would make fail, but the allowlist itself is
+//! only as strong as that parsing. Agents that cannot have their tool uses checked are not
+//! started under a policy with an allowlist.
+
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+
+use crate::executors::BaseCodingAgent;
+
+/// Programs that reach the network, refused when the policy has no network access
+const NETWORK_COMMANDS: &[&str] = &[
+    "curl", "wget", "ssh", "scp", "sftp", "rsync", "nc", "ncat", "netcat", "telnet", "ftp",
+];
+
+/// Subcommands of otherwise local programs that reach the network
+const NETWORK_SUBCOMMANDS: &[(&str, &[&str])] = &[
+    (
+        "git",
+        &["clone", "fetch", "pull", "push", "ls-remote", "submodule"],
+    ),
+    ("npm", &["install", "i", "ci", "add", "update", "publish"]),
+    ("pnpm", &["install", "i", "add", "update", "publish"]),
+    ("yarn", &["install", "add", "upgrade", "publish"]),
+    ("bun", &["install", "i", "add", "update", "publish"]),
+    ("pip", &["install", "download"]),
+    ("pip3", &["install", "download"]),
+    ("cargo", &["install", "publish"]),
+    ("go", &["get", "install"]),
+];
+
+/// Interpreters and the flags that run code given on the command line, which may do anything
+const INLINE_CODE_FLAGS: &[(&str, &[&str])] = &[
+    ("python", &["-c"]),
+    ("python3", &["-c"]),
+    ("node", &["-e", "--eval", "-p", "--print"]),
+    ("perl", &["-e", "-E"]),
+    ("ruby", &["-e"]),
+    ("php", &["-r"]),
+];
+
+/// Shells whose `-c` argument is a command line of its own
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh"];
+
+/// Programs that run the command given in their arguments
+const WRAPPERS: &[&str] = &[
+    "xargs", "env", "sudo", "nohup", "timeout", "nice", "exec", "command", "builtin",
+];
+
+/// Shell keywords that may start a command without being a program
+const SHELL_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "do", "done", "while", "until", "!", "time", "{", "}",
+];
+
+/// Limits on what a coding agent process may do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct AgentPolicy {
+    /// Whether the agent may reach the network
+    pub allow_network: bool,
+    /// Programs the agent may run in its shell; unset allows any
+    pub allowed_commands: Option<Vec<String>>,
+    /// Whether the agent may only write files inside the workspace
+    pub restrict_writes_to_worktree: bool,
+}
+
+impl AgentPolicy {
+    /// Whether `executor` can run under this policy: any agent can be sandboxed, but only
+    /// those that have their tool uses checked can be limited to allowed commands
+    pub fn is_enforced_by(&self, executor: &BaseCodingAgent) -> bool {
+        self.allowed_commands.is_none() || matches!(executor, BaseCodingAgent::ClaudeCode)
+    }
+
+    /// Check one tool use of an agent working in `worktree`. Returns why the policy refuses
+    /// it otherwise.
+    pub fn check_tool_use(
+        &self,
+        tool_name: &str,
+        tool_input: &Value,
+        worktree: &Path,
+    ) -> Result<(), String> {
+        match tool_name {
+            "WebFetch" | "WebSearch" if !self.allow_network => {
+                Err("Network access is not allowed by the project's agent policy".to_string())
+            }
+            "Bash" => {
+                let command = tool_input
+                    .get("command")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                self.check_command(command, worktree)
+            }
+            "Write" | "Edit" | "MultiEdit" | "NotebookEdit" if self.restrict_writes_to_worktree => {
+                let path = tool_input
+                    .get("file_path")
+                    .or_else(|| tool_input.get("notebook_path"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                check_writable(path, worktree)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_command(&self, command: &str, worktree: &Path) -> Result<(), String> {
+        if self.allowed_commands.is_some() && (command.contains("$(") || command.contains('`')) {
+            return Err(
+                "Command substitution is not allowed when the project's agent policy limits \
+                 commands"
+                    .to_string(),
+            );
+        }
+        let line = parse(command);
+        for invocation in &line.invocations {
+            let program = invocation.program.as_str();
+            // A program named by a variable could be anything
+            if program.contains('$') && (!self.allow_network || self.allowed_commands.is_some()) {
+                return Err(format!(
+                    "`{program}` cannot be checked against the project's agent policy"
+                ));
+            }
+            if !self.allow_network && reaches_network(invocation) {
+                return Err(format!(
+                    "`{program}` reaches the network, which the project's agent policy does not \
+                     allow"
+                ));
+            }
+            if let Some(allowed) = &self.allowed_commands
+                && !allowed.iter().any(|a| a == program)
+            {
+                return Err(format!(
+                    "`{program}` is not an allowed command; the project's agent policy allows {}",
+                    allowed.join(", ")
+                ));
+            }
+            if self.restrict_writes_to_worktree {
+                for target in written_paths(invocation) {
+                    check_writable(target, worktree)?;
+                }
+            }
+        }
+        if self.restrict_writes_to_worktree {
+            for target in &line.redirects {
+                check_writable(target, worktree)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The commands a shell command line runs and the files it redirects output to, including
+/// those of command lines run through shells and `eval`
+#[derive(Debug, Default)]
+struct CommandLine {
+    invocations: Vec<Invocation>,
+    redirects: Vec<String>,
+}
+
+/// One command of a shell command line
+#[derive(Debug)]
+struct Invocation {
+    /// The program run, without its directory
+    program: String,
+    args: Vec<String>,
+}
+
+/// A command of a command line as the shell splits it, before the program is picked out
+#[derive(Debug, Default)]
+struct Segment {
+    words: Vec<String>,
+    redirects: Vec<String>,
+}
+
+/// What the word being read is for
+#[derive(Clone, Copy)]
+enum WordKind {
+    Word,
+    /// The file a redirection writes to
+    Output,
+    /// A file read from, a heredoc delimiter or a duplicated descriptor
+    Ignored,
+}
+
+/// A command substitution being read, and the state of the command it is part of
+struct Substitution {
+    closer: char,
+    /// Subshells opened inside the substitution and not closed yet
+    depth: usize,
+    segment: usize,
+    word: String,
+    quote: Option<char>,
+    kind: WordKind,
+}
+
+/// Splits a command line into the commands it runs
+#[derive(Default)]
+struct Splitter {
+    segments: Vec<Segment>,
+    current: usize,
+    word: String,
+    in_word: bool,
+    quote: Option<char>,
+    kind: Option<WordKind>,
+    substitutions: Vec<Substitution>,
+}
+
+impl Splitter {
+    fn finish_word(&mut self) {
+        if !self.in_word {
+            return;
+        }
+        let word = std::mem::take(&mut self.word);
+        let segment = &mut self.segments[self.current];
+        match self.kind.take().unwrap_or(WordKind::Word) {
+            WordKind::Word => segment.words.push(word),
+            WordKind::Output => segment.redirects.push(word),
+            WordKind::Ignored => {}
+        }
+        self.in_word = false;
+    }
+
+    fn start_segment(&mut self) {
+        self.finish_word();
+        self.segments.push(Segment::default());
+        self.current = self.segments.len() - 1;
+    }
+
+    fn open_substitution(&mut self, closer: char) {
+        self.substitutions.push(Substitution {
+            closer,
+            depth: 0,
+            segment: self.current,
+            word: std::mem::take(&mut self.word),
+            quote: self.quote.take(),
+            kind: self.kind.take().unwrap_or(WordKind::Word),
+        });
+        self.in_word = false;
+        self.start_segment();
+    }
+
+    fn close_substitution(&mut self) {
+        self.finish_word();
+        let outer = self.substitutions.pop().expect("an open substitution");
+        self.current = outer.segment;
+        self.word = outer.word;
+        // The output of the substitution is part of the word it appears in
+        self.in_word = true;
+        self.quote = outer.quote;
+        self.kind = Some(outer.kind);
+    }
+
+    fn split(mut self, command: &str) -> Vec<Segment> {
+        self.segments.push(Segment::default());
+        let mut chars = command.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match (self.quote, c) {
+                (Some('\''), '\'') | (Some('"'), '"') => self.quote = None,
+                (Some('\''), _) => self.word.push(c),
+                (Some('"'), '\\') => {
+                    if let Some(next) = chars.next() {
+                        self.word.push(next);
+                    }
+                }
+                // Substitutions run inside double quotes too
+                (_, '$') if chars.next_if_eq(&'(').is_some() => self.open_substitution(')'),
+                (_, '`') if self.substitutions.last().is_some_and(|s| s.closer == '`') => {
+                    self.close_substitution()
+                }
+                (_, '`') => self.open_substitution('`'),
+                (Some(_), _) => self.word.push(c),
+                (None, '\'' | '"') => {
+                    self.quote = Some(c);
+                    self.in_word = true;
+                }
+                (None, '\\') => {
+                    if let Some(next) = chars.next() {
+                        self.word.push(next);
+                        self.in_word = true;
+                    }
+                }
+                (None, '(') => {
+                    if let Some(substitution) = self.substitutions.last_mut() {
+                        substitution.depth += 1;
+                    }
+                    self.start_segment();
+                }
+                (None, ')') => match self.substitutions.last_mut() {
+                    Some(substitution) if substitution.depth > 0 => {
+                        substitution.depth -= 1;
+                        self.start_segment();
+                    }
+                    Some(substitution) if substitution.closer == ')' => self.close_substitution(),
+                    _ => self.start_segment(),
+                },
+                (None, '\n' | ';' | '|' | '&') => self.start_segment(),
+                (None, '>' | '<') => {
+                    // A descriptor number such as the `2` of `2>` is part of the operator
+                    if self.in_word && self.word.chars().all(|c| c.is_ascii_digit()) {
+                        self.word.clear();
+                        self.in_word = false;
+                    }
+                    self.finish_word();
+                    self.kind = Some(if c == '>' {
+                        WordKind::Output
+                    } else {
+                        WordKind::Ignored
+                    });
+                    while chars
+                        .next_if(|next| matches!(next, '>' | '<' | '|'))
+                        .is_some()
+                    {}
+                    // `2>&1` duplicates a descriptor rather than writing a file
+                    if chars.next_if_eq(&'&').is_some() {
+                        self.kind = Some(WordKind::Ignored);
+                    }
+                }
+                (None, c) if c.is_whitespace() => self.finish_word(),
+                (None, _) => {
+                    self.word.push(c);
+                    self.in_word = true;
+                }
+            }
+        }
+        self.finish_word();
+        self.segments
+    }
+}
+
+/// Split a command line into its commands, removing quotes. Pipes, lists, subshells and
+/// command substitutions each make a command of their own, and redirections are taken out of
+/// the words.
+fn segments(command: &str) -> Vec<Segment> {
+    Splitter::default().split(command)
+}
+
+fn parse(command: &str) -> CommandLine {
+    let mut line = CommandLine::default();
+    for segment in segments(command) {
+        line.redirects.extend(segment.redirects);
+        resolve(&segment.words, &mut line);
+    }
+    line
+}
+
+fn resolve(words: &[String], line: &mut CommandLine) {
+    let mut words = words
+        .iter()
+        .skip_while(|word| SHELL_KEYWORDS.contains(&word.as_str()) || is_env_assignment(word));
+    let Some(first) = words.next() else {
+        return;
+    };
+    // The loop variable and list are not programs; the body is its own segment
+    if matches!(first.as_str(), "for" | "case" | "select") {
+        return;
+    }
+    // A program named by a variable keeps its whole name, so that it can be refused
+    let program = match first.rsplit_once('/') {
+        Some((_, name)) if !first.contains('$') => name.to_string(),
+        _ => first.clone(),
+    };
+    let args: Vec<String> = words.cloned().collect();
+
+    let nested = if SHELLS.contains(&program.as_str()) {
+        args.iter()
+            .position(|arg| arg.starts_with('-') && !arg.starts_with("--") && arg.contains('c'))
+            .and_then(|index| args.get(index + 1))
+            .map(String::as_str)
+            .map(parse)
+    } else if program == "eval" {
+        Some(parse(&args.join(" ")))
+    } else {
+        None
+    };
+    // Options, their numeric values and variable assignments come before the command
+    let wrapped: Option<Vec<String>> = (WRAPPERS.contains(&program.as_str())
+        && !(program == "command" && args.iter().any(|arg| arg == "-v" || arg == "-V")))
+    .then(|| {
+        args.iter()
+            .skip_while(|arg| {
+                arg.starts_with('-')
+                    || arg.starts_with(|c: char| c.is_ascii_digit())
+                    || is_env_assignment(arg)
+            })
+            .cloned()
+            .collect()
+    });
+
+    line.invocations.push(Invocation { program, args });
+    if let Some(nested) = nested {
+        line.invocations.extend(nested.invocations);
+        line.redirects.extend(nested.redirects);
+    }
+    if let Some(wrapped) = wrapped {
+        resolve(&wrapped, line);
+    }
+}
+
+fn is_env_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+fn reaches_network(invocation: &Invocation) -> bool {
+    let program = invocation.program.as_str();
+    if NETWORK_COMMANDS.contains(&program) {
+        return true;
+    }
+    if let Some((_, subcommands)) = NETWORK_SUBCOMMANDS.iter().find(|(p, _)| *p == program) {
+        // `git -C dir clone` names its subcommand after the global options
+        let mut args = invocation.args.iter();
+        while let Some(arg) = args.next() {
+            if matches!(arg.as_str(), "-C" | "-c") {
+                args.next();
+            } else if !arg.starts_with('-') {
+                return subcommands.contains(&arg.as_str());
+            }
+        }
+    }
+    INLINE_CODE_FLAGS
+        .iter()
+        .find(|(p, _)| *p == program)
+        .is_some_and(|(_, flags)| {
+            invocation
+                .args
+                .iter()
+                .any(|arg| flags.contains(&arg.as_str()))
+        })
+}
+
+/// Files the command writes, modifies or removes through its arguments
+fn written_paths(invocation: &Invocation) -> Vec<&str> {
+    let args = &invocation.args;
+    let operands: Vec<&str> = {
+        let mut after_dashes = false;
+        args.iter()
+            .map(String::as_str)
+            .filter(|arg| {
+                if after_dashes {
+                    return true;
+                }
+                if *arg == "--" {
+                    after_dashes = true;
+                    return false;
+                }
+                !arg.starts_with('-')
+            })
+            .collect()
+    };
+    let in_place = || {
+        args.iter().any(|arg| {
+            arg == "--in-place"
+                || (arg.starts_with('-') && !arg.starts_with("--") && arg.contains('i'))
+        })
+    };
+
+    match invocation.program.as_str() {
+        "tee" | "touch" | "mkdir" | "rm" | "rmdir" | "truncate" => operands,
+        "cp" | "mv" | "install" | "ln" => {
+            let target_dir = args
+                .iter()
+                .position(|arg| arg == "-t")
+                .and_then(|index| args.get(index + 1))
+                .map(String::as_str)
+                .or_else(|| {
+                    args.iter()
+                        .find_map(|arg| arg.strip_prefix("--target-directory="))
+                });
+            match target_dir {
+                Some(dir) => vec![dir],
+                None => operands.last().copied().into_iter().collect(),
+            }
+        }
+        // The first operand is the script unless it is given with `-e` or `-f`
+        "sed" if in_place() => {
+            let script_given = args.iter().any(|arg| arg == "-e" || arg == "-f");
+            operands
+                .into_iter()
+                .skip(if script_given { 0 } else { 1 })
+                .collect()
+        }
+        "perl" if in_place() => operands.into_iter().skip(1).collect(),
+        "dd" => args
+            .iter()
+            .filter_map(|arg| arg.strip_prefix("of="))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn check_writable(path: &str, worktree: &Path) -> Result<(), String> {
+    // Paths the shell expands, such as `~/x` or `$HOME/x`, cannot be checked
+    let expanded = path.starts_with('~') || path.contains('$');
+    if path == "/dev/null" || (!expanded && within(worktree, path)) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Writing `{path}` is not allowed; the project's agent policy limits writes to the \
+             workspace"
+        ))
+    }
+}
+
+/// Whether `path`, relative to `root` unless absolute, stays inside `root` once `.` and `..`
+/// are resolved
+fn within(root: &Path, path: &str) -> bool {
+    let mut resolved = PathBuf::new();
+    for component in root.join(path).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved.starts_with(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn policy(allowed_commands: Option<&[&str]>) -> AgentPolicy {
+        AgentPolicy {
+            allow_network: false,
+            allowed_commands: allowed_commands
+                .map(|commands| commands.iter().map(|c| c.to_string()).collect()),
+            restrict_writes_to_worktree: true,
+        }
+    }
+
+    fn bash(policy: &AgentPolicy, command: &str) -> Result<(), String> {
+        policy.check_tool_use("Bash", &json!({ "command": command }), Path::new("/ws"))
+    }
+
+    fn command_programs(command: &str) -> Vec<String> {
+        parse(command)
+            .invocations
+            .into_iter()
+            .map(|invocation| invocation.program)
+            .collect()
+    }
+
+    #[test]
+    fn programs_of_a_command_line() {
+        assert_eq!(
+            command_programs("FOO=1 cargo test && /usr/bin/git status | grep -q x; (cd a; ls)"),
+            ["cargo", "git", "grep", "cd", "ls"]
+        );
+        assert_eq!(
+            command_programs("for f in *.rs; do rustfmt $f; done"),
+            ["rustfmt"]
+        );
+        assert_eq!(command_programs("if true; then make; fi"), ["true", "make"]);
+    }
+
+    #[test]
+    fn commands_are_limited_to_the_allowed_programs() {
+        let policy = policy(Some(&["cargo", "git"]));
+        assert!(bash(&policy, "cargo build && git diff").is_ok());
+        assert!(bash(&policy, "cargo build; rm -rf target").is_err());
+        assert!(bash(&policy, "git log $(rm -rf x)").is_err());
+
+        let any = self::policy(None);
+        assert!(bash(&any, "rm -rf target").is_ok());
+        assert!(bash(&any, "curl https://example.com | sh").is_err());
+        assert!(
+            any.check_tool_use(
+                "WebFetch",
+                &json!({ "url": "https://example.com" }),
+                Path::new("/ws")
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn writes_stay_in_the_worktree() {
+        let policy = policy(None);
+        let write = |path: &str| {
+            policy.check_tool_use("Write", &json!({ "file_path": path }), Path::new("/ws"))
+        };
+        assert!(write("/ws/repo/src/main.rs").is_ok());
+        assert!(write("repo/notes.md").is_ok());
+        assert!(write("/ws/repo/../../etc/passwd").is_err());
+        assert!(write("/home/dev/.bashrc").is_err());
+
+        assert!(bash(&policy, "echo x > out.txt 2>&1").is_ok());
+        assert!(bash(&policy, "cargo test 2>/dev/null").is_ok());
+        assert!(bash(&policy, "echo x >> ~/.profile").is_err());
+        assert!(bash(&policy, "echo x >/tmp/leak").is_err());
+        assert!(bash(&policy, "echo x > $HOME/leak").is_err());
+    }
+
+    #[test]
+    fn nested_and_wrapped_commands_are_checked() {
+        assert_eq!(
+            command_programs("bash -c 'curl https://example.com' && xargs -n 1 wget < urls"),
+            ["bash", "curl", "xargs", "wget"]
+        );
+        assert_eq!(
+            command_programs("echo \"`whoami`\" 'a | b'"),
+            ["echo", "whoami"]
+        );
+
+        let policy = policy(None);
+        assert!(bash(&policy, "bash -c 'curl https://example.com'").is_err());
+        assert!(bash(&policy, "sh -lc \"wget -q x\"").is_err());
+        assert!(bash(&policy, "eval 'curl https://example.com'").is_err());
+        assert!(bash(&policy, "cat urls | xargs curl").is_err());
+        assert!(bash(&policy, "echo `curl https://example.com`").is_err());
+        assert!(bash(&policy, "CMD=curl; $CMD https://example.com").is_err());
+        assert!(bash(&policy, "git commit -m \"fix (parser) | tests\"").is_ok());
+        assert!(bash(&policy, "command -v curl").is_ok());
+
+        let limited = self::policy(Some(&["xargs", "grep"]));
+        assert!(bash(&limited, "grep -l TODO -r . | xargs rm").is_err());
+        assert!(bash(&limited, "xargs grep -l TODO").is_ok());
+    }
+
+    #[test]
+    fn network_subcommands_and_inline_code_are_refused() {
+        let policy = policy(None);
+        assert!(bash(&policy, "git clone https://example.com/repo.git").is_err());
+        assert!(bash(&policy, "git -C repo fetch origin").is_err());
+        assert!(bash(&policy, "npm install left-pad").is_err());
+        assert!(bash(&policy, "pip install requests").is_err());
+        assert!(bash(&policy, "python3 -c 'import urllib.request'").is_err());
+        assert!(bash(&policy, "node -e 'fetch(\"https://example.com\")'").is_err());
+        assert!(bash(&policy, "git status && npm test && python3 manage.py check").is_ok());
+
+        let online = AgentPolicy {
+            allow_network: true,
+            ..policy
+        };
+        assert!(bash(&online, "git clone https://example.com/repo.git").is_ok());
+        assert!(bash(&online, "bash -c 'curl https://example.com'").is_ok());
+    }
+
+    #[test]
+    fn commands_writing_files_stay_in_the_worktree() {
+        let policy = policy(None);
+        assert!(bash(&policy, "cp build/app /usr/local/bin/").is_err());
+        assert!(bash(&policy, "cp -t /tmp build/app").is_err());
+        assert!(bash(&policy, "echo x | tee -a ~/.bashrc").is_err());
+        assert!(bash(&policy, "sed -i 's/a/b/' /etc/hosts").is_err());
+        assert!(bash(&policy, "mv notes.md ../../elsewhere/").is_err());
+        assert!(bash(&policy, "dd if=/dev/zero of=/tmp/blob").is_err());
+        assert!(bash(&policy, "rm -rf /").is_err());
+        assert!(bash(&policy, "bash -c 'echo x > /tmp/leak'").is_err());
+        assert!(bash(&policy, "cmd &> /tmp/log").is_err());
+
+        assert!(bash(&policy, "cp src/a.rs src/b.rs && sed -i 's/a/b/' src/b.rs").is_ok());
+        assert!(bash(&policy, "cargo test 2>&1 | tee test.log").is_ok());
+        assert!(bash(&policy, "sed -n 1,5p /etc/hosts").is_ok());
+    }
+}
@@ -0,0 +1,354 @@
+//! Running execution processes on another machine over SSH.
+//!
+//! A project may name a host on which its agents and scripts run, keeping heavy builds off
+//! the machine serving the kanban. Before a process starts, its workspace is copied with
+//! rsync to `<remote dir>/<workspace dir name>` on the host; the process is then started
+//! there through `ssh`, whose stdio carries its output and input as a local process's
+//! would. Its environment variables are sent over stdin ahead of its input rather than on
+//! the `ssh` command line, where other users of either machine could read them. When it
+//! exits, the changes are copied back so diffs, commits and merges keep working on the local
+//! worktrees. Git metadata and ignored files are not copied in either direction, so agents
+//! and tools must be installed on the host and on its `PATH`.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Debug, Error)]
+pub enum RemoteHostError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Quote(#[from] shlex::QuoteError),
+    #[error("{command} failed: {stderr}")]
+    Failed {
+        command: &'static str,
+        stderr: String,
+    },
+}
+
+/// Filters shared by both sync directions
+const SYNC_FILTERS: [&str; 2] = ["--exclude=.git", "--filter=:- .gitignore"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteHost {
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<PathBuf>,
+    /// Absolute directory on the host under which workspaces are synced
+    pub remote_dir: String,
+}
+
+fn quote(s: &str) -> Result<String, shlex::QuoteError> {
+    shlex::try_quote(s).map(|quoted| quoted.into_owned())
+}
+
+/// Script setting the variables explicitly set on the local command, written to the remote
+/// shell's stdin so their values never appear in a command line other users can see
+fn env_script<'a>(
+    envs: impl IntoIterator<Item = (&'a OsStr, Option<&'a OsStr>)>,
+) -> Result<String, shlex::QuoteError> {
+    let mut script = String::new();
+    for (key, value) in envs {
+        let key = key.to_string_lossy();
+        match value {
+            Some(value) => {
+                let assignment = quote(&format!("{key}={}", value.to_string_lossy()))?;
+                script.push_str(&format!("export {assignment}\n"));
+            }
+            None => script.push_str(&format!("unset {}\n", quote(&key)?)),
+        }
+    }
+    Ok(script)
+}
+
+/// Shell line run on the host through `ssh`: read and evaluate the `env_len` bytes of the
+/// environment script from stdin, leaving the rest for the program, then enter `cwd` and
+/// exec the program. Local absolute program paths are replaced by their file name, to be
+/// found on the host's `PATH`.
+fn remote_command_line<'a>(
+    program: &OsStr,
+    args: impl IntoIterator<Item = &'a OsStr>,
+    env_len: usize,
+    cwd: &str,
+) -> Result<String, shlex::QuoteError> {
+    let program = Path::new(program);
+    let program = if program.is_absolute() {
+        program.file_name().unwrap_or(program.as_os_str())
+    } else {
+        program.as_os_str()
+    };
+
+    let mut parts = Vec::new();
+    if env_len > 0 {
+        parts.extend([
+            format!("eval \"$(dd bs=1 count={env_len} 2>/dev/null)\""),
+            "&&".to_string(),
+        ]);
+    }
+    parts.extend(["cd".to_string(), quote(cwd)?, "&&".to_string()]);
+    parts.push("exec".to_string());
+    parts.push(quote(&program.to_string_lossy())?);
+    for arg in args {
+        parts.push(quote(&arg.to_string_lossy())?);
+    }
+    Ok(parts.join(" "))
+}
+
+impl RemoteHost {
+    /// `user@host`, or the host alone to use the SSH configuration's user
+    fn destination(&self) -> String {
+        match &self.user {
+            Some(user) => format!("{user}@{}", self.host),
+            None => self.host.clone(),
+        }
+    }
+
+    /// Options shared by `ssh` and the remote shell rsync uses
+    fn ssh_options(&self) -> Vec<String> {
+        let mut options = vec!["-o".to_string(), "BatchMode=yes".to_string()];
+        if let Some(port) = self.port {
+            options.extend(["-p".to_string(), port.to_string()]);
+        }
+        if let Some(identity_file) = &self.identity_file {
+            options.extend([
+                "-i".to_string(),
+                identity_file.to_string_lossy().into_owned(),
+            ]);
+        }
+        options
+    }
+
+    /// Directory on the host mirroring `workspace_root`
+    pub fn remote_root(&self, workspace_root: &Path) -> String {
+        let name = workspace_root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("{}/{name}", self.remote_dir.trim_end_matches('/'))
+    }
+
+    /// Where `local`, inside `workspace_root`, is on the host
+    fn remote_path(&self, workspace_root: &Path, local: &Path) -> String {
+        let mut path = self.remote_root(workspace_root);
+        if let Ok(relative) = local.strip_prefix(workspace_root) {
+            for component in relative.components() {
+                path.push('/');
+                path.push_str(&component.as_os_str().to_string_lossy());
+            }
+        }
+        path
+    }
+
+    fn ssh(&self) -> Command {
+        let mut command = Command::new("ssh");
+        command.arg("-T").args(self.ssh_options());
+        command
+    }
+
+    /// `ssh` invocation running the program of `local` on the host in the directory
+    /// matching its working directory, with stdio piped like the executors' own processes,
+    /// and the environment script to write to its stdin before anything else
+    pub fn command(
+        &self,
+        local: &Command,
+        workspace_root: &Path,
+    ) -> Result<(Command, String), shlex::QuoteError> {
+        let local = local.as_std();
+        let cwd = local.get_current_dir().unwrap_or(workspace_root);
+        let env = env_script(local.get_envs())?;
+        let line = remote_command_line(
+            local.get_program(),
+            local.get_args(),
+            env.len(),
+            &self.remote_path(workspace_root, cwd),
+        )?;
+
+        let mut command = self.ssh();
+        command
+            .arg(self.destination())
+            .arg("--")
+            .arg(line)
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        Ok((command, env))
+    }
+
+    /// Copy the workspace to the host, removing files deleted locally
+    pub async fn sync_up(&self, workspace_root: &Path) -> Result<(), RemoteHostError> {
+        let remote_root = self.remote_root(workspace_root);
+        let mut mkdir = self.ssh();
+        mkdir
+            .arg(self.destination())
+            .arg("--")
+            .arg(format!("mkdir -p {}", quote(&remote_root)?));
+        run("ssh", mkdir).await?;
+
+        let source = format!("{}/", workspace_root.to_string_lossy());
+        let target = format!("{}:{remote_root}/", self.destination());
+        run("rsync", self.rsync(&source, &target)?).await
+    }
+
+    /// Copy the host's copy of the workspace back, removing files deleted there
+    pub async fn sync_down(&self, workspace_root: &Path) -> Result<(), RemoteHostError> {
+        let source = format!(
+            "{}:{}/",
+            self.destination(),
+            self.remote_root(workspace_root)
+        );
+        let target = format!("{}/", workspace_root.to_string_lossy());
+        run("rsync", self.rsync(&source, &target)?).await
+    }
+
+    fn rsync(&self, source: &str, target: &str) -> Result<Command, shlex::QuoteError> {
+        let mut ssh = vec!["ssh".to_string()];
+        ssh.extend(self.ssh_options());
+        let mut command = Command::new("rsync");
+        command
+            .args(["-az", "--delete"])
+            .args(SYNC_FILTERS)
+            .arg("-e")
+            .arg(shlex::try_join(ssh.iter().map(String::as_str))?)
+            .arg(source)
+            .arg(target);
+        Ok(command)
+    }
+}
+
+async fn run(name: &'static str, mut command: Command) -> Result<(), RemoteHostError> {
+    let output = command.stdin(Stdio::null()).output().await?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(RemoteHostError::Failed {
+        command: name,
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host() -> RemoteHost {
+        RemoteHost {
+            host: "build.example.com".to_string(),
+            user: Some("ci".to_string()),
+            port: Some(2222),
+            identity_file: Some(PathBuf::from("/keys/id_ed25519")),
+            remote_dir: "/srv/vk/".to_string(),
+        }
+    }
+
+    #[test]
+    fn processes_run_in_the_matching_remote_directory() {
+        let root = Path::new("/tmp/worktrees/ab12-task");
+        let mut local = Command::new("/usr/local/bin/npx");
+        local
+            .args(["-y", "it's"])
+            .env("VK_TASK_ID", "t 1")
+            .env_remove("NODE_OPTIONS")
+            .current_dir(root.join("api"));
+
+        let (remote, env) = host().command(&local, root).unwrap();
+        let remote = remote.as_std();
+        let args: Vec<_> = remote.get_args().map(|a| a.to_string_lossy()).collect();
+        assert_eq!(remote.get_program(), "ssh");
+        assert_eq!(
+            args[..8],
+            [
+                "-T",
+                "-o",
+                "BatchMode=yes",
+                "-p",
+                "2222",
+                "-i",
+                "/keys/id_ed25519",
+                "ci@build.example.com"
+            ]
+        );
+        assert_eq!(env, "unset NODE_OPTIONS\nexport 'VK_TASK_ID=t 1'\n");
+        assert_eq!(
+            shlex::split(&args[9]).unwrap(),
+            [
+                "eval",
+                &format!("$(dd bs=1 count={} 2>/dev/null)", env.len()),
+                "&&",
+                "cd",
+                "/srv/vk/ab12-task/api",
+                "&&",
+                "exec",
+                "npx",
+                "-y",
+                "it's"
+            ]
+        );
+        assert!(!args.iter().any(|arg| arg.contains("t 1")));
+    }
+
+    #[test]
+    fn commands_without_variables_skip_the_environment_script() {
+        let root = Path::new("/tmp/worktrees/ab12-task");
+        let mut local = Command::new("ls");
+        local.current_dir(root);
+
+        let (remote, env) = host().command(&local, root).unwrap();
+        let args: Vec<_> = remote
+            .as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        assert!(env.is_empty());
+        assert_eq!(args[9], "cd /srv/vk/ab12-task && exec ls");
+    }
+
+    #[test]
+    fn paths_outside_the_workspace_map_to_its_root() {
+        let host = RemoteHost {
+            user: None,
+            port: None,
+            identity_file: None,
+            ..host()
+        };
+        let root = Path::new("/tmp/worktrees/ab12-task");
+        assert_eq!(host.destination(), "build.example.com");
+        assert_eq!(host.ssh_options(), ["-o", "BatchMode=yes"]);
+        assert_eq!(
+            host.remote_path(root, Path::new("/elsewhere")),
+            "/srv/vk/ab12-task"
+        );
+    }
+
+    #[test]
+    fn rsync_skips_git_metadata_and_ignored_files() {
+        let rsync = host()
+            .rsync("/tmp/ws/", "ci@build.example.com:/srv/vk/ws/")
+            .unwrap();
+        let args: Vec<_> = rsync
+            .as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args,
+            [
+                "-az",
+                "--delete",
+                "--exclude=.git",
+                "--filter=:- .gitignore",
+                "-e",
+                "ssh -o BatchMode=yes -p 2222 -i /keys/id_ed25519",
+                "/tmp/ws/",
+                "ci@build.example.com:/srv/vk/ws/"
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Confining coding agent processes to their project's agent policy.
+//!
+//! An agent run under a policy is started inside a sandbox of the operating system:
+//! bubblewrap (`bwrap`) on Linux and `sandbox-exec` on macOS. Without network access the
+//! process and everything it starts get a network namespace of their own with only a loopback
+//! interface (Linux) or are denied network sockets (macOS). With writes restricted to the
+//! worktree, the filesystem is mounted read-only apart from the workspace and the temporary
+//! directory (Linux) or writes elsewhere are denied (macOS). The whole process is confined,
+//! the agent's own requests to its model included, so agents served over the network need
+//! network access.
+//!
+//! Agents are not started under a policy on machines without either sandbox.
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+use tokio::process::Command;
+use ts_rs::TS;
+use workspace_utils::shell::resolve_executable_path;
+
+use crate::policy::AgentPolicy;
+
+/// Sandbox an agent process runs in to enforce its policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum Sandbox {
+    Bubblewrap,
+    SandboxExec,
+}
+
+impl Sandbox {
+    /// The sandbox available on this machine, if any
+    pub async fn detect() -> Option<Self> {
+        let (sandbox, program) = if cfg!(target_os = "linux") {
+            (Self::Bubblewrap, "bwrap")
+        } else if cfg!(target_os = "macos") {
+            (Self::SandboxExec, "sandbox-exec")
+        } else {
+            return None;
+        };
+        resolve_executable_path(program).await.map(|_| sandbox)
+    }
+
+    /// `command` run inside this sandbox under `policy`, where restricted writes may only go
+    /// to `writable`, with stdio piped like the executors' own processes
+    pub fn wrap(self, command: &Command, policy: &AgentPolicy, writable: &[PathBuf]) -> Command {
+        let local = command.as_std();
+        let (program, mut args) = match self {
+            Self::Bubblewrap => ("bwrap", bwrap_args(policy, writable)),
+            Self::SandboxExec => (
+                "sandbox-exec",
+                vec!["-p".into(), sandbox_exec_profile(policy, writable).into()],
+            ),
+        };
+        args.push(local.get_program().to_owned());
+        args.extend(local.get_args().map(ToOwned::to_owned));
+
+        let mut sandboxed = Command::new(program);
+        sandboxed.args(args);
+        for (key, value) in local.get_envs() {
+            match value {
+                Some(value) => sandboxed.env(key, value),
+                None => sandboxed.env_remove(key),
+            };
+        }
+        if let Some(dir) = local.get_current_dir() {
+            sandboxed.current_dir(dir);
+        }
+        sandboxed
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        sandboxed
+    }
+}
+
+fn bwrap_args(policy: &AgentPolicy, writable: &[PathBuf]) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec!["--die-with-parent".into()];
+    if policy.restrict_writes_to_worktree {
+        args.extend(["--ro-bind", "/", "/", "--dev-bind", "/dev", "/dev"].map(OsString::from));
+        for dir in writable {
+            args.extend(["--bind".into(), dir.into(), dir.into()]);
+        }
+    } else {
+        args.extend(["--dev-bind", "/", "/"].map(OsString::from));
+    }
+    if !policy.allow_network {
+        args.push("--unshare-net".into());
+    }
+    args.push("--".into());
+    args
+}
+
+/// Paths as sandbox-exec matches them, with symlinks such as `/tmp` resolved
+fn real_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn profile_string(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn sandbox_exec_profile(policy: &AgentPolicy, writable: &[PathBuf]) -> String {
+    let mut profile = "(version 1)\n(allow default)\n".to_string();
+    if !policy.allow_network {
+        profile.push_str("(deny network*)\n");
+    }
+    if policy.restrict_writes_to_worktree {
+        profile.push_str("(deny file-write*)\n(allow file-write* (subpath \"/dev\")");
+        for dir in writable {
+            profile.push_str(&format!(" (subpath {})", profile_string(&real_path(dir))));
+        }
+        profile.push_str(")\n");
+    }
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow_network: bool, restrict_writes_to_worktree: bool) -> AgentPolicy {
+        AgentPolicy {
+            allow_network,
+            allowed_commands: None,
+            restrict_writes_to_worktree,
+        }
+    }
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn bubblewrap_confines_network_and_writes() {
+        let mut agent = Command::new("/usr/bin/claude");
+        agent
+            .arg("-p")
+            .env("VK_TASK_ID", "t1")
+            .current_dir("/work/ws/api");
+        let writable = [PathBuf::from("/work/ws"), PathBuf::from("/tmp")];
+
+        let sandboxed = Sandbox::Bubblewrap.wrap(&agent, &policy(false, true), &writable);
+        let std = sandboxed.as_std();
+        assert_eq!(std.get_program(), "bwrap");
+        assert_eq!(std.get_current_dir(), Some(Path::new("/work/ws/api")));
+        assert!(
+            std.get_envs()
+                .any(|(k, v)| k == "VK_TASK_ID" && v == Some("t1".as_ref()))
+        );
+        assert_eq!(
+            args(&sandboxed),
+            [
+                "--die-with-parent",
+                "--ro-bind",
+                "/",
+                "/",
+                "--dev-bind",
+                "/dev",
+                "/dev",
+                "--bind",
+                "/work/ws",
+                "/work/ws",
+                "--bind",
+                "/tmp",
+                "/tmp",
+                "--unshare-net",
+                "--",
+                "/usr/bin/claude",
+                "-p"
+            ]
+        );
+
+        let open = Sandbox::Bubblewrap.wrap(&agent, &policy(true, false), &writable);
+        assert_eq!(
+            args(&open),
+            [
+                "--die-with-parent",
+                "--dev-bind",
+                "/",
+                "/",
+                "--",
+                "/usr/bin/claude",
+                "-p"
+            ]
+        );
+    }
+
+    #[test]
+    fn sandbox_exec_profile_denies_network_and_writes_elsewhere() {
+        let writable = [PathBuf::from("/nonexistent/my \"ws\"")];
+        assert_eq!(
+            sandbox_exec_profile(&policy(false, true), &writable),
+            "(version 1)\n(allow default)\n(deny network*)\n(deny file-write*)\n\
+             (allow file-write* (subpath \"/dev\") (subpath \"/nonexistent/my \\\"ws\\\"\"))\n"
+        );
+        assert_eq!(
+            sandbox_exec_profile(&policy(true, false), &writable),
+            "(version 1)\n(allow default)\n"
+        );
+
+        let agent = Command::new("codex");
+        let sandboxed = Sandbox::SandboxExec.wrap(&agent, &policy(false, false), &writable);
+        assert_eq!(sandboxed.as_std().get_program(), "sandbox-exec");
+        assert_eq!(
+            args(&sandboxed),
+            [
+                "-p",
+                "(version 1)\n(allow default)\n(deny network*)\n",
+                "codex"
+            ]
+        );
+    }
+}
@@ -16,6 +16,7 @@
 //! `git` CLI, while keeping libgit2 for read‑only graph queries and credentialed
 //! network operations when useful.
 use std::{
+    collections::HashMap,
     ffi::{OsStr, OsString},
     io::Write as _,
     path::Path,
@@ -23,9 +24,11 @@ use std::{
 };
 
 use thiserror::Error;
-use utils::{path::ALWAYS_SKIP_DIRS, shell::resolve_executable_path_blocking};
+use utils::{
+    diff::DiffChangeState, path::ALWAYS_SKIP_DIRS, shell::resolve_executable_path_blocking,
+};
 
-use super::Commit;
+use super::{Commit, ConflictOp, credential::credential_envs};
 
 #[derive(Debug, Error)]
 pub enum GitCliError {
@@ -63,6 +66,10 @@ pub struct StatusDiffEntry {
     pub change: ChangeType,
     pub path: String,
     pub old_path: Option<String>,
+    /// Where the change is in the worktree; committed unless `git status` lists the path
+    pub state: DiffChangeState,
+    /// Similarity score git gave a rename or copy, in percent
+    pub similarity: Option<u8>,
 }
 
 /// Parsed worktree entry from `git worktree list --porcelain`
@@ -70,11 +77,32 @@ pub struct StatusDiffEntry {
 pub struct WorktreeEntry {
     pub path: String,
     pub branch: Option<String>,
+    /// `git worktree lock` was run on it; prune and remove skip it
+    pub locked: bool,
+    /// Git considers its metadata stale, usually because the directory is gone
+    pub prunable: bool,
+}
+
+/// One commit from `git log --follow` of a single file
+#[derive(Debug, Clone)]
+pub struct FileLogEntry {
+    pub sha: String,
+    pub author: String,
+    /// Commit time as a unix timestamp
+    pub time: i64,
+    pub subject: String,
+    /// Path of the file in this commit
+    pub path: String,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct StatusDiffOptions {
     pub path_filter: Option<Vec<String>>, // pathspecs to limit diff
+    pub include_untracked: bool,
+    /// Similarity percentages for rename and copy detection; git's defaults when unset,
+    /// and no copy detection
+    pub rename_threshold: Option<u16>,
+    pub copy_threshold: Option<u16>,
 }
 
 impl GitCli {
@@ -124,6 +152,22 @@ impl GitCli {
         Ok(())
     }
 
+    /// Run `git -C <repo> worktree repair <path>` to relink a worktree and its metadata
+    pub fn worktree_repair(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), GitCliError> {
+        self.ensure_available()?;
+        let args: Vec<OsString> = vec![
+            "worktree".into(),
+            "repair".into(),
+            worktree_path.as_os_str().into(),
+        ];
+        self.git(repo_path, args)?;
+        Ok(())
+    }
+
     /// Run `git -C <repo> worktree move <old_path> <new_path>`
     pub fn worktree_move(
         &self,
@@ -163,8 +207,8 @@ impl GitCli {
         Ok(!out.is_empty())
     }
 
-    /// Diff status vs a base branch using a temporary index (always includes untracked).
-    /// Path filter limits the reported paths.
+    /// Diff status vs a base branch using a temporary index, optionally including untracked
+    /// files. Path filter limits the reported paths.
     pub fn diff_status(
         &self,
         worktree_path: &Path,
@@ -187,7 +231,19 @@ impl GitCli {
         // Use raw paths from `get_worktree_status` to avoid lossy UTF-8 conversions for odd filenames.
         let status = self.get_worktree_status(worktree_path)?;
         let mut paths_to_add: Vec<Vec<u8>> = Vec::new();
+        let mut states = HashMap::new();
         for entry in status.entries {
+            if entry.is_untracked && !opts.include_untracked {
+                continue;
+            }
+            let state = if entry.is_untracked {
+                DiffChangeState::Untracked
+            } else if entry.unstaged != ' ' {
+                DiffChangeState::Unstaged
+            } else {
+                DiffChangeState::Staged
+            };
+            states.insert(String::from_utf8_lossy(&entry.path).into_owned(), state);
             paths_to_add.push(entry.path);
             if let Some(orig) = entry.orig_path {
                 paths_to_add.push(orig);
@@ -218,13 +274,25 @@ impl GitCli {
             "core.quotepath=false".into(),
             "diff".into(),
             "--cached".into(),
-            "-M".into(),
+            match opts.rename_threshold {
+                Some(threshold) => format!("-M{threshold}%").into(),
+                None => "-M".into(),
+            },
             "--name-status".into(),
-            OsString::from(base_commit.to_string()),
         ];
+        if let Some(threshold) = opts.copy_threshold {
+            args.push(format!("-C{threshold}%").into());
+        }
+        args.push(OsString::from(base_commit.to_string()));
         args = Self::apply_pathspec_filter(args, opts.path_filter.as_ref());
         let out = self.git_with_env(worktree_path, args, &envs)?;
-        Ok(Self::parse_name_status(&out))
+        let mut entries = Self::parse_name_status(&out);
+        for entry in &mut entries {
+            if let Some(state) = states.get(&entry.path) {
+                entry.state = *state;
+            }
+        }
+        Ok(entries)
     }
 
     /// Return `git status --porcelain` parsed into a structured summary
@@ -302,6 +370,8 @@ impl GitCli {
         let mut current_path: Option<String> = None;
         let mut current_head: Option<String> = None;
         let mut current_branch: Option<String> = None;
+        let mut locked = false;
+        let mut prunable = false;
 
         for line in out.lines() {
             let line = line.trim();
@@ -312,6 +382,8 @@ impl GitCli {
                     entries.push(WorktreeEntry {
                         path,
                         branch: current_branch.take(),
+                        locked: std::mem::take(&mut locked),
+                        prunable: std::mem::take(&mut prunable),
                     });
                 }
             } else if let Some(path) = line.strip_prefix("worktree ") {
@@ -323,6 +395,10 @@ impl GitCli {
                 current_branch = branch_ref
                     .strip_prefix("refs/heads/")
                     .map(|name| name.to_string());
+            } else if line == "locked" || line.starts_with("locked ") {
+                locked = true;
+            } else if line == "prunable" || line.starts_with("prunable ") {
+                prunable = true;
             }
         }
 
@@ -331,6 +407,8 @@ impl GitCli {
             entries.push(WorktreeEntry {
                 path,
                 branch: current_branch,
+                locked,
+                prunable,
             });
         }
 
@@ -342,6 +420,40 @@ impl GitCli {
         self.git(worktree_path, ["commit", "-m", message])?;
         Ok(())
     }
+
+    /// Stage and commit the given paths alone; other staged changes stay staged.
+    pub fn commit_paths(
+        &self,
+        worktree_path: &Path,
+        message: &str,
+        paths: &[&Path],
+    ) -> Result<(), GitCliError> {
+        let mut add: Vec<OsString> = vec!["add".into(), "--".into()];
+        add.extend(paths.iter().map(|p| p.as_os_str().to_owned()));
+        self.git(worktree_path, add)?;
+
+        let mut commit: Vec<OsString> = vec!["commit".into(), "-m".into(), message.into()];
+        commit.push("--".into());
+        commit.extend(paths.iter().map(|p| p.as_os_str().to_owned()));
+        self.git(worktree_path, commit)?;
+        Ok(())
+    }
+
+    /// Stage the given paths, including deletions of tracked files
+    pub fn stage_paths(&self, worktree_path: &Path, paths: &[String]) -> Result<(), GitCliError> {
+        let mut args: Vec<OsString> = vec!["add".into(), "-A".into(), "--".into()];
+        args.extend(paths.iter().map(OsString::from));
+        self.git(worktree_path, args)?;
+        Ok(())
+    }
+
+    /// Unstage the given paths, leaving their changes in the worktree
+    pub fn unstage_paths(&self, worktree_path: &Path, paths: &[String]) -> Result<(), GitCliError> {
+        let mut args: Vec<OsString> = vec!["reset".into(), "-q".into(), "--".into()];
+        args.extend(paths.iter().map(OsString::from));
+        self.git(worktree_path, args)?;
+        Ok(())
+    }
     /// Fetch a branch to the given remote using native git authentication.
     pub fn fetch_with_refspec(
         &self,
@@ -349,7 +461,7 @@ impl GitCli {
         remote_url: &str,
         refspec: &str,
     ) -> Result<(), GitCliError> {
-        let envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+        let envs = Self::remote_envs(repo_path);
 
         let args = [
             OsString::from("fetch"),
@@ -377,7 +489,7 @@ impl GitCli {
         } else {
             format!("refs/heads/{branch}:refs/heads/{branch}")
         };
-        let envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+        let envs = Self::remote_envs(repo_path);
 
         let args = [
             OsString::from("push"),
@@ -399,7 +511,7 @@ impl GitCli {
         remote_url: &str,
         branch_name: &str,
     ) -> Result<bool, GitCliError> {
-        let envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+        let envs = Self::remote_envs(repo_path);
 
         let args = [
             OsString::from("ls-remote"),
@@ -415,6 +527,48 @@ impl GitCli {
         }
     }
 
+    /// The commit `branch_name` points at on the remote, querying it without fetching.
+    /// `None` if the remote has no such branch.
+    pub fn remote_branch_head(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        branch_name: &str,
+    ) -> Result<Option<String>, GitCliError> {
+        let envs = Self::remote_envs(repo_path);
+
+        let args = [
+            OsString::from("ls-remote"),
+            OsString::from("--heads"),
+            OsString::from(remote_url),
+            OsString::from(format!("refs/heads/{branch_name}")),
+        ];
+
+        match self.git_with_env(repo_path, args, &envs) {
+            Ok(output) => Ok(output
+                .lines()
+                .find_map(|line| line.split_whitespace().next())
+                .map(str::to_string)),
+            Err(GitCliError::CommandFailed(msg)) => Err(self.classify_cli_error(msg)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Contact a remote without fetching, failing if it cannot be reached or authenticated
+    pub fn ls_remote_heads(&self, repo_path: &Path, remote: &str) -> Result<(), GitCliError> {
+        let envs = Self::remote_envs(repo_path);
+        let args = [
+            OsString::from("ls-remote"),
+            OsString::from("--heads"),
+            OsString::from(remote),
+        ];
+        match self.git_with_env(repo_path, args, &envs) {
+            Ok(_) => Ok(()),
+            Err(GitCliError::CommandFailed(msg)) => Err(self.classify_cli_error(msg)),
+            Err(err) => Err(err),
+        }
+    }
+
     pub fn get_remote_url(
         &self,
         repo_path: &Path,
@@ -424,6 +578,18 @@ impl GitCli {
         Ok(output.trim().to_string())
     }
 
+    /// Add a named remote using `git remote add`.
+    pub fn add_remote(&self, repo_path: &Path, name: &str, url: &str) -> Result<(), GitCliError> {
+        self.git(repo_path, ["remote", "add", name, url])?;
+        Ok(())
+    }
+
+    /// Remove a named remote and its remote-tracking branches using `git remote remove`.
+    pub fn remove_remote(&self, repo_path: &Path, name: &str) -> Result<(), GitCliError> {
+        self.git(repo_path, ["remote", "remove", name])?;
+        Ok(())
+    }
+
     /// List all remotes with their URLs using `git remote -v`.
     /// Returns a Vec of (name, url) tuples, deduplicated (fetch/push show the same URL).
     pub fn list_remotes(&self, repo_path: &Path) -> Result<Vec<(String, String)>, GitCliError> {
@@ -485,6 +651,9 @@ impl GitCli {
                             change,
                             path: newp.to_string(),
                             old_path: Some(old.to_string()),
+                            state: DiffChangeState::Committed,
+                            // R087: 87% similar
+                            similarity: code.get(1..).and_then(|score| score.parse().ok()),
                         });
                     }
                 }
@@ -494,6 +663,8 @@ impl GitCli {
                             change,
                             path: p.to_string(),
                             old_path: None,
+                            state: DiffChangeState::Committed,
+                            similarity: None,
                         });
                     }
                 }
@@ -692,6 +863,56 @@ impl GitCli {
         self.git(worktree_path, ["revert", "--abort"]).map(|_| ())
     }
 
+    /// Merge `from_branch` into the checked-out branch without committing, so conflicts are
+    /// left in the worktree with their markers.
+    pub fn merge_no_commit(
+        &self,
+        worktree_path: &Path,
+        from_branch: &str,
+    ) -> Result<(), GitCliError> {
+        self.git(
+            worktree_path,
+            ["merge", "--no-ff", "--no-commit", from_branch],
+        )
+        .map(|_| ())
+    }
+
+    /// Stage the given paths, marking conflicted ones as resolved.
+    pub fn add_paths(&self, worktree_path: &Path, paths: &[&Path]) -> Result<(), GitCliError> {
+        let mut args: Vec<OsString> = vec!["add".into(), "--".into()];
+        args.extend(paths.iter().map(|p| p.as_os_str().to_owned()));
+        self.git(worktree_path, args).map(|_| ())
+    }
+
+    /// Put the conflict markers of an unfinished merge back into `path`, discarding its
+    /// worktree contents and any staged resolution.
+    pub fn restore_conflict(&self, worktree_path: &Path, path: &Path) -> Result<(), GitCliError> {
+        let args: Vec<OsString> = vec![
+            "checkout".into(),
+            "--conflict=merge".into(),
+            "--".into(),
+            path.as_os_str().to_owned(),
+        ];
+        self.git(worktree_path, args).map(|_| ())
+    }
+
+    /// Finish an in-progress merge, rebase, cherry-pick or revert whose conflicts have been
+    /// resolved and staged, keeping git's prepared commit messages.
+    pub fn continue_conflict_op(
+        &self,
+        worktree_path: &Path,
+        op: &ConflictOp,
+    ) -> Result<(), GitCliError> {
+        let envs = vec![(OsString::from("GIT_EDITOR"), OsString::from("true"))];
+        let args = match op {
+            ConflictOp::Merge => ["commit", "--no-edit"],
+            ConflictOp::Rebase => ["rebase", "--continue"],
+            ConflictOp::CherryPick => ["cherry-pick", "--continue"],
+            ConflictOp::Revert => ["revert", "--continue"],
+        };
+        self.git_with_env(worktree_path, args, &envs).map(|_| ())
+    }
+
     /// List files currently in a conflicted (unmerged) state in the worktree.
     pub fn get_conflicted_files(&self, worktree_path: &Path) -> Result<Vec<String>, GitCliError> {
         // `--diff-filter=U` lists paths with unresolved conflicts
@@ -706,6 +927,98 @@ impl GitCli {
         Ok(files)
     }
 
+    /// Commits touching `path`, newest first, following renames.
+    pub fn log_file(
+        &self,
+        worktree_path: &Path,
+        path: &str,
+        limit: usize,
+    ) -> Result<Vec<FileLogEntry>, GitCliError> {
+        let max_count = format!("--max-count={limit}");
+        let out = self.git(
+            worktree_path,
+            [
+                "log",
+                "--follow",
+                max_count.as_str(),
+                "--format=%x1e%H%x1f%an%x1f%at%x1f%s",
+                "--name-only",
+                "--",
+                path,
+            ],
+        )?;
+
+        let mut entries = Vec::new();
+        for record in out.split('\x1e').filter(|r| !r.trim().is_empty()) {
+            let mut lines = record.lines();
+            let Some(header) = lines.next() else {
+                continue;
+            };
+            let mut fields = header.splitn(4, '\x1f');
+            let (Some(sha), Some(author), Some(time), Some(subject)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let path_at_commit = lines.map(str::trim).find(|l| !l.is_empty()).unwrap_or(path);
+            entries.push(FileLogEntry {
+                sha: sha.to_string(),
+                author: author.to_string(),
+                time: time.parse().unwrap_or(0),
+                subject: subject.to_string(),
+                path: path_at_commit.to_string(),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Commits in `base..tip` as an mbox, one `git format-patch` message per commit
+    pub fn format_patch(
+        &self,
+        repo_path: &Path,
+        base_sha: &str,
+        tip: &str,
+    ) -> Result<Vec<u8>, GitCliError> {
+        let range = format!("{base_sha}..{tip}");
+        self.git_impl(
+            repo_path,
+            ["format-patch", "--stdout", "--binary", range.as_str()],
+            None,
+            None,
+        )
+    }
+
+    /// Apply a unified diff to the index and worktree, falling back to a three-way merge
+    /// for hunks that do not apply cleanly.
+    pub fn apply_patch(&self, worktree_path: &Path, patch: &[u8]) -> Result<(), GitCliError> {
+        self.git_with_stdin(worktree_path, ["apply", "--index", "--3way"], None, patch)
+            .map(|_| ())
+    }
+
+    /// Apply an mbox with `git am`, one commit per message, with three-way fallback.
+    pub fn am(&self, worktree_path: &Path, mbox: &[u8]) -> Result<(), GitCliError> {
+        self.git_with_stdin(worktree_path, ["am", "--3way"], None, mbox)
+            .map(|_| ())
+    }
+
+    /// Return true if `git am` stopped partway through a mailbox.
+    pub fn is_am_in_progress(&self, worktree_path: &Path) -> Result<bool, GitCliError> {
+        let applying = self.git(
+            worktree_path,
+            ["rev-parse", "--git-path", "rebase-apply/applying"],
+        )?;
+        Ok(std::path::Path::new(applying.trim()).exists())
+    }
+
+    /// Abort an in-progress `git am`, restoring the branch to where it started. No-op if
+    /// none is in progress.
+    pub fn abort_am(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        if !self.is_am_in_progress(worktree_path)? {
+            return Ok(());
+        }
+        self.git(worktree_path, ["am", "--abort"]).map(|_| ())
+    }
+
     /// Delete a local branch (force delete with -D)
     pub fn delete_branch(&self, repo_path: &Path, branch_name: &str) -> Result<(), GitCliError> {
         self.git(repo_path, ["branch", "-D", branch_name])
@@ -715,6 +1028,14 @@ impl GitCli {
 
 // Private methods
 impl GitCli {
+    /// Environment for commands that contact a remote: never prompt, and use the repo's
+    /// registered credential instead of ambient ones when it has one
+    fn remote_envs(repo_path: &Path) -> Vec<(OsString, OsString)> {
+        let mut envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+        envs.extend(credential_envs(repo_path));
+        envs
+    }
+
     fn classify_cli_error(&self, msg: String) -> GitCliError {
         let lower = msg.to_ascii_lowercase();
         if lower.contains("authentication failed")
@@ -775,6 +1096,9 @@ impl GitCli {
         let git = resolve_executable_path_blocking("git").ok_or(GitCliError::NotAvailable)?;
         let mut cmd = Command::new(&git);
         cmd.arg("-C").arg(repo_path);
+        // Worktrees under deep workspace directories easily exceed MAX_PATH
+        #[cfg(windows)]
+        cmd.args(["-c", "core.longpaths=true"]);
 
         if let Some(envs) = envs {
             for (k, v) in envs {
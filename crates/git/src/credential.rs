@@ -0,0 +1,152 @@
+//! Per-repo credentials for git network operations.
+//!
+//! Without a registered credential, git falls back to whatever the server process has
+//! ambiently (credential helpers, ssh-agent, `~/.ssh`). Credentials are keyed by the repo's
+//! common git directory, so fetches and pushes from any of its worktrees use them too.
+
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{LazyLock, RwLock},
+};
+
+use git2::Repository;
+
+static CREDENTIALS: LazyLock<RwLock<HashMap<PathBuf, GitCredential>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+const USERNAME_ENV: &str = "RALPH_GIT_USERNAME";
+const TOKEN_ENV: &str = "RALPH_GIT_TOKEN";
+
+/// Credential helper answering from the environment, so the token never appears in arguments
+const CREDENTIAL_HELPER: &str = concat!(
+    "!f() { test \"$1\" = get && ",
+    "echo \"username=$RALPH_GIT_USERNAME\" && echo \"password=$RALPH_GIT_TOKEN\"; }; f"
+);
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum GitCredential {
+    /// Token sent as the password to HTTPS remotes
+    HttpsToken { username: String, token: String },
+    /// Private key used for SSH remotes
+    SshKey { path: PathBuf },
+}
+
+impl fmt::Debug for GitCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitCredential::HttpsToken { username, .. } => f
+                .debug_struct("HttpsToken")
+                .field("username", username)
+                .field("token", &"<redacted>")
+                .finish(),
+            GitCredential::SshKey { path } => f.debug_struct("SshKey").field("path", path).finish(),
+        }
+    }
+}
+
+impl GitCredential {
+    fn envs(&self) -> Vec<(OsString, OsString)> {
+        match self {
+            GitCredential::HttpsToken { username, token } => vec![
+                (OsString::from("GIT_CONFIG_COUNT"), OsString::from("2")),
+                // An empty helper resets the list so configured helpers are not consulted
+                (
+                    OsString::from("GIT_CONFIG_KEY_0"),
+                    OsString::from("credential.helper"),
+                ),
+                (OsString::from("GIT_CONFIG_VALUE_0"), OsString::new()),
+                (
+                    OsString::from("GIT_CONFIG_KEY_1"),
+                    OsString::from("credential.helper"),
+                ),
+                (
+                    OsString::from("GIT_CONFIG_VALUE_1"),
+                    OsString::from(CREDENTIAL_HELPER),
+                ),
+                (OsString::from(USERNAME_ENV), OsString::from(username)),
+                (OsString::from(TOKEN_ENV), OsString::from(token)),
+            ],
+            GitCredential::SshKey { path } => {
+                let path = path.to_string_lossy().replace('\'', r"'\''");
+                vec![(
+                    OsString::from("GIT_SSH_COMMAND"),
+                    OsString::from(format!("ssh -i '{path}' -o IdentitiesOnly=yes")),
+                )]
+            }
+        }
+    }
+}
+
+fn credential_key(repo_path: &Path) -> Option<PathBuf> {
+    let repo = Repository::open(repo_path).ok()?;
+    std::fs::canonicalize(repo.commondir()).ok()
+}
+
+/// Use `credential` for fetches and pushes of the repo at `repo_path`, or go back to the
+/// ambient credentials with `None`
+pub fn set_repo_credential(repo_path: &Path, credential: Option<GitCredential>) {
+    let Some(key) = credential_key(repo_path) else {
+        tracing::warn!(
+            "Cannot register git credential for {}: not a git repository",
+            repo_path.display()
+        );
+        return;
+    };
+    let mut credentials = CREDENTIALS.write().unwrap();
+    match credential {
+        Some(credential) => credentials.insert(key, credential),
+        None => credentials.remove(&key),
+    };
+}
+
+/// Environment applying the registered credential of the repo containing `repo_path`
+pub(crate) fn credential_envs(repo_path: &Path) -> Vec<(OsString, OsString)> {
+    if CREDENTIALS.read().unwrap().is_empty() {
+        return Vec::new();
+    }
+    let Some(key) = credential_key(repo_path) else {
+        return Vec::new();
+    };
+    CREDENTIALS
+        .read()
+        .unwrap()
+        .get(&key)
+        .map(GitCredential::envs)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credential_applies_to_worktrees_of_the_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path().join("repo");
+        let repo = Repository::init(&repo_path).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+        let worktree_path = dir.path().join("worktree");
+        repo.worktree("worktree", &worktree_path, None).unwrap();
+
+        set_repo_credential(
+            &repo_path,
+            Some(GitCredential::HttpsToken {
+                username: "x-access-token".to_string(),
+                token: "secret".to_string(),
+            }),
+        );
+        let envs = credential_envs(&worktree_path);
+        assert!(envs.contains(&(OsString::from(TOKEN_ENV), OsString::from("secret"))));
+
+        set_repo_credential(&repo_path, None);
+        assert!(credential_envs(&worktree_path).is_empty());
+    }
+}
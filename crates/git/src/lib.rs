@@ -8,15 +8,22 @@ use git2::{
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ts_rs::TS;
-use utils::diff::{Diff, DiffChangeKind, FileDiffDetails, compute_line_change_counts};
+use utils::diff::{
+    Diff, DiffChangeKind, DiffFormatOptions, FileDiffDetails,
+    compute_line_change_counts_with_options,
+};
 
 mod cli;
+mod credential;
 mod validation;
 
 use cli::{ChangeType, StatusDiffEntry, StatusDiffOptions};
 pub use cli::{GitCli, GitCliError, StatusEntry, WorktreeStatus};
+pub use credential::{GitCredential, set_repo_credential};
 pub use utils::path::ALWAYS_SKIP_DIRS;
-pub use validation::is_valid_branch_prefix;
+pub use validation::{
+    is_in_path_scope, is_valid_branch_name, is_valid_branch_prefix, normalize_path_scope,
+};
 
 /// Statistics for a single file based on git history
 #[derive(Clone, Debug)]
@@ -52,6 +59,12 @@ pub enum GitServiceError {
     WorktreeDirty(String, String),
     #[error("Rebase in progress; resolve or abort it before retrying")]
     RebaseInProgress,
+    #[error("Revision not found: {0}")]
+    RevisionNotFound(String),
+    #[error("Cannot diff {0}")]
+    NotDiffable(String),
+    #[error("{0} is not changed on the branch")]
+    PathNotChanged(String),
 }
 /// Service for managing Git operations in task execution workflows
 #[derive(Clone)]
@@ -86,6 +99,108 @@ pub struct GitRemote {
     pub url: String,
 }
 
+/// Where a blamed line comes from, relative to the workspace branch
+#[derive(Debug, Clone, Copy, Serialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum BlameLineOrigin {
+    /// Last changed by a commit that is already on the base branch
+    Base,
+    /// Last changed by a commit made on the workspace branch
+    Branch,
+    /// Changed in the worktree but not committed yet
+    Uncommitted,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct BlameLine {
+    /// 1-based line number in the worktree version of the file
+    pub line_number: usize,
+    pub content: String,
+    pub origin: BlameLineOrigin,
+    /// Commit that last changed the line; unset for uncommitted lines
+    pub commit_sha: Option<String>,
+    pub author: Option<String>,
+    pub summary: Option<String>,
+    #[ts(type = "Date | null")]
+    pub committed_at: Option<DateTime<Utc>>,
+}
+
+/// A commit that changed a file, as seen from a worktree
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct FileHistoryEntry {
+    pub commit_sha: String,
+    pub author: String,
+    pub summary: String,
+    #[ts(type = "Date")]
+    pub committed_at: DateTime<Utc>,
+    /// Path of the file in this commit, which differs from the current path across renames
+    pub path: String,
+    /// True if the commit was made on the workspace branch rather than inherited from the base
+    pub on_branch: bool,
+}
+
+/// A commit on a workspace branch
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    pub commit_sha: String,
+    pub author: String,
+    pub summary: String,
+    pub message: String,
+    pub committed_at: DateTime<Utc>,
+}
+
+impl From<&git2::Commit<'_>> for CommitInfo {
+    fn from(commit: &git2::Commit<'_>) -> Self {
+        Self {
+            commit_sha: commit.id().to_string(),
+            author: commit.author().name().unwrap_or("unknown").to_string(),
+            summary: commit.summary().unwrap_or("(no subject)").to_string(),
+            message: commit.message().unwrap_or("").to_string(),
+            committed_at: DateTime::from_timestamp(commit.time().seconds(), 0)
+                .unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// What git reports about a workspace worktree, for integrity checks
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorktreeHealth {
+    /// The worktree directory exists
+    pub exists: bool,
+    /// Its `.git` link opens as a worktree of the expected repository
+    pub git_link_valid: bool,
+    /// The repository lists the worktree
+    pub registered: bool,
+    /// Branch checked out in the worktree; None when detached or unreadable
+    pub branch: Option<String>,
+    pub locked: bool,
+    pub prunable: bool,
+}
+
+/// Outcome of merging a selection of a task branch's files
+#[derive(Debug, Clone)]
+pub struct PathMergeResult {
+    /// Squash commit on the base branch holding the selected files
+    pub merge_commit_sha: String,
+    /// New head of the task branch: a commit on top of the merge holding the files that were
+    /// left out, or the merge itself when every changed file was selected
+    pub task_branch_sha: String,
+    /// Changed files that were left out of the merge
+    pub remaining_paths: Vec<String>,
+}
+
+/// What merging one branch into another would do, worked out without touching either
+#[derive(Debug, Clone)]
+pub struct MergePreview {
+    /// Commits the merged branch has that the base branch does not
+    pub commits_ahead: usize,
+    /// Commits the base branch has that the merged branch does not
+    pub commits_behind: usize,
+    /// Files changed on both sides in ways that would conflict
+    pub conflicted_files: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct HeadInfo {
     pub branch: String,
@@ -146,6 +261,8 @@ pub enum DiffTarget<'p> {
     Worktree {
         worktree_path: &'p Path,
         base_commit: &'p Commit,
+        /// Also report files git does not track yet
+        include_untracked: bool,
     },
     /// Fully committed branch vs base branch
     Branch {
@@ -158,6 +275,29 @@ pub enum DiffTarget<'p> {
         repo_path: &'p Path,
         commit_sha: &'p str,
     },
+    /// Any two revisions, such as release tags or commits, that git can resolve
+    Range {
+        repo_path: &'p Path,
+        from: &'p str,
+        to: &'p str,
+    },
+    /// Base branch as it is now vs the result of merging the branch into it. Returns
+    /// `MergeConflicts` with the conflicted files when the merge would not be clean.
+    Merge {
+        repo_path: &'p Path,
+        branch_name: &'p str,
+        base_branch: &'p str,
+    },
+}
+
+/// Percentage of lines a renamed or copied file shares with the larger of its two versions
+fn line_similarity(old_lines: usize, additions: usize, deletions: usize) -> u8 {
+    let kept = old_lines.saturating_sub(deletions);
+    let total = old_lines.max(kept + additions);
+    if total == 0 {
+        return 100;
+    }
+    (kept * 100 / total) as u8
 }
 
 impl Default for GitService {
@@ -321,16 +461,43 @@ impl GitService {
         Ok(true)
     }
 
+    /// Commit the worktree contents of one file alone, leaving any other changes as they
+    /// are, and return the id of the new commit
+    pub fn commit_file(
+        &self,
+        worktree_path: &Path,
+        file_path: &Path,
+        message: &str,
+    ) -> Result<String, GitServiceError> {
+        self.ensure_cli_commit_identity(worktree_path)?;
+        GitCli::new()
+            .commit_paths(worktree_path, message, &[file_path])
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git commit failed: {e}")))?;
+        Ok(self.get_head_info(worktree_path)?.oid)
+    }
+
     /// Get diffs between branches or worktree changes
     pub fn get_diffs(
         &self,
         target: DiffTarget,
         path_filter: Option<&[&str]>,
+    ) -> Result<Vec<Diff>, GitServiceError> {
+        self.get_diffs_with_options(target, path_filter, &DiffFormatOptions::default())
+    }
+
+    /// Get diffs between branches or worktree changes, ignoring whitespace and detecting
+    /// renames and copies as `format` says
+    pub fn get_diffs_with_options(
+        &self,
+        target: DiffTarget,
+        path_filter: Option<&[&str]>,
+        format: &DiffFormatOptions,
     ) -> Result<Vec<Diff>, GitServiceError> {
         match target {
             DiffTarget::Worktree {
                 worktree_path,
                 base_commit,
+                include_untracked,
             } => {
                 // Use Git CLI to compute diff vs base to avoid sparse false deletions
                 let repo = Repository::open(worktree_path)?;
@@ -346,6 +513,9 @@ impl GitService {
                 let git = GitCli::new();
                 let cli_opts = StatusDiffOptions {
                     path_filter: path_filter.map(|fs| fs.iter().map(|s| s.to_string()).collect()),
+                    include_untracked,
+                    rename_threshold: format.rename_threshold,
+                    copy_threshold: format.copy_threshold,
                 };
                 let entries = git
                     .diff_status(worktree_path, base_commit, cli_opts)
@@ -354,7 +524,7 @@ impl GitService {
                     })?;
                 Ok(entries
                     .into_iter()
-                    .map(|e| Self::status_entry_to_diff(&repo, &base_tree, e))
+                    .map(|e| Self::status_entry_to_diff(&repo, &base_tree, e, format))
                     .collect())
             }
             DiffTarget::Branch {
@@ -374,6 +544,7 @@ impl GitService {
 
                 let mut diff_opts = DiffOptions::new();
                 diff_opts.include_typechange(true);
+                format.apply_to(&mut diff_opts);
 
                 // Add path filtering if specified
                 if let Some(paths) = path_filter {
@@ -389,7 +560,7 @@ impl GitService {
                 )?;
 
                 // Enable rename detection
-                let mut find_opts = DiffFindOptions::new();
+                let mut find_opts = Self::find_options(format);
                 diff.find_similar(Some(&mut find_opts))?;
 
                 self.convert_diff_to_file_diffs(diff, &repo)
@@ -418,6 +589,7 @@ impl GitService {
                 // Diff options
                 let mut diff_opts = git2::DiffOptions::new();
                 diff_opts.include_typechange(true);
+                format.apply_to(&mut diff_opts);
 
                 // Optional path filtering
                 if let Some(paths) = path_filter {
@@ -434,12 +606,113 @@ impl GitService {
                 )?;
 
                 // Enable rename detection
-                let mut find_opts = git2::DiffFindOptions::new();
+                let mut find_opts = Self::find_options(format);
                 diff.find_similar(Some(&mut find_opts))?;
 
                 self.convert_diff_to_file_diffs(diff, &repo)
             }
+            DiffTarget::Range {
+                repo_path,
+                from,
+                to,
+            } => {
+                let repo = self.open_repo(repo_path)?;
+                let from_tree = Self::find_revision(&repo, from)?.tree()?;
+                let to_tree = Self::find_revision(&repo, to)?.tree()?;
+
+                let mut diff_opts = DiffOptions::new();
+                diff_opts.include_typechange(true);
+                format.apply_to(&mut diff_opts);
+                if let Some(paths) = path_filter {
+                    for path in paths {
+                        diff_opts.pathspec(*path);
+                    }
+                }
+
+                let mut diff =
+                    repo.diff_tree_to_tree(Some(&from_tree), Some(&to_tree), Some(&mut diff_opts))?;
+                let mut find_opts = Self::find_options(format);
+                diff.find_similar(Some(&mut find_opts))?;
+
+                self.convert_diff_to_file_diffs(diff, &repo)
+            }
+            DiffTarget::Merge {
+                repo_path,
+                branch_name,
+                base_branch,
+            } => {
+                let repo = self.open_repo(repo_path)?;
+                let base_commit = Self::find_branch(&repo, base_branch)?
+                    .get()
+                    .peel_to_commit()?;
+                let branch_commit = Self::find_branch(&repo, branch_name)?
+                    .get()
+                    .peel_to_commit()?;
+
+                // Merge in memory; only the resulting tree objects are written
+                let mut merge_opts = git2::MergeOptions::new();
+                merge_opts.find_renames(true);
+                let mut index =
+                    repo.merge_commits(&base_commit, &branch_commit, Some(&merge_opts))?;
+                let conflicted_files = Self::conflicted_paths(&index)?;
+                if !conflicted_files.is_empty() {
+                    return Err(GitServiceError::MergeConflicts {
+                        message: format!(
+                            "Merging '{branch_name}' into '{base_branch}' would conflict"
+                        ),
+                        conflicted_files,
+                    });
+                }
+                let merged_tree = repo.find_tree(index.write_tree_to(&repo)?)?;
+
+                let mut diff_opts = DiffOptions::new();
+                diff_opts.include_typechange(true);
+                format.apply_to(&mut diff_opts);
+                if let Some(paths) = path_filter {
+                    for path in paths {
+                        diff_opts.pathspec(*path);
+                    }
+                }
+
+                let mut diff = repo.diff_tree_to_tree(
+                    Some(&base_commit.tree()?),
+                    Some(&merged_tree),
+                    Some(&mut diff_opts),
+                )?;
+                let mut find_opts = Self::find_options(format);
+                diff.find_similar(Some(&mut find_opts))?;
+
+                self.convert_diff_to_file_diffs(diff, &repo)
+            }
+        }
+    }
+
+    /// Rename and copy detection for tree-to-tree diffs
+    fn find_options(format: &DiffFormatOptions) -> DiffFindOptions {
+        let mut find_opts = DiffFindOptions::new();
+        if let Some(threshold) = format.rename_threshold {
+            find_opts.renames(true).rename_threshold(threshold);
         }
+        if let Some(threshold) = format.copy_threshold {
+            find_opts.copies(true).copy_threshold(threshold);
+        }
+        find_opts
+    }
+
+    /// Commit a revision such as a branch, tag, SHA or `HEAD~2` points at
+    fn find_revision<'r>(
+        repo: &'r Repository,
+        rev: &str,
+    ) -> Result<git2::Commit<'r>, GitServiceError> {
+        repo.revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| GitServiceError::RevisionNotFound(rev.to_string()))
+    }
+
+    /// Resolve a revision of the repository to the commit it points at
+    pub fn resolve_revision(&self, repo_path: &Path, rev: &str) -> Result<Commit, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        Ok(Commit::new(Self::find_revision(&repo, rev)?.id()))
     }
 
     /// Convert git2::Diff to our Diff structs
@@ -558,6 +831,22 @@ impl GitService {
                     (None, None)
                 };
 
+                // libgit2 does not expose its similarity score, so estimate it from the
+                // share of the old file's lines that survived
+                let similarity = if matches!(status, Delta::Renamed | Delta::Copied)
+                    && let Some(deletions) = deletions
+                    && let Ok(blob) = repo.find_blob(delta.old_file().id())
+                {
+                    let old_lines = blob.content().iter().filter(|b| **b == b'\n').count();
+                    Some(line_similarity(
+                        old_lines,
+                        additions.unwrap_or(0),
+                        deletions,
+                    ))
+                } else {
+                    None
+                };
+
                 file_diffs.push(Diff {
                     change,
                     old_path,
@@ -568,6 +857,8 @@ impl GitService {
                     additions,
                     deletions,
                     repo_id: None,
+                    state: None,
+                    similarity,
                 });
 
                 delta_index += 1;
@@ -675,7 +966,12 @@ impl GitService {
 
     /// Create Diff entries from git_cli::StatusDiffEntry
     /// New Diff format is flattened with change kind, paths, and optional contents.
-    fn status_entry_to_diff(repo: &Repository, base_tree: &git2::Tree, e: StatusDiffEntry) -> Diff {
+    fn status_entry_to_diff(
+        repo: &Repository,
+        base_tree: &git2::Tree,
+        e: StatusDiffEntry,
+        format: &DiffFormatOptions,
+    ) -> Diff {
         // Map ChangeType to DiffChangeKind
         let mut change = match e.change {
             ChangeType::Added => DiffChangeKind::Added,
@@ -766,7 +1062,7 @@ impl GitService {
         // Compute line stats from available content
         let (additions, deletions) = match (&old_content, &new_content) {
             (Some(old), Some(new)) => {
-                let (adds, dels) = compute_line_change_counts(old, new);
+                let (adds, dels) = compute_line_change_counts_with_options(old, new, format);
                 (Some(adds), Some(dels))
             }
             (Some(old), None) => {
@@ -790,11 +1086,13 @@ impl GitService {
             additions,
             deletions,
             repo_id: None,
+            state: Some(e.state),
+            similarity: e.similarity,
         }
     }
 
     /// Find where a branch is currently checked out
-    fn find_checkout_path_for_branch(
+    pub fn find_checkout_path_for_branch(
         &self,
         repo_path: &Path,
         branch_name: &str,
@@ -814,6 +1112,48 @@ impl GitService {
         Ok(None)
     }
 
+    /// Inspect the worktree of `repo_path` expected at `worktree_path`
+    pub fn inspect_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<WorktreeHealth, GitServiceError> {
+        let canonical = |path: &Path| {
+            utils::path::path_key(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()))
+        };
+        let source = self.open_repo(repo_path)?;
+        let common_dir = canonical(source.commondir());
+
+        let exists = worktree_path.is_dir();
+        let worktree = exists
+            .then(|| Repository::open(worktree_path).ok())
+            .flatten()
+            .filter(|repo| repo.is_worktree() && canonical(repo.commondir()) == common_dir);
+        let branch = worktree.as_ref().and_then(|repo| {
+            let head = repo.head().ok()?;
+            head.is_branch()
+                .then(|| head.shorthand().map(str::to_string))?
+        });
+
+        let expected = canonical(worktree_path);
+        let entry = GitCli::new()
+            .list_worktrees(repo_path)
+            .map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git worktree list failed: {e}"))
+            })?
+            .into_iter()
+            .find(|entry| canonical(Path::new(&entry.path)) == expected);
+
+        Ok(WorktreeHealth {
+            exists,
+            git_link_valid: worktree.is_some(),
+            registered: entry.is_some(),
+            branch,
+            locked: entry.as_ref().is_some_and(|e| e.locked),
+            prunable: entry.as_ref().is_some_and(|e| e.prunable),
+        })
+    }
+
     /// Merge changes from a task branch into the base branch.
     pub fn merge_changes(
         &self,
@@ -914,6 +1254,167 @@ impl GitService {
             }
         }
     }
+    /// Squash-merge only `paths` of a task branch, checked out at `task_worktree_path`, into
+    /// the base branch. The task branch is moved onto the merge with one commit holding the
+    /// changes that were left out, so its diff against the base branch is just those changes.
+    /// Selected files with uncommitted changes in the worktree are refused, and both branches
+    /// are only moved if they still point where the merge started from.
+    #[allow(clippy::too_many_arguments)]
+    pub fn merge_paths(
+        &self,
+        repo_path: &Path,
+        task_worktree_path: &Path,
+        task_branch_name: &str,
+        base_branch_name: &str,
+        paths: &[String],
+        commit_message: &str,
+        remainder_message: &str,
+    ) -> Result<PathMergeResult, GitServiceError> {
+        let (_, task_behind) =
+            self.get_branch_status(repo_path, task_branch_name, base_branch_name)?;
+        if task_behind > 0 {
+            return Err(GitServiceError::BranchesDiverged(format!(
+                "Cannot merge: base branch '{base_branch_name}' is {task_behind} commits ahead of task branch '{task_branch_name}'. The base branch has moved forward since the task was created.",
+            )));
+        }
+
+        // What is merged is what the worktree shows for the selected files
+        let status = self.get_worktree_status(task_worktree_path)?;
+        if let Some(path) = paths.iter().find(|path| {
+            status.entries.iter().any(|entry| {
+                entry.path == path.as_bytes() || entry.orig_path.as_deref() == Some(path.as_bytes())
+            })
+        }) {
+            return Err(GitServiceError::WorktreeDirty(
+                task_branch_name.to_string(),
+                format!("uncommitted changes to {path}"),
+            ));
+        }
+
+        let repo = self.open_repo(repo_path)?;
+        let base_commit = Self::find_branch(&repo, base_branch_name)?
+            .get()
+            .peel_to_commit()?;
+        let task_commit = Self::find_branch(&repo, task_branch_name)?
+            .get()
+            .peel_to_commit()?;
+        let base_tree = base_commit.tree()?;
+        let task_tree = task_commit.tree()?;
+
+        // Only files the task branch changed can be selected
+        let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&task_tree), None)?;
+        let changed: Vec<String> = diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        if let Some(path) = paths.iter().find(|path| !changed.contains(*path)) {
+            return Err(GitServiceError::PathNotChanged(path.clone()));
+        }
+
+        // The base tree with the selected files as they are on the task branch
+        let mut update = git2::build::TreeUpdateBuilder::new();
+        for path in paths {
+            match task_tree.get_path(Path::new(path)) {
+                Ok(entry) => {
+                    let mode = match entry.filemode() {
+                        0o100755 => git2::FileMode::BlobExecutable,
+                        0o120000 => git2::FileMode::Link,
+                        0o160000 => git2::FileMode::Commit,
+                        _ => git2::FileMode::Blob,
+                    };
+                    update.upsert(path.as_str(), entry.id(), mode);
+                }
+                Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                    update.remove(path.as_str());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let merged_tree = repo.find_tree(update.create_updated(&repo, &base_tree)?)?;
+
+        let signature = self.signature_with_fallback(&repo)?;
+        let merge_oid = repo.commit(
+            None,
+            &signature,
+            &signature,
+            commit_message,
+            &merged_tree,
+            &[&base_commit],
+        )?;
+        let remaining_paths: Vec<String> = changed
+            .into_iter()
+            .filter(|path| !paths.contains(path))
+            .collect();
+        let task_oid = if remaining_paths.is_empty() {
+            merge_oid
+        } else {
+            let merge_commit = repo.find_commit(merge_oid)?;
+            repo.commit(
+                None,
+                &signature,
+                &signature,
+                remainder_message,
+                &task_tree,
+                &[&merge_commit],
+            )?
+        };
+
+        // Where the base branch is checked out, fast-forward the checkout so its files follow
+        // the branch; this refuses to overwrite local changes to the merged files
+        let merge_sha = merge_oid.to_string();
+        match self.find_checkout_path_for_branch(repo_path, base_branch_name)? {
+            Some(base_checkout_path) => {
+                GitCli::new()
+                    .git(
+                        &base_checkout_path,
+                        ["merge", "--ff-only", merge_sha.as_str()],
+                    )
+                    .map_err(|e| {
+                        GitServiceError::InvalidRepository(format!("CLI merge failed: {e}"))
+                    })?;
+            }
+            None => {
+                repo.reference_matching(
+                    &format!("refs/heads/{base_branch_name}"),
+                    merge_oid,
+                    true,
+                    base_commit.id(),
+                    "Squash merge of selected files",
+                )
+                .map_err(|e| Self::moved_during_merge(e, base_branch_name))?;
+            }
+        }
+
+        // The task branch keeps the same files, so its worktree stays as it is
+        repo.reference_matching(
+            &format!("refs/heads/{task_branch_name}"),
+            task_oid,
+            true,
+            task_commit.id(),
+            "Keep changes left out of the merge",
+        )
+        .map_err(|e| Self::moved_during_merge(e, task_branch_name))?;
+
+        Ok(PathMergeResult {
+            merge_commit_sha: merge_sha,
+            task_branch_sha: task_oid.to_string(),
+            remaining_paths,
+        })
+    }
+
+    /// A compare-and-swap ref update that failed because `branch` moved meanwhile reads as
+    /// diverged branches
+    fn moved_during_merge(e: git2::Error, branch: &str) -> GitServiceError {
+        if e.code() == git2::ErrorCode::Modified {
+            GitServiceError::BranchesDiverged(format!(
+                "Cannot merge: '{branch}' moved while the merge was in progress"
+            ))
+        } else {
+            e.into()
+        }
+    }
+
     fn get_branch_status_inner(
         &self,
         repo: &Repository,
@@ -1111,6 +1612,279 @@ impl GitService {
         Ok(commit.summary().unwrap_or("(no subject)").to_string())
     }
 
+    pub fn get_commit_info(
+        &self,
+        repo_path: &Path,
+        commit_sha: &str,
+    ) -> Result<CommitInfo, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let oid = git2::Oid::from_str(commit_sha)
+            .map_err(|_| GitServiceError::InvalidRepository("Invalid commit SHA".into()))?;
+        Ok(CommitInfo::from(&repo.find_commit(oid)?))
+    }
+
+    /// Lines added and removed between two commits
+    pub fn diff_line_counts(
+        &self,
+        repo_path: &Path,
+        from_sha: &str,
+        to_sha: &str,
+    ) -> Result<(usize, usize), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let tree_of = |sha: &str| -> Result<git2::Tree<'_>, GitServiceError> {
+            let oid = git2::Oid::from_str(sha)
+                .map_err(|_| GitServiceError::InvalidRepository("Invalid commit SHA".into()))?;
+            Ok(repo.find_commit(oid)?.tree()?)
+        };
+        let (from_tree, to_tree) = (tree_of(from_sha)?, tree_of(to_sha)?);
+        let stats = repo
+            .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)?
+            .stats()?;
+        Ok((stats.insertions(), stats.deletions()))
+    }
+
+    /// Export the commits on `branch_name` after `base_commit` as an mbox of patches
+    pub fn format_patches(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_commit: &Commit,
+    ) -> Result<Vec<u8>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let tip = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        Ok(GitCli::new().format_patch(repo_path, &base_commit.to_string(), &tip.to_string())?)
+    }
+
+    /// Apply an externally produced patch to the checked-out branch of a worktree and commit
+    /// it. An mbox (as written by `git format-patch`) keeps its own commits and authors; a
+    /// plain unified diff becomes a single commit with `message`. Both fall back to a
+    /// three-way merge. On failure the worktree is reset to where it started and
+    /// `MergeConflicts` is returned. Returns the new commits, oldest first.
+    pub fn apply_patch(
+        &self,
+        worktree_path: &Path,
+        patch: &[u8],
+        message: &str,
+    ) -> Result<Vec<CommitInfo>, GitServiceError> {
+        let head = self.get_head_info(worktree_path)?;
+        let start = Commit::new(git2::Oid::from_str(&head.oid)?);
+        self.ensure_cli_commit_identity(worktree_path)?;
+
+        let git = GitCli::new();
+        let is_mbox = patch.starts_with(b"From ");
+        let result = if is_mbox {
+            git.am(worktree_path, patch)
+        } else {
+            git.apply_patch(worktree_path, patch)
+                .and_then(|()| git.commit(worktree_path, message))
+        };
+
+        if let Err(e) = result {
+            let conflicted_files = git.get_conflicted_files(worktree_path).unwrap_or_default();
+            if is_mbox {
+                git.abort_am(worktree_path)?;
+            }
+            self.reset_worktree_to_commit(worktree_path, &head.oid, true)?;
+            return Err(GitServiceError::MergeConflicts {
+                message: format!("Patch does not apply to '{}': {e}", head.branch),
+                conflicted_files,
+            });
+        }
+
+        self.get_branch_commits(worktree_path, &head.branch, &start)
+    }
+
+    /// Commits on `branch_name` that are not reachable from `base_commit`, oldest first
+    pub fn get_branch_commits(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_commit: &Commit,
+    ) -> Result<Vec<CommitInfo>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let tip = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+        revwalk.push(tip)?;
+        revwalk.hide(base_commit.as_oid())?;
+
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            commits.push(CommitInfo::from(&repo.find_commit(oid?)?));
+        }
+        Ok(commits)
+    }
+
+    /// Text of `file_path` at `base_commit` and in the worktree, for diffing files too large
+    /// to inline in diffs. A side is None when the file does not exist there.
+    pub fn get_worktree_file_texts(
+        &self,
+        worktree_path: &Path,
+        base_commit: &Commit,
+        file_path: &Path,
+        max_bytes: usize,
+    ) -> Result<(Option<String>, Option<String>), GitServiceError> {
+        let repo = Repository::open(worktree_path)?;
+        let base_tree = repo.find_commit(base_commit.as_oid())?.tree()?;
+        let old = match base_tree.get_path(file_path) {
+            Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => {
+                let blob = repo.find_blob(entry.id())?;
+                Some(Self::diffable_text(file_path, blob.content(), max_bytes)?)
+            }
+            _ => None,
+        };
+
+        let abs_path = worktree_path.join(file_path);
+        let new = match std::fs::metadata(&abs_path) {
+            Ok(metadata) if metadata.is_file() => {
+                if metadata.len() > max_bytes as u64 {
+                    return Err(Self::too_large(file_path, max_bytes));
+                }
+                let bytes = std::fs::read(&abs_path)?;
+                Some(Self::diffable_text(file_path, &bytes, max_bytes)?)
+            }
+            _ => None,
+        };
+        Ok((old, new))
+    }
+
+    fn diffable_text(
+        file_path: &Path,
+        bytes: &[u8],
+        max_bytes: usize,
+    ) -> Result<String, GitServiceError> {
+        if bytes.len() > max_bytes {
+            return Err(Self::too_large(file_path, max_bytes));
+        }
+        if bytes.contains(&0) {
+            return Err(GitServiceError::NotDiffable(format!(
+                "{}: binary file",
+                file_path.display()
+            )));
+        }
+        String::from_utf8(bytes.to_vec()).map_err(|_| {
+            GitServiceError::NotDiffable(format!("{}: not valid UTF-8", file_path.display()))
+        })
+    }
+
+    fn too_large(file_path: &Path, max_bytes: usize) -> GitServiceError {
+        GitServiceError::NotDiffable(format!(
+            "{}: larger than {} MiB",
+            file_path.display(),
+            max_bytes / (1024 * 1024)
+        ))
+    }
+
+    /// Line-by-line blame of the worktree version of `file_path`, including uncommitted
+    /// edits. Lines are classified against `base_commit`, the fork point of the workspace
+    /// branch: commits reachable from it are pre-existing, later ones were made on the branch.
+    pub fn blame_worktree_file(
+        &self,
+        worktree_path: &Path,
+        file_path: &Path,
+        base_commit: &Commit,
+    ) -> Result<Vec<BlameLine>, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let contents = std::fs::read(worktree_path.join(file_path))?;
+        let text = String::from_utf8_lossy(&contents);
+
+        // Files not yet in HEAD have nothing to blame against: every line is uncommitted
+        let blame = match repo.blame_file(file_path, None) {
+            Ok(committed) => Some(committed.blame_buffer(&contents)?),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let base = base_commit.as_oid();
+        let mut commits: HashMap<git2::Oid, BlameLine> = HashMap::new();
+        let mut lines = Vec::new();
+        for (index, content) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let oid = blame
+                .as_ref()
+                .and_then(|b| b.get_line(line_number))
+                .map(|hunk| hunk.final_commit_id())
+                .filter(|oid| !oid.is_zero());
+
+            let Some(oid) = oid else {
+                lines.push(BlameLine {
+                    line_number,
+                    content: content.to_string(),
+                    origin: BlameLineOrigin::Uncommitted,
+                    commit_sha: None,
+                    author: None,
+                    summary: None,
+                    committed_at: None,
+                });
+                continue;
+            };
+
+            if !commits.contains_key(&oid) {
+                let commit = repo.find_commit(oid)?;
+                let origin = if oid == base || repo.graph_descendant_of(base, oid)? {
+                    BlameLineOrigin::Base
+                } else {
+                    BlameLineOrigin::Branch
+                };
+                let template = BlameLine {
+                    line_number: 0,
+                    content: String::new(),
+                    origin,
+                    commit_sha: Some(oid.to_string()),
+                    author: commit.author().name().map(str::to_string),
+                    summary: commit.summary().map(str::to_string),
+                    committed_at: DateTime::from_timestamp(commit.time().seconds(), 0),
+                };
+                commits.insert(oid, template);
+            }
+            let template = &commits[&oid];
+            lines.push(BlameLine {
+                line_number,
+                content: content.to_string(),
+                ..template.clone()
+            });
+        }
+
+        Ok(lines)
+    }
+
+    /// Up to `limit` commits that changed `file_path` as reachable from the worktree HEAD,
+    /// newest first and following renames. Commits not reachable from `base_commit` are
+    /// marked as made on the workspace branch.
+    pub fn get_file_history(
+        &self,
+        worktree_path: &Path,
+        file_path: &Path,
+        base_commit: &Commit,
+        limit: usize,
+    ) -> Result<Vec<FileHistoryEntry>, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let base = base_commit.as_oid();
+        let entries = GitCli::new().log_file(worktree_path, &file_path.to_string_lossy(), limit)?;
+
+        let mut history = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let oid = git2::Oid::from_str(&entry.sha)?;
+            let on_branch = oid != base && !repo.graph_descendant_of(base, oid)?;
+            history.push(FileHistoryEntry {
+                commit_sha: entry.sha,
+                author: entry.author,
+                summary: entry.subject,
+                committed_at: DateTime::from_timestamp(entry.time, 0).unwrap_or_else(Utc::now),
+                path: entry.path,
+                on_branch,
+            });
+        }
+        Ok(history)
+    }
+
     /// Compare two OIDs and return (ahead, behind) counts: how many commits
     /// `from_oid` is ahead of and behind `to_oid`.
     pub fn ahead_behind_commits_by_oid(
@@ -1138,6 +1912,93 @@ impl GitService {
             .map_err(|e| GitServiceError::InvalidRepository(format!("git status failed: {e}")))
     }
 
+    /// Stage the given paths of a worktree, including deletions of tracked files
+    pub fn stage_paths(
+        &self,
+        worktree_path: &Path,
+        paths: &[String],
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .stage_paths(worktree_path, paths)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git add failed: {e}")))
+    }
+
+    /// Unstage the given paths of a worktree, leaving their changes in the worktree
+    pub fn unstage_paths(
+        &self,
+        worktree_path: &Path,
+        paths: &[String],
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .unstage_paths(worktree_path, paths)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git reset failed: {e}")))
+    }
+
+    /// Commit only what is staged in a worktree and return the new commit's id; None when
+    /// nothing is staged
+    pub fn commit_staged(
+        &self,
+        worktree_path: &Path,
+        message: &str,
+    ) -> Result<Option<String>, GitServiceError> {
+        let git = GitCli::new();
+        if !git.has_staged_changes(worktree_path)? {
+            return Ok(None);
+        }
+        self.ensure_cli_commit_identity(worktree_path)?;
+        git.commit(worktree_path, message)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git commit failed: {e}")))?;
+        Ok(Some(self.get_head_info(worktree_path)?.oid))
+    }
+
+    /// Restore files of a worktree, in the index and on disk, to their version at `source`.
+    /// Files that did not exist at `source` are removed; their paths are returned.
+    pub fn restore_paths(
+        &self,
+        worktree_path: &Path,
+        source: &str,
+        paths: &[String],
+    ) -> Result<Vec<String>, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let tree = repo.revparse_single(source)?.peel_to_tree()?;
+        let (present, absent): (Vec<String>, Vec<String>) = paths
+            .iter()
+            .cloned()
+            .partition(|path| tree.get_path(Path::new(path)).is_ok());
+
+        let git = GitCli::new();
+        if !present.is_empty() {
+            let mut args = vec![
+                "restore".to_string(),
+                format!("--source={source}"),
+                "--staged".to_string(),
+                "--worktree".to_string(),
+                "--".to_string(),
+            ];
+            args.extend(present);
+            git.git(worktree_path, args).map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git restore failed: {e}"))
+            })?;
+        }
+        if !absent.is_empty() {
+            let mut args = ["rm", "-r", "-q", "--cached", "--ignore-unmatch", "--"]
+                .map(String::from)
+                .to_vec();
+            args.extend(absent.iter().cloned());
+            git.git(worktree_path, args)
+                .map_err(|e| GitServiceError::InvalidRepository(format!("git rm failed: {e}")))?;
+            for path in &absent {
+                let full_path = worktree_path.join(path);
+                if full_path.is_dir() {
+                    std::fs::remove_dir_all(&full_path)?;
+                } else if full_path.symlink_metadata().is_ok() {
+                    std::fs::remove_file(&full_path)?;
+                }
+            }
+        }
+        Ok(absent)
+    }
+
     /// Return (uncommitted_tracked_changes, untracked_files) counts in worktree
     pub fn get_worktree_change_counts(
         &self,
@@ -1211,6 +2072,16 @@ impl GitService {
         Ok(())
     }
 
+    /// Limit the checkout of a worktree to `dir` and the files at the root of the repo
+    pub fn set_sparse_checkout(
+        &self,
+        worktree_path: &Path,
+        dir: &str,
+    ) -> Result<(), GitServiceError> {
+        GitCli::new().git(worktree_path, ["sparse-checkout", "set", "--cone", dir])?;
+        Ok(())
+    }
+
     /// Add a worktree for a branch, optionally creating the branch
     pub fn add_worktree(
         &self,
@@ -1251,6 +2122,18 @@ impl GitService {
         Ok(())
     }
 
+    /// Relink a worktree whose `.git` file or metadata no longer point at each other
+    pub fn repair_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        let git = GitCli::new();
+        git.worktree_repair(repo_path, worktree_path)
+            .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))?;
+        Ok(())
+    }
+
     pub fn prune_worktrees(&self, repo_path: &Path) -> Result<(), GitServiceError> {
         let git = GitCli::new();
         git.worktree_prune(repo_path)
@@ -1290,6 +2173,57 @@ impl GitService {
             })
     }
 
+    /// Merge `from_branch` into `base_branch` in memory to find the files that would conflict.
+    /// No branch, index or worktree is changed.
+    pub fn preview_merge(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+        from_branch: &str,
+    ) -> Result<MergePreview, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let base = Self::find_branch(&repo, base_branch)?
+            .get()
+            .peel_to_commit()?;
+        let from = Self::find_branch(&repo, from_branch)?
+            .get()
+            .peel_to_commit()?;
+        let (commits_ahead, commits_behind) = repo.graph_ahead_behind(from.id(), base.id())?;
+
+        let mut merge_opts = git2::MergeOptions::new();
+        merge_opts.find_renames(true);
+        let index = repo.merge_commits(&base, &from, Some(&merge_opts))?;
+
+        Ok(MergePreview {
+            commits_ahead,
+            commits_behind,
+            conflicted_files: Self::conflicted_paths(&index)?,
+        })
+    }
+
+    /// Paths with conflicts in a merged index, each listed once
+    fn conflicted_paths(index: &git2::Index) -> Result<Vec<String>, GitServiceError> {
+        let mut paths = Vec::new();
+        if !index.has_conflicts() {
+            return Ok(paths);
+        }
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let entry = conflict
+                .our
+                .or(conflict.their)
+                .or(conflict.ancestor)
+                .ok_or_else(|| {
+                    GitServiceError::InvalidRepository("Conflict without entries".to_string())
+                })?;
+            let path = String::from_utf8_lossy(&entry.path).into_owned();
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
     pub fn get_all_branches(&self, repo_path: &Path) -> Result<Vec<GitBranch>, git2::Error> {
         let repo = Repository::open(repo_path)?;
         let current_branch = self.get_current_branch(repo_path).unwrap_or_default();
@@ -1638,6 +2572,100 @@ impl GitService {
         Ok(())
     }
 
+    /// Merge `target_branch` into the branch checked out in the worktree so that its
+    /// conflicts can be resolved there. A merge or rebase that already stopped on conflicts
+    /// in the worktree is left as it is, and a merge without conflicts is committed with
+    /// `message`. Returns the conflicted files.
+    pub fn merge_target_into_worktree(
+        &self,
+        worktree_path: &Path,
+        target_branch: &str,
+        message: &str,
+    ) -> Result<Vec<String>, GitServiceError> {
+        if self.detect_conflict_op(worktree_path)?.is_some() {
+            return self.get_conflicted_files(worktree_path);
+        }
+        self.ensure_cli_commit_identity(worktree_path)?;
+        let git = GitCli::new();
+        if let Err(e) = git.merge_no_commit(worktree_path, target_branch) {
+            let conflicted_files = self.get_conflicted_files(worktree_path)?;
+            if conflicted_files.is_empty() {
+                // Failed before merging anything, e.g. local changes would be overwritten
+                git.abort_merge(worktree_path).map_err(|e| {
+                    GitServiceError::InvalidRepository(format!("git merge --abort failed: {e}"))
+                })?;
+                return Err(GitServiceError::InvalidRepository(format!(
+                    "git merge failed: {e}"
+                )));
+            }
+            return Ok(conflicted_files);
+        }
+        // Nothing is left to commit when the branch already contains the target
+        if git.is_merge_in_progress(worktree_path).unwrap_or(false) {
+            git.commit(worktree_path, message).map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git commit failed: {e}"))
+            })?;
+        }
+        Ok(Vec::new())
+    }
+
+    /// Stage the worktree contents of a conflicted file as its resolution
+    pub fn stage_resolved_file(
+        &self,
+        worktree_path: &Path,
+        file_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .add_paths(worktree_path, &[file_path])
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git add failed: {e}")))
+    }
+
+    /// Discard the resolution of a file in an unfinished merge or rebase and put its
+    /// conflict markers back
+    pub fn restore_conflict_markers(
+        &self,
+        worktree_path: &Path,
+        file_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .restore_conflict(worktree_path, file_path)
+            .map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git checkout --conflict failed: {e}"))
+            })
+    }
+
+    /// Finish the merge, rebase, cherry-pick or revert that stopped on conflicts in the
+    /// worktree, once every conflicted file is resolved and staged. A rebase that stops on
+    /// a later commit returns `MergeConflicts` again.
+    pub fn continue_conflicts(&self, worktree_path: &Path) -> Result<(), GitServiceError> {
+        let Some(op) = self.detect_conflict_op(worktree_path)? else {
+            return Err(GitServiceError::InvalidRepository(
+                "No merge or rebase is in progress".to_string(),
+            ));
+        };
+        let conflicted_files = self.get_conflicted_files(worktree_path)?;
+        if !conflicted_files.is_empty() {
+            return Err(GitServiceError::MergeConflicts {
+                message: format!("{} files still have conflicts", conflicted_files.len()),
+                conflicted_files,
+            });
+        }
+        self.ensure_cli_commit_identity(worktree_path)?;
+        GitCli::new()
+            .continue_conflict_op(worktree_path, &op)
+            .map_err(|e| {
+                let conflicted_files = self.get_conflicted_files(worktree_path).unwrap_or_default();
+                if conflicted_files.is_empty() {
+                    GitServiceError::InvalidRepository(format!("Continuing failed: {e}"))
+                } else {
+                    GitServiceError::MergeConflicts {
+                        message: "Stopped on conflicts in a later commit".to_string(),
+                        conflicted_files,
+                    }
+                }
+            })
+    }
+
     pub fn find_branch<'a>(
         repo: &'a Repository,
         branch_name: &str,
@@ -1691,6 +2719,24 @@ impl GitService {
         self.default_remote(&repo, repo_path)
     }
 
+    /// Branch the default remote's HEAD points at, falling back to the checked-out branch
+    pub fn get_default_branch(&self, repo_path: &Path) -> Result<Option<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        if let Ok(remote) = self.default_remote(&repo, repo_path)
+            && let Ok(remote_head) =
+                repo.find_reference(&format!("refs/remotes/{}/HEAD", remote.name))
+            && let Some(target) = remote_head.symbolic_target()
+            && let Some(branch) = target.strip_prefix(&format!("refs/remotes/{}/", remote.name))
+        {
+            return Ok(Some(branch.to_string()));
+        }
+        Ok(repo
+            .head()
+            .ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(str::to_string)))
+    }
+
     pub fn list_remotes(&self, repo_path: &Path) -> Result<Vec<GitRemote>, GitServiceError> {
         let cli = GitCli::new();
         let remotes = cli.list_remotes(repo_path)?;
@@ -1701,6 +2747,35 @@ impl GitService {
             .collect())
     }
 
+    /// Look up a configured remote by name
+    pub fn get_remote(&self, repo_path: &Path, name: &str) -> Result<GitRemote, GitServiceError> {
+        self.list_remotes(repo_path)?
+            .into_iter()
+            .find(|remote| remote.name == name)
+            .ok_or_else(|| GitServiceError::InvalidRepository(format!("Remote '{name}' not found")))
+    }
+
+    pub fn add_remote(
+        &self,
+        repo_path: &Path,
+        name: &str,
+        url: &str,
+    ) -> Result<(), GitServiceError> {
+        Ok(GitCli::new().add_remote(repo_path, name, url)?)
+    }
+
+    pub fn remove_remote(&self, repo_path: &Path, name: &str) -> Result<(), GitServiceError> {
+        Ok(GitCli::new().remove_remote(repo_path, name)?)
+    }
+
+    pub fn check_remote_reachable(
+        &self,
+        repo_path: &Path,
+        remote_name: &str,
+    ) -> Result<(), GitServiceError> {
+        Ok(GitCli::new().ls_remote_heads(repo_path, remote_name)?)
+    }
+
     pub fn check_remote_branch_exists(
         &self,
         repo_path: &Path,
@@ -1713,6 +2788,19 @@ impl GitService {
             .map_err(GitServiceError::from)
     }
 
+    /// The commit `branch_name` points at on the remote, or `None` if it was never pushed
+    pub fn get_remote_branch_head(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        branch_name: &str,
+    ) -> Result<Option<String>, GitServiceError> {
+        let git_cli = GitCli::new();
+        git_cli
+            .remote_branch_head(repo_path, remote_url, branch_name)
+            .map_err(GitServiceError::from)
+    }
+
     pub fn fetch_branch(
         &self,
         repo_path: &Path,
@@ -1726,6 +2814,20 @@ impl GitService {
             .map_err(GitServiceError::from)
     }
 
+    /// Fetch every branch of each remote of the repo into its remote-tracking branches. All
+    /// remotes are tried; when any fails, the error of the last one that did is returned.
+    pub fn fetch_all_remotes(&self, repo_path: &Path) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let mut result = Ok(());
+        for name in repo.remotes()?.iter().flatten() {
+            let remote = repo.find_remote(name)?;
+            if let Err(e) = self.fetch_all_from_remote(&repo, &remote) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
     pub fn resolve_remote_for_branch(
         &self,
         repo_path: &Path,
@@ -1760,9 +2862,12 @@ impl GitService {
         })
     }
 
+    /// Push a branch to `remote_name`, or to the default remote when none is given,
+    /// and track it there
     pub fn push_to_remote(
         &self,
         worktree_path: &Path,
+        remote_name: Option<&str>,
         branch_name: &str,
         force: bool,
     ) -> Result<(), GitServiceError> {
@@ -1770,7 +2875,10 @@ impl GitService {
         self.check_worktree_clean(&repo)?;
 
         // Get the remote
-        let remote = self.default_remote(&repo, worktree_path)?;
+        let remote = match remote_name {
+            Some(name) => self.get_remote(worktree_path, name)?,
+            None => self.default_remote(&repo, worktree_path)?,
+        };
 
         let git_cli = GitCli::new();
         if let Err(e) = git_cli.push(worktree_path, &remote.url, branch_name, force) {
@@ -10,6 +10,33 @@ pub fn is_valid_branch_prefix(prefix: &str) -> bool {
     git2::Branch::name_is_valid(&format!("{prefix}/x")).unwrap_or_default()
 }
 
+pub fn is_valid_branch_name(name: &str) -> bool {
+    git2::Branch::name_is_valid(name).unwrap_or_default()
+}
+
+/// Directory a path scope such as `services/billing` or `services/billing/**` limits a repo
+/// to, without the trailing `/**`. None if the scope is not a directory inside the repo.
+pub fn normalize_path_scope(scope: &str) -> Option<String> {
+    let scope = scope.trim().replace('\\', "/");
+    let scope = scope.trim_start_matches("./");
+    let scope = scope
+        .strip_suffix("/**")
+        .unwrap_or(scope)
+        .trim_end_matches('/');
+    let valid = !scope.is_empty()
+        && !scope.starts_with('/')
+        && scope
+            .split('/')
+            .all(|part| !matches!(part, "" | "." | "..") && !part.contains(['*', '?', '[', ':']));
+    valid.then(|| scope.to_string())
+}
+
+/// Whether a repo-relative path lies in the directory of a normalized path scope
+pub fn is_in_path_scope(path: &str, scope: &str) -> bool {
+    path.strip_prefix(scope)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +71,37 @@ mod tests {
         assert!(!is_valid_branch_prefix("foo/"));
         assert!(!is_valid_branch_prefix(".foo"));
     }
+
+    #[test]
+    fn test_branch_names() {
+        assert!(is_valid_branch_name("vk/1a2b-fix-login"));
+        assert!(!is_valid_branch_name("vk/1a2b fix"));
+        assert!(!is_valid_branch_name("vk//1a2b"));
+    }
+
+    #[test]
+    fn test_path_scopes() {
+        assert_eq!(
+            normalize_path_scope("services/billing/**").as_deref(),
+            Some("services/billing")
+        );
+        assert_eq!(
+            normalize_path_scope("./services/billing/").as_deref(),
+            Some("services/billing")
+        );
+        assert_eq!(normalize_path_scope("**"), None);
+        assert_eq!(normalize_path_scope("/etc"), None);
+        assert_eq!(normalize_path_scope("services/../.."), None);
+        assert_eq!(normalize_path_scope("services/*.rs"), None);
+
+        assert!(is_in_path_scope(
+            "services/billing/src/lib.rs",
+            "services/billing"
+        ));
+        assert!(is_in_path_scope("services/billing", "services/billing"));
+        assert!(!is_in_path_scope(
+            "services/billing-api/lib.rs",
+            "services/billing"
+        ));
+    }
 }
@@ -4,7 +4,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use git::{GitCli, GitCliError, GitService};
+use git::{GitCli, GitCliError, GitService, GitServiceError};
 use git2::{PushOptions, Repository, build::CheckoutBuilder};
 use tempfile::TempDir;
 // Avoid direct git CLI usage in tests; exercise GitService instead.
@@ -406,6 +406,64 @@ fn push_and_fetch_roundtrip_updates_tracking_branch() {
     );
 }
 
+#[test]
+fn fetch_all_remotes_updates_every_tracking_branch() {
+    let temp_dir = TempDir::new().unwrap();
+    let remote_path = temp_dir.path().join("remote.git");
+    Repository::init_bare(&remote_path).expect("init bare remote");
+    let remote_url = remote_path.to_str().expect("remote path str");
+
+    let seed_path = temp_dir.path().join("seed");
+    let service = GitService::new();
+    service
+        .initialize_repo_with_main_branch(&seed_path)
+        .expect("init seed repo");
+    let seed_repo = Repository::open(&seed_path).expect("open seed repo");
+    configure_user(&seed_repo);
+    seed_repo.remote("origin", remote_url).expect("add remote");
+    push_ref(&seed_repo, "refs/heads/main", "refs/heads/main");
+    Repository::open_bare(&remote_path)
+        .expect("open bare remote")
+        .set_head("refs/heads/main")
+        .expect("set remote HEAD");
+
+    let consumer_path = temp_dir.path().join("consumer");
+    let consumer_repo = Repository::clone(remote_url, &consumer_path).expect("clone consumer");
+
+    let producer_path = temp_dir.path().join("producer");
+    let producer_repo = Repository::clone(remote_url, &producer_path).expect("clone producer");
+    configure_user(&producer_repo);
+    checkout_branch(&producer_repo, "main");
+    write_file(&producer_path, "file.txt", "new work\n");
+    commit_all(&producer_repo, "producer commit");
+    push_ref(&producer_repo, "refs/heads/main", "refs/heads/main");
+    push_ref(&producer_repo, "refs/heads/main", "refs/heads/feature");
+    let new_oid = producer_repo
+        .head()
+        .expect("producer head")
+        .target()
+        .expect("producer head oid");
+
+    service
+        .fetch_all_remotes(&consumer_path)
+        .expect("fetch succeeded");
+
+    for tracking_ref in ["refs/remotes/origin/main", "refs/remotes/origin/feature"] {
+        let oid = consumer_repo
+            .find_reference(tracking_ref)
+            .expect("tracking ref")
+            .target()
+            .expect("tracking ref oid");
+        assert_eq!(oid, new_oid, "{tracking_ref} advanced to remote head");
+    }
+    // Local branches are left where they were
+    assert_ne!(
+        consumer_repo.head().unwrap().target().unwrap(),
+        new_oid,
+        "local main untouched"
+    );
+}
+
 #[test]
 fn rebase_preserves_untracked_files() {
     let td = TempDir::new().unwrap();
@@ -652,6 +710,92 @@ fn libgit2_merge_updates_base_ref_in_both_repos() {
     assert_eq!(after_main_wt, sha);
 }
 
+#[test]
+fn merge_paths_merges_selected_files_and_keeps_the_rest() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    write_file(&worktree_path, "wanted.txt", "wanted\n");
+    write_file(&worktree_path, "common.txt", "drive-by edit\n");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    commit_all(&wt_repo, "mixed changes");
+
+    let unchanged = s.merge_paths(
+        &repo_path,
+        &worktree_path,
+        "feature",
+        "main",
+        &["missing.txt".to_string()],
+        "merge",
+        "rest",
+    );
+    assert!(matches!(unchanged, Err(GitServiceError::PathNotChanged(_))));
+
+    // Uncommitted edits to a selected file would be left out of what is merged
+    write_file(&worktree_path, "wanted.txt", "edited\n");
+    let dirty = s.merge_paths(
+        &repo_path,
+        &worktree_path,
+        "feature",
+        "main",
+        &["wanted.txt".to_string()],
+        "merge",
+        "rest",
+    );
+    assert!(matches!(dirty, Err(GitServiceError::WorktreeDirty(..))));
+    write_file(&worktree_path, "wanted.txt", "wanted\n");
+
+    let result = s
+        .merge_paths(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            &["wanted.txt".to_string()],
+            "merge wanted",
+            "remaining changes",
+        )
+        .unwrap();
+    assert_eq!(
+        result.remaining_paths,
+        ["base.txt", "common.txt", "feat.txt"]
+    );
+
+    // main gained only the selected file
+    let repo = Repository::open(&repo_path).unwrap();
+    let read = |branch: &str, path: &str| {
+        let tree = repo
+            .find_branch(branch, git2::BranchType::Local)
+            .unwrap()
+            .get()
+            .peel_to_tree()
+            .unwrap();
+        let id = tree.get_path(Path::new(path)).unwrap().id();
+        String::from_utf8(repo.find_blob(id).unwrap().content().to_vec()).unwrap()
+    };
+    assert_eq!(
+        s.get_branch_oid(&repo_path, "main").unwrap(),
+        result.merge_commit_sha
+    );
+    assert_eq!(read("main", "wanted.txt"), "wanted\n");
+    assert_eq!(read("main", "common.txt"), "base\n");
+
+    // feature sits on the merge with the rest of its changes, and its worktree is untouched
+    let feature = repo
+        .find_commit(git2::Oid::from_str(&result.task_branch_sha).unwrap())
+        .unwrap();
+    assert_eq!(
+        feature.parent_id(0).unwrap().to_string(),
+        result.merge_commit_sha
+    );
+    assert_eq!(read("feature", "common.txt"), "drive-by edit\n");
+    assert_eq!(
+        s.get_head_info(&worktree_path).unwrap().oid,
+        result.task_branch_sha
+    );
+    assert!(s.is_worktree_clean(&worktree_path).unwrap());
+}
+
 #[test]
 fn libgit2_merge_updates_task_ref_and_feature_head_preserves_dirty() {
     // Hit libgit2 path (main repo not on base) and verify task ref + HEAD update safely
@@ -984,6 +1128,7 @@ fn sparse_checkout_respected_in_worktree_diffs_and_commit() {
             DiffTarget::Worktree {
                 worktree_path: Path::new(&wt),
                 base_commit: &base_commit,
+                include_untracked: true,
             },
             None,
         )
@@ -1053,6 +1198,7 @@ fn worktree_diff_ignores_commits_where_base_branch_is_ahead() {
             DiffTarget::Worktree {
                 worktree_path: Path::new(&wt),
                 base_commit: &base_commit,
+                include_untracked: true,
             },
             None,
         )
@@ -1406,3 +1552,75 @@ fn merge_base_ahead_of_task_should_error() {
         "Merge should error when base branch is ahead of task branch"
     );
 }
+
+#[test]
+fn preview_merge_reports_conflicts_without_changing_branches() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_direct_conflict_repo(&td);
+    write_file(&worktree_path, "feature_only.txt", "clean\n");
+    commit_all(&Repository::open(&worktree_path).unwrap(), "clean change");
+
+    let g = GitService::new();
+    let main_before = g.get_branch_oid(&repo_path, "main").unwrap();
+    let feature_before = g.get_branch_oid(&repo_path, "feature").unwrap();
+
+    let preview = g.preview_merge(&repo_path, "main", "feature").unwrap();
+    assert_eq!(preview.conflicted_files, vec!["conflict.txt".to_string()]);
+    assert_eq!((preview.commits_ahead, preview.commits_behind), (2, 1));
+
+    assert_eq!(g.get_branch_oid(&repo_path, "main").unwrap(), main_before);
+    assert_eq!(
+        g.get_branch_oid(&repo_path, "feature").unwrap(),
+        feature_before
+    );
+    assert!(g.is_worktree_clean(&worktree_path).unwrap());
+    assert!(
+        g.preview_merge(&repo_path, "main", "main")
+            .unwrap()
+            .conflicted_files
+            .is_empty()
+    );
+}
+
+#[test]
+fn remote_branch_head_tells_an_unpushed_branch_from_a_stale_one() {
+    let temp_dir = TempDir::new().unwrap();
+    let remote_path = temp_dir.path().join("remote.git");
+    Repository::init_bare(&remote_path).expect("init bare remote");
+    let remote_url = remote_path.to_str().expect("remote path str");
+
+    let local_path = temp_dir.path().join("local");
+    let service = GitService::new();
+    service
+        .initialize_repo_with_main_branch(&local_path)
+        .expect("init local repo");
+    let local_repo = Repository::open(&local_path).expect("open local repo");
+    configure_user(&local_repo);
+    local_repo.remote("origin", remote_url).expect("add remote");
+    push_ref(&local_repo, "refs/heads/main", "refs/heads/main");
+
+    let main_sha = service.get_branch_oid(&local_path, "main").unwrap();
+    assert_eq!(
+        service
+            .get_remote_branch_head(&local_path, remote_url, "main")
+            .unwrap(),
+        Some(main_sha.clone())
+    );
+    assert_eq!(
+        service
+            .get_remote_branch_head(&local_path, remote_url, "feature")
+            .unwrap(),
+        None
+    );
+
+    write_file(&local_path, "file.txt", "unpushed\n");
+    commit_all(&local_repo, "unpushed commit");
+    let local_sha = service.get_branch_oid(&local_path, "main").unwrap();
+    assert_ne!(local_sha, main_sha);
+    assert_eq!(
+        service
+            .get_remote_branch_head(&local_path, remote_url, "main")
+            .unwrap(),
+        Some(main_sha)
+    );
+}
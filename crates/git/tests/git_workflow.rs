@@ -4,10 +4,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use git::{DiffTarget, GitCli, GitService};
+use git::{BlameLineOrigin, DiffTarget, GitCli, GitService, GitServiceError};
 use git2::{Repository, build::CheckoutBuilder};
 use tempfile::TempDir;
-use utils::diff::DiffChangeKind;
+use utils::diff::{DiffChangeKind, DiffChangeState, DiffFormatOptions, WhitespaceMode};
 
 fn add_path(repo_path: &Path, path: &str) {
     let git = GitCli::new();
@@ -180,6 +180,85 @@ fn worktree_clean_detects_staged_deleted_and_renamed() {
     assert!(!s.is_worktree_clean(&repo_path).unwrap());
 }
 
+#[test]
+fn commit_staged_leaves_unstaged_changes() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "keep.txt", "1\n");
+    write_file(&repo_path, "drop.txt", "1\n");
+    let _ = s.commit(&repo_path, "seed").unwrap();
+
+    assert_eq!(s.commit_staged(&repo_path, "nothing").unwrap(), None);
+
+    write_file(&repo_path, "keep.txt", "2\n");
+    write_file(&repo_path, "new.txt", "new\n");
+    std::fs::remove_file(repo_path.join("drop.txt")).unwrap();
+    s.stage_paths(
+        &repo_path,
+        &[
+            "keep.txt".to_string(),
+            "new.txt".to_string(),
+            "drop.txt".to_string(),
+        ],
+    )
+    .unwrap();
+    s.unstage_paths(&repo_path, &["new.txt".to_string()])
+        .unwrap();
+
+    let sha = s.commit_staged(&repo_path, "curated").unwrap().unwrap();
+    assert_eq!(s.get_head_info(&repo_path).unwrap().oid, sha);
+    let status = s.get_worktree_status(&repo_path).unwrap();
+    let paths: Vec<_> = status
+        .entries
+        .iter()
+        .map(|e| String::from_utf8_lossy(&e.path).into_owned())
+        .collect();
+    assert_eq!(paths, ["new.txt"]);
+    assert!(status.entries[0].is_untracked);
+}
+
+#[test]
+fn restore_paths_reverts_changed_files_and_removes_new_ones() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "bad.txt", "base\n");
+    write_file(&repo_path, "good.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+    let base = s.get_head_info(&repo_path).unwrap().oid;
+
+    write_file(&repo_path, "bad.txt", "committed edit\n");
+    write_file(&repo_path, "good.txt", "committed edit\n");
+    write_file(&repo_path, "added.txt", "added\n");
+    let _ = s.commit(&repo_path, "branch work").unwrap();
+    write_file(&repo_path, "bad.txt", "uncommitted edit\n");
+    write_file(&repo_path, "scratch/notes.txt", "untracked\n");
+
+    let removed = s
+        .restore_paths(
+            &repo_path,
+            &base,
+            &[
+                "bad.txt".to_string(),
+                "added.txt".to_string(),
+                "scratch/".to_string(),
+            ],
+        )
+        .unwrap();
+    assert_eq!(removed, ["added.txt", "scratch/"]);
+    assert_eq!(
+        fs::read_to_string(repo_path.join("bad.txt")).unwrap(),
+        "base\n"
+    );
+    assert_eq!(
+        fs::read_to_string(repo_path.join("good.txt")).unwrap(),
+        "committed edit\n"
+    );
+    assert!(!repo_path.join("added.txt").exists());
+    assert!(!repo_path.join("scratch").exists());
+}
+
 #[test]
 fn diff_added_binary_file_has_no_content() {
     // ensure binary file content is not loaded (null byte guard)
@@ -353,6 +432,473 @@ fn get_branch_diffs_between_branches() {
     assert!(diffs.iter().any(|d| d.new_path.as_deref() == Some("b.txt")));
 }
 
+#[test]
+fn blame_worktree_file_classifies_lines() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "notes.txt", "one\ntwo\n");
+    let _ = s.commit(&repo_path, "add notes").unwrap();
+
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+    write_file(&repo_path, "notes.txt", "one\ntwo\nthree\n");
+    let _ = s.commit(&repo_path, "add three").unwrap();
+    // uncommitted edit on top
+    write_file(&repo_path, "notes.txt", "one\ntwo\nthree\nfour\n");
+
+    let base = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let lines = s
+        .blame_worktree_file(&repo_path, Path::new("notes.txt"), &base)
+        .unwrap();
+    let origins: Vec<_> = lines.iter().map(|l| l.origin).collect();
+    assert_eq!(
+        origins,
+        vec![
+            BlameLineOrigin::Base,
+            BlameLineOrigin::Base,
+            BlameLineOrigin::Branch,
+            BlameLineOrigin::Uncommitted,
+        ]
+    );
+    assert_eq!(lines[2].summary.as_deref(), Some("add three"));
+    assert!(lines[3].commit_sha.is_none());
+}
+
+#[test]
+fn file_history_marks_branch_commits() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "notes.txt", "one\n");
+    let _ = s.commit(&repo_path, "add notes").unwrap();
+    write_file(&repo_path, "other.txt", "x\n");
+    let _ = s.commit(&repo_path, "unrelated").unwrap();
+
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+    write_file(&repo_path, "notes.txt", "one\ntwo\n");
+    let _ = s.commit(&repo_path, "extend notes").unwrap();
+
+    let base = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let history = s
+        .get_file_history(&repo_path, Path::new("notes.txt"), &base, 10)
+        .unwrap();
+    let summaries: Vec<_> = history
+        .iter()
+        .map(|h| (h.summary.as_str(), h.on_branch))
+        .collect();
+    assert_eq!(
+        summaries,
+        vec![("extend notes", true), ("add notes", false)]
+    );
+}
+
+#[test]
+fn branch_commits_are_listed_oldest_first() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "add b").unwrap();
+    write_file(&repo_path, "c.txt", "c\n");
+    let _ = s.commit(&repo_path, "add c").unwrap();
+
+    let base = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let commits = s.get_branch_commits(&repo_path, "feature", &base).unwrap();
+    let summaries: Vec<_> = commits.iter().map(|c| c.summary.as_str()).collect();
+    assert_eq!(summaries, vec!["add b", "add c"]);
+}
+
+#[test]
+fn diff_line_counts_between_commits() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "one\ntwo\nthree\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    let from = s.get_head_info(&repo_path).unwrap().oid;
+
+    write_file(&repo_path, "a.txt", "one\n2\nthree\nfour\n");
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "edit a, add b").unwrap();
+    let to = s.get_head_info(&repo_path).unwrap().oid;
+
+    assert_eq!(s.diff_line_counts(&repo_path, &from, &to).unwrap(), (3, 1));
+    assert_eq!(s.diff_line_counts(&repo_path, &to, &to).unwrap(), (0, 0));
+}
+
+#[test]
+fn diff_between_revisions_covers_every_commit_in_between() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    let repo = Repository::open(&repo_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.tag_lightweight("v1", head.as_object(), false).unwrap();
+
+    write_file(&repo_path, "a.txt", "a\nb\n");
+    let _ = s.commit(&repo_path, "extend a").unwrap();
+    write_file(&repo_path, "c.txt", "c\n");
+    let _ = s.commit(&repo_path, "add c").unwrap();
+
+    let range = |from, to| {
+        s.get_diffs(
+            DiffTarget::Range {
+                repo_path: &repo_path,
+                from,
+                to,
+            },
+            None,
+        )
+    };
+    let diffs = range("v1", "main").unwrap();
+    let mut changed: Vec<_> = diffs
+        .iter()
+        .map(|d| (d.new_path.as_deref().unwrap(), d.additions))
+        .collect();
+    changed.sort();
+    assert_eq!(changed, [("a.txt", Some(1)), ("c.txt", Some(1))]);
+
+    let last = range("HEAD~1", "HEAD").unwrap();
+    assert_eq!(last.len(), 1);
+    assert_eq!(last[0].new_path.as_deref(), Some("c.txt"));
+
+    assert!(matches!(
+        range("v9", "main"),
+        Err(GitServiceError::RevisionNotFound(rev)) if rev == "v9"
+    ));
+    assert_eq!(
+        s.resolve_revision(&repo_path, "v1").unwrap().as_oid(),
+        head.id()
+    );
+}
+
+#[test]
+fn format_patches_exports_branch_commits_as_mbox() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "add b").unwrap();
+    write_file(&repo_path, "b.txt", "b\nc\n");
+    let _ = s.commit(&repo_path, "extend b").unwrap();
+
+    let base = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let mbox = String::from_utf8(s.format_patches(&repo_path, "feature", &base).unwrap()).unwrap();
+    assert!(mbox.contains("Subject: [PATCH 1/2] add b"));
+    assert!(mbox.contains("Subject: [PATCH 2/2] extend b"));
+    assert!(!mbox.contains("add a"));
+}
+
+#[test]
+fn apply_patch_commits_diff_and_rolls_back_conflicts() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    configure_user(&repo_path, "Test User", "test@example.com");
+    let s = GitService::new();
+    write_file(&repo_path, "notes.txt", "one\ntwo\n");
+    let _ = s.commit(&repo_path, "add notes").unwrap();
+
+    let patch = "--- a/notes.txt\n+++ b/notes.txt\n@@ -1,2 +1,3 @@\n one\n two\n+three\n";
+    let commits = s
+        .apply_patch(&repo_path, patch.as_bytes(), "external fix")
+        .unwrap();
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].summary, "external fix");
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("notes.txt")).unwrap(),
+        "one\ntwo\nthree\n"
+    );
+
+    // A patch against content that is not there fails and leaves the worktree untouched
+    let stale = "--- a/notes.txt\n+++ b/notes.txt\n@@ -1,2 +1,2 @@\n one\n-zwei\n+deux\n";
+    let err = s
+        .apply_patch(&repo_path, stale.as_bytes(), "stale")
+        .unwrap_err();
+    assert!(matches!(err, GitServiceError::MergeConflicts { .. }));
+    assert!(s.is_worktree_clean(&repo_path).unwrap());
+}
+
+#[test]
+fn diff_options_ignore_whitespace_and_tune_rename_detection() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    let long: String = (1..=10).map(|i| format!("line {i}\n")).collect();
+    write_file(&repo_path, "main.rs", "fn main() {\n    run();\n}\n");
+    write_file(&repo_path, "old.txt", &long);
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+
+    write_file(&repo_path, "main.rs", "fn main() {\n\trun();\n}\n");
+    fs::remove_file(repo_path.join("old.txt")).unwrap();
+    write_file(&repo_path, "new.txt", &long.replace("line 10", "line ten"));
+    let _ = s.commit(&repo_path, "reindent and move").unwrap();
+
+    let diff = |format: DiffFormatOptions| {
+        s.get_diffs_with_options(
+            DiffTarget::Range {
+                repo_path: &repo_path,
+                from: "HEAD~1",
+                to: "HEAD",
+            },
+            None,
+            &format,
+        )
+        .unwrap()
+    };
+    let additions = |diffs: &[utils::diff::Diff]| {
+        diffs
+            .iter()
+            .find(|d| d.new_path.as_deref() == Some("main.rs"))
+            .and_then(|d| d.additions)
+    };
+    let renamed = |diffs: &[utils::diff::Diff]| {
+        diffs
+            .iter()
+            .any(|d| matches!(d.change, DiffChangeKind::Renamed))
+    };
+
+    let loose = diff(DiffFormatOptions {
+        rename_threshold: Some(50),
+        ..Default::default()
+    });
+    assert_eq!(additions(&loose), Some(1));
+    assert!(renamed(&loose));
+
+    let strict = diff(DiffFormatOptions {
+        whitespace: WhitespaceMode::IgnoreAll,
+        rename_threshold: Some(100),
+        ..Default::default()
+    });
+    assert!(additions(&strict).is_none_or(|adds| adds == 0));
+    assert!(!renamed(&strict));
+}
+
+#[test]
+fn renames_carry_their_similarity() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    let long: String = (1..=10).map(|i| format!("line {i}\n")).collect();
+    write_file(&repo_path, "old.txt", &long);
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+
+    fs::remove_file(repo_path.join("old.txt")).unwrap();
+    write_file(&repo_path, "new.txt", &long.replace("line 10", "line ten"));
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let worktree = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: Path::new(&repo_path),
+                base_commit: &base_commit,
+                include_untracked: true,
+            },
+            None,
+        )
+        .unwrap();
+    assert_eq!(worktree.len(), 1);
+    assert!(matches!(worktree[0].change, DiffChangeKind::Renamed));
+    assert_eq!(worktree[0].old_path.as_deref(), Some("old.txt"));
+    assert!(
+        worktree[0]
+            .similarity
+            .is_some_and(|score| (50..100).contains(&score))
+    );
+
+    let _ = s.commit(&repo_path, "move").unwrap();
+    let committed = s
+        .get_diffs_with_options(
+            DiffTarget::Range {
+                repo_path: &repo_path,
+                from: "main",
+                to: "feature",
+            },
+            None,
+            &DiffFormatOptions {
+                rename_threshold: Some(50),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(committed.len(), 1);
+    assert_eq!(
+        (committed[0].additions, committed[0].deletions),
+        (Some(1), Some(1))
+    );
+    assert_eq!(committed[0].similarity, Some(90));
+}
+
+#[test]
+fn file_texts_for_chunked_diffs() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "big.txt", "old\n");
+    write_file(&repo_path, "gone.txt", "bye\n");
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+    write_file(&repo_path, "big.txt", "new\n");
+    fs::remove_file(repo_path.join("gone.txt")).unwrap();
+    fs::write(repo_path.join("blob.bin"), [0u8, 1, 2]).unwrap();
+
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let texts = |path: &str, max_bytes| {
+        s.get_worktree_file_texts(&repo_path, &base_commit, Path::new(path), max_bytes)
+    };
+    assert_eq!(
+        texts("big.txt", 1024).unwrap(),
+        (Some("old\n".to_string()), Some("new\n".to_string()))
+    );
+    assert_eq!(
+        texts("gone.txt", 1024).unwrap(),
+        (Some("bye\n".to_string()), None)
+    );
+    assert!(matches!(
+        texts("blob.bin", 1024),
+        Err(GitServiceError::NotDiffable(_))
+    ));
+    assert!(matches!(
+        texts("big.txt", 2),
+        Err(GitServiceError::NotDiffable(_))
+    ));
+}
+
+#[test]
+fn commit_file_leaves_other_changes_alone() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+
+    write_file(&repo_path, "a.txt", "a2\n");
+    write_file(&repo_path, "b.txt", "b2\n");
+    add_path(&repo_path, "b.txt");
+    write_file(&repo_path, "new.txt", "new\n");
+
+    let sha = s
+        .commit_file(&repo_path, Path::new("a.txt"), "Apply suggestion")
+        .unwrap();
+    let repo = Repository::open(&repo_path).unwrap();
+    let commit = repo
+        .find_commit(git2::Oid::from_str(&sha).unwrap())
+        .unwrap();
+    assert_eq!(commit.message(), Some("Apply suggestion\n"));
+    let diff = repo
+        .diff_tree_to_tree(
+            Some(&commit.parent(0).unwrap().tree().unwrap()),
+            Some(&commit.tree().unwrap()),
+            None,
+        )
+        .unwrap();
+    let paths: Vec<_> = diff
+        .deltas()
+        .map(|d| d.new_file().path().unwrap().to_path_buf())
+        .collect();
+    assert_eq!(paths, [PathBuf::from("a.txt")]);
+
+    // The staged edit stays staged and the untracked file untracked
+    let statuses = repo.statuses(None).unwrap();
+    let status = |path: &str| {
+        statuses
+            .iter()
+            .find(|e| e.path() == Some(path))
+            .map(|e| e.status())
+    };
+    assert_eq!(status("a.txt"), None);
+    assert_eq!(status("b.txt"), Some(git2::Status::INDEX_MODIFIED));
+    assert_eq!(status("new.txt"), Some(git2::Status::WT_NEW));
+}
+
+#[test]
+fn conflicts_merged_into_worktree_are_resolved_file_by_file() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "notes.txt", "one\n");
+    write_file(&repo_path, "other.txt", "a\n");
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+
+    create_branch(&repo_path, "feature");
+    write_file(&repo_path, "notes.txt", "one on main\n");
+    write_file(&repo_path, "other.txt", "a on main\n");
+    let _ = s.commit(&repo_path, "main edit").unwrap();
+    checkout_branch(&repo_path, "feature");
+    write_file(&repo_path, "notes.txt", "one on feature\n");
+    write_file(&repo_path, "other.txt", "a on feature\n");
+    let _ = s.commit(&repo_path, "feature edit").unwrap();
+
+    let conflicted = s
+        .merge_target_into_worktree(&repo_path, "main", "Merge main")
+        .unwrap();
+    assert_eq!(conflicted, ["notes.txt", "other.txt"]);
+    assert!(
+        fs::read_to_string(repo_path.join("notes.txt"))
+            .unwrap()
+            .contains("<<<<<<<")
+    );
+    // Running it again keeps the merge that is in progress
+    assert_eq!(
+        s.merge_target_into_worktree(&repo_path, "main", "Merge main")
+            .unwrap(),
+        conflicted
+    );
+
+    write_file(&repo_path, "notes.txt", "one on both\n");
+    s.stage_resolved_file(&repo_path, Path::new("notes.txt"))
+        .unwrap();
+    write_file(&repo_path, "other.txt", "a, badly resolved\n");
+    s.restore_conflict_markers(&repo_path, Path::new("other.txt"))
+        .unwrap();
+    assert!(
+        fs::read_to_string(repo_path.join("other.txt"))
+            .unwrap()
+            .contains("<<<<<<<")
+    );
+    let err = s.continue_conflicts(&repo_path).unwrap_err();
+    assert!(matches!(
+        err,
+        GitServiceError::MergeConflicts { conflicted_files, .. } if conflicted_files == ["other.txt"]
+    ));
+
+    write_file(&repo_path, "other.txt", "a on both\n");
+    s.stage_resolved_file(&repo_path, Path::new("other.txt"))
+        .unwrap();
+    s.continue_conflicts(&repo_path).unwrap();
+    assert!(s.detect_conflict_op(&repo_path).unwrap().is_none());
+    let repo = Repository::open(&repo_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.parent_count(), 2);
+    assert_eq!(
+        fs::read_to_string(repo_path.join("notes.txt")).unwrap(),
+        "one on both\n"
+    );
+
+    // Once merged, there is nothing left to conflict
+    assert!(
+        s.merge_target_into_worktree(&repo_path, "main", "Merge main")
+            .unwrap()
+            .is_empty()
+    );
+}
+
 #[test]
 fn worktree_diff_respects_path_filter() {
     // Use git CLI status diff under the hood
@@ -379,6 +925,7 @@ fn worktree_diff_respects_path_filter() {
             DiffTarget::Worktree {
                 worktree_path: Path::new(&repo_path),
                 base_commit: &base_commit,
+                include_untracked: true,
             },
             Some(&["src"]),
         )
@@ -395,6 +942,60 @@ fn worktree_diff_respects_path_filter() {
     );
 }
 
+#[test]
+fn worktree_diff_reports_where_each_change_is() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "committed.txt", "a\n");
+    write_file(&repo_path, "staged.txt", "b\n");
+    write_file(&repo_path, "unstaged.txt", "c\n");
+    let s = GitService::new();
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+
+    write_file(&repo_path, "committed.txt", "a2\n");
+    let _ = s.commit(&repo_path, "feature work").unwrap();
+    write_file(&repo_path, "staged.txt", "b2\n");
+    add_path(&repo_path, "staged.txt");
+    write_file(&repo_path, "unstaged.txt", "c2\n");
+    write_file(&repo_path, "untracked.txt", "d\n");
+
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let states = |include_untracked| {
+        let mut states: Vec<_> = s
+            .get_diffs(
+                DiffTarget::Worktree {
+                    worktree_path: Path::new(&repo_path),
+                    base_commit: &base_commit,
+                    include_untracked,
+                },
+                None,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.new_path.unwrap(), d.state.unwrap()))
+            .collect();
+        states.sort_by(|a, b| a.0.cmp(&b.0));
+        states
+    };
+
+    assert_eq!(
+        states(true),
+        [
+            ("committed.txt".to_string(), DiffChangeState::Committed),
+            ("staged.txt".to_string(), DiffChangeState::Staged),
+            ("unstaged.txt".to_string(), DiffChangeState::Unstaged),
+            ("untracked.txt".to_string(), DiffChangeState::Untracked),
+        ]
+    );
+    assert!(
+        states(false)
+            .iter()
+            .all(|(_, state)| *state != DiffChangeState::Untracked)
+    );
+}
+
 #[test]
 fn get_branch_oid_nonexistent_errors() {
     let td = TempDir::new().unwrap();
@@ -451,6 +1052,7 @@ fn worktree_diff_permission_only_change() {
             DiffTarget::Worktree {
                 worktree_path: Path::new(&repo_path),
                 base_commit: &base_commit,
+                include_untracked: true,
             },
             None,
         )
@@ -520,3 +1122,123 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+#[test]
+fn inspect_worktree_reports_branch_lock_and_missing_directory() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let worktree_path = td.path().join("wt_feature");
+    let s = GitService::new();
+
+    create_branch(&repo_path, "feature");
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+
+    let health = s.inspect_worktree(&repo_path, &worktree_path).unwrap();
+    assert!(health.exists && health.git_link_valid && health.registered);
+    assert_eq!(health.branch.as_deref(), Some("feature"));
+    assert!(!health.locked && !health.prunable);
+
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(&repo_path)
+        .args(["worktree", "lock"])
+        .arg(&worktree_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(
+        s.inspect_worktree(&repo_path, &worktree_path)
+            .unwrap()
+            .locked
+    );
+
+    let missing = td.path().join("wt_missing");
+    s.add_worktree(&repo_path, &missing, "missing", true)
+        .unwrap();
+    fs::remove_dir_all(&missing).unwrap();
+    let health = s.inspect_worktree(&repo_path, &missing).unwrap();
+    assert!(!health.exists && !health.git_link_valid);
+    assert!(health.registered && health.prunable);
+}
+
+#[test]
+fn merge_diff_shows_only_what_lands_on_a_moved_target() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "shared.txt", "one\n");
+    s.commit(&repo_path, "add shared").unwrap();
+
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+    write_file(&repo_path, "feature.txt", "feature\n");
+    s.commit(&repo_path, "add feature").unwrap();
+
+    // The target moves on after the branch was made
+    checkout_branch(&repo_path, "main");
+    write_file(&repo_path, "shared.txt", "two\n");
+    s.commit(&repo_path, "move main").unwrap();
+    let main_before = s.get_branch_oid(&repo_path, "main").unwrap();
+
+    let target = || DiffTarget::Merge {
+        repo_path: Path::new(&repo_path),
+        branch_name: "feature",
+        base_branch: "main",
+    };
+    let diffs = s.get_diffs(target(), None).unwrap();
+    let paths: Vec<_> = diffs.iter().map(GitService::diff_path).collect();
+    assert_eq!(paths, vec!["feature.txt".to_string()]);
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), main_before);
+
+    checkout_branch(&repo_path, "feature");
+    write_file(&repo_path, "shared.txt", "three\n");
+    s.commit(&repo_path, "conflicting change").unwrap();
+    match s.get_diffs(target(), None) {
+        Err(GitServiceError::MergeConflicts {
+            conflicted_files, ..
+        }) => assert_eq!(conflicted_files, vec!["shared.txt".to_string()]),
+        other => panic!("expected merge conflicts, got {other:?}"),
+    }
+}
+
+#[test]
+fn branches_are_pushed_to_the_named_remote() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    s.commit(&repo_path, "add a").unwrap();
+    let origin = td.path().join("origin.git");
+    let fork = td.path().join("fork.git");
+    Repository::init_bare(&origin).unwrap();
+    Repository::init_bare(&fork).unwrap();
+    s.add_remote(&repo_path, "origin", &origin.to_string_lossy())
+        .unwrap();
+    s.add_remote(&repo_path, "fork", &fork.to_string_lossy())
+        .unwrap();
+    assert_eq!(
+        s.get_remote(&repo_path, "fork").unwrap().url,
+        fork.to_string_lossy()
+    );
+
+    s.push_to_remote(&repo_path, Some("fork"), "main", false)
+        .unwrap();
+    let head = s.get_branch_oid(&repo_path, "main").unwrap();
+    let pushed = Repository::open_bare(&fork).unwrap();
+    assert_eq!(
+        pushed.refname_to_id("refs/heads/main").unwrap().to_string(),
+        head
+    );
+    let untouched = Repository::open_bare(&origin).unwrap();
+    assert!(untouched.find_reference("refs/heads/main").is_err());
+    let repo = Repository::open(&repo_path).unwrap();
+    assert!(repo.find_reference("refs/remotes/fork/main").is_ok());
+
+    s.remove_remote(&repo_path, "fork").unwrap();
+    assert!(s.get_remote(&repo_path, "fork").is_err());
+    assert!(
+        s.push_to_remote(&repo_path, Some("fork"), "main", false)
+            .is_err()
+    );
+}
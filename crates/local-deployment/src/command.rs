@@ -8,6 +8,12 @@ use services::services::container::ContainerError;
 #[cfg(unix)]
 use tokio::time::Duration;
 
+/// How long the process group gets to exit after each signal before the next, harsher one
+#[cfg(unix)]
+const SIGNAL_GRACE: Duration = Duration::from_secs(2);
+
+/// Stop the child's whole process group, escalating from SIGINT to SIGTERM to SIGKILL until
+/// every process in it, not just the leader, has exited
 pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), ContainerError> {
     // hit the whole process group, not just the leader
     #[cfg(unix)]
@@ -26,14 +32,12 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
                         e
                     );
                 }
-                tracing::info!("Waiting 2s for process group {} to exit", pgid);
-                tokio::time::sleep(Duration::from_secs(2)).await;
-                if child
-                    .inner()
-                    .try_wait()
-                    .map_err(ContainerError::Io)?
-                    .is_some()
-                {
+                tracing::info!(
+                    "Waiting {:?} for process group {} to exit",
+                    SIGNAL_GRACE,
+                    pgid
+                );
+                if wait_for_group_exit(child, pgid, SIGNAL_GRACE).await? {
                     tracing::info!("Process group {} exited after {:?}", pgid, sig);
                     break;
                 }
@@ -45,3 +49,62 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
     let _ = child.wait().await;
     Ok(())
 }
+
+/// Wait up to `grace` for the leader and every other process of the group to exit. Build
+/// tools and dev servers started by the leader stay in its group after it exits.
+#[cfg(unix)]
+async fn wait_for_group_exit(
+    child: &mut AsyncGroupChild,
+    pgid: Pid,
+    grace: Duration,
+) -> Result<bool, ContainerError> {
+    let deadline = tokio::time::Instant::now() + grace;
+    loop {
+        // Reap the leader first, so it is not counted as still in the group
+        let leader_exited = child
+            .inner()
+            .try_wait()
+            .map_err(ContainerError::Io)?
+            .is_some();
+        if leader_exited && killpg(pgid, None).is_err() {
+            return Ok(true);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Ok(false);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use command_group::AsyncCommandGroup;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn processes_left_in_the_group_are_killed_with_the_leader() {
+        // The leader exits on SIGINT, leaving behind a child that ignores SIGINT and SIGTERM
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg("(trap '' INT TERM; echo ready; exec sleep 30) & wait")
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .group_spawn()
+            .unwrap();
+        let mut stdout = BufReader::new(child.inner().stdout.take().unwrap());
+        let mut ready = String::new();
+        stdout.read_line(&mut ready).await.unwrap();
+        assert_eq!(ready, "ready\n");
+
+        kill_process_group(&mut child).await.unwrap();
+
+        // The pipe only closes once every process holding it has exited
+        let mut rest = Vec::new();
+        tokio::time::timeout(Duration::from_secs(1), stdout.read_to_end(&mut rest))
+            .await
+            .expect("a process of the group outlived the kill")
+            .unwrap();
+    }
+}
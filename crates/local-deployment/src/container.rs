@@ -13,42 +13,63 @@ use db::{
     DBService,
     models::{
         coding_agent_turn::CodingAgentTurn,
+        conflict_resolution::ConflictResolutionStatus,
         execution_process::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
         },
+        execution_process_pid::ExecutionProcessPid,
+        execution_process_policy::ExecutionProcessPolicy,
         execution_process_repo_state::ExecutionProcessRepoState,
+        project_agent_policy::ProjectAgentPolicy,
+        project_remote_host::ProjectRemoteHost,
+        project_repo::ProjectRepo,
         repo::Repo,
-        scratch::{DraftFollowUpData, Scratch, ScratchType},
-        session::{Session, SessionError},
+        scratch::{Scratch, ScratchPayload, ScratchType},
         task::{Task, TaskStatus},
         workspace::Workspace,
-        workspace_repo::WorkspaceRepo,
+        workspace_pipeline_stage::{PipelineStageStatus, WorkspacePipelineStage},
+        workspace_repo::{RepoWithTargetBranch, WorkspaceRepo},
     },
 };
 use deployment::DeploymentError;
 use executors::{
-    actions::{
-        Executable, ExecutorAction, ExecutorActionType,
-        coding_agent_follow_up::CodingAgentFollowUpRequest,
-        coding_agent_initial::CodingAgentInitialRequest,
-    },
+    actions::{Executable, ExecutorAction},
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
     env::{ExecutionEnv, RepoContext},
     executors::{BaseCodingAgent, CancellationToken, ExecutorExitResult, ExecutorExitSignal},
-    logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
+    logs::{NormalizedEntryType, TurnUsage, utils::patch::extract_normalized_entry_from_patch},
+    remote::RemoteHost,
+    sandbox::Sandbox,
 };
 use futures::{FutureExt, TryStreamExt, stream::select};
 use git::GitService;
 use serde_json::json;
 use services::services::{
+    agent_pipeline::AgentPipelineService,
+    agent_plan::AgentPlanService,
+    ai_review::AiReviewService,
     analytics::AnalyticsContext,
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
+    auto_sync::AutoSyncService,
     config::Config,
+    conflict_resolution::ConflictResolutionService,
     container::{ContainerError, ContainerRef, ContainerService},
+    cost_report::CostReportService,
     diff_stream::{self, DiffStreamHandle},
+    env_vars::{EnvVarService, layer_env},
+    execution_artifacts::ArtifactService,
     image::ImageService,
+    log_retention::LogRetentionService,
     notification::NotificationService,
+    output_redaction::{OutputRedactor, PARTIAL_LINE_FLUSH},
+    process_input::{INPUT_IDLE_TIMEOUT, ProcessInput},
+    process_reaper::{
+        ProcessReaperReport, ProcessReaperService, REAPER_INTERVAL, ReapAction, ReapedProcess,
+    },
     queued_message::QueuedMessageService,
+    secrets::SecretService,
+    settings::SettingsService,
+    target_drift::TargetDriftTracker,
     workspace_manager::{RepoWorkspaceInput, WorkspaceManager},
 };
 use tokio::{sync::RwLock, task::JoinHandle};
@@ -60,13 +81,17 @@ use utils::{
 };
 use uuid::Uuid;
 
-use crate::{command, copy};
+use crate::{
+    command, copy,
+    dev_server_ports::{DevServerPorts, PORT_ENV_VAR, VK_PORT_ENV_VAR},
+};
 
 #[derive(Clone)]
 pub struct LocalContainerService {
     db: DBService,
     child_store: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AsyncGroupChild>>>>>,
     cancellation_tokens: Arc<RwLock<HashMap<Uuid, CancellationToken>>>,
+    process_inputs: Arc<RwLock<HashMap<Uuid, ProcessInput>>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
     /// Tracks background tasks that stream logs to the database.
     /// When stopping execution, we await these to ensure logs are fully persisted.
@@ -79,6 +104,9 @@ pub struct LocalContainerService {
     approvals: Approvals,
     queued_message_service: QueuedMessageService,
     notification_service: NotificationService,
+    dev_server_ports: DevServerPorts,
+    settings: SettingsService,
+    reaper_report: Arc<RwLock<Option<ProcessReaperReport>>>,
 }
 
 impl LocalContainerService {
@@ -92,17 +120,20 @@ impl LocalContainerService {
         analytics: Option<AnalyticsContext>,
         approvals: Approvals,
         queued_message_service: QueuedMessageService,
+        settings: SettingsService,
     ) -> Self {
         let child_store = Arc::new(RwLock::new(HashMap::new()));
         let cancellation_tokens = Arc::new(RwLock::new(HashMap::new()));
+        let process_inputs = Arc::new(RwLock::new(HashMap::new()));
         let db_stream_handles = Arc::new(RwLock::new(HashMap::new()));
         let exit_monitor_handles = Arc::new(RwLock::new(HashMap::new()));
-        let notification_service = NotificationService::new(config.clone());
+        let notification_service = NotificationService::new(config.clone(), settings.subscribe());
 
         let container = LocalContainerService {
             db,
             child_store,
             cancellation_tokens,
+            process_inputs,
             msg_stores,
             db_stream_handles,
             exit_monitor_handles,
@@ -113,13 +144,26 @@ impl LocalContainerService {
             approvals,
             queued_message_service,
             notification_service,
+            dev_server_ports: DevServerPorts::new(),
+            settings,
+            reaper_report: Arc::new(RwLock::new(None)),
         };
 
         container.spawn_workspace_cleanup();
+        container.spawn_orphan_cleanup();
+        container.spawn_log_retention();
+        container.spawn_process_reaper();
+        container.spawn_target_drift_check();
+        container.spawn_auto_sync();
+        container.spawn_repo_fetch();
 
         container
     }
 
+    pub fn dev_server_ports(&self) -> &DevServerPorts {
+        &self.dev_server_ports
+    }
+
     pub async fn get_child_from_store(&self, id: &Uuid) -> Option<Arc<RwLock<AsyncGroupChild>>> {
         let map = self.child_store.read().await;
         map.get(id).cloned()
@@ -145,6 +189,16 @@ impl LocalContainerService {
         map.remove(id)
     }
 
+    async fn add_process_input(&self, id: Uuid, input: ProcessInput) {
+        let mut map = self.process_inputs.write().await;
+        map.insert(id, input);
+    }
+
+    async fn remove_process_input(&self, id: &Uuid) {
+        let mut map = self.process_inputs.write().await;
+        map.remove(id);
+    }
+
     async fn add_db_stream_handle(&self, id: Uuid, handle: JoinHandle<()>) {
         let mut map = self.db_stream_handles.write().await;
         map.insert(id, handle);
@@ -201,15 +255,24 @@ impl LocalContainerService {
         let _ = Workspace::clear_container_ref(&db.pool, workspace.id).await;
     }
 
-    pub async fn cleanup_expired_workspaces(db: &DBService) -> Result<(), DeploymentError> {
-        if std::env::var("DISABLE_WORKTREE_CLEANUP").is_ok() {
+    pub async fn cleanup_expired_workspaces(
+        db: &DBService,
+        settings: &SettingsService,
+    ) -> Result<(), DeploymentError> {
+        if settings.disable_worktree_cleanup() {
             tracing::info!(
-                "Expired workspace cleanup is disabled via DISABLE_WORKTREE_CLEANUP environment variable"
+                "Expired workspace cleanup is disabled by the disable_worktree_cleanup setting"
             );
             return Ok(());
         }
 
-        let expired_workspaces = Workspace::find_expired_for_cleanup(&db.pool).await?;
+        let current = settings.get();
+        let expired_workspaces = Workspace::find_expired_for_cleanup(
+            &db.pool,
+            current.workspace_retention_hours,
+            current.archived_workspace_retention_hours,
+        )
+        .await?;
         if expired_workspaces.is_empty() {
             tracing::debug!("No expired workspaces found");
             return Ok(());
@@ -226,22 +289,234 @@ impl LocalContainerService {
 
     pub fn spawn_workspace_cleanup(&self) {
         let db = self.db.clone();
+        let settings = self.settings.clone();
         let cleanup_expired = Self::cleanup_expired_workspaces;
         tokio::spawn(async move {
-            WorkspaceManager::cleanup_orphan_workspaces(&db.pool).await;
-
+            let mut settings_rx = settings.subscribe();
             let mut cleanup_interval =
                 tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
             loop {
-                cleanup_interval.tick().await;
+                // Re-run as soon as settings change so shorter retention applies right away
+                tokio::select! {
+                    _ = cleanup_interval.tick() => {}
+                    Ok(()) = settings_rx.changed() => {}
+                }
                 tracing::info!("Starting periodic workspace cleanup...");
-                cleanup_expired(&db).await.unwrap_or_else(|e| {
+                cleanup_expired(&db, &settings).await.unwrap_or_else(|e| {
                     tracing::error!("Failed to clean up expired workspaces: {}", e)
                 });
             }
         });
     }
 
+    /// Sweep for orphaned workspace directories at startup and then every configured interval
+    pub fn spawn_orphan_cleanup(&self) {
+        let db = self.db.clone();
+        let settings = self.settings.clone();
+        tokio::spawn(async move {
+            let mut settings_rx = settings.subscribe();
+            loop {
+                let last_run = tokio::time::Instant::now();
+                WorkspaceManager::cleanup_orphan_workspaces(&db.pool, &settings).await;
+
+                // A changed interval moves the next sweep without triggering one early
+                loop {
+                    let interval = tokio::time::Duration::from_secs(
+                        u64::from(settings.get().orphan_cleanup_interval_hours) * 3600,
+                    );
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(last_run + interval) => break,
+                        Ok(()) = settings_rx.changed() => {}
+                    }
+                }
+            }
+        });
+    }
+
+    /// Periodically compact the stored output of processes past the log retention period
+    pub fn spawn_log_retention(&self) {
+        let db = self.db.clone();
+        let settings = self.settings.clone();
+        tokio::spawn(async move {
+            let mut settings_rx = settings.subscribe();
+            let mut retention_interval =
+                tokio::time::interval(tokio::time::Duration::from_secs(3600)); // 1 hour
+            loop {
+                tokio::select! {
+                    _ = retention_interval.tick() => {}
+                    Ok(()) = settings_rx.changed() => {}
+                }
+                match LogRetentionService::run(&db.pool, &settings.get()).await {
+                    Ok(summary) if summary.processes > 0 => tracing::info!(
+                        "Compacted logs of {} execution processes, removing {} bytes",
+                        summary.processes,
+                        summary.removed_bytes
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Failed to apply log retention: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Periodically reconcile executions marked running with the processes running them
+    pub fn spawn_process_reaper(&self) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            let mut reaper_interval = tokio::time::interval(REAPER_INTERVAL);
+            // Startup has already reconciled what the last server run left behind
+            reaper_interval.tick().await;
+            loop {
+                reaper_interval.tick().await;
+                match container.reap_processes().await {
+                    Ok(report) if !report.reaped.is_empty() => tracing::info!(
+                        "Process reaper handled {} of {} running executions: {:?}",
+                        report.reaped.len(),
+                        report.running,
+                        report.reaped
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Failed to reap execution processes: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Periodically check how far the target branches of open workspaces have moved on,
+    /// notifying once a repo drifts past the configured threshold
+    pub fn spawn_target_drift_check(&self) {
+        let db = self.db.clone();
+        let git = self.git.clone();
+        let settings = self.settings.clone();
+        let notification_service = self.notification_service.clone();
+        tokio::spawn(async move {
+            let mut tracker = TargetDriftTracker::default();
+            let mut drift_interval = tokio::time::interval(Duration::from_secs(15 * 60));
+            loop {
+                drift_interval.tick().await;
+                let Some(threshold) = settings.target_drift_threshold_commits() else {
+                    continue;
+                };
+                let alerts = match tracker.check(&db.pool, &git, threshold).await {
+                    Ok(alerts) => alerts,
+                    Err(e) => {
+                        tracing::error!("Failed to check target branch drift: {}", e);
+                        continue;
+                    }
+                };
+                for alert in alerts {
+                    notification_service
+                        .notify(
+                            "Workspace needs a rebase",
+                            &format!(
+                                "'{}' of {} has gained {} commits since workspace branch '{}' \
+                                 was made",
+                                alert.drift.target_branch,
+                                alert.drift.repo_name,
+                                alert.drift.commits_behind,
+                                alert.workspace_branch
+                            ),
+                        )
+                        .await;
+                }
+            }
+        });
+    }
+
+    /// Periodically bring open workspaces of projects with an auto sync strategy up to date
+    /// with their target branches, notifying when a sync conflicts
+    pub fn spawn_auto_sync(&self) {
+        let db = self.db.clone();
+        let git = self.git.clone();
+        let notification_service = self.notification_service.clone();
+        tokio::spawn(async move {
+            let mut auto_sync = AutoSyncService::default();
+            let mut sync_interval = tokio::time::interval(Duration::from_secs(5 * 60));
+            loop {
+                sync_interval.tick().await;
+                let report = match auto_sync.run(&db.pool, &git).await {
+                    Ok(report) => report,
+                    Err(e) => {
+                        tracing::error!("Failed to sync workspaces automatically: {}", e);
+                        continue;
+                    }
+                };
+                if !report.synced.is_empty() {
+                    tracing::info!(
+                        "Synced {} workspace repos with their target branches",
+                        report.synced.len()
+                    );
+                }
+                for repo in report.conflicted {
+                    notification_service
+                        .notify(
+                            "Automatic sync conflicted",
+                            &format!(
+                                "Syncing workspace branch '{}' with '{}' in {} conflicted and was \
+                                 aborted",
+                                repo.workspace_branch, repo.target_branch, repo.repo_name
+                            ),
+                        )
+                        .await;
+                }
+            }
+        });
+    }
+
+    /// Fetch the remotes of every registered repo that has not opted out, so branch status
+    /// and target branches reflect the remote without a manual fetch
+    async fn fetch_registered_repos(db: &DBService, git: &GitService) {
+        let repos = match Repo::list_all(&db.pool).await {
+            Ok(repos) => repos,
+            Err(e) => {
+                tracing::error!("Failed to list repos to fetch: {}", e);
+                return;
+            }
+        };
+        let mut fetched = 0;
+        for repo in repos {
+            if !repo.auto_fetch || !repo.path.exists() {
+                continue;
+            }
+            let git = git.clone();
+            let path = repo.path.clone();
+            match tokio::task::spawn_blocking(move || git.fetch_all_remotes(&path)).await {
+                Ok(Ok(())) => fetched += 1,
+                Ok(Err(e)) => tracing::warn!("Failed to fetch repo '{}': {}", repo.name, e),
+                Err(e) => tracing::warn!("Fetch task for repo '{}' failed: {}", repo.name, e),
+            }
+        }
+        tracing::debug!("Fetched {} registered repos", fetched);
+    }
+
+    /// Fetch registered repos every configured interval; nothing is fetched while the
+    /// interval is unset
+    pub fn spawn_repo_fetch(&self) {
+        let db = self.db.clone();
+        let git = self.git.clone();
+        let settings = self.settings.clone();
+        tokio::spawn(async move {
+            let mut settings_rx = settings.subscribe();
+            let mut last_run = tokio::time::Instant::now();
+            loop {
+                // A changed interval moves the next fetch without triggering one early
+                let Some(minutes) = settings.repo_fetch_interval_minutes() else {
+                    if settings_rx.changed().await.is_err() {
+                        return;
+                    }
+                    continue;
+                };
+                let interval = Duration::from_secs(u64::from(minutes) * 60);
+                tokio::select! {
+                    _ = tokio::time::sleep_until(last_run + interval) => {}
+                    Ok(()) = settings_rx.changed() => continue,
+                }
+                last_run = tokio::time::Instant::now();
+                Self::fetch_registered_repos(&db, &git).await;
+            }
+        });
+    }
+
     /// Record the current HEAD commit for each repository as the "after" state.
     /// Errors are silently ignored since this runs after the main execution completes
     /// and failure should not block process finalization.
@@ -321,6 +596,13 @@ impl LocalContainerService {
         for repo in repos {
             let worktree_path = workspace_root.join(&repo.name);
 
+            // A merge or rebase stopped on conflicts is finished through the conflict
+            // resolution endpoints, not folded into an automatic commit
+            if matches!(git.detect_conflict_op(&worktree_path), Ok(Some(_))) {
+                tracing::debug!("Leaving conflicts in repo '{}' to be resolved", repo.name);
+                continue;
+            }
+
             match git.is_worktree_clean(&worktree_path) {
                 Ok(false) => {
                     // false = dirty = has changes
@@ -399,6 +681,57 @@ impl LocalContainerService {
         any_committed
     }
 
+    /// Copy back what a process changed in the remote host's copy of its workspace
+    async fn sync_from_remote_host(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
+        let Some(container_ref) = &ctx.workspace.container_ref else {
+            return Ok(());
+        };
+        let Some(host) =
+            ProjectRemoteHost::find_by_project_id(&self.db.pool, ctx.project.id).await?
+        else {
+            return Ok(());
+        };
+        RemoteHost::from(host)
+            .sync_down(Path::new(container_ref))
+            .await
+            .map_err(|e| ContainerError::Other(e.into()))
+    }
+
+    /// Keep the files the process's action declares as artifacts
+    async fn collect_artifacts(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
+        let Some(container_ref) = &ctx.workspace.container_ref else {
+            return Ok(());
+        };
+        let action = ctx.execution_process.executor_action()?;
+        let patterns = action.artifact_patterns();
+        if patterns.is_empty() {
+            return Ok(());
+        }
+        let root = match action.working_dir() {
+            Some(dir) => Path::new(container_ref).join(dir),
+            None => PathBuf::from(container_ref),
+        };
+        let store = self
+            .settings
+            .artifact_store()
+            .map_err(|e| ContainerError::Other(e.into()))?;
+        let artifacts = ArtifactService::collect(
+            &self.db.pool,
+            &store,
+            ctx.execution_process.id,
+            root,
+            patterns,
+        )
+        .await
+        .map_err(|e| ContainerError::Other(e.into()))?;
+        tracing::info!(
+            "Collected {} artifacts of execution {}",
+            artifacts.len(),
+            ctx.execution_process.id
+        );
+        Ok(())
+    }
+
     /// Spawn a background task that polls the child process for completion and
     /// cleans up the execution entry when it exits.
     pub fn spawn_exit_monitor(
@@ -471,16 +804,42 @@ impl LocalContainerService {
             }
 
             if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
+                if let Err(e) = container.sync_from_remote_host(&ctx).await {
+                    tracing::error!("Failed to sync workspace back from remote host: {}", e);
+                }
+                if let Err(e) = container.collect_artifacts(&ctx).await {
+                    tracing::warn!(
+                        "Failed to collect artifacts of execution {}: {}",
+                        exec_id,
+                        e
+                    );
+                }
                 // Update executor session summary if available
                 if let Err(e) = container.update_executor_session_summary(&exec_id).await {
                     tracing::warn!("Failed to update executor session summary: {}", e);
                 }
+                if let Err(e) = container.record_ai_review_findings(&ctx).await {
+                    tracing::warn!("Failed to record agent review findings: {}", e);
+                }
+                if let Err(e) = container.record_conflict_resolutions(&ctx).await {
+                    tracing::warn!("Failed to record conflict resolutions: {}", e);
+                }
+                if let Err(e) = container.record_agent_plan(&ctx).await {
+                    tracing::warn!("Failed to record agent plan: {}", e);
+                }
+                if let Err(e) = container.record_turn_usage(&exec_id).await {
+                    tracing::warn!("Failed to record coding agent turn usage: {}", e);
+                }
 
                 let success = matches!(
                     ctx.execution_process.status,
                     ExecutionProcessStatus::Completed
                 ) && exit_code == Some(0);
 
+                // A pipeline stage that succeeded hands over to the next stage instead of
+                // finalizing the task
+                let next_pipeline_stage = container.finish_pipeline_stage(&ctx, success).await;
+
                 let cleanup_done = matches!(
                     ctx.execution_process.run_reason,
                     ExecutionProcessRunReason::CleanupScript
@@ -526,14 +885,18 @@ impl LocalContainerService {
                         );
 
                         // Manually finalize task since we're bypassing normal execution flow
-                        container.finalize_task(&ctx).await;
+                        if next_pipeline_stage.is_none() {
+                            container.finalize_task(&ctx).await;
 
-                        // Try Ralph auto-continue if conditions are met
-                        container.try_ralph_auto_continue(&ctx).await;
+                            // Try Ralph auto-continue if conditions are met
+                            container.try_ralph_auto_continue(&ctx).await;
+                        }
                     }
                 }
 
-                if container.should_finalize(&ctx) {
+                if let Some(stage) = &next_pipeline_stage {
+                    container.start_next_pipeline_stage(&ctx, stage).await;
+                } else if container.should_finalize(&ctx) {
                     // Only execute queued messages if the execution succeeded
                     // If it failed or was killed, just clear the queue and finalize
                     let should_execute_queued = !matches!(
@@ -550,13 +913,24 @@ impl LocalContainerService {
                                 ctx.session.id
                             );
 
-                            // Delete the scratch since we're consuming the queued message
-                            if let Err(e) = Scratch::delete(
+                            // Delete the draft the queued message was made from, if it was
+                            let draft = Scratch::find_by_id(
                                 &db.pool,
                                 ctx.session.id,
                                 &ScratchType::DraftFollowUp,
                             )
-                            .await
+                            .await;
+                            if let Ok(Some(Scratch {
+                                payload: ScratchPayload::DraftFollowUp(draft),
+                                ..
+                            })) = draft
+                                && draft.message == queued_msg.data.message
+                                && let Err(e) = Scratch::delete(
+                                    &db.pool,
+                                    ctx.session.id,
+                                    &ScratchType::DraftFollowUp,
+                                )
+                                .await
                             {
                                 tracing::warn!(
                                     "Failed to delete scratch after consuming queued message: {}",
@@ -566,7 +940,11 @@ impl LocalContainerService {
 
                             // Execute the queued follow-up
                             if let Err(e) = container
-                                .start_queued_follow_up(&ctx, &queued_msg.data)
+                                .start_queued_follow_up(
+                                    &ctx.workspace,
+                                    &ctx.session,
+                                    &queued_msg.data,
+                                )
                                 .await
                             {
                                 tracing::error!("Failed to start queued follow-up: {}", e);
@@ -574,9 +952,14 @@ impl LocalContainerService {
                                 container.finalize_task(&ctx).await;
                             }
                         } else {
-                            // Execution failed or was killed - discard the queued message and finalize
+                            // Execution failed or was killed - discard the queued messages and finalize
+                            let discarded = 1 + container
+                                .queued_message_service
+                                .cancel_queued(ctx.session.id)
+                                .len();
                             tracing::info!(
-                                "Discarding queued message for session {} due to execution status {:?}",
+                                "Discarding {} queued message(s) for session {} due to execution status {:?}",
+                                discarded,
                                 ctx.session.id,
                                 ctx.execution_process.status
                             );
@@ -624,6 +1007,8 @@ impl LocalContainerService {
 
             // Cleanup child handle
             child_store.write().await.remove(&exec_id);
+            container.remove_process_input(&exec_id).await;
+            container.dev_server_ports.release(&exec_id).await;
         })
     }
 
@@ -669,19 +1054,31 @@ impl LocalContainerService {
         format!("{}-{}", short_uuid(workspace_id), task_title_id)
     }
 
-    async fn track_child_msgs_in_store(&self, id: Uuid, child: &mut AsyncGroupChild) {
+    async fn track_child_msgs_in_store(
+        &self,
+        id: Uuid,
+        child: &mut AsyncGroupChild,
+        redactor: Arc<OutputRedactor>,
+    ) {
         let store = Arc::new(MsgStore::new());
 
         let out = child.inner().stdout.take().expect("no stdout");
         let err = child.inner().stderr.take().expect("no stderr");
 
-        // Map stdout bytes -> LogMsg::Stdout
-        let out = ReaderStream::new(out)
-            .map_ok(|chunk| LogMsg::Stdout(String::from_utf8_lossy(&chunk).into_owned()));
-
-        // Map stderr bytes -> LogMsg::Stderr
-        let err = ReaderStream::new(err)
-            .map_ok(|chunk| LogMsg::Stderr(String::from_utf8_lossy(&chunk).into_owned()));
+        // Map stdout bytes -> LogMsg::Stdout, with secrets masked
+        let out =
+            ReaderStream::new(out).map_ok(|chunk| String::from_utf8_lossy(&chunk).into_owned());
+        let out = redactor
+            .clone()
+            .redact_stream(out, PARTIAL_LINE_FLUSH)
+            .map_ok(LogMsg::Stdout);
+
+        // Map stderr bytes -> LogMsg::Stderr, with secrets masked
+        let err =
+            ReaderStream::new(err).map_ok(|chunk| String::from_utf8_lossy(&chunk).into_owned());
+        let err = redactor
+            .redact_stream(err, PARTIAL_LINE_FLUSH)
+            .map_ok(LogMsg::Stderr);
 
         // If you have a JSON Patch source, map it to LogMsg::JsonPatch too, then select all three.
 
@@ -735,6 +1132,44 @@ impl LocalContainerService {
         None
     }
 
+    /// Extract the turn totals from the last token usage entry that reports them
+    fn extract_turn_usage(&self, exec_id: &Uuid) -> Option<TurnUsage> {
+        let msg_stores = self.msg_stores.try_read().ok()?;
+        let msg_store = msg_stores.get(exec_id)?;
+
+        msg_store.get_history().iter().rev().find_map(|msg| {
+            let LogMsg::JsonPatch(patch) = msg else {
+                return None;
+            };
+            match extract_normalized_entry_from_patch(patch)?.1.entry_type {
+                NormalizedEntryType::TokenUsageInfo(info) => info.turn_usage,
+                _ => None,
+            }
+        })
+    }
+
+    /// Store the usage the agent reported for the turn and notify when it takes the month's
+    /// spend over the configured budget
+    async fn record_turn_usage(&self, exec_id: &Uuid) -> Result<(), anyhow::Error> {
+        let Some(usage) = self.extract_turn_usage(exec_id) else {
+            return Ok(());
+        };
+        let budget = self.settings.monthly_budget_usd();
+        let exceeded =
+            CostReportService::record_turn_usage(&self.db.pool, *exec_id, &usage, budget).await?;
+        if let (Some(month_total), Some(budget)) = (exceeded, budget) {
+            self.notification_service
+                .notify(
+                    "Monthly budget exceeded",
+                    &format!(
+                        "Coding agents have spent ${month_total:.2} this month, over the ${budget} budget"
+                    ),
+                )
+                .await;
+        }
+        Ok(())
+    }
+
     /// Update the coding agent turn summary with the final assistant message
     async fn update_executor_session_summary(&self, exec_id: &Uuid) -> Result<(), anyhow::Error> {
         // Check if there's a coding agent turn for this execution process
@@ -754,6 +1189,136 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Store the findings of an agent review from the last message of its process
+    async fn record_ai_review_findings(&self, ctx: &ExecutionContext) -> Result<(), anyhow::Error> {
+        if ctx.execution_process.run_reason != ExecutionProcessRunReason::AiReview {
+            return Ok(());
+        }
+        let exec_id = ctx.execution_process.id;
+        let succeeded = matches!(
+            ctx.execution_process.status,
+            ExecutionProcessStatus::Completed
+        );
+        let summary = CodingAgentTurn::find_by_execution_process_id(&self.db.pool, exec_id)
+            .await?
+            .and_then(|turn| turn.summary);
+        if let Some(review) =
+            AiReviewService::record_findings(&self.db.pool, exec_id, succeeded, summary.as_deref())
+                .await?
+        {
+            tracing::info!(
+                "Agent review {} of workspace {} finished: {:?}",
+                review.id,
+                review.workspace_id,
+                review.status
+            );
+        }
+        Ok(())
+    }
+
+    /// Store the last message of a plan process as the proposed plan of its plan turn
+    async fn record_agent_plan(&self, ctx: &ExecutionContext) -> Result<(), anyhow::Error> {
+        if ctx.execution_process.run_reason != ExecutionProcessRunReason::Plan {
+            return Ok(());
+        }
+        let exec_id = ctx.execution_process.id;
+        let succeeded = matches!(
+            ctx.execution_process.status,
+            ExecutionProcessStatus::Completed
+        );
+        let summary = CodingAgentTurn::find_by_execution_process_id(&self.db.pool, exec_id)
+            .await?
+            .and_then(|turn| turn.summary);
+        if let Some(plan) =
+            AgentPlanService::record_plan(&self.db.pool, exec_id, succeeded, summary.as_deref())
+                .await?
+        {
+            tracing::info!(
+                "Plan {} of workspace {} finished: {:?}",
+                plan.id,
+                plan.workspace_id,
+                plan.status
+            );
+        }
+        Ok(())
+    }
+
+    /// Record the end of an agent pipeline stage, returning the stage to start next
+    async fn finish_pipeline_stage(
+        &self,
+        ctx: &ExecutionContext,
+        succeeded: bool,
+    ) -> Option<WorkspacePipelineStage> {
+        AgentPipelineService::finish_stage(&self.db.pool, ctx.execution_process.id, succeeded)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to record agent pipeline stage: {}", e);
+                None
+            })
+    }
+
+    /// Start the next stage of a workspace's agent pipeline, ending the pipeline and
+    /// finalizing the task if it cannot start
+    async fn start_next_pipeline_stage(
+        &self,
+        ctx: &ExecutionContext,
+        stage: &WorkspacePipelineStage,
+    ) {
+        tracing::info!(
+            "Starting pipeline stage '{}' of workspace {}",
+            stage.name,
+            ctx.workspace.id
+        );
+        let Err(e) = self
+            .start_pipeline_stage(&ctx.workspace, &ctx.session, stage)
+            .await
+        else {
+            return;
+        };
+        tracing::error!("Failed to start pipeline stage '{}': {}", stage.name, e);
+        let pool = &self.db.pool;
+        if let Err(e) =
+            WorkspacePipelineStage::update_status(pool, stage.id, PipelineStageStatus::Failed).await
+        {
+            tracing::warn!("Failed to mark pipeline stage failed: {}", e);
+        }
+        if let Err(e) = WorkspacePipelineStage::skip_pending(pool, ctx.workspace.id).await {
+            tracing::warn!("Failed to skip remaining pipeline stages: {}", e);
+        }
+        self.finalize_task(ctx).await;
+    }
+
+    /// Propose the files a conflict resolution process left without conflict markers
+    async fn record_conflict_resolutions(
+        &self,
+        ctx: &ExecutionContext,
+    ) -> Result<(), anyhow::Error> {
+        if ctx.execution_process.run_reason != ExecutionProcessRunReason::ConflictResolution {
+            return Ok(());
+        }
+        let Some(container_ref) = &ctx.workspace.container_ref else {
+            return Ok(());
+        };
+        let resolutions = ConflictResolutionService::record_proposals(
+            &self.db.pool,
+            ctx.execution_process.id,
+            Path::new(container_ref),
+        )
+        .await?;
+        if !resolutions.is_empty() {
+            tracing::info!(
+                "Conflict resolution of workspace {} finished: {} of {} files proposed",
+                ctx.workspace.id,
+                resolutions
+                    .iter()
+                    .filter(|r| r.status == ConflictResolutionStatus::Proposed)
+                    .count(),
+                resolutions.len()
+            );
+        }
+        Ok(())
+    }
+
     /// Copy project files and images to the workspace.
     /// Skips files/images that already exist (fast no-op if all exist).
     async fn copy_files_and_images(
@@ -850,81 +1415,6 @@ impl LocalContainerService {
 
         Ok(())
     }
-
-    /// Start a follow-up execution from a queued message
-    async fn start_queued_follow_up(
-        &self,
-        ctx: &ExecutionContext,
-        queued_data: &DraftFollowUpData,
-    ) -> Result<ExecutionProcess, ContainerError> {
-        let executor_profile_id = queued_data.executor_profile_id.clone();
-
-        // Validate executor matches session if session has prior executions
-        let expected_executor: Option<String> =
-            ExecutionProcess::latest_executor_profile_for_session(&self.db.pool, ctx.session.id)
-                .await?
-                .map(|profile| profile.executor.to_string())
-                .or_else(|| ctx.session.executor.clone());
-
-        if let Some(expected) = expected_executor {
-            let actual = executor_profile_id.executor.to_string();
-            if expected != actual {
-                return Err(SessionError::ExecutorMismatch { expected, actual }.into());
-            }
-        }
-
-        if ctx.session.executor.is_none() {
-            Session::update_executor(
-                &self.db.pool,
-                ctx.session.id,
-                &executor_profile_id.executor.to_string(),
-            )
-            .await?;
-        }
-
-        // Get latest agent session ID for session continuity (from coding agent turns)
-        let latest_agent_session_id = ExecutionProcess::find_latest_coding_agent_turn_session_id(
-            &self.db.pool,
-            ctx.session.id,
-        )
-        .await?;
-
-        let repos =
-            WorkspaceRepo::find_repos_for_workspace(&self.db.pool, ctx.workspace.id).await?;
-        let cleanup_action = self.cleanup_actions_for_repos(&repos);
-
-        let working_dir = ctx
-            .workspace
-            .agent_working_dir
-            .as_ref()
-            .filter(|dir| !dir.is_empty())
-            .cloned();
-
-        let action_type = if let Some(agent_session_id) = latest_agent_session_id {
-            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
-                prompt: queued_data.message.clone(),
-                session_id: agent_session_id,
-                executor_profile_id: executor_profile_id.clone(),
-                working_dir: working_dir.clone(),
-            })
-        } else {
-            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
-                prompt: queued_data.message.clone(),
-                executor_profile_id: executor_profile_id.clone(),
-                working_dir,
-            })
-        };
-
-        let action = ExecutorAction::new(action_type, cleanup_action.map(Box::new));
-
-        self.start_execution(
-            &ctx.workspace,
-            &ctx.session,
-            &action,
-            &ExecutionProcessRunReason::CodingAgent,
-        )
-        .await
-    }
 }
 
 fn failure_exit_status() -> std::process::ExitStatus {
@@ -966,6 +1456,11 @@ impl ContainerService for LocalContainerService {
         LocalContainerService::take_db_stream_handle(self, id).await
     }
 
+    async fn process_input(&self, id: &Uuid) -> Option<ProcessInput> {
+        let map = self.process_inputs.read().await;
+        map.get(id).cloned()
+    }
+
     async fn git_branch_prefix(&self) -> String {
         self.config.read().await.git_branch_prefix.clone()
     }
@@ -1004,6 +1499,13 @@ impl ContainerService for LocalContainerService {
             .iter()
             .filter_map(|wr| wr.start_from_ref.as_ref().map(|r| (wr.repo_id, r.clone())))
             .collect();
+        let sparse_checkout_dirs: HashMap<_, _> =
+            ProjectRepo::find_by_project_id(&self.db.pool, task.project_id)
+                .await?
+                .into_iter()
+                .filter(|project_repo| project_repo.sparse_checkout)
+                .filter_map(|project_repo| Some((project_repo.repo_id, project_repo.path_scope?)))
+                .collect();
 
         let workspace_inputs: Vec<RepoWorkspaceInput> = repositories
             .iter()
@@ -1011,6 +1513,7 @@ impl ContainerService for LocalContainerService {
                 let target_branch = target_branches.get(&repo.id).cloned().unwrap_or_default();
                 let start_from_ref = start_from_refs.get(&repo.id).cloned();
                 RepoWorkspaceInput::with_start_from_ref(repo.clone(), target_branch, start_from_ref)
+                    .with_sparse_checkout(sparse_checkout_dirs.get(&repo.id).cloned())
             })
             .collect();
 
@@ -1126,6 +1629,17 @@ impl ContainerService for LocalContainerService {
         execution_process: &ExecutionProcess,
         executor_action: &ExecutorAction,
     ) -> Result<(), ContainerError> {
+        if execution_process.run_reason == ExecutionProcessRunReason::CodingAgent
+            && let Some(limit) = self.settings.max_concurrent_agents()
+        {
+            let running =
+                ExecutionProcess::count_running_coding_agents(&self.db.pool, execution_process.id)
+                    .await?;
+            if running >= i64::from(limit) {
+                return Err(ContainerError::AgentLimitReached(limit));
+            }
+        }
+
         // Get the worktree path
         let container_ref = workspace
             .container_ref
@@ -1156,7 +1670,10 @@ impl ContainerService for LocalContainerService {
         let repo_names: Vec<String> = repos.iter().map(|r| r.name.clone()).collect();
         let repo_context = RepoContext::new(current_dir.clone(), repo_names);
 
-        let commit_reminder = self.config.read().await.commit_reminder;
+        let (commit_reminder, server_env) = {
+            let config = self.config.read().await;
+            (config.commit_reminder, config.env.clone())
+        };
         let mut env = ExecutionEnv::new(repo_context, commit_reminder);
 
         // Load task and project context for environment variables
@@ -1171,14 +1688,88 @@ impl ContainerService for LocalContainerService {
             .await?
             .ok_or(ContainerError::Other(anyhow!("Project not found for task")))?;
 
+        // Layered from lowest to highest precedence; VK_* are set last so they always win
+        let project_env = EnvVarService::env_for_project(&self.db.pool, project.id).await?;
+        let secrets = SecretService::new()?
+            .env_for_workspace(&self.db.pool, project.id, workspace.id)
+            .await?;
+        let workspace_env = EnvVarService::workspace_overrides(&self.db.pool, workspace.id).await?;
+        env.merge(&layer_env(
+            &server_env,
+            &project_env,
+            &workspace_env,
+            &secrets,
+        ));
+        // The values of the secrets are masked in the process's output
+        let redactor = Arc::new(OutputRedactor::new(
+            secrets.into_values(),
+            &self.settings.output_redaction_patterns(),
+        ));
+
         env.insert("VK_PROJECT_NAME", &project.name);
         env.insert("VK_PROJECT_ID", project.id.to_string());
         env.insert("VK_TASK_ID", task.id.to_string());
         env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
         env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
 
+        let remote_host = ProjectRemoteHost::find_by_project_id(&self.db.pool, project.id).await?;
+
+        // Agents run sandboxed under the project's policy, and refuse to start if it cannot be
+        // enforced
+        if let Some(executor) = executor_action.base_executor()
+            && let Some(policy) =
+                ProjectAgentPolicy::find_by_project_id(&self.db.pool, project.id).await?
+        {
+            let policy = policy.policy();
+            if !policy.is_enforced_by(&executor) {
+                return Err(ContainerError::AgentPolicyNotEnforced(executor));
+            }
+            // The sandbox is set up on this machine, so it cannot confine a remote process
+            let sandbox = if remote_host.is_some() {
+                None
+            } else {
+                Sandbox::detect().await
+            };
+            let Some(sandbox) = sandbox else {
+                return Err(ContainerError::SandboxUnavailable);
+            };
+            ExecutionProcessPolicy::create(&self.db.pool, execution_process.id, &policy, sandbox)
+                .await?;
+            env.policy = Some(policy);
+            env.sandbox = Some(sandbox);
+        }
+
+        if execution_process.run_reason == ExecutionProcessRunReason::DevServer {
+            let port = self
+                .dev_server_ports
+                .allocate(workspace.id, execution_process.id)
+                .await
+                .map_err(|e| ContainerError::Other(anyhow!("Failed to allocate port: {e}")))?;
+            tracing::info!(
+                "Allocated port {} for dev server {} in workspace {}",
+                port,
+                execution_process.id,
+                workspace.id
+            );
+            env.insert(PORT_ENV_VAR, port.to_string());
+            env.insert(VK_PORT_ENV_VAR, port.to_string());
+        }
+
+        if let Some(host) = remote_host {
+            let remote = RemoteHost::from(host);
+            if let Err(e) = remote.sync_up(&current_dir).await {
+                self.dev_server_ports.release(&execution_process.id).await;
+                return Err(ContainerError::Other(anyhow!(
+                    "Failed to sync workspace to {}: {e}",
+                    remote.host
+                )));
+            }
+            env.remote = Some(remote);
+        }
+
         // Create the child and stream, add to execution tracker with timeout
-        let mut spawned = tokio::time::timeout(
+        self.dev_server_ports.hand_over(&execution_process.id).await;
+        let spawned = tokio::time::timeout(
             Duration::from_secs(30),
             executor_action.spawn(&current_dir, approvals_service, &env),
         )
@@ -1187,11 +1778,49 @@ impl ContainerService for LocalContainerService {
             ContainerError::Other(anyhow!(
                 "Timeout: process took more than 30 seconds to start"
             ))
-        })??;
+        })
+        .and_then(|spawned| spawned.map_err(ContainerError::from));
+        let mut spawned = match spawned {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                self.dev_server_ports.release(&execution_process.id).await;
+                return Err(e);
+            }
+        };
 
-        self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
+        self.track_child_msgs_in_store(execution_process.id, &mut spawned.child, redactor)
             .await;
 
+        // Executors that take input mid-run deliver it themselves; otherwise an open stdin
+        // gets it directly
+        let input = match spawned.input.take() {
+            Some(sender) => Some(ProcessInput::from_executor(sender)),
+            None => spawned
+                .child
+                .inner()
+                .stdin
+                .take()
+                .map(ProcessInput::from_stdin),
+        };
+        if let Some(input) = input {
+            if let Some(store) = self.get_msg_store_by_id(&execution_process.id).await {
+                input.spawn_idle_watch(store.get_receiver(), INPUT_IDLE_TIMEOUT);
+            }
+            self.add_process_input(execution_process.id, input).await;
+        }
+
+        // Recorded so the process can be found again if the server stops while it runs
+        if let Some(pid) = spawned.child.inner().id()
+            && let Err(e) =
+                ExecutionProcessPid::create(&self.db.pool, execution_process.id, pid).await
+        {
+            tracing::warn!(
+                "Failed to record pid of execution process {}: {}",
+                execution_process.id,
+                e
+            );
+        }
+
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
 
@@ -1261,6 +1890,8 @@ impl ContainerService for LocalContainerService {
             }
         }
         self.remove_child_from_store(&execution_process.id).await;
+        self.remove_process_input(&execution_process.id).await;
+        self.dev_server_ports.release(&execution_process.id).await;
 
         // Mark the process finished in the MsgStore and wait for DB persistence
         let db_stream_handle = self.take_db_stream_handle(&execution_process.id).await;
@@ -1300,36 +1931,27 @@ impl ContainerService for LocalContainerService {
         stats_only: bool,
     ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>
     {
-        let workspace_repos =
-            WorkspaceRepo::find_by_workspace_id(&self.db.pool, workspace.id).await?;
-        let target_branches: HashMap<_, _> = workspace_repos
-            .iter()
-            .map(|wr| (wr.repo_id, wr.target_branch.clone()))
-            .collect();
-
         let repositories =
-            WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id).await?;
+            WorkspaceRepo::find_repos_with_target_branch_for_workspace(&self.db.pool, workspace.id)
+                .await?;
 
         let mut streams = Vec::new();
 
         let container_ref = self.ensure_container_exists(workspace).await?;
         let workspace_root = PathBuf::from(container_ref);
 
-        for repo in repositories {
+        for RepoWithTargetBranch {
+            repo,
+            target_branch,
+            path_scope,
+        } in repositories
+        {
             let worktree_path = workspace_root.join(&repo.name);
             let branch = &workspace.branch;
 
-            let Some(target_branch) = target_branches.get(&repo.id) else {
-                tracing::warn!(
-                    "Skipping diff stream for repo {}: no target branch configured",
-                    repo.name
-                );
-                continue;
-            };
-
             let base_commit = match self
                 .git()
-                .get_base_commit(&repo.path, branch, target_branch)
+                .get_base_commit(&repo.path, branch, &target_branch)
             {
                 Ok(c) => c,
                 Err(e) => {
@@ -1351,10 +1973,11 @@ impl ContainerService for LocalContainerService {
                     repo_path: repo.path.clone(),
                     worktree_path: worktree_path.clone(),
                     branch: branch.to_string(),
-                    target_branch: target_branch.clone(),
+                    target_branch,
                     base_commit: base_commit.clone(),
                     stats_only,
                     path_prefix: Some(repo.name.clone()),
+                    path_scope,
                 })
                 .await?;
 
@@ -1418,6 +2041,99 @@ impl ContainerService for LocalContainerService {
         .map_err(|e| ContainerError::Other(anyhow!("Copy files task failed: {e}")))?
     }
 
+    async fn reap_processes(&self) -> Result<ProcessReaperReport, ContainerError> {
+        let running = ExecutionProcess::find_running(&self.db.pool).await?;
+        let mut reaped = Vec::new();
+
+        for process in &running {
+            let tracked = self.get_child_from_store(&process.id).await.is_some();
+            let workspace = process
+                .parent_workspace_and_session(&self.db.pool)
+                .await?
+                .map(|(workspace, _)| workspace);
+            let worktree_exists = workspace
+                .as_ref()
+                .and_then(|w| w.container_ref.as_deref())
+                .is_some_and(|dir| Path::new(dir).exists());
+            let Some(action) = ProcessReaperService::action_for_running(
+                tracked,
+                worktree_exists,
+                process.started_at,
+            ) else {
+                continue;
+            };
+
+            let result = match action {
+                ReapAction::KilledMissingWorktree => {
+                    self.stop_execution(process, ExecutionProcessStatus::Killed)
+                        .await
+                }
+                _ => ExecutionProcess::update_completion(
+                    &self.db.pool,
+                    process.id,
+                    ExecutionProcessStatus::Failed,
+                    None,
+                )
+                .await
+                .map_err(ContainerError::from),
+            };
+            if let Err(e) = result {
+                tracing::error!("Failed to reap execution process {}: {}", process.id, e);
+                continue;
+            }
+            reaped.push(ReapedProcess {
+                execution_process_id: process.id,
+                workspace_id: workspace.map(|w| w.id),
+                run_reason: process.run_reason.clone(),
+                action,
+            });
+        }
+
+        // Spawned processes still running although their execution no longer is
+        let tracked_ids: Vec<Uuid> = self.child_store.read().await.keys().copied().collect();
+        for id in tracked_ids {
+            if running.iter().any(|p| p.id == id) {
+                continue;
+            }
+            let Some(process) = ExecutionProcess::find_by_id(&self.db.pool, id).await? else {
+                continue;
+            };
+            if !ProcessReaperService::should_kill_untracked(process.completed_at) {
+                continue;
+            }
+            let Some(child) = self.get_child_from_store(&id).await else {
+                continue;
+            };
+            let mut child = child.write().await;
+            // Exited ones are reaped by their exit monitor
+            if !matches!(child.try_wait(), Ok(None)) {
+                continue;
+            }
+            if let Err(e) = command::kill_process_group(&mut child).await {
+                tracing::error!("Failed to kill untracked execution process {}: {}", id, e);
+                continue;
+            }
+            let workspace_id = process
+                .parent_workspace_and_session(&self.db.pool)
+                .await?
+                .map(|(workspace, _)| workspace.id);
+            reaped.push(ReapedProcess {
+                execution_process_id: id,
+                workspace_id,
+                run_reason: process.run_reason,
+                action: ReapAction::KilledUntracked,
+            });
+        }
+
+        let report = ProcessReaperReport::new(running.len(), reaped);
+        *self.reaper_report.write().await = Some(report.clone());
+        Ok(report)
+    }
+
+    async fn last_reaper_report(&self) -> Option<ProcessReaperReport> {
+        self.reaper_report.read().await.clone()
+    }
+
     async fn kill_all_running_processes(&self) -> Result<(), ContainerError> {
         tracing::info!("Killing all running processes");
         let running_processes = ExecutionProcess::find_running(&self.db.pool).await?;
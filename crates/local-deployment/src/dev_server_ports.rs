@@ -0,0 +1,136 @@
+//! Port allocation for workspace dev servers.
+//!
+//! Each dev server process gets a free localhost port injected through its environment,
+//! so parallel workspaces can run previews without clashing. The port stays bound until the
+//! dev server is about to start, so nothing else is handed it in the meantime.
+
+use std::{collections::HashMap, net::TcpListener, sync::Arc, time::Instant};
+
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Env var the allocated port is exposed through, following the common `PORT` convention
+pub const PORT_ENV_VAR: &str = "PORT";
+/// Same port under a namespaced name, for scripts where `PORT` means something else
+pub const VK_PORT_ENV_VAR: &str = "VK_DEV_SERVER_PORT";
+
+const MAX_ALLOCATION_ATTEMPTS: usize = 16;
+
+#[derive(Debug)]
+pub struct DevServerPort {
+    pub workspace_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub port: u16,
+    allocated_at: Instant,
+    /// Listener holding the port until it is handed over to the dev server
+    reservation: Option<TcpListener>,
+}
+
+#[derive(Clone, Default)]
+pub struct DevServerPorts {
+    allocations: Arc<RwLock<HashMap<Uuid, DevServerPort>>>,
+}
+
+impl DevServerPorts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve a free localhost port for a dev server execution process. The port stays
+    /// bound until [`Self::hand_over`] is called right before the dev server starts.
+    pub async fn allocate(
+        &self,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+    ) -> std::io::Result<u16> {
+        let mut allocations = self.allocations.write().await;
+        for _ in 0..MAX_ALLOCATION_ATTEMPTS {
+            // Bind to port 0 so the OS picks a free port. Ports already handed over to a dev
+            // server are no longer bound, so they are skipped in case the OS hands one back.
+            let listener = TcpListener::bind(("127.0.0.1", 0))?;
+            let port = listener.local_addr()?.port();
+            if allocations.values().any(|a| a.port == port) {
+                continue;
+            }
+            allocations.insert(
+                execution_process_id,
+                DevServerPort {
+                    workspace_id,
+                    execution_process_id,
+                    port,
+                    allocated_at: Instant::now(),
+                    reservation: Some(listener),
+                },
+            );
+            return Ok(port);
+        }
+        Err(std::io::Error::new(
+            std::io::ErrorKind::AddrInUse,
+            "Could not find a free port for the dev server",
+        ))
+    }
+
+    /// Unbind the reserved port so the dev server about to start can bind it
+    pub async fn hand_over(&self, execution_process_id: &Uuid) {
+        if let Some(allocation) = self.allocations.write().await.get_mut(execution_process_id) {
+            allocation.reservation = None;
+        }
+    }
+
+    pub async fn release(&self, execution_process_id: &Uuid) {
+        self.allocations.write().await.remove(execution_process_id);
+    }
+
+    /// Port of the earliest dev server still running for the workspace
+    pub async fn port_for_workspace(&self, workspace_id: Uuid) -> Option<u16> {
+        self.allocations
+            .read()
+            .await
+            .values()
+            .filter(|a| a.workspace_id == workspace_id)
+            .min_by_key(|a| a.allocated_at)
+            .map(|a| a.port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_free(port: u16) -> bool {
+        TcpListener::bind(("127.0.0.1", port)).is_ok()
+    }
+
+    #[tokio::test]
+    async fn ports_stay_reserved_until_handed_over() {
+        let ports = DevServerPorts::new();
+        let (workspace_id, process_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let port = ports.allocate(workspace_id, process_id).await.unwrap();
+        assert!(!is_free(port));
+
+        ports.hand_over(&process_id).await;
+        assert!(is_free(port));
+        assert_eq!(ports.port_for_workspace(workspace_id).await, Some(port));
+    }
+
+    #[tokio::test]
+    async fn each_dev_server_gets_its_own_port() {
+        let ports = DevServerPorts::new();
+        let workspace_id = Uuid::new_v4();
+        let (first_process, second_process) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let first = ports.allocate(workspace_id, first_process).await.unwrap();
+        ports.hand_over(&first_process).await;
+        let second = ports.allocate(workspace_id, second_process).await.unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(ports.port_for_workspace(workspace_id).await, Some(first));
+
+        ports.release(&first_process).await;
+        assert_eq!(ports.port_for_workspace(workspace_id).await, Some(second));
+        ports.release(&second_process).await;
+        assert_eq!(ports.port_for_workspace(workspace_id).await, None);
+        assert_eq!(ports.port_for_workspace(Uuid::new_v4()).await, None);
+    }
+}
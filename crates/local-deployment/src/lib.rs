@@ -14,13 +14,14 @@ use services::services::{
     events::EventService,
     file_search::FileSearchCache,
     filesystem::FilesystemService,
+    git_credentials::GitCredentialService,
     image::ImageService,
     oauth_credentials::OAuthCredentials,
     project::ProjectService,
     queued_message::QueuedMessageService,
     remote_client::{RemoteClient, RemoteClientError},
     repo::RepoService,
-    worktree_manager::WorktreeManager,
+    settings::SettingsService,
 };
 use tokio::sync::RwLock;
 use utils::{
@@ -30,10 +31,11 @@ use utils::{
 };
 use uuid::Uuid;
 
-use crate::{container::LocalContainerService, pty::PtyService};
+use crate::{container::LocalContainerService, dev_server_ports::DevServerPorts, pty::PtyService};
 mod command;
 pub mod container;
 mod copy;
+pub mod dev_server_ports;
 pub mod pty;
 
 #[derive(Clone)]
@@ -56,6 +58,7 @@ pub struct LocalDeployment {
     auth_context: AuthContext,
     oauth_handoffs: Arc<RwLock<HashMap<Uuid, PendingHandoff>>>,
     pty: PtyService,
+    settings: SettingsService,
 }
 
 #[derive(Debug, Clone)]
@@ -91,11 +94,6 @@ impl Deployment for LocalDeployment {
         // Always save config (may have been migrated or version updated)
         save_config_to_file(&raw_config, &config_path()).await?;
 
-        if let Some(workspace_dir) = &raw_config.workspace_dir {
-            let path = utils::path::expand_tilde(workspace_dir);
-            WorktreeManager::set_workspace_dir_override(path);
-        }
-
         let config = Arc::new(RwLock::new(raw_config));
         let user_id = generate_user_id();
         let analytics = AnalyticsConfig::new().map(AnalyticsService::new);
@@ -119,6 +117,9 @@ impl Deployment for LocalDeployment {
             DBService::new_with_after_connect(hook).await?
         };
 
+        let workspace_dir = config.read().await.workspace_dir.clone();
+        let settings = SettingsService::load(db.pool.clone(), workspace_dir.as_deref()).await?;
+
         let image = ImageService::new(db.clone().pool)?;
         {
             let image_service = image.clone();
@@ -130,6 +131,15 @@ impl Deployment for LocalDeployment {
             });
         }
 
+        match GitCredentialService::new() {
+            Ok(git_credentials) => match git_credentials.register_all(&db.pool).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Loaded git credentials for {} repos", count),
+                Err(e) => tracing::warn!(?e, "failed to load git credentials"),
+            },
+            Err(e) => tracing::warn!(?e, "failed to open git credential store"),
+        }
+
         let approvals = Approvals::new(msg_stores.clone());
         let queued_message_service = QueuedMessageService::new();
 
@@ -179,6 +189,7 @@ impl Deployment for LocalDeployment {
             analytics_ctx,
             approvals.clone(),
             queued_message_service.clone(),
+            settings.clone(),
         )
         .await;
 
@@ -207,6 +218,7 @@ impl Deployment for LocalDeployment {
             auth_context,
             oauth_handoffs,
             pty,
+            settings,
         };
 
         Ok(deployment)
@@ -271,6 +283,10 @@ impl Deployment for LocalDeployment {
     fn auth_context(&self) -> &AuthContext {
         &self.auth_context
     }
+
+    fn settings(&self) -> &SettingsService {
+        &self.settings
+    }
 }
 
 impl LocalDeployment {
@@ -334,4 +350,8 @@ impl LocalDeployment {
     pub fn pty(&self) -> &PtyService {
         &self.pty
     }
+
+    pub fn dev_server_ports(&self) -> &DevServerPorts {
+        self.container.dev_server_ports()
+    }
 }
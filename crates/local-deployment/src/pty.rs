@@ -45,15 +45,20 @@ impl PtyService {
         }
     }
 
+    /// Spawn `shell` (or the platform's interactive shell) in a new PTY rooted at `working_dir`
     pub async fn create_session(
         &self,
         working_dir: PathBuf,
+        shell: Option<PathBuf>,
         cols: u16,
         rows: u16,
     ) -> Result<(Uuid, mpsc::UnboundedReceiver<Vec<u8>>), PtyError> {
         let session_id = Uuid::new_v4();
         let (output_tx, output_rx) = mpsc::unbounded_channel();
-        let shell = get_interactive_shell().await;
+        let shell = match shell {
+            Some(shell) => shell,
+            None => get_interactive_shell().await,
+        };
 
         let result = tokio::task::spawn_blocking(move || {
             let pty_system = NativePtySystem::default();
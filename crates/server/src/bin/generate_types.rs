@@ -18,11 +18,59 @@ fn generate_types_content() -> String {
         db::models::project::SearchMatchType::decl(),
         db::models::repo::Repo::decl(),
         db::models::repo::UpdateRepo::decl(),
+        db::models::repo_credential::RepoCredentialKind::decl(),
         db::models::project_repo::ProjectRepo::decl(),
         db::models::project_repo::CreateProjectRepo::decl(),
+        db::models::project_repo::UpdateProjectRepo::decl(),
+        db::models::project_quality_gate::ProjectQualityGate::decl(),
+        db::models::project_quality_gate::CreateProjectQualityGate::decl(),
+        db::models::project_quality_gate::UpdateProjectQualityGate::decl(),
+        db::models::project_remote_host::ProjectRemoteHost::decl(),
+        db::models::project_remote_host::SetProjectRemoteHost::decl(),
+        db::models::project_ai_review_policy::ProjectAiReviewPolicy::decl(),
+        db::models::project_ai_review_policy::UpsertProjectAiReviewPolicy::decl(),
+        db::models::project_agent_pipeline::AgentPipelineStage::decl(),
+        db::models::project_agent_pipeline::ProjectAgentPipeline::decl(),
+        db::models::project_agent_pipeline::UpsertProjectAgentPipeline::decl(),
+        db::models::project_agent_policy::ProjectAgentPolicy::decl(),
+        db::models::project_agent_policy::UpsertProjectAgentPolicy::decl(),
+        db::models::project_settings::MergeStrategy::decl(),
+        db::models::project_settings::AutoSyncStrategy::decl(),
+        db::models::project_settings::ProjectSettings::decl(),
+        db::models::project_settings::UpsertProjectSettings::decl(),
+        db::models::project_script::ProjectScript::decl(),
+        db::models::project_script::CreateProjectScript::decl(),
+        db::models::project_script::UpdateProjectScript::decl(),
+        db::models::project_env_set::ProjectEnvSet::decl(),
+        db::models::project_env_set::CreateProjectEnvSet::decl(),
+        db::models::project_env_set::UpdateProjectEnvSet::decl(),
         db::models::workspace_repo::WorkspaceRepo::decl(),
         db::models::workspace_repo::CreateWorkspaceRepo::decl(),
         db::models::workspace_repo::RepoWithTargetBranch::decl(),
+        db::models::workspace_test_run::TestFramework::decl(),
+        db::models::workspace_test_run::RepoTestResult::decl(),
+        db::models::workspace_test_run::WorkspaceTestRun::decl(),
+        db::models::workspace_event::WorkspaceEventKind::decl(),
+        db::models::workspace_event::WorkspaceEvent::decl(),
+        db::models::review_comment::ReviewCommentSide::decl(),
+        db::models::review_comment::ReviewSeverity::decl(),
+        db::models::review_comment::ReviewComment::decl(),
+        db::models::review_comment::CreateReviewComment::decl(),
+        db::models::review_comment::UpdateReviewComment::decl(),
+        db::models::workspace_review::ReviewDecision::decl(),
+        db::models::workspace_review::WorkspaceReview::decl(),
+        db::models::workspace_review::CreateWorkspaceReview::decl(),
+        db::models::workspace_merge_description::WorkspaceMergeDescription::decl(),
+        db::models::workspace_merge_description::SetWorkspaceMergeDescription::decl(),
+        db::models::workspace_pipeline_stage::PipelineStageStatus::decl(),
+        db::models::workspace_pipeline_stage::WorkspacePipelineStage::decl(),
+        db::models::ai_review::AiReviewStatus::decl(),
+        db::models::ai_review::AiReview::decl(),
+        db::models::conflict_resolution::ConflictResolutionStatus::decl(),
+        db::models::conflict_resolution::ConflictResolution::decl(),
+        db::models::plan_turn::PlanTurnStatus::decl(),
+        db::models::plan_turn::PlanTurn::decl(),
+        db::models::terminal_session::TerminalSession::decl(),
         db::models::tag::Tag::decl(),
         db::models::tag::CreateTag::decl(),
         db::models::tag::UpdateTag::decl(),
@@ -33,6 +81,11 @@ fn generate_types_content() -> String {
         db::models::task::TaskRelationships::decl(),
         db::models::task::CreateTask::decl(),
         db::models::task::UpdateTask::decl(),
+        db::models::task::TaskPriority::decl(),
+        db::models::task_status_transition::TaskStatusTransition::decl(),
+        db::models::task_template::TaskTemplate::decl(),
+        db::models::task_template::CreateTaskTemplate::decl(),
+        db::models::task_template::UpdateTaskTemplate::decl(),
         db::models::scratch::DraftFollowUpData::decl(),
         db::models::scratch::DraftWorkspaceData::decl(),
         db::models::scratch::DraftWorkspaceRepo::decl(),
@@ -51,7 +104,11 @@ fn generate_types_content() -> String {
         db::models::execution_process::ExecutionProcess::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
+        db::models::execution_process::ExecutionProcessSummary::decl(),
         db::models::execution_process_repo_state::ExecutionProcessRepoState::decl(),
+        db::models::execution_process_artifact::ExecutionProcessArtifact::decl(),
+        db::models::execution_process_policy::ExecutionProcessPolicy::decl(),
+        db::models::interrupted_execution::InterruptedExecution::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
@@ -62,6 +119,7 @@ fn generate_types_content() -> String {
         utils::approvals::ApprovalResponse::decl(),
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
+        utils::diff::DiffChangeState::decl(),
         utils::response::ApiResponse::<()>::decl(),
         utils::api::oauth::LoginStatus::decl(),
         utils::api::oauth::ProfileResponse::decl(),
@@ -91,8 +149,14 @@ fn generate_types_content() -> String {
         utils::api::projects::RemoteProject::decl(),
         utils::api::projects::ListProjectsResponse::decl(),
         utils::api::projects::RemoteProjectMembersResponse::decl(),
+        server::middleware::StaleVersion::<()>::decl(),
+        server::pagination::Page::<()>::decl(),
+        server::pagination::Listing::<()>::decl(),
         server::routes::repo::RegisterRepoRequest::decl(),
         server::routes::repo::InitRepoRequest::decl(),
+        server::routes::repo::DiscoverReposRequest::decl(),
+        server::routes::repo::DiscoverReposResponse::decl(),
+        server::routes::repo::AddRepoRemoteRequest::decl(),
         server::routes::repo::RalphCheckResponse::decl(),
         server::routes::tags::TagSearchParams::decl(),
         server::routes::oauth::TokenResponse::decl(),
@@ -110,18 +174,38 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::ChangeTargetBranchResponse::decl(),
         server::routes::task_attempts::MergeTaskAttemptRequest::decl(),
         server::routes::task_attempts::PushTaskAttemptRequest::decl(),
+        server::routes::task_attempts::SetPushRemoteRequest::decl(),
         server::routes::task_attempts::RenameBranchRequest::decl(),
         server::routes::task_attempts::RenameBranchResponse::decl(),
         server::routes::sessions::review::StartReviewRequest::decl(),
         server::routes::sessions::review::ReviewError::decl(),
+        server::routes::execution_processes::LogStream::decl(),
+        server::routes::execution_processes::LogChunkEntry::decl(),
+        server::routes::execution_processes::ExecutionProcessLogChunkResponse::decl(),
+        server::routes::execution_processes::ProcessInputRequest::decl(),
+        services::services::log_search::LogSearchMatch::decl(),
+        services::services::log_search::LogSearchResult::decl(),
+        services::services::process_reaper::ReapAction::decl(),
+        services::services::process_reaper::ReapedProcess::decl(),
+        services::services::process_reaper::ProcessReaperReport::decl(),
         server::routes::task_attempts::OpenEditorRequest::decl(),
         server::routes::task_attempts::OpenEditorResponse::decl(),
+        server::routes::tasks::TaskGroupBy::decl(),
+        server::routes::tasks::TaskGroup::decl(),
+        server::routes::tasks::TaskList::decl(),
+        server::routes::tasks::CreateTaskFromTemplateRequest::decl(),
+        server::routes::tasks::DuplicateTaskRequest::decl(),
+        server::routes::tasks::MoveTaskRequest::decl(),
+        server::routes::tasks::MoveTaskResponse::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
         server::routes::tasks::RalphStatusResponse::decl(),
         server::routes::tasks::RalphContinueResponse::decl(),
         server::routes::tasks::UpdateRalphAutoContinueRequest::decl(),
         server::routes::tasks::UpdateRalphAutoContinueResponse::decl(),
         server::routes::tasks::RalphStoryCommitsResponse::decl(),
+        server::routes::overview::Overview::decl(),
+        server::routes::overview::ProjectOverview::decl(),
+        server::routes::overview::OverviewWorkspace::decl(),
         server::routes::task_attempts::pr::CreatePrApiRequest::decl(),
         server::routes::images::ImageResponse::decl(),
         server::routes::images::ImageMetadata::decl(),
@@ -156,6 +240,24 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::workspace_summary::WorkspaceSummaryResponse::decl(),
         server::routes::task_attempts::workspace_summary::DiffStats::decl(),
         services::services::filesystem::DirectoryEntry::decl(),
+        services::services::repo::DiscoveredRepo::decl(),
+        services::services::repo::RepoIssueKind::decl(),
+        services::services::repo::RepoIssue::decl(),
+        services::services::repo::RepoValidation::decl(),
+        services::services::git_credentials::SetRepoCredential::decl(),
+        services::services::git_credentials::RepoCredentialSummary::decl(),
+        services::services::secrets::CreateSecret::decl(),
+        services::services::secrets::UpdateSecret::decl(),
+        services::services::secrets::SecretSummary::decl(),
+        services::services::secrets::SecretAllowlistNames::decl(),
+        services::services::project_stats::ProjectStats::decl(),
+        services::services::project_stats::RepoStats::decl(),
+        services::services::project_stats::AgentStats::decl(),
+        services::services::task_analytics::DurationSummary::decl(),
+        services::services::task_analytics::WeeklyThroughput::decl(),
+        services::services::cost_report::CostPeriod::decl(),
+        services::services::cost_report::PeriodCost::decl(),
+        services::services::cost_report::ProjectCost::decl(),
         services::services::filesystem::DirectoryListResponse::decl(),
         services::services::file_search::SearchMode::decl(),
         services::services::config::Config::decl(),
@@ -169,14 +271,32 @@ fn generate_types_content() -> String {
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
         services::services::config::SendMessageShortcut::decl(),
+        services::services::settings::Settings::decl(),
+        services::services::settings::LogRetentionPolicy::decl(),
+        services::services::settings::ObjectStorageSettings::decl(),
         git::GitBranch::decl(),
+        git::BlameLineOrigin::decl(),
+        git::BlameLine::decl(),
+        git::FileHistoryEntry::decl(),
         services::services::queued_message::QueuedMessage::decl(),
         services::services::queued_message::QueueStatus::decl(),
+        services::services::events::ProjectEventEntity::decl(),
+        services::services::events::ProjectEventOp::decl(),
+        services::services::events::ProjectEvent::decl(),
+        services::services::workspace_manager::OrphanWorktree::decl(),
+        services::services::workspace_manager::OrphanWorkspace::decl(),
+        services::services::workspace_integrity::WorktreeIssueKind::decl(),
+        services::services::workspace_integrity::WorktreeIssue::decl(),
+        services::services::workspace_integrity::RepoVerification::decl(),
+        services::services::workspace_integrity::WorkspaceVerification::decl(),
+        services::services::target_drift::RepoTargetDrift::decl(),
 git::ConflictOp::decl(),
         services::services::ralph::RalphStory::decl(),
         services::services::ralph::StoryCommit::decl(),
         executors::actions::ExecutorAction::decl(),
         executors::mcp_config::McpConfig::decl(),
+        executors::policy::AgentPolicy::decl(),
+        executors::sandbox::Sandbox::decl(),
         executors::actions::ExecutorActionType::decl(),
         executors::actions::script::ScriptContext::decl(),
         executors::actions::script::ScriptRequest::decl(),
@@ -216,6 +336,7 @@ git::ConflictOp::decl(),
         executors::logs::NormalizedEntry::decl(),
         executors::logs::NormalizedEntryType::decl(),
         executors::logs::TokenUsageInfo::decl(),
+        executors::logs::TurnUsage::decl(),
         executors::logs::FileChange::decl(),
         executors::logs::ActionType::decl(),
         executors::logs::TodoItem::decl(),
@@ -17,11 +17,19 @@ use local_deployment::pty::PtyError;
 use services::services::{
     config::{ConfigError, EditorOpenError},
     container::ContainerError,
+    env_vars::EnvVarError,
+    git_credentials::GitCredentialError,
     git_host::GitHostError,
     image::ImageError,
+    object_storage::ObjectStorageError,
     project::ProjectServiceError,
+    quality_gate::QualityGateError,
+    recorded_command::RecordedCommandError,
     remote_client::RemoteClientError,
     repo::RepoError as RepoServiceError,
+    secrets::SecretError,
+    settings::SettingsError,
+    workspace_lock::WorkspaceBusy,
     worktree_manager::WorktreeError,
 };
 use thiserror::Error;
@@ -68,6 +76,22 @@ pub enum ApiError {
     EditorOpen(#[from] EditorOpenError),
     #[error(transparent)]
     RemoteClient(#[from] RemoteClientError),
+    #[error(transparent)]
+    QualityGate(#[from] QualityGateError),
+    #[error(transparent)]
+    RecordedCommand(#[from] RecordedCommandError),
+    #[error(transparent)]
+    WorkspaceBusy(#[from] WorkspaceBusy),
+    #[error(transparent)]
+    GitCredential(#[from] GitCredentialError),
+    #[error(transparent)]
+    EnvVar(#[from] EnvVarError),
+    #[error(transparent)]
+    Secret(#[from] SecretError),
+    #[error(transparent)]
+    Settings(#[from] SettingsError),
+    #[error(transparent)]
+    ObjectStorage(#[from] ObjectStorageError),
     #[error("Unauthorized")]
     Unauthorized,
     #[error("Bad request: {0}")]
@@ -122,10 +146,25 @@ impl IntoResponse for ApiError {
                     (StatusCode::CONFLICT, "GitServiceError")
                 }
                 git::GitServiceError::RebaseInProgress => (StatusCode::CONFLICT, "GitServiceError"),
+                git::GitServiceError::RevisionNotFound(_) => {
+                    (StatusCode::NOT_FOUND, "GitServiceError")
+                }
+                git::GitServiceError::NotDiffable(_) | git::GitServiceError::PathNotChanged(_) => {
+                    (StatusCode::BAD_REQUEST, "GitServiceError")
+                }
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "GitServiceError"),
             },
             ApiError::GitHost(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHostError"),
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
+            ApiError::Container(ContainerError::AgentLimitReached(_)) => {
+                (StatusCode::TOO_MANY_REQUESTS, "AgentLimitReached")
+            }
+            ApiError::Container(ContainerError::AgentPolicyNotEnforced(_)) => {
+                (StatusCode::BAD_REQUEST, "AgentPolicyNotEnforced")
+            }
+            ApiError::Container(ContainerError::SandboxUnavailable) => {
+                (StatusCode::BAD_REQUEST, "SandboxUnavailable")
+            }
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
             ApiError::CommandBuilder(_) => (StatusCode::INTERNAL_SERVER_ERROR, "CommandBuildError"),
@@ -178,6 +217,41 @@ impl IntoResponse for ApiError {
                     (StatusCode::BAD_REQUEST, "RemoteClientError")
                 }
             },
+            ApiError::QualityGate(_) => (StatusCode::INTERNAL_SERVER_ERROR, "QualityGateError"),
+            ApiError::RecordedCommand(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "RecordedCommandError")
+            }
+            ApiError::WorkspaceBusy(_) => (StatusCode::LOCKED, "WorkspaceBusy"),
+            ApiError::GitCredential(err) => match err {
+                GitCredentialError::Invalid(_) => (StatusCode::BAD_REQUEST, "GitCredentialError"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "GitCredentialError"),
+            },
+            ApiError::EnvVar(err) => match err {
+                EnvVarError::InvalidName(_) | EnvVarError::EmptySetName => {
+                    (StatusCode::BAD_REQUEST, "EnvVarError")
+                }
+                EnvVarError::DuplicateSetName(_) => (StatusCode::CONFLICT, "EnvVarError"),
+                EnvVarError::SetNotFound(_) => (StatusCode::NOT_FOUND, "EnvVarError"),
+                EnvVarError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "EnvVarError"),
+            },
+            ApiError::Secret(err) => match err {
+                SecretError::InvalidName(_) | SecretError::EmptyValue => {
+                    (StatusCode::BAD_REQUEST, "SecretError")
+                }
+                SecretError::AlreadyExists(_) => (StatusCode::CONFLICT, "SecretError"),
+                SecretError::NotFound(_) => (StatusCode::NOT_FOUND, "SecretError"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "SecretError"),
+            },
+            ApiError::Settings(err) => match err {
+                SettingsError::Invalid(_) => (StatusCode::BAD_REQUEST, "SettingsError"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "SettingsError"),
+            },
+            ApiError::ObjectStorage(err) => match err {
+                ObjectStorageError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    (StatusCode::NOT_FOUND, "ObjectStorageError")
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "ObjectStorageError"),
+            },
             ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
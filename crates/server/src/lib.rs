@@ -1,7 +1,10 @@
 pub mod error;
 pub mod mcp;
 pub mod middleware;
+pub mod pagination;
 pub mod routes;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 // #[cfg(feature = "cloud")]
 // type DeploymentImpl = vibe_kanban_cloud::deployment::CloudDeployment;
@@ -1,8 +1,10 @@
 use std::{future::Future, str::FromStr};
 
 use db::models::{
+    execution_process::ExecutionProcess,
     project::Project,
     repo::Repo,
+    session::Session,
     tag::Tag,
     task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus, UpdateTask},
     workspace::{Workspace, WorkspaceContext},
@@ -23,6 +25,7 @@ use uuid::Uuid;
 
 use crate::routes::{
     containers::ContainerQuery,
+    sessions::CreateFollowUpAttempt,
     task_attempts::{CreateTaskAttemptBody, WorkspaceRepoInput},
 };
 
@@ -267,8 +270,10 @@ pub struct DeleteTaskRequest {
 pub struct McpWorkspaceRepoInput {
     #[schemars(description = "The repository ID")]
     pub repo_id: Uuid,
-    #[schemars(description = "The base branch for this repository")]
-    pub base_branch: String,
+    #[schemars(
+        description = "The base branch for this repository. Optional; defaults to the project's default target branch for the repository"
+    )]
+    pub base_branch: Option<String>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -281,7 +286,10 @@ pub struct StartWorkspaceSessionRequest {
     pub executor: String,
     #[schemars(description = "Optional executor variant, if needed")]
     pub variant: Option<String>,
-    #[schemars(description = "Base branch for each repository in the project")]
+    #[schemars(
+        description = "Repositories to work in, each with an optional base branch. Optional; defaults to all of the project's repositories"
+    )]
+    #[serde(default)]
     pub repos: Vec<McpWorkspaceRepoInput>,
 }
 
@@ -327,6 +335,11 @@ pub struct GetWorkspaceStatusResponse {
     pub lines_added: Option<usize>,
     #[schemars(description = "Total lines removed across all files")]
     pub lines_removed: Option<usize>,
+    #[schemars(
+        description = "Most recent test run: overall pass flag plus per-repo passed/failed/skipped counts and failing test names"
+    )]
+    #[serde(default)]
+    pub latest_test_run: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -351,6 +364,10 @@ pub struct GetWorkspaceTranscriptResponse {
 pub struct GetWorkspaceDiffRequest {
     #[schemars(description = "The ID of the workspace to get diff for")]
     pub workspace_id: Uuid,
+    #[schemars(description = "Optional repo name or ID to limit the diff to one repo")]
+    pub repo: Option<String>,
+    #[schemars(description = "Whether to include files git does not track yet (default true)")]
+    pub include_untracked: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
@@ -363,14 +380,41 @@ pub struct FileDiffInfo {
     pub deletions: usize,
     #[schemars(description = "The unified diff content")]
     pub diff_content: String,
+    #[schemars(
+        description = "Whether the change is 'committed', 'staged', 'unstaged' or 'untracked' in the worktree"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[schemars(description = "Previous path of a renamed or copied file")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    #[schemars(description = "How similar a renamed or copied file is to its previous path, in percent")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct RepoDiffInfo {
+    #[schemars(description = "The repo ID")]
+    pub repo_id: Uuid,
+    #[schemars(description = "The repo name")]
+    pub repo_name: String,
+    #[schemars(description = "Number of changed files in the repo")]
+    pub files_changed: usize,
+    #[schemars(description = "Number of lines added in the repo")]
+    pub additions: usize,
+    #[schemars(description = "Number of lines deleted in the repo")]
+    pub deletions: usize,
+    #[schemars(description = "List of file diffs in the repo")]
+    pub files: Vec<FileDiffInfo>,
 }
 
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct GetWorkspaceDiffResponse {
     #[schemars(description = "The workspace ID")]
     pub workspace_id: String,
-    #[schemars(description = "List of file diffs")]
-    pub files: Vec<FileDiffInfo>,
+    #[schemars(description = "File diffs grouped by repo")]
+    pub repos: Vec<RepoDiffInfo>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -391,6 +435,31 @@ pub struct CloseWorkspaceResponse {
     pub message: String,
     #[schemars(description = "The merge commit SHA (only present when strategy is 'merge')")]
     pub merge_commit_sha: Option<String>,
+    #[schemars(description = "How many merges into the same repo had to finish first (only present when strategy is 'merge')")]
+    pub queue_position: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SendFollowUpRequest {
+    #[schemars(description = "The ID of the workspace whose agent should continue")]
+    pub workspace_id: Uuid,
+    #[schemars(description = "The follow-up instruction for the coding agent")]
+    pub prompt: String,
+    #[schemars(
+        description = "Optional coding agent executor; defaults to the one the workspace's latest session used"
+    )]
+    pub executor: Option<String>,
+    #[schemars(description = "Optional executor variant, if needed")]
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct SendFollowUpResponse {
+    pub workspace_id: String,
+    #[schemars(description = "The session the follow-up was sent to")]
+    pub session_id: String,
+    #[schemars(description = "The execution process running the follow-up")]
+    pub execution_process_id: String,
 }
 
 #[derive(Debug, Clone)]
@@ -591,6 +660,45 @@ impl TaskServer {
         Ok(())
     }
 
+    /// Parse an executor name such as `claude-code` or `CLAUDE_CODE` plus an optional variant
+    fn parse_executor_profile(
+        executor: &str,
+        variant: Option<String>,
+    ) -> Result<ExecutorProfileId, CallToolResult> {
+        let executor_trimmed = executor.trim();
+        if executor_trimmed.is_empty() {
+            return Err(
+                Self::err("Executor must not be empty.".to_string(), None::<String>).unwrap(),
+            );
+        }
+
+        let normalized_executor = executor_trimmed.replace('-', "_").to_ascii_uppercase();
+        let base_executor = match BaseCodingAgent::from_str(&normalized_executor) {
+            Ok(exec) => exec,
+            Err(_) => {
+                return Err(Self::err(
+                    format!("Unknown executor '{executor_trimmed}'."),
+                    None::<String>,
+                )
+                .unwrap());
+            }
+        };
+
+        let variant = variant.and_then(|v| {
+            let trimmed = v.trim();
+            if trimmed.is_empty() {
+                None
+            } else {
+                Some(trimmed.to_string())
+            }
+        });
+
+        Ok(ExecutorProfileId {
+            executor: base_executor,
+            variant,
+        })
+    }
+
     fn url(&self, path: &str) -> String {
         format!(
             "{}/{}",
@@ -934,41 +1042,9 @@ impl TaskServer {
             repos,
         }): Parameters<StartWorkspaceSessionRequest>,
     ) -> Result<CallToolResult, ErrorData> {
-        if repos.is_empty() {
-            return Self::err(
-                "At least one repository must be specified.".to_string(),
-                None::<String>,
-            );
-        }
-
-        let executor_trimmed = executor.trim();
-        if executor_trimmed.is_empty() {
-            return Self::err("Executor must not be empty.".to_string(), None::<String>);
-        }
-
-        let normalized_executor = executor_trimmed.replace('-', "_").to_ascii_uppercase();
-        let base_executor = match BaseCodingAgent::from_str(&normalized_executor) {
-            Ok(exec) => exec,
-            Err(_) => {
-                return Self::err(
-                    format!("Unknown executor '{executor_trimmed}'."),
-                    None::<String>,
-                );
-            }
-        };
-
-        let variant = variant.and_then(|v| {
-            let trimmed = v.trim();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some(trimmed.to_string())
-            }
-        });
-
-        let executor_profile_id = ExecutorProfileId {
-            executor: base_executor,
-            variant,
+        let executor_profile_id = match Self::parse_executor_profile(&executor, variant) {
+            Ok(profile) => profile,
+            Err(e) => return Ok(e),
         };
 
         let workspace_repos: Vec<WorkspaceRepoInput> = repos
@@ -981,8 +1057,10 @@ impl TaskServer {
 
         let payload = CreateTaskAttemptBody {
             task_id,
-            executor_profile_id,
+            executor_profile_id: Some(executor_profile_id),
             repos: workspace_repos,
+            parent_workspace_id: None,
+            existing_branch: None,
         };
 
         let url = self.url("/api/task-attempts");
@@ -1038,6 +1116,9 @@ impl TaskServer {
             status,
             parent_workspace_id: None,
             image_ids: None,
+            priority: None,
+            labels: None,
+            assignee: None,
         };
         let url = self.url(&format!("/api/tasks/{}", task_id));
         let updated_task: Task = match self.send_json(self.client.put(&url).json(&payload)).await {
@@ -1120,14 +1201,25 @@ impl TaskServer {
     }
 
     #[tool(
-        description = "Get workspace file diffs. Returns the list of changed files with their unified diff content, additions, and deletions."
+        description = "Get workspace file diffs. Returns the changed files of each repo with their unified diff content, additions, deletions, and whether each change is committed, staged, unstaged or untracked, plus per-repo totals. Pass `repo` to only diff one repo."
     )]
     async fn get_workspace_diff(
         &self,
-        Parameters(GetWorkspaceDiffRequest { workspace_id }): Parameters<GetWorkspaceDiffRequest>,
+        Parameters(GetWorkspaceDiffRequest {
+            workspace_id,
+            repo,
+            include_untracked,
+        }): Parameters<GetWorkspaceDiffRequest>,
     ) -> Result<CallToolResult, ErrorData> {
         let url = self.url(&format!("/api/workspaces/{}/diff", workspace_id));
-        let diff: GetWorkspaceDiffResponse = match self.send_json(self.client.get(&url)).await {
+        let mut request = self.client.get(&url);
+        if let Some(repo) = &repo {
+            request = request.query(&[("repo", repo)]);
+        }
+        if let Some(include_untracked) = include_untracked {
+            request = request.query(&[("include_untracked", include_untracked)]);
+        }
+        let diff: GetWorkspaceDiffResponse = match self.send_json(request).await {
             Ok(d) => d,
             Err(e) => return Ok(e),
         };
@@ -1166,12 +1258,79 @@ impl TaskServer {
 
         TaskServer::success(&result)
     }
+
+    #[tool(
+        description = "Send a follow-up prompt to the coding agent of an existing workspace. Continues the workspace's most recently used session; the agent runs in the background, so poll `get_workspace_status` and read the result with `get_workspace_transcript`."
+    )]
+    async fn send_follow_up(
+        &self,
+        Parameters(SendFollowUpRequest {
+            workspace_id,
+            prompt,
+            executor,
+            variant,
+        }): Parameters<SendFollowUpRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if prompt.trim().is_empty() {
+            return Self::err("Prompt must not be empty.".to_string(), None::<String>);
+        }
+
+        let url = self.url("/api/sessions");
+        let sessions: Vec<Session> = match self
+            .send_json(
+                self.client
+                    .get(&url)
+                    .query(&[("workspace_id", workspace_id)]),
+            )
+            .await
+        {
+            Ok(sessions) => sessions,
+            Err(e) => return Ok(e),
+        };
+        let Some(session) = sessions.into_iter().next() else {
+            return Self::err(
+                format!("Workspace {workspace_id} has no session to follow up on."),
+                None::<String>,
+            );
+        };
+
+        let Some(executor) = executor.or_else(|| session.executor.clone()) else {
+            return Self::err(
+                "The session has no executor recorded; pass `executor`.".to_string(),
+                None::<String>,
+            );
+        };
+        let executor_profile_id = match Self::parse_executor_profile(&executor, variant) {
+            Ok(profile) => profile,
+            Err(e) => return Ok(e),
+        };
+
+        let payload = CreateFollowUpAttempt {
+            prompt: self.expand_tags(&prompt).await,
+            executor_profile_id,
+            retry_process_id: None,
+            force_when_dirty: None,
+            perform_git_reset: None,
+        };
+        let url = self.url(&format!("/api/sessions/{}/follow-up", session.id));
+        let process: ExecutionProcess =
+            match self.send_json(self.client.post(&url).json(&payload)).await {
+                Ok(process) => process,
+                Err(e) => return Ok(e),
+            };
+
+        TaskServer::success(&SendFollowUpResponse {
+            workspace_id: workspace_id.to_string(),
+            session_id: session.id.to_string(),
+            execution_process_id: process.id.to_string(),
+        })
+    }
 }
 
 #[tool_handler]
 impl ServerHandler for TaskServer {
     fn get_info(&self) -> ServerInfo {
-        let mut instruction = "A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. You can get project ids by using `list projects`. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_workspace_session', 'get_task', 'update_task', 'delete_task', 'list_repos', 'get_repo', 'update_setup_script', 'update_cleanup_script', 'update_dev_server_script'. Make sure to pass `project_id`, `task_id`, or `repo_id` where required. You can use list tools to get the available ids.".to_string();
+        let mut instruction = "A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. You can get project ids by using `list projects`. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_workspace_session', 'get_task', 'update_task', 'delete_task', 'list_repos', 'get_repo', 'update_setup_script', 'update_cleanup_script', 'update_dev_server_script', 'get_workspace_status', 'get_workspace_transcript', 'get_workspace_diff', 'close_workspace', 'send_follow_up'. Make sure to pass `project_id`, `task_id`, or `repo_id` where required. You can use list tools to get the available ids.".to_string();
         if self.context.is_some() {
             let context_instruction = "Use 'get_context' to fetch project/task/workspace metadata for the active Vibe Kanban workspace session when available.";
             instruction = format!("{} {}", context_instruction, instruction);
@@ -1188,3 +1347,37 @@ impl ServerHandler for TaskServer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn executor_names_are_normalized() {
+        let profile =
+            TaskServer::parse_executor_profile(" claude-code ", Some(" PLAN ".to_string()))
+                .unwrap();
+        assert_eq!(profile.executor, BaseCodingAgent::ClaudeCode);
+        assert_eq!(profile.variant.as_deref(), Some("PLAN"));
+
+        let profile =
+            TaskServer::parse_executor_profile("CLAUDE_CODE", Some(" ".to_string())).unwrap();
+        assert_eq!(profile.variant, None);
+
+        assert!(TaskServer::parse_executor_profile("", None).is_err());
+        assert!(TaskServer::parse_executor_profile("no-such-agent", None).is_err());
+    }
+
+    #[test]
+    fn instructions_list_every_tool() {
+        let server = TaskServer::new("http://127.0.0.1:1");
+        let instructions = server.get_info().instructions.unwrap();
+
+        for name in server.tool_router.map.keys() {
+            if name == "get_context" {
+                continue;
+            }
+            assert!(instructions.contains(&format!("'{name}'")), "{name}");
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::error::ApiError;
+
+/// Optional `If-Match` precondition on a mutation. The entity tag is the `updated_at`
+/// timestamp the client last saw (quotes optional); `*` or no header skips the check.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IfMatch(pub Option<DateTime<Utc>>);
+
+/// Conflict payload returned when an `If-Match` precondition no longer holds
+#[derive(Debug, Serialize, TS)]
+pub struct StaleVersion<T> {
+    /// Version the client sent in `If-Match`
+    pub expected_version: DateTime<Utc>,
+    /// The entity as it is now, so the client can reconcile
+    pub current: T,
+}
+
+impl IfMatch {
+    /// Ok if no precondition was sent or `current_version` still matches it
+    pub fn check<T: Clone>(
+        &self,
+        current_version: DateTime<Utc>,
+        current: &T,
+    ) -> Result<(), StaleVersion<T>> {
+        match self.0 {
+            Some(expected_version) if expected_version != current_version => Err(StaleVersion {
+                expected_version,
+                current: current.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(Self(None));
+        };
+        let value = value
+            .to_str()
+            .map_err(|_| ApiError::BadRequest("Invalid If-Match header".to_string()))?
+            .trim();
+        if value == "*" {
+            return Ok(Self(None));
+        }
+
+        let tag = value.strip_prefix("W/").unwrap_or(value).trim_matches('"');
+        let version = DateTime::parse_from_rfc3339(tag).map_err(|_| {
+            ApiError::BadRequest(format!(
+                "If-Match must be the entity's updated_at timestamp, got '{tag}'"
+            ))
+        })?;
+        Ok(Self(Some(version.with_timezone(&Utc))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+
+    async fn if_match(value: Option<&str>) -> Result<IfMatch, ApiError> {
+        let mut builder = Request::builder().uri("/test").method("PUT");
+        if let Some(value) = value {
+            builder = builder.header(header::IF_MATCH, value);
+        }
+        let (mut parts, _) = builder.body(()).unwrap().into_parts();
+        IfMatch::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn entity_tags_are_updated_at_timestamps() {
+        let version: DateTime<Utc> = "2026-01-02T03:04:05.678Z".parse().unwrap();
+        for value in [
+            "2026-01-02T03:04:05.678Z",
+            "\"2026-01-02T03:04:05.678Z\"",
+            "W/\"2026-01-02T03:04:05.678+00:00\"",
+        ] {
+            assert_eq!(
+                if_match(Some(value)).await.unwrap().0,
+                Some(version),
+                "{value}"
+            );
+        }
+        assert_eq!(if_match(Some("*")).await.unwrap().0, None);
+        assert_eq!(if_match(None).await.unwrap().0, None);
+        assert!(matches!(
+            if_match(Some("\"abc123\"")).await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn stale_versions_conflict() {
+        let seen: DateTime<Utc> = "2026-01-02T03:04:05Z".parse().unwrap();
+        let current = seen + chrono::Duration::seconds(1);
+
+        assert!(IfMatch(None).check(current, &"task").is_ok());
+        assert!(IfMatch(Some(current)).check(current, &"task").is_ok());
+        let stale = IfMatch(Some(seen)).check(current, &"task").unwrap_err();
+        assert_eq!(stale.expected_version, seen);
+        assert_eq!(stale.current, "task");
+    }
+}
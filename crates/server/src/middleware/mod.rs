@@ -1,5 +1,7 @@
+pub mod if_match;
 pub mod model_loaders;
 pub mod origin;
 
+pub use if_match::*;
 pub use model_loaders::*;
 pub use origin::*;
@@ -0,0 +1,145 @@
+//! Limit/offset pagination shared by the list endpoints.
+//!
+//! Pagination is opt-in so existing clients keep receiving plain arrays: a request without
+//! `limit` or `offset` gets [`Listing::All`], one with either gets a [`Page`] carrying the
+//! total number of matching rows.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::error::ApiError;
+
+/// Page size used when only `offset` is given
+pub const DEFAULT_PAGE_LIMIT: i64 = 50;
+/// Larger `limit` values are clamped to this
+pub const MAX_PAGE_LIMIT: i64 = 500;
+
+/// `?limit=&offset=` query parameters. Extract alongside the endpoint's own query struct;
+/// both read the same query string.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct PageQuery {
+    #[serde(default)]
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Validated window of rows to return
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageWindow {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl PageQuery {
+    /// The requested window, or `None` when the caller did not ask for a page
+    pub fn window(&self) -> Result<Option<PageWindow>, ApiError> {
+        if self.limit.is_none() && self.offset.is_none() {
+            return Ok(None);
+        }
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        if limit < 1 {
+            return Err(ApiError::BadRequest("limit must be at least 1".to_string()));
+        }
+        let offset = self.offset.unwrap_or(0);
+        if offset < 0 {
+            return Err(ApiError::BadRequest(
+                "offset must not be negative".to_string(),
+            ));
+        }
+        Ok(Some(PageWindow {
+            limit: limit.min(MAX_PAGE_LIMIT),
+            offset,
+        }))
+    }
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Rows matching the request across all pages
+    #[ts(type = "number")]
+    pub total: i64,
+    #[ts(type = "number")]
+    pub limit: i64,
+    #[ts(type = "number")]
+    pub offset: i64,
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: i64, window: PageWindow) -> Self {
+        let has_more = window.offset + (items.len() as i64) < total;
+        Self {
+            items,
+            total,
+            limit: window.limit,
+            offset: window.offset,
+            has_more,
+        }
+    }
+}
+
+/// Response of a list endpoint: every row, or one page when `limit`/`offset` was given
+#[derive(Debug, Serialize, TS)]
+#[serde(untagged)]
+pub enum Listing<T> {
+    All(Vec<T>),
+    Page(Page<T>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_is_opt_in_and_clamped() {
+        assert_eq!(PageQuery::default().window().unwrap(), None);
+
+        let window = PageQuery {
+            limit: None,
+            offset: Some(20),
+        }
+        .window()
+        .unwrap();
+        assert_eq!(
+            window,
+            Some(PageWindow {
+                limit: DEFAULT_PAGE_LIMIT,
+                offset: 20
+            })
+        );
+
+        let window = PageQuery {
+            limit: Some(10_000),
+            offset: None,
+        }
+        .window()
+        .unwrap()
+        .unwrap();
+        assert_eq!(window.limit, MAX_PAGE_LIMIT);
+
+        assert!(
+            PageQuery {
+                limit: Some(0),
+                offset: None
+            }
+            .window()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn page_reports_whether_more_rows_follow() {
+        let window = PageWindow {
+            limit: 2,
+            offset: 2,
+        };
+        assert!(Page::new(vec![3, 4], 5, window).has_more);
+        let last = PageWindow {
+            offset: 4,
+            ..window
+        };
+        assert!(!Page::new(vec![5], 5, last).has_more);
+    }
+}
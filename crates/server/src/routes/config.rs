@@ -13,6 +13,7 @@ use axum::{
 };
 use deployment::{Deployment, DeploymentError};
 use executors::{
+    env::is_user_env_var_name,
     executors::{
         AvailabilityInfo, BaseAgentCapability, BaseCodingAgent, StandardCodingAgentExecutor,
     },
@@ -28,6 +29,7 @@ use services::services::{
         save_config_to_file,
     },
     container::ContainerService,
+    settings::Settings,
 };
 use tokio::fs;
 use ts_rs::TS;
@@ -40,6 +42,7 @@ pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/info", get(get_user_system_info))
         .route("/config", put(update_config))
+        .route("/settings", get(get_settings).put(update_settings))
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
         .route("/profiles", get(get_profiles).put(update_profiles))
@@ -134,6 +137,16 @@ async fn update_config(
         ));
     }
 
+    if let Some(name) = new_config
+        .env
+        .keys()
+        .find(|name| !is_user_env_var_name(name))
+    {
+        return ResponseJson(ApiResponse::error(&format!(
+            "Invalid environment variable name '{name}'"
+        )));
+    }
+
     // Get old config state before updating
     let old_config = deployment.config().read().await.clone();
 
@@ -194,6 +207,40 @@ async fn handle_config_events(deployment: &DeploymentImpl, old: &Config, new: &C
             deployment_clone.trigger_auto_project_setup().await;
         });
     }
+
+    // The config's workspace directory mirrors the worktree_base_dir setting
+    if old.workspace_dir != new.workspace_dir {
+        let settings = Settings {
+            worktree_base_dir: new.workspace_dir.clone(),
+            ..deployment.settings().get()
+        };
+        if let Err(e) = deployment.settings().update(settings).await {
+            tracing::warn!("Failed to update worktree base directory: {}", e);
+        }
+    }
+}
+
+async fn get_settings(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Settings>> {
+    ResponseJson(ApiResponse::success(deployment.settings().get()))
+}
+
+/// Replace the server settings; changes apply without a restart
+async fn update_settings(
+    State(deployment): State<DeploymentImpl>,
+    Json(new_settings): Json<Settings>,
+) -> Result<ResponseJson<ApiResponse<Settings>>, ApiError> {
+    let settings = deployment.settings().update(new_settings).await?;
+
+    let mut config = deployment.config().write().await;
+    if config.workspace_dir != settings.worktree_base_dir {
+        config.workspace_dir = settings.worktree_base_dir.clone();
+        save_config_to_file(&config, &config_path()).await?;
+    }
+    drop(config);
+
+    Ok(ResponseJson(ApiResponse::success(settings)))
 }
 
 async fn get_sound(Path(sound): Path<SoundFile>) -> Result<Response, ApiError> {
@@ -1,12 +1,21 @@
+use std::path::PathBuf;
+
 use axum::{
-    Router,
+    Json, Router,
     extract::{Query, State},
     response::Json as ResponseJson,
-    routing::get,
+    routing::{get, post},
+};
+use db::models::{
+    project_repo::ProjectRepo,
+    repo::Repo,
+    task::Task,
+    workspace::{CreateWorkspace, Workspace, WorkspaceContext},
+    workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
-use db::models::workspace::{Workspace, WorkspaceContext};
 use deployment::Deployment;
 use serde::{Deserialize, Serialize};
+use services::services::workspace_manager::{OrphanWorkspace, WorkspaceManager};
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
@@ -63,6 +72,142 @@ pub async fn get_context(
     }
 }
 
+/// Request body for re-registering an orphaned workspace directory
+#[derive(Debug, Deserialize)]
+pub struct AdoptOrphanRequest {
+    /// Orphaned workspace directory, as listed by `/containers/orphans`
+    pub path: PathBuf,
+    /// Task the adopted workspace belongs to
+    pub task_id: Uuid,
+    /// Branch to diff and merge against; defaults to each repo's default target branch,
+    /// then to the branch checked out in the main repository
+    #[serde(default)]
+    pub target_branch: Option<String>,
+}
+
+/// Workspace directories without a database row that still hold worktrees. Enable the
+/// `reconcile_orphan_worktrees` setting to keep them at startup instead of deleting them.
+pub async fn list_orphans(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<OrphanWorkspace>>>, ApiError> {
+    let orphans = WorkspaceManager::find_orphan_workspaces(&deployment.db().pool)
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Failed to inspect orphans: {e}")))?;
+    Ok(ResponseJson(ApiResponse::success(orphans)))
+}
+
+/// Register an orphaned workspace directory as a workspace of `task_id`, keeping its
+/// worktrees and branch. Every worktree must be on the same branch of a repo in the
+/// task's project and live in a directory named after that repo.
+pub async fn adopt_orphan(
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<AdoptOrphanRequest>,
+) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let orphan = WorkspaceManager::find_orphan_workspaces(pool)
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Failed to inspect orphans: {e}")))?
+        .into_iter()
+        .find(|o| o.path == request.path)
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "{} is not an orphaned workspace",
+                request.path.display()
+            ))
+        })?;
+    let task = Task::find_by_id(pool, request.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", request.task_id)))?;
+
+    let mut branch: Option<&str> = None;
+    let mut repos = Vec::new();
+    for worktree in &orphan.worktrees {
+        let Some(worktree_branch) = worktree.branch.as_deref() else {
+            return Err(ApiError::BadRequest(format!(
+                "Worktree '{}' has a detached HEAD",
+                worktree.dir_name
+            )));
+        };
+        if branch.is_some_and(|b| b != worktree_branch) {
+            return Err(ApiError::BadRequest(
+                "Worktrees are on different branches; a workspace has a single branch".to_string(),
+            ));
+        }
+        branch = Some(worktree_branch);
+
+        let repo = match worktree.repo_id {
+            Some(repo_id) => Repo::find_by_id(pool, repo_id).await?,
+            None => None,
+        }
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Worktree '{}' belongs to unregistered repository {}",
+                worktree.dir_name,
+                worktree.repo_path.display()
+            ))
+        })?;
+        if ProjectRepo::find_by_project_and_repo(pool, task.project_id, repo.id)
+            .await?
+            .is_none()
+        {
+            return Err(ApiError::BadRequest(format!(
+                "Repo '{}' is not part of the task's project",
+                repo.name
+            )));
+        }
+        if worktree.dir_name != repo.name {
+            return Err(ApiError::BadRequest(format!(
+                "Worktree directory '{}' must be named after repo '{}'",
+                worktree.dir_name, repo.name
+            )));
+        }
+        repos.push(repo);
+    }
+    let branch = branch.ok_or_else(|| ApiError::BadRequest("No worktrees".to_string()))?;
+
+    let mut workspace_repos = Vec::with_capacity(repos.len());
+    for repo in &repos {
+        let target_branch = match request
+            .target_branch
+            .clone()
+            .or_else(|| repo.default_target_branch.clone())
+        {
+            Some(target_branch) => target_branch,
+            None => deployment.git().get_head_info(&repo.path)?.branch,
+        };
+        workspace_repos.push(CreateWorkspaceRepo {
+            repo_id: repo.id,
+            target_branch,
+            start_from_ref: None,
+        });
+    }
+
+    let workspace = Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch: branch.to_string(),
+            agent_working_dir: (repos.len() == 1).then(|| repos[0].name.clone()),
+        },
+        Uuid::new_v4(),
+        task.id,
+    )
+    .await?;
+    WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
+    Workspace::update_container_ref(pool, workspace.id, &orphan.path.to_string_lossy()).await?;
+
+    tracing::info!(
+        "Adopted orphaned workspace {} as {} for task {}",
+        orphan.path.display(),
+        workspace.id,
+        task.id
+    );
+
+    let workspace = Workspace::find_by_id(pool, workspace.id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace.id)))?;
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
 pub fn router(_deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     Router::new()
         // NOTE: /containers/info is required by the VSCode extension (vibe-kanban-vscode)
@@ -70,4 +215,6 @@ pub fn router(_deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         // Do not remove this endpoint without updating the extension.
         .route("/containers/info", get(get_container_info))
         .route("/containers/attempt-context", get(get_context))
+        .route("/containers/orphans", get(list_orphans))
+        .route("/containers/orphans/adopt", post(adopt_orphan))
 }
@@ -0,0 +1,82 @@
+use axum::{
+    Router,
+    body::Body,
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::cost_report::{
+    CostPeriod, CostReportService, ProjectCost, cost_window, project_costs_csv,
+};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Reporting window and granularity shared by the cost endpoints
+#[derive(Debug, Deserialize)]
+pub struct CostQuery {
+    /// Start of the window, inclusive; defaults to 30 days before `to`
+    pub from: Option<DateTime<Utc>>,
+    /// End of the window, exclusive; defaults to now
+    pub to: Option<DateTime<Utc>>,
+    /// Bucket size for per-period rollups; defaults to days
+    pub period: Option<CostPeriod>,
+}
+
+impl CostQuery {
+    pub fn window(&self) -> Result<(DateTime<Utc>, DateTime<Utc>), ApiError> {
+        let (from, to) = cost_window(self.from, self.to, Utc::now());
+        if from >= to {
+            return Err(ApiError::BadRequest(
+                "Cost window must start before it ends".to_string(),
+            ));
+        }
+        Ok((from, to))
+    }
+}
+
+/// Respond with a CSV attachment
+pub fn csv_response(filename: &str, csv: String) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        Body::from(csv),
+    )
+        .into_response()
+}
+
+/// Reported agent spend per project over the window, most expensive first
+pub async fn get_costs(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<CostQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectCost>>>, ApiError> {
+    let (from, to) = query.window()?;
+    let costs = CostReportService::by_project(&deployment.db().pool, from, to).await?;
+    Ok(ResponseJson(ApiResponse::success(costs)))
+}
+
+pub async fn export_costs(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<CostQuery>,
+) -> Result<Response, ApiError> {
+    let (from, to) = query.window()?;
+    let costs = CostReportService::by_project(&deployment.db().pool, from, to).await?;
+    Ok(csv_response("costs.csv", project_costs_csv(&costs)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_costs))
+        .route("/export", get(export_costs));
+
+    Router::new().nest("/costs", inner)
+}
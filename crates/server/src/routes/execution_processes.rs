@@ -1,26 +1,51 @@
 use anyhow;
 use axum::{
-    Extension, Router,
+    BoxError, Extension, Json, Router,
+    body::Body,
     extract::{
         Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
+    http::header,
     middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
+    response::{
+        IntoResponse, Json as ResponseJson, Response, Sse,
+        sse::{Event, KeepAlive},
+    },
     routing::{get, post},
 };
+use chrono::{DateTime, Utc};
 use db::models::{
-    execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus},
+    execution_process::{
+        ExecutionProcess, ExecutionProcessError, ExecutionProcessFilter, ExecutionProcessRunReason,
+        ExecutionProcessStatus, ExecutionProcessSummary,
+    },
+    execution_process_artifact::ExecutionProcessArtifact,
+    execution_process_logs::ExecutionProcessLogs,
+    execution_process_policy::ExecutionProcessPolicy,
     execution_process_repo_state::ExecutionProcessRepoState,
+    workspace::Workspace,
 };
 use deployment::Deployment;
-use futures_util::{SinkExt, StreamExt, TryStreamExt};
-use serde::Deserialize;
-use services::services::container::ContainerService;
+use futures_util::{SinkExt, Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::ContainerService,
+    log_search::{DEFAULT_CONTEXT_LINES, LogSearchResult, LogSearchService},
+    process_reaper::ProcessReaperReport,
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+};
+use tokio_util::io::ReaderStream;
+use ts_rs::TS;
 use utils::{log_msg::LogMsg, response::ApiResponse};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_execution_process_middleware,
+    pagination::{Listing, Page, PageQuery},
+};
 
 #[derive(Debug, Deserialize)]
 pub struct SessionExecutionProcessQuery {
@@ -37,6 +62,286 @@ pub async fn get_execution_process_by_id(
     Ok(ResponseJson(ApiResponse::success(execution_process)))
 }
 
+/// Execution processes of a session, oldest first
+pub async fn get_execution_processes(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<SessionExecutionProcessQuery>,
+    Query(page): Query<PageQuery>,
+) -> Result<ResponseJson<ApiResponse<Listing<ExecutionProcess>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let show_soft_deleted = query.show_soft_deleted.unwrap_or(false);
+    let listing = match page.window()? {
+        Some(window) => {
+            let processes = ExecutionProcess::find_page_by_session_id(
+                pool,
+                query.session_id,
+                show_soft_deleted,
+                window.limit,
+                window.offset,
+            )
+            .await?;
+            let total =
+                ExecutionProcess::count_by_session_id(pool, query.session_id, show_soft_deleted)
+                    .await?;
+            Listing::Page(Page::new(processes, total, window))
+        }
+        None => Listing::All(
+            ExecutionProcess::find_by_session_id(pool, query.session_id, show_soft_deleted).await?,
+        ),
+    };
+    Ok(ResponseJson(ApiResponse::success(listing)))
+}
+
+/// Filters of the workspace and project process listings
+#[derive(Debug, Deserialize)]
+pub struct ExecutionProcessListQuery {
+    #[serde(default)]
+    pub status: Option<ExecutionProcessStatus>,
+    #[serde(default)]
+    pub run_reason: Option<ExecutionProcessRunReason>,
+    /// Processes started at or after this time
+    #[serde(default)]
+    pub started_after: Option<DateTime<Utc>>,
+    /// Processes started before this time
+    #[serde(default)]
+    pub started_before: Option<DateTime<Utc>>,
+    /// If true, include soft-deleted (dropped) processes
+    #[serde(default)]
+    pub show_soft_deleted: Option<bool>,
+}
+
+impl ExecutionProcessListQuery {
+    pub fn filter(
+        &self,
+        workspace_id: Option<Uuid>,
+        project_id: Option<Uuid>,
+    ) -> Result<ExecutionProcessFilter, ApiError> {
+        if let (Some(after), Some(before)) = (self.started_after, self.started_before)
+            && after >= before
+        {
+            return Err(ApiError::BadRequest(
+                "started_after must be before started_before".to_string(),
+            ));
+        }
+        Ok(ExecutionProcessFilter {
+            workspace_id,
+            project_id,
+            status: self.status.clone(),
+            run_reason: self.run_reason.clone(),
+            started_after: self.started_after,
+            started_before: self.started_before,
+            show_soft_deleted: self.show_soft_deleted.unwrap_or(false),
+        })
+    }
+}
+
+/// Processes matching `filter`, newest first, with their workspace, duration and exit code
+pub async fn list_execution_process_summaries(
+    deployment: &DeploymentImpl,
+    filter: &ExecutionProcessFilter,
+    page: PageQuery,
+) -> Result<Listing<ExecutionProcessSummary>, ApiError> {
+    let pool = &deployment.db().pool;
+    Ok(match page.window()? {
+        Some(window) => {
+            let processes =
+                ExecutionProcess::find_filtered(pool, filter, Some(window.limit), window.offset)
+                    .await?;
+            let total = ExecutionProcess::count_filtered(pool, filter).await?;
+            Listing::Page(Page::new(processes, total, window))
+        }
+        None => Listing::All(ExecutionProcess::find_filtered(pool, filter, None, 0).await?),
+    })
+}
+
+const DEFAULT_LOG_CHUNK_LIMIT: i64 = 500;
+const MAX_LOG_CHUNK_LIMIT: i64 = 5000;
+
+#[derive(Debug, Deserialize)]
+pub struct LogChunkQuery {
+    /// `next_cursor` of the previous chunk; omit to read from the start
+    #[serde(default)]
+    pub after_cursor: Option<i64>,
+    /// Maximum number of stored log records to read
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct LogChunkEntry {
+    /// Cursor of the stored record this output came from
+    #[ts(type = "number")]
+    pub cursor: i64,
+    pub stream: LogStream,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ExecutionProcessLogChunkResponse {
+    pub entries: Vec<LogChunkEntry>,
+    /// Pass as `after_cursor` to continue; echoes the request's cursor when nothing new was read
+    #[ts(type = "number | null")]
+    pub next_cursor: Option<i64>,
+    /// More records were stored after this chunk at the time of the read
+    pub has_more: bool,
+}
+
+/// Stored stdout/stderr of a process in chunks, so large logs can be read incrementally.
+/// Cursors are storage row ids and stay valid while the process's logs are kept.
+pub async fn get_execution_process_logs(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogChunkQuery>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcessLogChunkResponse>>, ApiError> {
+    let chunk = read_log_chunk(&deployment.db().pool, execution_process.id, &query).await?;
+    Ok(ResponseJson(ApiResponse::success(chunk)))
+}
+
+async fn read_log_chunk(
+    pool: &sqlx::SqlitePool,
+    execution_id: Uuid,
+    query: &LogChunkQuery,
+) -> Result<ExecutionProcessLogChunkResponse, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LOG_CHUNK_LIMIT);
+    if limit < 1 {
+        return Err(ApiError::BadRequest("limit must be at least 1".to_string()));
+    }
+    let limit = limit.min(MAX_LOG_CHUNK_LIMIT);
+
+    // Read one record past the limit to learn whether more follow
+    let mut chunks =
+        ExecutionProcessLogs::find_chunks_after(pool, execution_id, query.after_cursor, limit + 1)
+            .await?;
+    let has_more = chunks.len() as i64 > limit;
+    chunks.truncate(limit as usize);
+
+    let next_cursor = chunks
+        .last()
+        .map(|chunk| chunk.cursor)
+        .or(query.after_cursor);
+    let mut entries = Vec::new();
+    for chunk in chunks {
+        for line in chunk.logs.lines().filter(|line| !line.trim().is_empty()) {
+            let (stream, content) = match serde_json::from_str::<LogMsg>(line) {
+                Ok(LogMsg::Stdout(content)) => (LogStream::Stdout, content),
+                Ok(LogMsg::Stderr(content)) => (LogStream::Stderr, content),
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::warn!(
+                        "Skipping unparseable log line for execution process {}: {}",
+                        execution_id,
+                        e
+                    );
+                    continue;
+                }
+            };
+            entries.push(LogChunkEntry {
+                cursor: chunk.cursor,
+                stream,
+                content,
+            });
+        }
+    }
+
+    Ok(ExecutionProcessLogChunkResponse {
+        entries,
+        next_cursor,
+        has_more,
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStreamSelection {
+    Stdout,
+    Stderr,
+    /// Both streams, interleaved in the order they were written
+    #[default]
+    Combined,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProcessOutputQuery {
+    #[serde(default)]
+    pub stream: OutputStreamSelection,
+}
+
+/// The stored output of `stream` of a process, as it was written
+async fn read_stored_output(
+    pool: &sqlx::SqlitePool,
+    execution_id: Uuid,
+    stream: OutputStreamSelection,
+) -> Result<String, ApiError> {
+    let records = ExecutionProcessLogs::find_by_execution_id(pool, execution_id).await?;
+
+    let mut output = String::new();
+    for line in records
+        .iter()
+        .flat_map(|record| record.logs.lines())
+        .filter(|line| !line.trim().is_empty())
+    {
+        match (serde_json::from_str::<LogMsg>(line), stream) {
+            (
+                Ok(LogMsg::Stdout(content)),
+                OutputStreamSelection::Stdout | OutputStreamSelection::Combined,
+            )
+            | (
+                Ok(LogMsg::Stderr(content)),
+                OutputStreamSelection::Stderr | OutputStreamSelection::Combined,
+            ) => output.push_str(&content),
+            (Ok(_), _) => {}
+            (Err(e), _) => tracing::warn!(
+                "Skipping unparseable log line for execution process {}: {}",
+                execution_id,
+                e
+            ),
+        }
+    }
+    Ok(output)
+}
+
+/// The process's full stored output as a plain-text download, for attaching to bug reports.
+/// Output of a running process is what has been stored so far.
+pub async fn download_execution_process_output(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProcessOutputQuery>,
+) -> Result<Response, ApiError> {
+    let output =
+        read_stored_output(&deployment.db().pool, execution_process.id, query.stream).await?;
+
+    let stream = match query.stream {
+        OutputStreamSelection::Stdout => "stdout",
+        OutputStreamSelection::Stderr => "stderr",
+        OutputStreamSelection::Combined => "output",
+    };
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/plain; charset=utf-8".to_string(),
+            ),
+            (header::CONTENT_LENGTH, output.len().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"process-{}-{stream}.log\"",
+                    execution_process.id
+                ),
+            ),
+        ],
+        output,
+    )
+        .into_response())
+}
+
 pub async fn stream_raw_logs_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
@@ -178,6 +483,158 @@ pub async fn stop_execution_process(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Run the action of a failed or killed process again, in the same session and worktree, as
+/// a new process linked to it. Returns 409 if the process did not fail or was killed, is
+/// dropped from the history, or is a coding agent run followed by another; 400 if the
+/// workspace is closed or other processes are running.
+pub async fn retry_execution_process(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let (workspace, session) = execution_process
+        .parent_workspace_and_session(pool)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Workspace of execution process {} not found",
+                execution_process.id
+            ))
+        })?;
+    let _lock = WorkspaceLock::try_acquire(workspace.id, WorkspaceOperation::Retry)?;
+    check_retryable(pool, &execution_process, &workspace, session.id).await?;
+    let action = execution_process
+        .executor_action()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let mut retry = deployment
+        .container()
+        .start_execution(&workspace, &session, action, &execution_process.run_reason)
+        .await?;
+    ExecutionProcess::set_retried_from(pool, retry.id, execution_process.id).await?;
+    retry.retried_from = Some(execution_process.id);
+    Ok(ResponseJson(ApiResponse::success(retry)))
+}
+
+/// Refuse to retry a process that did not fail, is dropped from the history or was
+/// followed by another coding agent run, or while its workspace is closed or busy
+async fn check_retryable(
+    pool: &sqlx::SqlitePool,
+    execution_process: &ExecutionProcess,
+    workspace: &Workspace,
+    session_id: Uuid,
+) -> Result<(), ApiError> {
+    if !matches!(
+        execution_process.status,
+        ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
+    ) {
+        return Err(ApiError::Conflict(
+            "Only failed or killed execution processes can be retried".to_string(),
+        ));
+    }
+    if execution_process.dropped {
+        return Err(ApiError::Conflict(
+            "Execution process is no longer part of the history".to_string(),
+        ));
+    }
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace is closed (no active worktree)".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot retry while processes are running".to_string(),
+        ));
+    }
+    if execution_process.run_reason == ExecutionProcessRunReason::CodingAgent {
+        let latest = ExecutionProcess::find_latest_by_session_and_run_reason(
+            pool,
+            session_id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+        if latest.is_some_and(|latest| latest.id != execution_process.id) {
+            return Err(ApiError::Conflict(
+                "A coding agent has run in the session since this run".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ProcessInputRequest {
+    /// Text for the agent; written to stdin as a line for plain processes
+    pub input: String,
+}
+
+/// Send input to a running process, such as the answer to a question the agent is waiting on.
+/// Returns 409 if the process is not running or does not accept input.
+pub async fn send_execution_process_input(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ProcessInputRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if execution_process.status != ExecutionProcessStatus::Running {
+        return Err(ApiError::Conflict(
+            "Execution process is not running".to_string(),
+        ));
+    }
+    let input = deployment
+        .container()
+        .process_input(&execution_process.id)
+        .await
+        .ok_or_else(|| ApiError::Conflict("Execution process does not accept input".to_string()))?;
+    input
+        .send(payload.input)
+        .await
+        .map_err(|e| ApiError::Conflict(e.to_string()))?;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Whether a running process appears to wait for input, as server-sent `awaiting_input`
+/// events with data `true` or `false`: the current state first, then each change until the
+/// process exits. A process is taken to wait once it has been silent for a while.
+/// Returns 409 if the process is not running or does not accept input.
+pub async fn stream_execution_process_input_state(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Sse<impl Stream<Item = Result<Event, BoxError>>>, ApiError> {
+    let awaiting = deployment
+        .container()
+        .process_input(&execution_process.id)
+        .await
+        .filter(|_| execution_process.status == ExecutionProcessStatus::Running)
+        .ok_or_else(|| {
+            ApiError::Conflict(
+                "Execution process is not running or does not accept input".to_string(),
+            )
+        })?
+        .subscribe();
+
+    let events = futures_util::stream::unfold((awaiting, true), |(mut rx, first)| async move {
+        // The stream ends when the process exits and the state's sender is dropped
+        if !first && rx.changed().await.is_err() {
+            return None;
+        }
+        let awaiting = *rx.borrow_and_update();
+        let event = Event::default()
+            .event("awaiting_input")
+            .data(awaiting.to_string());
+        Some((Ok(event), (rx, false)))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
 pub async fn stream_execution_processes_by_session_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
@@ -243,24 +700,409 @@ pub async fn get_execution_process_repo_states(
     Ok(ResponseJson(ApiResponse::success(repo_states)))
 }
 
+/// The agent policy the process ran under, if its project had one
+pub async fn get_execution_process_policy(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutionProcessPolicy>>>, ApiError> {
+    let policy = ExecutionProcessPolicy::find_by_execution_process_id(
+        &deployment.db().pool,
+        execution_process.id,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogSearchQuery {
+    /// Literal text to find in process output
+    pub q: String,
+    pub workspace_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    /// Lines of context around each match
+    pub context: Option<usize>,
+}
+
+/// Processes of a workspace or project whose stored output contains the query, newest first,
+/// with the matching lines and the lines around them
+pub async fn search_execution_process_logs(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<LogSearchResult>>>, ApiError> {
+    if query.q.is_empty() {
+        return Err(ApiError::BadRequest("q must not be empty".to_string()));
+    }
+    if query.workspace_id.is_none() && query.project_id.is_none() {
+        return Err(ApiError::BadRequest(
+            "workspace_id or project_id is required".to_string(),
+        ));
+    }
+    let results = LogSearchService::search(
+        &deployment.db().pool,
+        query.workspace_id,
+        query.project_id,
+        &query.q,
+        query.context.unwrap_or(DEFAULT_CONTEXT_LINES),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(results)))
+}
+
+/// Report of the process reaper's last run: executions marked running with no process, or
+/// whose worktree was removed, and processes that outlived their execution. `None` until it
+/// has run.
+pub async fn get_reaper_report(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProcessReaperReport>>>, ApiError> {
+    let report = deployment.container().last_reaper_report().await;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Run the process reaper now instead of waiting for its next periodic run
+pub async fn run_reaper(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProcessReaperReport>>, ApiError> {
+    let report = deployment.container().reap_processes().await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Files kept from the workspace after the process ran, in path order
+pub async fn get_execution_process_artifacts(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcessArtifact>>>, ApiError> {
+    let artifacts = ExecutionProcessArtifact::find_by_execution_process_id(
+        &deployment.db().pool,
+        execution_process.id,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(artifacts)))
+}
+
+pub async fn download_execution_process_artifact(
+    State(deployment): State<DeploymentImpl>,
+    Path((id, artifact_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, ApiError> {
+    let artifact = ExecutionProcessArtifact::find_by_id(&deployment.db().pool, artifact_id)
+        .await?
+        .filter(|artifact| artifact.execution_process_id == id)
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Artifact {} not found for execution process",
+                artifact_id
+            ))
+        })?;
+    let object = deployment
+        .settings()
+        .artifact_store()?
+        .open(&artifact.stored_path)
+        .await?;
+    let filename = artifact
+        .path
+        .rsplit('/')
+        .next()
+        .unwrap_or(&artifact.path)
+        .replace('"', "_");
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_LENGTH, object.size.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        Body::from_stream(ReaderStream::new(object.reader)),
+    )
+        .into_response())
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
+        .route("/retry", post(retry_execution_process))
+        .route("/input", post(send_execution_process_input))
+        .route("/input/stream", get(stream_execution_process_input_state))
         .route("/repo-states", get(get_execution_process_repo_states))
+        .route("/policy", get(get_execution_process_policy))
+        .route("/logs", get(get_execution_process_logs))
+        .route("/output", get(download_execution_process_output))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
+        .route("/artifacts", get(get_execution_process_artifacts))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
         ));
 
     let workspaces_router = Router::new()
+        .route("/", get(get_execution_processes))
+        .route("/search", get(search_execution_process_logs))
+        .route("/reaper", get(get_reaper_report).post(run_reaper))
         .route(
             "/stream/session/ws",
             get(stream_execution_processes_by_session_ws),
         )
+        .route(
+            "/{id}/artifacts/{artifact_id}",
+            get(download_execution_process_artifact),
+        )
         .nest("/{id}", workspace_id_router);
 
     Router::new().nest("/execution-processes", workspaces_router)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        seed_execution_process, seed_project, seed_task, seed_workspace, start_execution_process,
+        test_pool,
+    };
+
+    fn jsonl(messages: &[LogMsg]) -> String {
+        messages
+            .iter()
+            .map(|msg| serde_json::to_string(msg).unwrap() + "\n")
+            .collect()
+    }
+
+    fn contents(chunk: &ExecutionProcessLogChunkResponse) -> Vec<&str> {
+        chunk.entries.iter().map(|e| e.content.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn logs_are_read_in_chunks_after_a_cursor() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[&repos[0]]).await;
+        let process =
+            seed_execution_process(&pool, workspace.id, ExecutionProcessRunReason::CodingAgent)
+                .await;
+        for record in [
+            jsonl(&[LogMsg::Stdout("one".to_string())]),
+            jsonl(&[LogMsg::Stderr("two".to_string()), LogMsg::Ready]),
+            "not json\n".to_string(),
+            jsonl(&[LogMsg::Stdout("three".to_string())]),
+        ] {
+            ExecutionProcessLogs::append_log_line(&pool, process.id, &record)
+                .await
+                .unwrap();
+        }
+        let read = |after_cursor, limit| {
+            let pool = pool.clone();
+            async move {
+                read_log_chunk(
+                    &pool,
+                    process.id,
+                    &LogChunkQuery {
+                        after_cursor,
+                        limit,
+                    },
+                )
+                .await
+            }
+        };
+
+        let first = read(None, Some(2)).await.unwrap();
+        assert_eq!(contents(&first), vec!["one", "two"]);
+        assert!(matches!(first.entries[1].stream, LogStream::Stderr));
+        assert!(first.has_more);
+
+        let rest = read(first.next_cursor, None).await.unwrap();
+        assert_eq!(contents(&rest), vec!["three"]);
+        assert!(!rest.has_more);
+
+        let caught_up = read(rest.next_cursor, None).await.unwrap();
+        assert!(caught_up.entries.is_empty());
+        assert_eq!(caught_up.next_cursor, rest.next_cursor);
+
+        assert!(matches!(
+            read(None, Some(0)).await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    async fn reload(pool: &sqlx::SqlitePool, process: &ExecutionProcess) -> ExecutionProcess {
+        ExecutionProcess::find_by_id(pool, process.id)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn only_the_latest_failed_run_of_an_open_idle_workspace_can_be_retried() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "feature", &[&repos[0]]).await;
+        let reason = ExecutionProcessRunReason::CodingAgent;
+        let failed = seed_execution_process(&pool, workspace.id, reason.clone()).await;
+        let retryable = |process: ExecutionProcess, workspace: Workspace| {
+            let pool = pool.clone();
+            async move { check_retryable(&pool, &process, &workspace, process.session_id).await }
+        };
+
+        assert!(matches!(
+            retryable(failed.clone(), workspace.clone()).await,
+            Err(ApiError::Conflict(_))
+        ));
+        ExecutionProcess::update_completion(
+            &pool,
+            failed.id,
+            ExecutionProcessStatus::Failed,
+            Some(1),
+        )
+        .await
+        .unwrap();
+        let failed = reload(&pool, &failed).await;
+        assert!(matches!(
+            retryable(failed.clone(), workspace.clone()).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        Workspace::update_container_ref(&pool, workspace.id, "/tmp/workspace")
+            .await
+            .unwrap();
+        let workspace = Workspace::find_by_id(&pool, workspace.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(retryable(failed.clone(), workspace.clone()).await.is_ok());
+
+        // Runs are ordered by their millisecond timestamps
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let next = start_execution_process(&pool, failed.session_id, reason).await;
+        assert!(matches!(
+            retryable(failed.clone(), workspace.clone()).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        ExecutionProcess::update_completion(
+            &pool,
+            next.id,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            retryable(failed, workspace).await,
+            Err(ApiError::Conflict(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn process_listings_filter_by_scope_status_and_run_reason() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "feature", &[&repos[0]]).await;
+        let sibling = seed_workspace(&pool, task.id, "other", &[&repos[0]]).await;
+        let (elsewhere, other_repos) = seed_project(&pool, &["web"]).await;
+        let other_task = seed_task(&pool, elsewhere.id, "Fix footer").await;
+        let unrelated = seed_workspace(&pool, other_task.id, "footer", &[&other_repos[0]]).await;
+
+        let agent =
+            seed_execution_process(&pool, workspace.id, ExecutionProcessRunReason::CodingAgent)
+                .await;
+        ExecutionProcess::update_completion(
+            &pool,
+            agent.id,
+            ExecutionProcessStatus::Failed,
+            Some(1),
+        )
+        .await
+        .unwrap();
+        seed_execution_process(&pool, workspace.id, ExecutionProcessRunReason::SetupScript).await;
+        seed_execution_process(&pool, sibling.id, ExecutionProcessRunReason::CodingAgent).await;
+        seed_execution_process(&pool, unrelated.id, ExecutionProcessRunReason::CodingAgent).await;
+
+        let list = |filter: ExecutionProcessFilter| {
+            let pool = pool.clone();
+            async move {
+                let processes = ExecutionProcess::find_filtered(&pool, &filter, None, 0)
+                    .await
+                    .unwrap();
+                let total = ExecutionProcess::count_filtered(&pool, &filter)
+                    .await
+                    .unwrap();
+                assert_eq!(processes.len() as i64, total);
+                processes
+            }
+        };
+        let query = ExecutionProcessListQuery {
+            status: None,
+            run_reason: None,
+            started_after: None,
+            started_before: None,
+            show_soft_deleted: None,
+        };
+
+        let in_workspace = list(query.filter(Some(workspace.id), None).unwrap()).await;
+        assert_eq!(in_workspace.len(), 2);
+        assert!(in_workspace.iter().all(|p| p.workspace_id == workspace.id));
+        let in_project = list(query.filter(None, Some(project.id)).unwrap()).await;
+        assert_eq!(in_project.len(), 3);
+
+        let failed_agents = ExecutionProcessListQuery {
+            status: Some(ExecutionProcessStatus::Failed),
+            run_reason: Some(ExecutionProcessRunReason::CodingAgent),
+            ..query
+        };
+        let failed = list(failed_agents.filter(None, Some(project.id)).unwrap()).await;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].process.id, agent.id);
+        assert!(failed[0].duration_ms.is_some());
+
+        let now = Utc::now();
+        let inverted = ExecutionProcessListQuery {
+            started_after: Some(now),
+            started_before: Some(now - chrono::Duration::hours(1)),
+            ..failed_agents
+        };
+        assert!(matches!(
+            inverted.filter(None, None),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn stored_output_is_read_per_stream_in_write_order() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[&repos[0]]).await;
+        let process =
+            seed_execution_process(&pool, workspace.id, ExecutionProcessRunReason::DevServer).await;
+        for record in [
+            jsonl(&[LogMsg::Stdout("building\n".to_string())]),
+            jsonl(&[
+                LogMsg::Stderr("warning: unused\n".to_string()),
+                LogMsg::Ready,
+            ]),
+            "not json\n".to_string(),
+            jsonl(&[LogMsg::Stdout("done\n".to_string())]),
+        ] {
+            ExecutionProcessLogs::append_log_line(&pool, process.id, &record)
+                .await
+                .unwrap();
+        }
+        let output = |stream| read_stored_output(&pool, process.id, stream);
+
+        assert_eq!(
+            output(OutputStreamSelection::Stdout).await.unwrap(),
+            "building\ndone\n"
+        );
+        assert_eq!(
+            output(OutputStreamSelection::Stderr).await.unwrap(),
+            "warning: unused\n"
+        );
+        assert_eq!(
+            output(OutputStreamSelection::Combined).await.unwrap(),
+            "building\nwarning: unused\ndone\n"
+        );
+    }
+}
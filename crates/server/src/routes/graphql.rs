@@ -0,0 +1,426 @@
+//! Read-only GraphQL API over projects, tasks, workspaces and their agent turns.
+//!
+//! Dashboards can fetch a whole board, including workspace statuses and diff stats, in one
+//! request instead of walking the REST endpoints. `GET /api/graphql` serves GraphiQL.
+
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Object, Result, Schema, SchemaBuilder, SimpleObject,
+    http::GraphiQLSource,
+};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::{
+    Extension, Router,
+    response::{Html, IntoResponse},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use db::models::{
+    coding_agent_turn::CodingAgentTurn,
+    project::Project,
+    session::Session,
+    task::{Task, TaskFilter, TaskStatus, TaskWithAttemptStatus},
+    workspace::{Workspace, WorkspaceWithStatus},
+};
+use deployment::Deployment;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, routes::task_attempts::workspace_summary};
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Deepest selection accepted; project → task → workspace → session → turn is five levels
+const MAX_QUERY_DEPTH: usize = 10;
+/// Turns returned per session when `limit` is not given
+const DEFAULT_TURN_LIMIT: i64 = 50;
+
+pub fn schema(deployment: DeploymentImpl) -> ApiSchema {
+    schema_builder(deployment.db().pool.clone())
+        .data(deployment)
+        .finish()
+}
+
+/// Everything but the deployment, which only diff stats need
+fn schema_builder(pool: SqlitePool) -> SchemaBuilder<QueryRoot, EmptyMutation, EmptySubscription> {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(pool)
+        .limit_depth(MAX_QUERY_DEPTH)
+}
+
+fn deployment<'a>(ctx: &Context<'a>) -> &'a DeploymentImpl {
+    ctx.data_unchecked::<DeploymentImpl>()
+}
+
+fn pool<'a>(ctx: &Context<'a>) -> &'a SqlitePool {
+    ctx.data_unchecked::<SqlitePool>()
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn projects(&self, ctx: &Context<'_>) -> Result<Vec<ProjectNode>> {
+        let projects = Project::find_all(pool(ctx)).await?;
+        Ok(projects.into_iter().map(ProjectNode).collect())
+    }
+
+    async fn project(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<ProjectNode>> {
+        let project = Project::find_by_id(pool(ctx), id).await?;
+        Ok(project.map(ProjectNode))
+    }
+
+    async fn task(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<TaskNode>> {
+        let pool = pool(ctx);
+        let Some(task) = Task::find_by_id(pool, id).await? else {
+            return Ok(None);
+        };
+        let tasks = Task::find_by_project_id_with_attempt_status(pool, task.project_id).await?;
+        Ok(tasks.into_iter().find(|task| task.id == id).map(TaskNode))
+    }
+
+    async fn workspace(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<WorkspaceNode>> {
+        let workspace = Workspace::find_by_id_with_status(pool(ctx), id).await?;
+        Ok(workspace.map(WorkspaceNode))
+    }
+}
+
+pub struct ProjectNode(Project);
+
+#[Object]
+impl ProjectNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    /// Tasks of the project, newest first, optionally narrowed like `GET /tasks`
+    async fn tasks(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] statuses: Vec<String>,
+        #[graphql(default)] labels: Vec<String>,
+        assignee: Option<String>,
+        text: Option<String>,
+    ) -> Result<Vec<TaskNode>> {
+        let statuses = statuses
+            .iter()
+            .map(|status| {
+                status
+                    .parse::<TaskStatus>()
+                    .map_err(|_| format!("Unknown task status '{status}'"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let filter = TaskFilter {
+            statuses,
+            labels,
+            assignee,
+            text,
+            ..Default::default()
+        };
+        let tasks =
+            Task::find_filtered_with_attempt_status(pool(ctx), self.0.id, &filter, None, 0).await?;
+        Ok(tasks.into_iter().map(TaskNode).collect())
+    }
+}
+
+pub struct TaskNode(TaskWithAttemptStatus);
+
+#[Object]
+impl TaskNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn project_id(&self) -> Uuid {
+        self.0.project_id
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn status(&self) -> String {
+        self.0.status.to_string()
+    }
+
+    async fn priority(&self) -> Option<String> {
+        self.0.priority.map(|priority| priority.to_string())
+    }
+
+    async fn labels(&self) -> Vec<String> {
+        self.0.labels.to_vec()
+    }
+
+    async fn assignee(&self) -> Option<&str> {
+        self.0.assignee.as_deref()
+    }
+
+    async fn has_in_progress_attempt(&self) -> bool {
+        self.0.has_in_progress_attempt
+    }
+
+    async fn last_attempt_failed(&self) -> bool {
+        self.0.last_attempt_failed
+    }
+
+    async fn executor(&self) -> &str {
+        &self.0.executor
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    /// Workspaces (attempts) of the task, most recent first
+    async fn workspaces(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] include_archived: bool,
+    ) -> Result<Vec<WorkspaceNode>> {
+        let pool = pool(ctx);
+        let mut nodes = Vec::new();
+        for workspace in Workspace::fetch_all(pool, Some(self.0.id)).await? {
+            if workspace.archived && !include_archived {
+                continue;
+            }
+            if let Some(workspace) = Workspace::find_by_id_with_status(pool, workspace.id).await? {
+                nodes.push(WorkspaceNode(workspace));
+            }
+        }
+        Ok(nodes)
+    }
+}
+
+pub struct WorkspaceNode(WorkspaceWithStatus);
+
+#[Object]
+impl WorkspaceNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn task_id(&self) -> Uuid {
+        self.0.task_id
+    }
+
+    async fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+
+    async fn branch(&self) -> &str {
+        &self.0.branch
+    }
+
+    async fn archived(&self) -> bool {
+        self.0.archived
+    }
+
+    async fn pinned(&self) -> bool {
+        self.0.pinned
+    }
+
+    async fn is_running(&self) -> bool {
+        self.0.is_running
+    }
+
+    async fn is_errored(&self) -> bool {
+        self.0.is_errored
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    /// Changes against each repo's target branch; null while the workspace has no worktree
+    async fn diff_stats(&self, ctx: &Context<'_>) -> Result<Option<DiffStatsNode>> {
+        if self.0.container_ref.is_none() {
+            return Ok(None);
+        }
+        let stats =
+            workspace_summary::compute_workspace_diff_stats(deployment(ctx), &self.0.workspace)
+                .await?;
+        Ok(Some(DiffStatsNode {
+            files_changed: stats.files_changed,
+            lines_added: stats.lines_added,
+            lines_removed: stats.lines_removed,
+        }))
+    }
+
+    /// Sessions of the workspace, most recently used first
+    async fn sessions(&self, ctx: &Context<'_>) -> Result<Vec<SessionNode>> {
+        let sessions = Session::find_by_workspace_id(pool(ctx), self.0.id).await?;
+        Ok(sessions.into_iter().map(SessionNode).collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct DiffStatsNode {
+    files_changed: usize,
+    lines_added: usize,
+    lines_removed: usize,
+}
+
+pub struct SessionNode(Session);
+
+#[Object]
+impl SessionNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn executor(&self) -> Option<&str> {
+        self.0.executor.as_deref()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// Coding agent turns of the session, oldest first
+    async fn turns(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        #[graphql(default)] offset: i64,
+    ) -> Result<Vec<TurnNode>> {
+        let limit = limit.unwrap_or(DEFAULT_TURN_LIMIT).clamp(1, 500);
+        let turns =
+            CodingAgentTurn::find_page_by_session_id(pool(ctx), self.0.id, limit, offset.max(0))
+                .await?;
+        Ok(turns.into_iter().map(TurnNode).collect())
+    }
+}
+
+pub struct TurnNode(CodingAgentTurn);
+
+#[Object]
+impl TurnNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn execution_process_id(&self) -> Uuid {
+        self.0.execution_process_id
+    }
+
+    async fn prompt(&self) -> Option<&str> {
+        self.0.prompt.as_deref()
+    }
+
+    async fn summary(&self) -> Option<&str> {
+        self.0.summary.as_deref()
+    }
+
+    async fn seen(&self) -> bool {
+        self.0.seen
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+}
+
+pub async fn graphql_handler(
+    Extension(schema): Extension<ApiSchema>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    schema.execute(request.into_inner()).await.into()
+}
+
+pub async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/api/graphql").finish())
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/graphql", get(graphiql).post(graphql_handler))
+        .layer(Extension(schema(deployment.clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_support::{seed_project, seed_task, seed_workspace, test_pool};
+
+    #[tokio::test]
+    async fn a_board_is_fetched_in_one_query() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let login = seed_task(&pool, project.id, "Add login").await;
+        let done = seed_task(&pool, project.id, "Write docs").await;
+        Task::update_status(&pool, done.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        seed_workspace(&pool, login.id, "vk/login", &[&repos[0]]).await;
+        let archived = seed_workspace(&pool, login.id, "vk/login-old", &[&repos[0]]).await;
+        Workspace::set_archived(&pool, archived.id, true)
+            .await
+            .unwrap();
+        let schema = schema_builder(pool).finish();
+
+        let response = schema
+            .execute(format!(
+                r#"{{ project(id: "{}") {{
+                    name
+                    tasks(statuses: ["todo"]) {{ title status workspaces {{ branch archived }} }}
+                }} }}"#,
+                project.id
+            ))
+            .await;
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            json!({ "project": {
+                "name": "Test project",
+                "tasks": [{
+                    "title": "Add login",
+                    "status": "todo",
+                    "workspaces": [{ "branch": "vk/login", "archived": false }],
+                }],
+            }})
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_statuses_are_query_errors() {
+        let pool = test_pool().await;
+        let (project, _) = seed_project(&pool, &[]).await;
+        let schema = schema_builder(pool).finish();
+
+        let response = schema
+            .execute(format!(
+                r#"{{ project(id: "{}") {{ tasks(statuses: ["blocked"]) {{ id }} }} }}"#,
+                project.id
+            ))
+            .await;
+
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("blocked"));
+    }
+}
@@ -9,21 +9,26 @@ use crate::{DeploymentImpl, middleware};
 pub mod approvals;
 pub mod config;
 pub mod containers;
+pub mod costs;
 pub mod filesystem;
 // pub mod github;
 pub mod events;
 pub mod execution_processes;
 pub mod frontend;
+pub mod graphql;
 pub mod health;
 pub mod images;
 pub mod oauth;
 pub mod organizations;
+pub mod overview;
 pub mod projects;
 pub mod repo;
 pub mod scratch;
+pub mod secrets;
 pub mod sessions;
 pub mod tags;
 pub mod task_attempts;
+pub mod task_templates;
 pub mod tasks;
 pub mod terminal;
 pub mod workspaces;
@@ -34,18 +39,23 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .route("/health", get(health::health_check))
         .merge(config::router())
         .merge(containers::router(&deployment))
+        .merge(costs::router())
         .merge(projects::router(&deployment))
         .merge(tasks::router(&deployment))
+        .merge(task_templates::router())
         .merge(task_attempts::router(&deployment))
         .merge(execution_processes::router(&deployment))
         .merge(tags::router(&deployment))
         .merge(oauth::router())
         .merge(organizations::router())
+        .merge(overview::router())
         .merge(filesystem::router())
         .merge(repo::router())
         .merge(events::router(&deployment))
+        .merge(graphql::router(&deployment))
         .merge(approvals::router())
         .merge(scratch::router(&deployment))
+        .merge(secrets::router())
         .merge(sessions::router(&deployment))
         .merge(terminal::router())
         .nest("/workspaces", workspaces::router())
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use axum::{Router, extract::State, response::Json as ResponseJson, routing::get};
+use db::models::{
+    execution_process::{
+        ExecutionProcess, ExecutionProcessFilter, ExecutionProcessStatus, ExecutionProcessSummary,
+    },
+    project::Project,
+    task::{Task, TaskStatus, TaskWithAttemptStatus},
+    workspace::{Workspace, WorkspaceWithStatus},
+};
+use deployment::Deployment;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Work in progress across all projects
+#[derive(Debug, Serialize, TS)]
+pub struct Overview {
+    /// Projects with in-progress tasks or active workspaces, in project order
+    pub projects: Vec<ProjectOverview>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ProjectOverview {
+    pub project_id: Uuid,
+    pub project_name: String,
+    /// Tasks in progress or in review, or with an agent run in progress, newest first
+    pub tasks: Vec<TaskWithAttemptStatus>,
+    /// Workspaces that are not archived and are open or running processes, most recently
+    /// updated first
+    pub workspaces: Vec<OverviewWorkspace>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct OverviewWorkspace {
+    pub workspace: WorkspaceWithStatus,
+    pub task_title: String,
+    pub task_status: TaskStatus,
+    pub running_processes: Vec<ExecutionProcess>,
+}
+
+/// In-progress tasks and active workspaces of every project, with their running processes
+pub async fn get_overview(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Overview>>, ApiError> {
+    let overview = build_overview(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(overview)))
+}
+
+async fn build_overview(pool: &SqlitePool) -> Result<Overview, ApiError> {
+    let running = ExecutionProcess::find_filtered(
+        pool,
+        &ExecutionProcessFilter {
+            status: Some(ExecutionProcessStatus::Running),
+            ..Default::default()
+        },
+        None,
+        0,
+    )
+    .await?;
+    let mut running_by_workspace: HashMap<Uuid, Vec<ExecutionProcess>> = HashMap::new();
+    for ExecutionProcessSummary {
+        process,
+        workspace_id,
+        ..
+    } in running
+    {
+        running_by_workspace
+            .entry(workspace_id)
+            .or_default()
+            .push(process);
+    }
+
+    let mut workspaces_by_task: HashMap<Uuid, Vec<WorkspaceWithStatus>> = HashMap::new();
+    for workspace in Workspace::find_all_with_status(pool, Some(false), None).await? {
+        if workspace.container_ref.is_some() || running_by_workspace.contains_key(&workspace.id) {
+            workspaces_by_task
+                .entry(workspace.task_id)
+                .or_default()
+                .push(workspace);
+        }
+    }
+
+    let mut projects = Vec::new();
+    for project in Project::find_all(pool).await? {
+        let mut tasks = Vec::new();
+        let mut workspaces = Vec::new();
+        for task in Task::find_by_project_id_with_attempt_status(pool, project.id).await? {
+            for workspace in workspaces_by_task.remove(&task.id).unwrap_or_default() {
+                workspaces.push(OverviewWorkspace {
+                    running_processes: running_by_workspace
+                        .remove(&workspace.id)
+                        .unwrap_or_default(),
+                    workspace,
+                    task_title: task.title.clone(),
+                    task_status: task.status.clone(),
+                });
+            }
+            if task.has_in_progress_attempt
+                || matches!(task.status, TaskStatus::InProgress | TaskStatus::InReview)
+            {
+                tasks.push(task);
+            }
+        }
+        if tasks.is_empty() && workspaces.is_empty() {
+            continue;
+        }
+        workspaces.sort_by(|a, b| b.workspace.updated_at.cmp(&a.workspace.updated_at));
+        projects.push(ProjectOverview {
+            project_id: project.id,
+            project_name: project.name,
+            tasks,
+            workspaces,
+        });
+    }
+
+    Ok(Overview { projects })
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/overview", get(get_overview))
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::execution_process::ExecutionProcessRunReason;
+
+    use super::*;
+    use crate::test_support::{
+        seed_execution_process, seed_project, seed_task, seed_workspace, test_pool,
+    };
+
+    #[tokio::test]
+    async fn only_work_in_progress_is_shown() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let (idle_project, _) = seed_project(&pool, &[]).await;
+        seed_task(&pool, idle_project.id, "Someday").await;
+        seed_task(&pool, project.id, "Later").await;
+        let reviewing = seed_task(&pool, project.id, "Review login").await;
+        Task::update_status(&pool, reviewing.id, TaskStatus::InReview)
+            .await
+            .unwrap();
+        let running = seed_task(&pool, project.id, "Add signup").await;
+        let busy = seed_workspace(&pool, running.id, "signup", &[&repos[0]]).await;
+        let process =
+            seed_execution_process(&pool, busy.id, ExecutionProcessRunReason::CodingAgent).await;
+        let open = seed_workspace(&pool, reviewing.id, "login", &[&repos[0]]).await;
+        Workspace::update_container_ref(&pool, open.id, "/tmp/login")
+            .await
+            .unwrap();
+        seed_workspace(&pool, reviewing.id, "closed", &[&repos[0]]).await;
+
+        let overview = build_overview(&pool).await.unwrap();
+
+        assert_eq!(overview.projects.len(), 1);
+        let listed = &overview.projects[0];
+        assert_eq!(listed.project_id, project.id);
+        let mut tasks: Vec<_> = listed.tasks.iter().map(|t| t.title.as_str()).collect();
+        tasks.sort();
+        assert_eq!(tasks, vec!["Add signup", "Review login"]);
+        let mut workspaces: Vec<_> = listed
+            .workspaces
+            .iter()
+            .map(|w| {
+                let running: Vec<_> = w.running_processes.iter().map(|p| p.id).collect();
+                (w.workspace.id, w.task_title.as_str(), running)
+            })
+            .collect();
+        workspaces.sort_by_key(|(_, title, _)| *title);
+        assert_eq!(
+            workspaces,
+            vec![
+                (busy.id, "Add signup", vec![process.id]),
+                (open.id, "Review login", vec![]),
+            ]
+        );
+    }
+}
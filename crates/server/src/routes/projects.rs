@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{convert::Infallible, path::PathBuf};
 
 use anyhow;
 use axum::{
@@ -7,23 +7,61 @@ use axum::{
         Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    response::{
+        IntoResponse, Json as ResponseJson, Response, Sse,
+        sse::{Event, KeepAlive},
+    },
+    routing::{get, post, put},
 };
 use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessSummary},
     project::{CreateProject, Project, ProjectError, SearchResult, UpdateProject},
-    project_repo::{CreateProjectRepo, ProjectRepo},
+    project_agent_pipeline::{ProjectAgentPipeline, UpsertProjectAgentPipeline},
+    project_agent_policy::{ProjectAgentPolicy, UpsertProjectAgentPolicy},
+    project_ai_review_policy::{ProjectAiReviewPolicy, UpsertProjectAiReviewPolicy},
+    project_env_set::{CreateProjectEnvSet, ProjectEnvSet, UpdateProjectEnvSet},
+    project_quality_gate::{
+        CreateProjectQualityGate, ProjectQualityGate, UpdateProjectQualityGate,
+    },
+    project_remote_host::{ProjectRemoteHost, SetProjectRemoteHost},
+    project_repo::{CreateProjectRepo, ProjectRepo, UpdateProjectRepo},
+    project_script::{CreateProjectScript, ProjectScript, UpdateProjectScript},
+    project_settings::{ProjectSettings, UpsertProjectSettings},
     repo::Repo,
 };
 use deployment::Deployment;
+use executors::profile::ExecutorConfigs;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
-use services::services::{file_search::SearchQuery, project::ProjectServiceError};
-use utils::response::ApiResponse;
+use serde::Deserialize;
+use services::services::{
+    agent_pipeline::AgentPipelineService,
+    cost_report::{CostPeriod, CostReportService, PeriodCost, period_costs_csv},
+    env_vars::EnvVarService,
+    events::ProjectEvent,
+    file_search::SearchQuery,
+    project::ProjectServiceError,
+    project_stats::{AgentStats, ProjectStats, ProjectStatsService},
+    secrets::{SecretAllowlistNames, SecretService},
+    task_analytics::{
+        DEFAULT_THROUGHPUT_WEEKS, DurationSummary, TaskAnalyticsService, WeeklyThroughput,
+    },
+};
+use tokio::sync::broadcast::error::RecvError;
+use utils::{response::ApiResponse, text::render_branch_template};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_project_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_project_middleware,
+    pagination::{Listing, PageQuery},
+    routes::{
+        costs::{CostQuery, csv_response},
+        execution_processes::{ExecutionProcessListQuery, list_execution_process_summaries},
+    },
+};
 
 pub async fn get_projects(
     State(deployment): State<DeploymentImpl>,
@@ -433,6 +471,732 @@ pub async fn get_project_repository(
     }
 }
 
+/// Set the branch the project's new workspaces target in a repo when none is given and the
+/// directory of the repo the project is limited to. Returns 404 if the repo is not part of
+/// the project and 400 if the branch does not exist in it or the path scope is not a
+/// directory inside the repo.
+pub async fn update_project_repository(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, repo_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateProjectRepo>,
+) -> Result<ResponseJson<ApiResponse<ProjectRepo>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let default_target_branch = payload
+        .default_target_branch
+        .as_deref()
+        .map(str::trim)
+        .filter(|branch| !branch.is_empty());
+
+    if let Some(branch) = default_target_branch {
+        let repo = Repo::find_by_id(pool, repo_id)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Repository {repo_id} not found")))?;
+        if !deployment.git().check_branch_exists(&repo.path, branch)? {
+            return Err(ApiError::BadRequest(format!(
+                "Branch '{}' does not exist in repository '{}'",
+                branch, repo.name
+            )));
+        }
+    }
+
+    let path_scope = match payload.path_scope.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(scope) => Some(git::normalize_path_scope(scope).ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Path scope '{scope}' must be a directory inside the repository, such as \
+                 'services/billing/**'"
+            ))
+        })?),
+    };
+    if payload.sparse_checkout && path_scope.is_none() {
+        return Err(ApiError::BadRequest(
+            "Sparse checkout requires a path scope".to_string(),
+        ));
+    }
+
+    let update = UpdateProjectRepo {
+        default_target_branch: default_target_branch.map(str::to_string),
+        path_scope,
+        sparse_checkout: payload.sparse_checkout,
+    };
+    let project_repo = ProjectRepo::update(pool, project_id, repo_id, &update)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Repository {repo_id} not found in project {project_id}"
+            ))
+        })?;
+    Ok(ResponseJson(ApiResponse::success(project_repo)))
+}
+
+pub async fn get_project_quality_gates(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectQualityGate>>>, ApiError> {
+    let gates = ProjectQualityGate::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(gates)))
+}
+
+pub async fn create_project_quality_gate(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateProjectQualityGate>,
+) -> Result<ResponseJson<ApiResponse<ProjectQualityGate>>, ApiError> {
+    if payload.command.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Quality gate command cannot be empty".to_string(),
+        ));
+    }
+    if payload.timeout_secs == Some(0) {
+        return Err(ApiError::BadRequest(
+            "Quality gate timeout must be at least one second".to_string(),
+        ));
+    }
+
+    let gate = ProjectQualityGate::create(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_quality_gate_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "quality_gate_id": gate.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(gate)))
+}
+
+pub async fn update_project_quality_gate(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, gate_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateProjectQualityGate>,
+) -> Result<ResponseJson<ApiResponse<ProjectQualityGate>>, ApiError> {
+    let pool = &deployment.db().pool;
+    match ProjectQualityGate::find_by_id(pool, gate_id).await? {
+        Some(gate) if gate.project_id == project_id => {}
+        _ => {
+            return Err(ApiError::NotFound(format!(
+                "Quality gate {} not found in project",
+                gate_id
+            )));
+        }
+    }
+    if payload
+        .command
+        .as_deref()
+        .is_some_and(|command| command.trim().is_empty())
+    {
+        return Err(ApiError::BadRequest(
+            "Quality gate command cannot be empty".to_string(),
+        ));
+    }
+    if payload.timeout_secs == Some(0) {
+        return Err(ApiError::BadRequest(
+            "Quality gate timeout must be at least one second".to_string(),
+        ));
+    }
+
+    let gate = ProjectQualityGate::update(pool, gate_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(gate)))
+}
+
+pub async fn delete_project_quality_gate(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, gate_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    match ProjectQualityGate::find_by_id(pool, gate_id).await? {
+        Some(gate) if gate.project_id == project_id => {}
+        _ => {
+            return Err(ApiError::NotFound(format!(
+                "Quality gate {} not found in project",
+                gate_id
+            )));
+        }
+    }
+
+    ProjectQualityGate::delete(pool, gate_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+async fn validate_project_script(
+    pool: &sqlx::SqlitePool,
+    project_id: Uuid,
+    name: Option<&str>,
+    command: Option<&str>,
+    repo_id: Option<Uuid>,
+) -> Result<(), ApiError> {
+    if name.is_some_and(|name| name.trim().is_empty()) {
+        return Err(ApiError::BadRequest(
+            "Script name cannot be empty".to_string(),
+        ));
+    }
+    if command.is_some_and(|command| command.trim().is_empty()) {
+        return Err(ApiError::BadRequest(
+            "Script command cannot be empty".to_string(),
+        ));
+    }
+    if let Some(repo_id) = repo_id
+        && ProjectRepo::find_by_project_and_repo(pool, project_id, repo_id)
+            .await?
+            .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Repository {} is not part of this project",
+            repo_id
+        )));
+    }
+    Ok(())
+}
+
+async fn find_project_script(
+    pool: &sqlx::SqlitePool,
+    project_id: Uuid,
+    script_id: Uuid,
+) -> Result<ProjectScript, ApiError> {
+    match ProjectScript::find_by_id(pool, script_id).await? {
+        Some(script) if script.project_id == project_id => Ok(script),
+        _ => Err(ApiError::NotFound(format!(
+            "Script {} not found in project",
+            script_id
+        ))),
+    }
+}
+
+pub async fn get_project_scripts(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectScript>>>, ApiError> {
+    let scripts = ProjectScript::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(scripts)))
+}
+
+pub async fn create_project_script(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateProjectScript>,
+) -> Result<ResponseJson<ApiResponse<ProjectScript>>, ApiError> {
+    let pool = &deployment.db().pool;
+    validate_project_script(
+        pool,
+        project.id,
+        Some(&payload.name),
+        Some(&payload.command),
+        payload.repo_id,
+    )
+    .await?;
+
+    let script = ProjectScript::create(pool, project.id, &payload)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                ApiError::Conflict(format!("A script named '{}' already exists", payload.name))
+            }
+            e => e.into(),
+        })?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_script_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "script_id": script.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(script)))
+}
+
+pub async fn update_project_script(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, script_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateProjectScript>,
+) -> Result<ResponseJson<ApiResponse<ProjectScript>>, ApiError> {
+    let pool = &deployment.db().pool;
+    find_project_script(pool, project_id, script_id).await?;
+    validate_project_script(
+        pool,
+        project_id,
+        payload.name.as_deref(),
+        payload.command.as_deref(),
+        payload.repo_id.flatten(),
+    )
+    .await?;
+
+    let script = ProjectScript::update(pool, script_id, &payload)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                ApiError::Conflict("A script with this name already exists".to_string())
+            }
+            e => e.into(),
+        })?;
+    Ok(ResponseJson(ApiResponse::success(script)))
+}
+
+pub async fn delete_project_script(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, script_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    find_project_script(pool, project_id, script_id).await?;
+    ProjectScript::delete(pool, script_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectScriptRunsQuery {
+    pub workspace_id: Option<Uuid>,
+}
+
+/// Execution processes that ran a project script, most recent first
+pub async fn get_project_script_runs(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, script_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<ProjectScriptRunsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcess>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    find_project_script(pool, project_id, script_id).await?;
+    let runs = ProjectScript::find_runs(pool, script_id, query.workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(runs)))
+}
+
+/// Server-sent task, workspace and execution process events for one project. A client
+/// reconnecting with `Last-Event-ID` gets the events it missed replayed from a short window;
+/// a `resync` event means some were lost and the board should be refetched.
+pub async fn stream_project_events(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+) -> Sse<impl futures_util::Stream<Item = Result<Event, Infallible>>> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    let project_id = project.id;
+    let (replay, receiver) = deployment
+        .events()
+        .project_events()
+        .subscribe(project_id, last_event_id);
+
+    let replayed = (!replay.complete)
+        .then(resync_event)
+        .into_iter()
+        .chain(replay.events.iter().map(project_sse_event))
+        .collect::<Vec<_>>();
+    let live = futures_util::stream::unfold(receiver, move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if event.project_id == project_id => {
+                    return Some((project_sse_event(&event), receiver));
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => return Some((resync_event(), receiver)),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let stream = futures_util::stream::iter(replayed).chain(live).map(Ok);
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn project_sse_event(event: &ProjectEvent) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event(event.event_type())
+        .data(serde_json::to_string(event).unwrap_or_default())
+}
+
+fn resync_event() -> Event {
+    Event::default().event("resync").data("resync")
+}
+
+pub async fn get_project_env_sets(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectEnvSet>>>, ApiError> {
+    let sets = ProjectEnvSet::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(sets)))
+}
+
+pub async fn create_project_env_set(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateProjectEnvSet>,
+) -> Result<ResponseJson<ApiResponse<ProjectEnvSet>>, ApiError> {
+    let set = EnvVarService::create_set(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(set)))
+}
+
+pub async fn update_project_env_set(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, set_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateProjectEnvSet>,
+) -> Result<ResponseJson<ApiResponse<ProjectEnvSet>>, ApiError> {
+    let set =
+        EnvVarService::update_set(&deployment.db().pool, project_id, set_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(set)))
+}
+
+pub async fn delete_project_env_set(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, set_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    EnvVarService::delete_set(&deployment.db().pool, project_id, set_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_secret_allowlist(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<SecretAllowlistNames>>, ApiError> {
+    let allowlist = SecretService::project_allowlist(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(allowlist)))
+}
+
+/// Replace the names of the secrets passed to the project's execution processes
+pub async fn set_project_secret_allowlist(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SecretAllowlistNames>,
+) -> Result<ResponseJson<ApiResponse<SecretAllowlistNames>>, ApiError> {
+    let allowlist =
+        SecretService::set_project_allowlist(&deployment.db().pool, project.id, &payload.names)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(allowlist)))
+}
+
+/// Host the project's execution processes run on, if any
+pub async fn get_project_remote_host(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectRemoteHost>>>, ApiError> {
+    let host = ProjectRemoteHost::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(host)))
+}
+
+/// Run the project's execution processes on a host over SSH
+pub async fn set_project_remote_host(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetProjectRemoteHost>,
+) -> Result<ResponseJson<ApiResponse<ProjectRemoteHost>>, ApiError> {
+    let host = payload.host.trim();
+    if host.is_empty() || host.starts_with('-') || host.contains(char::is_whitespace) {
+        return Err(ApiError::BadRequest(
+            "Remote host must be a host name or SSH alias".to_string(),
+        ));
+    }
+    if !payload.remote_dir.starts_with('/') {
+        return Err(ApiError::BadRequest(
+            "Remote directory must be an absolute path".to_string(),
+        ));
+    }
+    if payload
+        .port
+        .is_some_and(|port| !(1..=65535).contains(&port))
+    {
+        return Err(ApiError::BadRequest(
+            "Remote port must be between 1 and 65535".to_string(),
+        ));
+    }
+
+    let payload = SetProjectRemoteHost {
+        host: host.to_string(),
+        ..payload
+    };
+    let host = ProjectRemoteHost::set(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(host)))
+}
+
+/// Run the project's execution processes on this machine again
+pub async fn delete_project_remote_host(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectRemoteHost::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_ai_review_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectAiReviewPolicy>>>, ApiError> {
+    let policy =
+        ProjectAiReviewPolicy::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+/// Have a coding agent review each workspace's diff before the workspace is merged
+pub async fn set_project_ai_review_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectAiReviewPolicy>,
+) -> Result<ResponseJson<ApiResponse<ProjectAiReviewPolicy>>, ApiError> {
+    if ExecutorConfigs::get_cached()
+        .get_coding_agent(&payload.executor_profile_id)
+        .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown executor profile '{}'",
+            payload.executor_profile_id
+        )));
+    }
+
+    let policy = ProjectAiReviewPolicy::upsert(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+/// Merge workspaces without an agent review again
+pub async fn delete_project_ai_review_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectAiReviewPolicy::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_agent_pipeline(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectAgentPipeline>>>, ApiError> {
+    let pipeline =
+        ProjectAgentPipeline::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(pipeline)))
+}
+
+/// Run the project's workspaces through a sequence of agent stages, each with its own agent
+/// and prompt template
+pub async fn set_project_agent_pipeline(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectAgentPipeline>,
+) -> Result<ResponseJson<ApiResponse<ProjectAgentPipeline>>, ApiError> {
+    AgentPipelineService::validate(&payload.stages).map_err(ApiError::BadRequest)?;
+    let executor_configs = ExecutorConfigs::get_cached();
+    if let Some(stage) = payload.stages.iter().find(|stage| {
+        executor_configs
+            .get_coding_agent(&stage.executor_profile_id)
+            .is_none()
+    }) {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown executor profile '{}' in stage '{}'",
+            stage.executor_profile_id, stage.name
+        )));
+    }
+
+    let pipeline =
+        ProjectAgentPipeline::upsert(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(pipeline)))
+}
+
+/// Run workspaces as a single agent run again
+pub async fn delete_project_agent_pipeline(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectAgentPipeline::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_agent_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectAgentPolicy>>>, ApiError> {
+    let policy = ProjectAgentPolicy::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+/// Limit what the project's coding agents may do. Agents whose tool uses cannot be checked
+/// refuse to start while the policy is set.
+pub async fn set_project_agent_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectAgentPolicy>,
+) -> Result<ResponseJson<ApiResponse<ProjectAgentPolicy>>, ApiError> {
+    if let Some(command) = payload
+        .allowed_commands
+        .iter()
+        .flatten()
+        .find(|command| command.is_empty() || command.contains(char::is_whitespace))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Allowed command '{command}' must be a single program name"
+        )));
+    }
+
+    let policy = ProjectAgentPolicy::upsert(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+/// Let the project's coding agents run without limits again
+pub async fn delete_project_agent_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectAgentPolicy::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// The project's overrides of server-wide behavior; `null` when it uses the server's settings
+/// throughout
+pub async fn get_project_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectSettings>>>, ApiError> {
+    let settings = ProjectSettings::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+/// Replace the project's settings. Returns 400 for an unknown executor profile, a branch
+/// template without `{short_id}` or one that does not make a valid branch name, or a
+/// retention period of zero hours.
+pub async fn set_project_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectSettings>,
+) -> Result<ResponseJson<ApiResponse<ProjectSettings>>, ApiError> {
+    if let Some(profile) = &payload.default_executor_profile_id
+        && ExecutorConfigs::get_cached()
+            .get_coding_agent(profile)
+            .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown executor profile '{profile}'"
+        )));
+    }
+    if let Some(template) = &payload.branch_template {
+        // Without the workspace's short id, workspaces of tasks with the same title collide
+        if !template.contains("{short_id}") {
+            return Err(ApiError::BadRequest(
+                "Branch template must contain {short_id}".to_string(),
+            ));
+        }
+        let example = render_branch_template(template, "vk", &Uuid::nil(), "example task");
+        if !git::is_valid_branch_name(&example) {
+            return Err(ApiError::BadRequest(format!(
+                "Branch template makes an invalid branch name '{example}'"
+            )));
+        }
+    }
+    if payload.workspace_retention_hours == Some(0)
+        || payload.archived_workspace_retention_hours == Some(0)
+    {
+        return Err(ApiError::BadRequest(
+            "Retention periods must be at least one hour".to_string(),
+        ));
+    }
+
+    let settings = ProjectSettings::upsert(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+/// Execution processes across all of the project's workspaces, newest first, filtered by
+/// status, run reason and start time
+pub async fn get_project_processes(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExecutionProcessListQuery>,
+    Query(page): Query<PageQuery>,
+) -> Result<ResponseJson<ApiResponse<Listing<ExecutionProcessSummary>>>, ApiError> {
+    let filter = query.filter(None, Some(project.id))?;
+    let processes = list_execution_process_summaries(&deployment, &filter, page).await?;
+    Ok(ResponseJson(ApiResponse::success(processes)))
+}
+
+/// Counts and aggregates across all of the project's workspaces
+pub async fn get_project_stats(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProjectStats>>, ApiError> {
+    let stats =
+        ProjectStatsService::compute(&deployment.db().pool, deployment.git(), project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(stats)))
+}
+
+/// Workspace outcomes per agent configuration, most used first
+pub async fn get_project_agent_stats(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<AgentStats>>>, ApiError> {
+    let agents = ProjectStatsService::by_agent(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(agents)))
+}
+
+/// Hours from a task first moving to in progress until it is done
+pub async fn get_project_cycle_time(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DurationSummary>>, ApiError> {
+    let summary = TaskAnalyticsService::cycle_time(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+/// Hours from a task's creation until it is done
+pub async fn get_project_lead_time(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DurationSummary>>, ApiError> {
+    let summary = TaskAnalyticsService::lead_time(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThroughputQuery {
+    pub weeks: Option<u32>,
+}
+
+/// Tasks completed per week, oldest week first
+pub async fn get_project_throughput(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ThroughputQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<WeeklyThroughput>>>, ApiError> {
+    let weeks = query.weeks.unwrap_or(DEFAULT_THROUGHPUT_WEEKS);
+    let throughput =
+        TaskAnalyticsService::throughput(&deployment.db().pool, project.id, weeks).await?;
+    Ok(ResponseJson(ApiResponse::success(throughput)))
+}
+
+async fn project_period_costs(
+    deployment: &DeploymentImpl,
+    project: &Project,
+    query: &CostQuery,
+) -> Result<Vec<PeriodCost>, ApiError> {
+    let (from, to) = query.window()?;
+    let period = query.period.unwrap_or(CostPeriod::Day);
+    Ok(
+        CostReportService::by_period(&deployment.db().pool, Some(project.id), from, to, period)
+            .await?,
+    )
+}
+
+/// Reported agent spend per day, week or month over the window, oldest first
+pub async fn get_project_costs(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<CostQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<PeriodCost>>>, ApiError> {
+    let costs = project_period_costs(&deployment, &project, &query).await?;
+    Ok(ResponseJson(ApiResponse::success(costs)))
+}
+
+pub async fn export_project_costs(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<CostQuery>,
+) -> Result<Response, ApiError> {
+    let costs = project_period_costs(&deployment, &project, &query).await?;
+    Ok(csv_response(
+        &format!("project-{}-costs.csv", project.id),
+        period_costs_csv(&costs),
+    ))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -440,11 +1204,64 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             get(get_project).put(update_project).delete(delete_project),
         )
         .route("/search", get(search_project_files))
+        .route("/events", get(stream_project_events))
+        .route("/processes", get(get_project_processes))
+        .route("/stats", get(get_project_stats))
+        .route("/stats/agents", get(get_project_agent_stats))
+        .route("/analytics/cycle-time", get(get_project_cycle_time))
+        .route("/analytics/lead-time", get(get_project_lead_time))
+        .route("/analytics/throughput", get(get_project_throughput))
+        .route("/costs", get(get_project_costs))
+        .route("/costs/export", get(export_project_costs))
         .route("/open-editor", post(open_project_in_editor))
         .route(
             "/repositories",
             get(get_project_repositories).post(add_project_repository),
         )
+        .route(
+            "/quality-gates",
+            get(get_project_quality_gates).post(create_project_quality_gate),
+        )
+        .route(
+            "/scripts",
+            get(get_project_scripts).post(create_project_script),
+        )
+        .route(
+            "/env-sets",
+            get(get_project_env_sets).post(create_project_env_set),
+        )
+        .route(
+            "/secrets",
+            get(get_project_secret_allowlist).put(set_project_secret_allowlist),
+        )
+        .route(
+            "/remote-host",
+            get(get_project_remote_host)
+                .put(set_project_remote_host)
+                .delete(delete_project_remote_host),
+        )
+        .route(
+            "/ai-review-policy",
+            get(get_project_ai_review_policy)
+                .put(set_project_ai_review_policy)
+                .delete(delete_project_ai_review_policy),
+        )
+        .route(
+            "/agent-pipeline",
+            get(get_project_agent_pipeline)
+                .put(set_project_agent_pipeline)
+                .delete(delete_project_agent_pipeline),
+        )
+        .route(
+            "/agent-policy",
+            get(get_project_agent_policy)
+                .put(set_project_agent_policy)
+                .delete(delete_project_agent_policy),
+        )
+        .route(
+            "/settings",
+            get(get_project_settings).put(set_project_settings),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
@@ -454,10 +1271,158 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/", get(get_projects).post(create_project))
         .route(
             "/{project_id}/repositories/{repo_id}",
-            get(get_project_repository).delete(delete_project_repository),
+            get(get_project_repository)
+                .put(update_project_repository)
+                .delete(delete_project_repository),
+        )
+        .route(
+            "/{project_id}/quality-gates/{gate_id}",
+            put(update_project_quality_gate).delete(delete_project_quality_gate),
+        )
+        .route(
+            "/{project_id}/scripts/{script_id}",
+            put(update_project_script).delete(delete_project_script),
+        )
+        .route(
+            "/{project_id}/scripts/{script_id}/runs",
+            get(get_project_script_runs),
+        )
+        .route(
+            "/{project_id}/env-sets/{set_id}",
+            put(update_project_env_set).delete(delete_project_env_set),
         )
         .route("/stream/ws", get(stream_projects_ws))
         .nest("/{id}", project_id_router);
 
     Router::new().nest("/projects", projects_router)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use db::models::execution_process::ExecutionProcessRunReason;
+
+    use super::*;
+    use crate::test_support::{
+        seed_execution_process, seed_project, seed_task, seed_workspace, test_pool,
+    };
+
+    #[tokio::test]
+    async fn scripts_are_validated_against_and_scoped_to_their_project() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let (other, strangers) = seed_project(&pool, &["web"]).await;
+        let validate = |name: &'static str, command: &'static str, repo_id: Option<Uuid>| {
+            let pool = pool.clone();
+            async move {
+                validate_project_script(&pool, project.id, Some(name), Some(command), repo_id).await
+            }
+        };
+
+        assert!(
+            validate("lint", "make lint", Some(repos[0].id))
+                .await
+                .is_ok()
+        );
+        for (name, command, repo_id) in [
+            (" ", "make lint", None),
+            ("lint", "", None),
+            ("lint", "make lint", Some(strangers[0].id)),
+        ] {
+            assert!(
+                matches!(
+                    validate(name, command, repo_id).await,
+                    Err(ApiError::BadRequest(_))
+                ),
+                "{name:?} {command:?}"
+            );
+        }
+        // Updates leave unset fields alone
+        assert!(
+            validate_project_script(&pool, project.id, None, None, None)
+                .await
+                .is_ok()
+        );
+
+        let create = CreateProjectScript {
+            name: "lint".to_string(),
+            command: "make lint".to_string(),
+            repo_id: None,
+            env: HashMap::new(),
+            artifacts: Vec::new(),
+        };
+        let script = ProjectScript::create(&pool, project.id, &create)
+            .await
+            .unwrap();
+        assert_eq!(
+            find_project_script(&pool, project.id, script.id)
+                .await
+                .unwrap()
+                .id,
+            script.id
+        );
+        assert!(matches!(
+            find_project_script(&pool, other.id, script.id).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn script_runs_are_listed_most_recent_first_per_workspace() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[&repos[0]]).await;
+        let other = seed_workspace(&pool, task.id, "vk/logout", &[&repos[0]]).await;
+        let create = CreateProjectScript {
+            name: "lint".to_string(),
+            command: "make lint".to_string(),
+            repo_id: None,
+            env: HashMap::new(),
+            artifacts: Vec::new(),
+        };
+        let script = ProjectScript::create(&pool, project.id, &create)
+            .await
+            .unwrap();
+
+        let mut runs = Vec::new();
+        for workspace_id in [workspace.id, other.id, workspace.id] {
+            let process = seed_execution_process(
+                &pool,
+                workspace_id,
+                ExecutionProcessRunReason::ProjectScript,
+            )
+            .await;
+            ProjectScript::record_run(&pool, script.id, workspace_id, process.id)
+                .await
+                .unwrap();
+            runs.push(process.id);
+            // Runs are ordered by their millisecond timestamps
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        // Processes that did not run the script are not part of its history
+        seed_execution_process(
+            &pool,
+            workspace.id,
+            ExecutionProcessRunReason::ProjectScript,
+        )
+        .await;
+
+        let ids = |runs: Vec<ExecutionProcess>| runs.into_iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(ProjectScript::find_runs(&pool, script.id, None)
+                .await
+                .unwrap()),
+            vec![runs[2], runs[1], runs[0]]
+        );
+        assert_eq!(
+            ids(
+                ProjectScript::find_runs(&pool, script.id, Some(workspace.id))
+                    .await
+                    .unwrap()
+            ),
+            vec![runs[2], runs[0]]
+        );
+    }
+}
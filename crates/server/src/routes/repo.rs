@@ -3,28 +3,35 @@ use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
     response::Json as ResponseJson,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use db::models::{
     project::SearchResult,
     repo::{Repo, UpdateRepo},
 };
 use deployment::Deployment;
-use git::{GitBranch, GitRemote};
+use git::{DiffTarget, GitBranch, GitRemote};
 use serde::{Deserialize, Serialize};
 use services::services::{
     file_search::SearchQuery,
+    filesystem::FilesystemError,
+    git_credentials::{GitCredentialService, RepoCredentialSummary, SetRepoCredential},
     git_host::{GitHostError, GitHostProvider, GitHostService, OpenPrInfo, ProviderKind},
     ralph::RalphService,
+    repo::{DiscoveredRepo, RepoValidation},
+    secrets::{SecretAllowlistNames, SecretService},
 };
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{diff::DiffFormatOptions, response::ApiResponse};
 use uuid::Uuid;
 
 use crate::{
     DeploymentImpl,
     error::ApiError,
-    routes::projects::{OpenEditorRequest, OpenEditorResponse},
+    routes::{
+        projects::{OpenEditorRequest, OpenEditorResponse},
+        workspaces::{FileDiff, diffs_to_file_diffs},
+    },
 };
 
 #[derive(Debug, Deserialize, TS)]
@@ -41,6 +48,33 @@ pub struct InitRepoRequest {
     pub folder_name: String,
 }
 
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct DiscoverReposRequest {
+    /// Directory to scan for git repositories
+    pub path: String,
+    /// How many directory levels below `path` to search; defaults to 3
+    pub max_depth: Option<usize>,
+    /// Paths of discovered repos to register
+    #[serde(default)]
+    pub register: Vec<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct DiscoverReposResponse {
+    pub repos: Vec<DiscoveredRepo>,
+    /// Repos registered from `register`, in request order
+    pub registered: Vec<Repo>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct AddRepoRemoteRequest {
+    pub name: String,
+    pub url: String,
+}
+
 #[derive(Debug, Deserialize, TS)]
 #[ts(export)]
 pub struct BatchRepoRequest {
@@ -80,6 +114,68 @@ pub async fn init_repo(
     Ok(ResponseJson(ApiResponse::success(repo)))
 }
 
+/// Scan a directory for git repositories and register the selected ones in bulk.
+/// Only paths found by the scan can be registered.
+pub async fn discover_repos(
+    State(deployment): State<DeploymentImpl>,
+    ResponseJson(payload): ResponseJson<DiscoverReposRequest>,
+) -> Result<ResponseJson<ApiResponse<DiscoverReposResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let base_path = deployment.repo().normalize_path(&payload.path)?;
+    let entries = deployment
+        .filesystem()
+        .list_git_repos(
+            Some(base_path.to_string_lossy().to_string()),
+            5000,
+            8000,
+            Some(payload.max_depth.unwrap_or(3)),
+        )
+        .await
+        .map_err(|e| match e {
+            FilesystemError::DirectoryDoesNotExist | FilesystemError::PathIsNotDirectory => {
+                ApiError::BadRequest(format!("{}: {}", e, payload.path))
+            }
+            FilesystemError::Io(e) => ApiError::Io(e),
+        })?;
+
+    let repos = deployment
+        .repo()
+        .describe_discovered(
+            pool,
+            deployment.git(),
+            entries.into_iter().map(|e| e.path).collect(),
+        )
+        .await?;
+
+    let mut registered = Vec::with_capacity(payload.register.len());
+    for path in &payload.register {
+        let normalized = deployment.repo().normalize_path(path)?;
+        if !repos.iter().any(|r| r.path == normalized) {
+            return Err(ApiError::BadRequest(format!(
+                "{} was not found under {}",
+                path, payload.path
+            )));
+        }
+        registered.push(deployment.repo().register(pool, path, None).await?);
+    }
+
+    // Reflect the new registrations in the listing
+    let repos = repos
+        .into_iter()
+        .map(|mut repo| {
+            if let Some(r) = registered.iter().find(|r| r.path == repo.path) {
+                repo.registered_repo_id = Some(r.id);
+            }
+            repo
+        })
+        .collect();
+
+    Ok(ResponseJson(ApiResponse::success(DiscoverReposResponse {
+        repos,
+        registered,
+    })))
+}
+
 pub async fn get_repo_branches(
     State(deployment): State<DeploymentImpl>,
     Path(repo_id): Path<Uuid>,
@@ -93,6 +189,67 @@ pub async fn get_repo_branches(
     Ok(ResponseJson(ApiResponse::success(branches)))
 }
 
+/// Query parameters for diffing two revisions of a repo
+#[derive(Debug, Deserialize)]
+pub struct RepoDiffQuery {
+    /// Revision the diff starts from: a branch, tag, commit SHA or expression like `HEAD~3`
+    pub from: String,
+    /// Revision the diff ends at
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct RepoDiffResponse {
+    pub repo_id: Uuid,
+    /// Commit `from` resolved to
+    pub from_commit: String,
+    /// Commit `to` resolved to
+    pub to_commit: String,
+    pub files: Vec<FileDiff>,
+}
+
+/// Changes between the trees of two revisions, such as what landed on a branch between
+/// two release tags.
+/// Returns 404 if the repo or either revision is not found.
+pub async fn get_repo_diff(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    Query(query): Query<RepoDiffQuery>,
+    Query(format): Query<DiffFormatOptions>,
+) -> Result<ResponseJson<ApiResponse<RepoDiffResponse>>, ApiError> {
+    format.validate().map_err(ApiError::BadRequest)?;
+    let repo = deployment
+        .repo()
+        .get_by_id(&deployment.db().pool, repo_id)
+        .await?;
+
+    let git = deployment.git().clone();
+    let (from_commit, to_commit, diffs) =
+        tokio::task::spawn_blocking(move || -> Result<_, git::GitServiceError> {
+            let from_commit = git.resolve_revision(&repo.path, &query.from)?;
+            let to_commit = git.resolve_revision(&repo.path, &query.to)?;
+            let diffs = git.get_diffs_with_options(
+                DiffTarget::Range {
+                    repo_path: &repo.path,
+                    from: &query.from,
+                    to: &query.to,
+                },
+                None,
+                &format,
+            )?;
+            Ok((from_commit, to_commit, diffs))
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+    Ok(ResponseJson(ApiResponse::success(RepoDiffResponse {
+        repo_id,
+        from_commit: from_commit.to_string(),
+        to_commit: to_commit.to_string(),
+        files: diffs_to_file_diffs(diffs, &format),
+    })))
+}
+
 pub async fn get_repo_remotes(
     State(deployment): State<DeploymentImpl>,
     Path(repo_id): Path<Uuid>,
@@ -106,6 +263,146 @@ pub async fn get_repo_remotes(
     Ok(ResponseJson(ApiResponse::success(remotes)))
 }
 
+/// Add a named remote (e.g. a fork) to the repo's git config
+pub async fn add_repo_remote(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<AddRepoRemoteRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<GitRemote>>>, ApiError> {
+    let repo = deployment
+        .repo()
+        .get_by_id(&deployment.db().pool, repo_id)
+        .await?;
+
+    let name = payload.name.trim();
+    let url = payload.url.trim();
+    if !git2::Remote::is_valid_name(name) {
+        return Err(ApiError::BadRequest(format!(
+            "'{name}' is not a valid remote name"
+        )));
+    }
+    if url.is_empty() {
+        return Err(ApiError::BadRequest("Remote URL is required".to_string()));
+    }
+    if deployment.git().get_remote(&repo.path, name).is_ok() {
+        return Err(ApiError::Conflict(format!(
+            "Remote '{name}' already exists"
+        )));
+    }
+
+    deployment.git().add_remote(&repo.path, name, url)?;
+    let remotes = deployment.git().list_remotes(&repo.path)?;
+    Ok(ResponseJson(ApiResponse::success(remotes)))
+}
+
+/// Remove a remote from the repo's git config, clearing it as the push remote if selected
+pub async fn remove_repo_remote(
+    State(deployment): State<DeploymentImpl>,
+    Path((repo_id, name)): Path<(Uuid, String)>,
+) -> Result<ResponseJson<ApiResponse<Vec<GitRemote>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+
+    if deployment.git().get_remote(&repo.path, &name).is_err() {
+        return Err(ApiError::NotFound(format!("Remote '{name}' not found")));
+    }
+    deployment.git().remove_remote(&repo.path, &name)?;
+
+    if repo.push_remote.as_deref() == Some(name.as_str()) {
+        Repo::update(
+            pool,
+            repo_id,
+            &UpdateRepo {
+                push_remote: Some(None),
+                ..Default::default()
+            },
+        )
+        .await?;
+    }
+
+    let remotes = deployment.git().list_remotes(&repo.path)?;
+    Ok(ResponseJson(ApiResponse::success(remotes)))
+}
+
+pub async fn get_repo_credential(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Option<RepoCredentialSummary>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+
+    let credential = GitCredentialService::new()?.get(pool, repo.id).await?;
+    Ok(ResponseJson(ApiResponse::success(credential)))
+}
+
+/// Store the HTTPS token or SSH key used for the repo's fetches and pushes
+pub async fn set_repo_credential(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<SetRepoCredential>,
+) -> Result<ResponseJson<ApiResponse<RepoCredentialSummary>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+
+    let credential = GitCredentialService::new()?
+        .set(pool, &repo, &payload)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(credential)))
+}
+
+pub async fn delete_repo_credential(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+
+    if !GitCredentialService::new()?.clear(pool, &repo).await? {
+        return Err(ApiError::NotFound(
+            "Repository has no stored credential".to_string(),
+        ));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_repo_secret_allowlist(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<SecretAllowlistNames>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+
+    let allowlist = SecretService::repo_allowlist(pool, repo.id).await?;
+    Ok(ResponseJson(ApiResponse::success(allowlist)))
+}
+
+/// Replace the names of the secrets passed to processes in workspaces of the repo
+pub async fn set_repo_secret_allowlist(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<SecretAllowlistNames>,
+) -> Result<ResponseJson<ApiResponse<SecretAllowlistNames>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+
+    let allowlist = SecretService::set_repo_allowlist(pool, repo.id, &payload.names).await?;
+    Ok(ResponseJson(ApiResponse::success(allowlist)))
+}
+
+/// Check that the repo can host workspaces, returning each problem found as a structured issue
+pub async fn validate_repo(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<RepoValidation>>, ApiError> {
+    let repo = deployment
+        .repo()
+        .get_by_id(&deployment.db().pool, repo_id)
+        .await?;
+
+    let validation = deployment.repo().validate(deployment.git(), &repo).await;
+    Ok(ResponseJson(ApiResponse::success(validation)))
+}
+
 pub async fn get_repos_batch(
     State(deployment): State<DeploymentImpl>,
     ResponseJson(payload): ResponseJson<BatchRepoRequest>,
@@ -137,7 +434,18 @@ pub async fn update_repo(
     Path(repo_id): Path<Uuid>,
     ResponseJson(payload): ResponseJson<UpdateRepo>,
 ) -> Result<ResponseJson<ApiResponse<Repo>>, ApiError> {
-    let repo = Repo::update(&deployment.db().pool, repo_id, &payload).await?;
+    let pool = &deployment.db().pool;
+    if let Some(Some(name)) = &payload.push_remote {
+        let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+        if deployment.git().get_remote(&repo.path, name).is_err() {
+            return Err(ApiError::BadRequest(format!(
+                "Remote '{name}' is not configured in repository '{}'",
+                repo.name
+            )));
+        }
+    }
+
+    let repo = Repo::update(pool, repo_id, &payload).await?;
     Ok(ResponseJson(ApiResponse::success(repo)))
 }
 
@@ -323,10 +631,30 @@ pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/repos", get(get_repos).post(register_repo))
         .route("/repos/init", post(init_repo))
+        .route("/repos/discover", post(discover_repos))
         .route("/repos/batch", post(get_repos_batch))
         .route("/repos/{repo_id}", get(get_repo).put(update_repo))
         .route("/repos/{repo_id}/branches", get(get_repo_branches))
-        .route("/repos/{repo_id}/remotes", get(get_repo_remotes))
+        .route("/repos/{repo_id}/diff", get(get_repo_diff))
+        .route(
+            "/repos/{repo_id}/remotes",
+            get(get_repo_remotes).post(add_repo_remote),
+        )
+        .route(
+            "/repos/{repo_id}/remotes/{name}",
+            delete(remove_repo_remote),
+        )
+        .route(
+            "/repos/{repo_id}/credentials",
+            get(get_repo_credential)
+                .put(set_repo_credential)
+                .delete(delete_repo_credential),
+        )
+        .route(
+            "/repos/{repo_id}/secrets",
+            get(get_repo_secret_allowlist).put(set_repo_secret_allowlist),
+        )
+        .route("/repos/{repo_id}/validate", get(validate_repo))
         .route("/repos/{repo_id}/prs", get(list_open_prs))
         .route("/repos/{repo_id}/search", get(search_repo))
         .route("/repos/{repo_id}/open-editor", post(open_repo_in_editor))
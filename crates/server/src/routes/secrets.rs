@@ -0,0 +1,56 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{get, put},
+};
+use deployment::Deployment;
+use services::services::secrets::{CreateSecret, SecretService, SecretSummary, UpdateSecret};
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+pub async fn get_secrets(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<SecretSummary>>>, ApiError> {
+    let secrets = SecretService::list(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(secrets)))
+}
+
+pub async fn create_secret(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateSecret>,
+) -> Result<ResponseJson<ApiResponse<SecretSummary>>, ApiError> {
+    let secret = SecretService::new()?
+        .create(&deployment.db().pool, &payload)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(secret)))
+}
+
+pub async fn update_secret(
+    State(deployment): State<DeploymentImpl>,
+    Path(secret_id): Path<Uuid>,
+    Json(payload): Json<UpdateSecret>,
+) -> Result<ResponseJson<ApiResponse<SecretSummary>>, ApiError> {
+    let secret = SecretService::new()?
+        .update(&deployment.db().pool, secret_id, &payload)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(secret)))
+}
+
+pub async fn delete_secret(
+    State(deployment): State<DeploymentImpl>,
+    Path(secret_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    SecretService::delete(&deployment.db().pool, secret_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_secrets).post(create_secret))
+        .route("/{secret_id}", put(update_secret).delete(delete_secret));
+
+    Router::new().nest("/secrets", inner)
+}
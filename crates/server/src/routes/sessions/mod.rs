@@ -9,6 +9,7 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    coding_agent_turn::CodingAgentTurn,
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
     scratch::{Scratch, ScratchType},
     session::{CreateSession, Session, SessionError},
@@ -22,14 +23,20 @@ use executors::{
     },
     profile::ExecutorProfileId,
 };
-use serde::Deserialize;
-use services::services::container::ContainerService;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::ContainerService,
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+};
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_session_middleware,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_session_middleware,
+    pagination::{DEFAULT_PAGE_LIMIT, Page, PageQuery, PageWindow},
     routes::task_attempts::util::restore_worktrees_to_process,
 };
 
@@ -59,6 +66,25 @@ pub async fn get_session(
     Ok(ResponseJson(ApiResponse::success(session)))
 }
 
+/// Coding agent turns of a session, oldest first. Always paginated.
+pub async fn get_session_turns(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Query(page): Query<PageQuery>,
+) -> Result<ResponseJson<ApiResponse<Page<CodingAgentTurn>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let window = page.window()?.unwrap_or(PageWindow {
+        limit: DEFAULT_PAGE_LIMIT,
+        offset: 0,
+    });
+    let turns =
+        CodingAgentTurn::find_page_by_session_id(pool, session.id, window.limit, window.offset)
+            .await?;
+    let total = CodingAgentTurn::count_by_session_id(pool, session.id).await?;
+    let turns = Page::new(turns, total, window);
+    Ok(ResponseJson(ApiResponse::success(turns)))
+}
+
 pub async fn create_session(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateSessionRequest>,
@@ -85,7 +111,7 @@ pub async fn create_session(
     Ok(ResponseJson(ApiResponse::success(session)))
 }
 
-#[derive(Debug, Deserialize, TS)]
+#[derive(Debug, Serialize, Deserialize, TS)]
 pub struct CreateFollowUpAttempt {
     pub prompt: String,
     pub executor_profile_id: ExecutorProfileId,
@@ -107,6 +133,7 @@ pub async fn follow_up(
         .ok_or(ApiError::Workspace(WorkspaceError::ValidationError(
             "Workspace not found".to_string(),
         )))?;
+    let _lock = WorkspaceLock::try_acquire(workspace.id, WorkspaceOperation::FollowUp)?;
 
     tracing::info!("{:?}", workspace);
 
@@ -234,6 +261,7 @@ pub async fn follow_up(
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let session_id_router = Router::new()
         .route("/", get(get_session))
+        .route("/turns", get(get_session_turns))
         .route("/follow-up", post(follow_up))
         .route("/review", post(review::start_review))
         .layer(from_fn_with_state(
@@ -7,7 +7,7 @@ pub mod util;
 pub mod workspace_summary;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
@@ -26,12 +26,17 @@ use db::models::{
     coding_agent_turn::CodingAgentTurn,
     execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+    merge_conflict::MergeConflict,
     project::SearchResult,
+    project_repo::ProjectRepo,
+    project_settings::ProjectSettings,
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
     task::{Task, TaskRelationships, TaskStatus},
     workspace::{CreateWorkspace, Workspace, WorkspaceError},
+    workspace_merge_description::WorkspaceMergeDescription,
     workspace_repo::{CreateWorkspaceRepo, RepoWithTargetBranch, WorkspaceRepo},
+    workspace_stack::WorkspaceStack,
 };
 use deployment::Deployment;
 use executors::{
@@ -42,11 +47,14 @@ use executors::{
     executors::{CodingAgent, ExecutorError},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
-use git::{ConflictOp, GitCliError, GitServiceError};
+use git::{ConflictOp, GitCliError, GitService, GitServiceError};
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService, file_search::SearchQuery, workspace_manager::WorkspaceManager,
+    container::ContainerService,
+    file_search::SearchQuery,
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+    workspace_manager::WorkspaceManager,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
@@ -54,7 +62,10 @@ use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_workspace_middleware,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{IfMatch, StaleVersion, load_workspace_middleware},
+    pagination::{Listing, Page, PageQuery},
     routes::task_attempts::gh_cli_setup::GhCliSetupError,
 };
 
@@ -110,10 +121,19 @@ pub struct UpdateWorkspace {
 pub async fn get_task_attempts(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskAttemptQuery>,
-) -> Result<ResponseJson<ApiResponse<Vec<Workspace>>>, ApiError> {
+    Query(page): Query<PageQuery>,
+) -> Result<ResponseJson<ApiResponse<Listing<Workspace>>>, ApiError> {
     let pool = &deployment.db().pool;
-    let workspaces = Workspace::fetch_all(pool, query.task_id).await?;
-    Ok(ResponseJson(ApiResponse::success(workspaces)))
+    let listing = match page.window()? {
+        Some(window) => {
+            let workspaces =
+                Workspace::fetch_page(pool, query.task_id, window.limit, window.offset).await?;
+            let total = Workspace::count(pool, query.task_id).await?;
+            Listing::Page(Page::new(workspaces, total, window))
+        }
+        None => Listing::All(Workspace::fetch_all(pool, query.task_id).await?),
+    };
+    Ok(ResponseJson(ApiResponse::success(listing)))
 }
 
 pub async fn get_workspace_count(
@@ -130,12 +150,27 @@ pub async fn get_task_attempt(
     Ok(ResponseJson(ApiResponse::success(workspace)))
 }
 
+/// Update workspace metadata. Honours `If-Match` with the workspace's `updated_at`, returning
+/// 409 with the current workspace when it has changed since the client loaded it.
 pub async fn update_workspace(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
+    if_match: IfMatch,
     Json(request): Json<UpdateWorkspace>,
-) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+) -> Result<
+    (
+        StatusCode,
+        ResponseJson<ApiResponse<Workspace, StaleVersion<Workspace>>>,
+    ),
+    ApiError,
+> {
     let pool = &deployment.db().pool;
+    if let Err(stale) = if_match.check(workspace.updated_at, &workspace) {
+        return Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::error_with_data(stale)),
+        ));
+    }
     Workspace::update(
         pool,
         workspace.id,
@@ -147,20 +182,108 @@ pub async fn update_workspace(
     let updated = Workspace::find_by_id(pool, workspace.id)
         .await?
         .ok_or(WorkspaceError::TaskNotFound)?;
-    Ok(ResponseJson(ApiResponse::success(updated)))
+    Ok((StatusCode::OK, ResponseJson(ApiResponse::success(updated))))
 }
 
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
 pub struct CreateTaskAttemptBody {
     pub task_id: Uuid,
-    pub executor_profile_id: ExecutorProfileId,
+    /// Agent to start; unset uses the project's default agent
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+    /// Repos the workspace provisions worktrees for, a subset of the project's; empty uses
+    /// all of them, or the parent workspace's repos when stacking
+    #[serde(default)]
     pub repos: Vec<WorkspaceRepoInput>,
+    /// Stack the new workspace on this one: each repo starts from the parent workspace's
+    /// branch and targets it instead of `target_branch`
+    #[serde(default)]
+    #[ts(optional)]
+    pub parent_workspace_id: Option<Uuid>,
+    /// Adopt this existing local branch instead of creating one: worktrees are checked out
+    /// from it in every repo and no coding agent is started
+    #[serde(default)]
+    #[ts(optional)]
+    pub existing_branch: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
 pub struct WorkspaceRepoInput {
     pub repo_id: Uuid,
-    pub target_branch: String,
+    /// Branch the workspace targets in this repo; unset uses the project's default target
+    /// branch for the repo, then the repo's own, then the branch checked out in the repo
+    #[serde(default)]
+    #[ts(optional)]
+    pub target_branch: Option<String>,
+}
+
+/// Repos a new workspace of `project_id` provisions worktrees for: the requested subset of
+/// the project's repos, or all of them when none are requested. Returns 400 if a requested
+/// repo is not part of the project or is requested twice.
+pub async fn select_workspace_repos(
+    pool: &sqlx::SqlitePool,
+    project_id: Uuid,
+    repos: Vec<WorkspaceRepoInput>,
+) -> Result<Vec<WorkspaceRepoInput>, ApiError> {
+    let project_repos = ProjectRepo::find_by_project_id(pool, project_id).await?;
+    if project_repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Project has no repositories".to_string(),
+        ));
+    }
+    if repos.is_empty() {
+        return Ok(project_repos
+            .into_iter()
+            .map(|project_repo| WorkspaceRepoInput {
+                repo_id: project_repo.repo_id,
+                target_branch: None,
+            })
+            .collect());
+    }
+
+    let mut seen = HashSet::new();
+    for input in &repos {
+        if !project_repos.iter().any(|pr| pr.repo_id == input.repo_id) {
+            return Err(ApiError::BadRequest(format!(
+                "Repo {} is not part of the task's project",
+                input.repo_id
+            )));
+        }
+        if !seen.insert(input.repo_id) {
+            return Err(ApiError::BadRequest(format!(
+                "Repo {} is requested more than once",
+                input.repo_id
+            )));
+        }
+    }
+    Ok(repos)
+}
+
+/// Target branch of a new workspace of `project_id` in the requested repo
+pub async fn resolve_target_branch(
+    git: &GitService,
+    pool: &sqlx::SqlitePool,
+    project_id: Uuid,
+    input: &WorkspaceRepoInput,
+) -> Result<String, ApiError> {
+    if let Some(target_branch) = &input.target_branch {
+        return Ok(target_branch.clone());
+    }
+    if let Some(target_branch) =
+        ProjectRepo::find_by_project_and_repo(pool, project_id, input.repo_id)
+            .await?
+            .and_then(|project_repo| project_repo.default_target_branch)
+    {
+        return Ok(target_branch);
+    }
+    let repo = Repo::find_by_id(pool, input.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+    match repo.default_target_branch {
+        Some(target_branch) => Ok(target_branch),
+        None => Ok(git.get_head_info(&repo.path)?.branch),
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -171,29 +294,125 @@ pub struct RunAgentSetupRequest {
 #[derive(Debug, Serialize, TS)]
 pub struct RunAgentSetupResponse {}
 
+/// Load the workspace a new workspace is being stacked on, checking that it is still open
+/// and contains every requested repo
+async fn find_stack_parent(
+    pool: &sqlx::SqlitePool,
+    parent_id: Uuid,
+    repos: &[WorkspaceRepoInput],
+) -> Result<Workspace, ApiError> {
+    let parent = Workspace::find_by_id(pool, parent_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Parent workspace {parent_id} not found")))?;
+    if parent.archived {
+        return Err(ApiError::BadRequest(
+            "Cannot stack a workspace on an archived workspace".to_string(),
+        ));
+    }
+
+    let parent_repos = WorkspaceRepo::find_by_workspace_id(pool, parent_id).await?;
+    if let Some(missing) = repos
+        .iter()
+        .find(|r| !parent_repos.iter().any(|p| p.repo_id == r.repo_id))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Repo {} is not part of parent workspace {}",
+            missing.repo_id, parent_id
+        )));
+    }
+    Ok(parent)
+}
+
+/// Check that a branch made outside the tool can back a new workspace: it must exist locally
+/// in every requested repo and not be checked out anywhere, since git allows a branch in
+/// only one worktree at a time
+async fn check_adoptable_branch(
+    git: &GitService,
+    pool: &sqlx::SqlitePool,
+    branch: &str,
+    repos: &[WorkspaceRepoInput],
+) -> Result<(), ApiError> {
+    for input in repos {
+        let repo = Repo::find_by_id(pool, input.repo_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+        if !matches!(
+            git.find_branch_type(&repo.path, branch),
+            Ok(BranchType::Local)
+        ) {
+            return Err(ApiError::BadRequest(format!(
+                "Branch '{}' is not a local branch of repo '{}'",
+                branch, repo.name
+            )));
+        }
+        if let Some(path) = git.find_checkout_path_for_branch(&repo.path, branch)? {
+            return Err(ApiError::BadRequest(format!(
+                "Branch '{}' is checked out at {}; switch that checkout to another branch first",
+                branch,
+                path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[axum::debug_handler]
 pub async fn create_task_attempt(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateTaskAttemptBody>,
 ) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
-    let executor_profile_id = payload.executor_profile_id.clone();
-
-    if payload.repos.is_empty() {
-        return Err(ApiError::BadRequest(
-            "At least one repository is required".to_string(),
-        ));
-    }
-
     let pool = &deployment.db().pool;
     let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
         .await?
         .ok_or(SqlxError::RowNotFound)?;
+    let project_settings = ProjectSettings::find_by_project_id(pool, task.project_id).await?;
+    let executor_profile_id = match payload.executor_profile_id.clone().or_else(|| {
+        project_settings
+            .as_ref()
+            .and_then(|settings| settings.default_executor_profile_id.clone())
+            .map(|profile| profile.0)
+    }) {
+        Some(executor_profile_id) => executor_profile_id,
+        None => {
+            return Err(ApiError::BadRequest(
+                "An executor profile is required; the project has no default agent".to_string(),
+            ));
+        }
+    };
+
+    let repos = match payload.parent_workspace_id {
+        Some(parent_id) if payload.repos.is_empty() => {
+            WorkspaceRepo::find_by_workspace_id(pool, parent_id)
+                .await?
+                .into_iter()
+                .map(|workspace_repo| WorkspaceRepoInput {
+                    repo_id: workspace_repo.repo_id,
+                    target_branch: None,
+                })
+                .collect()
+        }
+        _ => select_workspace_repos(pool, task.project_id, payload.repos).await?,
+    };
+
+    if let Some(branch) = &payload.existing_branch {
+        if payload.parent_workspace_id.is_some() {
+            return Err(ApiError::BadRequest(
+                "A workspace cannot both adopt a branch and be stacked".to_string(),
+            ));
+        }
+        check_adoptable_branch(deployment.git(), pool, branch, &repos).await?;
+    }
+
+    let parent_workspace = match payload.parent_workspace_id {
+        Some(parent_id) => Some(find_stack_parent(pool, parent_id, &repos).await?),
+        None => None,
+    };
 
     // Compute agent_working_dir based on repo count:
     // - Single repo: use repo name as working dir (agent runs in repo directory)
     // - Multiple repos: use None (agent runs in workspace root)
-    let agent_working_dir = if payload.repos.len() == 1 {
-        let repo = Repo::find_by_id(pool, payload.repos[0].repo_id)
+    let agent_working_dir = if repos.len() == 1 {
+        let repo = Repo::find_by_id(pool, repos[0].repo_id)
             .await?
             .ok_or(RepoError::NotFound)?;
         Some(repo.name)
@@ -202,10 +421,21 @@ pub async fn create_task_attempt(
     };
 
     let attempt_id = Uuid::new_v4();
-    let git_branch_name = deployment
-        .container()
-        .git_branch_from_workspace(&attempt_id, &task.title)
-        .await;
+    let git_branch_name = match &payload.existing_branch {
+        Some(branch) => branch.clone(),
+        None => {
+            deployment
+                .container()
+                .git_branch_from_template(
+                    project_settings
+                        .as_ref()
+                        .and_then(|settings| settings.branch_template.as_deref()),
+                    &attempt_id,
+                    &task.title,
+                )
+                .await
+        }
+    };
 
     let workspace = Workspace::create(
         pool,
@@ -218,17 +448,41 @@ pub async fn create_task_attempt(
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
-        .iter()
-        .map(|r| CreateWorkspaceRepo {
-            repo_id: r.repo_id,
-            target_branch: r.target_branch.clone(),
-            start_from_ref: None,
-        })
-        .collect();
+    let mut workspace_repos = Vec::with_capacity(repos.len());
+    for r in &repos {
+        workspace_repos.push(match &parent_workspace {
+            Some(parent) => CreateWorkspaceRepo {
+                repo_id: r.repo_id,
+                target_branch: parent.branch.clone(),
+                start_from_ref: Some(parent.branch.clone()),
+            },
+            None => CreateWorkspaceRepo {
+                repo_id: r.repo_id,
+                target_branch: resolve_target_branch(deployment.git(), pool, task.project_id, r)
+                    .await?,
+                start_from_ref: None,
+            },
+        });
+    }
 
     WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
+    if let Some(parent) = &parent_workspace {
+        WorkspaceStack::create(pool, workspace.id, parent.id).await?;
+    }
+    if payload.existing_branch.is_some() {
+        // The branch already exists, so only its worktrees are checked out
+        deployment
+            .container()
+            .ensure_container_exists(&workspace)
+            .await?;
+        tracing::info!(
+            "Adopted branch '{}' as workspace {} for task {}",
+            workspace.branch,
+            workspace.id,
+            task.id
+        );
+        return Ok(ResponseJson(ApiResponse::success(workspace)));
+    }
     if let Err(err) = deployment
         .container()
         .start_workspace(&workspace, executor_profile_id.clone())
@@ -245,7 +499,7 @@ pub async fn create_task_attempt(
                 "variant": &executor_profile_id.variant,
                 "executor": &executor_profile_id.executor,
                 "workspace_id": workspace.id.to_string(),
-                "repository_count": payload.repos.len(),
+                "repository_count": repos.len(),
             }),
         )
         .await;
@@ -409,6 +663,12 @@ async fn handle_workspaces_ws(
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct MergeTaskAttemptRequest {
     pub repo_id: Uuid,
+    /// Merge only these changed files, given relative to the repo root. The other changes
+    /// are committed back onto the workspace branch on top of the merge, and the workspace
+    /// stays open for them.
+    #[serde(default)]
+    #[ts(optional)]
+    pub paths: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -423,6 +683,7 @@ pub async fn merge_task_attempt(
     Json(request): Json<MergeTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
     let pool = &deployment.db().pool;
+    let _lock = WorkspaceLock::try_acquire(workspace.id, WorkspaceOperation::Merge)?;
 
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
@@ -460,21 +721,57 @@ pub async fn merge_task_attempt(
 
     let mut commit_message = format!("{} (vibe-kanban {})", task.title, first_uuid_section);
 
-    // Add description on next line if it exists
-    if let Some(description) = &task.description
+    // A stored merge description replaces the task's; otherwise add the task description
+    // on the next line if it exists
+    if let Some(description) =
+        WorkspaceMergeDescription::find_by_workspace_id(pool, workspace.id).await?
+    {
+        commit_message = description.commit_message();
+    } else if let Some(description) = &task.description
         && !description.trim().is_empty()
     {
         commit_message.push_str("\n\n");
         commit_message.push_str(description);
     }
 
-    let merge_commit_id = deployment.git().merge_changes(
-        &repo.path,
-        &worktree_path,
-        &workspace.branch,
-        &workspace_repo.target_branch,
-        &commit_message,
-    )?;
+    let selected_paths = request.paths.as_ref().filter(|paths| !paths.is_empty());
+    let (merge_commit_id, changes_remain) = match selected_paths {
+        Some(paths) => {
+            let result = WorkspaceManager::merge_paths(
+                deployment.git(),
+                &repo.path,
+                &worktree_path,
+                &workspace.branch,
+                &workspace_repo.target_branch,
+                paths,
+                &commit_message,
+                &format!(
+                    "Changes left out of the merge into '{}'",
+                    workspace_repo.target_branch
+                ),
+            )
+            .await?;
+            (result.merge_commit_sha, !result.remaining_paths.is_empty())
+        }
+        None => match WorkspaceManager::merge_changes(
+            deployment.git(),
+            &repo.path,
+            &worktree_path,
+            &workspace.branch,
+            &workspace_repo.target_branch,
+            &commit_message,
+        )
+        .await
+        {
+            Ok(merge_commit_id) => (merge_commit_id, false),
+            Err(e) => {
+                if matches!(e, GitServiceError::MergeConflicts { .. }) {
+                    MergeConflict::record(pool, workspace.id, workspace_repo.repo_id).await?;
+                }
+                return Err(e.into());
+            }
+        },
+    };
 
     Merge::create_direct(
         pool,
@@ -484,6 +781,21 @@ pub async fn merge_task_attempt(
         &merge_commit_id,
     )
     .await?;
+
+    // The workspace carries on with the changes that were left out
+    if changes_remain {
+        deployment
+            .track_if_analytics_allowed(
+                "task_attempt_partially_merged",
+                serde_json::json!({
+                    "task_id": task.id.to_string(),
+                    "workspace_id": workspace.id.to_string(),
+                }),
+            )
+            .await;
+        return Ok(ResponseJson(ApiResponse::success(())));
+    }
+
     Task::update_status(pool, task.id, TaskStatus::Done).await?;
     if !workspace.pinned {
         Workspace::set_archived(pool, workspace.id, true).await?;
@@ -533,6 +845,7 @@ pub async fn push_task_attempt_branch(
     Json(request): Json<PushTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
     let pool = &deployment.db().pool;
+    let _lock = WorkspaceLock::try_acquire(workspace.id, WorkspaceOperation::Push)?;
 
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
@@ -550,10 +863,12 @@ pub async fn push_task_attempt_branch(
     let workspace_path = Path::new(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
 
-    match deployment
-        .git()
-        .push_to_remote(&worktree_path, &workspace.branch, false)
-    {
+    match deployment.git().push_to_remote(
+        &worktree_path,
+        workspace_repo.effective_push_remote(&repo),
+        &workspace.branch,
+        false,
+    ) {
         Ok(_) => Ok(ResponseJson(ApiResponse::success(()))),
         Err(GitServiceError::GitCLI(GitCliError::PushRejected(_))) => Ok(ResponseJson(
             ApiResponse::error_with_data(PushError::ForcePushRequired),
@@ -568,6 +883,7 @@ pub async fn force_push_task_attempt_branch(
     Json(request): Json<PushTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
     let pool = &deployment.db().pool;
+    let _lock = WorkspaceLock::try_acquire(workspace.id, WorkspaceOperation::Push)?;
 
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
@@ -585,9 +901,12 @@ pub async fn force_push_task_attempt_branch(
     let workspace_path = Path::new(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
 
-    deployment
-        .git()
-        .push_to_remote(&worktree_path, &workspace.branch, true)?;
+    deployment.git().push_to_remote(
+        &worktree_path,
+        workspace_repo.effective_push_remote(&repo),
+        &workspace.branch,
+        true,
+    )?;
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
@@ -869,6 +1188,13 @@ pub struct ChangeTargetBranchResponse {
     pub status: (usize, usize),
 }
 
+#[derive(serde::Deserialize, Debug, TS)]
+pub struct SetPushRemoteRequest {
+    pub repo_id: Uuid,
+    /// Remote to push this workspace's branch to; `null` falls back to the repo's push remote
+    pub remote_name: Option<String>,
+}
+
 #[derive(serde::Deserialize, Debug, TS)]
 pub struct RenameBranchRequest {
     pub new_branch_name: String,
@@ -944,6 +1270,36 @@ pub async fn change_target_branch(
     )))
 }
 
+/// Choose which remote pushes and PRs for one repo of the workspace go through
+pub async fn set_push_remote(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetPushRemoteRequest>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceRepo>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let repo = Repo::find_by_id(pool, payload.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    if let Some(name) = &payload.remote_name {
+        deployment.git().get_remote(&repo.path, name).map_err(|_| {
+            ApiError::BadRequest(format!(
+                "Remote '{}' is not configured in repository '{}'",
+                name, repo.name
+            ))
+        })?;
+    }
+
+    WorkspaceRepo::update_push_remote(pool, workspace.id, repo.id, payload.remote_name.as_deref())
+        .await?;
+    let workspace_repo = WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, repo.id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    Ok(ResponseJson(ApiResponse::success(workspace_repo)))
+}
+
 #[axum::debug_handler]
 pub async fn rename_branch(
     Extension(workspace): Extension<Workspace>,
@@ -1054,13 +1410,23 @@ pub async fn rename_branch(
 
     Workspace::update_branch_name(pool, workspace.id, new_branch_name).await?;
     // What will become of me?
-    let updated_children_count = WorkspaceRepo::update_target_branch_for_children_of_workspace(
+    let mut updated_children_count = WorkspaceRepo::update_target_branch_for_children_of_workspace(
         pool,
         workspace.id,
         &old_branch,
         new_branch_name,
     )
     .await?;
+    for repo in &repos {
+        updated_children_count += WorkspaceStack::retarget_children(
+            pool,
+            workspace.id,
+            repo.id,
+            &old_branch,
+            new_branch_name,
+        )
+        .await?;
+    }
 
     if updated_children_count > 0 {
         tracing::info!(
@@ -1091,6 +1457,7 @@ pub async fn rebase_task_attempt(
     Json(payload): Json<RebaseTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<(), GitOperationError>>, ApiError> {
     let pool = &deployment.db().pool;
+    let _lock = WorkspaceLock::try_acquire(workspace.id, WorkspaceOperation::Rebase)?;
 
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, payload.repo_id)
@@ -1147,6 +1514,9 @@ pub async fn rebase_task_attempt(
         &workspace.branch.clone(),
     );
     if let Err(e) = result {
+        if matches!(e, GitServiceError::MergeConflicts { .. }) {
+            MergeConflict::record(pool, workspace.id, payload.repo_id).await?;
+        }
         return match e {
             GitServiceError::MergeConflicts {
                 message,
@@ -1294,6 +1664,8 @@ pub async fn start_dev_server(
                 language: ScriptRequestLanguage::Bash,
                 context: ScriptContext::DevServer,
                 working_dir: Some(repo.name.clone()),
+                env: HashMap::new(),
+                artifacts: Vec::new(),
             }),
             None,
         );
@@ -1778,6 +2150,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/children", get(get_task_attempt_children))
         .route("/stop", post(stop_task_attempt_execution))
         .route("/change-target-branch", post(change_target_branch))
+        .route("/push-remote", put(set_push_remote))
         .route("/rename-branch", post(rename_branch))
         .route("/repos", get(get_task_attempt_repos))
         .route("/search", get(search_workspace_files))
@@ -1799,3 +2172,223 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     Router::new().nest("/task-attempts", task_attempts_router)
 }
+
+#[cfg(test)]
+mod tests {
+    use db::models::repo::UpdateRepo;
+
+    use super::*;
+    use crate::test_support::{
+        ScratchDir, init_repo, run_git, seed_project, seed_task, seed_workspace, test_pool,
+    };
+
+    #[tokio::test]
+    async fn stack_parents_must_be_open_and_hold_every_repo() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api", "web"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let parent = seed_workspace(&pool, task.id, "vk/login", &[&repos[0]]).await;
+        let input = |repo: &Repo| WorkspaceRepoInput {
+            repo_id: repo.id,
+            target_branch: None,
+        };
+
+        let found = find_stack_parent(&pool, parent.id, &[input(&repos[0])])
+            .await
+            .unwrap();
+        assert_eq!(found.id, parent.id);
+        assert!(matches!(
+            find_stack_parent(&pool, parent.id, &[input(&repos[0]), input(&repos[1])]).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(matches!(
+            find_stack_parent(&pool, Uuid::new_v4(), &[input(&repos[0])]).await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        Workspace::set_archived(&pool, parent.id, true)
+            .await
+            .unwrap();
+        assert!(matches!(
+            find_stack_parent(&pool, parent.id, &[input(&repos[0])]).await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn renaming_a_parent_branch_retargets_only_its_stacked_children() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let repo = &repos[0];
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let parent = seed_workspace(&pool, task.id, "vk/login", &[repo]).await;
+        let child = Workspace::create(
+            &pool,
+            &CreateWorkspace {
+                branch: "vk/login-tests".to_string(),
+                agent_working_dir: None,
+            },
+            Uuid::new_v4(),
+            task.id,
+        )
+        .await
+        .unwrap();
+        WorkspaceRepo::create_many(
+            &pool,
+            child.id,
+            &[CreateWorkspaceRepo {
+                repo_id: repo.id,
+                target_branch: parent.branch.clone(),
+                start_from_ref: Some(parent.branch.clone()),
+            }],
+        )
+        .await
+        .unwrap();
+        WorkspaceStack::create(&pool, child.id, parent.id)
+            .await
+            .unwrap();
+        let unrelated = seed_workspace(&pool, task.id, "vk/other", &[repo]).await;
+
+        let retargeted =
+            WorkspaceStack::retarget_children(&pool, parent.id, repo.id, "vk/login", "vk/auth")
+                .await
+                .unwrap();
+
+        assert_eq!(retargeted, 1);
+        let target_of = |workspace_id| {
+            let pool = pool.clone();
+            async move {
+                WorkspaceRepo::find_by_workspace_id(&pool, workspace_id)
+                    .await
+                    .unwrap()[0]
+                    .target_branch
+                    .clone()
+            }
+        };
+        assert_eq!(target_of(child.id).await, "vk/auth");
+        assert_eq!(target_of(unrelated.id).await, "main");
+        assert_eq!(
+            WorkspaceStack::find_parent_id(&pool, child.id)
+                .await
+                .unwrap(),
+            Some(parent.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn only_local_branches_checked_out_nowhere_can_be_adopted() {
+        let pool = test_pool().await;
+        let scratch = ScratchDir::new();
+        let repo_path = scratch.0.join("api");
+        init_repo(&repo_path, "README.md", "# api\n");
+        run_git(&repo_path, &["branch", "feature"]);
+        run_git(&repo_path, &["branch", "busy"]);
+        let worktree = scratch.0.join("busy-worktree");
+        run_git(
+            &repo_path,
+            &["worktree", "add", "-q", &worktree.to_string_lossy(), "busy"],
+        );
+        let repo = Repo::find_or_create(&pool, &repo_path, "api")
+            .await
+            .unwrap();
+        let repos = [WorkspaceRepoInput {
+            repo_id: repo.id,
+            target_branch: None,
+        }];
+        let git = GitService::new();
+
+        check_adoptable_branch(&git, &pool, "feature", &repos)
+            .await
+            .unwrap();
+        for branch in ["missing", "main", "busy"] {
+            assert!(
+                matches!(
+                    check_adoptable_branch(&git, &pool, branch, &repos).await,
+                    Err(ApiError::BadRequest(_))
+                ),
+                "{branch}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn target_branches_default_from_the_project_then_the_repo_then_head() {
+        let pool = test_pool().await;
+        let dir = ScratchDir::new();
+        let path = dir.0.join("api");
+        init_repo(&path, "README.md", "hello\n");
+        let (project, _) = seed_project(&pool, &[]).await;
+        let repo = Repo::find_or_create(&pool, &path, "api").await.unwrap();
+        ProjectRepo::create(&pool, project.id, repo.id)
+            .await
+            .unwrap();
+        let git = GitService::new();
+        let resolve = |target_branch: Option<&str>| {
+            let input = WorkspaceRepoInput {
+                repo_id: repo.id,
+                target_branch: target_branch.map(str::to_string),
+            };
+            let (git, pool) = (git.clone(), pool.clone());
+            async move {
+                resolve_target_branch(&git, &pool, project.id, &input)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert_eq!(resolve(None).await, "main");
+        let update = UpdateRepo {
+            default_target_branch: Some(Some("develop".to_string())),
+            ..Default::default()
+        };
+        Repo::update(&pool, repo.id, &update).await.unwrap();
+        assert_eq!(resolve(None).await, "develop");
+        ProjectRepo::update_default_target_branch(&pool, project.id, repo.id, Some("release"))
+            .await
+            .unwrap();
+        assert_eq!(resolve(None).await, "release");
+        assert_eq!(resolve(Some("hotfix")).await, "hotfix");
+    }
+
+    #[tokio::test]
+    async fn workspaces_provision_the_requested_project_repos_or_all_of_them() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api", "web"]).await;
+        let (_, foreign) = seed_project(&pool, &["docs"]).await;
+        let (empty, _) = seed_project(&pool, &[]).await;
+        let input = |repo: &Repo| WorkspaceRepoInput {
+            repo_id: repo.id,
+            target_branch: None,
+        };
+        let selected_ids = |selected: Vec<WorkspaceRepoInput>| {
+            let mut ids: Vec<Uuid> = selected.into_iter().map(|r| r.repo_id).collect();
+            ids.sort();
+            ids
+        };
+
+        let all = select_workspace_repos(&pool, project.id, Vec::new())
+            .await
+            .unwrap();
+        let mut expected = vec![repos[0].id, repos[1].id];
+        expected.sort();
+        assert_eq!(selected_ids(all), expected);
+        let subset = select_workspace_repos(&pool, project.id, vec![input(&repos[1])])
+            .await
+            .unwrap();
+        assert_eq!(selected_ids(subset), vec![repos[1].id]);
+
+        for requested in [
+            vec![input(&foreign[0])],
+            vec![input(&repos[0]), input(&repos[0])],
+        ] {
+            assert!(matches!(
+                select_workspace_repos(&pool, project.id, requested).await,
+                Err(ApiError::BadRequest(_))
+            ));
+        }
+        assert!(matches!(
+            select_workspace_repos(&pool, empty.id, Vec::new()).await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
     session::{CreateSession, Session},
@@ -92,6 +94,8 @@ async fn get_setup_helper_action(codex: &Codex) -> Result<ExecutorAction, ApiErr
         language: ScriptRequestLanguage::Bash,
         context: ScriptContext::ToolInstallScript,
         working_dir: None,
+        env: HashMap::new(),
+        artifacts: Vec::new(),
     };
 
     Ok(ExecutorAction::new(
@@ -1,3 +1,6 @@
+#[cfg(unix)]
+use std::collections::HashMap;
+
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
     session::{CreateSession, Session},
@@ -109,6 +112,8 @@ fi"#
             language: ScriptRequestLanguage::Bash,
             context: ScriptContext::ToolInstallScript,
             working_dir: None,
+            env: HashMap::new(),
+            artifacts: Vec::new(),
         };
         // Second action (chained): Login
         let login_script = format!(
@@ -123,6 +128,8 @@ export PATH="$HOME/.local/bin:$PATH"
             language: ScriptRequestLanguage::Bash,
             context: ScriptContext::ToolInstallScript,
             working_dir: None,
+            env: HashMap::new(),
+            artifacts: Vec::new(),
         };
 
         // Chain them: install → login
@@ -1,3 +1,6 @@
+#[cfg(unix)]
+use std::collections::HashMap;
+
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
     session::{CreateSession, Session},
@@ -96,6 +99,8 @@ fi"#
             language: ScriptRequestLanguage::Bash,
             context: ScriptContext::ToolInstallScript,
             working_dir: None,
+            env: HashMap::new(),
+            artifacts: Vec::new(),
         };
 
         // Auth script
@@ -111,6 +116,8 @@ gh auth login --web --git-protocol https --skip-ssh-key
             language: ScriptRequestLanguage::Bash,
             context: ScriptContext::ToolInstallScript,
             working_dir: None,
+            env: HashMap::new(),
+            artifacts: Vec::new(),
         };
 
         // Chain them: install → auth
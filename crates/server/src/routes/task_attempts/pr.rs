@@ -7,12 +7,13 @@ use axum::{
 };
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
-    merge::{Merge, MergeStatus},
+    merge::{Merge, MergeStatus, PullRequestInfo},
     project_repo::ProjectRepo,
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
     task::{CreateTask, Task, TaskStatus},
     workspace::{CreateWorkspace, Workspace, WorkspaceError},
+    workspace_merge_description::WorkspaceMergeDescription,
     workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
 use deployment::Deployment;
@@ -199,6 +200,40 @@ pub async fn create_pr(
     State(deployment): State<DeploymentImpl>,
     Json(request): Json<CreatePrApiRequest>,
 ) -> Result<ResponseJson<ApiResponse<String, PrError>>, ApiError> {
+    let pr_info = match open_workspace_pr(&deployment, &workspace, &request).await? {
+        Ok(pr_info) => pr_info,
+        Err(error) => return Ok(ResponseJson(ApiResponse::error_with_data(error))),
+    };
+
+    // Auto-open PR in browser
+    if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
+        tracing::warn!("Failed to open PR in browser: {}", e);
+    }
+
+    // Trigger auto-description follow-up if enabled
+    if request.auto_generate_description
+        && let Err(e) =
+            trigger_pr_description_follow_up(&deployment, &workspace, pr_info.number, &pr_info.url)
+                .await
+    {
+        tracing::warn!(
+            "Failed to trigger PR description follow-up for attempt {}: {}",
+            workspace.id,
+            e
+        );
+    }
+
+    Ok(ResponseJson(ApiResponse::success(pr_info.url)))
+}
+
+/// Push the workspace branch of `request.repo_id` and open a PR for it, recording the PR
+/// against the workspace. Problems the user can fix, such as a missing or logged-out CLI, are
+/// returned as a `PrError`.
+pub(crate) async fn open_workspace_pr(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    request: &CreatePrApiRequest,
+) -> Result<Result<PullRequestInfo, PrError>, ApiError> {
     let pool = &deployment.db().pool;
 
     let workspace_repo =
@@ -211,7 +246,7 @@ pub async fn create_pr(
         .ok_or(RepoError::NotFound)?;
 
     let repo_path = repo.path.clone();
-    let target_branch = if let Some(branch) = request.target_branch {
+    let target_branch = if let Some(branch) = request.target_branch.clone() {
         branch
     } else {
         workspace_repo.target_branch.clone()
@@ -219,13 +254,16 @@ pub async fn create_pr(
 
     let container_ref = deployment
         .container()
-        .ensure_container_exists(&workspace)
+        .ensure_container_exists(workspace)
         .await?;
     let workspace_path = PathBuf::from(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
 
     let git = deployment.git();
-    let push_remote = git.resolve_remote_for_branch(&repo_path, &workspace.branch)?;
+    let push_remote = match workspace_repo.effective_push_remote(&repo) {
+        Some(name) => git.get_remote(&repo_path, name)?,
+        None => git.resolve_remote_for_branch(&repo_path, &workspace.branch)?,
+    };
 
     // Try to get the remote from the branch name (works for remote-tracking branches like "upstream/main").
     // Fall back to push_remote if the branch doesn't exist locally or isn't a remote-tracking branch.
@@ -242,38 +280,33 @@ pub async fn create_pr(
 
     match git.check_remote_branch_exists(&repo_path, &target_remote.url, &base_branch) {
         Ok(false) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::TargetBranchNotFound {
-                    branch: target_branch.clone(),
-                },
-            )));
+            return Ok(Err(PrError::TargetBranchNotFound {
+                branch: target_branch.clone(),
+            }));
         }
         Err(GitServiceError::GitCLI(GitCliError::AuthFailed(_))) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::GitCliNotLoggedIn,
-            )));
+            return Ok(Err(PrError::GitCliNotLoggedIn));
         }
         Err(GitServiceError::GitCLI(GitCliError::NotAvailable)) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::GitCliNotInstalled,
-            )));
+            return Ok(Err(PrError::GitCliNotInstalled));
         }
         Err(e) => return Err(ApiError::GitService(e)),
         Ok(true) => {}
     }
 
-    if let Err(e) = git.push_to_remote(&worktree_path, &workspace.branch, false) {
+    if let Err(e) = git.push_to_remote(
+        &worktree_path,
+        Some(&push_remote.name),
+        &workspace.branch,
+        false,
+    ) {
         tracing::error!("Failed to push branch to remote: {}", e);
         match e {
             GitServiceError::GitCLI(GitCliError::AuthFailed(_)) => {
-                return Ok(ResponseJson(ApiResponse::error_with_data(
-                    PrError::GitCliNotLoggedIn,
-                )));
+                return Ok(Err(PrError::GitCliNotLoggedIn));
             }
             GitServiceError::GitCLI(GitCliError::NotAvailable) => {
-                return Ok(ResponseJson(ApiResponse::error_with_data(
-                    PrError::GitCliNotInstalled,
-                )));
+                return Ok(Err(PrError::GitCliNotInstalled));
             }
             _ => return Err(ApiError::GitService(e)),
         }
@@ -282,24 +315,27 @@ pub async fn create_pr(
     let git_host = match git_host::GitHostService::from_url(&target_remote.url) {
         Ok(host) => host,
         Err(GitHostError::UnsupportedProvider) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::UnsupportedProvider,
-            )));
+            return Ok(Err(PrError::UnsupportedProvider));
         }
         Err(GitHostError::CliNotInstalled { provider }) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::CliNotInstalled { provider },
-            )));
+            return Ok(Err(PrError::CliNotInstalled { provider }));
         }
         Err(e) => return Err(ApiError::GitHost(e)),
     };
 
     let provider = git_host.provider_kind();
 
-    // Create the PR
+    // Create the PR, describing it with the workspace's merge description unless a body
+    // was given
+    let body = match &request.body {
+        Some(body) => Some(body.clone()),
+        None => WorkspaceMergeDescription::find_by_workspace_id(pool, workspace.id)
+            .await?
+            .map(|description| description.body),
+    };
     let pr_request = CreatePrRequest {
         title: request.title.clone(),
-        body: request.body.clone(),
+        body,
         head_branch: workspace.branch.clone(),
         base_branch: base_branch.clone(),
         draft: request.draft,
@@ -325,11 +361,6 @@ pub async fn create_pr(
                 tracing::error!("Failed to update workspace PR status: {}", e);
             }
 
-            // Auto-open PR in browser
-            if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
-                tracing::warn!("Failed to open PR in browser: {}", e);
-            }
-
             deployment
                 .track_if_analytics_allowed(
                     "pr_created",
@@ -340,24 +371,7 @@ pub async fn create_pr(
                 )
                 .await;
 
-            // Trigger auto-description follow-up if enabled
-            if request.auto_generate_description
-                && let Err(e) = trigger_pr_description_follow_up(
-                    &deployment,
-                    &workspace,
-                    pr_info.number,
-                    &pr_info.url,
-                )
-                .await
-            {
-                tracing::warn!(
-                    "Failed to trigger PR description follow-up for attempt {}: {}",
-                    workspace.id,
-                    e
-                );
-            }
-
-            Ok(ResponseJson(ApiResponse::success(pr_info.url)))
+            Ok(Ok(pr_info))
         }
         Err(e) => {
             tracing::error!(
@@ -367,14 +381,10 @@ pub async fn create_pr(
                 e
             );
             match &e {
-                GitHostError::CliNotInstalled { provider } => Ok(ResponseJson(
-                    ApiResponse::error_with_data(PrError::CliNotInstalled {
-                        provider: *provider,
-                    }),
-                )),
-                GitHostError::AuthFailed(_) => Ok(ResponseJson(ApiResponse::error_with_data(
-                    PrError::CliNotLoggedIn { provider },
-                ))),
+                GitHostError::CliNotInstalled { provider } => Ok(Err(PrError::CliNotInstalled {
+                    provider: *provider,
+                })),
+                GitHostError::AuthFailed(_) => Ok(Err(PrError::CliNotLoggedIn { provider })),
                 _ => Err(ApiError::GitHost(e)),
             }
         }
@@ -654,6 +664,9 @@ pub async fn create_workspace_from_pr(
         image_ids: None,
         ralph_auto_continue: None,
         ralph_max_iterations: None,
+        priority: None,
+        labels: None,
+        assignee: None,
     };
     let task = Task::create(pool, &create_task, task_id).await?;
 
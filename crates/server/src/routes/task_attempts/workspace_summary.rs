@@ -165,7 +165,7 @@ pub async fn get_workspace_summaries(
 }
 
 /// Compute diff stats for a workspace.
-async fn compute_workspace_diff_stats(
+pub(crate) async fn compute_workspace_diff_stats(
     deployment: &DeploymentImpl,
     workspace: &Workspace,
 ) -> Result<DiffStats, ApiError> {
@@ -204,13 +204,16 @@ async fn compute_workspace_diff_stats(
         let diffs_result = tokio::task::spawn_blocking({
             let git = deployment.git().clone();
             let worktree = worktree_path.clone();
+            let path_scope = repo_with_branch.path_scope.clone();
             move || {
+                let path_scope = path_scope.as_deref();
                 git.get_diffs(
                     DiffTarget::Worktree {
                         worktree_path: &worktree,
                         base_commit: &base_commit,
+                        include_untracked: true,
                     },
-                    None,
+                    path_scope.as_ref().map(std::slice::from_ref),
                 )
             }
         })
@@ -0,0 +1,91 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{get, put},
+};
+use db::models::task_template::{CreateTaskTemplate, TaskTemplate, UpdateTaskTemplate};
+use deployment::Deployment;
+use serde::Deserialize;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct TaskTemplateQuery {
+    /// Only list templates usable in this project (its own and the global ones)
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+}
+
+pub async fn get_task_templates(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskTemplateQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskTemplate>>>, ApiError> {
+    let templates = TaskTemplate::find_all(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(templates)))
+}
+
+pub async fn create_task_template(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskTemplate>,
+) -> Result<ResponseJson<ApiResponse<TaskTemplate>>, ApiError> {
+    if payload.name.trim().is_empty() || payload.title_pattern.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Template name and title pattern must not be empty".to_string(),
+        ));
+    }
+    let template = TaskTemplate::create(&deployment.db().pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_template_created",
+            serde_json::json!({
+                "template_id": template.id.to_string(),
+                "is_global": template.project_id.is_none(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn update_task_template(
+    State(deployment): State<DeploymentImpl>,
+    Path(template_id): Path<Uuid>,
+    Json(payload): Json<UpdateTaskTemplate>,
+) -> Result<ResponseJson<ApiResponse<TaskTemplate>>, ApiError> {
+    let pool = &deployment.db().pool;
+    TaskTemplate::find_by_id(pool, template_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task template {template_id} not found")))?;
+
+    let template = TaskTemplate::update(pool, template_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn delete_task_template(
+    State(deployment): State<DeploymentImpl>,
+    Path(template_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = TaskTemplate::delete(&deployment.db().pool, template_id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::NotFound(format!(
+            "Task template {template_id} not found"
+        )));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_task_templates).post(create_task_template))
+        .route(
+            "/{template_id}",
+            put(update_task_template).delete(delete_task_template),
+        );
+
+    Router::new().nest("/task-templates", inner)
+}
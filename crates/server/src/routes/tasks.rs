@@ -1,10 +1,14 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 
 use anyhow;
 use axum::{
     Extension, Json, Router,
     extract::{
-        Query, State,
+        Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
     http::StatusCode,
@@ -12,12 +16,21 @@ use axum::{
     response::{IntoResponse, Json as ResponseJson},
     routing::{delete, get, post, put},
 };
+use chrono::{DateTime, Utc};
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
-    image::TaskImage,
+    image::{Image, TaskImage},
+    project::Project,
+    project_repo::ProjectRepo,
+    project_settings::ProjectSettings,
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
-    task::{CreateTask, Task, TaskType, TaskWithAttemptStatus, UpdateTask},
+    task::{
+        CreateTask, Task, TaskFilter, TaskPriority, TaskSort, TaskSortField, TaskStatus, TaskType,
+        TaskWithAttemptStatus, UpdateTask,
+    },
+    task_status_transition::TaskStatusTransition,
+    task_template::TaskTemplate,
     workspace::{CreateWorkspace, Workspace},
     workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
@@ -44,24 +57,213 @@ use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_task_middleware,
-    routes::task_attempts::WorkspaceRepoInput,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{IfMatch, StaleVersion, load_task_middleware},
+    pagination::{Page, PageQuery},
+    routes::task_attempts::{WorkspaceRepoInput, resolve_target_branch, select_workspace_repos},
 };
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskGroupBy {
+    Assignee,
+    Label,
+    Repo,
+    Priority,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskQuery {
     pub project_id: Uuid,
+    /// Return the tasks as swimlanes instead of a flat list
+    #[serde(default)]
+    pub group_by: Option<TaskGroupBy>,
+    /// Comma-separated statuses, e.g. `todo,inprogress`
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Comma-separated labels; tasks carrying any of them match
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Case-insensitive substring of the title or description
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub has_open_workspace: Option<bool>,
+    #[serde(default)]
+    pub updated_since: Option<DateTime<Utc>>,
+    /// Comma-separated sort keys, `-` prefixed for descending, e.g. `-priority,title`
+    #[serde(default)]
+    pub sort: Option<String>,
+}
+
+impl TaskQuery {
+    fn filter(&self) -> Result<TaskFilter, ApiError> {
+        let statuses = comma_separated(self.status.as_deref())
+            .map(|status| {
+                status
+                    .parse::<TaskStatus>()
+                    .map_err(|_| ApiError::BadRequest(format!("Unknown task status '{status}'")))
+            })
+            .collect::<Result<_, _>>()?;
+        let sort = comma_separated(self.sort.as_deref())
+            .map(|key| -> Result<TaskSort, ApiError> {
+                let (descending, field) = match key.strip_prefix('-') {
+                    Some(field) => (true, field),
+                    None => (false, key),
+                };
+                let field = field
+                    .parse::<TaskSortField>()
+                    .map_err(|_| ApiError::BadRequest(format!("Unknown sort key '{field}'")))?;
+                Ok(TaskSort { field, descending })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(TaskFilter {
+            statuses,
+            labels: comma_separated(self.label.as_deref())
+                .map(str::to_string)
+                .collect(),
+            assignee: self.assignee.clone().filter(|a| !a.trim().is_empty()),
+            text: self.text.clone().filter(|t| !t.trim().is_empty()),
+            has_open_workspace: self.has_open_workspace,
+            updated_since: self.updated_since,
+            sort,
+        })
+    }
+}
+
+fn comma_separated(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+}
+
+/// One swimlane of a grouped task list
+#[derive(Debug, Serialize, TS)]
+pub struct TaskGroup {
+    /// Assignee, label, repo name or priority; unset for tasks that have none
+    pub key: Option<String>,
+    pub count: usize,
+    pub tasks: Vec<TaskWithAttemptStatus>,
+}
+
+/// Flat list, one page of it when `limit`/`offset` is set, or swimlanes when `group_by` is
+#[derive(Debug, Serialize, TS)]
+#[serde(untagged)]
+pub enum TaskList {
+    Tasks(Vec<TaskWithAttemptStatus>),
+    Page(Page<TaskWithAttemptStatus>),
+    Groups(Vec<TaskGroup>),
 }
 
 pub async fn get_tasks(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskQuery>,
-) -> Result<ResponseJson<ApiResponse<Vec<TaskWithAttemptStatus>>>, ApiError> {
+    Query(page): Query<PageQuery>,
+) -> Result<ResponseJson<ApiResponse<TaskList>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let filter = query.filter()?;
+
+    if let Some(window) = page.window()? {
+        if query.group_by.is_some() {
+            return Err(ApiError::BadRequest(
+                "group_by cannot be combined with limit or offset".to_string(),
+            ));
+        }
+        let tasks = Task::find_filtered_with_attempt_status(
+            pool,
+            query.project_id,
+            &filter,
+            Some(window.limit),
+            window.offset,
+        )
+        .await?;
+        let total = Task::count_filtered(pool, query.project_id, &filter).await?;
+        return Ok(ResponseJson(ApiResponse::success(TaskList::Page(
+            Page::new(tasks, total, window),
+        ))));
+    }
+
     let tasks =
-        Task::find_by_project_id_with_attempt_status(&deployment.db().pool, query.project_id)
-            .await?;
+        Task::find_filtered_with_attempt_status(pool, query.project_id, &filter, None, 0).await?;
+    let Some(group_by) = query.group_by else {
+        return Ok(ResponseJson(ApiResponse::success(TaskList::Tasks(tasks))));
+    };
+    let groups = match group_by {
+        TaskGroupBy::Assignee => group_tasks(tasks, |task| task.assignee.iter().cloned().collect()),
+        TaskGroupBy::Label => group_tasks(tasks, |task| task.labels.0.clone()),
+        TaskGroupBy::Repo => {
+            let repos = WorkspaceRepo::find_repo_names_by_task(pool, query.project_id).await?;
+            group_tasks(tasks, |task| {
+                repos.get(&task.id).cloned().unwrap_or_default()
+            })
+        }
+        TaskGroupBy::Priority => group_by_priority(tasks),
+    };
 
-    Ok(ResponseJson(ApiResponse::success(tasks)))
+    Ok(ResponseJson(ApiResponse::success(TaskList::Groups(groups))))
+}
+
+/// Swimlanes by priority, most urgent first and tasks without a priority last
+fn group_by_priority(tasks: Vec<TaskWithAttemptStatus>) -> Vec<TaskGroup> {
+    let mut groups = group_tasks(tasks, |task| {
+        task.priority.iter().map(ToString::to_string).collect()
+    });
+    // The ungrouped lane parses to None and stays last
+    groups.sort_by_key(|group| {
+        Reverse(
+            group
+                .key
+                .as_deref()
+                .and_then(|key| key.parse::<TaskPriority>().ok()),
+        )
+    });
+    groups
+}
+
+/// Bucket tasks by the keys `keys_for` returns, keeping their order within each group.
+/// A task with several keys appears in each of those groups; one with none goes into a
+/// trailing ungrouped group.
+fn group_tasks(
+    tasks: Vec<TaskWithAttemptStatus>,
+    keys_for: impl Fn(&TaskWithAttemptStatus) -> Vec<String>,
+) -> Vec<TaskGroup> {
+    let mut grouped: BTreeMap<String, Vec<TaskWithAttemptStatus>> = BTreeMap::new();
+    let mut ungrouped = Vec::new();
+    for task in tasks {
+        let mut keys = keys_for(&task);
+        keys.sort();
+        keys.dedup();
+        if keys.is_empty() {
+            ungrouped.push(task);
+            continue;
+        }
+        for key in keys {
+            grouped.entry(key).or_default().push(task.clone());
+        }
+    }
+
+    let mut groups: Vec<TaskGroup> = grouped
+        .into_iter()
+        .map(|(key, tasks)| TaskGroup {
+            key: Some(key),
+            count: tasks.len(),
+            tasks,
+        })
+        .collect();
+    if !ungrouped.is_empty() {
+        groups.push(TaskGroup {
+            key: None,
+            count: ungrouped.len(),
+            tasks: ungrouped,
+        });
+    }
+    groups
 }
 
 pub async fn stream_tasks_ws(
@@ -151,10 +353,302 @@ pub async fn create_task(
     Ok(ResponseJson(ApiResponse::success(task)))
 }
 
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskFromTemplateRequest {
+    pub project_id: Uuid,
+    /// Values for the template's `{{name}}` placeholders
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    pub status: Option<TaskStatus>,
+}
+
+/// Create a task from a template, filling its placeholders from `variables`
+pub async fn create_task_from_template(
+    State(deployment): State<DeploymentImpl>,
+    Path(template_id): Path<Uuid>,
+    Json(payload): Json<CreateTaskFromTemplateRequest>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let template = TaskTemplate::find_by_id(pool, template_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task template {template_id} not found")))?;
+    if template
+        .project_id
+        .is_some_and(|project_id| project_id != payload.project_id)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Task template '{}' belongs to a different project",
+            template.name
+        )));
+    }
+
+    let rendered = template.render(&payload.variables).map_err(|missing| {
+        ApiError::BadRequest(format!(
+            "Missing values for template placeholders: {}",
+            missing.join(", ")
+        ))
+    })?;
+
+    let mut create = CreateTask::from_title_description(
+        payload.project_id,
+        rendered.title,
+        rendered.description,
+    );
+    if payload.status.is_some() {
+        create.status = payload.status;
+    }
+    create.priority = template.priority;
+    create.labels = Some(template.labels.0.clone());
+    let task = Task::create(pool, &create, Uuid::new_v4()).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_created",
+            serde_json::json!({
+            "task_id": task.id.to_string(),
+            "project_id": payload.project_id,
+            "has_description": task.description.is_some(),
+            "has_images": false,
+            "template_id": template.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct DuplicateTaskRequest {
+    /// Project to create the copy in; unset copies into the task's own project
+    #[serde(default)]
+    #[ts(optional)]
+    pub project_id: Option<Uuid>,
+}
+
+/// Create a to-do copy of the task with its title, description, type, priority, labels,
+/// assignee and images. Workspaces and history are not copied.
+/// Returns 404 if the target project does not exist.
+pub async fn duplicate_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<DuplicateTaskRequest>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let project_id = payload.project_id.unwrap_or(task.project_id);
+    let (duplicate, image_ids) = copy_task(&deployment.db().pool, &task, project_id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_created",
+            serde_json::json!({
+            "task_id": duplicate.id.to_string(),
+            "project_id": project_id,
+            "has_description": duplicate.description.is_some(),
+            "has_images": !image_ids.is_empty(),
+            "duplicated_from": task.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(duplicate)))
+}
+
+/// Create a to-do copy of `task` in `project_id` sharing its images, returning the copy and
+/// the ids of those images
+async fn copy_task(
+    pool: &sqlx::SqlitePool,
+    task: &Task,
+    project_id: Uuid,
+) -> Result<(Task, Vec<Uuid>), ApiError> {
+    if project_id != task.project_id {
+        Project::find_by_id(pool, project_id)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Project {project_id} not found")))?;
+    }
+
+    let image_ids: Vec<Uuid> = Image::find_by_task_id(pool, task.id)
+        .await?
+        .into_iter()
+        .map(|image| image.id)
+        .collect();
+    let create = CreateTask {
+        project_id,
+        title: task.title.clone(),
+        description: task.description.clone(),
+        status: Some(TaskStatus::Todo),
+        task_type: Some(task.task_type.clone()),
+        parent_workspace_id: None,
+        image_ids: None,
+        ralph_auto_continue: Some(task.ralph_auto_continue),
+        ralph_max_iterations: Some(task.ralph_max_iterations),
+        priority: task.priority.clone(),
+        labels: Some(task.labels.0.clone()),
+        assignee: task.assignee.clone(),
+    };
+    let duplicate = Task::create(pool, &create, Uuid::new_v4()).await?;
+    if !image_ids.is_empty() {
+        TaskImage::associate_many_dedup(pool, duplicate.id, &image_ids).await?;
+    }
+    Ok((duplicate, image_ids))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct MoveTaskRequest {
+    pub project_id: Uuid,
+    /// Keep the task's closed workspaces with it; otherwise their records are deleted
+    #[serde(default)]
+    pub include_workspaces: bool,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct MoveTaskResponse {
+    pub task: Task,
+    /// Labels renamed to the spelling the target project already uses, as `[from, to]`
+    pub remapped_labels: Vec<(String, String)>,
+    /// Closed workspaces whose records were deleted rather than moved
+    pub dropped_workspaces: Vec<Uuid>,
+    /// Parent workspace the task no longer belongs to, as it is in another project
+    pub cleared_parent_workspace_id: Option<Uuid>,
+    /// Repos of the moved workspaces that the target project does not have
+    pub missing_repos: Vec<String>,
+}
+
+/// Move the task to another project. Labels are matched case-insensitively to those the
+/// target project already uses; statuses carry over as they are. The task's workspaces must
+/// all be closed: they move with it when `include_workspaces` is set and are deleted
+/// otherwise. Everything changed or dropped on the way is reported.
+/// Returns 404 if the target project does not exist, 400 if it is the task's own project and
+/// 409 if a workspace of the task is still open or running processes.
+pub async fn move_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<MoveTaskRequest>,
+) -> Result<ResponseJson<ApiResponse<MoveTaskResponse>>, ApiError> {
+    let (response, workspace_count) =
+        move_task_to_project(&deployment.db().pool, &task, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_moved",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "from_project_id": task.project_id.to_string(),
+                "to_project_id": payload.project_id.to_string(),
+                "include_workspaces": payload.include_workspaces,
+                "workspace_count": workspace_count,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(response)))
+}
+
+/// Move `task` as `payload` asks, returning what changed on the way and how many
+/// workspaces the task had
+async fn move_task_to_project(
+    pool: &sqlx::SqlitePool,
+    task: &Task,
+    payload: &MoveTaskRequest,
+) -> Result<(MoveTaskResponse, usize), ApiError> {
+    let project_id = payload.project_id;
+    if project_id == task.project_id {
+        return Err(ApiError::BadRequest(
+            "Task is already in this project".to_string(),
+        ));
+    }
+    Project::find_by_id(pool, project_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Project {project_id} not found")))?;
+
+    let workspaces = Workspace::fetch_all(pool, Some(task.id)).await?;
+    for workspace in &workspaces {
+        if workspace.container_ref.is_some()
+            || ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+                pool,
+                workspace.id,
+            )
+            .await?
+        {
+            return Err(ApiError::Conflict(format!(
+                "Workspace {} of the task is still open; close it before moving the task",
+                workspace.id
+            )));
+        }
+    }
+
+    let target_labels = Task::find_labels_by_project_id(pool, project_id).await?;
+    let mut labels: Vec<String> = Vec::new();
+    let mut remapped_labels = Vec::new();
+    for label in &task.labels.0 {
+        let mapped = target_labels
+            .iter()
+            .find(|target| target.eq_ignore_ascii_case(label))
+            .unwrap_or(label);
+        if mapped != label {
+            remapped_labels.push((label.clone(), mapped.clone()));
+        }
+        if !labels.contains(mapped) {
+            labels.push(mapped.clone());
+        }
+    }
+
+    let mut cleared_parent_workspace_id = None;
+    let mut parent_workspace_id = task.parent_workspace_id;
+    if let Some(parent_id) = task.parent_workspace_id {
+        let parent_project_id = match Workspace::find_by_id(pool, parent_id).await? {
+            Some(parent) => parent.parent_task(pool).await?.map(|t| t.project_id),
+            None => None,
+        };
+        if parent_project_id != Some(project_id) {
+            cleared_parent_workspace_id = Some(parent_id);
+            parent_workspace_id = None;
+        }
+    }
+
+    let mut missing_repos = Vec::new();
+    if payload.include_workspaces {
+        let target_repo_ids: Vec<Uuid> = ProjectRepo::find_by_project_id(pool, project_id)
+            .await?
+            .into_iter()
+            .map(|project_repo| project_repo.repo_id)
+            .collect();
+        missing_repos = WorkspaceRepo::find_unique_repos_for_task(pool, task.id)
+            .await?
+            .into_iter()
+            .filter(|repo| !target_repo_ids.contains(&repo.id))
+            .map(|repo| repo.display_name)
+            .collect();
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut dropped_workspaces = Vec::new();
+    if !payload.include_workspaces {
+        for workspace in &workspaces {
+            Task::nullify_children_by_workspace_id(&mut *tx, workspace.id).await?;
+            Workspace::delete(&mut *tx, workspace.id).await?;
+            dropped_workspaces.push(workspace.id);
+        }
+    }
+    let moved =
+        Task::move_to_project(&mut *tx, task.id, project_id, &labels, parent_workspace_id).await?;
+    tx.commit().await?;
+
+    let response = MoveTaskResponse {
+        task: moved,
+        remapped_labels,
+        dropped_workspaces,
+        cleared_parent_workspace_id,
+        missing_repos,
+    };
+    Ok((response, workspaces.len()))
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateAndStartTaskRequest {
     pub task: CreateTask,
     pub executor_profile_id: ExecutorProfileId,
+    /// Repos the workspace provisions worktrees for, a subset of the project's; empty uses
+    /// all of them
+    #[serde(default)]
     pub repos: Vec<WorkspaceRepoInput>,
 }
 
@@ -162,13 +656,8 @@ pub async fn create_task_and_start(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateAndStartTaskRequest>,
 ) -> Result<ResponseJson<ApiResponse<TaskWithAttemptStatus>>, ApiError> {
-    if payload.repos.is_empty() {
-        return Err(ApiError::BadRequest(
-            "At least one repository is required".to_string(),
-        ));
-    }
-
     let pool = &deployment.db().pool;
+    let repos = select_workspace_repos(pool, payload.task.project_id, payload.repos).await?;
 
     let task_id = Uuid::new_v4();
     let task = Task::create(pool, &payload.task, task_id).await?;
@@ -190,16 +679,19 @@ pub async fn create_task_and_start(
         .await;
 
     let attempt_id = Uuid::new_v4();
+    let branch_template = ProjectSettings::find_by_project_id(pool, task.project_id)
+        .await?
+        .and_then(|settings| settings.branch_template);
     let git_branch_name = deployment
         .container()
-        .git_branch_from_workspace(&attempt_id, &task.title)
+        .git_branch_from_template(branch_template.as_deref(), &attempt_id, &task.title)
         .await;
 
     // Compute agent_working_dir based on repo count:
     // - Single repo: use repo name as working dir (agent runs in repo directory)
     // - Multiple repos: use None (agent runs in workspace root)
-    let agent_working_dir = if payload.repos.len() == 1 {
-        let repo = Repo::find_by_id(pool, payload.repos[0].repo_id)
+    let agent_working_dir = if repos.len() == 1 {
+        let repo = Repo::find_by_id(pool, repos[0].repo_id)
             .await?
             .ok_or(RepoError::NotFound)?;
         Some(repo.name)
@@ -218,15 +710,15 @@ pub async fn create_task_and_start(
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
-        .iter()
-        .map(|r| CreateWorkspaceRepo {
+    let mut workspace_repos = Vec::with_capacity(repos.len());
+    for r in &repos {
+        workspace_repos.push(CreateWorkspaceRepo {
             repo_id: r.repo_id,
-            target_branch: r.target_branch.clone(),
+            target_branch: resolve_target_branch(deployment.git(), pool, task.project_id, r)
+                .await?,
             start_from_ref: None,
-        })
-        .collect();
+        });
+    }
     WorkspaceRepo::create_many(&deployment.db().pool, workspace.id, &workspace_repos).await?;
 
     let is_attempt_running = deployment
@@ -260,12 +752,27 @@ pub async fn create_task_and_start(
     })))
 }
 
+/// Update a task. Send the task's `updated_at` as `If-Match` to get a 409 with the current
+/// task instead of overwriting a change made since it was loaded.
 pub async fn update_task(
     Extension(existing_task): Extension<Task>,
     State(deployment): State<DeploymentImpl>,
-
+    if_match: IfMatch,
     Json(payload): Json<UpdateTask>,
-) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+) -> Result<
+    (
+        StatusCode,
+        ResponseJson<ApiResponse<Task, StaleVersion<Task>>>,
+    ),
+    ApiError,
+> {
+    if let Err(stale) = if_match.check(existing_task.updated_at, &existing_task) {
+        return Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::error_with_data(stale)),
+        ));
+    }
+
     // Use existing values if not provided in update
     let title = payload.title.unwrap_or(existing_task.title);
     let description = match payload.description {
@@ -277,6 +784,17 @@ pub async fn update_task(
     let parent_workspace_id = payload
         .parent_workspace_id
         .or(existing_task.parent_workspace_id);
+    let priority = payload.priority.unwrap_or(existing_task.priority);
+    let labels = payload.labels.unwrap_or(existing_task.labels.0);
+    let assignee = payload.assignee.unwrap_or(existing_task.assignee);
+    Task::update_board_fields(
+        &deployment.db().pool,
+        existing_task.id,
+        priority,
+        &labels,
+        assignee.as_deref(),
+    )
+    .await?;
 
     let task = Task::update(
         &deployment.db().pool,
@@ -294,7 +812,7 @@ pub async fn update_task(
         TaskImage::associate_many_dedup(&deployment.db().pool, task.id, image_ids).await?;
     }
 
-    Ok(ResponseJson(ApiResponse::success(task)))
+    Ok((StatusCode::OK, ResponseJson(ApiResponse::success(task))))
 }
 
 pub async fn delete_task(
@@ -778,6 +1296,15 @@ pub async fn get_ralph_story_commits(
     })))
 }
 
+/// Every status change of the task, oldest first
+pub async fn get_task_status_history(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskStatusTransition>>>, ApiError> {
+    let history = TaskStatusTransition::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(history)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     // Ralph-specific routes under /{task_id}/ralph
     let ralph_router = Router::new()
@@ -793,6 +1320,9 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let task_id_router = Router::new()
         .route("/", get(get_task))
+        .route("/status-history", get(get_task_status_history))
+        .route("/duplicate", post(duplicate_task))
+        .route("/move", post(move_task))
         .merge(task_actions_router)
         .layer(from_fn_with_state(deployment.clone(), load_task_middleware));
 
@@ -800,8 +1330,328 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/", get(get_tasks).post(create_task))
         .route("/stream/ws", get(stream_tasks_ws))
         .route("/create-and-start", post(create_task_and_start))
+        .route(
+            "/from-template/{template_id}",
+            post(create_task_from_template),
+        )
         .nest("/{task_id}", task_id_router);
 
     // mount under /projects/:project_id/tasks
     Router::new().nest("/tasks", inner)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{seed_project, seed_task, seed_workspace, test_pool};
+
+    fn lanes(groups: &[TaskGroup]) -> Vec<(Option<&str>, Vec<&str>)> {
+        groups
+            .iter()
+            .map(|group| {
+                assert_eq!(group.count, group.tasks.len());
+                (
+                    group.key.as_deref(),
+                    group.tasks.iter().map(|t| t.title.as_str()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    async fn tasks_by_title(
+        pool: &sqlx::SqlitePool,
+        project_id: Uuid,
+    ) -> Vec<TaskWithAttemptStatus> {
+        let mut tasks = Task::find_by_project_id_with_attempt_status(pool, project_id)
+            .await
+            .unwrap();
+        tasks.sort_by(|a, b| a.title.cmp(&b.title));
+        tasks
+    }
+
+    #[tokio::test]
+    async fn tasks_are_grouped_into_swimlanes() {
+        let pool = test_pool().await;
+        let (project, _) = seed_project(&pool, &[]).await;
+        for (title, priority, labels, assignee) in [
+            (
+                "Login",
+                Some(TaskPriority::Low),
+                &["auth", "ui"][..],
+                Some("ana"),
+            ),
+            ("Crash", Some(TaskPriority::Urgent), &["bug"][..], None),
+            ("Docs", None, &[][..], Some("ana")),
+        ] {
+            let task = seed_task(&pool, project.id, title).await;
+            let labels: Vec<String> = labels.iter().map(|l| l.to_string()).collect();
+            Task::update_board_fields(&pool, task.id, priority, &labels, assignee)
+                .await
+                .unwrap();
+        }
+
+        let tasks = tasks_by_title(&pool, project.id).await;
+
+        assert_eq!(
+            lanes(&group_tasks(tasks.clone(), |task| task.labels.0.clone())),
+            vec![
+                (Some("auth"), vec!["Login"]),
+                (Some("bug"), vec!["Crash"]),
+                (Some("ui"), vec!["Login"]),
+                (None, vec!["Docs"]),
+            ]
+        );
+        assert_eq!(
+            lanes(&group_tasks(tasks.clone(), |task| {
+                task.assignee.clone().into_iter().collect()
+            })),
+            vec![(Some("ana"), vec!["Docs", "Login"]), (None, vec!["Crash"])]
+        );
+        assert_eq!(
+            lanes(&group_by_priority(tasks)),
+            vec![
+                (Some("urgent"), vec!["Crash"]),
+                (Some("low"), vec!["Login"]),
+                (None, vec!["Docs"]),
+            ]
+        );
+    }
+
+    fn query(project_id: Uuid) -> TaskQuery {
+        TaskQuery {
+            project_id,
+            group_by: None,
+            status: None,
+            label: None,
+            assignee: None,
+            text: None,
+            has_open_workspace: None,
+            updated_since: None,
+            sort: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn task_filters_and_sort_keys_apply_in_sql() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        for (title, status, priority, labels) in [
+            ("Fix 100% CPU", TaskStatus::InProgress, None, &["bug"][..]),
+            (
+                "Add login",
+                TaskStatus::Todo,
+                Some(TaskPriority::High),
+                &["auth"][..],
+            ),
+            (
+                "Add logout",
+                TaskStatus::Todo,
+                Some(TaskPriority::Low),
+                &["auth"][..],
+            ),
+            ("Write docs", TaskStatus::Done, None, &[][..]),
+        ] {
+            let task = seed_task(&pool, project.id, title).await;
+            Task::update_status(&pool, task.id, status).await.unwrap();
+            let labels: Vec<String> = labels.iter().map(|l| l.to_string()).collect();
+            Task::update_board_fields(&pool, task.id, priority, &labels, None)
+                .await
+                .unwrap();
+            if title == "Add logout" {
+                seed_workspace(&pool, task.id, "vk/logout", &[&repos[0]]).await;
+            }
+        }
+        let titles = |query: TaskQuery| {
+            let pool = pool.clone();
+            async move {
+                let filter = query.filter().unwrap();
+                let tasks =
+                    Task::find_filtered_with_attempt_status(&pool, project.id, &filter, None, 0)
+                        .await
+                        .unwrap();
+                let count = Task::count_filtered(&pool, project.id, &filter)
+                    .await
+                    .unwrap();
+                assert_eq!(count as usize, tasks.len());
+                tasks.into_iter().map(|t| t.task.title).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            titles(TaskQuery {
+                status: Some("todo, inprogress".to_string()),
+                sort: Some("-priority,title".to_string()),
+                ..query(project.id)
+            })
+            .await,
+            vec!["Add login", "Add logout", "Fix 100% CPU"]
+        );
+        assert_eq!(
+            titles(TaskQuery {
+                label: Some("bug,docs".to_string()),
+                ..query(project.id)
+            })
+            .await,
+            vec!["Fix 100% CPU"]
+        );
+        assert_eq!(
+            titles(TaskQuery {
+                text: Some("0% c".to_string()),
+                ..query(project.id)
+            })
+            .await,
+            vec!["Fix 100% CPU"]
+        );
+        assert!(
+            titles(TaskQuery {
+                text: Some("login%".to_string()),
+                ..query(project.id)
+            })
+            .await
+            .is_empty()
+        );
+        assert_eq!(
+            titles(TaskQuery {
+                has_open_workspace: Some(true),
+                ..query(project.id)
+            })
+            .await,
+            vec!["Add logout"]
+        );
+    }
+
+    #[test]
+    fn unknown_statuses_and_sort_keys_are_rejected() {
+        let project_id = Uuid::new_v4();
+        assert!(matches!(
+            TaskQuery {
+                status: Some("todo,blocked".to_string()),
+                ..query(project_id)
+            }
+            .filter(),
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(matches!(
+            TaskQuery {
+                sort: Some("-owner".to_string()),
+                ..query(project_id)
+            }
+            .filter(),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn duplicates_are_fresh_to_dos_in_the_chosen_project() {
+        let pool = test_pool().await;
+        let (project, _) = seed_project(&pool, &[]).await;
+        let (other, _) = seed_project(&pool, &[]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        Task::update_status(&pool, task.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        let labels = vec!["auth".to_string()];
+        Task::update_board_fields(
+            &pool,
+            task.id,
+            Some(TaskPriority::High),
+            &labels,
+            Some("ana"),
+        )
+        .await
+        .unwrap();
+        let task = Task::find_by_id(&pool, task.id).await.unwrap().unwrap();
+
+        for project_id in [project.id, other.id] {
+            let (copy, image_ids) = copy_task(&pool, &task, project_id).await.unwrap();
+            assert_ne!(copy.id, task.id);
+            assert_eq!(copy.project_id, project_id);
+            assert_eq!(copy.title, "Add login");
+            assert_eq!(copy.status, TaskStatus::Todo);
+            assert_eq!(copy.priority, Some(TaskPriority::High));
+            assert_eq!(copy.labels.0, labels);
+            assert_eq!(copy.assignee.as_deref(), Some("ana"));
+            assert!(image_ids.is_empty());
+        }
+
+        assert!(matches!(
+            copy_task(&pool, &task, Uuid::new_v4()).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn moved_tasks_adopt_the_target_labels_and_report_what_they_leave_behind() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let (target, _) = seed_project(&pool, &["web"]).await;
+        let existing = seed_task(&pool, target.id, "Fix footer").await;
+        let target_labels = vec!["Bug".to_string()];
+        Task::update_board_fields(&pool, existing.id, None, &target_labels, None)
+            .await
+            .unwrap();
+        let labelled = |title: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let task = seed_task(&pool, project.id, title).await;
+                let labels = vec!["bug".to_string(), "ui".to_string()];
+                Task::update_board_fields(&pool, task.id, None, &labels, None)
+                    .await
+                    .unwrap();
+                Task::find_by_id(&pool, task.id).await.unwrap().unwrap()
+            }
+        };
+        let request = |project_id, include_workspaces| MoveTaskRequest {
+            project_id,
+            include_workspaces,
+        };
+
+        let kept = labelled("Add login").await;
+        seed_workspace(&pool, kept.id, "login", &[&repos[0]]).await;
+        assert!(matches!(
+            move_task_to_project(&pool, &kept, &request(project.id, true)).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(matches!(
+            move_task_to_project(&pool, &kept, &request(Uuid::new_v4(), true)).await,
+            Err(ApiError::NotFound(_))
+        ));
+        let (moved, workspace_count) =
+            move_task_to_project(&pool, &kept, &request(target.id, true))
+                .await
+                .unwrap();
+        assert_eq!(workspace_count, 1);
+        assert_eq!(moved.task.project_id, target.id);
+        assert_eq!(moved.task.labels.0, vec!["Bug", "ui"]);
+        assert_eq!(
+            moved.remapped_labels,
+            vec![("bug".to_string(), "Bug".to_string())]
+        );
+        assert_eq!(moved.missing_repos, vec!["api"]);
+        assert!(moved.dropped_workspaces.is_empty());
+
+        let dropped = labelled("Add logout").await;
+        let workspace = seed_workspace(&pool, dropped.id, "logout", &[&repos[0]]).await;
+        let (moved, _) = move_task_to_project(&pool, &dropped, &request(target.id, false))
+            .await
+            .unwrap();
+        assert_eq!(moved.dropped_workspaces, vec![workspace.id]);
+        assert!(moved.missing_repos.is_empty());
+        assert!(
+            Workspace::find_by_id(&pool, workspace.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let open = labelled("Add signup").await;
+        let workspace = seed_workspace(&pool, open.id, "signup", &[&repos[0]]).await;
+        Workspace::update_container_ref(&pool, workspace.id, "/tmp/signup")
+            .await
+            .unwrap();
+        assert!(matches!(
+            move_task_to_project(&pool, &open, &request(target.id, true)).await,
+            Err(ApiError::Conflict(_))
+        ));
+    }
+}
@@ -6,14 +6,22 @@ use axum::{
         Query, State,
         ws::{Message, WebSocket, WebSocketUpgrade},
     },
-    response::IntoResponse,
+    response::{IntoResponse, Json as ResponseJson},
     routing::get,
 };
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
-use db::models::{workspace::Workspace, workspace_repo::WorkspaceRepo};
+use db::models::{
+    terminal_session::{CreateTerminalSession, TerminalSession},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
 use deployment::Deployment;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use utils::{
+    response::ApiResponse,
+    shell::{get_interactive_shell, resolve_executable_path},
+};
 use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError};
@@ -21,6 +29,11 @@ use crate::{DeploymentImpl, error::ApiError};
 #[derive(Debug, Deserialize)]
 pub struct TerminalQuery {
     pub workspace_id: Uuid,
+    /// Repo whose worktree the shell starts in. Defaults to the only repo of
+    /// single-repo workspaces, otherwise the workspace directory.
+    pub repo_id: Option<Uuid>,
+    /// Shell to run, as a name on PATH or an absolute path. Defaults to the user's shell.
+    pub shell: Option<String>,
     #[serde(default = "default_cols")]
     pub cols: u16,
     #[serde(default = "default_rows")]
@@ -58,11 +71,50 @@ pub async fn terminal_ws(
         .await?
         .ok_or_else(|| ApiError::BadRequest("Attempt not found".to_string()))?;
 
+    let (working_dir, repo_id) =
+        resolve_working_dir(&deployment.db().pool, &attempt, query.repo_id).await?;
+
+    let shell = match query.shell.as_deref() {
+        Some(shell) => resolve_executable_path(shell)
+            .await
+            .ok_or_else(|| ApiError::BadRequest(format!("Shell '{shell}' not found")))?,
+        None => get_interactive_shell().await,
+    };
+
+    let audit = CreateTerminalSession {
+        workspace_id: attempt.id,
+        repo_id,
+        shell: shell.to_string_lossy().into_owned(),
+        working_dir: working_dir.to_string_lossy().into_owned(),
+    };
+
+    Ok(ws.on_upgrade(move |socket| {
+        handle_terminal_ws(
+            socket,
+            deployment,
+            working_dir,
+            shell,
+            audit,
+            query.cols,
+            query.rows,
+        )
+    }))
+}
+
+/// Directory a workspace terminal starts in, and the repo it belongs to: the requested
+/// repo's worktree, else the only repo's worktree of single-repo workspaces, else the
+/// workspace directory
+async fn resolve_working_dir(
+    pool: &sqlx::SqlitePool,
+    attempt: &Workspace,
+    requested_repo_id: Option<Uuid>,
+) -> Result<(PathBuf, Option<Uuid>), ApiError> {
     let container_ref = attempt
         .container_ref
+        .as_deref()
         .ok_or_else(|| ApiError::BadRequest("Attempt has no workspace directory".to_string()))?;
 
-    let base_dir = PathBuf::from(&container_ref);
+    let base_dir = PathBuf::from(container_ref);
     if !base_dir.exists() {
         return Err(ApiError::BadRequest(
             "Workspace directory does not exist".to_string(),
@@ -70,38 +122,58 @@ pub async fn terminal_ws(
     }
 
     let mut working_dir = base_dir.clone();
-    match WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, query.workspace_id).await {
-        Ok(repos) if repos.len() == 1 => {
-            let repo_dir = base_dir.join(&repos[0].name);
-            if repo_dir.exists() {
-                working_dir = repo_dir;
-            }
+    let mut repo_id = None;
+    if let Some(requested) = requested_repo_id {
+        let repo = WorkspaceRepo::find_repos_for_workspace(pool, attempt.id)
+            .await?
+            .into_iter()
+            .find(|repo| repo.id == requested)
+            .ok_or_else(|| {
+                ApiError::BadRequest("Repo is not part of this workspace".to_string())
+            })?;
+        working_dir = base_dir.join(&repo.name);
+        if !working_dir.exists() {
+            return Err(ApiError::BadRequest(format!(
+                "Worktree for repo '{}' does not exist",
+                repo.name
+            )));
         }
-        Ok(_) => {}
-        Err(e) => {
-            tracing::warn!(
-                "Failed to resolve repos for workspace {}: {}",
-                attempt.id,
-                e
-            );
+        repo_id = Some(repo.id);
+    } else {
+        match WorkspaceRepo::find_repos_for_workspace(pool, attempt.id).await {
+            Ok(repos) if repos.len() == 1 => {
+                let repo_dir = base_dir.join(&repos[0].name);
+                if repo_dir.exists() {
+                    working_dir = repo_dir;
+                    repo_id = Some(repos[0].id);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to resolve repos for workspace {}: {}",
+                    attempt.id,
+                    e
+                );
+            }
         }
     }
 
-    Ok(ws.on_upgrade(move |socket| {
-        handle_terminal_ws(socket, deployment, working_dir, query.cols, query.rows)
-    }))
+    Ok((working_dir, repo_id))
 }
 
 async fn handle_terminal_ws(
     socket: WebSocket,
     deployment: DeploymentImpl,
     working_dir: PathBuf,
+    shell: PathBuf,
+    audit: CreateTerminalSession,
     cols: u16,
     rows: u16,
 ) {
     let (session_id, mut output_rx) = match deployment
         .pty()
-        .create_session(working_dir, cols, rows)
+        .create_session(working_dir, Some(shell), cols, rows)
         .await
     {
         Ok(result) => result,
@@ -112,6 +184,11 @@ async fn handle_terminal_ws(
         }
     };
 
+    let pool = &deployment.db().pool;
+    if let Err(e) = TerminalSession::create(pool, &audit, session_id).await {
+        tracing::error!("Failed to record terminal session {}: {}", session_id, e);
+    }
+
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
     let pty_service = deployment.pty().clone();
@@ -156,6 +233,29 @@ async fn handle_terminal_ws(
 
     let _ = deployment.pty().close_session(session_id).await;
     output_task.abort();
+
+    if let Err(e) = TerminalSession::mark_ended(pool, session_id).await {
+        tracing::error!(
+            "Failed to record end of terminal session {}: {}",
+            session_id,
+            e
+        );
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TerminalSessionsQuery {
+    pub workspace_id: Uuid,
+}
+
+/// Audit log of terminals opened in a workspace, most recent first
+pub async fn list_terminal_sessions(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TerminalSessionsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TerminalSession>>>, ApiError> {
+    let sessions =
+        TerminalSession::find_by_workspace_id(&deployment.db().pool, query.workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(sessions)))
 }
 
 async fn send_error(mut socket: WebSocket, message: &str) -> Result<(), axum::Error> {
@@ -169,5 +269,105 @@ async fn send_error(mut socket: WebSocket, message: &str) -> Result<(), axum::Er
 }
 
 pub fn router() -> Router<DeploymentImpl> {
-    Router::new().route("/terminal/ws", get(terminal_ws))
+    Router::new()
+        .route("/terminal/ws", get(terminal_ws))
+        .route("/terminal/sessions", get(list_terminal_sessions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ScratchDir, seed_project, seed_task, seed_workspace, test_pool};
+
+    #[tokio::test]
+    async fn terminals_start_in_the_chosen_or_only_worktree() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api", "web", "docs"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let dir = ScratchDir::new();
+        for name in ["api", "web"] {
+            std::fs::create_dir_all(dir.0.join(name)).unwrap();
+        }
+        let open = |workspace: Workspace| Workspace {
+            container_ref: Some(dir.0.to_string_lossy().into_owned()),
+            ..workspace
+        };
+        let (api, web, docs) = (&repos[0], &repos[1], &repos[2]);
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[api, web, docs]).await;
+
+        assert!(matches!(
+            resolve_working_dir(&pool, &workspace, None).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        let workspace = open(workspace);
+        assert_eq!(
+            resolve_working_dir(&pool, &workspace, None).await.unwrap(),
+            (dir.0.clone(), None)
+        );
+        assert_eq!(
+            resolve_working_dir(&pool, &workspace, Some(web.id))
+                .await
+                .unwrap(),
+            (dir.0.join("web"), Some(web.id))
+        );
+        // The docs worktree was never created
+        assert!(matches!(
+            resolve_working_dir(&pool, &workspace, Some(docs.id)).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        let (_, strangers) = seed_project(&pool, &["ops"]).await;
+        assert!(matches!(
+            resolve_working_dir(&pool, &workspace, Some(strangers[0].id)).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let single = open(seed_workspace(&pool, task.id, "vk/logout", &[api]).await);
+        assert_eq!(
+            resolve_working_dir(&pool, &single, None).await.unwrap(),
+            (dir.0.join("api"), Some(api.id))
+        );
+    }
+
+    #[tokio::test]
+    async fn terminal_sessions_are_audited_per_workspace() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[&repos[0]]).await;
+        let other = seed_workspace(&pool, task.id, "vk/logout", &[&repos[0]]).await;
+        let audit = |workspace_id: Uuid, repo_id: Option<Uuid>| CreateTerminalSession {
+            workspace_id,
+            repo_id,
+            shell: "/bin/sh".to_string(),
+            working_dir: "/tmp".to_string(),
+        };
+
+        let first = TerminalSession::create(&pool, &audit(workspace.id, None), Uuid::new_v4())
+            .await
+            .unwrap();
+        TerminalSession::mark_ended(&pool, first.id).await.unwrap();
+        // Sessions are ordered by their millisecond timestamps
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let second = TerminalSession::create(
+            &pool,
+            &audit(workspace.id, Some(repos[0].id)),
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        TerminalSession::create(&pool, &audit(other.id, None), Uuid::new_v4())
+            .await
+            .unwrap();
+
+        let sessions = TerminalSession::find_by_workspace_id(&pool, workspace.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            sessions.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![second.id, first.id]
+        );
+        assert_eq!(sessions[0].repo_id, Some(repos[0].id));
+        assert!(sessions[0].ended_at.is_none());
+        assert!(sessions[1].ended_at.is_some());
+    }
 }
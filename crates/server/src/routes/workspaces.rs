@@ -1,29 +1,114 @@
-use std::path::PathBuf;
+pub mod conflicts;
+pub mod index;
+pub mod plans;
+pub mod queue;
+pub mod review_comments;
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    path::{Component, PathBuf},
+    sync::LazyLock,
+    time::Duration,
+};
 
 use axum::{
-    Json, Router,
-    extract::{Path, State},
-    response::Json as ResponseJson,
-    routing::{get, post},
+    BoxError, Json, Router,
+    body::Body,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, Request, State},
+    http::{HeaderMap, StatusCode, Uri, header},
+    response::{
+        IntoResponse, Json as ResponseJson, Response, Sse,
+        sse::{Event, KeepAlive},
+    },
+    routing::{any, delete, get, post, put},
 };
+use chrono::{DateTime, Utc};
 use db::models::{
+    ai_review::{AiReview, AiReviewStatus},
     coding_agent_turn::CodingAgentTurn,
-    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
-    merge::Merge,
+    execution_process::{
+        ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+        ExecutionProcessSummary,
+    },
+    interrupted_execution::InterruptedExecution,
+    merge::{Merge, MergeStatus},
+    merge_conflict::MergeConflict,
+    project::Project,
+    project_agent_pipeline::ProjectAgentPipeline,
+    project_ai_review_policy::ProjectAiReviewPolicy,
+    project_repo::ProjectRepo,
+    project_script::ProjectScript,
+    project_settings::{MergeStrategy, ProjectSettings},
+    repo::Repo,
+    review_comment::ReviewComment,
+    session::Session,
     task::{Task, TaskStatus},
     workspace::Workspace,
-    workspace_repo::WorkspaceRepo,
+    workspace_event::{WorkspaceEvent, WorkspaceEventKind},
+    workspace_merge_description::{SetWorkspaceMergeDescription, WorkspaceMergeDescription},
+    workspace_pipeline_stage::{PipelineStageStatus, WorkspacePipelineStage},
+    workspace_repo::{CreateWorkspaceRepo, RepoWithTargetBranch, WorkspaceRepo},
+    workspace_review::{CreateWorkspaceReview, ReviewDecision, WorkspaceReview},
+    workspace_stack::WorkspaceStack,
+    workspace_test_run::WorkspaceTestRun,
 };
 use deployment::Deployment;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType,
+        review::ReviewRequest,
+        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    },
+    profile::ExecutorProfileId,
+};
+use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
-use git::DiffTarget;
-use services::services::workspace_manager::WorkspaceManager;
+use git::{BlameLine, Commit, CommitInfo, DiffTarget, FileHistoryEntry};
+use services::services::{
+    agent_resume::AgentResumeService,
+    ai_review::{AiReviewFile, AiReviewService, partition_findings},
+    container::ContainerService,
+    diff_html::{DiffHtmlFile, DiffHtmlSection, DiffHtmlService},
+    env_vars::EnvVarService,
+    git_host::{GitHostError, GitHostProvider, GitHostService, blocking_checks},
+    merge_description::{MergeDescriptionRepo, MergeDescriptionService},
+    output_redaction::OutputRedactor,
+    quality_gate::{QualityGateFailure, QualityGateService, QualityGateStatus},
+    recorded_command::RecordedCommandService,
+    target_drift::{RepoTargetDrift, workspace_drift},
+    test_runner::TestRunnerService,
+    workspace_archive::WorkspaceArchiveService,
+    workspace_integrity::{WorkspaceIntegrityService, WorkspaceVerification},
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+    workspace_manager::{
+        RepoWorkspaceInput, WorkspaceError as WorkspaceManagerError, WorkspaceManager,
+    },
+    worktree_manager::{WorktreeCleanup, WorktreeManager},
+};
 use ts_rs::TS;
-use utils::diff::create_unified_diff;
+use utils::diff::{
+    DiffChangeKind, DiffChangeState, DiffFormatOptions, DiffView, SplitDiffHunk,
+    create_unified_diff_hunks, create_unified_diff_with_options, split_unified_diff,
+};
+use utils::log_msg::LogMsg;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::IfMatch,
+    pagination::{Listing, PageQuery},
+    routes::{
+        execution_processes::{ExecutionProcessListQuery, list_execution_process_summaries},
+        task_attempts::{
+            WorkspaceRepoInput,
+            pr::{CreatePrApiRequest, PrError, open_workspace_pr},
+            resolve_target_branch, select_workspace_repos,
+        },
+    },
+};
 
 /// Response for workspace status endpoint
 #[derive(Debug, Serialize, TS)]
@@ -37,6 +122,15 @@ pub struct WorkspaceStatusResponse {
     pub lines_added: Option<usize>,
     /// Total lines removed across all files
     pub lines_removed: Option<usize>,
+    /// Most recent test run for this workspace, if tests have been run
+    pub latest_test_run: Option<WorkspaceTestRun>,
+    /// Stages of the workspace's agent pipeline run, empty if it has not run one
+    pub pipeline: Vec<WorkspacePipelineStage>,
+    /// The coding agent run interrupted by a server restart, while it can be resumed
+    pub interrupted: Option<InterruptedExecution>,
+    /// How far each repo's target branch has moved past its merge base with the workspace
+    /// branch; empty when the workspace is closed
+    pub target_drift: Vec<RepoTargetDrift>,
 }
 
 /// Response for workspace transcript endpoint
@@ -52,7 +146,7 @@ pub struct WorkspaceTranscriptResponse {
 }
 
 /// A single file's diff information
-#[derive(Debug, Serialize, TS)]
+#[derive(Debug, Clone, Serialize, TS)]
 pub struct FileDiff {
     /// File path (new path for added/modified, old path for deleted)
     pub path: String,
@@ -62,21 +156,208 @@ pub struct FileDiff {
     pub deletions: usize,
     /// The unified diff content
     pub diff_content: String,
+    /// Whether the change is committed, staged, unstaged or untracked in the worktree;
+    /// absent for diffs between commits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<DiffChangeState>,
+    /// Previous path of a renamed or copied file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// How similar a renamed or copied file is to its previous path, in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u8>,
+    /// The diff's lines aligned into old/new rows, with `view=split`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split: Option<Vec<SplitDiffHunk>>,
+    /// Review comments on the file; only in workspace diffs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_comments: Option<FileReviewComments>,
+}
+
+/// Number of review comments on a file of a workspace diff
+#[derive(Debug, Clone, Copy, Default, Serialize, TS)]
+pub struct FileReviewComments {
+    pub total: usize,
+    pub unresolved: usize,
+}
+
+/// Query parameters for the workspace diff endpoint
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceDiffQuery {
+    /// Only diff this repo, by name or id
+    pub repo: Option<String>,
+    /// Also return the files of all repos in one list, as before they were grouped by repo
+    #[serde(default)]
+    pub flat: bool,
+    /// Report files git does not track yet (default true)
+    #[serde(default = "default_include_untracked")]
+    pub include_untracked: bool,
+}
+
+fn default_include_untracked() -> bool {
+    true
+}
+
+/// Changed files of one repo in a workspace diff
+#[derive(Debug, Serialize, TS)]
+pub struct RepoFileDiffs {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub files_changed: usize,
+    /// Lines added across the repo's files
+    pub additions: usize,
+    /// Lines deleted across the repo's files
+    pub deletions: usize,
+    pub files: Vec<FileDiff>,
+}
+
+impl RepoFileDiffs {
+    /// Total up the changed `files` of one repo
+    fn new(repo_id: Uuid, repo_name: String, files: Vec<FileDiff>) -> Self {
+        Self {
+            repo_id,
+            repo_name,
+            files_changed: files.len(),
+            additions: files.iter().map(|f| f.additions).sum(),
+            deletions: files.iter().map(|f| f.deletions).sum(),
+            files,
+        }
+    }
 }
 
 /// Response for workspace diff endpoint
 #[derive(Debug, Serialize, TS)]
 pub struct WorkspaceDiffResponse {
     pub workspace_id: String,
-    /// List of file diffs
-    pub files: Vec<FileDiff>,
+    /// File diffs grouped by repo
+    pub repos: Vec<RepoFileDiffs>,
+    /// File diffs of all repos in one list; only included with `flat=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<FileDiff>>,
 }
 
 /// Request body for closing a workspace
 #[derive(Debug, Deserialize)]
 pub struct CloseWorkspaceRequest {
-    /// Strategy for closing: "merge" or "discard"
-    pub strategy: String,
+    /// Strategy for closing: "merge", "discard" or "pr"; unset uses the project's default
+    #[serde(default)]
+    pub strategy: Option<String>,
+    /// Strategies of individual repos; repos not listed use `strategy`
+    #[serde(default)]
+    pub repos: Vec<RepoCloseRequest>,
+    /// What to do with workspaces stacked on this one; required when it has any
+    #[serde(default)]
+    pub stacked_children: Option<StackedChildrenStrategy>,
+}
+
+/// How one repo of a closing workspace ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoCloseStrategy {
+    /// Merge the workspace branch into the repo's target branch
+    Merge,
+    /// Remove the repo's worktree and workspace branch without merging
+    Discard,
+    /// Push the workspace branch and open a PR into the target branch; the branch is kept
+    Pr,
+}
+
+impl From<MergeStrategy> for RepoCloseStrategy {
+    fn from(strategy: MergeStrategy) -> Self {
+        match strategy {
+            MergeStrategy::Merge => RepoCloseStrategy::Merge,
+            MergeStrategy::Discard => RepoCloseStrategy::Discard,
+        }
+    }
+}
+
+/// Close strategy of one repo of the workspace
+#[derive(Debug, Deserialize, TS)]
+pub struct RepoCloseRequest {
+    pub repo_id: Uuid,
+    pub strategy: RepoCloseStrategy,
+    /// Title of the PR opened with the `pr` strategy; defaults to the task's title
+    #[serde(default)]
+    #[ts(optional)]
+    pub pr_title: Option<String>,
+    /// Open the PR as a draft (`pr` strategy only)
+    #[serde(default)]
+    #[ts(optional)]
+    pub draft: Option<bool>,
+}
+
+/// How workspaces stacked on a closing workspace follow it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum StackedChildrenStrategy {
+    /// Point the children at the closing workspace's target branches
+    Retarget,
+    /// Retarget, then rebase each child's own commits onto the new target
+    Rebase,
+}
+
+/// Query parameters for closing a workspace
+#[derive(Debug, Deserialize)]
+pub struct CloseWorkspaceQuery {
+    /// Skip the CI status check for repos that require passing CI
+    #[serde(default)]
+    pub force: bool,
+    /// Merge without the agent review the project's review policy requires
+    #[serde(default)]
+    pub skip_ai_review: bool,
+}
+
+/// Repo scripts that can be run by name through the exec endpoint
+#[derive(Debug, Clone, Copy, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoScriptKind {
+    Setup,
+    Cleanup,
+    Test,
+}
+
+impl RepoScriptKind {
+    fn command_for(self, repo: &Repo) -> Option<String> {
+        let script = match self {
+            RepoScriptKind::Setup => &repo.setup_script,
+            RepoScriptKind::Cleanup => &repo.cleanup_script,
+            RepoScriptKind::Test => &repo.test_script,
+        };
+        script.clone().filter(|s| !s.trim().is_empty())
+    }
+}
+
+/// Request body for running a command in a workspace worktree
+#[derive(Debug, Deserialize, TS)]
+pub struct ExecWorkspaceRequest {
+    /// Repo whose worktree the command runs in
+    pub repo_id: Uuid,
+    /// Shell command to run; give either this or `script`
+    pub command: Option<String>,
+    /// Run one of the repo's configured scripts instead of `command`
+    pub script: Option<RepoScriptKind>,
+    /// Wait for the command to exit and return its output (default true). Otherwise
+    /// return straight away and follow the execution process raw logs instead.
+    #[serde(default = "default_exec_wait")]
+    pub wait: bool,
+}
+
+fn default_exec_wait() -> bool {
+    true
+}
+
+/// Response for workspace exec endpoint
+#[derive(Debug, Serialize, TS)]
+pub struct ExecWorkspaceResponse {
+    pub workspace_id: String,
+    pub execution_process_id: String,
+    /// Still "running" if the request didn't wait or the wait timed out
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+    /// Captured stdout (only present when waiting)
+    pub stdout: Option<String>,
+    /// Captured stderr (only present when waiting)
+    pub stderr: Option<String>,
 }
 
 /// Response for workspace close endpoint
@@ -89,6 +370,387 @@ pub struct CloseWorkspaceResponse {
     pub message: String,
     /// Merge commit SHA (only present for merge strategy)
     pub merge_commit_sha: Option<String>,
+    /// Most merges into the same repo this close had to wait behind in the merge queue
+    /// (only present for merge strategy)
+    pub queue_position: Option<usize>,
+    /// What happened to each repo of the workspace
+    pub repos: Vec<RepoCloseResult>,
+    /// Workspaces that were stacked on this one and have been retargeted
+    pub stacked_children: Vec<StackedChildUpdate>,
+    /// Unresolved findings of the project's agent review that were below its blocking
+    /// severity
+    pub review_warnings: Vec<ReviewComment>,
+}
+
+/// Outcome of closing one repo of a workspace
+#[derive(Debug, Serialize, TS)]
+pub struct RepoCloseResult {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub target_branch: String,
+    pub strategy: RepoCloseStrategy,
+    /// Merge commit on the target branch (only present for the merge strategy)
+    pub merge_commit_sha: Option<String>,
+    /// Merges into the repo this close waited behind (only present for the merge strategy)
+    pub queue_position: Option<usize>,
+    /// URL of the PR opened from the workspace branch (only present for the pr strategy)
+    pub pr_url: Option<String>,
+}
+
+/// Outcome of moving a stacked workspace off its closed parent
+#[derive(Debug, Serialize, TS)]
+pub struct StackedChildUpdate {
+    pub workspace_id: Uuid,
+    /// Whether the child's branch was rebased onto the new target
+    pub rebased: bool,
+    /// Why the rebase did not complete, e.g. conflicts left in the child worktree
+    pub error: Option<String>,
+}
+
+/// Structured reasons a workspace close was refused
+#[derive(Debug, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum CloseWorkspaceError {
+    /// A project quality gate exited non-zero; its output is in the referenced execution process
+    QualityGateFailed { failure: QualityGateFailure },
+    /// The project's quality gates have not run on the current HEAD commits, so they have been
+    /// started in the background; close again once these execution processes have finished
+    QualityGatesStarted { execution_process_ids: Vec<Uuid> },
+    /// Quality gates started by an earlier close are still running
+    QualityGatesRunning { execution_process_ids: Vec<Uuid> },
+    /// CI on the workspace branch head has not passed for a repo that requires it
+    CiChecksNotPassing {
+        repo_name: String,
+        commit_sha: String,
+        /// Names of checks that are pending or failed; empty if no checks were reported
+        blocking_checks: Vec<String>,
+    },
+    /// CI status could not be fetched for a repo that requires passing CI
+    CiChecksUnavailable { repo_name: String, message: String },
+    /// The workspace branch head is not on the remote, so CI has not run on it; push it first
+    CiBranchNotPushed {
+        repo_name: String,
+        commit_sha: String,
+        /// Where the branch is on the remote; unset if it was never pushed
+        remote_commit_sha: Option<String>,
+    },
+    /// The workspace changed since the version sent in `If-Match`
+    StaleVersion {
+        #[ts(type = "string")]
+        expected_version: DateTime<Utc>,
+        current: Workspace,
+    },
+    /// Other workspaces are stacked on this one; retry with `stacked_children` set to
+    /// retarget or rebase them
+    HasStackedChildren { children: Vec<Workspace> },
+    /// The project requires an approving review and the latest review, if any, is not one
+    ApprovalRequired {
+        latest_review: Option<WorkspaceReview>,
+    },
+    /// The project requires an agent review and none is current, so one has been started;
+    /// close again once it has finished
+    AiReviewStarted { review: AiReview },
+    /// The agent review reported unresolved findings at or above the project's blocking
+    /// severity
+    AiReviewBlocked {
+        review: AiReview,
+        findings: Vec<ReviewComment>,
+    },
+    /// A PR could not be opened for a repo closed with the `pr` strategy; no repo was merged
+    PrFailed { repo_name: String, error: PrError },
+}
+
+/// Check CI on the workspace branch head for every repo with `require_ci_pass` set, which
+/// must have been pushed. Repos without a remote, or on a host whose CI status is not read
+/// (only GitHub's is), are skipped.
+async fn check_required_ci(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repos: &[Repo],
+) -> Option<CloseWorkspaceError> {
+    for repo in repos.iter().filter(|r| r.require_ci_pass) {
+        let Ok(remote) = deployment.git().get_default_remote(&repo.path) else {
+            tracing::debug!("Repo '{}' has no remote, skipping CI check", repo.name);
+            continue;
+        };
+
+        let commit_sha = match deployment
+            .git()
+            .get_branch_oid(&repo.path, &workspace.branch)
+        {
+            Ok(sha) => sha,
+            Err(e) => {
+                return Some(CloseWorkspaceError::CiChecksUnavailable {
+                    repo_name: repo.name.clone(),
+                    message: e.to_string(),
+                });
+            }
+        };
+
+        let git_host = match GitHostService::from_url(&remote.url) {
+            Ok(host) => host,
+            Err(GitHostError::UnsupportedProvider) => {
+                tracing::debug!(
+                    "Repo '{}' is on an unsupported git host, skipping CI check",
+                    repo.name
+                );
+                continue;
+            }
+            Err(e) => {
+                return Some(CloseWorkspaceError::CiChecksUnavailable {
+                    repo_name: repo.name.clone(),
+                    message: e.to_string(),
+                });
+            }
+        };
+
+        // CI only ever runs on what was pushed
+        match deployment
+            .git()
+            .get_remote_branch_head(&repo.path, &remote.url, &workspace.branch)
+        {
+            Ok(Some(remote_sha)) if remote_sha == commit_sha => {}
+            Ok(remote_commit_sha) => {
+                return Some(CloseWorkspaceError::CiBranchNotPushed {
+                    repo_name: repo.name.clone(),
+                    commit_sha,
+                    remote_commit_sha,
+                });
+            }
+            Err(e) => {
+                return Some(CloseWorkspaceError::CiChecksUnavailable {
+                    repo_name: repo.name.clone(),
+                    message: e.to_string(),
+                });
+            }
+        }
+
+        let checks = match git_host
+            .get_commit_checks(&repo.path, &remote.url, &commit_sha)
+            .await
+        {
+            Ok(checks) => checks,
+            Err(GitHostError::UnsupportedProvider) => continue,
+            Err(e) => {
+                return Some(CloseWorkspaceError::CiChecksUnavailable {
+                    repo_name: repo.name.clone(),
+                    message: e.to_string(),
+                });
+            }
+        };
+
+        if let Some(blocking_checks) = blocking_checks(&checks) {
+            return Some(CloseWorkspaceError::CiChecksNotPassing {
+                repo_name: repo.name.clone(),
+                commit_sha,
+                blocking_checks,
+            });
+        }
+    }
+
+    None
+}
+
+/// Refuse the merge when the project requires an approving review and the workspace's
+/// latest review, if any, is not one
+async fn check_merge_approval(
+    pool: &sqlx::SqlitePool,
+    project_id: Uuid,
+    workspace_id: Uuid,
+) -> Result<Option<CloseWorkspaceError>, ApiError> {
+    let requires_approval = Project::find_by_id(pool, project_id)
+        .await?
+        .is_some_and(|project| project.require_merge_approval);
+    if !requires_approval {
+        return Ok(None);
+    }
+    let latest_review = WorkspaceReview::find_latest_by_workspace_id(pool, workspace_id).await?;
+    if latest_review
+        .as_ref()
+        .is_some_and(|review| review.decision == ReviewDecision::Approved)
+    {
+        return Ok(None);
+    }
+    Ok(Some(CloseWorkspaceError::ApprovalRequired {
+        latest_review,
+    }))
+}
+
+/// Path parameters for the dev server preview proxy
+#[derive(Debug, Deserialize)]
+pub struct PreviewPath {
+    pub id: Uuid,
+}
+
+static PREVIEW_CLIENT: LazyLock<Result<reqwest::Client, reqwest::Error>> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+});
+
+/// The request path below `/preview`, still percent-encoded so that an encoded `?`, `#` or `/`
+/// reaches the dev server as sent, with the original query
+fn preview_upstream_path(uri: &Uri) -> String {
+    let path = uri.path();
+    let below = path
+        .find("/preview")
+        .map_or("", |i| &path[i + "/preview".len()..]);
+    let query = uri.query().map(|q| format!("?{q}")).unwrap_or_default();
+    if below.is_empty() {
+        format!("/{query}")
+    } else {
+        format!("{below}{query}")
+    }
+}
+
+/// Headers that only apply to a single connection and must not be forwarded by a proxy
+fn is_hop_by_hop_header(name: &header::HeaderName) -> bool {
+    matches!(
+        name.as_str(),
+        "connection"
+            | "keep-alive"
+            | "proxy-authenticate"
+            | "proxy-authorization"
+            | "te"
+            | "trailer"
+            | "transfer-encoding"
+            | "upgrade"
+    )
+}
+
+fn forwardable_headers(headers: &HeaderMap) -> HeaderMap {
+    headers
+        .iter()
+        .filter(|(name, _)| *name != header::HOST && !is_hop_by_hop_header(name))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// Proxy a request to the workspace's running dev server on its allocated port.
+/// Returns 404 if no dev server with an allocated port is running for the workspace.
+/// Returns 502 if the dev server could not be reached or sent an invalid response, or the
+/// proxy client could not be built.
+///
+/// The request path below `/preview` is forwarded as-is, so apps that reference assets by
+/// absolute path need a base path of `/api/workspaces/{id}/preview/` to render correctly.
+pub async fn preview_workspace(
+    State(deployment): State<DeploymentImpl>,
+    Path(PreviewPath { id }): Path<PreviewPath>,
+    request: Request,
+) -> Result<Response, ApiError> {
+    let port = deployment
+        .dev_server_ports()
+        .port_for_workspace(id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("No running dev server for workspace {}", id)))?;
+
+    let client = match &*PREVIEW_CLIENT {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("Failed to build preview proxy client: {}", e);
+            return Ok((
+                StatusCode::BAD_GATEWAY,
+                format!("Preview proxy is unavailable: {e}"),
+            )
+                .into_response());
+        }
+    };
+    let url = format!(
+        "http://127.0.0.1:{port}{}",
+        preview_upstream_path(request.uri())
+    );
+
+    let (parts, body) = request.into_parts();
+    let upstream = client
+        .request(parts.method, &url)
+        .headers(forwardable_headers(&parts.headers))
+        .body(reqwest::Body::wrap_stream(body.into_data_stream()))
+        .send()
+        .await;
+
+    let upstream = match upstream {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::debug!("Preview proxy to {} failed: {}", url, e);
+            return Ok((
+                StatusCode::BAD_GATEWAY,
+                format!("Dev server on port {port} is not reachable: {e}"),
+            )
+                .into_response());
+        }
+    };
+
+    let mut response = Response::builder().status(upstream.status());
+    if let Some(headers) = response.headers_mut() {
+        *headers = forwardable_headers(upstream.headers());
+    }
+    Ok(response
+        .body(Body::from_stream(upstream.bytes_stream()))
+        .unwrap_or_else(|e| {
+            tracing::debug!("Invalid preview response from {}: {}", url, e);
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("Dev server on port {port} sent an invalid response: {e}"),
+            )
+                .into_response()
+        }))
+}
+
+const DEFAULT_DEV_SERVER_LOG_TAIL_KB: usize = 64;
+const MAX_DEV_SERVER_LOG_TAIL_KB: usize = 4096;
+
+/// Path parameters for the dev server log stream
+#[derive(Debug, Deserialize)]
+pub struct DevServerLogsPath {
+    pub id: Uuid,
+    pub process_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DevServerLogsQuery {
+    /// How many KB of recent output to replay before following live output
+    pub tail_kb: Option<usize>,
+}
+
+/// Stream stdout/stderr of a workspace dev server as server-sent events.
+/// Late subscribers first receive the last `tail_kb` KB of output (64 by default), then live
+/// output until the process exits. Returns 404 if the process is not a dev server of this
+/// workspace or has no logs.
+pub async fn stream_dev_server_logs(
+    State(deployment): State<DeploymentImpl>,
+    Path(DevServerLogsPath { id, process_id }): Path<DevServerLogsPath>,
+    Query(query): Query<DevServerLogsQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, BoxError>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let not_found = || {
+        ApiError::NotFound(format!(
+            "No dev server process {process_id} in workspace {id}"
+        ))
+    };
+
+    let process = ExecutionProcess::find_by_id(pool, process_id)
+        .await?
+        .filter(|p| p.run_reason == ExecutionProcessRunReason::DevServer)
+        .ok_or_else(not_found)?;
+    Session::find_by_id(pool, process.session_id)
+        .await?
+        .filter(|session| session.workspace_id == id)
+        .ok_or_else(not_found)?;
+
+    let tail_bytes = query
+        .tail_kb
+        .unwrap_or(DEFAULT_DEV_SERVER_LOG_TAIL_KB)
+        .min(MAX_DEV_SERVER_LOG_TAIL_KB)
+        * 1024;
+    let stream = deployment
+        .container()
+        .stream_raw_logs_tail(&process_id, tail_bytes)
+        .await
+        .ok_or_else(not_found)?;
+
+    let events = stream.map(|msg| msg.map(|m| m.to_sse_event()).map_err(BoxError::from));
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
 }
 
 /// Get workspace execution status and diff stats.
@@ -138,282 +800,3542 @@ pub async fn get_workspace_status(
         (None, None, None)
     };
 
+    let latest_test_run = WorkspaceTestRun::find_latest_by_workspace_id(pool, workspace_id).await?;
+    let pipeline = WorkspacePipelineStage::find_by_workspace_id(pool, workspace_id).await?;
+    let interrupted =
+        InterruptedExecution::find_unresumed_by_workspace_id(pool, workspace_id).await?;
+    let target_drift = if workspace.container_ref.is_some() {
+        let repos =
+            WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace_id).await?;
+        workspace_drift(deployment.git(), &workspace.branch, &repos)
+    } else {
+        Vec::new()
+    };
+
     Ok(ResponseJson(ApiResponse::success(WorkspaceStatusResponse {
         workspace_id: workspace_id.to_string(),
         status,
         files_changed,
         lines_added,
         lines_removed,
+        latest_test_run,
+        pipeline,
+        interrupted,
+        target_drift,
     })))
 }
 
-/// Get workspace transcript (prompt, summary, agent_session_id).
+/// Relink or recreate missing and broken worktrees before they are read, when enabled
+async fn repair_worktrees_if_enabled(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+) -> Result<(), ApiError> {
+    if !deployment.settings().auto_repair_worktrees() {
+        return Ok(());
+    }
+    WorkspaceIntegrityService::repair(
+        &deployment.db().pool,
+        deployment.git(),
+        deployment.container(),
+        workspace,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Events recorded for the workspace, such as worktree repairs, newest first.
 /// Returns 404 if workspace not found.
-/// Returns empty fields if no coding agent turns exist.
 #[axum::debug_handler]
-pub async fn get_workspace_transcript(
+pub async fn get_workspace_events(
     State(deployment): State<DeploymentImpl>,
     Path(workspace_id): Path<Uuid>,
-) -> Result<ResponseJson<ApiResponse<WorkspaceTranscriptResponse>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceEvent>>>, ApiError> {
     let pool = &deployment.db().pool;
 
-    // Find workspace, return 404 if not found
-    let _workspace = Workspace::find_by_id(pool, workspace_id)
+    Workspace::find_by_id(pool, workspace_id)
         .await?
         .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let events = WorkspaceEvent::find_by_workspace_id(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(events)))
+}
 
-    // Find the latest coding agent turn for this workspace
-    let coding_agent_turn = CodingAgentTurn::find_latest_by_workspace_id(pool, workspace_id).await?;
+/// Environment variables set for the workspace itself. Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_env(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<HashMap<String, String>>>, ApiError> {
+    let pool = &deployment.db().pool;
 
-    let response = match coding_agent_turn {
-        Some(turn) => WorkspaceTranscriptResponse {
-            workspace_id: workspace_id.to_string(),
-            prompt: turn.prompt,
-            summary: turn.summary,
-            agent_session_id: turn.agent_session_id,
-        },
-        None => WorkspaceTranscriptResponse {
-            workspace_id: workspace_id.to_string(),
-            prompt: None,
-            summary: None,
-            agent_session_id: None,
-        },
-    };
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let vars = EnvVarService::workspace_overrides(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(vars)))
+}
 
-    Ok(ResponseJson(ApiResponse::success(response)))
+/// Replace the workspace's environment variables, layered over the project's env sets but
+/// under the allowlisted secrets in processes started from now on; an empty map removes them. Returns 404 if workspace not
+/// found, 400 for a reserved or invalid variable name.
+#[axum::debug_handler]
+pub async fn set_workspace_env(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<HashMap<String, String>>,
+) -> Result<ResponseJson<ApiResponse<HashMap<String, String>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    EnvVarService::set_workspace_overrides(pool, workspace_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(payload)))
 }
 
-/// Get workspace file diffs with full diff content.
-/// Returns 404 if workspace not found or has no container_ref.
+/// Execution processes of the workspace across its sessions, newest first, filtered by
+/// status, run reason and start time. Returns 404 if workspace not found.
 #[axum::debug_handler]
-pub async fn get_workspace_diff(
+pub async fn get_workspace_processes(
     State(deployment): State<DeploymentImpl>,
     Path(workspace_id): Path<Uuid>,
-) -> Result<ResponseJson<ApiResponse<WorkspaceDiffResponse>>, ApiError> {
+    Query(query): Query<ExecutionProcessListQuery>,
+    Query(page): Query<PageQuery>,
+) -> Result<ResponseJson<ApiResponse<Listing<ExecutionProcessSummary>>>, ApiError> {
+    Workspace::find_by_id(&deployment.db().pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let filter = query.filter(Some(workspace_id), None)?;
+    let processes = list_execution_process_summaries(&deployment, &filter, page).await?;
+    Ok(ResponseJson(ApiResponse::success(processes)))
+}
+
+/// Reviews of the workspace, newest first; the first is its current review state.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_reviews(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceReview>>>, ApiError> {
     let pool = &deployment.db().pool;
 
-    // Find workspace, return 404 if not found
-    let workspace = Workspace::find_by_id(pool, workspace_id)
+    Workspace::find_by_id(pool, workspace_id)
         .await?
         .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let reviews = WorkspaceReview::find_by_workspace_id(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(reviews)))
+}
 
-    // Return 404 if no container_ref (workspace not active)
-    let container_ref = workspace
-        .container_ref
-        .as_ref()
-        .ok_or_else(|| ApiError::NotFound("Workspace has no active worktree".to_string()))?;
+/// Approve the workspace's changes or request changes to them.
+/// Returns 404 if workspace not found.
+/// Returns 400 if the reviewer is empty.
+#[axum::debug_handler]
+pub async fn create_workspace_review(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<CreateWorkspaceReview>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceReview>>, ApiError> {
+    let pool = &deployment.db().pool;
 
-    let workspace_repos =
-        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    if payload.reviewer.trim().is_empty() {
+        return Err(ApiError::BadRequest("Reviewer is required".to_string()));
+    }
 
-    let mut all_files: Vec<FileDiff> = Vec::new();
+    let review = WorkspaceReview::create(pool, workspace_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(review)))
+}
 
-    for repo_with_branch in workspace_repos {
-        let worktree_path = PathBuf::from(container_ref).join(&repo_with_branch.repo.name);
-        let repo_path = repo_with_branch.repo.path.clone();
+/// Request body for starting an agent review of a workspace
+#[derive(Debug, Deserialize, TS)]
+pub struct StartAiReviewRequest {
+    /// The agent to review with; defaults to the one in the project's review policy
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
 
-        // Get base commit (merge base) between workspace branch and target branch
-        let base_commit_result = tokio::task::spawn_blocking({
-            let git = deployment.git().clone();
-            let repo_path = repo_path.clone();
-            let workspace_branch = workspace.branch.clone();
-            let target_branch = repo_with_branch.target_branch.clone();
-            move || git.get_base_commit(&repo_path, &workspace_branch, &target_branch)
+/// Start a coding agent reviewing the workspace diff against its task. The findings are
+/// stored as review comments when the agent exits.
+async fn start_ai_review(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    task: &Task,
+    executor_profile_id: ExecutorProfileId,
+) -> Result<AiReview, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let repos = collect_workspace_diffs(
+        deployment,
+        workspace,
+        &WorkspaceDiffQuery {
+            repo: None,
+            flat: false,
+            include_untracked: true,
+        },
+        &DiffFormatOptions::default(),
+    )
+    .await?;
+    let files: Vec<AiReviewFile> = repos
+        .iter()
+        .flat_map(|repo| {
+            repo.files.iter().map(|file| AiReviewFile {
+                repo_name: &repo.repo_name,
+                path: &file.path,
+                diff_content: &file.diff_content,
+            })
         })
-        .await;
+        .collect();
+    let prompt = AiReviewService::build_prompt(&task.title, task.description.as_deref(), &files);
 
-        let base_commit = match base_commit_result {
-            Ok(Ok(commit)) => commit,
-            _ => continue,
-        };
+    // A fresh agent session, so the review is not biased by the conversation that made
+    // the changes
+    let session = RecordedCommandService::session_for_workspace(pool, workspace.id).await?;
+    let action = ExecutorAction::new(
+        ExecutorActionType::ReviewRequest(ReviewRequest {
+            executor_profile_id,
+            context: None,
+            prompt,
+            session_id: None,
+            working_dir: workspace.agent_working_dir.clone(),
+        }),
+        None,
+    );
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::AiReview,
+        )
+        .await?;
 
-        // Get diffs with content
-        let diffs_result = tokio::task::spawn_blocking({
-            let git = deployment.git().clone();
-            let worktree = worktree_path.clone();
-            move || {
-                git.get_diffs(
-                    DiffTarget::Worktree {
-                        worktree_path: &worktree,
-                        base_commit: &base_commit,
-                    },
+    Ok(AiReview::create(pool, workspace.id, execution_process.id).await?)
+}
+
+/// Whether `review` finished after the last coding agent turn of the workspace, so it saw
+/// the changes about to be merged
+async fn is_current_ai_review(
+    pool: &sqlx::SqlitePool,
+    review: &AiReview,
+) -> Result<bool, sqlx::Error> {
+    let Some(completed_at) = review.completed_at else {
+        return Ok(false);
+    };
+    let latest_agent_run = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+        pool,
+        review.workspace_id,
+        &ExecutionProcessRunReason::CodingAgent,
+    )
+    .await?;
+    Ok(review.status == AiReviewStatus::Completed
+        && latest_agent_run.is_none_or(|process| process.started_at <= completed_at))
+}
+
+/// Where the project's agent review policy leaves a close of the workspace
+#[derive(Debug)]
+enum AiReviewCheck {
+    /// The project has no review policy, or the close skips it with `skip_ai_review`
+    NotRequired,
+    /// No review is current, so one has to be started with this agent
+    Start(ExecutorProfileId),
+    /// The current review has findings at or above the blocking severity
+    Blocked(CloseWorkspaceError),
+    /// The current review passed; its findings below the blocking severity are warnings
+    Passed(Vec<ReviewComment>),
+}
+
+/// Check the workspace against the project's agent review policy. Only `skip_ai_review`
+/// bypasses it; `force` skips CI alone.
+async fn check_ai_review(
+    pool: &sqlx::SqlitePool,
+    project_id: Uuid,
+    workspace_id: Uuid,
+    query: &CloseWorkspaceQuery,
+) -> Result<AiReviewCheck, ApiError> {
+    if query.skip_ai_review {
+        return Ok(AiReviewCheck::NotRequired);
+    }
+    let Some(policy) = ProjectAiReviewPolicy::find_by_project_id(pool, project_id).await? else {
+        return Ok(AiReviewCheck::NotRequired);
+    };
+    let review = match AiReview::find_latest_by_workspace_id(pool, workspace_id).await? {
+        Some(review) if is_current_ai_review(pool, &review).await? => review,
+        _ => return Ok(AiReviewCheck::Start(policy.executor_profile_id.0)),
+    };
+    let findings = ReviewComment::find_by_ai_review_id(pool, review.id).await?;
+    let (blocking, warnings) = partition_findings(findings, policy.block_severity);
+    if !blocking.is_empty() {
+        return Ok(AiReviewCheck::Blocked(
+            CloseWorkspaceError::AiReviewBlocked {
+                review,
+                findings: blocking,
+            },
+        ));
+    }
+    Ok(AiReviewCheck::Passed(warnings))
+}
+
+/// List agent reviews of a workspace, newest first.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_ai_reviews(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<AiReview>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let reviews = AiReview::find_by_workspace_id(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(reviews)))
+}
+
+/// Have a coding agent review the workspace diff now rather than when it is closed.
+/// Returns 404 if workspace not found.
+/// Returns 400 if workspace is closed, has running processes, or no agent is given and the
+/// project has no review policy.
+#[axum::debug_handler]
+pub async fn start_workspace_ai_review(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<StartAiReviewRequest>,
+) -> Result<ResponseJson<ApiResponse<AiReview>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace is closed (no active worktree)".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot review workspace with running processes".to_string(),
+        ));
+    }
+
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+    let executor_profile_id = match payload.executor_profile_id {
+        Some(profile) => profile,
+        None => ProjectAiReviewPolicy::find_by_project_id(pool, task.project_id)
+            .await?
+            .map(|policy| policy.executor_profile_id.0)
+            .ok_or_else(|| {
+                ApiError::BadRequest(
+                    "No executor profile given and the project has no review policy".to_string(),
+                )
+            })?,
+    };
+
+    let review = start_ai_review(&deployment, &workspace, &task, executor_profile_id).await?;
+    Ok(ResponseJson(ApiResponse::success(review)))
+}
+
+/// Check that each repo worktree exists, is linked to its repository, has the workspace
+/// branch checked out and is not locked or prunable, with a suggested repair per issue.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn verify_workspace(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceVerification>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace_id).await?;
+
+    let verification = WorkspaceIntegrityService::verify(deployment.git(), &workspace, &repos)?;
+    Ok(ResponseJson(ApiResponse::success(verification)))
+}
+
+/// Run each repo's test script in the workspace worktrees and store the parsed results.
+/// Returns 404 if workspace not found.
+/// Returns 400 if workspace is closed or no repo has a test script configured.
+#[axum::debug_handler]
+pub async fn run_workspace_tests(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceTestRun>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+
+    let container_ref = workspace.container_ref.as_ref().ok_or_else(|| {
+        ApiError::BadRequest("Workspace already closed (no active worktree)".to_string())
+    })?;
+
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace_id).await?;
+    if !repos.iter().any(|r| {
+        r.test_script
+            .as_deref()
+            .is_some_and(|s| !s.trim().is_empty())
+    }) {
+        return Err(ApiError::BadRequest(
+            "No test script configured for any repo in this workspace".to_string(),
+        ));
+    }
+
+    let task = workspace.parent_task(pool).await?.ok_or_else(|| {
+        ApiError::NotFound(format!("Task for workspace {} not found", workspace_id))
+    })?;
+    let redactor = OutputRedactor::for_workspace(
+        pool,
+        task.project_id,
+        workspace.id,
+        &deployment.settings().output_redaction_patterns(),
+    )
+    .await?;
+    let test_run = TestRunnerService::run_for_workspace(
+        pool,
+        &workspace,
+        &repos,
+        &PathBuf::from(container_ref),
+        &redactor,
+    )
+    .await?;
+
+    Ok(ResponseJson(ApiResponse::success(test_run)))
+}
+
+/// The workspace repo an exec request runs in and the command it runs there
+async fn resolve_exec_command(
+    pool: &sqlx::SqlitePool,
+    workspace_id: Uuid,
+    request: &ExecWorkspaceRequest,
+) -> Result<(Repo, String), ApiError> {
+    let repo = WorkspaceRepo::find_repos_for_workspace(pool, workspace_id)
+        .await?
+        .into_iter()
+        .find(|r| r.id == request.repo_id)
+        .ok_or_else(|| ApiError::BadRequest("Repo is not part of this workspace".to_string()))?;
+
+    let command = match (&request.command, request.script) {
+        (Some(command), None) if !command.trim().is_empty() => command.clone(),
+        (None, Some(kind)) => kind.command_for(&repo).ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Repo '{}' has no {:?} script configured",
+                repo.name, kind
+            ))
+        })?,
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Provide exactly one of `command` or `script`".to_string(),
+            ));
+        }
+    };
+    Ok((repo, command))
+}
+
+/// Longest an exec request waits for its command before returning what it has so far
+const EXEC_WAIT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Run a command or a configured repo script in one of the workspace worktrees, recorded as
+/// an execution process. Changes it leaves in the worktree are committed like script changes.
+/// Returns 404 if workspace not found.
+/// Returns 400 if workspace is closed, the repo is not in the workspace, or no command was given.
+#[axum::debug_handler]
+pub async fn exec_in_workspace(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(request): Json<ExecWorkspaceRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecWorkspaceResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace already closed (no active worktree)".to_string(),
+        ));
+    }
+
+    let (repo, command) = resolve_exec_command(pool, workspace_id, &request).await?;
+
+    let session = RecordedCommandService::session_for_workspace(pool, workspace_id).await?;
+    let action = ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script: command,
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::AdHocCommand,
+            working_dir: Some(repo.name.clone()),
+            env: HashMap::new(),
+            artifacts: Vec::new(),
+        }),
+        None,
+    );
+    let process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::AdHocCommand,
+        )
+        .await?;
+
+    if !request.wait {
+        return Ok(ResponseJson(ApiResponse::success(ExecWorkspaceResponse {
+            workspace_id: workspace_id.to_string(),
+            execution_process_id: process.id.to_string(),
+            status: process.status,
+            exit_code: process.exit_code,
+            stdout: None,
+            stderr: None,
+        })));
+    }
+
+    let (stdout, stderr) = collect_process_output(&deployment, process.id).await;
+    let process = ExecutionProcess::find_by_id(pool, process.id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Execution process {} not found", process.id)))?;
+
+    Ok(ResponseJson(ApiResponse::success(ExecWorkspaceResponse {
+        workspace_id: workspace_id.to_string(),
+        execution_process_id: process.id.to_string(),
+        status: process.status,
+        exit_code: process.exit_code,
+        stdout: Some(stdout),
+        stderr: Some(stderr),
+    })))
+}
+
+/// Worktree a project script runs in: its repo's, else the only repo's of single-repo
+/// workspaces, else the workspace directory (None)
+async fn script_working_dir(
+    pool: &sqlx::SqlitePool,
+    workspace_id: Uuid,
+    script: &ProjectScript,
+) -> Result<Option<String>, ApiError> {
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace_id).await?;
+    let working_dir = match script.repo_id {
+        Some(repo_id) => {
+            let repo = repos.iter().find(|r| r.id == repo_id).ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "Script '{}' runs in a repo that is not part of this workspace",
+                    script.name
+                ))
+            })?;
+            Some(repo.name.clone())
+        }
+        None if repos.len() == 1 => Some(repos[0].name.clone()),
+        None => None,
+    };
+    Ok(working_dir)
+}
+
+/// Path parameters for running a project script in a workspace
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceScriptPath {
+    pub id: Uuid,
+    pub script_id: Uuid,
+}
+
+/// Start one of the project's named scripts in the workspace. The run is recorded as an
+/// execution process and listed in the script's run history.
+/// Returns 404 if the workspace or script is not found, or the script belongs to another project.
+/// Returns 400 if workspace is closed or the script's repo is not part of the workspace.
+#[axum::debug_handler]
+pub async fn run_project_script(
+    State(deployment): State<DeploymentImpl>,
+    Path(WorkspaceScriptPath { id, script_id }): Path<WorkspaceScriptPath>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", id)))?;
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace already closed (no active worktree)".to_string(),
+        ));
+    }
+
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task for workspace {} not found", id)))?;
+    let script = ProjectScript::find_by_id(pool, script_id)
+        .await?
+        .filter(|script| script.project_id == task.project_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Script {} not found in project", script_id)))?;
+
+    let working_dir = script_working_dir(pool, id, &script).await?;
+
+    let session = RecordedCommandService::session_for_workspace(pool, id).await?;
+    let action = ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script: script.command.clone(),
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::ProjectScript,
+            working_dir,
+            env: script.env.0.clone(),
+            artifacts: script.artifacts.0.clone(),
+        }),
+        None,
+    );
+    let process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::ProjectScript,
+        )
+        .await?;
+    ProjectScript::record_run(pool, script.id, id, process.id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_script_run",
+            serde_json::json!({
+                "project_id": task.project_id.to_string(),
+                "script_id": script.id.to_string(),
+                "workspace_id": id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(process)))
+}
+
+/// Gather stdout/stderr of an execution process until it finishes or the wait times out
+async fn collect_process_output(deployment: &DeploymentImpl, id: Uuid) -> (String, String) {
+    let (mut stdout, mut stderr) = (String::new(), String::new());
+    let Some(mut stream) = deployment.container().stream_raw_logs(&id).await else {
+        return (stdout, stderr);
+    };
+
+    let collect = async {
+        while let Some(Ok(msg)) = stream.next().await {
+            match msg {
+                LogMsg::Stdout(s) => stdout.push_str(&s),
+                LogMsg::Stderr(s) => stderr.push_str(&s),
+                LogMsg::Finished => break,
+                _ => {}
+            }
+        }
+    };
+    if tokio::time::timeout(EXEC_WAIT_TIMEOUT, collect)
+        .await
+        .is_err()
+    {
+        tracing::debug!("Timed out waiting for execution process {} to finish", id);
+    }
+
+    (stdout, stderr)
+}
+
+/// Query parameters identifying a file inside one of the workspace worktrees
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceFileQuery {
+    /// Repo name or id
+    pub repo: String,
+    /// File path relative to the repo root
+    pub path: String,
+}
+
+/// Response for workspace blame endpoint
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceBlameResponse {
+    pub workspace_id: String,
+    pub repo_name: String,
+    pub path: String,
+    /// Fork point of the workspace branch that lines are classified against
+    pub base_commit: String,
+    pub lines: Vec<BlameLine>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceFileHistoryQuery {
+    /// Repo name or id
+    pub repo: String,
+    /// File path relative to the repo root
+    pub path: String,
+    /// Maximum number of commits to return (default 100)
+    pub limit: Option<usize>,
+}
+
+/// Response for workspace file history endpoint
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceFileHistoryResponse {
+    pub workspace_id: String,
+    pub repo_name: String,
+    pub path: String,
+    /// Fork point of the workspace branch; older commits have `on_branch: false`
+    pub base_commit: String,
+    pub commits: Vec<FileHistoryEntry>,
+}
+
+const DEFAULT_FILE_HISTORY_LIMIT: usize = 100;
+const MAX_FILE_HISTORY_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceFileDiffQuery {
+    /// Repo name or id
+    pub repo: String,
+    /// File path relative to the repo root
+    pub path: String,
+    /// Index of the first hunk to return
+    #[serde(default)]
+    pub cursor: usize,
+    /// Maximum number of hunks to return (default 50)
+    pub limit: Option<usize>,
+}
+
+/// Response for the per-file chunked diff endpoint
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceFileDiffChunk {
+    pub path: String,
+    /// Index of the first hunk in this chunk
+    pub cursor: usize,
+    pub total_hunks: usize,
+    /// Unified diff hunks, each starting with its `@@` header
+    pub hunks: Vec<String>,
+    /// Cursor of the next chunk; absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<usize>,
+}
+
+const DEFAULT_DIFF_CHUNK_HUNKS: usize = 50;
+const MAX_DIFF_CHUNK_HUNKS: usize = 500;
+/// A chunk stops early once its hunks reach this size, but always holds at least one
+const MAX_DIFF_CHUNK_BYTES: usize = 1024 * 1024;
+/// Files larger than this are not diffed at all, even in chunks
+const MAX_CHUNKED_DIFF_FILE_BYTES: usize = 64 * 1024 * 1024;
+
+/// A file in a workspace worktree, resolved from a repo and a relative path
+struct WorkspaceFile {
+    repo: RepoWithTargetBranch,
+    worktree_path: PathBuf,
+    relative_path: PathBuf,
+    base_commit: Commit,
+}
+
+async fn resolve_workspace_file(
+    deployment: &DeploymentImpl,
+    workspace_id: Uuid,
+    repo: &str,
+    path: &str,
+) -> Result<WorkspaceFile, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let container_ref = workspace
+        .container_ref
+        .as_ref()
+        .ok_or_else(|| ApiError::NotFound("Workspace has no active worktree".to_string()))?;
+
+    let relative_path = PathBuf::from(path);
+    if path.is_empty()
+        || !relative_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(ApiError::BadRequest(
+            "Path must be relative to the repo root and stay inside it".to_string(),
+        ));
+    }
+
+    let repo = resolve_workspace_repo(pool, workspace_id, repo).await?;
+
+    let worktree_path = PathBuf::from(container_ref).join(&repo.repo.name);
+    if !worktree_path.join(&relative_path).is_file() {
+        return Err(ApiError::NotFound(format!(
+            "File '{}' not found in repo '{}'",
+            path, repo.repo.name
+        )));
+    }
+
+    let base_commit = tokio::task::spawn_blocking({
+        let git = deployment.git().clone();
+        let repo_path = repo.repo.path.clone();
+        let workspace_branch = workspace.branch.clone();
+        let target_branch = repo.target_branch.clone();
+        move || git.get_base_commit(&repo_path, &workspace_branch, &target_branch)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    Ok(WorkspaceFile {
+        repo,
+        worktree_path,
+        relative_path,
+        base_commit,
+    })
+}
+
+/// Line-by-line blame of the worktree version of a file, including uncommitted edits.
+/// Each line is marked as pre-existing (`base`), introduced on the workspace branch
+/// (`branch`) or `uncommitted`.
+/// Returns 404 if the workspace, repo or file is not found.
+/// Returns 400 if the path is absolute or escapes the repo.
+#[axum::debug_handler]
+pub async fn get_workspace_blame(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<WorkspaceFileQuery>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceBlameResponse>>, ApiError> {
+    let file = resolve_workspace_file(&deployment, workspace_id, &query.repo, &query.path).await?;
+
+    let base_commit = file.base_commit.to_string();
+    let lines = tokio::task::spawn_blocking({
+        let git = deployment.git().clone();
+        move || git.blame_worktree_file(&file.worktree_path, &file.relative_path, &file.base_commit)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    Ok(ResponseJson(ApiResponse::success(WorkspaceBlameResponse {
+        workspace_id: workspace_id.to_string(),
+        repo_name: file.repo.repo.name,
+        path: query.path,
+        base_commit,
+        lines,
+    })))
+}
+
+/// Commit history of a file as seen from the workspace worktree, newest first and following
+/// renames. Includes commits made on the workspace branch and those inherited from the base.
+/// Returns 404 if the workspace, repo or file is not found.
+/// Returns 400 if the path is absolute or escapes the repo.
+#[axum::debug_handler]
+pub async fn get_workspace_file_history(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<WorkspaceFileHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceFileHistoryResponse>>, ApiError> {
+    let file = resolve_workspace_file(&deployment, workspace_id, &query.repo, &query.path).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_FILE_HISTORY_LIMIT)
+        .clamp(1, MAX_FILE_HISTORY_LIMIT);
+
+    let base_commit = file.base_commit.to_string();
+    let commits = tokio::task::spawn_blocking({
+        let git = deployment.git().clone();
+        move || {
+            git.get_file_history(
+                &file.worktree_path,
+                &file.relative_path,
+                &file.base_commit,
+                limit,
+            )
+        }
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    Ok(ResponseJson(ApiResponse::success(
+        WorkspaceFileHistoryResponse {
+            workspace_id: workspace_id.to_string(),
+            repo_name: file.repo.repo.name,
+            path: query.path,
+            base_commit,
+            commits,
+        },
+    )))
+}
+
+/// One chunk of the hunks of a single file's worktree diff, for files whose content the
+/// workspace diff omits for size. Request chunks in turn, passing `next_cursor` as `cursor`;
+/// the diff is recomputed for each, so chunks follow the worktree if it changes meanwhile.
+/// Returns 404 if the workspace, repo or file is not found.
+/// Returns 400 if the path escapes the repo, or the file is binary or too large to diff.
+#[axum::debug_handler]
+pub async fn get_workspace_file_diff(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<WorkspaceFileDiffQuery>,
+    Query(format): Query<DiffFormatOptions>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceFileDiffChunk>>, ApiError> {
+    format.validate().map_err(ApiError::BadRequest)?;
+    let file = resolve_workspace_file(&deployment, workspace_id, &query.repo, &query.path).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DIFF_CHUNK_HUNKS)
+        .clamp(1, MAX_DIFF_CHUNK_HUNKS);
+
+    let hunks = tokio::task::spawn_blocking({
+        let git = deployment.git().clone();
+        move || -> Result<Vec<String>, git::GitServiceError> {
+            let (old, new) = git.get_worktree_file_texts(
+                &file.worktree_path,
+                &file.base_commit,
+                &file.relative_path,
+                MAX_CHUNKED_DIFF_FILE_BYTES,
+            )?;
+            Ok(create_unified_diff_hunks(
+                old.as_deref().unwrap_or(""),
+                new.as_deref().unwrap_or(""),
+                &format,
+            ))
+        }
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    let total_hunks = hunks.len();
+    let mut chunk = Vec::new();
+    let mut chunk_bytes = 0;
+    for hunk in hunks.into_iter().skip(query.cursor).take(limit) {
+        if !chunk.is_empty() && chunk_bytes + hunk.len() > MAX_DIFF_CHUNK_BYTES {
+            break;
+        }
+        chunk_bytes += hunk.len();
+        chunk.push(hunk);
+    }
+    let end = query.cursor + chunk.len();
+
+    Ok(ResponseJson(ApiResponse::success(WorkspaceFileDiffChunk {
+        path: query.path,
+        cursor: query.cursor,
+        total_hunks,
+        next_cursor: (end < total_hunks).then_some(end),
+        hunks: chunk,
+    })))
+}
+
+/// Get workspace transcript (prompt, summary, agent_session_id).
+/// Returns 404 if workspace not found.
+/// Returns empty fields if no coding agent turns exist.
+#[axum::debug_handler]
+pub async fn get_workspace_transcript(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceTranscriptResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    // Find workspace, return 404 if not found
+    let _workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+
+    // Find the latest coding agent turn for this workspace
+    let coding_agent_turn = CodingAgentTurn::find_latest_by_workspace_id(pool, workspace_id).await?;
+
+    let response = match coding_agent_turn {
+        Some(turn) => WorkspaceTranscriptResponse {
+            workspace_id: workspace_id.to_string(),
+            prompt: turn.prompt,
+            summary: turn.summary,
+            agent_session_id: turn.agent_session_id,
+        },
+        None => WorkspaceTranscriptResponse {
+            workspace_id: workspace_id.to_string(),
+            prompt: None,
+            summary: None,
+            agent_session_id: None,
+        },
+    };
+
+    Ok(ResponseJson(ApiResponse::success(response)))
+}
+
+/// Diffs of each of the workspace's repos against its merge base with the target branch,
+/// limited to `query.repo` when given. Repos without a merge base are left out.
+async fn collect_workspace_diffs(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    query: &WorkspaceDiffQuery,
+    format: &DiffFormatOptions,
+) -> Result<Vec<RepoFileDiffs>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    // Return 404 if no container_ref (workspace not active)
+    let container_ref = workspace
+        .container_ref
+        .as_ref()
+        .ok_or_else(|| ApiError::NotFound("Workspace has no active worktree".to_string()))?;
+    repair_worktrees_if_enabled(deployment, workspace).await?;
+
+    let workspace_repos = match &query.repo {
+        Some(repo) => vec![resolve_workspace_repo(pool, workspace.id, repo).await?],
+        None => {
+            WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?
+        }
+    };
+
+    let comment_counts: HashMap<(Uuid, String), FileReviewComments> =
+        ReviewComment::count_by_file(pool, workspace.id)
+            .await?
+            .into_iter()
+            .map(|count| {
+                let comments = FileReviewComments {
+                    total: count.total as usize,
+                    unresolved: count.unresolved as usize,
+                };
+                ((count.repo_id, count.path), comments)
+            })
+            .collect();
+    let mut repos: Vec<RepoFileDiffs> = Vec::new();
+
+    for repo_with_branch in workspace_repos {
+        let worktree_path = PathBuf::from(container_ref).join(&repo_with_branch.repo.name);
+        let repo_path = repo_with_branch.repo.path.clone();
+
+        // Get base commit (merge base) between workspace branch and target branch
+        let base_commit_result = tokio::task::spawn_blocking({
+            let git = deployment.git().clone();
+            let repo_path = repo_path.clone();
+            let workspace_branch = workspace.branch.clone();
+            let target_branch = repo_with_branch.target_branch.clone();
+            move || git.get_base_commit(&repo_path, &workspace_branch, &target_branch)
+        })
+        .await;
+
+        let base_commit = match base_commit_result {
+            Ok(Ok(commit)) => commit,
+            _ => {
+                tracing::warn!(
+                    "Skipping repo '{}' of workspace {}: no merge base with {}",
+                    repo_with_branch.repo.name,
+                    workspace.id,
+                    repo_with_branch.target_branch
+                );
+                continue;
+            }
+        };
+
+        // Get diffs with content
+        let diffs_result = tokio::task::spawn_blocking({
+            let git = deployment.git().clone();
+            let worktree = worktree_path.clone();
+            let include_untracked = query.include_untracked;
+            let format = *format;
+            let path_scope = repo_with_branch.path_scope.clone();
+            move || {
+                let path_scope = path_scope.as_deref();
+                git.get_diffs_with_options(
+                    DiffTarget::Worktree {
+                        worktree_path: &worktree,
+                        base_commit: &base_commit,
+                        include_untracked,
+                    },
+                    path_scope.as_ref().map(std::slice::from_ref),
+                    &format,
+                )
+            }
+        })
+        .await;
+
+        let mut files = match diffs_result {
+            Ok(Ok(diffs)) => diffs_to_file_diffs(diffs, format),
+            _ => Vec::new(),
+        };
+        for file in &mut files {
+            let key = (repo_with_branch.repo.id, file.path.clone());
+            file.review_comments = Some(comment_counts.get(&key).copied().unwrap_or_default());
+        }
+        repos.push(RepoFileDiffs::new(
+            repo_with_branch.repo.id,
+            repo_with_branch.repo.name,
+            files,
+        ));
+    }
+    Ok(repos)
+}
+
+/// The workspace repo `repo` names, by name or id; 404 if there is none
+async fn resolve_workspace_repo(
+    pool: &sqlx::SqlitePool,
+    workspace_id: Uuid,
+    repo: &str,
+) -> Result<RepoWithTargetBranch, ApiError> {
+    let repo_id = Uuid::parse_str(repo).ok();
+    WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace_id)
+        .await?
+        .into_iter()
+        .find(|r| Some(r.repo.id) == repo_id || r.repo.name == repo)
+        .ok_or_else(|| ApiError::NotFound(format!("Repo '{}' not found in workspace", repo)))
+}
+
+/// Get workspace file diffs with full diff content, grouped by repo.
+/// Returns 404 if workspace not found, has no container_ref, or `repo` is not one of its repos.
+#[axum::debug_handler]
+pub async fn get_workspace_diff(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<WorkspaceDiffQuery>,
+    Query(format): Query<DiffFormatOptions>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceDiffResponse>>, ApiError> {
+    format.validate().map_err(ApiError::BadRequest)?;
+
+    // Find workspace, return 404 if not found
+    let workspace = Workspace::find_by_id(&deployment.db().pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let repos = collect_workspace_diffs(&deployment, &workspace, &query, &format).await?;
+
+    let files = query.flat.then(|| {
+        repos
+            .iter()
+            .flat_map(|repo| repo.files.iter().cloned())
+            .collect()
+    });
+    Ok(ResponseJson(ApiResponse::success(WorkspaceDiffResponse {
+        workspace_id: workspace_id.to_string(),
+        repos,
+        files,
+    })))
+}
+
+/// Get the workspace diff as a standalone HTML page with syntax-highlighted code, titled
+/// with the task's title. Takes the same query parameters as the JSON diff.
+/// Returns 404 if workspace not found, has no container_ref, or `repo` is not one of its repos.
+pub async fn get_workspace_diff_html(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<WorkspaceDiffQuery>,
+    Query(format): Query<DiffFormatOptions>,
+) -> Result<Response, ApiError> {
+    format.validate().map_err(ApiError::BadRequest)?;
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+    let repos = collect_workspace_diffs(&deployment, &workspace, &query, &format).await?;
+
+    let sections: Vec<DiffHtmlSection> = repos
+        .iter()
+        .map(|repo| DiffHtmlSection {
+            title: &repo.repo_name,
+            files: repo
+                .files
+                .iter()
+                .map(|file| DiffHtmlFile {
+                    path: &file.path,
+                    additions: file.additions,
+                    deletions: file.deletions,
+                    diff_content: &file.diff_content,
+                })
+                .collect(),
+        })
+        .collect();
+    let html = DiffHtmlService::render(&task.title, &sections);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"workspace-{workspace_id}-diff.html\""),
+            ),
+        ],
+        html,
+    )
+        .into_response())
+}
+
+/// Query parameters for exporting workspace commits as patches
+#[derive(Debug, Deserialize)]
+pub struct WorkspacePatchesQuery {
+    /// Repo name or id
+    pub repo: String,
+}
+
+/// Download the commits of the workspace branch in one repo as an mbox produced by
+/// `git format-patch` from the merge base, ready for `git am`. Uncommitted worktree
+/// changes are not included.
+/// Returns 404 if the workspace, repo or workspace branch is not found.
+/// Returns 400 if the branch has no commits since the merge base.
+#[axum::debug_handler]
+pub async fn get_workspace_patches(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<WorkspacePatchesQuery>,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+
+    let repo = resolve_workspace_repo(pool, workspace_id, &query.repo).await?;
+
+    let git = deployment.git().clone();
+    let repo_path = repo.repo.path.clone();
+    let branch = workspace.branch.clone();
+    let target_branch = repo.target_branch.clone();
+    let patches = tokio::task::spawn_blocking(move || -> Result<_, git::GitServiceError> {
+        if !git.check_branch_exists(&repo_path, &branch)? {
+            return Ok(None);
+        }
+        let base_commit = git.get_base_commit(&repo_path, &branch, &target_branch)?;
+        git.format_patches(&repo_path, &branch, &base_commit)
+            .map(Some)
+    })
+    .await
+    .map_err(std::io::Error::other)??
+    .ok_or_else(|| {
+        ApiError::NotFound(format!(
+            "Branch '{}' no longer exists in repo '{}'",
+            workspace.branch, repo.repo.name
+        ))
+    })?;
+
+    if patches.is_empty() {
+        return Err(ApiError::BadRequest(format!(
+            "Branch '{}' has no commits since '{}'",
+            workspace.branch, repo.target_branch
+        )));
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/mbox".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"workspace-{workspace_id}-{}.mbox\"",
+                    repo.repo.name
+                ),
+            ),
+        ],
+        Body::from(patches),
+    )
+        .into_response())
+}
+
+/// Query parameters for downloading a workspace worktree
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceArchiveQuery {
+    /// Repo name or id
+    pub repo: String,
+    /// Leave out files ignored by git, such as build outputs and dependencies
+    #[serde(default)]
+    pub exclude_ignored: bool,
+}
+
+/// Download the current files of one repo's worktree, including uncommitted changes, as a
+/// tar.gz with the files under a directory named after the repo. `.git` is left out.
+/// Returns 404 if the workspace or repo is not found or the workspace has no worktree.
+#[axum::debug_handler]
+pub async fn get_workspace_archive(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<WorkspaceArchiveQuery>,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let container_ref = workspace
+        .container_ref
+        .as_ref()
+        .ok_or_else(|| ApiError::NotFound("Workspace has no active worktree".to_string()))?;
+
+    let repo = resolve_workspace_repo(pool, workspace_id, &query.repo)
+        .await?
+        .repo;
+
+    let worktree_path = PathBuf::from(container_ref).join(&repo.name);
+    if !worktree_path.is_dir() {
+        return Err(ApiError::NotFound(format!(
+            "Worktree of repo '{}' does not exist",
+            repo.name
+        )));
+    }
+
+    let archive =
+        WorkspaceArchiveService::stream(worktree_path, repo.name.clone(), query.exclude_ignored);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"workspace-{workspace_id}-{}.tar.gz\"",
+                    repo.name
+                ),
+            ),
+        ],
+        Body::from_stream(archive),
+    )
+        .into_response())
+}
+
+/// Request body for applying an external patch to a workspace
+#[derive(Debug, Deserialize)]
+pub struct ApplyPatchRequest {
+    /// Repo name or id
+    pub repo: String,
+    /// Unified diff, or an mbox as produced by `git format-patch`
+    pub patch: String,
+    /// Commit message for a unified diff; mbox patches keep their own messages
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Response for applying an external patch to a workspace
+#[derive(Debug, Serialize, TS)]
+pub struct ApplyPatchResponse {
+    pub workspace_id: String,
+    pub repo_name: String,
+    /// Commits created on the workspace branch, oldest first
+    pub commit_shas: Vec<String>,
+}
+
+/// Apply a unified diff or mbox to one repo worktree of the workspace, with three-way
+/// fallback, and commit the result on the workspace branch.
+/// Returns 404 if the workspace or repo is not found.
+/// Returns 400 if processes are running or the worktree has uncommitted changes.
+/// Returns 409 if the patch does not apply; the worktree is left as it was.
+/// Returns 423 if another mutating operation is already running for the workspace.
+///
+/// The commits created, or why the patch did not apply, are recorded as a workspace event.
+#[axum::debug_handler]
+pub async fn apply_workspace_patch(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(request): Json<ApplyPatchRequest>,
+) -> Result<ResponseJson<ApiResponse<ApplyPatchResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::ApplyPatch)?;
+
+    if request.patch.trim().is_empty() {
+        return Err(ApiError::BadRequest("Patch is empty".to_string()));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot apply a patch while processes are running".to_string(),
+        ));
+    }
+
+    let repo = resolve_workspace_repo(pool, workspace_id, &request.repo).await?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let worktree_path = PathBuf::from(container_ref).join(&repo.repo.name);
+
+    let message = request
+        .message
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| "Apply external patch".to_string());
+    let commit_shas = apply_patch_to_repo(
+        deployment.git(),
+        pool,
+        workspace_id,
+        &repo.repo.name,
+        worktree_path,
+        request.patch,
+        message,
+    )
+    .await?;
+
+    Ok(ResponseJson(ApiResponse::success(ApplyPatchResponse {
+        workspace_id: workspace_id.to_string(),
+        repo_name: repo.repo.name,
+        commit_shas,
+    })))
+}
+
+/// Apply `patch` to the clean worktree of a repo and record the resulting commits, or why the
+/// patch did not apply, as an event of the workspace
+async fn apply_patch_to_repo(
+    git: &git::GitService,
+    pool: &sqlx::SqlitePool,
+    workspace_id: Uuid,
+    repo_name: &str,
+    worktree_path: PathBuf,
+    patch: String,
+    message: String,
+) -> Result<Vec<String>, ApiError> {
+    let git = git.clone();
+    let applied = tokio::task::spawn_blocking(move || -> Result<_, git::GitServiceError> {
+        if !git.is_worktree_clean(&worktree_path)? {
+            return Ok(None);
+        }
+        git.apply_patch(&worktree_path, patch.as_bytes(), &message)
+            .map(Some)
+    })
+    .await
+    .map_err(std::io::Error::other)?;
+
+    let commits = match applied {
+        Ok(Some(commits)) => commits,
+        Ok(None) => {
+            return Err(ApiError::BadRequest(format!(
+                "Repo '{}' has uncommitted changes; commit or discard them first",
+                repo_name
+            )));
+        }
+        Err(e) => {
+            WorkspaceEvent::create(
+                pool,
+                workspace_id,
+                WorkspaceEventKind::PatchFailed,
+                &format!(
+                    "External patch to repo '{}' did not apply: {}",
+                    repo_name, e
+                ),
+            )
+            .await?;
+            return Err(e.into());
+        }
+    };
+
+    tracing::info!(
+        "Applied external patch to workspace {} repo '{}': {} commit(s)",
+        workspace_id,
+        repo_name,
+        commits.len()
+    );
+    let commit_shas: Vec<String> = commits.into_iter().map(|c| c.commit_sha).collect();
+    WorkspaceEvent::create(
+        pool,
+        workspace_id,
+        WorkspaceEventKind::PatchApplied,
+        &format!(
+            "Applied external patch to repo '{}' as {}",
+            repo_name,
+            commit_shas.join(", ")
+        ),
+    )
+    .await?;
+    Ok(commit_shas)
+}
+
+/// Largest file accepted by the upload endpoint
+const MAX_UPLOAD_FILE_BYTES: usize = 50 * 1024 * 1024;
+/// Largest request body accepted by the upload endpoint
+const MAX_UPLOAD_BODY_BYTES: usize = 200 * 1024 * 1024;
+
+/// Query parameters for uploading files into a workspace worktree
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceUploadQuery {
+    /// Repo name or id
+    pub repo: String,
+    /// Directory relative to the repo root the files are written to; created if missing.
+    /// Defaults to the repo root.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Replace files that already exist instead of rejecting the upload
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct UploadedFile {
+    /// Path relative to the repo root
+    pub path: String,
+    pub size_bytes: usize,
+}
+
+/// Response for uploading files into a workspace
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceUploadResponse {
+    pub workspace_id: String,
+    pub repo_name: String,
+    pub files: Vec<UploadedFile>,
+}
+
+/// The directory of an upload, relative to the repo root
+fn upload_directory(path: Option<&str>) -> Result<PathBuf, ApiError> {
+    let directory = PathBuf::from(path.unwrap_or_default());
+    if !directory.components().all(|c| match c {
+        Component::Normal(name) => name != ".git",
+        _ => false,
+    }) {
+        return Err(ApiError::BadRequest(
+            "Path must be a directory relative to the repo root that stays inside it".to_string(),
+        ));
+    }
+    Ok(directory)
+}
+
+/// The name an uploaded file is written under, which must be a single path component
+fn upload_file_name(file_name: &str) -> Result<PathBuf, ApiError> {
+    let name = std::path::Path::new(file_name);
+    let mut components = name.components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(n)), None) if n != ".git" => Ok(name.to_path_buf()),
+        _ => Err(ApiError::BadRequest(format!(
+            "Invalid file name '{file_name}'"
+        ))),
+    }
+}
+
+fn check_upload_size(file_name: &str, size: usize) -> Result<(), ApiError> {
+    if size > MAX_UPLOAD_FILE_BYTES {
+        return Err(ApiError::BadRequest(format!(
+            "File '{file_name}' is larger than {} MiB",
+            MAX_UPLOAD_FILE_BYTES / (1024 * 1024)
+        )));
+    }
+    Ok(())
+}
+
+/// Check that `directory` of the worktree stays inside it once symlinks are followed,
+/// without creating any of it: its nearest existing ancestor must resolve to a directory
+/// inside the worktree, as the rest does not exist yet and will be created there.
+async fn check_upload_directory(
+    worktree_path: &std::path::Path,
+    directory: &std::path::Path,
+    repo_name: &str,
+) -> Result<(), ApiError> {
+    let mut existing = worktree_path.join(directory);
+    loop {
+        match tokio::fs::symlink_metadata(&existing).await {
+            Ok(_) => break,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::NotADirectory
+                ) && existing.pop() => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let leads_outside = || {
+        ApiError::BadRequest(format!(
+            "Path '{}' leads outside the repo",
+            directory.display()
+        ))
+    };
+    // A dangling symlink cannot be resolved, and creating the directory would follow it
+    let resolved = tokio::fs::canonicalize(&existing)
+        .await
+        .map_err(|_| leads_outside())?;
+    if !resolved.starts_with(tokio::fs::canonicalize(worktree_path).await?) {
+        return Err(leads_outside());
+    }
+    if !tokio::fs::metadata(&resolved).await?.is_dir() {
+        return Err(ApiError::Conflict(format!(
+            "'{}' is a file in repo '{repo_name}'",
+            existing
+                .strip_prefix(worktree_path)
+                .unwrap_or(&existing)
+                .display()
+        )));
+    }
+    Ok(())
+}
+
+/// Check that an upload may be written to `target`, shown to the client as `display_path`.
+/// Symlinks are refused even with `overwrite`, since writing through one could change a file
+/// outside the repo.
+async fn check_upload_target(
+    target: &std::path::Path,
+    display_path: &std::path::Path,
+    repo_name: &str,
+    overwrite: bool,
+) -> Result<(), ApiError> {
+    let metadata = match tokio::fs::symlink_metadata(target).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    if metadata.file_type().is_symlink() {
+        return Err(ApiError::BadRequest(format!(
+            "'{}' is a symbolic link in repo '{repo_name}'",
+            display_path.display()
+        )));
+    }
+    if metadata.is_dir() {
+        return Err(ApiError::Conflict(format!(
+            "'{}' is a directory in repo '{repo_name}'",
+            display_path.display()
+        )));
+    }
+    if !overwrite {
+        return Err(ApiError::Conflict(format!(
+            "File '{}' already exists in repo '{repo_name}'",
+            display_path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Write `data` to `name` in `dir` without following a symlink at the target: new files are
+/// created exclusively, and replaced files are written beside the target and renamed over it
+async fn write_uploaded_file(
+    dir: &std::path::Path,
+    name: &std::path::Path,
+    data: &[u8],
+    overwrite: bool,
+) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt as _;
+
+    let target = dir.join(name);
+    let path = if overwrite {
+        dir.join(format!(
+            ".{}.upload-{}",
+            name.to_string_lossy(),
+            Uuid::new_v4()
+        ))
+    } else {
+        target.clone()
+    };
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .await?;
+    let written = async {
+        file.write_all(data).await?;
+        file.sync_all().await
+    }
+    .await;
+    drop(file);
+    if let Err(e) = written {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(e);
+    }
+    if overwrite && let Err(e) = tokio::fs::rename(&path, &target).await {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Write the files of a multipart upload into a directory of one repo worktree. Every part
+/// with a file name is written under that name; the changes are left uncommitted. No file is
+/// written unless all of them can be.
+/// Returns 404 if the workspace or repo is not found.
+/// Returns 400 if the directory or a file name escapes the repo or names `.git`, a file is
+/// larger than 50 MiB, a name is sent twice or is a symlink in the repo, or no files were sent.
+/// Returns 409 if a file already exists and `overwrite` is not set, or the directory is a file.
+#[axum::debug_handler]
+pub async fn upload_workspace_files(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<WorkspaceUploadQuery>,
+    mut multipart: Multipart,
+) -> Result<ResponseJson<ApiResponse<WorkspaceUploadResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Upload)?;
+
+    let directory = upload_directory(query.path.as_deref())?;
+
+    let repo = resolve_workspace_repo(pool, workspace_id, &query.repo)
+        .await?
+        .repo;
+
+    // Every part is read and checked before any file is written
+    let mut uploads: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    while let Some(mut field) = multipart.next_field().await? {
+        let Some(file_name) = field.file_name().map(str::to_string) else {
+            continue;
+        };
+        let name = upload_file_name(&file_name)?;
+        if uploads.iter().any(|(existing, _)| *existing == name) {
+            return Err(ApiError::BadRequest(format!(
+                "File '{file_name}' was sent more than once"
+            )));
+        }
+
+        let mut data = Vec::new();
+        while let Some(chunk) = field.chunk().await? {
+            check_upload_size(&file_name, data.len() + chunk.len())?;
+            data.extend_from_slice(&chunk);
+        }
+        uploads.push((name, data));
+    }
+    if uploads.is_empty() {
+        return Err(ApiError::BadRequest("No files were uploaded".to_string()));
+    }
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let worktree_path = PathBuf::from(container_ref).join(&repo.name);
+    let target_dir = worktree_path.join(&directory);
+    // Nothing is created until every target is known to be writable
+    check_upload_directory(&worktree_path, &directory, &repo.name).await?;
+    for (name, _) in &uploads {
+        check_upload_target(
+            &target_dir.join(name),
+            &directory.join(name),
+            &repo.name,
+            query.overwrite,
+        )
+        .await?;
+    }
+    tokio::fs::create_dir_all(&target_dir).await?;
+
+    let mut files = Vec::new();
+    for (name, data) in uploads {
+        write_uploaded_file(&target_dir, &name, &data, query.overwrite).await?;
+        files.push(UploadedFile {
+            path: directory.join(&name).to_string_lossy().replace('\\', "/"),
+            size_bytes: data.len(),
+        });
+    }
+
+    tracing::info!(
+        "Uploaded {} file(s) to workspace {} repo '{}'",
+        files.len(),
+        workspace_id,
+        repo.name
+    );
+
+    Ok(ResponseJson(ApiResponse::success(
+        WorkspaceUploadResponse {
+            workspace_id: workspace_id.to_string(),
+            repo_name: repo.name,
+            files,
+        },
+    )))
+}
+
+/// Where the changes of one repo in a workspace report were read from
+enum ReportSource {
+    /// Live worktree, including uncommitted changes
+    Worktree,
+    /// Workspace branch, after its worktree was removed
+    Branch,
+    /// Squash commit recorded when the workspace was merged
+    MergeCommit,
+}
+
+struct ReportRepoChanges {
+    source: ReportSource,
+    commits: Vec<CommitInfo>,
+    files: Vec<FileDiff>,
+}
+
+pub(crate) fn diffs_to_file_diffs(
+    diffs: Vec<utils::diff::Diff>,
+    format: &DiffFormatOptions,
+) -> Vec<FileDiff> {
+    diffs
+        .into_iter()
+        .map(|diff| {
+            let path = diff
+                .new_path
+                .clone()
+                .or(diff.old_path.clone())
+                .unwrap_or_else(|| "unknown".to_string());
+            let moved = matches!(
+                diff.change,
+                DiffChangeKind::Renamed | DiffChangeKind::Copied
+            );
+            let old_path = diff.old_path.filter(|_| moved);
+            let diff_content = if diff.content_omitted {
+                "[Content omitted - file too large]".to_string()
+            } else {
+                let old = diff.old_content.as_deref().unwrap_or("");
+                let new = diff.new_content.as_deref().unwrap_or("");
+                if old.is_empty() && new.is_empty() {
+                    String::new()
+                } else {
+                    create_unified_diff_with_options(
+                        old_path.as_deref().unwrap_or(&path),
+                        &path,
+                        old,
+                        new,
+                        format,
+                    )
+                }
+            };
+            let split = (format.view == DiffView::Split).then(|| split_unified_diff(&diff_content));
+            FileDiff {
+                path,
+                additions: diff.additions.unwrap_or(0),
+                deletions: diff.deletions.unwrap_or(0),
+                diff_content,
+                state: diff.state,
+                old_path,
+                similarity: diff.similarity,
+                split,
+                review_comments: None,
+            }
+        })
+        .collect()
+}
+
+/// Read the commits and diff of one repo from whatever is left of the workspace: the
+/// worktree while it exists, then the branch, then the merge commit once both are gone.
+fn collect_report_repo_changes(
+    git: &git::GitService,
+    repo_path: &std::path::Path,
+    worktree_path: Option<PathBuf>,
+    branch: &str,
+    target_branch: &str,
+    merge_commit: Option<&str>,
+    path_scope: Option<&str>,
+) -> Result<Option<ReportRepoChanges>, git::GitServiceError> {
+    let path_filter = path_scope.as_ref().map(std::slice::from_ref);
+    if git.check_branch_exists(repo_path, branch)? {
+        let base_commit = git.get_base_commit(repo_path, branch, target_branch)?;
+        let commits = git.get_branch_commits(repo_path, branch, &base_commit)?;
+        let (source, diffs) = match worktree_path.filter(|path| path.exists()) {
+            Some(worktree_path) => (
+                ReportSource::Worktree,
+                git.get_diffs(
+                    DiffTarget::Worktree {
+                        worktree_path: &worktree_path,
+                        base_commit: &base_commit,
+                        include_untracked: true,
+                    },
+                    path_filter,
+                )?,
+            ),
+            None => (
+                ReportSource::Branch,
+                git.get_diffs(
+                    DiffTarget::Branch {
+                        repo_path,
+                        branch_name: branch,
+                        base_branch: target_branch,
+                    },
+                    path_filter,
+                )?,
+            ),
+        };
+        return Ok(Some(ReportRepoChanges {
+            source,
+            commits,
+            files: diffs_to_file_diffs(diffs, &DiffFormatOptions::default()),
+        }));
+    }
+
+    let Some(merge_commit) = merge_commit else {
+        return Ok(None);
+    };
+    let commit = git.get_commit_info(repo_path, merge_commit)?;
+    let diffs = git.get_diffs(
+        DiffTarget::Commit {
+            repo_path,
+            commit_sha: merge_commit,
+        },
+        path_filter,
+    )?;
+    Ok(Some(ReportRepoChanges {
+        source: ReportSource::MergeCommit,
+        commits: vec![commit],
+        files: diffs_to_file_diffs(diffs, &DiffFormatOptions::default()),
+    }))
+}
+
+fn write_report_repo_section(
+    report: &mut String,
+    repo: &RepoWithTargetBranch,
+    merges: &[&Merge],
+    changes: Result<Option<ReportRepoChanges>, String>,
+) {
+    let _ = writeln!(report, "## {} → `{}`\n", repo.repo.name, repo.target_branch);
+
+    report.push_str("### Merge\n\n");
+    if merges.is_empty() {
+        report.push_str("Not merged.\n\n");
+    }
+    for merge in merges {
+        match merge {
+            Merge::Direct(direct) => {
+                let _ = writeln!(
+                    report,
+                    "- Merged into `{}` as `{}` on {}",
+                    direct.target_branch_name,
+                    direct.merge_commit,
+                    direct.created_at.to_rfc3339()
+                );
+            }
+            Merge::Pr(pr) => {
+                let _ = write!(
+                    report,
+                    "- PR #{} into `{}` ({:?}): {}",
+                    pr.pr_info.number, pr.target_branch_name, pr.pr_info.status, pr.pr_info.url
+                );
+                if let Some(sha) = &pr.pr_info.merge_commit_sha {
+                    let _ = write!(report, ", merged as `{sha}`");
+                }
+                report.push('\n');
+            }
+        }
+    }
+    if !merges.is_empty() {
+        report.push('\n');
+    }
+
+    let changes = match changes {
+        Ok(Some(changes)) => changes,
+        Ok(None) => {
+            report.push_str("The workspace branch no longer exists and was never merged.\n\n");
+            return;
+        }
+        Err(e) => {
+            let _ = writeln!(report, "Changes could not be read: {e}\n");
+            return;
+        }
+    };
+
+    report.push_str("### Commits\n\n");
+    if let ReportSource::MergeCommit = changes.source {
+        report.push_str(
+            "The workspace branch was deleted after merging; its squash commit is shown.\n\n",
+        );
+    }
+    if changes.commits.is_empty() {
+        report.push_str("No commits.\n");
+    }
+    for commit in &changes.commits {
+        let _ = writeln!(
+            report,
+            "- `{}` {} — {}, {}",
+            &commit.commit_sha[..commit.commit_sha.len().min(7)],
+            commit.summary,
+            commit.author,
+            commit.committed_at.to_rfc3339()
+        );
+    }
+
+    report.push_str("\n### Diff\n\n");
+    if let ReportSource::Worktree = changes.source {
+        report.push_str("Includes uncommitted changes in the worktree.\n\n");
+    }
+    if changes.files.is_empty() {
+        report.push_str("No changes.\n\n");
+    }
+    for file in &changes.files {
+        let _ = writeln!(
+            report,
+            "#### {} (+{} -{})\n\n````diff\n{}\n````\n",
+            file.path,
+            file.additions,
+            file.deletions,
+            file.diff_content.trim_end()
+        );
+    }
+}
+
+/// Download a markdown report of everything the workspace did: task, agent prompts and
+/// summaries, commits, full diff and merge records. Works after the worktree has been
+/// cleaned up, falling back to the branch or the recorded merge commit.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_report(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+    let workspace_repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+    let merges = Merge::find_by_workspace_id(pool, workspace.id).await?;
+
+    let mut report = String::new();
+    let _ = writeln!(report, "# {}\n", task.title);
+    let _ = writeln!(report, "- Workspace: `{}`", workspace.id);
+    if let Some(name) = &workspace.name {
+        let _ = writeln!(report, "- Name: {name}");
+    }
+    let _ = writeln!(report, "- Branch: `{}`", workspace.branch);
+    let _ = writeln!(report, "- Created: {}", workspace.created_at.to_rfc3339());
+    let _ = writeln!(report, "- Report generated: {}\n", Utc::now().to_rfc3339());
+    if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        let _ = writeln!(report, "## Task\n\n{}\n", description.trim_end());
+    }
+
+    report.push_str("## Agent turns\n\n");
+    let mut turn_number = 0;
+    // Sessions come most recently used first; the report reads oldest first
+    for session in Session::find_by_workspace_id(pool, workspace.id)
+        .await?
+        .into_iter()
+        .rev()
+    {
+        let turns = CodingAgentTurn::find_page_by_session_id(pool, session.id, i64::MAX, 0).await?;
+        for turn in turns {
+            turn_number += 1;
+            let _ = write!(
+                report,
+                "### Turn {turn_number} ({})",
+                turn.created_at.to_rfc3339()
+            );
+            if let Some(executor) = &session.executor {
+                let _ = write!(report, " — {executor}");
+            }
+            report.push_str("\n\n");
+            if let Some(prompt) = &turn.prompt {
+                let _ = writeln!(
+                    report,
+                    "**Prompt**\n\n````text\n{}\n````\n",
+                    prompt.trim_end()
+                );
+            }
+            if let Some(summary) = &turn.summary {
+                let _ = writeln!(report, "**Summary**\n\n{}\n", summary.trim_end());
+            }
+        }
+    }
+    if turn_number == 0 {
+        report.push_str("No coding agent turns.\n\n");
+    }
+
+    for repo_with_branch in &workspace_repos {
+        let repo_merges: Vec<&Merge> = merges
+            .iter()
+            .filter(|merge| match merge {
+                Merge::Direct(direct) => direct.repo_id == repo_with_branch.repo.id,
+                Merge::Pr(pr) => pr.repo_id == repo_with_branch.repo.id,
+            })
+            .collect();
+        let merge_commit = repo_merges.iter().find_map(|merge| match merge {
+            Merge::Direct(direct) => Some(direct.merge_commit.clone()),
+            Merge::Pr(pr) => pr.pr_info.merge_commit_sha.clone(),
+        });
+
+        let changes = tokio::task::spawn_blocking({
+            let git = deployment.git().clone();
+            let repo_path = repo_with_branch.repo.path.clone();
+            let worktree_path = workspace.container_ref.as_ref().map(|container_ref| {
+                PathBuf::from(container_ref).join(&repo_with_branch.repo.name)
+            });
+            let branch = workspace.branch.clone();
+            let target_branch = repo_with_branch.target_branch.clone();
+            let path_scope = repo_with_branch.path_scope.clone();
+            move || {
+                collect_report_repo_changes(
+                    &git,
+                    &repo_path,
+                    worktree_path,
+                    &branch,
+                    &target_branch,
+                    merge_commit.as_deref(),
+                    path_scope.as_deref(),
+                )
+            }
+        })
+        .await
+        .map_err(std::io::Error::other)?
+        .map_err(|e| e.to_string());
+
+        write_report_repo_section(&mut report, repo_with_branch, &repo_merges, changes);
+    }
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/markdown; charset=utf-8".to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"workspace-{workspace_id}-report.md\""),
+            ),
+        ],
+        report,
+    )
+        .into_response())
+}
+
+/// Get the description stored for the workspace's merge commit and pull request.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_merge_description(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Option<WorkspaceMergeDescription>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let description = WorkspaceMergeDescription::find_by_workspace_id(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(description)))
+}
+
+/// Describe the workspace's changes from its commits, diff stats and the last agent
+/// summary, and store the description for the merge commit and pull request, replacing any
+/// previous one.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn generate_merge_description(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceMergeDescription>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+    let workspace_repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+    let agent_summary =
+        CodingAgentTurn::find_latest_summary_by_workspace_id(pool, workspace.id).await?;
+
+    let mut repo_changes = Vec::with_capacity(workspace_repos.len());
+    for repo_with_branch in &workspace_repos {
+        let changes = tokio::task::spawn_blocking({
+            let git = deployment.git().clone();
+            let repo_path = repo_with_branch.repo.path.clone();
+            let worktree_path = workspace.container_ref.as_ref().map(|container_ref| {
+                PathBuf::from(container_ref).join(&repo_with_branch.repo.name)
+            });
+            let branch = workspace.branch.clone();
+            let target_branch = repo_with_branch.target_branch.clone();
+            let path_scope = repo_with_branch.path_scope.clone();
+            move || {
+                collect_report_repo_changes(
+                    &git,
+                    &repo_path,
+                    worktree_path,
+                    &branch,
+                    &target_branch,
                     None,
+                    path_scope.as_deref(),
                 )
             }
         })
+        .await
+        .map_err(std::io::Error::other)??;
+        if let Some(changes) = changes {
+            repo_changes.push((&repo_with_branch.repo, changes));
+        }
+    }
+
+    let repos: Vec<MergeDescriptionRepo> = repo_changes
+        .iter()
+        .map(|(repo, changes)| MergeDescriptionRepo {
+            repo_name: &repo.display_name,
+            commits: &changes.commits,
+            files_changed: changes.files.len(),
+            additions: changes.files.iter().map(|file| file.additions).sum(),
+            deletions: changes.files.iter().map(|file| file.deletions).sum(),
+        })
+        .collect();
+    let (title, body) = MergeDescriptionService::generate(
+        &task.title,
+        task.description.as_deref(),
+        agent_summary.as_deref(),
+        &repos,
+    );
+
+    let description = WorkspaceMergeDescription::set(
+        pool,
+        workspace_id,
+        &SetWorkspaceMergeDescription { title, body },
+        false,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(description)))
+}
+
+/// Replace the workspace's merge description, e.g. with an edited version of the
+/// generated one.
+/// Returns 404 if workspace not found.
+/// Returns 400 if the title is empty.
+#[axum::debug_handler]
+pub async fn set_merge_description(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<SetWorkspaceMergeDescription>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceMergeDescription>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    if payload.title.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Description title cannot be empty".to_string(),
+        ));
+    }
+
+    let description = WorkspaceMergeDescription::set(pool, workspace_id, &payload, true).await?;
+    Ok(ResponseJson(ApiResponse::success(description)))
+}
+
+/// Drop the workspace's merge description, so merges use the default commit message again.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn delete_merge_description(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    WorkspaceMergeDescription::delete(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Request body for adding a repo to an open workspace
+#[derive(Debug, Deserialize, TS)]
+pub struct AddWorkspaceRepoRequest {
+    pub repo_id: Uuid,
+    /// Branch the workspace targets in the repo; unset uses the project's default target
+    /// branch for the repo, then the repo's own, then the branch checked out in the repo
+    #[ts(optional)]
+    pub target_branch: Option<String>,
+}
+
+/// Add a worktree for another of the project's repos to an open workspace, on a new branch
+/// named like the workspace's other branches. Diffs and the close include the repo from then
+/// on; live diff streams pick it up when they reconnect.
+/// Returns 404 if workspace not found.
+/// Returns 400 if the workspace is archived or closed, or the repo is not part of the task's
+/// project or already part of the workspace.
+/// Returns 409 if the workspace's branch already exists in the repo.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn add_workspace_repo(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<AddWorkspaceRepoRequest>,
+) -> Result<ResponseJson<ApiResponse<RepoWithTargetBranch>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    if workspace.archived || workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Repos can only be added to an open workspace".to_string(),
+        ));
+    }
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::AddRepo)?;
+
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+    let input = WorkspaceRepoInput {
+        repo_id: payload.repo_id,
+        target_branch: payload.target_branch,
+    };
+    let input = select_workspace_repos(pool, task.project_id, vec![input])
+        .await?
+        .remove(0);
+    let existing_repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace_id).await?;
+    if existing_repos.iter().any(|repo| repo.id == input.repo_id) {
+        return Err(ApiError::BadRequest(format!(
+            "Repo {} is already part of the workspace",
+            input.repo_id
+        )));
+    }
+    let repo = Repo::find_by_id(pool, input.repo_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Repo {} not found", input.repo_id)))?;
+    if deployment
+        .git()
+        .check_branch_exists(&repo.path, &workspace.branch)?
+    {
+        return Err(ApiError::Conflict(format!(
+            "Branch '{}' already exists in repo '{}'",
+            workspace.branch, repo.name
+        )));
+    }
+    let target_branch =
+        resolve_target_branch(deployment.git(), pool, task.project_id, &input).await?;
+    let sparse_checkout_dir = ProjectRepo::find_by_project_and_repo(pool, task.project_id, repo.id)
+        .await?
+        .filter(|project_repo| project_repo.sparse_checkout)
+        .and_then(|project_repo| project_repo.path_scope);
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    WorkspaceManager::add_repo_worktree(
+        std::path::Path::new(&container_ref),
+        &existing_repos,
+        &RepoWorkspaceInput::new(repo.clone(), target_branch.clone())
+            .with_sparse_checkout(sparse_checkout_dir),
+        &workspace.branch,
+    )
+    .await
+    .map_err(|e| match e {
+        WorkspaceManagerError::RepoNameCollision(..) => ApiError::BadRequest(e.to_string()),
+        e => ApiError::Container(e.into()),
+    })?;
+    WorkspaceRepo::create_many(
+        pool,
+        workspace_id,
+        &[CreateWorkspaceRepo {
+            repo_id: repo.id,
+            target_branch,
+            start_from_ref: None,
+        }],
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_repo_added",
+            serde_json::json!({
+                "workspace_id": workspace_id.to_string(),
+                "repo_id": repo.id.to_string(),
+            }),
+        )
+        .await;
+
+    let added = WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace_id)
+        .await?
+        .into_iter()
+        .find(|r| r.repo.id == repo.id)
+        .ok_or_else(|| ApiError::NotFound(format!("Repo {} not found", repo.id)))?;
+    Ok(ResponseJson(ApiResponse::success(added)))
+}
+
+/// Query parameters for removing a repo from a workspace
+#[derive(Debug, Deserialize)]
+pub struct RemoveWorkspaceRepoQuery {
+    /// Remove the repo even if the workspace has commits or uncommitted changes in it,
+    /// discarding them
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Remove one repo's worktree and branch from an open workspace, so closing the workspace
+/// does not merge an empty branch into that repo.
+/// Returns 404 if workspace not found or the repo is not part of it.
+/// Returns 400 if the workspace is archived or closed or has running processes, or the repo
+/// is the workspace's only repo or the one its agent works in.
+/// Returns 409 if the workspace has commits or uncommitted changes in the repo (pass
+/// `?force=true` to discard them), or a workspace stacked on this one includes the repo.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn remove_workspace_repo(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, repo_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<RemoveWorkspaceRepoQuery>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let Some(container_ref) = workspace
+        .container_ref
+        .as_deref()
+        .filter(|_| !workspace.archived)
+    else {
+        return Err(ApiError::BadRequest(
+            "Repos can only be removed from an open workspace".to_string(),
+        ));
+    };
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::RemoveRepo)?;
+    remove_repo_from_workspace(
+        deployment.git(),
+        pool,
+        &workspace,
+        container_ref,
+        repo_id,
+        query.force,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_repo_removed",
+            serde_json::json!({
+                "workspace_id": workspace_id.to_string(),
+                "repo_id": repo_id.to_string(),
+                "forced": query.force,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Remove `repo_id`'s worktree and branch from `workspace`, whose worktrees live in
+/// `container_ref`, refusing when that would lose work or break the workspace
+async fn remove_repo_from_workspace(
+    git: &git::GitService,
+    pool: &sqlx::SqlitePool,
+    workspace: &Workspace,
+    container_ref: &str,
+    repo_id: Uuid,
+    force: bool,
+) -> Result<(), ApiError> {
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot remove a repo from a workspace with running processes".to_string(),
+        ));
+    }
+
+    let repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+    let RepoWithTargetBranch {
+        repo,
+        target_branch,
+        ..
+    } = repos
+        .iter()
+        .find(|r| r.repo.id == repo_id)
+        .cloned()
+        .ok_or_else(|| ApiError::NotFound(format!("Repo {repo_id} not found in workspace")))?;
+    if repos.len() == 1 {
+        return Err(ApiError::BadRequest(
+            "Cannot remove the only repo of a workspace".to_string(),
+        ));
+    }
+    if workspace.agent_working_dir.as_deref() == Some(repo.name.as_str()) {
+        return Err(ApiError::BadRequest(format!(
+            "Cannot remove repo '{}': the workspace's agent works in it",
+            repo.name
+        )));
+    }
+    for child in WorkspaceStack::find_active_children(pool, workspace.id).await? {
+        if WorkspaceRepo::find_by_workspace_and_repo_id(pool, child.id, repo_id)
+            .await?
+            .is_some()
+        {
+            return Err(ApiError::Conflict(format!(
+                "Workspace {} is stacked on this one in repo '{}'",
+                child.id, repo.name
+            )));
+        }
+    }
+
+    let worktree_path = PathBuf::from(container_ref).join(&repo.name);
+    if !force {
+        let (ahead, _) = git.get_branch_status(&repo.path, &workspace.branch, &target_branch)?;
+        let clean = !worktree_path.exists() || git.is_worktree_clean(&worktree_path)?;
+        if ahead > 0 || !clean {
+            return Err(ApiError::Conflict(format!(
+                "The workspace has changes in repo '{}'; pass ?force=true to discard them",
+                repo.name
+            )));
+        }
+    }
+
+    WorktreeManager::cleanup_worktree(&WorktreeCleanup::new(
+        worktree_path,
+        Some(repo.path.clone()),
+    ))
+    .await?;
+    if let Err(e) = git.delete_branch(&repo.path, &workspace.branch) {
+        tracing::warn!(
+            "Failed to delete branch '{}' of repo '{}': {}",
+            workspace.branch,
+            repo.name,
+            e
+        );
+    }
+    WorkspaceRepo::delete(pool, workspace.id, repo_id).await?;
+    Ok(())
+}
+
+/// Version that discarded files are restored to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscardBase {
+    /// Where the workspace branch left the target branch, dropping the branch's changes too
+    #[default]
+    MergeBase,
+    /// The workspace branch head, dropping only uncommitted changes
+    Head,
+    /// The repo's target branch as it is now, which may have moved past the merge base
+    TargetBranch,
+}
+
+impl DiscardBase {
+    fn version_name(self) -> &'static str {
+        match self {
+            DiscardBase::MergeBase => "merge base",
+            DiscardBase::Head => "branch head",
+            DiscardBase::TargetBranch => "target branch",
+        }
+    }
+}
+
+/// A worktree file to restore
+#[derive(Debug, Deserialize, TS)]
+pub struct DiscardFile {
+    pub repo_id: Uuid,
+    /// Path relative to the repo root
+    pub path: String,
+}
+
+/// Request body for discarding changes to some files of a workspace
+#[derive(Debug, Deserialize, TS)]
+pub struct DiscardFilesRequest {
+    pub files: Vec<DiscardFile>,
+    #[serde(default)]
+    pub base: DiscardBase,
+}
+
+/// A file whose changes were discarded
+#[derive(Debug, Serialize, TS)]
+pub struct DiscardedFile {
+    pub repo_id: Uuid,
+    pub path: String,
+    /// Commit the file was restored from
+    pub restored_from: String,
+    /// Whether the file did not exist there and was removed
+    pub removed: bool,
+}
+
+/// Restore selected worktree files, across the workspace's repos, to their version at the
+/// merge base with the target branch, at the workspace branch head or on the target branch.
+/// Files that did not exist there are removed. The reverted paths are recorded as a
+/// workspace event.
+/// Returns 404 if workspace not found or a repo is not part of it.
+/// Returns 400 if the workspace is archived or closed or has running processes, if no files
+/// are given, or if a path is absolute or escapes its repo.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn discard_workspace_files(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(request): Json<DiscardFilesRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<DiscardedFile>>>, ApiError> {
+    let discarded =
+        restore_workspace_files(&deployment, workspace_id, &request.files, request.base).await?;
+    Ok(ResponseJson(ApiResponse::success(discarded)))
+}
+
+/// Request body for retaking a file from the target branch
+#[derive(Debug, Deserialize, TS)]
+pub struct RestoreFileFromTargetRequest {
+    pub repo_id: Uuid,
+    /// Path relative to the repo root
+    pub path: String,
+}
+
+/// Replace a worktree file with its version currently on the repo's target branch, e.g. a
+/// generated file to retake rather than merge after the target moved. The file is removed
+/// if the target branch does not have it. Recorded as a workspace event.
+/// Returns 404 if workspace not found or the repo is not part of it.
+/// Returns 400 if the workspace is archived or closed or has running processes, or if the
+/// path is absolute or escapes the repo.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn restore_workspace_file_from_target(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(request): Json<RestoreFileFromTargetRequest>,
+) -> Result<ResponseJson<ApiResponse<DiscardedFile>>, ApiError> {
+    let file = DiscardFile {
+        repo_id: request.repo_id,
+        path: request.path,
+    };
+    let restored = restore_workspace_files(
+        &deployment,
+        workspace_id,
+        std::slice::from_ref(&file),
+        DiscardBase::TargetBranch,
+    )
+    .await?
+    .pop()
+    .ok_or_else(|| ApiError::BadRequest("No file was restored".to_string()))?;
+    Ok(ResponseJson(ApiResponse::success(restored)))
+}
+
+async fn restore_workspace_files(
+    deployment: &DeploymentImpl,
+    workspace_id: Uuid,
+    files: &[DiscardFile],
+    base: DiscardBase,
+) -> Result<Vec<DiscardedFile>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let Some(container_ref) = workspace
+        .container_ref
+        .as_deref()
+        .filter(|_| !workspace.archived)
+    else {
+        return Err(ApiError::BadRequest(
+            "Files can only be discarded in an open workspace".to_string(),
+        ));
+    };
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::DiscardFiles)?;
+    let discarded = restore_files(
+        deployment.git(),
+        pool,
+        &workspace,
+        container_ref,
+        files,
+        base,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_files_discarded",
+            serde_json::json!({
+                "workspace_id": workspace_id.to_string(),
+                "file_count": discarded.len(),
+                "base": base.version_name(),
+            }),
+        )
         .await;
 
-        if let Ok(Ok(diffs)) = diffs_result {
-            for diff in diffs {
-                // Determine file path (prefer new_path, fall back to old_path)
-                let path = diff
-                    .new_path
-                    .clone()
-                    .or(diff.old_path.clone())
-                    .unwrap_or_else(|| "unknown".to_string());
-
-                // Compute unified diff content
-                let diff_content = if diff.content_omitted {
-                    "[Content omitted - file too large]".to_string()
-                } else {
-                    let old = diff.old_content.as_deref().unwrap_or("");
-                    let new = diff.new_content.as_deref().unwrap_or("");
-                    if old.is_empty() && new.is_empty() {
-                        String::new()
-                    } else {
-                        create_unified_diff(&path, old, new)
-                    }
-                };
+    Ok(discarded)
+}
 
-                all_files.push(FileDiff {
-                    path,
-                    additions: diff.additions.unwrap_or(0),
-                    deletions: diff.deletions.unwrap_or(0),
-                    diff_content,
-                });
-            }
+/// Restore `files` in the worktrees of `workspace`, which live in `container_ref`, to their
+/// version at `base`, and record it as a workspace event
+async fn restore_files(
+    git: &git::GitService,
+    pool: &sqlx::SqlitePool,
+    workspace: &Workspace,
+    container_ref: &str,
+    files: &[DiscardFile],
+    base: DiscardBase,
+) -> Result<Vec<DiscardedFile>, ApiError> {
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot discard files while processes are running".to_string(),
+        ));
+    }
+    if files.is_empty() {
+        return Err(ApiError::BadRequest("No files given".to_string()));
+    }
+
+    // Group the paths by repo, keeping the order repos were first named in
+    let repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+    let mut by_repo: Vec<(&RepoWithTargetBranch, Vec<String>)> = Vec::new();
+    for file in files {
+        if file.path.is_empty()
+            || !std::path::Path::new(&file.path)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(ApiError::BadRequest(format!(
+                "Path '{}' must be relative to the repo root and stay inside it",
+                file.path
+            )));
+        }
+        let repo = repos
+            .iter()
+            .find(|r| r.repo.id == file.repo_id)
+            .ok_or_else(|| {
+                ApiError::NotFound(format!("Repo {} not found in workspace", file.repo_id))
+            })?;
+        match by_repo.iter_mut().find(|(r, _)| r.repo.id == repo.repo.id) {
+            Some((_, paths)) => paths.push(file.path.clone()),
+            None => by_repo.push((repo, vec![file.path.clone()])),
         }
     }
 
-    Ok(ResponseJson(ApiResponse::success(WorkspaceDiffResponse {
-        workspace_id: workspace_id.to_string(),
-        files: all_files,
+    let mut discarded = Vec::new();
+    let mut listed = Vec::new();
+    for (repo, paths) in by_repo {
+        let worktree_path = PathBuf::from(container_ref).join(&repo.repo.name);
+        let source = match base {
+            DiscardBase::MergeBase => git
+                .get_base_commit(&repo.repo.path, &workspace.branch, &repo.target_branch)?
+                .to_string(),
+            DiscardBase::Head => git.get_head_info(&worktree_path)?.oid,
+            DiscardBase::TargetBranch => git
+                .resolve_revision(&repo.repo.path, &repo.target_branch)?
+                .to_string(),
+        };
+        let removed = git.restore_paths(&worktree_path, &source, &paths)?;
+        listed.extend(
+            paths
+                .iter()
+                .map(|path| format!("{}/{}", repo.repo.name, path)),
+        );
+        discarded.extend(paths.into_iter().map(|path| DiscardedFile {
+            repo_id: repo.repo.id,
+            removed: removed.contains(&path),
+            path,
+            restored_from: source.clone(),
+        }));
+    }
+
+    WorkspaceEvent::create(
+        pool,
+        workspace.id,
+        WorkspaceEventKind::FilesDiscarded,
+        &format!(
+            "Discarded changes to {}, restoring the {} version",
+            listed.join(", "),
+            base.version_name()
+        ),
+    )
+    .await?;
+
+    Ok(discarded)
+}
+
+/// What closing with a merge would do in one repo of a workspace
+#[derive(Debug, Serialize, TS)]
+pub struct RepoMergePreview {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub target_branch: String,
+    /// Commits on the workspace branch that are not on the target branch
+    pub commits_ahead: usize,
+    /// Commits on the target branch since the workspace branched off it
+    pub commits_behind: usize,
+    /// Files that would conflict; empty when the repo merges cleanly
+    pub conflicted_files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceMergePreview {
+    pub has_conflicts: bool,
+    pub repos: Vec<RepoMergePreview>,
+}
+
+/// Work out, per repo, whether merging the workspace branch into its target branch would
+/// conflict and in which files. The merge is done in memory, so no branch or worktree
+/// changes; only committed work is considered.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_merge_preview(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceMergePreview>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+
+    let git = deployment.git();
+    let mut repos = Vec::new();
+    for repo in
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace_id).await?
+    {
+        let preview = git.preview_merge(&repo.repo.path, &repo.target_branch, &workspace.branch)?;
+        repos.push(RepoMergePreview {
+            repo_id: repo.repo.id,
+            repo_name: repo.repo.name,
+            target_branch: repo.target_branch,
+            commits_ahead: preview.commits_ahead,
+            commits_behind: preview.commits_behind,
+            conflicted_files: preview.conflicted_files,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(WorkspaceMergePreview {
+        has_conflicts: repos.iter().any(|r| !r.conflicted_files.is_empty()),
+        repos,
     })))
 }
 
-/// Close a workspace with merge or discard strategy.
+/// How one repo's target branch would change when the workspace is merged into it
+#[derive(Debug, Serialize, TS)]
+pub struct RepoLandingDiff {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub target_branch: String,
+    /// Files that would conflict; when any do, there is no merge result to diff and `files`
+    /// is empty
+    pub conflicted_files: Vec<String>,
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+    pub files: Vec<FileDiff>,
+}
+
+/// Get, per repo, the diff from the target branch as it is now to the result of merging the
+/// workspace branch into it. Unlike the workspace diff, which is against the merge base, this
+/// is what lands once the target branch has moved. The merge is done in memory and only
+/// committed work is considered. Takes the same formatting parameters as the diff.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_landing_diff(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(format): Query<DiffFormatOptions>,
+) -> Result<ResponseJson<ApiResponse<Vec<RepoLandingDiff>>>, ApiError> {
+    format.validate().map_err(ApiError::BadRequest)?;
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+
+    let git = deployment.git();
+    let mut repos = Vec::new();
+    for repo in
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace_id).await?
+    {
+        let diffs = git.get_diffs_with_options(
+            DiffTarget::Merge {
+                repo_path: &repo.repo.path,
+                branch_name: &workspace.branch,
+                base_branch: &repo.target_branch,
+            },
+            None,
+            &format,
+        );
+        let (conflicted_files, files) = match diffs {
+            Ok(diffs) => (Vec::new(), diffs_to_file_diffs(diffs, &format)),
+            Err(git::GitServiceError::MergeConflicts {
+                conflicted_files, ..
+            }) => (conflicted_files, Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        repos.push(RepoLandingDiff {
+            repo_id: repo.repo.id,
+            repo_name: repo.repo.name,
+            target_branch: repo.target_branch,
+            conflicted_files,
+            files_changed: files.len(),
+            additions: files.iter().map(|f| f.additions).sum(),
+            deletions: files.iter().map(|f| f.deletions).sum(),
+            files,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(repos)))
+}
+
+/// Resolve each repo's close strategy: its own entry in `request`, else the request's
+/// strategy, else the project's default. Also returns the entries keyed by repo.
+fn resolve_close_strategies<'a>(
+    workspace_repos: &[RepoWithTargetBranch],
+    request: &'a CloseWorkspaceRequest,
+    project_default: Option<MergeStrategy>,
+) -> Result<
+    (
+        HashMap<Uuid, &'a RepoCloseRequest>,
+        HashMap<Uuid, RepoCloseStrategy>,
+    ),
+    ApiError,
+> {
+    let mut repo_requests: HashMap<Uuid, &RepoCloseRequest> = HashMap::new();
+    for entry in &request.repos {
+        if !workspace_repos.iter().any(|r| r.repo.id == entry.repo_id) {
+            return Err(ApiError::BadRequest(format!(
+                "Repo {} is not part of the workspace",
+                entry.repo_id
+            )));
+        }
+        if repo_requests.insert(entry.repo_id, entry).is_some() {
+            return Err(ApiError::BadRequest(format!(
+                "Repo {} is listed more than once",
+                entry.repo_id
+            )));
+        }
+    }
+    let default_strategy = match request.strategy.as_deref() {
+        Some("merge") => Some(RepoCloseStrategy::Merge),
+        Some("discard") => Some(RepoCloseStrategy::Discard),
+        Some("pr") => Some(RepoCloseStrategy::Pr),
+        Some(strategy) => {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid strategy '{}'. Must be 'merge', 'discard' or 'pr'",
+                strategy
+            )));
+        }
+        None => project_default.map(RepoCloseStrategy::from),
+    };
+    let mut strategies = HashMap::new();
+    for workspace_repo in workspace_repos {
+        let strategy = match repo_requests.get(&workspace_repo.repo.id) {
+            Some(entry) => entry.strategy,
+            None => default_strategy.ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "A strategy is required for repo '{}'; the project has no default merge strategy",
+                    workspace_repo.repo.name
+                ))
+            })?,
+        };
+        strategies.insert(workspace_repo.repo.id, strategy);
+    }
+    Ok((repo_requests, strategies))
+}
+
+/// Close a workspace with merge, discard or pr strategy, or the project's default strategy when
+/// the request names none. `repos` gives individual repos their own strategy, so one close can
+/// merge one repo, discard another and open a PR for a third; the response reports each repo.
+/// PRs are opened before anything is merged, and the branches they come from are kept. Quality
+/// gates are skipped when the project's settings turn them off, and only run when a repo is
+/// merged. They run in the background: a close that finds no current run of them starts one
+/// and returns 202 with its execution processes, and a close while they run returns 409 with
+/// the processes still running.
 /// Returns 404 if workspace not found.
-/// Returns 400 if workspace already closed (no container_ref) or has running processes.
-/// Returns 409 on merge conflicts.
+/// Returns 400 if workspace already closed (no container_ref) or has running processes, if a
+/// listed repo is not part of the workspace or is listed twice, if a repo has no strategy and
+/// the project has no default, or if a PR cannot be opened.
+/// Returns 423 if another mutating operation is already running for the workspace.
+/// Returns 409 on merge conflicts, when a project quality gate fails, when CI has not
+/// passed for a repo that requires it (pass `?force=true` to skip the CI check), when the
+/// project requires an approving review the workspace does not have, or when an
+/// `If-Match` precondition on the workspace's `updated_at` no longer holds. When the
+/// project has an agent review policy, a close without a current review starts one and
+/// returns 409, and unresolved findings at the blocking severity return 409
+/// (`?skip_ai_review=true` skips the review; `?force=true` does not).
 #[axum::debug_handler]
 pub async fn close_workspace(
     State(deployment): State<DeploymentImpl>,
     Path(workspace_id): Path<Uuid>,
+    Query(query): Query<CloseWorkspaceQuery>,
+    if_match: IfMatch,
     Json(request): Json<CloseWorkspaceRequest>,
-) -> Result<ResponseJson<ApiResponse<CloseWorkspaceResponse>>, ApiError> {
+) -> Result<
+    (
+        StatusCode,
+        ResponseJson<ApiResponse<CloseWorkspaceResponse, CloseWorkspaceError>>,
+    ),
+    ApiError,
+> {
     let pool = &deployment.db().pool;
 
     // Find workspace, return 404 if not found
     let workspace = Workspace::find_by_id(pool, workspace_id)
         .await?
         .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Close)?;
+    if let Err(stale) = if_match.check(workspace.updated_at, &workspace) {
+        return Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::error_with_data(
+                CloseWorkspaceError::StaleVersion {
+                    expected_version: stale.expected_version,
+                    current: stale.current,
+                },
+            )),
+        ));
+    }
 
     // Return 400 if workspace already closed (no container_ref)
     let container_ref = workspace.container_ref.as_ref().ok_or_else(|| {
         ApiError::BadRequest("Workspace already closed (no active worktree)".to_string())
     })?;
 
-    // Check for running processes
-    let has_running = ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+    let running_gates = QualityGateService::running_for_workspace(pool, workspace_id).await?;
+    if !running_gates.is_empty() {
+        return Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::error_with_data(
+                CloseWorkspaceError::QualityGatesRunning {
+                    execution_process_ids: running_gates,
+                },
+            )),
+        ));
+    }
+
+    // Check for running processes
+    let has_running = ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+        pool,
+        workspace_id,
+    )
+    .await?;
+    if has_running {
+        return Err(ApiError::BadRequest(
+            "Cannot close workspace with running processes".to_string(),
+        ));
+    }
+
+    let workspace_repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace_id).await?;
+    let project_settings = ProjectSettings::find_by_task_id(pool, workspace.task_id).await?;
+    let (repo_requests, strategies) = resolve_close_strategies(
+        &workspace_repos,
+        &request,
+        project_settings
+            .as_ref()
+            .and_then(|settings| settings.default_merge_strategy),
+    )?;
+    let repos_closed_with = |strategy: RepoCloseStrategy| -> Vec<&RepoWithTargetBranch> {
+        workspace_repos
+            .iter()
+            .filter(|r| strategies[&r.repo.id] == strategy)
+            .collect()
+    };
+    let merge_repos = repos_closed_with(RepoCloseStrategy::Merge);
+    let pr_repos = repos_closed_with(RepoCloseStrategy::Pr);
+    let discarded = repos_closed_with(RepoCloseStrategy::Discard).len();
+
+    let children = WorkspaceStack::find_active_children(pool, workspace_id).await?;
+    if !children.is_empty() && request.stacked_children.is_none() {
+        return Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::error_with_data(
+                CloseWorkspaceError::HasStackedChildren { children },
+            )),
+        ));
+    }
+
+    let repos: Vec<_> = workspace_repos.iter().map(|r| r.repo.clone()).collect();
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+
+    // CI, approval, agent review and quality gates only guard repos that are merged
+    let mut review_warnings = Vec::new();
+    if !merge_repos.is_empty() {
+        let repos_to_merge: Vec<_> = merge_repos.iter().map(|r| r.repo.clone()).collect();
+        if !query.force
+            && let Some(error) = check_required_ci(&deployment, &workspace, &repos_to_merge).await
+        {
+            return Ok((
+                StatusCode::CONFLICT,
+                ResponseJson(ApiResponse::error_with_data(error)),
+            ));
+        }
+
+        if let Some(error) = check_merge_approval(pool, task.project_id, workspace_id).await? {
+            return Ok((
+                StatusCode::CONFLICT,
+                ResponseJson(ApiResponse::error_with_data(error)),
+            ));
+        }
+
+        match check_ai_review(pool, task.project_id, workspace_id, &query).await? {
+            AiReviewCheck::NotRequired => {}
+            AiReviewCheck::Start(executor_profile_id) => {
+                let review =
+                    start_ai_review(&deployment, &workspace, &task, executor_profile_id).await?;
+                return Ok((
+                    StatusCode::CONFLICT,
+                    ResponseJson(ApiResponse::error_with_data(
+                        CloseWorkspaceError::AiReviewStarted { review },
+                    )),
+                ));
+            }
+            AiReviewCheck::Blocked(error) => {
+                return Ok((
+                    StatusCode::CONFLICT,
+                    ResponseJson(ApiResponse::error_with_data(error)),
+                ));
+            }
+            AiReviewCheck::Passed(warnings) => review_warnings = warnings,
+        }
+
+        // Project quality gates must have passed in every worktree on the current HEAD
+        // commits before the target branches are touched
+        let run_quality_gates = project_settings
+            .as_ref()
+            .is_none_or(|settings| settings.run_quality_gates);
+        let workspace_dir = PathBuf::from(container_ref);
+        if run_quality_gates {
+            match QualityGateService::status_for_workspace(
+                pool,
+                deployment.git(),
+                &workspace,
+                task.project_id,
+                &workspace_dir,
+            )
+            .await?
+            {
+                QualityGateStatus::Passed => {}
+                QualityGateStatus::Failed(failure) => {
+                    return Ok((
+                        StatusCode::CONFLICT,
+                        ResponseJson(ApiResponse::error_with_data(
+                            CloseWorkspaceError::QualityGateFailed { failure },
+                        )),
+                    ));
+                }
+                QualityGateStatus::Outdated => {
+                    let redactor = OutputRedactor::for_workspace(
+                        pool,
+                        task.project_id,
+                        workspace.id,
+                        &deployment.settings().output_redaction_patterns(),
+                    )
+                    .await?;
+                    let execution_process_ids = QualityGateService::start_for_workspace(
+                        pool,
+                        deployment.git(),
+                        &workspace,
+                        task.project_id,
+                        &workspace_dir,
+                        redactor,
+                    )
+                    .await?;
+                    return Ok((
+                        StatusCode::ACCEPTED,
+                        ResponseJson(ApiResponse::error_with_data(
+                            CloseWorkspaceError::QualityGatesStarted {
+                                execution_process_ids,
+                            },
+                        )),
+                    ));
+                }
+            }
+        }
+    }
+
+    // Open PRs before merging anything, so a PR that cannot be opened leaves every target
+    // branch untouched. A PR still open from an earlier close attempt is reused.
+    let mut pr_urls = HashMap::new();
+    for workspace_repo in &pr_repos {
+        let repo = &workspace_repo.repo;
+        let open_pr = Merge::find_by_workspace_and_repo_id(pool, workspace_id, repo.id)
+            .await?
+            .into_iter()
+            .find_map(|merge| match merge {
+                Merge::Pr(pr) if matches!(pr.pr_info.status, MergeStatus::Open) => {
+                    Some(pr.pr_info.url)
+                }
+                _ => None,
+            });
+        let url = match open_pr {
+            Some(url) => url,
+            None => {
+                let entry = repo_requests.get(&repo.id);
+                let pr_request = CreatePrApiRequest {
+                    title: entry
+                        .and_then(|entry| entry.pr_title.clone())
+                        .unwrap_or_else(|| task.title.clone()),
+                    body: None,
+                    target_branch: None,
+                    draft: entry.and_then(|entry| entry.draft),
+                    repo_id: repo.id,
+                    auto_generate_description: false,
+                };
+                match open_workspace_pr(&deployment, &workspace, &pr_request).await? {
+                    Ok(pr_info) => pr_info.url,
+                    Err(error) => {
+                        return Ok((
+                            StatusCode::BAD_REQUEST,
+                            ResponseJson(ApiResponse::error_with_data(
+                                CloseWorkspaceError::PrFailed {
+                                    repo_name: repo.name.clone(),
+                                    error,
+                                },
+                            )),
+                        ));
+                    }
+                }
+            }
+        };
+        pr_urls.insert(repo.id, url);
+    }
+
+    let mut merge_results = Vec::new();
+    if !merge_repos.is_empty() {
+        // Prepare repos with targets for merge
+        let repos_with_targets: Vec<_> = merge_repos
+            .iter()
+            .map(|r| (r.repo.clone(), r.target_branch.clone()))
+            .collect();
+
+        // Perform merge, with the workspace's merge description as the message if it has one
+        let commit_message =
+            match WorkspaceMergeDescription::find_by_workspace_id(pool, workspace_id).await? {
+                Some(description) => description.commit_message(),
+                None => format!("Merge workspace branch '{}' via close", workspace.branch),
+            };
+        let results = WorkspaceManager::close_workspace_merge(
+            &repos_with_targets,
+            &workspace.branch,
+            &commit_message,
+        )
+        .await;
+        merge_results = match results {
+            Ok(merge_results) => merge_results,
+            Err(services::services::workspace_manager::WorkspaceError::MergeConflicts {
+                repo_name,
+                message,
+            }) => {
+                if let Some((repo, _)) =
+                    repos_with_targets.iter().find(|(r, _)| r.name == repo_name)
+                {
+                    MergeConflict::record(pool, workspace_id, repo.id).await?;
+                }
+                return Err(ApiError::Conflict(format!(
+                    "Merge conflicts in repo '{}': {}",
+                    repo_name, message
+                )));
+            }
+            Err(e) => {
+                return Err(ApiError::BadRequest(format!(
+                    "Workspace close failed: {}",
+                    e
+                )));
+            }
+        };
+
+        // Create DirectMerge records for each repo
+        for result in &merge_results {
+            Merge::create_direct(
+                pool,
+                workspace_id,
+                result.repo_id,
+                &result.target_branch,
+                &result.merge_commit_sha,
+            )
+            .await?;
+        }
+    }
+
+    // Children are moved while the workspace branch still exists, since a rebase replays
+    // only the commits made on top of it
+    let stacked_children = update_stacked_children(
+        &deployment,
+        &workspace,
+        &workspace_repos,
+        &children,
+        request.stacked_children,
+    )
+    .await?;
+
+    // Now cleanup the workspace, keeping the branches PRs were opened from
+    let workspace_dir = PathBuf::from(container_ref);
+    let pr_repo_ids: Vec<Uuid> = pr_urls.keys().copied().collect();
+    WorkspaceManager::close_workspace_discard(
+        &workspace_dir,
+        &repos,
+        &workspace.branch,
+        &pr_repo_ids,
+    )
+    .await
+    .map_err(|e| ApiError::BadRequest(format!("Workspace cleanup failed: {}", e)))?;
+
+    // Update database: set archived and clear container_ref
+    Workspace::set_archived(pool, workspace_id, true).await?;
+    Workspace::clear_container_ref(pool, workspace_id).await?;
+
+    // Update task status based on what happened to the repos
+    let new_status = if !merge_results.is_empty() {
+        TaskStatus::Done
+    } else if !pr_urls.is_empty() {
+        TaskStatus::InReview
+    } else {
+        TaskStatus::Todo
+    };
+    Task::update_status(pool, workspace.task_id, new_status).await?;
+
+    let mut outcomes = Vec::new();
+    if !merge_results.is_empty() {
+        outcomes.push(format!(
+            "merged workspace into {} repo(s)",
+            merge_results.len()
+        ));
+    }
+    if !pr_urls.is_empty() {
+        outcomes.push(format!("opened PRs for {} repo(s)", pr_urls.len()));
+    }
+    if !outcomes.is_empty() && discarded > 0 {
+        outcomes.push(format!("discarded {} repo(s)", discarded));
+    }
+    let message = if outcomes.is_empty() {
+        "Successfully discarded workspace changes".to_string()
+    } else {
+        format!("Successfully {}", outcomes.join(", "))
+    };
+
+    let repo_results = workspace_repos
+        .iter()
+        .map(|r| {
+            let merge = merge_results.iter().find(|m| m.repo_id == r.repo.id);
+            RepoCloseResult {
+                repo_id: r.repo.id,
+                repo_name: r.repo.name.clone(),
+                target_branch: r.target_branch.clone(),
+                strategy: strategies[&r.repo.id],
+                merge_commit_sha: merge.map(|m| m.merge_commit_sha.clone()),
+                queue_position: merge.map(|m| m.queue_position),
+                pr_url: pr_urls.get(&r.repo.id).cloned(),
+            }
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        ResponseJson(ApiResponse::success(CloseWorkspaceResponse {
+            workspace_id: workspace_id.to_string(),
+            success: true,
+            message,
+            merge_commit_sha: merge_results.first().map(|r| r.merge_commit_sha.clone()),
+            queue_position: merge_results.iter().map(|r| r.queue_position).max(),
+            repos: repo_results,
+            stacked_children,
+            review_warnings,
+        })),
+    ))
+}
+
+/// Run the workspace through its project's agent pipeline, replacing any earlier pipeline
+/// run. Each stage starts once the one before it succeeds, and a failed or stopped stage
+/// skips the rest; the stages are part of the workspace status.
+/// Returns 404 if workspace not found.
+/// Returns 400 if workspace is closed, has running processes, or its project has no agent
+/// pipeline.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn start_workspace_pipeline(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspacePipelineStage>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Pipeline)?;
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace is closed (no active worktree)".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot start the pipeline while processes are running".to_string(),
+        ));
+    }
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+    let pipeline = ProjectAgentPipeline::find_by_project_id(pool, task.project_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::BadRequest("The workspace's project has no agent pipeline".to_string())
+        })?;
+
+    let session = RecordedCommandService::session_for_workspace(pool, workspace_id).await?;
+    let stages = WorkspacePipelineStage::create_run(pool, workspace_id, &pipeline.stages).await?;
+    let first = stages
+        .first()
+        .ok_or_else(|| ApiError::BadRequest("The agent pipeline has no stages".to_string()))?;
+    if let Err(e) = deployment
+        .container()
+        .start_pipeline_stage(&workspace, &session, first)
+        .await
+    {
+        WorkspacePipelineStage::update_status(pool, first.id, PipelineStageStatus::Failed).await?;
+        WorkspacePipelineStage::skip_pending(pool, workspace_id).await?;
+        return Err(e.into());
+    }
+
+    let stages = WorkspacePipelineStage::find_by_workspace_id(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(stages)))
+}
+
+/// Stages of the workspace's agent pipeline run, in order.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_pipeline(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspacePipelineStage>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let stages = WorkspacePipelineStage::find_by_workspace_id(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(stages)))
+}
+
+/// Request body for resuming a coding agent run interrupted by a server restart
+#[derive(Debug, Deserialize, TS)]
+pub struct ResumeWorkspaceRequest {
+    /// Prompt for the resumed run; defaults to asking the agent to continue where it stopped
+    #[serde(default)]
+    #[ts(optional)]
+    pub prompt: Option<String>,
+}
+
+/// Resume the workspace's coding agent run that was interrupted by a server restart,
+/// continuing the agent's stored session. An agent that never reported a session is given
+/// its original prompt again.
+/// Returns 404 if workspace not found or it has no interrupted run to resume.
+/// Returns 400 if workspace is closed or has running processes.
+/// Returns 409 if a coding agent has run in the workspace since the interruption.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn resume_workspace(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<ResumeWorkspaceRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Resume)?;
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace is closed (no active worktree)".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot resume while processes are running".to_string(),
+        ));
+    }
+
+    let interrupted = InterruptedExecution::find_unresumed_by_workspace_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Workspace {} has no interrupted run to resume",
+                workspace_id
+            ))
+        })?;
+    let process = ExecutionProcess::find_by_id(pool, interrupted.execution_process_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Execution process {} not found",
+                interrupted.execution_process_id
+            ))
+        })?;
+    let session = process
+        .parent_session(pool)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Session {} not found", process.session_id)))?;
+    let latest = ExecutionProcess::find_latest_by_session_and_run_reason(
         pool,
-        workspace_id,
+        session.id,
+        &ExecutionProcessRunReason::CodingAgent,
     )
     .await?;
-    if has_running {
-        return Err(ApiError::BadRequest(
-            "Cannot close workspace with running processes".to_string(),
+    if latest.is_some_and(|latest| latest.id != process.id) {
+        return Err(ApiError::Conflict(
+            "A coding agent has run in the workspace since the interrupted run".to_string(),
         ));
     }
 
-    // Validate strategy
-    if request.strategy != "merge" && request.strategy != "discard" {
-        return Err(ApiError::BadRequest(format!(
-            "Invalid strategy '{}'. Must be 'merge' or 'discard'",
-            request.strategy
-        )));
-    }
-
-    // Get workspace repos with target branches
-    let workspace_repos =
-        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace_id).await?;
-    let repos: Vec<_> = workspace_repos.iter().map(|r| r.repo.clone()).collect();
-
-    let (message, merge_commit_sha) = if request.strategy == "merge" {
-        // Prepare repos with targets for merge
-        let repos_with_targets: Vec<_> = workspace_repos
-            .iter()
-            .map(|r| (r.repo.clone(), r.target_branch.clone()))
-            .collect();
-
-        // Perform merge
-        let commit_message = format!("Merge workspace branch '{}' via close", workspace.branch);
-        let merge_results = WorkspaceManager::close_workspace_merge(
-            &repos_with_targets,
-            &workspace.branch,
-            &commit_message,
-        )
-        .await
-        .map_err(|e| {
-            if let services::services::workspace_manager::WorkspaceError::MergeConflicts {
-                repo_name,
-                message,
-            } = e
-            {
-                ApiError::Conflict(format!(
-                    "Merge conflicts in repo '{}': {}",
-                    repo_name, message
-                ))
-            } else {
-                ApiError::BadRequest(format!("Workspace close failed: {}", e))
-            }
+    let agent_session_id = CodingAgentTurn::find_by_execution_process_id(pool, process.id)
+        .await?
+        .and_then(|turn| turn.agent_session_id);
+    let action = process
+        .executor_action()
+        .ok()
+        .and_then(|action| {
+            AgentResumeService::resume_action(action, agent_session_id, payload.prompt.as_deref())
+        })
+        .ok_or_else(|| {
+            ApiError::BadRequest("The interrupted run has no coding agent action".to_string())
         })?;
 
-        // Create DirectMerge records for each repo
-        for result in &merge_results {
-            Merge::create_direct(
-                pool,
-                workspace_id,
-                result.repo_id,
-                &result.target_branch,
-                &result.merge_commit_sha,
-            )
-            .await?;
-        }
-
-        // Get the first merge commit SHA for the response
-        let first_sha = merge_results.first().map(|r| r.merge_commit_sha.clone());
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+    InterruptedExecution::mark_resumed(pool, process.id, execution_process.id).await?;
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
 
-        // Now cleanup the workspace (discard worktrees and branches)
-        let workspace_dir = PathBuf::from(container_ref);
-        WorkspaceManager::close_workspace_discard(&workspace_dir, &repos, &workspace.branch)
-            .await
-            .map_err(|e| ApiError::BadRequest(format!("Workspace cleanup failed: {}", e)))?;
+/// Retarget workspaces stacked on `workspace` at its target branches, optionally rebasing
+/// them, and move them onto the closing workspace's own parent. Rebase failures are
+/// reported per child rather than failing the close.
+async fn update_stacked_children(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    workspace_repos: &[RepoWithTargetBranch],
+    children: &[Workspace],
+    strategy: Option<StackedChildrenStrategy>,
+) -> Result<Vec<StackedChildUpdate>, ApiError> {
+    let Some(strategy) = strategy.filter(|_| !children.is_empty()) else {
+        return Ok(Vec::new());
+    };
+    let pool = &deployment.db().pool;
 
-        (
-            format!(
-                "Successfully merged workspace into {} repo(s)",
-                merge_results.len()
-            ),
-            first_sha,
+    for repo in workspace_repos {
+        WorkspaceStack::retarget_children(
+            pool,
+            workspace.id,
+            repo.repo.id,
+            &workspace.branch,
+            &repo.target_branch,
         )
-    } else {
-        // Discard strategy - just cleanup
-        let workspace_dir = PathBuf::from(container_ref);
-        WorkspaceManager::close_workspace_discard(&workspace_dir, &repos, &workspace.branch)
-            .await
-            .map_err(|e| ApiError::BadRequest(format!("Workspace cleanup failed: {}", e)))?;
+        .await?;
+    }
 
-        ("Successfully discarded workspace changes".to_string(), None)
-    };
+    let mut updates = Vec::with_capacity(children.len());
+    for child in children {
+        let error = match strategy {
+            StackedChildrenStrategy::Retarget => None,
+            StackedChildrenStrategy::Rebase => {
+                rebase_stacked_child(deployment, workspace, workspace_repos, child)
+                    .await
+                    .err()
+            }
+        };
+        updates.push(StackedChildUpdate {
+            workspace_id: child.id,
+            rebased: strategy == StackedChildrenStrategy::Rebase && error.is_none(),
+            error,
+        });
+    }
 
-    // Update database: set archived and clear container_ref
-    Workspace::set_archived(pool, workspace_id, true).await?;
-    Workspace::clear_container_ref(pool, workspace_id).await?;
+    let grandparent_id = WorkspaceStack::find_parent_id(pool, workspace.id).await?;
+    WorkspaceStack::reparent_children(pool, workspace.id, grandparent_id).await?;
+    Ok(updates)
+}
 
-    // Update task status based on strategy
-    let new_status = if request.strategy == "merge" {
-        TaskStatus::Done
-    } else {
-        TaskStatus::Todo
-    };
-    Task::update_status(pool, workspace.task_id, new_status).await?;
+/// Rebase the commits a stacked child made on top of `parent`'s branch onto the parent's
+/// target branch, in every repo the two share
+async fn rebase_stacked_child(
+    deployment: &DeploymentImpl,
+    parent: &Workspace,
+    parent_repos: &[RepoWithTargetBranch],
+    child: &Workspace,
+) -> Result<(), String> {
+    let _lock = WorkspaceLock::try_acquire(child.id, WorkspaceOperation::Rebase)
+        .map_err(|e| e.to_string())?;
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(child)
+        .await
+        .map_err(|e| e.to_string())?;
+    let child_repos = WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, child.id)
+        .await
+        .map_err(|e| e.to_string())?;
 
-    Ok(ResponseJson(ApiResponse::success(CloseWorkspaceResponse {
-        workspace_id: workspace_id.to_string(),
-        success: true,
-        message,
-        merge_commit_sha,
-    })))
+    for repo in &child_repos {
+        let Some(parent_repo) = parent_repos.iter().find(|r| r.repo.id == repo.id) else {
+            continue;
+        };
+        let worktree_path = PathBuf::from(&container_ref).join(&repo.name);
+        deployment
+            .git()
+            .rebase_branch(
+                &repo.path,
+                &worktree_path,
+                &parent_repo.target_branch,
+                &parent.branch,
+                &child.branch,
+            )
+            .map_err(|e| format!("Rebase failed in repo '{}': {}", repo.name, e))?;
+    }
+    Ok(())
 }
 
 /// Diff stats for a workspace
@@ -436,6 +4358,7 @@ async fn compute_workspace_diff_stats(
         .container_ref
         .as_ref()
         .ok_or_else(|| ApiError::BadRequest("No container ref".to_string()))?;
+    repair_worktrees_if_enabled(deployment, workspace).await?;
 
     let workspace_repos =
         WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
@@ -458,20 +4381,31 @@ async fn compute_workspace_diff_stats(
 
         let base_commit = match base_commit_result {
             Ok(Ok(commit)) => commit,
-            _ => continue,
+            _ => {
+                tracing::warn!(
+                    "Skipping repo '{}' of workspace {}: no merge base with {}",
+                    repo_with_branch.repo.name,
+                    workspace.id,
+                    repo_with_branch.target_branch
+                );
+                continue;
+            }
         };
 
         // Get diffs
         let diffs_result = tokio::task::spawn_blocking({
             let git = deployment.git().clone();
             let worktree = worktree_path.clone();
+            let path_scope = repo_with_branch.path_scope.clone();
             move || {
+                let path_scope = path_scope.as_deref();
                 git.get_diffs(
                     DiffTarget::Worktree {
                         worktree_path: &worktree,
                         base_commit: &base_commit,
+                        include_untracked: true,
                     },
-                    None,
+                    path_scope.as_ref().map(std::slice::from_ref),
                 )
             }
         })
@@ -492,7 +4426,900 @@ async fn compute_workspace_diff_stats(
 pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/{id}/status", get(get_workspace_status))
+        .route("/{id}/verify", get(verify_workspace))
+        .route("/{id}/events", get(get_workspace_events))
+        .route("/{id}/env", get(get_workspace_env).put(set_workspace_env))
+        .route("/{id}/processes", get(get_workspace_processes))
+        .route(
+            "/{id}/ai-reviews",
+            get(get_workspace_ai_reviews).post(start_workspace_ai_review),
+        )
+        .route(
+            "/{id}/reviews",
+            get(get_workspace_reviews).post(create_workspace_review),
+        )
         .route("/{id}/transcript", get(get_workspace_transcript))
         .route("/{id}/diff", get(get_workspace_diff))
+        .route("/{id}/diff.html", get(get_workspace_diff_html))
+        .route("/{id}/diff/file", get(get_workspace_file_diff))
+        .route(
+            "/{id}/review-comments",
+            get(review_comments::get_review_comments).post(review_comments::create_review_comment),
+        )
+        .route(
+            "/{id}/review-comments/{comment_id}",
+            put(review_comments::update_review_comment)
+                .delete(review_comments::delete_review_comment),
+        )
+        .route(
+            "/{id}/review-comments/{comment_id}/apply-suggestion",
+            post(review_comments::apply_review_suggestion),
+        )
+        .route("/{id}/report", get(get_workspace_report))
+        .route(
+            "/{id}/merge-description",
+            get(get_merge_description)
+                .put(set_merge_description)
+                .delete(delete_merge_description),
+        )
+        .route(
+            "/{id}/merge-description/generate",
+            post(generate_merge_description),
+        )
+        .route("/{id}/patches", get(get_workspace_patches))
+        .route("/{id}/archive", get(get_workspace_archive))
+        .route("/{id}/apply-patch", post(apply_workspace_patch))
+        .route(
+            "/{id}/upload",
+            post(upload_workspace_files).layer(DefaultBodyLimit::max(MAX_UPLOAD_BODY_BYTES)),
+        )
+        .route("/{id}/blame", get(get_workspace_blame))
+        .route("/{id}/file-history", get(get_workspace_file_history))
+        .route("/{id}/repos", post(add_workspace_repo))
+        .route("/{id}/repos/{repo_id}", delete(remove_workspace_repo))
+        .route(
+            "/{id}/repos/{repo_id}/index",
+            get(index::get_workspace_repo_index),
+        )
+        .route(
+            "/{id}/repos/{repo_id}/stage",
+            post(index::stage_workspace_repo_paths),
+        )
+        .route(
+            "/{id}/repos/{repo_id}/unstage",
+            post(index::unstage_workspace_repo_paths),
+        )
+        .route(
+            "/{id}/repos/{repo_id}/commit",
+            post(index::commit_workspace_repo),
+        )
+        .route("/{id}/discard-files", post(discard_workspace_files))
+        .route(
+            "/{id}/restore-from-target",
+            post(restore_workspace_file_from_target),
+        )
+        .route("/{id}/merge-preview", get(get_workspace_merge_preview))
+        .route("/{id}/merge-preview/diff", get(get_workspace_landing_diff))
         .route("/{id}/close", post(close_workspace))
+        .route(
+            "/{id}/conflicts/resolve",
+            post(conflicts::resolve_workspace_conflicts),
+        )
+        .route(
+            "/{id}/conflicts/continue",
+            post(conflicts::continue_workspace_conflicts),
+        )
+        .route(
+            "/{id}/conflict-resolutions",
+            get(conflicts::get_conflict_resolutions),
+        )
+        .route(
+            "/{id}/conflict-resolutions/{resolution_id}/accept",
+            post(conflicts::accept_conflict_resolution),
+        )
+        .route(
+            "/{id}/conflict-resolutions/{resolution_id}/reject",
+            post(conflicts::reject_conflict_resolution),
+        )
+        .route(
+            "/{id}/plans",
+            get(plans::get_workspace_plans).post(plans::start_workspace_plan),
+        )
+        .route(
+            "/{id}/plans/{plan_id}/approve",
+            post(plans::approve_workspace_plan),
+        )
+        .route(
+            "/{id}/plans/{plan_id}/reject",
+            post(plans::reject_workspace_plan),
+        )
+        .route(
+            "/{id}/pipeline",
+            get(get_workspace_pipeline).post(start_workspace_pipeline),
+        )
+        .route("/{id}/resume", post(resume_workspace))
+        .route(
+            "/{id}/queue",
+            get(queue::get_workspace_queue)
+                .post(queue::queue_workspace_follow_up)
+                .delete(queue::clear_workspace_queue),
+        )
+        .route("/{id}/queue/order", put(queue::reorder_workspace_queue))
+        .route(
+            "/{id}/queue/{message_id}",
+            delete(queue::cancel_workspace_queued_message),
+        )
+        .route("/{id}/test", post(run_workspace_tests))
+        .route("/{id}/exec", post(exec_in_workspace))
+        .route("/{id}/scripts/{script_id}/run", post(run_project_script))
+        .route("/{id}/preview", any(preview_workspace))
+        .route("/{id}/preview/{*path}", any(preview_workspace))
+        .route(
+            "/{id}/dev-servers/{process_id}/logs",
+            get(stream_dev_server_logs),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::{
+        project::UpdateProject, project_script::CreateProjectScript, repo::UpdateRepo,
+    };
+
+    use super::*;
+    use crate::test_support::{
+        ScratchDir, init_repo, run_git, seed_execution_process, seed_project, seed_task,
+        seed_workspace, test_pool,
+    };
+
+    #[test]
+    fn upload_paths_stay_inside_the_repo() {
+        assert_eq!(upload_directory(None).unwrap(), PathBuf::new());
+        assert_eq!(
+            upload_directory(Some("docs/img")).unwrap(),
+            PathBuf::from("docs/img")
+        );
+        for path in [
+            "../elsewhere",
+            "docs/../../x",
+            "/etc",
+            ".git/hooks",
+            "./docs",
+        ] {
+            assert!(
+                matches!(upload_directory(Some(path)), Err(ApiError::BadRequest(_))),
+                "{path}"
+            );
+        }
+
+        assert_eq!(
+            upload_file_name("logo.png").unwrap(),
+            PathBuf::from("logo.png")
+        );
+        for name in [
+            "../logo.png",
+            "img/logo.png",
+            "/etc/passwd",
+            ".git",
+            "..",
+            "",
+        ] {
+            assert!(
+                matches!(upload_file_name(name), Err(ApiError::BadRequest(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn uploads_are_limited_in_size() {
+        assert!(check_upload_size("big.bin", MAX_UPLOAD_FILE_BYTES).is_ok());
+        assert!(matches!(
+            check_upload_size("big.bin", MAX_UPLOAD_FILE_BYTES + 1),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn uploads_never_write_through_symlinks() {
+        let scratch = ScratchDir::new();
+        let repo = scratch.0.join("repo");
+        let outside = scratch.0.join("outside.txt");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(&outside, "keep").unwrap();
+        std::fs::write(repo.join("notes.txt"), "old").unwrap();
+        std::os::unix::fs::symlink(&outside, repo.join("link.txt")).unwrap();
+
+        let check = |name: &str, overwrite: bool| {
+            let target = repo.join(name);
+            let display_path = PathBuf::from(name);
+            async move { check_upload_target(&target, &display_path, "repo", overwrite).await }
+        };
+        assert!(matches!(
+            check("link.txt", true).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(matches!(
+            check("notes.txt", false).await,
+            Err(ApiError::Conflict(_))
+        ));
+        assert!(check("notes.txt", true).await.is_ok());
+        assert!(check("new.txt", false).await.is_ok());
+
+        // A symlink that appears after the check is replaced or refused, never followed
+        let link = std::path::Path::new("link.txt");
+        assert!(
+            write_uploaded_file(&repo, link, b"new", false)
+                .await
+                .is_err()
+        );
+        write_uploaded_file(&repo, link, b"new", true)
+            .await
+            .unwrap();
+        assert!(
+            !std::fs::symlink_metadata(repo.join("link.txt"))
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo.join("link.txt")).unwrap(),
+            "new"
+        );
+        assert_eq!(std::fs::read_to_string(&outside).unwrap(), "keep");
+
+        write_uploaded_file(&repo, std::path::Path::new("notes.txt"), b"updated", true)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("notes.txt")).unwrap(),
+            "updated"
+        );
+        assert_eq!(std::fs::read_dir(&repo).unwrap().count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn upload_directories_are_checked_before_any_is_created() {
+        let scratch = ScratchDir::new();
+        let repo = scratch.0.join("repo");
+        let outside = scratch.0.join("outside");
+        std::fs::create_dir_all(repo.join("docs")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(repo.join("README.md"), "# repo").unwrap();
+        std::os::unix::fs::symlink(&outside, repo.join("escape")).unwrap();
+        std::os::unix::fs::symlink(scratch.0.join("missing"), repo.join("dangling")).unwrap();
+
+        let check = |directory: &str| {
+            let (repo, directory) = (repo.clone(), PathBuf::from(directory));
+            async move { check_upload_directory(&repo, &directory, "repo").await }
+        };
+        assert!(check("").await.is_ok());
+        assert!(check("docs").await.is_ok());
+        assert!(check("docs/img/icons").await.is_ok());
+        for directory in ["escape", "escape/img", "dangling/img"] {
+            assert!(
+                matches!(check(directory).await, Err(ApiError::BadRequest(_))),
+                "{directory}"
+            );
+        }
+        assert!(matches!(
+            check("README.md/img").await,
+            Err(ApiError::Conflict(_))
+        ));
+
+        assert!(!repo.join("docs/img").exists());
+        assert!(!scratch.0.join("missing").exists());
+        assert_eq!(std::fs::read_dir(&outside).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn preview_proxy_drops_connection_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, "localhost:3000".parse().unwrap());
+        headers.insert(header::CONNECTION, "keep-alive".parse().unwrap());
+        headers.insert(header::TRANSFER_ENCODING, "chunked".parse().unwrap());
+        headers.insert(header::CONTENT_TYPE, "text/html".parse().unwrap());
+        headers.insert(header::COOKIE, "session=1".parse().unwrap());
+
+        let forwarded = forwardable_headers(&headers);
+
+        assert_eq!(forwarded.len(), 2);
+        assert_eq!(forwarded[header::CONTENT_TYPE], "text/html");
+        assert_eq!(forwarded[header::COOKIE], "session=1");
+    }
+
+    #[test]
+    fn preview_proxy_forwards_the_raw_path_and_query() {
+        let upstream = |uri: &str| preview_upstream_path(&uri.parse().unwrap());
+        let id = Uuid::new_v4();
+
+        assert_eq!(upstream(&format!("/{id}/preview")), "/");
+        assert_eq!(upstream(&format!("/{id}/preview/?tab=1")), "/?tab=1");
+        assert_eq!(
+            upstream(&format!("/{id}/preview/a%3Fb/c%23d/e%2Ff?x=%26#")),
+            "/a%3Fb/c%23d/e%2Ff?x=%26"
+        );
+        assert_eq!(
+            upstream(&format!("/api/workspaces/{id}/preview/assets/app.js")),
+            "/assets/app.js"
+        );
+    }
+
+    fn changed_file(path: &str, additions: usize, deletions: usize) -> FileDiff {
+        FileDiff {
+            path: path.to_string(),
+            additions,
+            deletions,
+            diff_content: String::new(),
+            state: None,
+            old_path: None,
+            similarity: None,
+            split: None,
+            review_comments: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn diffs_are_totalled_per_repo_and_filtered_by_name_or_id() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api", "web"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "feature", &[&repos[0], &repos[1]]).await;
+        let by_name = resolve_workspace_repo(&pool, workspace.id, "web")
+            .await
+            .unwrap();
+        assert_eq!(by_name.repo.id, repos[1].id);
+        let by_id = resolve_workspace_repo(&pool, workspace.id, &repos[0].id.to_string())
+            .await
+            .unwrap();
+        assert_eq!(by_id.repo.name, "api");
+        assert!(matches!(
+            resolve_workspace_repo(&pool, workspace.id, "docs").await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        let diffs = RepoFileDiffs::new(
+            repos[0].id,
+            repos[0].name.clone(),
+            vec![
+                changed_file("src/lib.rs", 3, 1),
+                changed_file("README.md", 2, 4),
+            ],
+        );
+        assert_eq!(diffs.files_changed, 2);
+        assert_eq!(diffs.additions, 5);
+        assert_eq!(diffs.deletions, 5);
+    }
+
+    async fn review(pool: &sqlx::SqlitePool, workspace_id: Uuid, decision: ReviewDecision) {
+        let review = CreateWorkspaceReview {
+            reviewer: "alice".to_string(),
+            decision,
+            comment: None,
+        };
+        WorkspaceReview::create(pool, workspace_id, &review)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn merges_wait_for_an_approving_latest_review_when_required() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "feature", &[&repos[0]]).await;
+        let blocked = || check_merge_approval(&pool, project.id, workspace.id);
+
+        // Without the policy, reviews are advisory
+        review(&pool, workspace.id, ReviewDecision::ChangesRequested).await;
+        assert!(blocked().await.unwrap().is_none());
+
+        let policy = UpdateProject {
+            name: None,
+            require_merge_approval: Some(true),
+        };
+        Project::update(&pool, project.id, &policy).await.unwrap();
+        match blocked().await.unwrap() {
+            Some(CloseWorkspaceError::ApprovalRequired {
+                latest_review: Some(latest),
+            }) => assert_eq!(latest.decision, ReviewDecision::ChangesRequested),
+            other => panic!("expected approval to be required, got {other:?}"),
+        }
+
+        // Reviews are ordered by their millisecond timestamps
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        review(&pool, workspace.id, ReviewDecision::Approved).await;
+        assert!(blocked().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn only_skip_ai_review_bypasses_the_review_policy() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "feature", &[&repos[0]]).await;
+        let check = |force: bool, skip_ai_review: bool| {
+            let pool = pool.clone();
+            async move {
+                let query = CloseWorkspaceQuery {
+                    force,
+                    skip_ai_review,
+                };
+                check_ai_review(&pool, project.id, workspace.id, &query)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert!(matches!(
+            check(true, false).await,
+            AiReviewCheck::NotRequired
+        ));
+
+        let agent = ExecutorProfileId::new(executors::executors::BaseCodingAgent::ClaudeCode);
+        ProjectAiReviewPolicy::upsert(
+            &pool,
+            project.id,
+            &db::models::project_ai_review_policy::UpsertProjectAiReviewPolicy {
+                executor_profile_id: agent.clone(),
+                block_severity: None,
+            },
+        )
+        .await
+        .unwrap();
+        match check(true, false).await {
+            AiReviewCheck::Start(profile) => assert_eq!(profile, agent),
+            other => panic!("expected a review to be started, got {other:?}"),
+        }
+        assert!(matches!(
+            check(false, true).await,
+            AiReviewCheck::NotRequired
+        ));
+    }
+
+    #[tokio::test]
+    async fn repos_are_removed_only_when_no_work_or_stacked_workspace_depends_on_them() {
+        let pool = test_pool().await;
+        let git = git::GitService::new();
+        let dir = ScratchDir::new();
+        let mut repos = Vec::new();
+        for name in ["api", "web"] {
+            let path = dir.0.join("repos").join(name);
+            init_repo(&path, "README.md", &format!("# {name}\n"));
+            repos.push(Repo::find_or_create(&pool, &path, name).await.unwrap());
+        }
+        let (api, web) = (&repos[0], &repos[1]);
+        let (project, _) = seed_project(&pool, &[]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[api, web]).await;
+        let container = dir.0.join("workspace");
+        let container_ref = container.to_string_lossy().into_owned();
+        for repo in [api, web] {
+            let worktree = container.join(&repo.name);
+            run_git(
+                &repo.path,
+                &[
+                    "worktree",
+                    "add",
+                    "-q",
+                    "-b",
+                    "vk/login",
+                    &worktree.to_string_lossy(),
+                ],
+            );
+        }
+        let remove = |workspace: Workspace, repo_id: Uuid, force: bool| {
+            let (git, pool, container_ref) = (git.clone(), pool.clone(), container_ref.clone());
+            async move {
+                remove_repo_from_workspace(&git, &pool, &workspace, &container_ref, repo_id, force)
+                    .await
+            }
+        };
+
+        let (_, strangers) = seed_project(&pool, &["docs"]).await;
+        assert!(matches!(
+            remove(workspace.clone(), strangers[0].id, false).await,
+            Err(ApiError::NotFound(_))
+        ));
+        let agent_in_web = Workspace {
+            agent_working_dir: Some("web".to_string()),
+            ..workspace.clone()
+        };
+        assert!(matches!(
+            remove(agent_in_web, web.id, false).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let child_task = seed_task(&pool, project.id, "Add logout").await;
+        let child = seed_workspace(&pool, child_task.id, "vk/logout", &[web]).await;
+        WorkspaceStack::create(&pool, child.id, workspace.id)
+            .await
+            .unwrap();
+        assert!(matches!(
+            remove(workspace.clone(), web.id, false).await,
+            Err(ApiError::Conflict(_))
+        ));
+        Workspace::set_archived(&pool, child.id, true)
+            .await
+            .unwrap();
+
+        // Uncommitted and committed work alike needs force to be thrown away
+        let web_worktree = container.join("web");
+        std::fs::write(web_worktree.join("login.ts"), "export {};\n").unwrap();
+        assert!(matches!(
+            remove(workspace.clone(), web.id, false).await,
+            Err(ApiError::Conflict(_))
+        ));
+        run_git(&web_worktree, &["add", "-A"]);
+        run_git(&web_worktree, &["commit", "-q", "-m", "Add login"]);
+        assert!(matches!(
+            remove(workspace.clone(), web.id, false).await,
+            Err(ApiError::Conflict(_))
+        ));
+
+        remove(workspace.clone(), web.id, true).await.unwrap();
+        assert!(!web_worktree.exists());
+        assert!(!git.check_branch_exists(&web.path, "vk/login").unwrap());
+        let remaining = WorkspaceRepo::find_repos_for_workspace(&pool, workspace.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            remaining.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![api.id]
+        );
+
+        assert!(matches!(
+            remove(workspace.clone(), api.id, true).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        seed_execution_process(&pool, workspace.id, ExecutionProcessRunReason::CodingAgent).await;
+        assert!(matches!(
+            remove(workspace.clone(), api.id, true).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(container.join("api").exists());
+    }
+
+    #[tokio::test]
+    async fn close_strategies_come_from_the_repo_then_the_request_then_the_project() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api", "web", "docs"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(
+            &pool,
+            task.id,
+            "feature",
+            &[&repos[0], &repos[1], &repos[2]],
+        )
+        .await;
+        let workspace_repos =
+            WorkspaceRepo::find_repos_with_target_branch_for_workspace(&pool, workspace.id)
+                .await
+                .unwrap();
+        let (api, web, docs) = (repos[0].id, repos[1].id, repos[2].id);
+        let request = |body: serde_json::Value| -> CloseWorkspaceRequest {
+            serde_json::from_value(body).unwrap()
+        };
+
+        let mixed = request(serde_json::json!({
+            "strategy": "merge",
+            "repos": [
+                { "repo_id": web, "strategy": "pr", "pr_title": "Login page" },
+                { "repo_id": docs, "strategy": "discard" },
+            ],
+        }));
+        let (entries, strategies) =
+            resolve_close_strategies(&workspace_repos, &mixed, Some(MergeStrategy::Discard))
+                .unwrap();
+        assert_eq!(strategies[&api], RepoCloseStrategy::Merge);
+        assert_eq!(strategies[&web], RepoCloseStrategy::Pr);
+        assert_eq!(strategies[&docs], RepoCloseStrategy::Discard);
+        assert_eq!(entries[&web].pr_title.as_deref(), Some("Login page"));
+        assert!(!entries.contains_key(&api));
+
+        // Repos without an entry fall back to the project's default when the request names none
+        let partial = request(serde_json::json!({
+            "repos": [{ "repo_id": web, "strategy": "pr" }],
+        }));
+        let (_, strategies) =
+            resolve_close_strategies(&workspace_repos, &partial, Some(MergeStrategy::Discard))
+                .unwrap();
+        assert_eq!(strategies[&api], RepoCloseStrategy::Discard);
+        assert_eq!(strategies[&web], RepoCloseStrategy::Pr);
+        assert!(matches!(
+            resolve_close_strategies(&workspace_repos, &partial, None),
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let (_, strangers) = seed_project(&pool, &["ops"]).await;
+        for invalid in [
+            serde_json::json!({ "strategy": "squash" }),
+            serde_json::json!({
+                "strategy": "merge",
+                "repos": [{ "repo_id": strangers[0].id, "strategy": "discard" }],
+            }),
+            serde_json::json!({
+                "strategy": "merge",
+                "repos": [
+                    { "repo_id": api, "strategy": "discard" },
+                    { "repo_id": api, "strategy": "pr" },
+                ],
+            }),
+        ] {
+            assert!(
+                matches!(
+                    resolve_close_strategies(&workspace_repos, &request(invalid.clone()), None),
+                    Err(ApiError::BadRequest(_))
+                ),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn files_are_restored_from_the_merge_base_the_branch_head_or_the_target_branch() {
+        let pool = test_pool().await;
+        let git = git::GitService::new();
+        let dir = ScratchDir::new();
+        let repo_path = dir.0.join("repos").join("api");
+        init_repo(&repo_path, "schema.json", "v1\n");
+        let repo = Repo::find_or_create(&pool, &repo_path, "api")
+            .await
+            .unwrap();
+        let (project, _) = seed_project(&pool, &[]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[&repo]).await;
+        let container = dir.0.join("workspace");
+        let container_ref = container.to_string_lossy().into_owned();
+        let worktree = container.join("api");
+        run_git(
+            &repo_path,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "vk/login",
+                &worktree.to_string_lossy(),
+            ],
+        );
+        std::fs::write(worktree.join("schema.json"), "v2\n").unwrap();
+        run_git(&worktree, &["commit", "-q", "-am", "Regenerate schema"]);
+        std::fs::write(repo_path.join("schema.json"), "v3\n").unwrap();
+        run_git(
+            &repo_path,
+            &["commit", "-q", "-am", "Regenerate schema on main"],
+        );
+        std::fs::write(worktree.join("scratch.txt"), "notes\n").unwrap();
+
+        let file = |repo_id: Uuid, path: &str| DiscardFile {
+            repo_id,
+            path: path.to_string(),
+        };
+        let restore = |files: Vec<DiscardFile>, base: DiscardBase| {
+            let (git, pool, workspace, container_ref) = (
+                git.clone(),
+                pool.clone(),
+                workspace.clone(),
+                container_ref.clone(),
+            );
+            async move { restore_files(&git, &pool, &workspace, &container_ref, &files, base).await }
+        };
+        let schema = || std::fs::read_to_string(worktree.join("schema.json")).unwrap();
+
+        let restored = restore(
+            vec![file(repo.id, "schema.json"), file(repo.id, "scratch.txt")],
+            DiscardBase::TargetBranch,
+        )
+        .await
+        .unwrap();
+        assert_eq!(schema(), "v3\n");
+        assert!(!worktree.join("scratch.txt").exists());
+        let main_head = run_git(&repo_path, &["rev-parse", "main"]);
+        assert!(restored.iter().all(|f| f.restored_from == main_head.trim()));
+        assert_eq!(
+            restored
+                .iter()
+                .map(|f| (f.path.as_str(), f.removed))
+                .collect::<Vec<_>>(),
+            vec![("schema.json", false), ("scratch.txt", true)]
+        );
+
+        restore(vec![file(repo.id, "schema.json")], DiscardBase::Head)
+            .await
+            .unwrap();
+        assert_eq!(schema(), "v2\n");
+        restore(vec![file(repo.id, "schema.json")], DiscardBase::MergeBase)
+            .await
+            .unwrap();
+        assert_eq!(schema(), "v1\n");
+        let events = WorkspaceEvent::find_by_workspace_id(&pool, workspace.id)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().any(|e| {
+            e.message
+                .contains("api/scratch.txt, restoring the target branch")
+        }));
+
+        let (_, strangers) = seed_project(&pool, &["web"]).await;
+        assert!(matches!(
+            restore(
+                vec![file(strangers[0].id, "schema.json")],
+                DiscardBase::Head
+            )
+            .await,
+            Err(ApiError::NotFound(_))
+        ));
+        for files in [vec![], vec![file(repo.id, "../schema.json")]] {
+            assert!(matches!(
+                restore(files, DiscardBase::Head).await,
+                Err(ApiError::BadRequest(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn applied_and_failed_patches_are_recorded_as_workspace_events() {
+        let pool = test_pool().await;
+        let git = git::GitService::new();
+        let dir = ScratchDir::new();
+        let repo_path = dir.0.join("repos").join("api");
+        init_repo(&repo_path, "README.md", "v1\n");
+        let repo = Repo::find_or_create(&pool, &repo_path, "api")
+            .await
+            .unwrap();
+        let (project, _) = seed_project(&pool, &[]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[&repo]).await;
+        let worktree = dir.0.join("workspace").join("api");
+        run_git(
+            &repo_path,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "vk/login",
+                &worktree.to_string_lossy(),
+            ],
+        );
+        std::fs::write(worktree.join("README.md"), "v2\n").unwrap();
+        let patch = run_git(&worktree, &["diff"]);
+        run_git(&worktree, &["checkout", "--", "README.md"]);
+
+        let apply = |patch: String| {
+            apply_patch_to_repo(
+                &git,
+                &pool,
+                workspace.id,
+                "api",
+                worktree.clone(),
+                patch,
+                "Bump readme".to_string(),
+            )
+        };
+        let shas = apply(patch).await.unwrap();
+        assert_eq!(shas.len(), 1);
+        let missing = "--- a/missing.txt\n+++ b/missing.txt\n@@ -1 +1 @@\n-old\n+new\n";
+        assert!(apply(missing.to_string()).await.is_err());
+
+        let events = WorkspaceEvent::find_by_workspace_id(&pool, workspace.id)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        let message = |kind| {
+            events
+                .iter()
+                .find(|e| e.kind == kind)
+                .map(|e| e.message.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            message(WorkspaceEventKind::PatchApplied),
+            format!("Applied external patch to repo 'api' as {}", shas[0])
+        );
+        assert!(
+            message(WorkspaceEventKind::PatchFailed)
+                .starts_with("External patch to repo 'api' did not apply")
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_runs_a_command_or_a_configured_script_in_a_workspace_repo() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api", "web"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "vk/login", &[&repos[0], &repos[1]]).await;
+        let update = UpdateRepo {
+            test_script: Some(Some("cargo test".to_string())),
+            ..Default::default()
+        };
+        Repo::update(&pool, repos[0].id, &update).await.unwrap();
+        let resolve = |body: serde_json::Value| {
+            let pool = pool.clone();
+            async move {
+                let request: ExecWorkspaceRequest = serde_json::from_value(body).unwrap();
+                resolve_exec_command(&pool, workspace.id, &request)
+                    .await
+                    .map(|(repo, command)| (repo.name, command))
+            }
+        };
+
+        assert_eq!(
+            resolve(serde_json::json!({ "repo_id": repos[1].id, "command": "ls -la" }))
+                .await
+                .unwrap(),
+            ("web".to_string(), "ls -la".to_string())
+        );
+        assert_eq!(
+            resolve(serde_json::json!({ "repo_id": repos[0].id, "script": "test" }))
+                .await
+                .unwrap(),
+            ("api".to_string(), "cargo test".to_string())
+        );
+
+        let (_, strangers) = seed_project(&pool, &["ops"]).await;
+        for invalid in [
+            // web has no test script
+            serde_json::json!({ "repo_id": repos[1].id, "script": "test" }),
+            serde_json::json!({ "repo_id": repos[0].id, "command": "  " }),
+            serde_json::json!({ "repo_id": repos[0].id }),
+            serde_json::json!({ "repo_id": repos[0].id, "command": "ls", "script": "test" }),
+            serde_json::json!({ "repo_id": strangers[0].id, "command": "ls" }),
+        ] {
+            assert!(
+                matches!(resolve(invalid.clone()).await, Err(ApiError::BadRequest(_))),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn project_scripts_run_in_their_repo_or_the_only_one() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api", "web", "ops"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let both = seed_workspace(&pool, task.id, "vk/login", &[&repos[0], &repos[1]]).await;
+        let single = seed_workspace(&pool, task.id, "vk/logout", &[&repos[1]]).await;
+        let script = |name: &str, repo_id: Option<Uuid>| CreateProjectScript {
+            name: name.to_string(),
+            command: "make lint".to_string(),
+            repo_id,
+            env: HashMap::new(),
+            artifacts: Vec::new(),
+        };
+        let anywhere = ProjectScript::create(&pool, project.id, &script("lint", None))
+            .await
+            .unwrap();
+        let in_api =
+            ProjectScript::create(&pool, project.id, &script("lint-api", Some(repos[0].id)))
+                .await
+                .unwrap();
+        let in_ops = ProjectScript::create(&pool, project.id, &script("deploy", Some(repos[2].id)))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            script_working_dir(&pool, both.id, &in_api).await.unwrap(),
+            Some("api".to_string())
+        );
+        assert_eq!(
+            script_working_dir(&pool, both.id, &anywhere).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            script_working_dir(&pool, single.id, &anywhere)
+                .await
+                .unwrap(),
+            Some("web".to_string())
+        );
+        for workspace in [&both, &single] {
+            assert!(matches!(
+                script_working_dir(&pool, workspace.id, &in_ops).await,
+                Err(ApiError::BadRequest(_))
+            ));
+        }
+    }
 }
@@ -0,0 +1,446 @@
+use std::path::PathBuf;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    conflict_resolution::{ConflictResolution, ConflictResolutionStatus},
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    repo::Repo,
+    task::Task,
+    workspace::Workspace,
+    workspace_repo::{RepoWithTargetBranch, WorkspaceRepo},
+};
+use deployment::Deployment;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+        coding_agent_initial::CodingAgentInitialRequest,
+    },
+    profile::ExecutorProfileId,
+};
+use git::{CommitInfo, ConflictOp};
+use serde::Deserialize;
+use services::services::{
+    conflict_resolution::{
+        ConflictFile, ConflictRepo, ConflictResolutionService, has_conflict_markers,
+    },
+    container::ContainerService,
+    recorded_command::RecordedCommandService,
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+    workspace_manager::WorkspaceManager,
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Request body for having a coding agent resolve a workspace's merge conflicts
+#[derive(Debug, Deserialize, TS)]
+pub struct ResolveConflictsRequest {
+    /// Only resolve the conflicts of this repo; all repos of the workspace by default
+    #[serde(default)]
+    #[ts(optional)]
+    pub repo_id: Option<Uuid>,
+    /// The agent to resolve with; defaults to the workspace's last coding agent
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+/// Request body for finishing a merge whose conflicts are resolved
+#[derive(Debug, Deserialize, TS)]
+pub struct ContinueConflictsRequest {
+    pub repo_id: Uuid,
+}
+
+/// Conflicts left in one repo's worktree, ready to be put into a resolution prompt
+struct RepoConflicts {
+    repo: Repo,
+    target_branch: String,
+    op: ConflictOp,
+    target_commits: Vec<CommitInfo>,
+    /// Paths of the conflicted files with their worktree contents
+    files: Vec<(String, String)>,
+}
+
+async fn find_conflict_resolution(
+    pool: &sqlx::SqlitePool,
+    workspace_id: Uuid,
+    resolution_id: Uuid,
+) -> Result<ConflictResolution, ApiError> {
+    match ConflictResolution::find_by_id(pool, resolution_id).await? {
+        Some(resolution) if resolution.workspace_id == workspace_id => Ok(resolution),
+        _ => Err(ApiError::NotFound(format!(
+            "Conflict resolution {} not found in workspace",
+            resolution_id
+        ))),
+    }
+}
+
+/// Worktree of one repo of an open workspace
+async fn repo_worktree_path(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repo_id: Uuid,
+) -> Result<PathBuf, ApiError> {
+    let repo = WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, workspace.id)
+        .await?
+        .into_iter()
+        .find(|r| r.id == repo_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Repo {} not found in workspace", repo_id)))?;
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(workspace)
+        .await?;
+    Ok(PathBuf::from(container_ref).join(&repo.name))
+}
+
+/// Merge each repo's target branch into its worktree and have a coding agent resolve the
+/// conflicts, for when closing the workspace stopped on merge conflicts. A merge or rebase
+/// that already stopped on conflicts in a worktree is resolved as it is, and repos that
+/// merge cleanly are committed. The agent edits the conflicted files without staging them;
+/// once it exits, each file is a proposed or unresolved conflict resolution to accept or
+/// reject. An empty list means nothing conflicted and no agent was started.
+/// Returns 404 if workspace or repo not found.
+/// Returns 400 if workspace is closed, has running processes, or no agent is given and the
+/// workspace has not run one.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn resolve_workspace_conflicts(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<ResolveConflictsRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<ConflictResolution>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::ResolveConflicts)?;
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace is closed (no active worktree)".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot resolve conflicts while processes are running".to_string(),
+        ));
+    }
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+
+    // Pick the agent before touching the worktrees, so a missing one changes nothing
+    let session = RecordedCommandService::session_for_workspace(pool, workspace_id).await?;
+    let session_profile =
+        ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?;
+    let executor_profile_id = payload
+        .executor_profile_id
+        .or_else(|| session_profile.clone())
+        .ok_or_else(|| {
+            ApiError::BadRequest(
+                "No executor profile given and the workspace has not run a coding agent"
+                    .to_string(),
+            )
+        })?;
+
+    let mut repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace_id).await?;
+    if let Some(repo_id) = payload.repo_id {
+        repos.retain(|r| r.repo.id == repo_id);
+        if repos.is_empty() {
+            return Err(ApiError::NotFound(format!(
+                "Repo {} not found in workspace",
+                repo_id
+            )));
+        }
+    }
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let workspace_dir = PathBuf::from(container_ref);
+
+    let mut conflicts = Vec::new();
+    for RepoWithTargetBranch {
+        repo,
+        target_branch,
+        ..
+    } in repos
+    {
+        let worktree_path = workspace_dir.join(&repo.name);
+        let paths = WorkspaceManager::merge_target_into_worktree(
+            deployment.git(),
+            &repo.path,
+            &worktree_path,
+            &target_branch,
+            &format!("Merge '{target_branch}' into '{}'", workspace.branch),
+        )
+        .await?;
+        let (op, target_commits) = tokio::task::spawn_blocking({
+            let git = deployment.git().clone();
+            let repo_path = repo.path.clone();
+            let worktree_path = worktree_path.clone();
+            let branch = workspace.branch.clone();
+            let target_branch = target_branch.clone();
+            move || -> Result<_, git::GitServiceError> {
+                let Some(op) = git.detect_conflict_op(&worktree_path)? else {
+                    return Ok((None, Vec::new()));
+                };
+                let base_commit = git.get_base_commit(&repo_path, &branch, &target_branch)?;
+                let target_commits =
+                    git.get_branch_commits(&repo_path, &target_branch, &base_commit)?;
+                Ok((Some(op), target_commits))
+            }
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+        let Some(op) = op.filter(|_| !paths.is_empty()) else {
+            continue;
+        };
+
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let content = match tokio::fs::read(worktree_path.join(&path)).await {
+                Ok(content) => String::from_utf8_lossy(&content).into_owned(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    "(deleted on one side)".to_string()
+                }
+                Err(e) => return Err(e.into()),
+            };
+            files.push((path, content));
+        }
+        conflicts.push(RepoConflicts {
+            repo,
+            target_branch,
+            op,
+            target_commits,
+            files,
+        });
+    }
+    if conflicts.is_empty() {
+        return Ok(ResponseJson(ApiResponse::success(Vec::new())));
+    }
+
+    let files: Vec<Vec<ConflictFile>> = conflicts
+        .iter()
+        .map(|repo| {
+            repo.files
+                .iter()
+                .map(|(path, content)| ConflictFile { path, content })
+                .collect()
+        })
+        .collect();
+    let prompt_repos: Vec<ConflictRepo> = conflicts
+        .iter()
+        .zip(&files)
+        .map(|(repo, files)| ConflictRepo {
+            repo_name: &repo.repo.name,
+            target_branch: &repo.target_branch,
+            op: &repo.op,
+            target_commits: &repo.target_commits,
+            files,
+        })
+        .collect();
+    let prompt = ConflictResolutionService::build_prompt(
+        &task.title,
+        task.description.as_deref(),
+        &prompt_repos,
+    );
+
+    // Continue the conversation that made the changes when the same agent resolves them
+    let agent_session_id = match &session_profile {
+        Some(profile) if profile.executor == executor_profile_id.executor => {
+            ExecutionProcess::find_latest_coding_agent_turn_session_id(pool, session.id).await?
+        }
+        _ => None,
+    };
+    let working_dir = workspace.agent_working_dir.clone();
+    let action_type = match agent_session_id {
+        Some(session_id) => {
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt,
+                session_id,
+                executor_profile_id,
+                working_dir,
+            })
+        }
+        None => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt,
+            executor_profile_id,
+            working_dir,
+        }),
+    };
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &ExecutorAction::new(action_type, None),
+            &ExecutionProcessRunReason::ConflictResolution,
+        )
+        .await?;
+
+    let mut resolutions = Vec::new();
+    for repo in &conflicts {
+        for (path, _) in &repo.files {
+            resolutions.push(
+                ConflictResolution::create(
+                    pool,
+                    workspace_id,
+                    repo.repo.id,
+                    execution_process.id,
+                    path,
+                )
+                .await?,
+            );
+        }
+    }
+    Ok(ResponseJson(ApiResponse::success(resolutions)))
+}
+
+/// List agent conflict resolutions of a workspace, newest first.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_conflict_resolutions(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<ConflictResolution>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let resolutions = ConflictResolution::find_by_workspace_id(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(resolutions)))
+}
+
+/// Stage the file of a proposed conflict resolution as it is in the worktree, so any edits
+/// made to the proposal are kept, and mark the resolution accepted.
+/// Returns 404 if the workspace, resolution or repo is not found.
+/// Returns 409 if the resolution is not proposed or the file has conflict markers again.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn accept_conflict_resolution(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, resolution_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<ConflictResolution>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let resolution = find_conflict_resolution(pool, workspace_id, resolution_id).await?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::ResolveConflicts)?;
+
+    if resolution.status != ConflictResolutionStatus::Proposed {
+        return Err(ApiError::Conflict(format!(
+            "Only proposed resolutions can be accepted; '{}' is {:?}",
+            resolution.path, resolution.status
+        )));
+    }
+    let worktree_path = repo_worktree_path(&deployment, &workspace, resolution.repo_id).await?;
+    let file_path = worktree_path.join(&resolution.path);
+    if let Ok(content) = tokio::fs::read(&file_path).await
+        && has_conflict_markers(&String::from_utf8_lossy(&content))
+    {
+        return Err(ApiError::Conflict(format!(
+            "'{}' has conflict markers again",
+            resolution.path
+        )));
+    }
+
+    tokio::task::spawn_blocking({
+        let git = deployment.git().clone();
+        let relative_path = PathBuf::from(&resolution.path);
+        move || git.stage_resolved_file(&worktree_path, &relative_path)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    let resolution =
+        ConflictResolution::update_status(pool, resolution_id, ConflictResolutionStatus::Accepted)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(resolution)))
+}
+
+/// Discard the agent's resolution of a file, putting its conflict markers back in the
+/// worktree, and mark the resolution rejected.
+/// Returns 404 if the workspace, resolution or repo is not found.
+/// Returns 409 if the resolution is still running or already accepted or rejected.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn reject_conflict_resolution(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, resolution_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<ConflictResolution>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let resolution = find_conflict_resolution(pool, workspace_id, resolution_id).await?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::ResolveConflicts)?;
+
+    if !matches!(
+        resolution.status,
+        ConflictResolutionStatus::Proposed | ConflictResolutionStatus::Unresolved
+    ) {
+        return Err(ApiError::Conflict(format!(
+            "Only proposed or unresolved resolutions can be rejected; '{}' is {:?}",
+            resolution.path, resolution.status
+        )));
+    }
+    let worktree_path = repo_worktree_path(&deployment, &workspace, resolution.repo_id).await?;
+
+    tokio::task::spawn_blocking({
+        let git = deployment.git().clone();
+        let relative_path = PathBuf::from(&resolution.path);
+        move || git.restore_conflict_markers(&worktree_path, &relative_path)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    let resolution =
+        ConflictResolution::update_status(pool, resolution_id, ConflictResolutionStatus::Rejected)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(resolution)))
+}
+
+/// Finish the merge or rebase in a repo's worktree once all of its conflicts are resolved
+/// and staged. Closing the workspace then merges it without conflicts.
+/// Returns 404 if workspace or repo not found.
+/// Returns 400 if no merge or rebase is in progress in the repo.
+/// Returns 409 if files still have conflicts, including those a rebase stopped on in a
+/// later commit.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn continue_workspace_conflicts(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<ContinueConflictsRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::ResolveConflicts)?;
+    let worktree_path = repo_worktree_path(&deployment, &workspace, payload.repo_id).await?;
+
+    let git = deployment.git().clone();
+    if git.detect_conflict_op(&worktree_path)?.is_none() {
+        return Err(ApiError::BadRequest(
+            "No merge or rebase is in progress in the repo".to_string(),
+        ));
+    }
+    tokio::task::spawn_blocking(move || git.continue_conflicts(&worktree_path))
+        .await
+        .map_err(std::io::Error::other)??;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
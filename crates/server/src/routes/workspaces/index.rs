@@ -0,0 +1,307 @@
+use std::path::PathBuf;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    execution_process::ExecutionProcess, workspace::Workspace, workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use git::StatusEntry;
+use serde::{Deserialize, Serialize};
+use services::services::workspace_lock::{WorkspaceLock, WorkspaceOperation};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// How a file differs in the index or the worktree of a repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexChange {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+    TypeChanged,
+    Untracked,
+    Conflicted,
+}
+
+impl IndexChange {
+    /// Change shown by one column of `git status --porcelain`; None when the column is blank
+    fn from_status(status: char) -> Option<Self> {
+        match status {
+            'A' => Some(IndexChange::Added),
+            'M' => Some(IndexChange::Modified),
+            'D' => Some(IndexChange::Deleted),
+            'R' => Some(IndexChange::Renamed),
+            'C' => Some(IndexChange::Copied),
+            'T' => Some(IndexChange::TypeChanged),
+            _ => None,
+        }
+    }
+}
+
+/// A changed file of a repo worktree
+#[derive(Debug, Serialize, TS)]
+pub struct IndexEntry {
+    pub path: String,
+    /// Path before a rename or copy
+    pub orig_path: Option<String>,
+    /// Change staged for the next commit
+    pub staged: Option<IndexChange>,
+    /// Change in the worktree that is not staged
+    pub unstaged: Option<IndexChange>,
+}
+
+impl From<&StatusEntry> for IndexEntry {
+    fn from(entry: &StatusEntry) -> Self {
+        let conflicted = entry.staged == 'U'
+            || entry.unstaged == 'U'
+            || (entry.staged == entry.unstaged && matches!(entry.staged, 'A' | 'D'));
+        let (staged, unstaged) = if entry.is_untracked {
+            (None, Some(IndexChange::Untracked))
+        } else if conflicted {
+            (None, Some(IndexChange::Conflicted))
+        } else {
+            (
+                IndexChange::from_status(entry.staged),
+                IndexChange::from_status(entry.unstaged),
+            )
+        };
+        Self {
+            path: String::from_utf8_lossy(&entry.path).into_owned(),
+            orig_path: entry
+                .orig_path
+                .as_ref()
+                .map(|path| String::from_utf8_lossy(path).into_owned()),
+            staged,
+            unstaged,
+        }
+    }
+}
+
+/// Staged and unstaged changes of one repo worktree of a workspace
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceRepoIndex {
+    pub repo_id: Uuid,
+    pub entries: Vec<IndexEntry>,
+}
+
+/// Request body for staging or unstaging files of a repo worktree
+#[derive(Debug, Deserialize, TS)]
+pub struct WorkspaceRepoPathsRequest {
+    /// Paths relative to the repo root, as listed in the repo's index state
+    pub paths: Vec<String>,
+}
+
+/// Request body for committing what is staged in a repo worktree
+#[derive(Debug, Deserialize, TS)]
+pub struct CommitWorkspaceRepoRequest {
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct CommitWorkspaceRepoResponse {
+    pub commit_sha: String,
+}
+
+/// Worktree of one repo of an open workspace
+async fn open_repo_worktree(
+    deployment: &DeploymentImpl,
+    workspace_id: Uuid,
+    repo_id: Uuid,
+) -> Result<PathBuf, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let Some(container_ref) = workspace
+        .container_ref
+        .as_deref()
+        .filter(|_| !workspace.archived)
+    else {
+        return Err(ApiError::BadRequest(
+            "Workspace has no open worktrees".to_string(),
+        ));
+    };
+    let repo = WorkspaceRepo::find_repos_for_workspace(pool, workspace_id)
+        .await?
+        .into_iter()
+        .find(|r| r.id == repo_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Repo {repo_id} not found in workspace")))?;
+    Ok(PathBuf::from(container_ref).join(&repo.name))
+}
+
+/// Changing the index while an agent works in the worktree would race its own git use
+async fn ensure_no_running_processes(
+    deployment: &DeploymentImpl,
+    workspace_id: Uuid,
+) -> Result<(), ApiError> {
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+        &deployment.db().pool,
+        workspace_id,
+    )
+    .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot change the index of a workspace with running processes".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn repo_index(
+    deployment: &DeploymentImpl,
+    repo_id: Uuid,
+    worktree_path: &std::path::Path,
+) -> Result<WorkspaceRepoIndex, ApiError> {
+    let status = deployment.git().get_worktree_status(worktree_path)?;
+    Ok(WorkspaceRepoIndex {
+        repo_id,
+        entries: status.entries.iter().map(IndexEntry::from).collect(),
+    })
+}
+
+/// Check every path names an entry of the index that `applies` to
+fn check_index_paths(
+    index: &WorkspaceRepoIndex,
+    paths: &[String],
+    applies: impl Fn(&IndexEntry) -> bool,
+    problem: &str,
+) -> Result<(), ApiError> {
+    if paths.is_empty() {
+        return Err(ApiError::BadRequest("No paths given".to_string()));
+    }
+    for path in paths {
+        let listed = index.entries.iter().any(|entry| {
+            (entry.path == *path || entry.orig_path.as_ref() == Some(path)) && applies(entry)
+        });
+        if !listed {
+            return Err(ApiError::BadRequest(format!("'{path}' {problem}")));
+        }
+    }
+    Ok(())
+}
+
+/// Staged and unstaged changes of one repo worktree, for curating the next commit.
+/// Returns 404 if workspace not found or the repo is not part of it.
+/// Returns 400 if the workspace is archived or closed.
+pub async fn get_workspace_repo_index(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, repo_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceRepoIndex>>, ApiError> {
+    let worktree_path = open_repo_worktree(&deployment, workspace_id, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(repo_index(
+        &deployment,
+        repo_id,
+        &worktree_path,
+    )?)))
+}
+
+/// Stage files of one repo worktree for the next commit, including deletions, and return
+/// the repo's index state.
+/// Returns 404 if workspace not found or the repo is not part of it.
+/// Returns 400 if the workspace is archived or closed or has running processes, or if a
+/// path has no unstaged changes.
+/// Returns 423 if another mutating operation is already running for the workspace.
+pub async fn stage_workspace_repo_paths(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, repo_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<WorkspaceRepoPathsRequest>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceRepoIndex>>, ApiError> {
+    let worktree_path = open_repo_worktree(&deployment, workspace_id, repo_id).await?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Stage)?;
+    ensure_no_running_processes(&deployment, workspace_id).await?;
+
+    let index = repo_index(&deployment, repo_id, &worktree_path)?;
+    check_index_paths(
+        &index,
+        &request.paths,
+        |entry| entry.unstaged.is_some(),
+        "has no unstaged changes",
+    )?;
+    deployment
+        .git()
+        .stage_paths(&worktree_path, &request.paths)?;
+    Ok(ResponseJson(ApiResponse::success(repo_index(
+        &deployment,
+        repo_id,
+        &worktree_path,
+    )?)))
+}
+
+/// Unstage files of one repo worktree, keeping their changes in the worktree, and return
+/// the repo's index state. Unstage both paths of a staged rename to undo it.
+/// Returns 404 if workspace not found or the repo is not part of it.
+/// Returns 400 if the workspace is archived or closed or has running processes, or if a
+/// path has no staged changes.
+/// Returns 423 if another mutating operation is already running for the workspace.
+pub async fn unstage_workspace_repo_paths(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, repo_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<WorkspaceRepoPathsRequest>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceRepoIndex>>, ApiError> {
+    let worktree_path = open_repo_worktree(&deployment, workspace_id, repo_id).await?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Stage)?;
+    ensure_no_running_processes(&deployment, workspace_id).await?;
+
+    let index = repo_index(&deployment, repo_id, &worktree_path)?;
+    check_index_paths(
+        &index,
+        &request.paths,
+        |entry| entry.staged.is_some(),
+        "has no staged changes",
+    )?;
+    deployment
+        .git()
+        .unstage_paths(&worktree_path, &request.paths)?;
+    Ok(ResponseJson(ApiResponse::success(repo_index(
+        &deployment,
+        repo_id,
+        &worktree_path,
+    )?)))
+}
+
+/// Commit only what is staged in one repo worktree onto the workspace branch; unstaged
+/// changes stay in the worktree.
+/// Returns 404 if workspace not found or the repo is not part of it.
+/// Returns 400 if the workspace is archived or closed or has running processes, if the
+/// message is empty, or if nothing is staged.
+/// Returns 423 if another mutating operation is already running for the workspace.
+pub async fn commit_workspace_repo(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, repo_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<CommitWorkspaceRepoRequest>,
+) -> Result<ResponseJson<ApiResponse<CommitWorkspaceRepoResponse>>, ApiError> {
+    let worktree_path = open_repo_worktree(&deployment, workspace_id, repo_id).await?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Commit)?;
+    ensure_no_running_processes(&deployment, workspace_id).await?;
+    if request.message.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "A commit message is required".to_string(),
+        ));
+    }
+
+    let commit_sha = deployment
+        .git()
+        .commit_staged(&worktree_path, &request.message)?
+        .ok_or_else(|| ApiError::BadRequest("Nothing is staged to commit".to_string()))?;
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_repo_committed",
+            serde_json::json!({
+                "workspace_id": workspace_id.to_string(),
+                "repo_id": repo_id.to_string(),
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(
+        CommitWorkspaceRepoResponse { commit_sha },
+    )))
+}
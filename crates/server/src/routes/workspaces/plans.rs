@@ -0,0 +1,302 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    plan_turn::{PlanTurn, PlanTurnStatus},
+    scratch::DraftFollowUpData,
+    session::Session,
+    task::Task,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_initial::CodingAgentInitialRequest,
+    },
+    profile::ExecutorProfileId,
+};
+use serde::Deserialize;
+use services::services::{
+    agent_plan::AgentPlanService,
+    container::ContainerService,
+    recorded_command::RecordedCommandService,
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Request body for starting a plan-only agent run on a workspace
+#[derive(Debug, Deserialize, TS)]
+pub struct StartPlanRequest {
+    /// Extra instructions for the plan, added to the task
+    #[serde(default)]
+    #[ts(optional)]
+    pub prompt: Option<String>,
+    /// The agent to plan with; defaults to the workspace's last coding agent
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+/// Request body for approving a proposed plan
+#[derive(Debug, Deserialize, TS)]
+pub struct ApprovePlanRequest {
+    /// Notes for the run carrying out the plan, taking precedence over the plan
+    #[serde(default)]
+    #[ts(optional)]
+    pub feedback: Option<String>,
+    /// The agent to carry out the plan with; defaults to the workspace's last coding agent,
+    /// then the one that made the plan
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+async fn find_plan_turn(
+    pool: &sqlx::SqlitePool,
+    workspace_id: Uuid,
+    plan_id: Uuid,
+) -> Result<PlanTurn, ApiError> {
+    match PlanTurn::find_by_id(pool, plan_id).await? {
+        Some(plan) if plan.workspace_id == workspace_id => Ok(plan),
+        _ => Err(ApiError::NotFound(format!(
+            "Plan {} not found in workspace",
+            plan_id
+        ))),
+    }
+}
+
+/// Have a coding agent propose a plan for the workspace's task without changing anything.
+/// The plan run is never committed; when it exits, the agent's last message is the proposed
+/// plan, to approve or reject.
+/// Returns 404 if workspace not found.
+/// Returns 400 if workspace is closed, has running processes, or no agent is given and the
+/// workspace has not run one.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn start_workspace_plan(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<StartPlanRequest>,
+) -> Result<ResponseJson<ApiResponse<PlanTurn>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Plan)?;
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace is closed (no active worktree)".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot plan while processes are running".to_string(),
+        ));
+    }
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+
+    let session = RecordedCommandService::session_for_workspace(pool, workspace_id).await?;
+    let executor_profile_id = match payload.executor_profile_id {
+        Some(profile) => profile,
+        None => ExecutionProcess::latest_executor_profile_for_session(pool, session.id)
+            .await?
+            .ok_or_else(|| {
+                ApiError::BadRequest(
+                    "No executor profile given and the workspace has not run a coding agent"
+                        .to_string(),
+                )
+            })?,
+    };
+    let prompt = AgentPlanService::build_plan_prompt(
+        &task.title,
+        task.description.as_deref(),
+        payload.prompt.as_deref(),
+    );
+
+    // A fresh agent conversation and no cleanup script: the plan run only reads
+    let action = ExecutorAction::new(
+        ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt,
+            executor_profile_id,
+            working_dir: workspace.agent_working_dir.clone(),
+        }),
+        None,
+    );
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::Plan,
+        )
+        .await?;
+
+    let plan = PlanTurn::create(pool, workspace_id, execution_process.id).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+/// List plan turns of a workspace, newest first.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_plans(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<PlanTurn>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let plans = PlanTurn::find_by_workspace_id(pool, workspace_id).await?;
+    Ok(ResponseJson(ApiResponse::success(plans)))
+}
+
+/// Approve a proposed plan and start the coding agent run that carries it out, with the
+/// plan in its prompt. The run continues the workspace's agent conversation and is
+/// committed like any other.
+/// Returns 404 if the workspace or plan is not found.
+/// Returns 400 if workspace is closed, has running processes, or the agent differs from
+/// the one the workspace runs.
+/// Returns 409 if the plan is not proposed.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn approve_workspace_plan(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, plan_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<ApprovePlanRequest>,
+) -> Result<ResponseJson<ApiResponse<PlanTurn>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let plan = find_plan_turn(pool, workspace_id, plan_id).await?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Plan)?;
+
+    let Some(plan_text) = plan
+        .plan
+        .as_deref()
+        .filter(|_| plan.status == PlanTurnStatus::Proposed)
+    else {
+        return Err(ApiError::Conflict(format!(
+            "Only proposed plans can be approved; plan {} is {:?}",
+            plan.id, plan.status
+        )));
+    };
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace is closed (no active worktree)".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot start the plan while processes are running".to_string(),
+        ));
+    }
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Task {} not found", workspace.task_id)))?;
+
+    let plan_process = ExecutionProcess::find_by_id(pool, plan.execution_process_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Execution process {} not found",
+                plan.execution_process_id
+            ))
+        })?;
+    let session = Session::find_by_id(pool, plan_process.session_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Session {} not found", plan_process.session_id))
+        })?;
+    let session_profile =
+        ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?;
+    let expected_executor = session_profile
+        .as_ref()
+        .map(|profile| profile.executor.to_string())
+        .or_else(|| session.executor.clone());
+    let executor_profile_id = match payload.executor_profile_id.or(session_profile) {
+        Some(profile) => profile,
+        None => match plan_process.executor_action().map(|action| action.typ()) {
+            Ok(ExecutorActionType::CodingAgentInitialRequest(request)) => {
+                request.executor_profile_id.clone()
+            }
+            _ => {
+                return Err(ApiError::BadRequest(
+                    "No executor profile given and the workspace has not run a coding agent"
+                        .to_string(),
+                ));
+            }
+        },
+    };
+    if let Some(expected) = expected_executor
+        && expected != executor_profile_id.executor.to_string()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "The workspace runs {}; the plan must be carried out by the same agent",
+            expected
+        )));
+    }
+
+    let message = AgentPlanService::build_execution_prompt(
+        &task.title,
+        task.description.as_deref(),
+        plan_text,
+        payload.feedback.as_deref(),
+    );
+    let execution_process = deployment
+        .container()
+        .start_queued_follow_up(
+            &workspace,
+            &session,
+            &DraftFollowUpData {
+                message,
+                executor_profile_id,
+            },
+        )
+        .await?;
+
+    let plan = PlanTurn::approve(pool, plan_id, execution_process.id).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+/// Reject a proposed plan; nothing is changed in the workspace.
+/// Returns 404 if the workspace or plan is not found.
+/// Returns 409 if the plan is not proposed.
+#[axum::debug_handler]
+pub async fn reject_workspace_plan(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, plan_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<PlanTurn>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let plan = find_plan_turn(pool, workspace_id, plan_id).await?;
+    if plan.status != PlanTurnStatus::Proposed {
+        return Err(ApiError::Conflict(format!(
+            "Only proposed plans can be rejected; plan {} is {:?}",
+            plan.id, plan.status
+        )));
+    }
+
+    let plan = PlanTurn::reject(pool, plan_id).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
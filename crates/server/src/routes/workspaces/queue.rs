@@ -0,0 +1,246 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    execution_process::ExecutionProcess, scratch::DraftFollowUpData, session::Session,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::ContainerService,
+    queued_message::QueuedMessage,
+    recorded_command::RecordedCommandService,
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Request body for adding a follow-up prompt to a workspace's queue
+#[derive(Debug, Deserialize, TS)]
+pub struct QueueFollowUpRequest {
+    pub message: String,
+    /// Agent to run the prompt with; defaults to the one the workspace last ran
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+/// Request body for reordering a workspace's queued follow-up prompts
+#[derive(Debug, Deserialize, TS)]
+pub struct ReorderQueueRequest {
+    /// Every queued message id, in the order the prompts should run
+    pub message_ids: Vec<Uuid>,
+}
+
+/// Response for adding a follow-up prompt to a workspace's queue
+#[derive(Debug, Serialize, TS)]
+pub struct QueueFollowUpResponse {
+    pub queued: QueuedMessage,
+    /// The agent run started for the prompt, if nothing was running so it did not wait
+    pub started: Option<ExecutionProcess>,
+}
+
+/// Follow-up prompts queued on a workspace's latest session, in the order they will run.
+/// An empty list if the workspace has no session yet.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn get_workspace_queue(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedMessage>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let queued = match Session::find_latest_by_workspace_id(pool, workspace_id).await? {
+        Some(session) => deployment.queued_message_service().list_queued(session.id),
+        None => Vec::new(),
+    };
+    Ok(ResponseJson(ApiResponse::success(queued)))
+}
+
+/// Add a follow-up prompt to the end of a workspace's queue. Queued prompts run one after
+/// another, each once the agent run before it completes; a failed or killed run discards
+/// the rest of the queue. If no agent is running the prompt starts right away.
+/// Returns 404 if workspace not found.
+/// Returns 400 if workspace is closed, no agent is given and the workspace has not run one,
+/// or the agent differs from the one the workspace runs.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn queue_workspace_follow_up(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<QueueFollowUpRequest>,
+) -> Result<ResponseJson<ApiResponse<QueueFollowUpResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::FollowUp)?;
+    if workspace.container_ref.is_none() {
+        return Err(ApiError::BadRequest(
+            "Workspace is closed (no active worktree)".to_string(),
+        ));
+    }
+    if payload.message.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Message must not be empty".to_string(),
+        ));
+    }
+
+    let session = RecordedCommandService::session_for_workspace(pool, workspace_id).await?;
+    let session_profile =
+        ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?;
+    let expected_executor = session_profile
+        .as_ref()
+        .map(|profile| profile.executor.to_string())
+        .or_else(|| session.executor.clone());
+    let executor_profile_id = payload
+        .executor_profile_id
+        .or(session_profile)
+        .ok_or_else(|| {
+            ApiError::BadRequest(
+                "No executor profile given and the workspace has not run a coding agent"
+                    .to_string(),
+            )
+        })?;
+    if let Some(expected) = expected_executor
+        && expected != executor_profile_id.executor.to_string()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "The workspace runs {}; queued prompts must use the same agent",
+            expected
+        )));
+    }
+
+    let queue = deployment.queued_message_service();
+    let queued = queue.append_message(
+        session.id,
+        DraftFollowUpData {
+            message: payload.message,
+            executor_profile_id,
+        },
+    );
+
+    // With nothing running no exit will pick the queue up, so start its first prompt now
+    let mut started = None;
+    if !ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+        && let Some(next) = queue.take_queued(session.id)
+    {
+        started = Some(
+            deployment
+                .container()
+                .start_queued_follow_up(&workspace, &session, &next.data)
+                .await?,
+        );
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "follow_up_queued",
+            serde_json::json!({
+                "session_id": session.id.to_string(),
+                "workspace_id": workspace_id.to_string(),
+                "started": started.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(QueueFollowUpResponse {
+        queued,
+        started,
+    })))
+}
+
+/// Put a workspace's queued follow-up prompts in a new order.
+/// Returns 404 if workspace not found.
+/// Returns 409 if the ids are not every queued message exactly once, as happens when a
+/// prompt started or was cancelled in the meantime.
+#[axum::debug_handler]
+pub async fn reorder_workspace_queue(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<ReorderQueueRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedMessage>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let queue = deployment.queued_message_service();
+    let reordered = match Session::find_latest_by_workspace_id(pool, workspace_id).await? {
+        Some(session) if queue.has_queued(session.id) => {
+            queue.reorder(session.id, &payload.message_ids)
+        }
+        _ => payload.message_ids.is_empty().then(Vec::new),
+    };
+    let reordered = reordered.ok_or_else(|| {
+        ApiError::Conflict("Message ids must list every queued message exactly once".to_string())
+    })?;
+    Ok(ResponseJson(ApiResponse::success(reordered)))
+}
+
+/// Remove one follow-up prompt from a workspace's queue.
+/// Returns 404 if workspace or queued message not found.
+#[axum::debug_handler]
+pub async fn cancel_workspace_queued_message(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, message_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<QueuedMessage>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let cancelled = Session::find_latest_by_workspace_id(pool, workspace_id)
+        .await?
+        .and_then(|session| {
+            deployment
+                .queued_message_service()
+                .cancel_message(session.id, message_id)
+        })
+        .ok_or_else(|| ApiError::NotFound(format!("Queued message {} not found", message_id)))?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "follow_up_queue_cancelled",
+            serde_json::json!({
+                "session_id": cancelled.session_id.to_string(),
+                "workspace_id": workspace_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(cancelled)))
+}
+
+/// Remove every follow-up prompt from a workspace's queue, returning those removed.
+/// Returns 404 if workspace not found.
+#[axum::debug_handler]
+pub async fn clear_workspace_queue(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedMessage>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let cancelled = match Session::find_latest_by_workspace_id(pool, workspace_id).await? {
+        Some(session) => deployment
+            .queued_message_service()
+            .cancel_queued(session.id),
+        None => Vec::new(),
+    };
+    Ok(ResponseJson(ApiResponse::success(cancelled)))
+}
@@ -0,0 +1,447 @@
+use std::path::{Component, PathBuf};
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    execution_process::ExecutionProcess,
+    review_comment::{CreateReviewComment, ReviewComment, ReviewCommentSide, UpdateReviewComment},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::ContainerService,
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+};
+use ts_rs::TS;
+use utils::{response::ApiResponse, text::replace_lines};
+use uuid::Uuid;
+
+use super::resolve_workspace_repo;
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Query parameters for listing review comments
+#[derive(Debug, Deserialize)]
+pub struct ReviewCommentsQuery {
+    /// Only comments on this repo, by name or id
+    pub repo: Option<String>,
+    /// Only comments on this file
+    pub path: Option<String>,
+    /// Only resolved (true) or open (false) comments
+    pub resolved: Option<bool>,
+}
+
+async fn find_review_comment(
+    pool: &sqlx::SqlitePool,
+    workspace_id: Uuid,
+    comment_id: Uuid,
+) -> Result<ReviewComment, ApiError> {
+    match ReviewComment::find_by_id(pool, comment_id).await? {
+        Some(comment) if comment.workspace_id == workspace_id => Ok(comment),
+        _ => Err(ApiError::NotFound(format!(
+            "Review comment {} not found in workspace",
+            comment_id
+        ))),
+    }
+}
+
+/// Review comments of the workspace in file and line order.
+/// Returns 404 if workspace not found, or `repo` is not one of its repos.
+#[axum::debug_handler]
+pub async fn get_review_comments(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Query(query): Query<ReviewCommentsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ReviewComment>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let repo_id = match &query.repo {
+        Some(repo) => {
+            let found = resolve_workspace_repo(pool, workspace_id, repo).await?;
+            Some(found.repo.id)
+        }
+        None => None,
+    };
+
+    let comments = ReviewComment::find_by_workspace_id(pool, workspace_id)
+        .await?
+        .into_iter()
+        .filter(|c| repo_id.is_none_or(|id| c.repo_id == id))
+        .filter(|c| query.path.as_ref().is_none_or(|path| &c.path == path))
+        .filter(|c| {
+            query
+                .resolved
+                .is_none_or(|resolved| c.resolved_at.is_some() == resolved)
+        })
+        .collect();
+    Ok(ResponseJson(ApiResponse::success(comments)))
+}
+
+/// Comment on a line of a file in the workspace diff.
+/// Returns 404 if workspace not found, or the repo is not one of its repos.
+/// Returns 400 if the body or path is empty or the line is not positive.
+#[axum::debug_handler]
+pub async fn create_review_comment(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<CreateReviewComment>,
+) -> Result<ResponseJson<ApiResponse<ReviewComment>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace_id, payload.repo_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Repo {} not found in workspace", payload.repo_id))
+        })?;
+    if payload.body.trim().is_empty() {
+        return Err(ApiError::BadRequest("Comment body is required".to_string()));
+    }
+    if payload.path.trim().is_empty() {
+        return Err(ApiError::BadRequest("Comment path is required".to_string()));
+    }
+    if payload.line < 1 {
+        return Err(ApiError::BadRequest(
+            "Comment line must be 1 or greater".to_string(),
+        ));
+    }
+    if payload
+        .end_line
+        .is_some_and(|end_line| end_line < payload.line)
+    {
+        return Err(ApiError::BadRequest(
+            "Comment end_line must not be before line".to_string(),
+        ));
+    }
+    if payload.suggestion.is_some() && payload.side != ReviewCommentSide::New {
+        return Err(ApiError::BadRequest(
+            "Suggestions can only be made on the new side of the diff".to_string(),
+        ));
+    }
+
+    let comment = ReviewComment::create(pool, workspace_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(comment)))
+}
+
+/// Edit a review comment's body, or resolve or reopen it.
+/// Returns 404 if the comment is not found in the workspace.
+/// Returns 400 if the new body is empty.
+#[axum::debug_handler]
+pub async fn update_review_comment(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateReviewComment>,
+) -> Result<ResponseJson<ApiResponse<ReviewComment>>, ApiError> {
+    let pool = &deployment.db().pool;
+    find_review_comment(pool, workspace_id, comment_id).await?;
+    if payload
+        .body
+        .as_ref()
+        .is_some_and(|body| body.trim().is_empty())
+    {
+        return Err(ApiError::BadRequest("Comment body is required".to_string()));
+    }
+
+    let comment = ReviewComment::update(pool, comment_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(comment)))
+}
+
+/// Request body for applying a review comment's suggestion
+#[derive(Debug, Default, Deserialize)]
+pub struct ApplySuggestionRequest {
+    /// Commit message; defaults to one naming the file
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Response for applying a review comment's suggestion
+#[derive(Debug, Serialize, TS)]
+pub struct ApplySuggestionResponse {
+    /// The comment, now resolved
+    pub comment: ReviewComment,
+    pub commit_sha: String,
+}
+
+/// `content` with the commented lines replaced by `suggestion`. Conflicts if the lines are no
+/// longer in the file or already read as suggested, as there would be nothing to commit.
+fn apply_suggestion(
+    content: &str,
+    comment: &ReviewComment,
+    suggestion: &str,
+) -> Result<String, ApiError> {
+    let first = comment.line as usize;
+    let last = comment.end_line.unwrap_or(comment.line) as usize;
+    let updated = replace_lines(content, first, last, suggestion).ok_or_else(|| {
+        ApiError::Conflict(format!(
+            "Lines {first}-{last} are no longer in '{}'",
+            comment.path
+        ))
+    })?;
+    if updated == content {
+        return Err(ApiError::Conflict(format!(
+            "The suggestion is already applied to '{}'",
+            comment.path
+        )));
+    }
+    Ok(updated)
+}
+
+/// Replace the commented lines of the worktree file with the comment's suggestion, commit
+/// that file alone on the workspace branch and resolve the comment.
+/// Returns 404 if the comment, repo or file is not found.
+/// Returns 400 if the comment has no suggestion or processes are running.
+/// Returns 409 if the comment is already resolved, its lines are no longer in the file or
+/// already read as suggested.
+/// Returns 423 if another mutating operation is already running for the workspace.
+#[axum::debug_handler]
+pub async fn apply_review_suggestion(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<ApplySuggestionRequest>,
+) -> Result<ResponseJson<ApiResponse<ApplySuggestionResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+    let comment = find_review_comment(pool, workspace_id, comment_id).await?;
+    let _lock = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::ApplySuggestion)?;
+
+    let Some(suggestion) = &comment.suggestion else {
+        return Err(ApiError::BadRequest(
+            "Review comment has no suggestion".to_string(),
+        ));
+    };
+    if comment.resolved_at.is_some() {
+        return Err(ApiError::Conflict(
+            "Review comment is already resolved".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::BadRequest(
+            "Cannot apply a suggestion while processes are running".to_string(),
+        ));
+    }
+
+    let relative_path = PathBuf::from(&comment.path);
+    if !relative_path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(ApiError::BadRequest(
+            "Comment path must be relative to the repo root and stay inside it".to_string(),
+        ));
+    }
+    let repo = WorkspaceRepo::find_repos_for_workspace(pool, workspace_id)
+        .await?
+        .into_iter()
+        .find(|r| r.id == comment.repo_id)
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Repo {} not found in workspace", comment.repo_id))
+        })?;
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let worktree_path = PathBuf::from(container_ref).join(&repo.name);
+    let file_path = worktree_path.join(&relative_path);
+    if !file_path.is_file() {
+        return Err(ApiError::NotFound(format!(
+            "File '{}' not found in repo '{}'",
+            comment.path, repo.name
+        )));
+    }
+
+    let content = tokio::fs::read_to_string(&file_path).await?;
+    let updated = apply_suggestion(&content, &comment, suggestion)?;
+    tokio::fs::write(&file_path, updated).await?;
+
+    let message = request
+        .message
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| format!("Apply review suggestion to {}", comment.path));
+    let commit_sha = tokio::task::spawn_blocking({
+        let git = deployment.git().clone();
+        move || git.commit_file(&worktree_path, &relative_path, &message)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    let comment = ReviewComment::update(
+        pool,
+        comment_id,
+        &UpdateReviewComment {
+            body: None,
+            resolved: Some(true),
+        },
+    )
+    .await?;
+    tracing::info!(
+        "Applied review suggestion {} to workspace {} repo '{}' as {}",
+        comment_id,
+        workspace_id,
+        repo.name,
+        commit_sha
+    );
+
+    Ok(ResponseJson(ApiResponse::success(
+        ApplySuggestionResponse {
+            comment,
+            commit_sha,
+        },
+    )))
+}
+
+/// Returns 404 if the comment is not found in the workspace.
+#[axum::debug_handler]
+pub async fn delete_review_comment(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    find_review_comment(pool, workspace_id, comment_id).await?;
+    ReviewComment::delete(pool, comment_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::test_support::{seed_project, seed_task, seed_workspace, test_pool};
+
+    #[test]
+    fn suggestions_that_change_nothing_conflict() {
+        let comment = ReviewComment {
+            id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            repo_id: Uuid::new_v4(),
+            path: "src/lib.rs".to_string(),
+            line: 2,
+            end_line: Some(3),
+            side: ReviewCommentSide::New,
+            body: "Use a constant".to_string(),
+            suggestion: None,
+            severity: None,
+            ai_review_id: None,
+            resolved_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let content = "fn main() {\n    let x = 1;\n    let y = 2;\n}\n";
+
+        assert_eq!(
+            apply_suggestion(content, &comment, "    const X: u8 = 1;").unwrap(),
+            "fn main() {\n    const X: u8 = 1;\n}\n"
+        );
+        assert!(matches!(
+            apply_suggestion(content, &comment, "    let x = 1;\n    let y = 2;"),
+            Err(ApiError::Conflict(_))
+        ));
+        assert!(matches!(
+            apply_suggestion("fn main() {}\n", &comment, "x"),
+            Err(ApiError::Conflict(_))
+        ));
+    }
+
+    async fn comment_on(
+        pool: &sqlx::SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        path: &str,
+        line: i64,
+    ) -> ReviewComment {
+        let comment = CreateReviewComment {
+            repo_id,
+            path: path.to_string(),
+            line,
+            end_line: None,
+            side: ReviewCommentSide::New,
+            body: "Please rename".to_string(),
+            suggestion: None,
+            severity: None,
+        };
+        ReviewComment::create(pool, workspace_id, &comment)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn review_comments_are_counted_per_file_and_scoped_to_their_workspace() {
+        let pool = test_pool().await;
+        let (project, repos) = seed_project(&pool, &["api"]).await;
+        let task = seed_task(&pool, project.id, "Add login").await;
+        let workspace = seed_workspace(&pool, task.id, "feature", &[&repos[0]]).await;
+        let other = seed_workspace(&pool, task.id, "other", &[&repos[0]]).await;
+        let repo_id = repos[0].id;
+
+        let resolved = comment_on(&pool, workspace.id, repo_id, "src/lib.rs", 3).await;
+        comment_on(&pool, workspace.id, repo_id, "src/lib.rs", 1).await;
+        comment_on(&pool, workspace.id, repo_id, "README.md", 7).await;
+        let update = UpdateReviewComment {
+            body: None,
+            resolved: Some(true),
+        };
+        let resolved = ReviewComment::update(&pool, resolved.id, &update)
+            .await
+            .unwrap();
+        assert!(resolved.resolved_at.is_some());
+        assert_eq!(resolved.body, "Please rename");
+
+        let order: Vec<_> = ReviewComment::find_by_workspace_id(&pool, workspace.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.path, c.line))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("README.md".to_string(), 7),
+                ("src/lib.rs".to_string(), 1),
+                ("src/lib.rs".to_string(), 3),
+            ]
+        );
+        let mut counts: Vec<_> = ReviewComment::count_by_file(&pool, workspace.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.path, c.total, c.unresolved))
+            .collect();
+        counts.sort();
+        assert_eq!(
+            counts,
+            vec![
+                ("README.md".to_string(), 1, 1),
+                ("src/lib.rs".to_string(), 2, 1),
+            ]
+        );
+
+        assert!(
+            find_review_comment(&pool, workspace.id, resolved.id)
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            find_review_comment(&pool, other.id, resolved.id).await,
+            Err(ApiError::NotFound(_))
+        ));
+        assert!(
+            ReviewComment::count_by_file(&pool, other.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Fixtures shared by the tests of route handlers that work on the database.
+
+use std::path::{Path, PathBuf};
+
+use db::{
+    DBService,
+    models::{
+        execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason},
+        project::{CreateProject, Project},
+        project_repo::ProjectRepo,
+        repo::Repo,
+        session::{CreateSession, Session},
+        task::{CreateTask, Task},
+        workspace::{CreateWorkspace, Workspace},
+        workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
+    },
+};
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_initial::CodingAgentInitialRequest,
+    },
+    executors::BaseCodingAgent,
+    profile::ExecutorProfileId,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// A scratch directory removed when dropped
+pub(crate) struct ScratchDir(pub PathBuf);
+
+impl ScratchDir {
+    pub(crate) fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("vk-server-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run git in `dir` as a test user, returning its stdout
+pub(crate) fn run_git(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("run git");
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Initialize a repo at `path` on `main` with `file` committed
+pub(crate) fn init_repo(path: &Path, file: &str, content: &str) {
+    std::fs::create_dir_all(path).unwrap();
+    run_git(path, &["init", "-q", "-b", "main"]);
+    std::fs::write(path.join(file), content).unwrap();
+    run_git(path, &["add", "-A"]);
+    run_git(path, &["commit", "-q", "-m", "Initial commit"]);
+}
+
+/// A fresh in-memory database with the schema in place
+pub(crate) async fn test_pool() -> SqlitePool {
+    DBService::new_in_memory()
+        .await
+        .expect("in-memory database")
+        .pool
+}
+
+/// A project with a repo named after each of `repo_names`; the repo paths need not exist
+pub(crate) async fn seed_project(pool: &SqlitePool, repo_names: &[&str]) -> (Project, Vec<Repo>) {
+    let project = Project::create(
+        pool,
+        &CreateProject {
+            name: "Test project".to_string(),
+            repositories: Vec::new(),
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+    let mut repos = Vec::new();
+    for name in repo_names {
+        let path = Path::new("/nonexistent")
+            .join(Uuid::new_v4().to_string())
+            .join(name);
+        let repo = Repo::find_or_create(pool, &path, name).await.unwrap();
+        ProjectRepo::create(pool, project.id, repo.id)
+            .await
+            .unwrap();
+        repos.push(repo);
+    }
+    (project, repos)
+}
+
+pub(crate) async fn seed_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Task {
+    Task::create(
+        pool,
+        &CreateTask::from_title_description(project_id, title.to_string(), None),
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap()
+}
+
+/// A workspace of `task_id` on `branch`, targeting `main` in each of `repos`
+pub(crate) async fn seed_workspace(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    branch: &str,
+    repos: &[&Repo],
+) -> Workspace {
+    let workspace = Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch: branch.to_string(),
+            agent_working_dir: None,
+        },
+        Uuid::new_v4(),
+        task_id,
+    )
+    .await
+    .unwrap();
+    let workspace_repos: Vec<CreateWorkspaceRepo> = repos
+        .iter()
+        .map(|repo| CreateWorkspaceRepo {
+            repo_id: repo.id,
+            target_branch: "main".to_string(),
+            start_from_ref: None,
+        })
+        .collect();
+    WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos)
+        .await
+        .unwrap();
+    workspace
+}
+
+/// Record a running agent process in a new session of `workspace_id`
+pub(crate) async fn seed_execution_process(
+    pool: &SqlitePool,
+    workspace_id: Uuid,
+    run_reason: ExecutionProcessRunReason,
+) -> ExecutionProcess {
+    let session = Session::create(
+        pool,
+        &CreateSession { executor: None },
+        Uuid::new_v4(),
+        workspace_id,
+    )
+    .await
+    .unwrap();
+    start_execution_process(pool, session.id, run_reason).await
+}
+
+/// Record a running agent process in `session_id`
+pub(crate) async fn start_execution_process(
+    pool: &SqlitePool,
+    session_id: Uuid,
+    run_reason: ExecutionProcessRunReason,
+) -> ExecutionProcess {
+    let action = ExecutorAction::new(
+        ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt: "Do the task".to_string(),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            working_dir: None,
+        }),
+        None,
+    );
+    ExecutionProcess::create(
+        pool,
+        &CreateExecutionProcess {
+            session_id,
+            executor_action: action,
+            run_reason,
+        },
+        Uuid::new_v4(),
+        &[],
+    )
+    .await
+    .unwrap()
+}
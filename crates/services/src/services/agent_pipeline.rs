@@ -0,0 +1,165 @@
+//! Multi-stage agent pipelines.
+//!
+//! A project's pipeline is a sequence of coding agent stages, each with its own agent and
+//! prompt template, that a workspace runs through one after another instead of a single
+//! agent run. Each stage starts when the one before it exits successfully, with that
+//! stage's last message in its prompt; a failed or stopped stage skips the rest.
+
+use std::collections::HashMap;
+
+use db::models::{
+    project_agent_pipeline::AgentPipelineStage,
+    task_template::fill_placeholders,
+    workspace_pipeline_stage::{PipelineStageStatus, WorkspacePipelineStage},
+};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Placeholders a stage's prompt template can use
+pub const PIPELINE_PLACEHOLDERS: [&str; 4] =
+    ["task_title", "task_description", "stage", "previous_output"];
+
+#[derive(Debug, Error)]
+pub enum AgentPipelineError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct AgentPipelineService;
+
+impl AgentPipelineService {
+    /// Check that a pipeline has stages and each has a name and a prompt template using only
+    /// known placeholders. Returns what is wrong otherwise.
+    pub fn validate(stages: &[AgentPipelineStage]) -> Result<(), String> {
+        if stages.is_empty() {
+            return Err("A pipeline needs at least one stage".to_string());
+        }
+        let known: HashMap<String, String> = PIPELINE_PLACEHOLDERS
+            .iter()
+            .map(|name| (name.to_string(), String::new()))
+            .collect();
+        for (index, stage) in stages.iter().enumerate() {
+            if stage.name.trim().is_empty() {
+                return Err(format!("Stage {} has no name", index + 1));
+            }
+            if stage.prompt_template.trim().is_empty() {
+                return Err(format!("Stage '{}' has no prompt template", stage.name));
+            }
+            let mut unknown = Vec::new();
+            fill_placeholders(&stage.prompt_template, &known, &mut unknown);
+            if !unknown.is_empty() {
+                return Err(format!(
+                    "Stage '{}' uses unknown placeholders: {}",
+                    stage.name,
+                    unknown.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Prompt of a stage from its template
+    pub fn render_prompt(
+        prompt_template: &str,
+        stage_name: &str,
+        task_title: &str,
+        task_description: Option<&str>,
+        previous_output: Option<&str>,
+    ) -> String {
+        let variables = HashMap::from([
+            ("task_title".to_string(), task_title.to_string()),
+            (
+                "task_description".to_string(),
+                task_description.unwrap_or_default().to_string(),
+            ),
+            ("stage".to_string(), stage_name.to_string()),
+            (
+                "previous_output".to_string(),
+                previous_output.unwrap_or_default().to_string(),
+            ),
+        ]);
+        fill_placeholders(prompt_template, &variables, &mut Vec::new())
+    }
+
+    /// Mark the pipeline stage run by a finished process completed or failed. A failed
+    /// stage skips the rest of the pipeline; a completed one returns the next stage to
+    /// start, if any. Returns `None` if the process is not a running pipeline stage.
+    pub async fn finish_stage(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        succeeded: bool,
+    ) -> Result<Option<WorkspacePipelineStage>, AgentPipelineError> {
+        let Some(stage) =
+            WorkspacePipelineStage::find_by_execution_process_id(pool, execution_process_id)
+                .await?
+                .filter(|stage| stage.status == PipelineStageStatus::Running)
+        else {
+            return Ok(None);
+        };
+
+        if !succeeded {
+            WorkspacePipelineStage::update_status(pool, stage.id, PipelineStageStatus::Failed)
+                .await?;
+            WorkspacePipelineStage::skip_pending(pool, stage.workspace_id).await?;
+            return Ok(None);
+        }
+        WorkspacePipelineStage::update_status(pool, stage.id, PipelineStageStatus::Completed)
+            .await?;
+        let next = WorkspacePipelineStage::find_by_workspace_id(pool, stage.workspace_id)
+            .await?
+            .into_iter()
+            .find(|next| {
+                next.position > stage.position && next.status == PipelineStageStatus::Pending
+            });
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+
+    use super::*;
+
+    fn stage(name: &str, prompt_template: &str) -> AgentPipelineStage {
+        AgentPipelineStage {
+            name: name.to_string(),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            prompt_template: prompt_template.to_string(),
+        }
+    }
+
+    #[test]
+    fn validate_rejects_empty_and_unknown_placeholders() {
+        assert!(AgentPipelineService::validate(&[]).is_err());
+        assert!(AgentPipelineService::validate(&[stage(" ", "Plan {{task_title}}")]).is_err());
+        assert!(AgentPipelineService::validate(&[stage("plan", "")]).is_err());
+        assert_eq!(
+            AgentPipelineService::validate(&[
+                stage("plan", "Plan {{task_title}}"),
+                stage("implement", "Do {{plan}} for {{ ticket }}"),
+            ]),
+            Err("Stage 'implement' uses unknown placeholders: plan, ticket".to_string())
+        );
+        assert!(
+            AgentPipelineService::validate(&[
+                stage("plan", "Plan {{task_title}}: {{task_description}}"),
+                stage("review", "{{stage}} of {{previous_output}}"),
+            ])
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn render_fills_placeholders() {
+        let prompt = AgentPipelineService::render_prompt(
+            "{{stage}}: {{task_title}}\n{{task_description}}\nEarlier: {{previous_output}}",
+            "fix",
+            "Add login",
+            None,
+            Some("Two findings"),
+        );
+        assert_eq!(prompt, "fix: Add login\n\nEarlier: Two findings");
+    }
+}
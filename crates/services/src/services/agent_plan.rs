@@ -0,0 +1,131 @@
+//! Plan-only coding agent runs.
+//!
+//! A plan run asks the agent how it would carry out the task without changing any files, and
+//! runs with its own run reason so nothing it leaves behind is committed. When the process
+//! exits, the agent's last message becomes the proposed plan of the workspace's plan turn.
+//! Approving the plan starts a regular coding agent run whose prompt carries the plan.
+
+use db::models::plan_turn::{PlanTurn, PlanTurnStatus};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum AgentPlanError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct AgentPlanService;
+
+impl AgentPlanService {
+    /// Prompt asking the agent for a plan of the task, with `instructions` from whoever asked
+    pub fn build_plan_prompt(
+        task_title: &str,
+        task_description: Option<&str>,
+        instructions: Option<&str>,
+    ) -> String {
+        let mut prompt = String::from(
+            "Please plan how you would carry out this task. Do not carry it out yet.\n\n",
+        );
+        prompt.push_str(&format!("Task: {task_title}\n"));
+        if let Some(description) = task_description.filter(|d| !d.trim().is_empty()) {
+            prompt.push_str(&format!("\n{}\n", description.trim_end()));
+        }
+        if let Some(instructions) = instructions.filter(|i| !i.trim().is_empty()) {
+            prompt.push_str(&format!(
+                "\nNotes for the plan:\n{}\n",
+                instructions.trim_end()
+            ));
+        }
+        prompt.push_str(
+            "\nRead whatever code you need, but do not create, modify or delete files and do \
+             not commit. Reply with the plan: the files you would change and how, the order of \
+             the steps, open questions and risks. If a diff makes a change clearer, include it \
+             in a fenced ```diff block. The plan is reviewed before anything is changed.\n",
+        );
+        prompt
+    }
+
+    /// Prompt for the run that carries out an approved plan
+    pub fn build_execution_prompt(
+        task_title: &str,
+        task_description: Option<&str>,
+        plan: &str,
+        feedback: Option<&str>,
+    ) -> String {
+        let mut prompt = format!("Task: {task_title}\n");
+        if let Some(description) = task_description.filter(|d| !d.trim().is_empty()) {
+            prompt.push_str(&format!("\n{}\n", description.trim_end()));
+        }
+        prompt.push_str(&format!(
+            "\nThis plan for the task has been reviewed and approved:\n\n{}\n",
+            plan.trim_end()
+        ));
+        if let Some(feedback) = feedback.filter(|f| !f.trim().is_empty()) {
+            prompt.push_str(&format!(
+                "\nThe reviewer added these notes, which take precedence over the plan:\n{}\n",
+                feedback.trim_end()
+            ));
+        }
+        prompt.push_str("\nCarry out the plan now.\n");
+        prompt
+    }
+
+    /// Store the last message of a finished plan process as its proposed plan. Returns `None`
+    /// if the process is not a plan run.
+    pub async fn record_plan(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        succeeded: bool,
+        last_message: Option<&str>,
+    ) -> Result<Option<PlanTurn>, AgentPlanError> {
+        let Some(turn) = PlanTurn::find_by_execution_process_id(pool, execution_process_id)
+            .await?
+            .filter(|turn| turn.status == PlanTurnStatus::Running)
+        else {
+            return Ok(None);
+        };
+
+        let plan = last_message.filter(|message| !message.trim().is_empty());
+        let status = match (succeeded, plan) {
+            (true, Some(_)) => PlanTurnStatus::Proposed,
+            _ => PlanTurnStatus::Failed,
+        };
+        Ok(Some(PlanTurn::finish(pool, turn.id, status, plan).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_prompt_forbids_changes_and_keeps_notes() {
+        let prompt = AgentPlanService::build_plan_prompt(
+            "Add login",
+            Some("Use the existing session store.\n"),
+            Some("Keep the old endpoint working"),
+        );
+        assert!(prompt.contains("Task: Add login\n\nUse the existing session store.\n"));
+        assert!(prompt.contains("Notes for the plan:\nKeep the old endpoint working\n"));
+        assert!(prompt.contains("do not create, modify or delete files"));
+
+        let bare = AgentPlanService::build_plan_prompt("Add login", Some("  "), None);
+        assert!(!bare.contains("Notes for the plan"));
+    }
+
+    #[test]
+    fn execution_prompt_carries_the_plan_and_feedback() {
+        let prompt = AgentPlanService::build_execution_prompt(
+            "Add login",
+            None,
+            "1. Add a handler\n2. Add a test\n",
+            Some("Skip the test"),
+        );
+        assert!(prompt.starts_with("Task: Add login\n\nThis plan"));
+        assert!(prompt.contains("approved:\n\n1. Add a handler\n2. Add a test\n"));
+        assert!(prompt.contains("take precedence over the plan:\nSkip the test\n"));
+        assert!(prompt.ends_with("Carry out the plan now.\n"));
+    }
+}
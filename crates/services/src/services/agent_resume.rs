@@ -0,0 +1,127 @@
+//! Resuming coding agent runs interrupted by a server restart.
+//!
+//! Agent processes do not survive the server, so on boot every coding agent process still
+//! marked running is failed and recorded as interrupted. Resuming one continues the agent's
+//! stored session with a prompt to pick up where it stopped; an agent that never reported a
+//! session is given its original prompt again.
+
+use executors::actions::{
+    ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+    coding_agent_initial::CodingAgentInitialRequest,
+};
+
+/// Prompt of the run continuing an interrupted session, unless another is given
+pub const RESUME_PROMPT: &str = "Your previous run was interrupted by a server restart before \
+                                 it finished. Check the state of the workspace, then continue \
+                                 the task from where you left off.";
+
+pub struct AgentResumeService;
+
+impl AgentResumeService {
+    /// The action continuing the interrupted coding agent `action`, keeping what was to run
+    /// after it. Returns `None` if the action did not run a coding agent.
+    pub fn resume_action(
+        action: &ExecutorAction,
+        agent_session_id: Option<String>,
+        prompt: Option<&str>,
+    ) -> Option<ExecutorAction> {
+        let (original_prompt, executor_profile_id, working_dir) = match action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => (
+                &request.prompt,
+                &request.executor_profile_id,
+                &request.working_dir,
+            ),
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => (
+                &request.prompt,
+                &request.executor_profile_id,
+                &request.working_dir,
+            ),
+            _ => return None,
+        };
+        let prompt = prompt.filter(|p| !p.trim().is_empty());
+
+        let typ = match agent_session_id {
+            Some(session_id) => {
+                ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                    prompt: prompt.unwrap_or(RESUME_PROMPT).to_string(),
+                    session_id,
+                    executor_profile_id: executor_profile_id.clone(),
+                    working_dir: working_dir.clone(),
+                })
+            }
+            // Without a session there is nothing to continue, so the run starts over
+            None => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: prompt.unwrap_or(original_prompt).to_string(),
+                executor_profile_id: executor_profile_id.clone(),
+                working_dir: working_dir.clone(),
+            }),
+        };
+        Some(ExecutorAction::new(typ, action.next_action.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use executors::{
+        actions::script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        executors::BaseCodingAgent,
+        profile::ExecutorProfileId,
+    };
+
+    use super::*;
+
+    fn script() -> ExecutorAction {
+        ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "cargo fmt".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::CleanupScript,
+                working_dir: None,
+                env: HashMap::new(),
+                artifacts: Vec::new(),
+            }),
+            None,
+        )
+    }
+
+    fn interrupted() -> ExecutorAction {
+        ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: "Add login".to_string(),
+                executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+                working_dir: Some("app".to_string()),
+            }),
+            Some(Box::new(script())),
+        )
+    }
+
+    #[test]
+    fn resumes_the_stored_session_and_keeps_the_next_action() {
+        let action =
+            AgentResumeService::resume_action(&interrupted(), Some("sess-1".to_string()), None)
+                .unwrap();
+        let ExecutorActionType::CodingAgentFollowUpRequest(request) = action.typ() else {
+            panic!("expected a follow-up, got {:?}", action.typ());
+        };
+        assert_eq!(request.session_id, "sess-1");
+        assert_eq!(request.prompt, RESUME_PROMPT);
+        assert_eq!(request.working_dir.as_deref(), Some("app"));
+        assert!(matches!(
+            action.next_action().map(ExecutorAction::typ),
+            Some(ExecutorActionType::ScriptRequest(_))
+        ));
+    }
+
+    #[test]
+    fn starts_over_without_a_session() {
+        let action = AgentResumeService::resume_action(&interrupted(), None, Some("  ")).unwrap();
+        let ExecutorActionType::CodingAgentInitialRequest(request) = action.typ() else {
+            panic!("expected an initial request, got {:?}", action.typ());
+        };
+        assert_eq!(request.prompt, "Add login");
+
+        assert!(AgentResumeService::resume_action(&script(), None, None).is_none());
+    }
+}
@@ -0,0 +1,332 @@
+//! Coding agent reviews of a workspace's changes before it is merged.
+//!
+//! The agent is given the task and the workspace diff and asked to end its reply with a
+//! fenced `json` block listing its findings. When the agent process exits, the block is read
+//! from its last message and every finding is stored as a review comment carrying the
+//! review's id and a severity. A project's review policy decides which unresolved findings
+//! block the merge; the rest are reported as warnings.
+
+use db::models::{
+    ai_review::{AiReview, AiReviewStatus},
+    review_comment::{CreateReviewComment, ReviewComment, ReviewCommentSide, ReviewSeverity},
+    workspace_repo::WorkspaceRepo,
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Diff text given to the agent; files past this are listed by path only
+const MAX_PROMPT_DIFF_BYTES: usize = 200 * 1024;
+
+#[derive(Debug, Error)]
+pub enum AiReviewError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// A changed file to review
+pub struct AiReviewFile<'a> {
+    pub repo_name: &'a str,
+    pub path: &'a str,
+    /// Unified diff of the file
+    pub diff_content: &'a str,
+}
+
+/// A finding as the agent reports it
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct AiReviewFinding {
+    /// Repo name; may be left out in single-repo workspaces
+    #[serde(default)]
+    repo: Option<String>,
+    path: String,
+    line: i64,
+    #[serde(default)]
+    end_line: Option<i64>,
+    severity: ReviewSeverity,
+    #[serde(alias = "message")]
+    body: String,
+}
+
+/// Findings from the last fenced `json` block of the agent's reply, or from the whole reply
+/// when it is bare JSON
+fn parse_findings(message: &str) -> Result<Vec<AiReviewFinding>, String> {
+    let json = match message.rfind("```json") {
+        Some(start) => {
+            let block = &message[start + "```json".len()..];
+            let end = block
+                .find("```")
+                .ok_or_else(|| "The findings block is not closed".to_string())?;
+            &block[..end]
+        }
+        None => message.trim(),
+    };
+    serde_json::from_str(json).map_err(|e| format!("The findings block is not valid: {e}"))
+}
+
+/// Split unresolved findings into those that block a merge under `block_severity` and
+/// those that are only warnings
+pub fn partition_findings(
+    findings: Vec<ReviewComment>,
+    block_severity: Option<ReviewSeverity>,
+) -> (Vec<ReviewComment>, Vec<ReviewComment>) {
+    findings
+        .into_iter()
+        .filter(|finding| finding.resolved_at.is_none())
+        .partition(|finding| {
+            block_severity
+                .is_some_and(|block| finding.severity.is_some_and(|severity| severity >= block))
+        })
+}
+
+pub struct AiReviewService;
+
+impl AiReviewService {
+    /// Prompt asking the agent to review the diff against the task and report findings in a
+    /// form [`AiReviewService::record_findings`] can read
+    pub fn build_prompt(
+        task_title: &str,
+        task_description: Option<&str>,
+        files: &[AiReviewFile],
+    ) -> String {
+        let mut prompt = String::from(
+            "Please review the changes made for this task before they are merged.\n\n",
+        );
+        prompt.push_str(&format!("Task: {task_title}\n"));
+        if let Some(description) = task_description.filter(|d| !d.trim().is_empty()) {
+            prompt.push_str(&format!("\n{}\n", description.trim_end()));
+        }
+
+        prompt.push_str("\nChanges:\n\n");
+        let mut diff_bytes = 0;
+        let mut omitted = Vec::new();
+        for file in files {
+            if diff_bytes + file.diff_content.len() > MAX_PROMPT_DIFF_BYTES {
+                omitted.push(file);
+                continue;
+            }
+            diff_bytes += file.diff_content.len();
+            prompt.push_str(&format!(
+                "Repository {}, {}:\n```diff\n{}\n```\n\n",
+                file.repo_name,
+                file.path,
+                file.diff_content.trim_end()
+            ));
+        }
+        if files.is_empty() {
+            prompt.push_str("No files changed.\n\n");
+        }
+        if !omitted.is_empty() {
+            prompt.push_str(
+                "These files also changed but their diffs are too large to include; \
+                 inspect them in the worktree:\n",
+            );
+            for file in omitted {
+                prompt.push_str(&format!("- {}: {}\n", file.repo_name, file.path));
+            }
+            prompt.push('\n');
+        }
+
+        prompt.push_str(
+            "Look for bugs, missing parts of the task, security problems and risky changes. \
+             Do not modify any files.\n\n\
+             End your reply with a fenced ```json block holding an array of findings, empty if \
+             there are none. Each finding is an object with `repo` (the repository name), \
+             `path` (relative to the repository), `line` (a line of the new file), an optional \
+             `end_line`, `severity` (\"info\", \"warning\" or \"error\") and `body` \
+             (what is wrong and how to fix it).\n",
+        );
+        prompt
+    }
+
+    /// Store the findings in the last message of a finished review process. Returns `None`
+    /// if the process is not an agent review.
+    pub async fn record_findings(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        succeeded: bool,
+        last_message: Option<&str>,
+    ) -> Result<Option<AiReview>, AiReviewError> {
+        let Some(review) = AiReview::find_by_execution_process_id(pool, execution_process_id)
+            .await?
+            .filter(|review| review.status == AiReviewStatus::Running)
+        else {
+            return Ok(None);
+        };
+
+        let findings = match (succeeded, last_message) {
+            (false, _) => Err("The review agent did not finish".to_string()),
+            (true, None) => Err("The review agent did not reply".to_string()),
+            (true, Some(message)) => parse_findings(message),
+        };
+        let findings = match findings {
+            Ok(findings) => findings,
+            Err(error) => {
+                tracing::warn!("Agent review {} failed: {}", review.id, error);
+                let review =
+                    AiReview::finish(pool, review.id, AiReviewStatus::Failed, Some(&error)).await?;
+                return Ok(Some(review));
+            }
+        };
+
+        let repos = WorkspaceRepo::find_repos_for_workspace(pool, review.workspace_id).await?;
+        for finding in findings {
+            let repo = match &finding.repo {
+                Some(name) => repos
+                    .iter()
+                    .find(|repo| &repo.name == name || &repo.display_name == name),
+                None if repos.len() == 1 => repos.first(),
+                None => None,
+            };
+            let Some(repo) = repo else {
+                tracing::warn!(
+                    "Skipping finding of agent review {} on {} in unknown repo {:?}",
+                    review.id,
+                    finding.path,
+                    finding.repo
+                );
+                continue;
+            };
+            if finding.path.trim().is_empty() || finding.body.trim().is_empty() {
+                continue;
+            }
+            let line = finding.line.max(1);
+            ReviewComment::create_for_ai_review(
+                pool,
+                review.workspace_id,
+                review.id,
+                &CreateReviewComment {
+                    repo_id: repo.id,
+                    path: finding.path,
+                    line,
+                    end_line: finding.end_line.filter(|end| *end > line),
+                    side: ReviewCommentSide::New,
+                    body: finding.body,
+                    suggestion: None,
+                    severity: Some(finding.severity),
+                },
+            )
+            .await?;
+        }
+
+        Ok(Some(
+            AiReview::finish(pool, review.id, AiReviewStatus::Completed, None).await?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn comment(severity: Option<ReviewSeverity>, resolved: bool) -> ReviewComment {
+        ReviewComment {
+            id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            repo_id: Uuid::new_v4(),
+            path: "src/lib.rs".to_string(),
+            line: 1,
+            end_line: None,
+            side: ReviewCommentSide::New,
+            body: "body".to_string(),
+            suggestion: None,
+            severity,
+            ai_review_id: None,
+            resolved_at: resolved.then(Utc::now),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn findings_are_read_from_the_last_json_block() {
+        let message = "Looks mostly fine.\n\n```json\n[]\n```\n\nOn second look:\n\n```json\n[\
+            {\"repo\": \"api\", \"path\": \"src/lib.rs\", \"line\": 12, \"severity\": \"error\", \
+             \"body\": \"Panics on empty input\"},\n\
+            {\"path\": \"README.md\", \"line\": 3, \"end_line\": 5, \"severity\": \"info\", \
+             \"message\": \"Typo\"}\n]\n```\n";
+        assert_eq!(
+            parse_findings(message).unwrap(),
+            [
+                AiReviewFinding {
+                    repo: Some("api".to_string()),
+                    path: "src/lib.rs".to_string(),
+                    line: 12,
+                    end_line: None,
+                    severity: ReviewSeverity::Error,
+                    body: "Panics on empty input".to_string(),
+                },
+                AiReviewFinding {
+                    repo: None,
+                    path: "README.md".to_string(),
+                    line: 3,
+                    end_line: Some(5),
+                    severity: ReviewSeverity::Info,
+                    body: "Typo".to_string(),
+                },
+            ]
+        );
+        assert!(parse_findings("  []\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn replies_without_valid_findings_are_rejected() {
+        assert!(parse_findings("No issues found.").is_err());
+        assert!(parse_findings("```json\n[]").is_err());
+        assert!(
+            parse_findings("```json\n[{\"path\": \"a\", \"line\": 1, \"severity\": \"fatal\", \"body\": \"x\"}]\n```")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn severities_at_or_above_the_policy_block() {
+        let findings = vec![
+            comment(Some(ReviewSeverity::Info), false),
+            comment(Some(ReviewSeverity::Warning), false),
+            comment(Some(ReviewSeverity::Error), false),
+            comment(Some(ReviewSeverity::Error), true),
+        ];
+
+        let (blocking, warnings) =
+            partition_findings(findings.clone(), Some(ReviewSeverity::Warning));
+        assert_eq!(blocking.len(), 2);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Some(ReviewSeverity::Info));
+
+        let (blocking, warnings) = partition_findings(findings, None);
+        assert!(blocking.is_empty());
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn oversized_diffs_are_listed_by_path() {
+        let big = "+".repeat(MAX_PROMPT_DIFF_BYTES);
+        let prompt = AiReviewService::build_prompt(
+            "Add login",
+            Some("Users sign in with email"),
+            &[
+                AiReviewFile {
+                    repo_name: "api",
+                    path: "src/auth.rs",
+                    diff_content: "@@ -0,0 +1 @@\n+fn login() {}\n",
+                },
+                AiReviewFile {
+                    repo_name: "api",
+                    path: "fixtures/users.json",
+                    diff_content: &big,
+                },
+            ],
+        );
+        assert!(prompt.contains("Task: Add login\n\nUsers sign in with email\n"));
+        assert!(
+            prompt.contains(
+                "Repository api, src/auth.rs:\n```diff\n@@ -0,0 +1 @@\n+fn login() {}\n```"
+            )
+        );
+        assert!(prompt.contains("- api: fixtures/users.json\n"));
+        assert!(!prompt.contains(&big));
+    }
+}
@@ -0,0 +1,356 @@
+//! Keeping open workspaces up with their target branches.
+//!
+//! Projects can opt in to having the branches of their open workspaces rebased onto, or
+//! merged with, their target branches whenever those gain commits. The check runs
+//! periodically and leaves alone workspaces that are busy: those running processes or
+//! another operation, with uncommitted changes, or with a rebase or merge already stopped in
+//! a worktree. A sync that conflicts is aborted, so the worktree is as it was, and flagged
+//! with a merge conflict and a workspace event; the repo is not tried again until its target
+//! branch moves on.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use db::models::{
+    execution_process::ExecutionProcess,
+    merge_conflict::MergeConflict,
+    project_settings::{AutoSyncStrategy, ProjectSettings},
+    workspace::Workspace,
+    workspace_event::{WorkspaceEvent, WorkspaceEventKind},
+    workspace_repo::{RepoWithTargetBranch, WorkspaceRepo},
+};
+use git::{GitService, GitServiceError};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::{
+    workspace_lock::{WorkspaceLock, WorkspaceOperation},
+    workspace_manager::WorkspaceManager,
+};
+
+/// A repo of a workspace whose branch the check synced or tried to
+#[derive(Debug, Clone)]
+pub struct SyncedRepo {
+    pub workspace_id: Uuid,
+    pub workspace_branch: String,
+    pub repo_name: String,
+    pub target_branch: String,
+    pub strategy: AutoSyncStrategy,
+    /// Files that conflicted when the sync was aborted, as far as git reported them; None
+    /// when it went through
+    pub conflicted_files: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default)]
+pub struct AutoSyncReport {
+    pub synced: Vec<SyncedRepo>,
+    pub conflicted: Vec<SyncedRepo>,
+}
+
+/// Remembers the target branch commit each conflicting repo was tried against
+#[derive(Debug, Default)]
+pub struct AutoSyncService {
+    conflicted_at: HashMap<(Uuid, Uuid), String>,
+}
+
+impl AutoSyncService {
+    /// Sync the open workspaces of every project with an auto sync strategy
+    pub async fn run(
+        &mut self,
+        pool: &SqlitePool,
+        git: &GitService,
+    ) -> Result<AutoSyncReport, sqlx::Error> {
+        let workspaces = Workspace::find_all_with_status(pool, Some(false), None).await?;
+        let open: HashSet<Uuid> = workspaces
+            .iter()
+            .filter(|workspace| workspace.container_ref.is_some())
+            .map(|workspace| workspace.id)
+            .collect();
+        self.conflicted_at
+            .retain(|(workspace_id, _), _| open.contains(workspace_id));
+
+        let mut report = AutoSyncReport::default();
+        for workspace in workspaces {
+            let Some(container_ref) = workspace.container_ref.as_deref() else {
+                continue;
+            };
+            let Some(strategy) = ProjectSettings::find_by_task_id(pool, workspace.task_id)
+                .await?
+                .and_then(|settings| settings.auto_sync_strategy)
+            else {
+                continue;
+            };
+            if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+                pool,
+                workspace.id,
+            )
+            .await?
+            {
+                continue;
+            }
+            let Ok(_lock) = WorkspaceLock::try_acquire(workspace.id, WorkspaceOperation::AutoSync)
+            else {
+                continue;
+            };
+
+            let repos =
+                WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id)
+                    .await?;
+            for repo in repos {
+                let worktree_path = PathBuf::from(container_ref).join(&repo.repo.name);
+                let synced = self
+                    .sync_repo(git, &workspace, &repo, &worktree_path, strategy)
+                    .await;
+                let outcome = match synced {
+                    Ok(Some(outcome)) => outcome,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Skipping automatic sync of repo '{}' in workspace {}: {}",
+                            repo.repo.name,
+                            workspace.id,
+                            e
+                        );
+                        continue;
+                    }
+                };
+
+                let verb = match strategy {
+                    AutoSyncStrategy::Rebase => "Rebased onto",
+                    AutoSyncStrategy::Merge => "Merged",
+                };
+                let Some(conflicted_files) = &outcome.conflicted_files else {
+                    WorkspaceEvent::create(
+                        pool,
+                        workspace.id,
+                        WorkspaceEventKind::AutoSynced,
+                        &format!(
+                            "{verb} '{}' in repo '{}' automatically",
+                            repo.target_branch, repo.repo.name
+                        ),
+                    )
+                    .await?;
+                    report.synced.push(outcome);
+                    continue;
+                };
+                let files = if conflicted_files.is_empty() {
+                    String::new()
+                } else {
+                    format!(" in {}", conflicted_files.join(", "))
+                };
+                MergeConflict::record(pool, workspace.id, repo.repo.id).await?;
+                WorkspaceEvent::create(
+                    pool,
+                    workspace.id,
+                    WorkspaceEventKind::AutoSyncConflicted,
+                    &format!(
+                        "Automatic sync with '{}' in repo '{}' conflicted{files} and was aborted",
+                        repo.target_branch, repo.repo.name
+                    ),
+                )
+                .await?;
+                report.conflicted.push(outcome);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Sync one repo when its target branch has commits the workspace branch lacks. Returns
+    /// None when there is nothing to do or the worktree is busy.
+    async fn sync_repo(
+        &mut self,
+        git: &GitService,
+        workspace: &Workspace,
+        repo: &RepoWithTargetBranch,
+        worktree_path: &Path,
+        strategy: AutoSyncStrategy,
+    ) -> Result<Option<SyncedRepo>, GitServiceError> {
+        let key = (workspace.id, repo.repo.id);
+        let (_, behind) =
+            git.get_branch_status(&repo.repo.path, &workspace.branch, &repo.target_branch)?;
+        if behind == 0 {
+            self.conflicted_at.remove(&key);
+            return Ok(None);
+        }
+        let target_oid = git.get_branch_oid(&repo.repo.path, &repo.target_branch)?;
+        if self.conflicted_at.get(&key) == Some(&target_oid)
+            || !worktree_path.exists()
+            || git.detect_conflict_op(worktree_path)?.is_some()
+            || !git.is_worktree_clean(worktree_path)?
+        {
+            return Ok(None);
+        }
+
+        let conflicted_files = match strategy {
+            AutoSyncStrategy::Rebase => match git.rebase_branch(
+                &repo.repo.path,
+                worktree_path,
+                &repo.target_branch,
+                &repo.target_branch,
+                &workspace.branch,
+            ) {
+                Ok(_) => None,
+                Err(GitServiceError::MergeConflicts {
+                    conflicted_files, ..
+                }) => Some(conflicted_files),
+                Err(e) => return Err(e),
+            },
+            AutoSyncStrategy::Merge => Some(
+                WorkspaceManager::merge_target_into_worktree(
+                    git,
+                    &repo.repo.path,
+                    worktree_path,
+                    &repo.target_branch,
+                    &format!("Merge '{}' into '{}'", repo.target_branch, workspace.branch),
+                )
+                .await?,
+            )
+            .filter(|files| !files.is_empty()),
+        };
+        if conflicted_files.is_none() {
+            self.conflicted_at.remove(&key);
+        } else {
+            // Leave the worktree as it was; the conflict is flagged for someone to resolve
+            if git.detect_conflict_op(worktree_path)?.is_some() {
+                git.abort_conflicts(worktree_path)?;
+            }
+            self.conflicted_at.insert(key, target_oid);
+        }
+
+        Ok(Some(SyncedRepo {
+            workspace_id: workspace.id,
+            workspace_branch: workspace.branch.clone(),
+            repo_name: repo.repo.name.clone(),
+            target_branch: repo.target_branch.clone(),
+            strategy,
+            conflicted_files,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::project_settings::UpsertProjectSettings;
+
+    use super::*;
+    use crate::services::test_support::{init_repo, run_git, seed_workspace, test_pool};
+
+    fn commit_file(dir: &Path, file: &str, content: &str, message: &str) {
+        std::fs::write(dir.join(file), content).unwrap();
+        run_git(dir, &["add", "-A"]);
+        run_git(dir, &["commit", "-q", "-m", message]);
+    }
+
+    #[tokio::test]
+    async fn workspaces_follow_their_target_branch_until_a_sync_conflicts() {
+        let pool = test_pool().await;
+        let git = GitService::new();
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path().join("api");
+        init_repo(&repo_path, "README.md", "# api\n");
+        // Rebases and merges commit as the repo's configured user
+        run_git(&repo_path, &["config", "user.name", "Test"]);
+        run_git(&repo_path, &["config", "user.email", "test@example.com"]);
+        let workspace_dir = dir.path().join("workspace");
+        let seeded = seed_workspace(&pool, &workspace_dir, &repo_path).await;
+        let worktree = workspace_dir.join("api");
+        run_git(
+            &repo_path,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                &worktree.to_string_lossy(),
+            ],
+        );
+        commit_file(&worktree, "login.rs", "fn login() {}\n", "Add login");
+        commit_file(&repo_path, "ci.yml", "on: push\n", "Add CI");
+        let mut auto_sync = AutoSyncService::default();
+
+        // Without a policy the workspace is left behind
+        let report = auto_sync.run(&pool, &git).await.unwrap();
+        assert!(report.synced.is_empty() && report.conflicted.is_empty());
+
+        let settings = UpsertProjectSettings {
+            default_executor_profile_id: None,
+            branch_template: None,
+            default_merge_strategy: None,
+            run_quality_gates: true,
+            workspace_retention_hours: None,
+            archived_workspace_retention_hours: None,
+            auto_sync_strategy: Some(AutoSyncStrategy::Rebase),
+        };
+        ProjectSettings::upsert(&pool, seeded.project.id, &settings)
+            .await
+            .unwrap();
+        assert_eq!(
+            ProjectSettings::find_by_task_id(&pool, seeded.task.id)
+                .await
+                .unwrap()
+                .and_then(|settings| settings.auto_sync_strategy),
+            Some(AutoSyncStrategy::Rebase)
+        );
+
+        // Uncommitted changes keep the workspace from being touched
+        std::fs::write(worktree.join("login.rs"), "fn login() { todo!() }\n").unwrap();
+        let report = auto_sync.run(&pool, &git).await.unwrap();
+        assert!(report.synced.is_empty() && report.conflicted.is_empty());
+        std::fs::write(worktree.join("login.rs"), "fn login() {}\n").unwrap();
+
+        let report = auto_sync.run(&pool, &git).await.unwrap();
+        assert_eq!(report.synced.len(), 1);
+        assert_eq!(report.synced[0].workspace_id, seeded.workspace.id);
+        assert!(worktree.join("ci.yml").is_file());
+        assert_eq!(
+            git.get_branch_status(&repo_path, "feature", "main")
+                .unwrap(),
+            (1, 0)
+        );
+
+        // A conflicting sync is aborted, flagged, and not retried against the same target
+        commit_file(
+            &worktree,
+            "README.md",
+            "# api (feature)\n",
+            "Describe feature",
+        );
+        commit_file(&repo_path, "README.md", "# api (main)\n", "Describe main");
+        let head = git.get_head_info(&worktree).unwrap().oid;
+        let report = auto_sync.run(&pool, &git).await.unwrap();
+        assert!(report.synced.is_empty());
+        assert_eq!(report.conflicted.len(), 1);
+        assert_eq!(
+            report.conflicted[0].conflicted_files.as_deref(),
+            Some(&["README.md".to_string()][..])
+        );
+        assert_eq!(git.get_head_info(&worktree).unwrap().oid, head);
+        assert!(git.detect_conflict_op(&worktree).unwrap().is_none());
+        assert!(
+            MergeConflict::find_workspace_ids_for_project(&pool, seeded.project.id)
+                .await
+                .unwrap()
+                .contains(&seeded.workspace.id)
+        );
+        let report = auto_sync.run(&pool, &git).await.unwrap();
+        assert!(report.synced.is_empty() && report.conflicted.is_empty());
+
+        let kinds: Vec<WorkspaceEventKind> =
+            WorkspaceEvent::find_by_workspace_id(&pool, seeded.workspace.id)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|event| event.kind)
+                .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                WorkspaceEventKind::AutoSyncConflicted,
+                WorkspaceEventKind::AutoSynced
+            ]
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Coding agent resolutions of merge conflicts in a workspace worktree.
+//!
+//! The agent is given the conflicted files with their markers, the operation that stopped
+//! and the commits on the target branch, and asked to edit the files in place without
+//! staging them. When the agent process exits, every file it left without conflict markers
+//! becomes a proposed resolution; a person then accepts it, which stages the file, or
+//! rejects it, which puts the markers back.
+
+use std::path::Path;
+
+use db::models::{
+    conflict_resolution::{ConflictResolution, ConflictResolutionStatus},
+    workspace_repo::WorkspaceRepo,
+};
+use git::{CommitInfo, ConflictOp};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// File contents given to the agent; files past this are listed by path only
+const MAX_PROMPT_CONFLICT_BYTES: usize = 200 * 1024;
+
+/// Target branch commits listed per repo; the rest are counted
+const MAX_TARGET_COMMITS: usize = 30;
+
+#[derive(Debug, Error)]
+pub enum ConflictResolutionError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// A conflicted file
+pub struct ConflictFile<'a> {
+    pub path: &'a str,
+    /// Worktree contents of the file, with conflict markers
+    pub content: &'a str,
+}
+
+/// The conflicts of one repo of the workspace
+pub struct ConflictRepo<'a> {
+    pub repo_name: &'a str,
+    pub target_branch: &'a str,
+    /// The operation that stopped on the conflicts
+    pub op: &'a ConflictOp,
+    /// Commits on the target branch since the workspace branch forked, oldest first
+    pub target_commits: &'a [CommitInfo],
+    pub files: &'a [ConflictFile<'a>],
+}
+
+/// Whether `content` still holds a conflict marker at the start of a line
+pub fn has_conflict_markers(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| line.starts_with("<<<<<<<") || line.starts_with(">>>>>>>") || line == "=======")
+}
+
+fn describe_op(op: &ConflictOp, target_branch: &str) -> String {
+    match op {
+        ConflictOp::Merge => format!("Merging `{target_branch}` into the workspace branch"),
+        ConflictOp::Rebase => format!("Rebasing the workspace branch onto `{target_branch}`"),
+        ConflictOp::CherryPick => "A cherry-pick".to_string(),
+        ConflictOp::Revert => "A revert".to_string(),
+    }
+}
+
+pub struct ConflictResolutionService;
+
+impl ConflictResolutionService {
+    /// Prompt asking the agent to resolve the conflicts in place, leaving staging and
+    /// finishing the merge to a person
+    pub fn build_prompt(
+        task_title: &str,
+        task_description: Option<&str>,
+        repos: &[ConflictRepo],
+    ) -> String {
+        let mut prompt = String::from(
+            "Please resolve the merge conflicts in this workspace so its changes can be merged.\n\n",
+        );
+        prompt.push_str(&format!(
+            "The changes were made for this task: {task_title}\n"
+        ));
+        if let Some(description) = task_description.filter(|d| !d.trim().is_empty()) {
+            prompt.push_str(&format!("\n{}\n", description.trim_end()));
+        }
+
+        let mut content_bytes = 0;
+        let mut omitted = Vec::new();
+        for repo in repos {
+            prompt.push_str(&format!(
+                "\nRepository {}: {} stopped on conflicts.\n",
+                repo.repo_name,
+                describe_op(repo.op, repo.target_branch)
+            ));
+            if !repo.target_commits.is_empty() {
+                prompt.push_str(&format!(
+                    "\nCommits on `{}` that the workspace branch does not have:\n",
+                    repo.target_branch
+                ));
+                for commit in repo.target_commits.iter().take(MAX_TARGET_COMMITS) {
+                    let short_sha = commit.commit_sha.get(..7).unwrap_or(&commit.commit_sha);
+                    prompt.push_str(&format!("- {short_sha} {}\n", commit.summary.trim()));
+                }
+                if repo.target_commits.len() > MAX_TARGET_COMMITS {
+                    prompt.push_str(&format!(
+                        "- and {} more\n",
+                        repo.target_commits.len() - MAX_TARGET_COMMITS
+                    ));
+                }
+            }
+            prompt.push('\n');
+            for file in repo.files {
+                if content_bytes + file.content.len() > MAX_PROMPT_CONFLICT_BYTES {
+                    omitted.push((repo.repo_name, file.path));
+                    continue;
+                }
+                content_bytes += file.content.len();
+                prompt.push_str(&format!(
+                    "{}:\n```\n{}\n```\n\n",
+                    file.path,
+                    file.content.trim_end()
+                ));
+            }
+        }
+        if !omitted.is_empty() {
+            prompt.push_str(
+                "These files also have conflicts but are too large to include; open them in \
+                 the worktree:\n",
+            );
+            for (repo_name, path) in omitted {
+                prompt.push_str(&format!("- {repo_name}: {path}\n"));
+            }
+            prompt.push('\n');
+        }
+
+        prompt.push_str(
+            "Edit each conflicted file so that it keeps the intent of both sides and remove \
+             every conflict marker. Do not change other files. Do not run `git add`, \
+             `git commit`, `git merge --continue`, `git rebase --continue` or abort the merge: \
+             each resolution is reviewed and staged by a person. If you cannot resolve a \
+             file, leave its markers in place. End your reply with a short note on how you \
+             resolved each file.\n",
+        );
+        prompt
+    }
+
+    /// Mark the files of a finished resolution process proposed if the agent removed their
+    /// conflict markers and unresolved otherwise. Returns the process's resolutions, empty if
+    /// it is not a conflict resolution.
+    pub async fn record_proposals(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        workspace_dir: &Path,
+    ) -> Result<Vec<ConflictResolution>, ConflictResolutionError> {
+        let resolutions =
+            ConflictResolution::find_by_execution_process_id(pool, execution_process_id).await?;
+        let Some(first) = resolutions.first() else {
+            return Ok(resolutions);
+        };
+        let repos = WorkspaceRepo::find_repos_for_workspace(pool, first.workspace_id).await?;
+
+        let mut recorded = Vec::with_capacity(resolutions.len());
+        for resolution in resolutions {
+            if resolution.status != ConflictResolutionStatus::Running {
+                recorded.push(resolution);
+                continue;
+            }
+            let resolved = match repos.iter().find(|repo| repo.id == resolution.repo_id) {
+                Some(repo) => {
+                    let file_path = workspace_dir.join(&repo.name).join(&resolution.path);
+                    match tokio::fs::read(&file_path).await {
+                        Ok(content) => !has_conflict_markers(&String::from_utf8_lossy(&content)),
+                        // Deleting the file is a resolution of a modify/delete conflict
+                        Err(e) => e.kind() == std::io::ErrorKind::NotFound,
+                    }
+                }
+                None => false,
+            };
+            let status = if resolved {
+                ConflictResolutionStatus::Proposed
+            } else {
+                ConflictResolutionStatus::Unresolved
+            };
+            recorded.push(ConflictResolution::update_status(pool, resolution.id, status).await?);
+        }
+        Ok(recorded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::execution_process::ExecutionProcessRunReason;
+    use git::GitService;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::services::test_support::{
+        init_repo, run_git, seed_workspace, start_agent_process, test_pool,
+    };
+
+    #[test]
+    fn markers_only_count_at_the_start_of_a_line() {
+        assert!(has_conflict_markers(
+            "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> main\n"
+        ));
+        assert!(has_conflict_markers("a\n=======\n"));
+        assert!(!has_conflict_markers("a\n// ======= banner =======\n"));
+        assert!(!has_conflict_markers("let s = \"<<<<<<<\";\n"));
+    }
+
+    #[test]
+    fn prompt_has_the_conflicts_and_target_commits() {
+        let commits = [CommitInfo {
+            commit_sha: "abcdef123456".to_string(),
+            author: "dev".to_string(),
+            summary: "Rename login handler".to_string(),
+            message: "Rename login handler".to_string(),
+            committed_at: Utc::now(),
+        }];
+        let big = "x".repeat(MAX_PROMPT_CONFLICT_BYTES);
+        let files = [
+            ConflictFile {
+                path: "src/auth.rs",
+                content: "<<<<<<< ours\nfn login() {}\n=======\nfn sign_in() {}\n>>>>>>> theirs\n",
+            },
+            ConflictFile {
+                path: "fixtures/users.json",
+                content: &big,
+            },
+        ];
+        let prompt = ConflictResolutionService::build_prompt(
+            "Add login",
+            None,
+            &[ConflictRepo {
+                repo_name: "api",
+                target_branch: "main",
+                op: &ConflictOp::Merge,
+                target_commits: &commits,
+                files: &files,
+            }],
+        );
+        assert!(prompt.contains(
+            "Repository api: Merging `main` into the workspace branch stopped on conflicts."
+        ));
+        assert!(prompt.contains("- abcdef1 Rename login handler\n"));
+        assert!(prompt.contains("src/auth.rs:\n```\n<<<<<<< ours\nfn login() {}\n"));
+        assert!(prompt.contains("- api: fixtures/users.json\n"));
+        assert!(!prompt.contains(&big));
+    }
+
+    #[tokio::test]
+    async fn finished_resolution_is_left_staged_and_uncommitted() {
+        let td = TempDir::new().unwrap();
+        let workspace_dir = td.path().join("ws");
+        let worktree = workspace_dir.join("api");
+        init_repo(&worktree, "notes.txt", "one\n");
+        run_git(&worktree, &["checkout", "-q", "-b", "feature"]);
+        std::fs::write(worktree.join("notes.txt"), "one on feature\n").unwrap();
+        run_git(&worktree, &["commit", "-q", "-am", "Feature change"]);
+        run_git(&worktree, &["checkout", "-q", "main"]);
+        std::fs::write(worktree.join("notes.txt"), "one on main\n").unwrap();
+        run_git(&worktree, &["commit", "-q", "-am", "Main change"]);
+        run_git(&worktree, &["checkout", "-q", "feature"]);
+        run_git(&worktree, &["merge", "main"]);
+        let head = run_git(&worktree, &["rev-parse", "HEAD"]);
+
+        let pool = test_pool().await;
+        let seeded = seed_workspace(&pool, &workspace_dir, &worktree).await;
+        let process = start_agent_process(
+            &pool,
+            seeded.session.id,
+            ExecutionProcessRunReason::ConflictResolution,
+        )
+        .await;
+        ConflictResolution::create(
+            &pool,
+            seeded.workspace.id,
+            seeded.repo.id,
+            process.id,
+            "notes.txt",
+        )
+        .await
+        .unwrap();
+
+        // The agent edits the file in place and exits
+        std::fs::write(worktree.join("notes.txt"), "one on both\n").unwrap();
+        let recorded =
+            ConflictResolutionService::record_proposals(&pool, process.id, &workspace_dir)
+                .await
+                .unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].status, ConflictResolutionStatus::Proposed);
+
+        // Accepting stages the file; nothing is committed and the merge is still to finish
+        let git_service = GitService::new();
+        git_service
+            .stage_resolved_file(&worktree, Path::new("notes.txt"))
+            .unwrap();
+        assert_eq!(run_git(&worktree, &["rev-parse", "HEAD"]), head);
+        assert_eq!(
+            run_git(&worktree, &["diff", "--cached", "--name-only"]),
+            "notes.txt\n"
+        );
+        assert_eq!(
+            git_service.detect_conflict_op(&worktree).unwrap(),
+            Some(ConflictOp::Merge)
+        );
+    }
+}
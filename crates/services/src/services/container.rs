@@ -15,13 +15,18 @@ use db::{
             ExecutionProcessRunReason, ExecutionProcessStatus,
         },
         execution_process_logs::ExecutionProcessLogs,
+        execution_process_pid::ExecutionProcessPid,
         execution_process_repo_state::{
             CreateExecutionProcessRepoState, ExecutionProcessRepoState,
         },
+        interrupted_execution::InterruptedExecution,
         repo::Repo,
+        scratch::DraftFollowUpData,
         session::{CreateSession, Session, SessionError},
         task::{Task, TaskStatus, TaskType},
         workspace::{Workspace, WorkspaceError},
+        workspace_event::{WorkspaceEvent, WorkspaceEventKind},
+        workspace_pipeline_stage::WorkspacePipelineStage,
         workspace_repo::WorkspaceRepo,
     },
 };
@@ -36,7 +41,7 @@ use executors::{
         coding_agent_initial::CodingAgentInitialRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
-    executors::{ExecutorError, StandardCodingAgentExecutor},
+    executors::{BaseCodingAgent, ExecutorError, StandardCodingAgentExecutor},
     logs::{NormalizedEntry, NormalizedEntryError, NormalizedEntryType, utils::ConversationPatch},
     profile::ExecutorProfileId,
 };
@@ -48,14 +53,20 @@ use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinHandle};
 use utils::{
     log_msg::LogMsg,
-    msg_store::MsgStore,
-    text::{git_branch_id, short_uuid},
+    msg_store::{MsgStore, tail_within_bytes},
+    text::{git_branch_id, render_branch_template, short_uuid},
 };
 use uuid::Uuid;
 
 use crate::services::{
+    agent_pipeline::AgentPipelineService,
+    env_vars::EnvVarError,
     notification::NotificationService,
+    orphan_processes::{ORPHAN_KILL_GRACE, OrphanProcessService},
+    process_input::ProcessInput,
+    process_reaper::ProcessReaperReport,
     ralph::RalphService,
+    secrets::SecretError,
     workspace_manager::WorkspaceError as WorkspaceManagerError,
     worktree_manager::WorktreeError,
 };
@@ -79,6 +90,19 @@ pub enum ContainerError {
     Session(#[from] SessionError),
     #[error(transparent)]
     ExecutionProcess(#[from] ExecutionProcessError),
+    #[error(transparent)]
+    EnvVar(#[from] EnvVarError),
+    #[error(transparent)]
+    Secret(#[from] SecretError),
+    #[error("Concurrent coding agent limit of {0} reached; wait for a running agent to finish")]
+    AgentLimitReached(u32),
+    #[error("The project's agent policy cannot be enforced for {0}")]
+    AgentPolicyNotEnforced(BaseCodingAgent),
+    #[error(
+        "The project's agent policy needs a sandbox on the machine running the agent: \
+         bubblewrap (bwrap) on Linux or sandbox-exec on macOS"
+    )]
+    SandboxUnavailable,
     #[error("Io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Failed to kill process: {0}")]
@@ -156,10 +180,20 @@ pub trait ContainerService {
 
     async fn take_db_stream_handle(&self, id: &Uuid) -> Option<JoinHandle<()>>;
 
+    /// Input for a running execution process, if it accepts any
+    async fn process_input(&self, id: &Uuid) -> Option<ProcessInput>;
+
     async fn create(&self, workspace: &Workspace) -> Result<ContainerRef, ContainerError>;
 
     async fn kill_all_running_processes(&self) -> Result<(), ContainerError>;
 
+    /// Reconcile executions marked running with the processes running them, keeping the
+    /// report as the last one
+    async fn reap_processes(&self) -> Result<ProcessReaperReport, ContainerError>;
+
+    /// Report of the reaper's last run, if it has run
+    async fn last_reaper_report(&self) -> Option<ProcessReaperReport>;
+
     async fn delete(&self, workspace: &Workspace) -> Result<(), ContainerError>;
 
     /// Check if a task has any running execution processes
@@ -190,10 +224,15 @@ pub trait ContainerService {
     /// - Never when a setup script has no next_action (parallel mode)
     /// - The next action is None (no follow-up actions)
     fn should_finalize(&self, ctx: &ExecutionContext) -> bool {
-        // Never finalize DevServer processes
+        // Never finalize DevServer processes or on-demand commands, which run alongside the task,
+        // or agent reviews and conflict resolutions, which leave the task where it was
         if matches!(
             ctx.execution_process.run_reason,
             ExecutionProcessRunReason::DevServer
+                | ExecutionProcessRunReason::AdHocCommand
+                | ExecutionProcessRunReason::ProjectScript
+                | ExecutionProcessRunReason::AiReview
+                | ExecutionProcessRunReason::ConflictResolution
         ) {
             return false;
         }
@@ -360,6 +399,17 @@ pub trait ContainerService {
             return false;
         }
 
+        // A plan waits for approval before anything runs, a review only comments and a
+        // conflict resolution waits for a person to stage it
+        if matches!(
+            ctx.execution_process.run_reason,
+            ExecutionProcessRunReason::Plan
+                | ExecutionProcessRunReason::AiReview
+                | ExecutionProcessRunReason::ConflictResolution
+        ) {
+            return false;
+        }
+
         // Only if execution completed successfully (not failed or killed)
         if !matches!(ctx.execution_process.status, ExecutionProcessStatus::Completed) {
             tracing::info!(
@@ -606,11 +656,30 @@ pub trait ContainerService {
                 process.id,
                 process.session_id
             );
+            // A process that outlived the server can no longer be followed, so stop it
+            let surviving_pid =
+                ExecutionProcessPid::find_by_execution_process_id(&self.db().pool, process.id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .filter(|pid| OrphanProcessService::is_running(*pid));
+            if let Some(pid) = surviving_pid {
+                tracing::info!(
+                    "Stopping process group {} of orphaned execution process {}",
+                    pid,
+                    process.id
+                );
+                OrphanProcessService::kill_group(pid, ORPHAN_KILL_GRACE).await;
+            }
+            let status = match surviving_pid {
+                Some(_) => ExecutionProcessStatus::Killed,
+                None => ExecutionProcessStatus::Failed,
+            };
             // Update the execution process status first
             if let Err(e) = ExecutionProcess::update_completion(
                 &self.db().pool,
                 process.id,
-                ExecutionProcessStatus::Failed,
+                status.clone(),
                 None, // No exit code for orphaned processes
             )
             .await
@@ -647,8 +716,54 @@ pub trait ContainerService {
                     }
                 }
             }
-            // Process marked as failed
-            tracing::info!("Marked orphaned execution process {} as failed", process.id);
+            tracing::info!(
+                "Marked orphaned execution process {} as {:?}",
+                process.id,
+                status
+            );
+            if let Ok(Some(session)) =
+                Session::find_by_id(&self.db().pool, process.session_id).await
+            {
+                let message = match surviving_pid {
+                    Some(_) => format!(
+                        "Execution process {} outlived a server restart and was stopped",
+                        process.id
+                    ),
+                    None => format!(
+                        "Execution process {} was interrupted by a server restart",
+                        process.id
+                    ),
+                };
+                if let Err(e) = WorkspaceEvent::create(
+                    &self.db().pool,
+                    session.workspace_id,
+                    WorkspaceEventKind::ProcessInterrupted,
+                    &message,
+                )
+                .await
+                {
+                    tracing::error!(
+                        "Failed to record interruption of execution process {}: {}",
+                        process.id,
+                        e
+                    );
+                }
+                // Coding agent runs can be resumed later from the agent's stored session
+                if process.run_reason == ExecutionProcessRunReason::CodingAgent
+                    && let Err(e) = InterruptedExecution::create(
+                        &self.db().pool,
+                        process.id,
+                        session.workspace_id,
+                    )
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to record interrupted execution process {}: {}",
+                        process.id,
+                        e
+                    );
+                }
+            }
             // Update task status to InReview for coding agent and setup script failures
             if matches!(
                 process.run_reason,
@@ -768,6 +883,8 @@ pub trait ContainerService {
                 language: ScriptRequestLanguage::Bash,
                 context: ScriptContext::CleanupScript,
                 working_dir: Some(first.name.clone()),
+                env: HashMap::new(),
+                artifacts: Vec::new(),
             }),
             None,
         );
@@ -779,6 +896,8 @@ pub trait ContainerService {
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::CleanupScript,
                     working_dir: Some(repo.name.clone()),
+                    env: HashMap::new(),
+                    artifacts: Vec::new(),
                 }),
                 None,
             ));
@@ -802,6 +921,8 @@ pub trait ContainerService {
                 language: ScriptRequestLanguage::Bash,
                 context: ScriptContext::SetupScript,
                 working_dir: Some(first.name.clone()),
+                env: HashMap::new(),
+                artifacts: Vec::new(),
             }),
             None,
         );
@@ -813,6 +934,8 @@ pub trait ContainerService {
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::SetupScript,
                     working_dir: Some(repo.name.clone()),
+                    env: HashMap::new(),
+                    artifacts: Vec::new(),
                 }),
                 None,
             ));
@@ -829,6 +952,8 @@ pub trait ContainerService {
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::SetupScript,
                     working_dir: Some(repo.name.clone()),
+                    env: HashMap::new(),
+                    artifacts: Vec::new(),
                 }),
                 None,
             )
@@ -848,6 +973,8 @@ pub trait ContainerService {
                         language: ScriptRequestLanguage::Bash,
                         context: ScriptContext::SetupScript,
                         working_dir: Some(repo.name.clone()),
+                        env: HashMap::new(),
+                        artifacts: Vec::new(),
                     }),
                     Some(Box::new(chained)),
                 );
@@ -946,6 +1073,24 @@ pub trait ContainerService {
         }
     }
 
+    /// Branch name for a new workspace, from the project's branch template when it has one
+    async fn git_branch_from_template(
+        &self,
+        branch_template: Option<&str>,
+        workspace_id: &Uuid,
+        task_title: &str,
+    ) -> String {
+        match branch_template {
+            Some(template) => render_branch_template(
+                template,
+                &self.git_branch_prefix().await,
+                workspace_id,
+                task_title,
+            ),
+            None => self.git_branch_from_workspace(workspace_id, task_title).await,
+        }
+    }
+
     async fn stream_raw_logs(
         &self,
         id: &Uuid,
@@ -997,6 +1142,51 @@ pub trait ContainerService {
         }
     }
 
+    /// Like [`Self::stream_raw_logs`], but replays at most `max_bytes` of the most recent
+    /// output before following live output.
+    async fn stream_raw_logs_tail(
+        &self,
+        id: &Uuid,
+        max_bytes: usize,
+    ) -> Option<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            return Some(store.raw_output_tail_plus_stream(max_bytes));
+        }
+
+        let log_records = match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, *id)
+            .await
+        {
+            Ok(records) if !records.is_empty() => records,
+            Ok(_) => return None,
+            Err(e) => {
+                tracing::error!("Failed to fetch logs for execution {}: {}", id, e);
+                return None;
+            }
+        };
+
+        let messages = match ExecutionProcessLogs::parse_logs(&log_records) {
+            Ok(msgs) => msgs,
+            Err(e) => {
+                tracing::error!("Failed to parse logs for execution {}: {}", id, e);
+                return None;
+            }
+        };
+
+        let raw_output = messages
+            .into_iter()
+            .filter(|m| matches!(m, LogMsg::Stdout(_) | LogMsg::Stderr(_)))
+            .collect();
+        let stream = futures::stream::iter(
+            tail_within_bytes(raw_output, max_bytes)
+                .into_iter()
+                .chain(std::iter::once(LogMsg::Finished))
+                .map(Ok::<_, std::io::Error>),
+        )
+        .boxed();
+
+        Some(stream)
+    }
+
     async fn stream_normalized_logs(
         &self,
         id: &Uuid,
@@ -1376,7 +1566,14 @@ pub trait ContainerService {
             .await?
             .ok_or(SqlxError::RowNotFound)?;
         if task.status != TaskStatus::InProgress
-            && run_reason != &ExecutionProcessRunReason::DevServer
+            && !matches!(
+                run_reason,
+                ExecutionProcessRunReason::DevServer
+                    | ExecutionProcessRunReason::AdHocCommand
+                    | ExecutionProcessRunReason::ProjectScript
+                    | ExecutionProcessRunReason::AiReview
+                    | ExecutionProcessRunReason::ConflictResolution
+            )
         {
             Task::update_status(&self.db().pool, task.id, TaskStatus::InProgress).await?;
         }
@@ -1554,6 +1751,163 @@ pub trait ContainerService {
         Ok(execution_process)
     }
 
+    /// Start a follow-up execution from a queued message
+    async fn start_queued_follow_up(
+        &self,
+        workspace: &Workspace,
+        session: &Session,
+        queued_data: &DraftFollowUpData,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let pool = &self.db().pool;
+        let executor_profile_id = queued_data.executor_profile_id.clone();
+
+        // Validate executor matches session if session has prior executions
+        let expected_executor: Option<String> =
+            ExecutionProcess::latest_executor_profile_for_session(pool, session.id)
+                .await?
+                .map(|profile| profile.executor.to_string())
+                .or_else(|| session.executor.clone());
+
+        if let Some(expected) = expected_executor {
+            let actual = executor_profile_id.executor.to_string();
+            if expected != actual {
+                return Err(SessionError::ExecutorMismatch { expected, actual }.into());
+            }
+        }
+
+        if session.executor.is_none() {
+            Session::update_executor(pool, session.id, &executor_profile_id.executor.to_string())
+                .await?;
+        }
+
+        // Get latest agent session ID for session continuity (from coding agent turns)
+        let latest_agent_session_id =
+            ExecutionProcess::find_latest_coding_agent_turn_session_id(pool, session.id).await?;
+
+        let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+        let cleanup_action = self.cleanup_actions_for_repos(&repos);
+
+        let working_dir = workspace
+            .agent_working_dir
+            .as_ref()
+            .filter(|dir| !dir.is_empty())
+            .cloned();
+
+        let action_type = if let Some(agent_session_id) = latest_agent_session_id {
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt: queued_data.message.clone(),
+                session_id: agent_session_id,
+                executor_profile_id: executor_profile_id.clone(),
+                working_dir: working_dir.clone(),
+            })
+        } else {
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: queued_data.message.clone(),
+                executor_profile_id: executor_profile_id.clone(),
+                working_dir,
+            })
+        };
+
+        let action = ExecutorAction::new(action_type, cleanup_action.map(Box::new));
+
+        self.start_execution(
+            workspace,
+            session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await
+    }
+
+    /// Start a pending stage of a workspace's agent pipeline with the last message of the
+    /// stage before it. A stage continues the agent conversation when it runs the same agent
+    /// as the workspace's last coding agent run, and the last stage runs the cleanup scripts.
+    async fn start_pipeline_stage(
+        &self,
+        workspace: &Workspace,
+        session: &Session,
+        stage: &WorkspacePipelineStage,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let pool = &self.db().pool;
+        let task = workspace
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let stages = WorkspacePipelineStage::find_by_workspace_id(pool, workspace.id).await?;
+        let previous_output = match stages
+            .iter()
+            .rev()
+            .find(|previous| previous.position < stage.position)
+            .and_then(|previous| previous.execution_process_id)
+        {
+            Some(process_id) => CodingAgentTurn::find_by_execution_process_id(pool, process_id)
+                .await?
+                .and_then(|turn| turn.summary),
+            None => None,
+        };
+        let is_last = stages.iter().all(|other| other.position <= stage.position);
+
+        let prompt = AgentPipelineService::render_prompt(
+            &stage.prompt_template,
+            &stage.name,
+            &task.title,
+            task.description.as_deref(),
+            previous_output.as_deref(),
+        );
+        let executor_profile_id = stage.executor_profile_id.0.clone();
+        let session_profile =
+            ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?;
+        let agent_session_id = match session_profile {
+            Some(profile) if profile.executor == executor_profile_id.executor => {
+                ExecutionProcess::find_latest_coding_agent_turn_session_id(pool, session.id).await?
+            }
+            _ => None,
+        };
+        if session.executor.is_none() {
+            Session::update_executor(pool, session.id, &executor_profile_id.executor.to_string())
+                .await?;
+        }
+
+        let working_dir = workspace
+            .agent_working_dir
+            .as_ref()
+            .filter(|dir| !dir.is_empty())
+            .cloned();
+        let action_type = match agent_session_id {
+            Some(session_id) => {
+                ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                    prompt,
+                    session_id,
+                    executor_profile_id,
+                    working_dir,
+                })
+            }
+            None => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt,
+                executor_profile_id,
+                working_dir,
+            }),
+        };
+        let cleanup_action = if is_last {
+            let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+            self.cleanup_actions_for_repos(&repos)
+        } else {
+            None
+        };
+        let action = ExecutorAction::new(action_type, cleanup_action.map(Box::new));
+
+        let execution_process = self
+            .start_execution(
+                workspace,
+                session,
+                &action,
+                &ExecutionProcessRunReason::CodingAgent,
+            )
+            .await?;
+        WorkspacePipelineStage::start(pool, stage.id, execution_process.id).await?;
+        Ok(execution_process)
+    }
+
     async fn try_start_next_action(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
         let action = ctx.execution_process.executor_action()?;
         let next_action = if let Some(next_action) = action.next_action() {
@@ -0,0 +1,289 @@
+//! Rollups of the token usage and cost coding agents report for their turns.
+//!
+//! Only agents that report usage when a turn ends are counted; turns without recorded usage
+//! are left out. Costs are what the agent reported, in US dollars.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use db::models::coding_agent_turn::{CodingAgentTurn, TurnCost};
+use executors::logs::TurnUsage;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Window reported when a request does not give a start
+pub const DEFAULT_COST_WINDOW_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum CostPeriod {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct PeriodCost {
+    /// First day of the period; weeks start on Monday
+    #[ts(type = "string")]
+    pub period_start: NaiveDate,
+    pub turns: usize,
+    #[ts(type = "number")]
+    pub input_tokens: u64,
+    #[ts(type = "number")]
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct ProjectCost {
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub turns: usize,
+    #[ts(type = "number")]
+    pub input_tokens: u64,
+    #[ts(type = "number")]
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Resolve an optional `[from, to)` window; it ends now and spans the default days by default
+pub fn cost_window(
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let to = to.unwrap_or(now);
+    let from = from.unwrap_or(to - Duration::days(DEFAULT_COST_WINDOW_DAYS));
+    (from, to)
+}
+
+fn period_start(date: NaiveDate, period: CostPeriod) -> NaiveDate {
+    match period {
+        CostPeriod::Day => date,
+        CostPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+        CostPeriod::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+fn token_count(tokens: i64) -> u64 {
+    tokens.max(0) as u64
+}
+
+/// Totals per period that has usage, oldest first
+fn rollup_by_period(costs: &[TurnCost], period: CostPeriod) -> Vec<PeriodCost> {
+    let mut buckets: BTreeMap<NaiveDate, PeriodCost> = BTreeMap::new();
+    for cost in costs {
+        let start = period_start(cost.created_at.date_naive(), period);
+        let bucket = buckets.entry(start).or_insert_with(|| PeriodCost {
+            period_start: start,
+            turns: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd: 0.0,
+        });
+        bucket.turns += 1;
+        bucket.input_tokens += token_count(cost.input_tokens);
+        bucket.output_tokens += token_count(cost.output_tokens);
+        bucket.cost_usd += cost.cost_usd.unwrap_or(0.0);
+    }
+    buckets.into_values().collect()
+}
+
+/// Totals per project, most expensive first
+fn rollup_by_project(costs: &[TurnCost]) -> Vec<ProjectCost> {
+    let mut projects: HashMap<Uuid, ProjectCost> = HashMap::new();
+    for cost in costs {
+        let project = projects
+            .entry(cost.project_id)
+            .or_insert_with(|| ProjectCost {
+                project_id: cost.project_id,
+                project_name: cost.project_name.clone(),
+                turns: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+                cost_usd: 0.0,
+            });
+        project.turns += 1;
+        project.input_tokens += token_count(cost.input_tokens);
+        project.output_tokens += token_count(cost.output_tokens);
+        project.cost_usd += cost.cost_usd.unwrap_or(0.0);
+    }
+    let mut projects: Vec<ProjectCost> = projects.into_values().collect();
+    projects.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.project_name.cmp(&b.project_name))
+    });
+    projects
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn period_costs_csv(rows: &[PeriodCost]) -> String {
+    let mut csv = String::from("period_start,turns,input_tokens,output_tokens,cost_usd\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{:.4}\n",
+            row.period_start, row.turns, row.input_tokens, row.output_tokens, row.cost_usd
+        ));
+    }
+    csv
+}
+
+pub fn project_costs_csv(rows: &[ProjectCost]) -> String {
+    let mut csv =
+        String::from("project_id,project_name,turns,input_tokens,output_tokens,cost_usd\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{:.4}\n",
+            row.project_id,
+            csv_field(&row.project_name),
+            row.turns,
+            row.input_tokens,
+            row.output_tokens,
+            row.cost_usd
+        ));
+    }
+    csv
+}
+
+/// Whether a turn costing `turn_cost` took the month's spend, now `month_total`, over the
+/// budget; true for exactly one turn per month
+fn crossed_budget(month_total: f64, turn_cost: f64, budget_usd: u32) -> bool {
+    let budget = f64::from(budget_usd);
+    month_total > budget && month_total - turn_cost <= budget
+}
+
+pub struct CostReportService;
+
+impl CostReportService {
+    pub async fn by_period(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        period: CostPeriod,
+    ) -> Result<Vec<PeriodCost>, sqlx::Error> {
+        let costs = CodingAgentTurn::find_costs_between(pool, project_id, from, to).await?;
+        Ok(rollup_by_period(&costs, period))
+    }
+
+    pub async fn by_project(
+        pool: &SqlitePool,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ProjectCost>, sqlx::Error> {
+        let costs = CodingAgentTurn::find_costs_between(pool, None, from, to).await?;
+        Ok(rollup_by_project(&costs))
+    }
+
+    /// Store the usage reported for a turn. Returns the month's spend when this turn took it
+    /// over the monthly budget, so the caller can notify.
+    pub async fn record_turn_usage(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        usage: &TurnUsage,
+        monthly_budget_usd: Option<u32>,
+    ) -> Result<Option<f64>, sqlx::Error> {
+        CodingAgentTurn::update_usage(
+            pool,
+            execution_process_id,
+            usage.input_tokens.try_into().unwrap_or(i64::MAX),
+            usage.output_tokens.try_into().unwrap_or(i64::MAX),
+            usage.cost_usd,
+        )
+        .await?;
+
+        let (Some(budget), Some(turn_cost)) = (monthly_budget_usd, usage.cost_usd) else {
+            return Ok(None);
+        };
+        let month_total = CodingAgentTurn::total_cost_since(pool, month_start(Utc::now())).await?;
+        Ok(crossed_budget(month_total, turn_cost, budget).then_some(month_total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(project: Uuid, name: &str, day: u32, cost: Option<f64>) -> TurnCost {
+        TurnCost {
+            project_id: project,
+            project_name: name.to_string(),
+            created_at: Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap(),
+            input_tokens: 1000,
+            output_tokens: 100,
+            cost_usd: cost,
+        }
+    }
+
+    #[test]
+    fn turns_roll_up_by_day_week_and_month() {
+        let project = Uuid::new_v4();
+        // 2026-03-01 is a Sunday, so it belongs to the week starting Monday 2026-02-23
+        let costs = [
+            turn(project, "api", 1, Some(0.5)),
+            turn(project, "api", 2, Some(1.0)),
+            turn(project, "api", 4, None),
+        ];
+
+        let days = rollup_by_period(&costs, CostPeriod::Day);
+        assert_eq!(days.len(), 3);
+        assert_eq!(days[2].cost_usd, 0.0);
+
+        let weeks = rollup_by_period(&costs, CostPeriod::Week);
+        let starts: Vec<String> = weeks.iter().map(|w| w.period_start.to_string()).collect();
+        assert_eq!(starts, ["2026-02-23", "2026-03-02"]);
+        assert_eq!(weeks[1].turns, 2);
+        assert_eq!(weeks[1].input_tokens, 2000);
+
+        let months = rollup_by_period(&costs, CostPeriod::Month);
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].cost_usd, 1.5);
+    }
+
+    #[test]
+    fn projects_are_ordered_by_cost_and_exported_as_csv() {
+        let (cheap, costly) = (Uuid::new_v4(), Uuid::new_v4());
+        let costs = [
+            turn(cheap, "docs", 1, Some(0.25)),
+            turn(costly, "web, \"app\"", 1, Some(2.0)),
+            turn(costly, "web, \"app\"", 2, Some(1.0)),
+        ];
+        let projects = rollup_by_project(&costs);
+        assert_eq!(projects[0].project_id, costly);
+        assert_eq!(projects[0].turns, 2);
+
+        let csv = project_costs_csv(&projects);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            format!("{costly},\"web, \"\"app\"\"\",2,2000,200,3.0000")
+        );
+    }
+
+    #[test]
+    fn budget_is_crossed_by_exactly_one_turn() {
+        assert!(crossed_budget(10.5, 1.0, 10));
+        assert!(!crossed_budget(11.5, 1.0, 10));
+        assert!(!crossed_budget(9.5, 1.0, 10));
+    }
+}
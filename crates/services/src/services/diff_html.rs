@@ -0,0 +1,201 @@
+//! Standalone HTML rendering of unified diffs.
+//!
+//! The page carries its own styles and no scripts, so it can be emailed, attached to a pull
+//! request or opened from disk without the frontend. Code lines are highlighted with the
+//! syntax picked from each file's extension; a file's removed and added lines share one
+//! highlighter, which is close enough inside short hunks.
+
+use std::{fmt::Write, path::Path, sync::LazyLock};
+
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    html::{IncludeBackground, styled_line_to_highlighted_html},
+    parsing::SyntaxSet,
+};
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    let mut themes = ThemeSet::load_defaults();
+    themes.themes.remove("InspiredGitHub").unwrap_or_default()
+});
+
+const STYLE: &str = "body{font-family:-apple-system,'Segoe UI',sans-serif;margin:2em;color:#1f2328}\
+h1{font-size:1.5em}h2{font-size:1.2em;margin-top:2em}\
+.file{border:1px solid #d0d7de;border-radius:6px;margin:1em 0;overflow:hidden}\
+.file-header{background:#f6f8fa;padding:.5em 1em;border-bottom:1px solid #d0d7de;font-family:monospace}\
+.stats{float:right}.add-count{color:#1a7f37}.del-count{color:#cf222e}\
+pre{margin:0;padding:.5em 0;font-size:12px;line-height:1.5;overflow-x:auto}\
+.line{display:block;padding:0 1em;white-space:pre}\
+.add{background:#e6ffec}.del{background:#ffebe9}.hunk{background:#ddf4ff;color:#57606a}\
+.empty{padding:.5em 1em;color:#57606a}";
+
+/// A changed file to render
+pub struct DiffHtmlFile<'a> {
+    pub path: &'a str,
+    pub additions: usize,
+    pub deletions: usize,
+    /// Unified diff of the file, `---`/`+++` header included
+    pub diff_content: &'a str,
+}
+
+/// A group of files rendered under its own heading, such as one repo of a workspace
+pub struct DiffHtmlSection<'a> {
+    pub title: &'a str,
+    pub files: Vec<DiffHtmlFile<'a>>,
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_file(html: &mut String, file: &DiffHtmlFile) {
+    let syntax = Path::new(file.path)
+        .extension()
+        .and_then(|ext| SYNTAXES.find_syntax_by_extension(&ext.to_string_lossy()))
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+
+    let _ = write!(
+        html,
+        "<div class=\"file\"><div class=\"file-header\">{}<span class=\"stats\">\
+         <span class=\"add-count\">+{}</span> <span class=\"del-count\">-{}</span></span></div>",
+        escape(file.path),
+        file.additions,
+        file.deletions
+    );
+    if !file.diff_content.lines().any(|line| line.starts_with("@@")) {
+        // Placeholders such as "[Content omitted - file too large]" are shown as they are
+        let content = if file.diff_content.starts_with('[') {
+            file.diff_content.trim()
+        } else {
+            "No content changes"
+        };
+        let _ = write!(html, "<div class=\"empty\">{}</div></div>", escape(content));
+        return;
+    }
+
+    html.push_str("<pre>");
+    for line in file.diff_content.split_inclusive('\n') {
+        if line.starts_with("--- ") || line.starts_with("+++ ") {
+            continue;
+        }
+        if line.starts_with("@@") {
+            let _ = write!(
+                html,
+                "<span class=\"line hunk\">{}</span>",
+                escape(line.trim_end())
+            );
+            continue;
+        }
+        let (class, marker, code) = match line.split_at_checked(1) {
+            Some(("+", code)) => ("add", "+", code),
+            Some(("-", code)) => ("del", "-", code),
+            Some((" ", code)) => ("ctx", " ", code),
+            _ => ("ctx", "", line),
+        };
+        let code = match highlighter.highlight_line(code, &SYNTAXES) {
+            Ok(regions) => styled_line_to_highlighted_html(&regions, IncludeBackground::No)
+                .unwrap_or_else(|_| escape(code)),
+            Err(_) => escape(code),
+        };
+        let _ = write!(
+            html,
+            "<span class=\"line {class}\">{marker}{}</span>",
+            code.trim_end_matches('\n')
+        );
+    }
+    html.push_str("</pre></div>");
+}
+
+pub struct DiffHtmlService;
+
+impl DiffHtmlService {
+    /// Render the sections as a complete HTML document titled `title`
+    pub fn render(title: &str, sections: &[DiffHtmlSection]) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{0}</title>\
+             <style>{STYLE}</style></head><body><h1>{0}</h1>",
+            escape(title)
+        );
+        for section in sections {
+            let (additions, deletions) = section.files.iter().fold((0, 0), |(a, d), file| {
+                (a + file.additions, d + file.deletions)
+            });
+            let _ = write!(
+                html,
+                "<h2>{} <span class=\"add-count\">+{additions}</span> \
+                 <span class=\"del-count\">-{deletions}</span></h2>",
+                escape(section.title)
+            );
+            if section.files.is_empty() {
+                html.push_str("<p class=\"empty\">No changes</p>");
+            }
+            for file in &section.files {
+                render_file(&mut html, file);
+            }
+        }
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(path: &str, diff_content: &str) -> String {
+        DiffHtmlService::render(
+            "Add <main> & friends",
+            &[DiffHtmlSection {
+                title: "api",
+                files: vec![DiffHtmlFile {
+                    path,
+                    additions: 1,
+                    deletions: 1,
+                    diff_content,
+                }],
+            }],
+        )
+    }
+
+    #[test]
+    fn lines_are_classified_and_highlighted() {
+        let html = render(
+            "src/main.rs",
+            "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,2 +1,2 @@\n fn main() {\n-    old();\n+    new();\n",
+        );
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Add &lt;main&gt; &amp; friends</title>"));
+        assert!(html.contains("<span class=\"line hunk\">@@ -1,2 +1,2 @@</span>"));
+        assert_eq!(html.matches("class=\"line add\"").count(), 1);
+        assert_eq!(html.matches("class=\"line del\"").count(), 1);
+        assert_eq!(html.matches("class=\"line ctx\"").count(), 1);
+        // Highlighted code carries inline colours
+        assert!(html.contains("style=\"color:"));
+        assert!(!html.contains("+++ b/src/main.rs"));
+    }
+
+    #[test]
+    fn code_is_escaped_and_omitted_content_is_noted() {
+        let html = render("notes", "@@ -0,0 +1 @@\n+<script>alert(1)</script>\n");
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+
+        let html = render("big.json", "[Content omitted - file too large]");
+        assert!(html.contains("<div class=\"empty\">[Content omitted - file too large]</div>"));
+    }
+}
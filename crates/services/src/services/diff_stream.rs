@@ -12,7 +12,7 @@ use std::{
 use db::{DBService, models::workspace_repo::WorkspaceRepo};
 use executors::logs::utils::{ConversationPatch, patch::escape_json_pointer_segment};
 use futures::StreamExt;
-use git::{Commit, DiffTarget, GitService, GitServiceError};
+use git::{Commit, DiffTarget, GitService, GitServiceError, is_in_path_scope};
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{
     DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache, new_debouncer,
@@ -101,6 +101,8 @@ pub struct DiffStreamArgs {
     pub base_commit: Commit,
     pub stats_only: bool,
     pub path_prefix: Option<String>,
+    /// Directory of the repo the diff is limited to
+    pub path_scope: Option<String>,
 }
 
 struct DiffStreamManager {
@@ -234,14 +236,17 @@ impl DiffStreamManager {
         let base = self.current_base_commit.clone();
         let stats_only = self.args.stats_only;
         let cumulative = self.cumulative.clone();
+        let path_scope = self.args.path_scope.clone();
 
         tokio::task::spawn_blocking(move || {
+            let path_scope = path_scope.as_deref();
             let diffs = git.get_diffs(
                 DiffTarget::Worktree {
                     worktree_path: &worktree,
                     base_commit: &base,
+                    include_untracked: true,
                 },
-                None,
+                path_scope.as_ref().map(std::slice::from_ref),
             )?;
 
             let mut processed_diffs = Vec::with_capacity(diffs.len());
@@ -291,8 +296,11 @@ impl DiffStreamManager {
         events: Vec<DebouncedEvent>,
         canonical_worktree: &Path,
     ) -> Result<(), DiffStreamError> {
-        let changed_paths =
+        let mut changed_paths =
             extract_changed_paths(&events, canonical_worktree, &self.args.worktree_path);
+        if let Some(scope) = &self.args.path_scope {
+            changed_paths.retain(|path| is_in_path_scope(path, scope));
+        }
 
         if changed_paths.is_empty() {
             return Ok(());
@@ -468,6 +476,7 @@ fn process_file_changes(
         DiffTarget::Worktree {
             worktree_path,
             base_commit,
+            include_untracked: true,
         },
         Some(&path_filter),
     )?;
@@ -0,0 +1,267 @@
+//! Layered environment variables for execution processes.
+//!
+//! Every agent run, setup script and dev server of a workspace receives, from lowest to
+//! highest precedence: the server-wide variables from the config, the project's enabled env
+//! sets, the workspace's own overrides and finally allowlisted secrets. Secrets come last so
+//! that no other layer can replace the value of one, which would then not be masked in the
+//! process's output.
+
+use std::collections::HashMap;
+
+use db::models::{
+    project_env_set::{CreateProjectEnvSet, ProjectEnvSet, UpdateProjectEnvSet},
+    workspace_env_override::WorkspaceEnvOverride,
+};
+use executors::env::is_user_env_var_name;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum EnvVarError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Invalid environment variable name '{0}'")]
+    InvalidName(String),
+    #[error("Env set name must not be empty")]
+    EmptySetName,
+    #[error("An env set named '{0}' already exists")]
+    DuplicateSetName(String),
+    #[error("Env set {0} not found")]
+    SetNotFound(Uuid),
+}
+
+pub fn validate_env_vars(vars: &HashMap<String, String>) -> Result<(), EnvVarError> {
+    match vars.keys().find(|name| !is_user_env_var_name(name)) {
+        Some(name) => Err(EnvVarError::InvalidName(name.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Combine the enabled sets in name order; later sets win over earlier ones
+fn merge_env_sets(sets: &[ProjectEnvSet]) -> HashMap<String, String> {
+    let mut enabled: Vec<&ProjectEnvSet> = sets.iter().filter(|set| set.enabled).collect();
+    enabled.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut vars = HashMap::new();
+    for set in enabled {
+        vars.extend(set.vars.0.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    vars
+}
+
+pub struct EnvVarService;
+
+impl EnvVarService {
+    async fn ensure_unique_set_name(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        name: &str,
+        except: Option<Uuid>,
+    ) -> Result<(), EnvVarError> {
+        let taken = ProjectEnvSet::find_by_project_id(pool, project_id)
+            .await?
+            .iter()
+            .any(|set| set.name == name && Some(set.id) != except);
+        if taken {
+            return Err(EnvVarError::DuplicateSetName(name.to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn create_set(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateProjectEnvSet,
+    ) -> Result<ProjectEnvSet, EnvVarError> {
+        if data.name.trim().is_empty() {
+            return Err(EnvVarError::EmptySetName);
+        }
+        validate_env_vars(&data.vars)?;
+        Self::ensure_unique_set_name(pool, project_id, &data.name, None).await?;
+        Ok(ProjectEnvSet::create(pool, project_id, data).await?)
+    }
+
+    pub async fn update_set(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        set_id: Uuid,
+        data: &UpdateProjectEnvSet,
+    ) -> Result<ProjectEnvSet, EnvVarError> {
+        ProjectEnvSet::find_by_id(pool, set_id)
+            .await?
+            .filter(|set| set.project_id == project_id)
+            .ok_or(EnvVarError::SetNotFound(set_id))?;
+        if let Some(name) = &data.name {
+            if name.trim().is_empty() {
+                return Err(EnvVarError::EmptySetName);
+            }
+            Self::ensure_unique_set_name(pool, project_id, name, Some(set_id)).await?;
+        }
+        if let Some(vars) = &data.vars {
+            validate_env_vars(vars)?;
+        }
+        Ok(ProjectEnvSet::update(pool, set_id, data).await?)
+    }
+
+    pub async fn delete_set(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        set_id: Uuid,
+    ) -> Result<(), EnvVarError> {
+        ProjectEnvSet::find_by_id(pool, set_id)
+            .await?
+            .filter(|set| set.project_id == project_id)
+            .ok_or(EnvVarError::SetNotFound(set_id))?;
+        ProjectEnvSet::delete(pool, set_id).await?;
+        Ok(())
+    }
+
+    /// Variables of the project's enabled env sets
+    pub async fn env_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<String, String>, EnvVarError> {
+        let sets = ProjectEnvSet::find_by_project_id(pool, project_id).await?;
+        Ok(merge_env_sets(&sets))
+    }
+
+    /// Variables set for the workspace itself
+    pub async fn workspace_overrides(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<HashMap<String, String>, EnvVarError> {
+        Ok(WorkspaceEnvOverride::find_by_workspace_id(pool, workspace_id).await?)
+    }
+
+    /// Replace the workspace's overrides; an empty map removes them
+    pub async fn set_workspace_overrides(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        vars: &HashMap<String, String>,
+    ) -> Result<(), EnvVarError> {
+        validate_env_vars(vars)?;
+        WorkspaceEnvOverride::set(pool, workspace_id, vars).await?;
+        Ok(())
+    }
+}
+
+/// The variables of every layer, each replacing those before it: `server`, `project`,
+/// `workspace` and last `secrets`
+pub fn layer_env(
+    server: &HashMap<String, String>,
+    project: &HashMap<String, String>,
+    workspace: &HashMap<String, String>,
+    secrets: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    [server, project, workspace, secrets]
+        .into_iter()
+        .flat_map(|layer| layer.iter().map(|(k, v)| (k.clone(), v.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::services::test_support::{seed_workspace, test_pool};
+
+    fn env_set(name: &str, enabled: bool, vars: &[(&str, &str)]) -> ProjectEnvSet {
+        ProjectEnvSet {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            name: name.to_string(),
+            vars: sqlx::types::Json(
+                vars.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+            enabled,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn enabled_sets_merge_in_name_order() {
+        let sets = [
+            env_set("b-overrides", true, &[("API_URL", "b"), ("B", "1")]),
+            env_set("a-base", true, &[("API_URL", "a"), ("A", "1")]),
+            env_set("c-disabled", false, &[("API_URL", "c")]),
+        ];
+        let vars = merge_env_sets(&sets);
+        assert_eq!(vars.get("API_URL").map(String::as_str), Some("b"));
+        assert_eq!(vars.len(), 3);
+    }
+
+    #[test]
+    fn workspace_overrides_cannot_replace_secrets() {
+        let layer = |vars: &[(&str, &str)]| -> HashMap<String, String> {
+            vars.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let vars = layer_env(
+            &layer(&[("API_URL", "server"), ("LOG", "info")]),
+            &layer(&[("API_URL", "project"), ("NODE_ENV", "test")]),
+            &layer(&[("API_URL", "workspace"), ("API_TOKEN", "visible")]),
+            &layer(&[("API_TOKEN", "secret-token")]),
+        );
+        assert_eq!(
+            vars,
+            layer(&[
+                ("API_URL", "workspace"),
+                ("LOG", "info"),
+                ("NODE_ENV", "test"),
+                ("API_TOKEN", "secret-token"),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_and_reserved_names() {
+        let vars = HashMap::from([("VK_TASK_ID".to_string(), "x".to_string())]);
+        assert!(matches!(
+            validate_env_vars(&vars),
+            Err(EnvVarError::InvalidName(_))
+        ));
+        let vars = HashMap::from([("NODE_ENV".to_string(), "test".to_string())]);
+        assert!(validate_env_vars(&vars).is_ok());
+    }
+
+    #[tokio::test]
+    async fn workspace_overrides_are_validated_and_cleared_by_an_empty_map() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let seeded = seed_workspace(&pool, &dir.path().join("ws"), &dir.path().join("api")).await;
+        let workspace_id = seeded.workspace.id;
+
+        let reserved = HashMap::from([("VK_TASK_ID".to_string(), "x".to_string())]);
+        assert!(matches!(
+            EnvVarService::set_workspace_overrides(&pool, workspace_id, &reserved).await,
+            Err(EnvVarError::InvalidName(_))
+        ));
+
+        let vars = HashMap::from([("API_URL".to_string(), "http://localhost".to_string())]);
+        EnvVarService::set_workspace_overrides(&pool, workspace_id, &vars)
+            .await
+            .unwrap();
+        assert_eq!(
+            EnvVarService::workspace_overrides(&pool, workspace_id)
+                .await
+                .unwrap(),
+            vars
+        );
+
+        EnvVarService::set_workspace_overrides(&pool, workspace_id, &HashMap::new())
+            .await
+            .unwrap();
+        assert!(
+            EnvVarService::workspace_overrides(&pool, workspace_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
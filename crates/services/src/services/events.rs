@@ -15,6 +15,8 @@ use uuid::Uuid;
 
 #[path = "events/patches.rs"]
 pub mod patches;
+#[path = "events/project_events.rs"]
+pub mod project_events;
 #[path = "events/streams.rs"]
 mod streams;
 #[path = "events/types.rs"]
@@ -23,26 +25,41 @@ pub mod types;
 pub use patches::{
     execution_process_patch, project_patch, scratch_patch, task_patch, workspace_patch,
 };
+pub use project_events::{
+    PROJECT_EVENT_REPLAY_CAPACITY, ProjectEvent, ProjectEventEntity, ProjectEventLog,
+    ProjectEventOp, ProjectEventReplay,
+};
 pub use types::{EventError, EventPatch, EventPatchInner, HookTables, RecordTypes};
 
 #[derive(Clone)]
 pub struct EventService {
     msg_store: Arc<MsgStore>,
     db: DBService,
+    project_events: Arc<ProjectEventLog>,
     #[allow(dead_code)]
     entry_count: Arc<RwLock<usize>>,
 }
 
 impl EventService {
     /// Creates a new EventService that will work with a DBService configured with hooks
+    /// Spawns the per-project event feed, so it must be called inside a Tokio runtime.
     pub fn new(db: DBService, msg_store: Arc<MsgStore>, entry_count: Arc<RwLock<usize>>) -> Self {
+        let project_events = Arc::new(ProjectEventLog::new(PROJECT_EVENT_REPLAY_CAPACITY));
+        project_events.spawn(db.clone(), msg_store.clone());
         Self {
             msg_store,
             db,
+            project_events,
             entry_count,
         }
     }
 
+    /// Task, workspace and execution process events numbered per deployment, with a
+    /// replay window for reconnecting clients
+    pub fn project_events(&self) -> &Arc<ProjectEventLog> {
+        &self.project_events
+    }
+
     async fn push_task_update_for_task(
         pool: &SqlitePool,
         msg_store: Arc<MsgStore>,
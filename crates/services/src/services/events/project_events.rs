@@ -0,0 +1,343 @@
+//! Per-project event feed with a replay window.
+//!
+//! The global msg store carries JSON patches for every record in the database. This module
+//! classifies task, workspace and execution process patches by project, numbers them, and
+//! keeps the most recent ones in a ring buffer so a reconnecting client can resume from the
+//! last event id it saw.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use db::{
+    DBService,
+    models::{
+        execution_process::ExecutionProcess,
+        session::Session,
+        task::{Task, TaskWithAttemptStatus},
+        workspace::{Workspace, WorkspaceWithStatus},
+    },
+};
+use futures::StreamExt;
+use json_patch::PatchOperation;
+use serde::Serialize;
+use strum_macros::Display;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use ts_rs::TS;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+/// Number of events kept for `Last-Event-ID` replay, across all projects
+pub const PROJECT_EVENT_REPLAY_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ProjectEventEntity {
+    Task,
+    Workspace,
+    ExecutionProcess,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ProjectEventOp {
+    Created,
+    Updated,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectEvent {
+    /// Monotonic id shared by all projects; sent as the SSE event id
+    #[ts(type = "number")]
+    pub id: u64,
+    pub project_id: Uuid,
+    pub entity: ProjectEventEntity,
+    pub entity_id: Uuid,
+    pub op: ProjectEventOp,
+    /// The record after the change (`TaskWithAttemptStatus`, `WorkspaceWithStatus` or
+    /// `ExecutionProcess`); absent for deletions
+    #[ts(type = "unknown")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl ProjectEvent {
+    /// SSE event type, e.g. `task.updated`
+    pub fn event_type(&self) -> String {
+        format!("{}.{}", self.entity, self.op)
+    }
+}
+
+/// Events a subscriber has to catch up on before following the live feed
+pub struct ProjectEventReplay {
+    pub events: Vec<ProjectEvent>,
+    /// False when events after the requested id have already left the buffer, in which
+    /// case the client should refetch the board instead of relying on the replay
+    pub complete: bool,
+}
+
+struct LogState {
+    next_id: u64,
+    buffer: VecDeque<ProjectEvent>,
+    /// Project of every entity seen so far, so deletions (which carry no record) and
+    /// child records can be attributed
+    entity_projects: HashMap<Uuid, Uuid>,
+}
+
+pub struct ProjectEventLog {
+    capacity: usize,
+    state: Mutex<LogState>,
+    sender: broadcast::Sender<ProjectEvent>,
+}
+
+impl ProjectEventLog {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(16));
+        Self {
+            capacity,
+            state: Mutex::new(LogState {
+                next_id: 1,
+                buffer: VecDeque::with_capacity(capacity),
+                entity_projects: HashMap::new(),
+            }),
+            sender,
+        }
+    }
+
+    /// Buffered events for `project_id` newer than `last_event_id`, plus a receiver for
+    /// everything recorded afterwards. Both are taken under the same lock so no event
+    /// falls between the replay and the live feed.
+    pub fn subscribe(
+        &self,
+        project_id: Uuid,
+        last_event_id: Option<u64>,
+    ) -> (ProjectEventReplay, broadcast::Receiver<ProjectEvent>) {
+        let state = self.state.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let Some(last_event_id) = last_event_id else {
+            return (
+                ProjectEventReplay {
+                    events: Vec::new(),
+                    complete: true,
+                },
+                receiver,
+            );
+        };
+
+        let oldest = state.buffer.front().map_or(state.next_id, |event| event.id);
+        let events = state
+            .buffer
+            .iter()
+            .filter(|event| event.id > last_event_id && event.project_id == project_id)
+            .cloned()
+            .collect();
+        let complete = last_event_id + 1 >= oldest && last_event_id < state.next_id;
+        (ProjectEventReplay { events, complete }, receiver)
+    }
+
+    fn project_of(&self, entity_id: Uuid) -> Option<Uuid> {
+        self.state
+            .lock()
+            .unwrap()
+            .entity_projects
+            .get(&entity_id)
+            .copied()
+    }
+
+    fn remember(&self, entity_id: Uuid, project_id: Uuid) {
+        self.state
+            .lock()
+            .unwrap()
+            .entity_projects
+            .insert(entity_id, project_id);
+    }
+
+    fn record(
+        &self,
+        project_id: Uuid,
+        entity: ProjectEventEntity,
+        entity_id: Uuid,
+        op: ProjectEventOp,
+        data: Option<serde_json::Value>,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        if op == ProjectEventOp::Deleted {
+            state.entity_projects.remove(&entity_id);
+        } else {
+            state.entity_projects.insert(entity_id, project_id);
+        }
+
+        let event = ProjectEvent {
+            id: state.next_id,
+            project_id,
+            entity,
+            entity_id,
+            op,
+            data,
+        };
+        state.next_id += 1;
+        if state.buffer.len() == self.capacity {
+            state.buffer.pop_front();
+        }
+        state.buffer.push_back(event.clone());
+        // Sending under the lock keeps the live feed ordered with the buffer
+        let _ = self.sender.send(event);
+    }
+
+    /// Follow the msg store and record project events until it closes
+    pub fn spawn(self: &Arc<Self>, db: DBService, msg_store: Arc<MsgStore>) {
+        let log = self.clone();
+        let mut stream = BroadcastStream::new(msg_store.get_receiver());
+        tokio::spawn(async move {
+            while let Some(msg) = stream.next().await {
+                let Ok(LogMsg::JsonPatch(patch)) = msg else {
+                    continue;
+                };
+                for op in patch.0 {
+                    if let Err(err) = log.classify(&db, op).await {
+                        tracing::warn!("Failed to attribute event to a project: {err}");
+                    }
+                }
+            }
+        });
+    }
+
+    async fn classify(&self, db: &DBService, op: PatchOperation) -> Result<(), sqlx::Error> {
+        let Some((entity, entity_id)) = parse_entity_path(op.path().as_str()) else {
+            return Ok(());
+        };
+        let (op, value) = match op {
+            PatchOperation::Add(op) => (ProjectEventOp::Created, Some(op.value)),
+            PatchOperation::Replace(op) => (ProjectEventOp::Updated, Some(op.value)),
+            PatchOperation::Remove(_) => (ProjectEventOp::Deleted, None),
+            _ => return Ok(()),
+        };
+
+        let project_id = match &value {
+            None => self.project_of(entity_id),
+            Some(value) => self.resolve_project(db, entity, value.clone()).await?,
+        };
+        if let Some(project_id) = project_id {
+            self.record(project_id, entity, entity_id, op, value);
+        }
+        Ok(())
+    }
+
+    async fn resolve_project(
+        &self,
+        db: &DBService,
+        entity: ProjectEventEntity,
+        value: serde_json::Value,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        match entity {
+            ProjectEventEntity::Task => Ok(serde_json::from_value::<TaskWithAttemptStatus>(value)
+                .ok()
+                .map(|task| task.project_id)),
+            ProjectEventEntity::Workspace => {
+                match serde_json::from_value::<WorkspaceWithStatus>(value) {
+                    Ok(workspace) => self.project_of_task(db, workspace.task_id).await,
+                    Err(_) => Ok(None),
+                }
+            }
+            ProjectEventEntity::ExecutionProcess => {
+                let Ok(process) = serde_json::from_value::<ExecutionProcess>(value) else {
+                    return Ok(None);
+                };
+                let Some(session) = Session::find_by_id(&db.pool, process.session_id).await? else {
+                    return Ok(None);
+                };
+                if let Some(project_id) = self.project_of(session.workspace_id) {
+                    return Ok(Some(project_id));
+                }
+                let Some(workspace) = Workspace::find_by_id(&db.pool, session.workspace_id).await?
+                else {
+                    return Ok(None);
+                };
+                let project_id = self.project_of_task(db, workspace.task_id).await?;
+                if let Some(project_id) = project_id {
+                    self.remember(workspace.id, project_id);
+                }
+                Ok(project_id)
+            }
+        }
+    }
+
+    async fn project_of_task(
+        &self,
+        db: &DBService,
+        task_id: Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        if let Some(project_id) = self.project_of(task_id) {
+            return Ok(Some(project_id));
+        }
+        let project_id = Task::find_by_id(&db.pool, task_id)
+            .await?
+            .map(|task| task.project_id);
+        if let Some(project_id) = project_id {
+            self.remember(task_id, project_id);
+        }
+        Ok(project_id)
+    }
+}
+
+/// Entity addressed by a msg store patch path such as `/tasks/{id}`
+fn parse_entity_path(path: &str) -> Option<(ProjectEventEntity, Uuid)> {
+    let mut segments = path.strip_prefix('/')?.split('/');
+    let entity = match segments.next()? {
+        "tasks" => ProjectEventEntity::Task,
+        "workspaces" => ProjectEventEntity::Workspace,
+        "execution_processes" => ProjectEventEntity::ExecutionProcess,
+        _ => return None,
+    };
+    let id = segments.next()?.parse().ok()?;
+    segments.next().is_none().then_some((entity, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entity_paths() {
+        let id = Uuid::new_v4();
+        assert_eq!(
+            parse_entity_path(&format!("/workspaces/{id}")),
+            Some((ProjectEventEntity::Workspace, id))
+        );
+        assert_eq!(parse_entity_path("/tasks"), None);
+        assert_eq!(parse_entity_path(&format!("/projects/{id}")), None);
+    }
+
+    #[test]
+    fn replays_events_after_last_id_for_the_project() {
+        let log = ProjectEventLog::new(3);
+        let (project, other) = (Uuid::new_v4(), Uuid::new_v4());
+        for owner in [project, other, project, project] {
+            log.record(
+                owner,
+                ProjectEventEntity::Task,
+                Uuid::new_v4(),
+                ProjectEventOp::Updated,
+                None,
+            );
+        }
+
+        // Event 1 was evicted, so resuming after it is still gap-free
+        let (replay, _) = log.subscribe(project, Some(1));
+        assert!(replay.complete);
+        let ids: Vec<u64> = replay.events.iter().map(|event| event.id).collect();
+        assert_eq!(ids, vec![3, 4]);
+
+        let (replay, _) = log.subscribe(project, Some(0));
+        assert!(!replay.complete);
+
+        // An id from before a server restart is unknown
+        let (replay, _) = log.subscribe(project, Some(99));
+        assert!(!replay.complete);
+        assert!(replay.events.is_empty());
+    }
+}
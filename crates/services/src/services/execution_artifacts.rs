@@ -0,0 +1,210 @@
+//! Collection of the files execution processes declare as artifacts.
+//!
+//! Scripts and agent profiles list glob patterns of files worth keeping, such as build
+//! outputs and coverage reports. After a process exits, the matching files under the
+//! directory it ran in are copied to the artifact store under `<process id>/` and recorded,
+//! so they can be listed and downloaded once the worktree is gone. Ignore files are not honoured,
+//! as build outputs are usually ignored, but `.git` is never searched. A pattern starting
+//! with `!` excludes the files it matches.
+
+use std::path::{Path, PathBuf};
+
+use db::models::execution_process_artifact::ExecutionProcessArtifact;
+use ignore::{WalkBuilder, overrides::OverrideBuilder};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::object_storage::{ObjectStorageError, ObjectStore};
+
+/// Files beyond these limits are left behind, keeping a mistaken `**` from copying a tree
+const MAX_FILES: usize = 500;
+const MAX_TOTAL_BYTES: u64 = 1 << 30;
+
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Pattern(#[from] ignore::Error),
+    #[error(transparent)]
+    Storage(#[from] ObjectStorageError),
+}
+
+#[derive(Debug, PartialEq)]
+struct ArtifactFile {
+    /// Relative to the search root, with `/` separators
+    path: String,
+    size_bytes: u64,
+}
+
+/// Files under `root` matching `patterns` in path order, up to the collection limits
+fn matching_files(root: &Path, patterns: &[String]) -> Result<Vec<ArtifactFile>, ArtifactError> {
+    let mut builder = OverrideBuilder::new(root);
+    for pattern in patterns {
+        builder.add(pattern)?;
+    }
+    let overrides = builder.build()?;
+
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_path(|a, b| a.cmp(b))
+        .build();
+
+    let mut files = Vec::new();
+    let mut total_bytes = 0;
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::debug!("Skipping unreadable entry under {}: {}", root.display(), e);
+                continue;
+            }
+        };
+        if !entry.file_type().is_some_and(|t| t.is_file())
+            || !overrides.matched(entry.path(), false).is_whitelist()
+        {
+            continue;
+        }
+        let size_bytes = entry.metadata()?.len();
+        if files.len() == MAX_FILES || total_bytes + size_bytes > MAX_TOTAL_BYTES {
+            tracing::warn!(
+                "Artifacts under {} exceed {} files or {} bytes; the rest are not collected",
+                root.display(),
+                MAX_FILES,
+                MAX_TOTAL_BYTES
+            );
+            break;
+        }
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let path: Vec<_> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        total_bytes += size_bytes;
+        files.push(ArtifactFile {
+            path: path.join("/"),
+            size_bytes,
+        });
+    }
+    Ok(files)
+}
+
+pub struct ArtifactService;
+
+impl ArtifactService {
+    /// Copy the files under `root` matching `patterns` into the store and record them for
+    /// the process
+    pub async fn collect(
+        pool: &SqlitePool,
+        store: &ObjectStore,
+        execution_process_id: Uuid,
+        root: PathBuf,
+        patterns: Vec<String>,
+    ) -> Result<Vec<ExecutionProcessArtifact>, ArtifactError> {
+        if patterns.is_empty() || !root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let files = tokio::task::spawn_blocking({
+            let root = root.clone();
+            move || matching_files(&root, &patterns)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        let mut artifacts = Vec::with_capacity(files.len());
+        for file in files {
+            let key = format!("{execution_process_id}/{}", file.path);
+            let location = store.put_file(&key, &root.join(&file.path)).await?;
+            let artifact = ExecutionProcessArtifact::create(
+                pool,
+                execution_process_id,
+                &file.path,
+                file.size_bytes as i64,
+                &location,
+            )
+            .await?;
+            artifacts.push(artifact);
+        }
+        Ok(artifacts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn paths(root: &Path, patterns: &[&str]) -> Vec<String> {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        matching_files(root, &patterns)
+            .unwrap()
+            .into_iter()
+            .map(|file| file.path)
+            .collect()
+    }
+
+    #[test]
+    fn ignored_build_outputs_are_collected() {
+        let td = TempDir::new().unwrap();
+        write(td.path(), ".gitignore", "coverage/\ndist/\n");
+        write(td.path(), "coverage/lcov.info", "TN:");
+        write(td.path(), "coverage/html/index.html", "<html>");
+        write(td.path(), "dist/app.js", "console.log(1)");
+        write(td.path(), "src/main.ts", "");
+
+        assert_eq!(
+            paths(td.path(), &["coverage/**", "dist/*.js"]),
+            [
+                "coverage/html/index.html",
+                "coverage/lcov.info",
+                "dist/app.js"
+            ]
+        );
+    }
+
+    #[test]
+    fn negated_patterns_exclude_and_git_is_skipped() {
+        let td = TempDir::new().unwrap();
+        write(td.path(), "reports/junit.xml", "<testsuite/>");
+        write(td.path(), "reports/tmp/scratch.xml", "");
+        write(td.path(), ".git/config", "");
+
+        assert_eq!(
+            paths(td.path(), &["**/*.xml", "!reports/tmp/**", "**/config"]),
+            ["reports/junit.xml"]
+        );
+    }
+
+    #[test]
+    fn sizes_are_recorded() {
+        let td = TempDir::new().unwrap();
+        write(td.path(), "out.txt", "hello");
+        let files = matching_files(td.path(), &["*.txt".to_string()]).unwrap();
+        assert_eq!(
+            files,
+            [ArtifactFile {
+                path: "out.txt".to_string(),
+                size_bytes: 5,
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let td = TempDir::new().unwrap();
+        assert!(matching_files(td.path(), &["a[".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Per-repo git credentials, stored encrypted.
+//!
+//! Secrets are sealed with the server master key and registered with the git crate so that
+//! fetches and pushes of the repo use them instead of whatever credentials the server process
+//! has ambiently.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    repo::Repo,
+    repo_credential::{RepoCredential, RepoCredentialKind},
+};
+use git::{GitCredential, set_repo_credential};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::master_key::{CipherError, MasterKey};
+
+/// Username sent with a token when none is given; accepted by GitHub and ignored by most hosts
+const DEFAULT_TOKEN_USERNAME: &str = "x-access-token";
+
+#[derive(Debug, Error)]
+pub enum GitCredentialError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Invalid credential: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Cipher(#[from] CipherError),
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum SetRepoCredential {
+    HttpsToken {
+        username: Option<String>,
+        token: String,
+    },
+    SshKey {
+        /// Path to the private key on the server
+        path: String,
+    },
+}
+
+/// What clients see of a stored credential; tokens are never returned
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RepoCredentialSummary {
+    pub repo_id: Uuid,
+    pub kind: RepoCredentialKind,
+    pub username: Option<String>,
+    pub ssh_key_path: Option<String>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+pub struct GitCredentialService {
+    key: MasterKey,
+}
+
+impl GitCredentialService {
+    pub fn new() -> Result<Self, GitCredentialError> {
+        Ok(Self {
+            key: MasterKey::load()?,
+        })
+    }
+
+    fn to_git_credential(
+        &self,
+        stored: &RepoCredential,
+    ) -> Result<GitCredential, GitCredentialError> {
+        let secret = self.key.open(&stored.secret)?;
+        Ok(match stored.kind {
+            RepoCredentialKind::HttpsToken => GitCredential::HttpsToken {
+                username: stored
+                    .username
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_TOKEN_USERNAME.to_string()),
+                token: secret,
+            },
+            RepoCredentialKind::SshKey => GitCredential::SshKey {
+                path: PathBuf::from(secret),
+            },
+        })
+    }
+
+    fn summarize(
+        &self,
+        stored: &RepoCredential,
+    ) -> Result<RepoCredentialSummary, GitCredentialError> {
+        let ssh_key_path = match stored.kind {
+            RepoCredentialKind::SshKey => Some(self.key.open(&stored.secret)?),
+            RepoCredentialKind::HttpsToken => None,
+        };
+        Ok(RepoCredentialSummary {
+            repo_id: stored.repo_id,
+            kind: stored.kind,
+            username: stored.username.clone(),
+            ssh_key_path,
+            updated_at: stored.updated_at,
+        })
+    }
+
+    /// Register every stored credential with git; run at startup. Returns how many were loaded.
+    pub async fn register_all(&self, pool: &SqlitePool) -> Result<usize, GitCredentialError> {
+        let mut registered = 0;
+        for stored in RepoCredential::find_all(pool).await? {
+            let Some(repo) = Repo::find_by_id(pool, stored.repo_id).await? else {
+                continue;
+            };
+            match self.to_git_credential(&stored) {
+                Ok(credential) => {
+                    set_repo_credential(&repo.path, Some(credential));
+                    registered += 1;
+                }
+                Err(e) => tracing::warn!("Skipping git credential for repo {}: {}", repo.name, e),
+            }
+        }
+        Ok(registered)
+    }
+
+    pub async fn get(
+        &self,
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Option<RepoCredentialSummary>, GitCredentialError> {
+        RepoCredential::find_by_repo_id(pool, repo_id)
+            .await?
+            .map(|stored| self.summarize(&stored))
+            .transpose()
+    }
+
+    pub async fn set(
+        &self,
+        pool: &SqlitePool,
+        repo: &Repo,
+        request: &SetRepoCredential,
+    ) -> Result<RepoCredentialSummary, GitCredentialError> {
+        let (kind, username, secret) = match request {
+            SetRepoCredential::HttpsToken { username, token } => {
+                let token = token.trim();
+                if token.is_empty() {
+                    return Err(GitCredentialError::Invalid("Token is required".to_string()));
+                }
+                let username = username.as_deref().map(str::trim).filter(|u| !u.is_empty());
+                (RepoCredentialKind::HttpsToken, username, token.to_string())
+            }
+            SetRepoCredential::SshKey { path } => {
+                let path = utils::path::expand_tilde(path.trim());
+                if !path.is_file() {
+                    return Err(GitCredentialError::Invalid(format!(
+                        "SSH key not found: {}",
+                        path.display()
+                    )));
+                }
+                (
+                    RepoCredentialKind::SshKey,
+                    None,
+                    path.to_string_lossy().to_string(),
+                )
+            }
+        };
+
+        let stored =
+            RepoCredential::upsert(pool, repo.id, kind, username, &self.key.seal(&secret)?).await?;
+        set_repo_credential(&repo.path, Some(self.to_git_credential(&stored)?));
+        self.summarize(&stored)
+    }
+
+    /// Forget the repo's credential so git goes back to ambient credentials
+    pub async fn clear(&self, pool: &SqlitePool, repo: &Repo) -> Result<bool, GitCredentialError> {
+        let removed = RepoCredential::delete(pool, repo.id).await? > 0;
+        set_repo_credential(&repo.path, None);
+        Ok(removed)
+    }
+}
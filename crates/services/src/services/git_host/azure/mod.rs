@@ -14,7 +14,9 @@ use tracing::info;
 
 use super::{
     GitHostProvider,
-    types::{CreatePrRequest, GitHostError, OpenPrInfo, ProviderKind, UnifiedPrComment},
+    types::{
+        CommitCheck, CreatePrRequest, GitHostError, OpenPrInfo, ProviderKind, UnifiedPrComment,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -255,6 +257,16 @@ impl GitHostProvider for AzureDevOpsProvider {
         Err(GitHostError::UnsupportedProvider)
     }
 
+    /// Azure DevOps build statuses are not read, so repos hosted there skip the CI check
+    async fn get_commit_checks(
+        &self,
+        _repo_path: &Path,
+        _remote_url: &str,
+        _commit_sha: &str,
+    ) -> Result<Vec<CommitCheck>, GitHostError> {
+        Err(GitHostError::UnsupportedProvider)
+    }
+
     fn provider_kind(&self) -> ProviderKind {
         ProviderKind::AzureDevOps
     }
@@ -18,7 +18,8 @@ use thiserror::Error;
 use utils::shell::resolve_executable_path_blocking;
 
 use crate::services::git_host::types::{
-    CreatePrRequest, OpenPrInfo, PrComment, PrCommentAuthor, PrReviewComment, ReviewCommentUser,
+    CommitCheck, CommitCheckStatus, CreatePrRequest, OpenPrInfo, PrComment, PrCommentAuthor,
+    PrReviewComment, ReviewCommentUser,
 };
 
 #[derive(Debug, Clone)]
@@ -108,6 +109,35 @@ struct GhPrListExtendedResponse {
     base_ref_name: String,
 }
 
+#[derive(Deserialize)]
+struct GhCheckRunsResponse {
+    #[serde(default)]
+    check_runs: Vec<GhCheckRun>,
+}
+
+#[derive(Deserialize)]
+struct GhCheckRun {
+    name: String,
+    #[serde(default)]
+    status: String,
+    conclusion: Option<String>,
+    html_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GhCombinedStatusResponse {
+    #[serde(default)]
+    statuses: Vec<GhCommitStatus>,
+}
+
+#[derive(Deserialize)]
+struct GhCommitStatus {
+    context: String,
+    #[serde(default)]
+    state: String,
+    target_url: Option<String>,
+}
+
 #[derive(Debug, Error)]
 pub enum GhCliError {
     #[error("GitHub CLI (`gh`) executable not found or not runnable")]
@@ -338,6 +368,33 @@ impl GhCli {
         Self::parse_pr_review_comments(&raw)
     }
 
+    /// Fetch check runs and legacy commit statuses for a commit via API.
+    pub fn get_commit_checks(
+        &self,
+        owner: &str,
+        repo: &str,
+        commit_sha: &str,
+    ) -> Result<Vec<CommitCheck>, GhCliError> {
+        let check_runs = self.run(
+            [
+                "api",
+                &format!("repos/{owner}/{repo}/commits/{commit_sha}/check-runs?per_page=100"),
+            ],
+            None,
+        )?;
+        let statuses = self.run(
+            [
+                "api",
+                &format!("repos/{owner}/{repo}/commits/{commit_sha}/status?per_page=100"),
+            ],
+            None,
+        )?;
+
+        let mut checks = Self::parse_check_runs(&check_runs)?;
+        checks.extend(Self::parse_commit_statuses(&statuses)?);
+        Ok(checks)
+    }
+
     pub fn pr_checkout(
         &self,
         repo_path: &Path,
@@ -513,4 +570,132 @@ impl GhCli {
             })
             .collect())
     }
+
+    fn parse_check_runs(raw: &str) -> Result<Vec<CommitCheck>, GhCliError> {
+        let resp: GhCheckRunsResponse = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse check runs API response: {err}; raw: {raw}"
+            ))
+        })?;
+
+        Ok(resp
+            .check_runs
+            .into_iter()
+            .map(|run| {
+                let status = if !run.status.eq_ignore_ascii_case("completed") {
+                    CommitCheckStatus::Pending
+                } else {
+                    match run
+                        .conclusion
+                        .as_deref()
+                        .unwrap_or_default()
+                        .to_ascii_lowercase()
+                        .as_str()
+                    {
+                        "success" => CommitCheckStatus::Success,
+                        "neutral" | "skipped" => CommitCheckStatus::Neutral,
+                        _ => CommitCheckStatus::Failure,
+                    }
+                };
+                CommitCheck {
+                    name: run.name,
+                    status,
+                    url: run.html_url,
+                }
+            })
+            .collect())
+    }
+
+    fn parse_commit_statuses(raw: &str) -> Result<Vec<CommitCheck>, GhCliError> {
+        let resp: GhCombinedStatusResponse = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse commit status API response: {err}; raw: {raw}"
+            ))
+        })?;
+
+        Ok(resp
+            .statuses
+            .into_iter()
+            .map(|status| CommitCheck {
+                name: status.context,
+                status: match status.state.to_ascii_lowercase().as_str() {
+                    "success" => CommitCheckStatus::Success,
+                    "pending" => CommitCheckStatus::Pending,
+                    _ => CommitCheckStatus::Failure,
+                },
+                url: status.target_url,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(checks: &[CommitCheck]) -> Vec<(&str, CommitCheckStatus)> {
+        checks.iter().map(|c| (c.name.as_str(), c.status)).collect()
+    }
+
+    #[test]
+    fn check_runs_are_pending_until_completed() {
+        let raw = r#"{"total_count": 5, "check_runs": [
+            {"name": "build", "status": "completed", "conclusion": "success", "html_url": "https://github.com/o/r/runs/1"},
+            {"name": "test", "status": "in_progress", "conclusion": null},
+            {"name": "lint", "status": "completed", "conclusion": "failure"},
+            {"name": "docs", "status": "completed", "conclusion": "skipped"},
+            {"name": "deploy", "status": "completed", "conclusion": "timed_out"}
+        ]}"#;
+
+        let checks = GhCli::parse_check_runs(raw).unwrap();
+
+        assert_eq!(
+            statuses(&checks),
+            vec![
+                ("build", CommitCheckStatus::Success),
+                ("test", CommitCheckStatus::Pending),
+                ("lint", CommitCheckStatus::Failure),
+                ("docs", CommitCheckStatus::Neutral),
+                ("deploy", CommitCheckStatus::Failure),
+            ]
+        );
+        assert_eq!(
+            checks[0].url.as_deref(),
+            Some("https://github.com/o/r/runs/1")
+        );
+    }
+
+    #[test]
+    fn commit_statuses_map_their_state() {
+        let raw = r#"{"state": "pending", "statuses": [
+            {"context": "ci/build", "state": "success", "target_url": null},
+            {"context": "ci/test", "state": "pending"},
+            {"context": "ci/lint", "state": "error"},
+            {"context": "ci/e2e", "state": "failure"}
+        ]}"#;
+
+        let checks = GhCli::parse_commit_statuses(raw).unwrap();
+
+        assert_eq!(
+            statuses(&checks),
+            vec![
+                ("ci/build", CommitCheckStatus::Success),
+                ("ci/test", CommitCheckStatus::Pending),
+                ("ci/lint", CommitCheckStatus::Failure),
+                ("ci/e2e", CommitCheckStatus::Failure),
+            ]
+        );
+    }
+
+    #[test]
+    fn unexpected_check_output_is_an_error() {
+        assert!(matches!(
+            GhCli::parse_check_runs("Not Found"),
+            Err(GhCliError::UnexpectedOutput(_))
+        ));
+        assert!(matches!(
+            GhCli::parse_commit_statuses(""),
+            Err(GhCliError::UnexpectedOutput(_))
+        ));
+    }
 }
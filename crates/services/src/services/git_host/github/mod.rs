@@ -14,7 +14,9 @@ use tracing::info;
 
 use super::{
     GitHostProvider,
-    types::{CreatePrRequest, GitHostError, OpenPrInfo, ProviderKind, UnifiedPrComment},
+    types::{
+        CommitCheck, CreatePrRequest, GitHostError, OpenPrInfo, ProviderKind, UnifiedPrComment,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -400,6 +402,51 @@ impl GitHostProvider for GitHubProvider {
         .await
     }
 
+    async fn get_commit_checks(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        commit_sha: &str,
+    ) -> Result<Vec<CommitCheck>, GitHostError> {
+        let repo_info = self.get_repo_info(remote_url, repo_path).await?;
+
+        let cli = self.gh_cli.clone();
+        let sha = commit_sha.to_string();
+
+        (|| async {
+            let cli = cli.clone();
+            let owner = repo_info.owner.clone();
+            let repo_name = repo_info.repo_name.clone();
+            let sha = sha.clone();
+
+            let checks =
+                task::spawn_blocking(move || cli.get_commit_checks(&owner, &repo_name, &sha))
+                    .await
+                    .map_err(|err| {
+                        GitHostError::Repository(format!(
+                            "Failed to execute GitHub CLI for fetching commit checks: {err}"
+                        ))
+                    })?;
+            checks.map_err(GitHostError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHostError| e.should_retry())
+        .notify(|err: &GitHostError, dur: Duration| {
+            tracing::warn!(
+                "GitHub API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
     fn provider_kind(&self) -> ProviderKind {
         ProviderKind::GitHub
     }
@@ -11,8 +11,9 @@ use db::models::merge::PullRequestInfo;
 use detection::detect_provider_from_url;
 use enum_dispatch::enum_dispatch;
 pub use types::{
-    CreatePrRequest, GitHostError, OpenPrInfo, PrComment, PrCommentAuthor, PrReviewComment,
-    ProviderKind, ReviewCommentUser, UnifiedPrComment,
+    CommitCheck, CommitCheckStatus, CreatePrRequest, GitHostError, OpenPrInfo, PrComment,
+    PrCommentAuthor, PrReviewComment, ProviderKind, ReviewCommentUser, UnifiedPrComment,
+    blocking_checks,
 };
 
 use self::{azure::AzureDevOpsProvider, github::GitHubProvider};
@@ -49,6 +50,14 @@ pub trait GitHostProvider: Send + Sync {
         remote_url: &str,
     ) -> Result<Vec<OpenPrInfo>, GitHostError>;
 
+    /// CI checks and commit statuses reported for `commit_sha` on the remote
+    async fn get_commit_checks(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        commit_sha: &str,
+    ) -> Result<Vec<CommitCheck>, GitHostError>;
+
     fn provider_kind(&self) -> ProviderKind;
 }
 
@@ -143,3 +143,74 @@ pub struct OpenPrInfo {
     pub head_branch: String,
     pub base_branch: String,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitCheckStatus {
+    Pending,
+    Success,
+    Failure,
+    Neutral,
+}
+
+/// A single CI check (check run or commit status) reported for a commit
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct CommitCheck {
+    pub name: String,
+    pub status: CommitCheckStatus,
+    pub url: Option<String>,
+}
+
+impl CommitCheck {
+    /// Whether this check should block a merge
+    pub fn is_blocking(&self) -> bool {
+        !matches!(
+            self.status,
+            CommitCheckStatus::Success | CommitCheckStatus::Neutral
+        )
+    }
+}
+
+/// Names of the checks that keep a commit from merging, or `None` if its CI passed. A commit
+/// with no checks reported has not passed: CI may not have started on it yet.
+pub fn blocking_checks(checks: &[CommitCheck]) -> Option<Vec<String>> {
+    let blocking: Vec<String> = checks
+        .iter()
+        .filter(|c| c.is_blocking())
+        .map(|c| c.name.clone())
+        .collect();
+    (checks.is_empty() || !blocking.is_empty()).then_some(blocking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: &str, status: CommitCheckStatus) -> CommitCheck {
+        CommitCheck {
+            name: name.to_string(),
+            status,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn ci_passes_only_when_every_check_succeeded_or_was_neutral() {
+        assert_eq!(
+            blocking_checks(&[
+                check("build", CommitCheckStatus::Success),
+                check("docs", CommitCheckStatus::Neutral),
+            ]),
+            None
+        );
+        assert_eq!(
+            blocking_checks(&[
+                check("build", CommitCheckStatus::Success),
+                check("lint", CommitCheckStatus::Failure),
+                check("e2e", CommitCheckStatus::Pending),
+            ]),
+            Some(vec!["lint".to_string(), "e2e".to_string()])
+        );
+        assert_eq!(blocking_checks(&[]), Some(Vec::new()));
+    }
+}
@@ -0,0 +1,236 @@
+//! Retention for stored execution process output.
+//!
+//! Process output is stored in SQLite as it streams, so the database grows with every run.
+//! A background job compacts the output of processes that finished more than the configured
+//! number of days ago using the configured policy, and records the compaction so each
+//! process is handled once. SQLite reuses the freed pages for new output.
+
+use chrono::{Duration, Utc};
+use db::models::execution_process_logs::{
+    ExecutionProcessLogRecordSize, ExecutionProcessLogs, LogCompaction,
+};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use super::{
+    object_storage::{ObjectStorageError, ObjectStore},
+    settings::{LogRetentionPolicy, Settings},
+};
+
+const BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Error)]
+pub enum LogRetentionError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Storage(#[from] ObjectStorageError),
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct LogRetentionSummary {
+    pub processes: usize,
+    pub removed_bytes: i64,
+}
+
+/// Records a policy keeps at each end of the output and what it removes
+#[derive(Debug, PartialEq)]
+struct CompactionPlan {
+    keep_through: Option<i64>,
+    keep_from: Option<i64>,
+    removed_records: usize,
+    removed_bytes: i64,
+}
+
+/// Keep `keep` records at each end; None when the output is no longer than that
+fn head_tail_plan(
+    records: &[ExecutionProcessLogRecordSize],
+    keep: usize,
+) -> Option<CompactionPlan> {
+    if records.len() <= keep * 2 {
+        return None;
+    }
+    let removed = &records[keep..records.len() - keep];
+    Some(CompactionPlan {
+        keep_through: keep.checked_sub(1).map(|i| records[i].cursor),
+        keep_from: (keep > 0).then(|| records[records.len() - keep].cursor),
+        removed_records: removed.len(),
+        removed_bytes: removed.iter().map(|r| r.byte_size).sum(),
+    })
+}
+
+/// JSONL record shown to readers of the logs in place of the removed output
+fn marker_line(note: &str) -> Result<String, serde_json::Error> {
+    let msg = LogMsg::Stderr(format!("[{note}]\n"));
+    Ok(format!("{}\n", serde_json::to_string(&msg)?))
+}
+
+pub struct LogRetentionService;
+
+impl LogRetentionService {
+    /// Compact the output of every process past the retention period
+    pub async fn run(
+        pool: &SqlitePool,
+        settings: &Settings,
+    ) -> Result<LogRetentionSummary, LogRetentionError> {
+        let mut summary = LogRetentionSummary::default();
+        let Some(days) = settings.log_retention_days else {
+            return Ok(summary);
+        };
+        let cutoff = Utc::now() - Duration::days(days.into());
+
+        loop {
+            let ids = ExecutionProcessLogs::find_ids_to_compact(pool, cutoff, BATCH_SIZE).await?;
+            if ids.is_empty() {
+                break;
+            }
+            for id in ids {
+                summary.removed_bytes += Self::compact_process(pool, settings, id).await?;
+                summary.processes += 1;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Compact one process's output and return the bytes removed
+    async fn compact_process(
+        pool: &SqlitePool,
+        settings: &Settings,
+        execution_id: Uuid,
+    ) -> Result<i64, LogRetentionError> {
+        let records = ExecutionProcessLogs::find_record_sizes(pool, execution_id).await?;
+        let total_bytes: i64 = records.iter().map(|r| r.byte_size).sum();
+        let policy = settings.log_retention_policy;
+
+        let everything = CompactionPlan {
+            keep_through: None,
+            keep_from: None,
+            removed_records: records.len(),
+            removed_bytes: total_bytes,
+        };
+
+        let (plan, note, archive_path) = match policy {
+            LogRetentionPolicy::Truncate => (
+                everything,
+                Some("Output removed by the log retention policy".to_string()),
+                None,
+            ),
+            LogRetentionPolicy::HeadTail => {
+                let keep = settings.log_retention_keep_entries as usize;
+                match head_tail_plan(&records, keep) {
+                    Some(plan) => {
+                        let note = format!(
+                            "{} log entries removed by the log retention policy",
+                            plan.removed_records
+                        );
+                        (plan, Some(note), None)
+                    }
+                    // Short enough to keep in full; recorded so it is not checked again
+                    None => (
+                        CompactionPlan {
+                            keep_through: records.last().map(|r| r.cursor),
+                            keep_from: None,
+                            removed_records: 0,
+                            removed_bytes: 0,
+                        },
+                        None,
+                        None,
+                    ),
+                }
+            }
+            LogRetentionPolicy::Archive => {
+                let path = Self::archive(pool, settings, execution_id).await?;
+                let note = format!("Output archived to {path} by the log retention policy");
+                (everything, Some(note), Some(path))
+            }
+        };
+        let marker = note.as_deref().map(marker_line).transpose()?;
+
+        ExecutionProcessLogs::compact(
+            pool,
+            execution_id,
+            &LogCompaction {
+                keep_through: plan.keep_through,
+                keep_from: plan.keep_from,
+                marker: marker.as_deref(),
+                policy: policy.as_str(),
+                removed_bytes: plan.removed_bytes,
+                archive_path: archive_path.as_deref(),
+            },
+        )
+        .await?;
+        Ok(plan.removed_bytes)
+    }
+
+    /// Write the process's output to `<execution id>.jsonl` in the log archive store
+    async fn archive(
+        pool: &SqlitePool,
+        settings: &Settings,
+        execution_id: Uuid,
+    ) -> Result<String, LogRetentionError> {
+        let store = ObjectStore::log_archives(settings)?.ok_or_else(|| {
+            std::io::Error::other("No log archive directory or object storage is configured")
+        })?;
+
+        let records = ExecutionProcessLogs::find_by_execution_id(pool, execution_id).await?;
+        let contents: String = records.iter().map(|record| record.logs.as_str()).collect();
+        let location = store
+            .put_bytes(&format!("{execution_id}.jsonl"), contents.into_bytes())
+            .await?;
+        Ok(location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(sizes: &[i64]) -> Vec<ExecutionProcessLogRecordSize> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &byte_size)| ExecutionProcessLogRecordSize {
+                cursor: i as i64 + 10,
+                byte_size,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn head_tail_keeps_both_ends() {
+        let records = records(&[1, 2, 4, 8, 16, 32]);
+        assert_eq!(
+            head_tail_plan(&records, 2),
+            Some(CompactionPlan {
+                keep_through: Some(11),
+                keep_from: Some(14),
+                removed_records: 2,
+                removed_bytes: 12,
+            })
+        );
+        assert_eq!(head_tail_plan(&records, 3), None);
+        assert_eq!(
+            head_tail_plan(&records, 0),
+            Some(CompactionPlan {
+                keep_through: None,
+                keep_from: None,
+                removed_records: 6,
+                removed_bytes: 63,
+            })
+        );
+    }
+
+    #[test]
+    fn marker_is_a_stored_stderr_record() {
+        let line = marker_line("Output removed").unwrap();
+        assert!(line.ends_with('\n'));
+        let msg: LogMsg = serde_json::from_str(line.trim_end()).unwrap();
+        assert!(matches!(msg, LogMsg::Stderr(s) if s == "[Output removed]\n"));
+    }
+}
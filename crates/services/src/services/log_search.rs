@@ -0,0 +1,212 @@
+//! Literal search of stored execution process output.
+//!
+//! Candidate processes are found in SQL by looking for the query, JSON-escaped as it is
+//! stored, in the raw log records. Their stdout and stderr are then decoded and searched line
+//! by line, and each matching line is returned with the lines around it.
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::ExecutionProcessRunReason,
+    execution_process_logs::{ExecutionProcessLogHit, ExecutionProcessLogs},
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+pub const DEFAULT_CONTEXT_LINES: usize = 2;
+pub const MAX_CONTEXT_LINES: usize = 10;
+const MAX_PROCESSES: i64 = 50;
+const MAX_MATCHES_PER_PROCESS: usize = 20;
+/// Longer lines are clipped around the match so agent JSON output stays readable
+const MAX_LINE_LENGTH: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct LogSearchMatch {
+    /// 1-based line of the process output
+    pub line_number: usize,
+    pub line: String,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogSearchResult {
+    pub execution_process_id: Uuid,
+    pub session_id: Uuid,
+    pub workspace_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    pub matches: Vec<LogSearchMatch>,
+    /// More lines matched than are returned for the process
+    pub truncated: bool,
+}
+
+/// The query as it appears inside a JSON string in the stored records
+fn stored_needle(query: &str) -> String {
+    let encoded = serde_json::Value::String(query.to_string()).to_string();
+    encoded[1..encoded.len() - 1].to_string()
+}
+
+/// Stdout and stderr of the process in the order they were written
+fn output_text(messages: &[LogMsg]) -> String {
+    messages
+        .iter()
+        .filter_map(|msg| match msg {
+            LogMsg::Stdout(s) | LogMsg::Stderr(s) => Some(s.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Byte index at or before `index` that falls on a char boundary
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// At most `MAX_LINE_LENGTH` bytes of the line, starting shortly before `at`
+fn clip_line(line: &str, at: usize) -> String {
+    if line.len() <= MAX_LINE_LENGTH {
+        return line.to_string();
+    }
+    let start = floor_char_boundary(line, at.saturating_sub(MAX_LINE_LENGTH / 4));
+    let end = floor_char_boundary(line, start + MAX_LINE_LENGTH);
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < line.len() { "…" } else { "" };
+    format!("{prefix}{}{suffix}", &line[start..end])
+}
+
+/// Lines containing `query` with `context` lines on each side; true when matches were cut off
+fn find_matches(text: &str, query: &str, context: usize) -> (Vec<LogSearchMatch>, bool) {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut matches = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(at) = line.find(query) else {
+            continue;
+        };
+        if matches.len() == MAX_MATCHES_PER_PROCESS {
+            return (matches, true);
+        }
+        let before = &lines[index.saturating_sub(context)..index];
+        let after = &lines[index + 1..(index + 1 + context).min(lines.len())];
+        matches.push(LogSearchMatch {
+            line_number: index + 1,
+            line: clip_line(line, at),
+            context_before: before.iter().map(|l| clip_line(l, 0)).collect(),
+            context_after: after.iter().map(|l| clip_line(l, 0)).collect(),
+        });
+    }
+    (matches, false)
+}
+
+pub struct LogSearchService;
+
+impl LogSearchService {
+    /// Search the output of the newest processes of a workspace and/or project
+    pub async fn search(
+        pool: &SqlitePool,
+        workspace_id: Option<Uuid>,
+        project_id: Option<Uuid>,
+        query: &str,
+        context: usize,
+    ) -> Result<Vec<LogSearchResult>, sqlx::Error> {
+        let context = context.min(MAX_CONTEXT_LINES);
+        let hits = ExecutionProcessLogs::find_processes_containing(
+            pool,
+            workspace_id,
+            project_id,
+            &stored_needle(query),
+            MAX_PROCESSES,
+        )
+        .await?;
+
+        let mut results = Vec::new();
+        for hit in hits {
+            let records =
+                ExecutionProcessLogs::find_by_execution_id(pool, hit.execution_id).await?;
+            let messages = match ExecutionProcessLogs::parse_logs(&records) {
+                Ok(messages) => messages,
+                Err(e) => {
+                    tracing::warn!("Skipping logs of execution {}: {}", hit.execution_id, e);
+                    continue;
+                }
+            };
+            let (matches, truncated) = find_matches(&output_text(&messages), query, context);
+            if !matches.is_empty() {
+                results.push(Self::result(hit, matches, truncated));
+            }
+        }
+        Ok(results)
+    }
+
+    fn result(
+        hit: ExecutionProcessLogHit,
+        matches: Vec<LogSearchMatch>,
+        truncated: bool,
+    ) -> LogSearchResult {
+        LogSearchResult {
+            execution_process_id: hit.execution_id,
+            session_id: hit.session_id,
+            workspace_id: hit.workspace_id,
+            run_reason: hit.run_reason,
+            created_at: hit.created_at,
+            matches,
+            truncated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_carry_surrounding_lines() {
+        let messages = [
+            LogMsg::Stdout("Compiling app\nerror[E0308]: mismatched ".to_string()),
+            LogMsg::Stderr("types\n  --> src/main.rs:4:5\n".to_string()),
+            LogMsg::SessionId("ignored".to_string()),
+            LogMsg::Stdout("done\n".to_string()),
+        ];
+        let (matches, truncated) = find_matches(&output_text(&messages), "error[E0308]", 1);
+        assert!(!truncated);
+        assert_eq!(
+            matches,
+            vec![LogSearchMatch {
+                line_number: 2,
+                line: "error[E0308]: mismatched types".to_string(),
+                context_before: vec!["Compiling app".to_string()],
+                context_after: vec!["  --> src/main.rs:4:5".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn matches_per_process_are_capped() {
+        let text = "fail\n".repeat(MAX_MATCHES_PER_PROCESS + 1);
+        let (matches, truncated) = find_matches(&text, "fail", 0);
+        assert_eq!(matches.len(), MAX_MATCHES_PER_PROCESS);
+        assert!(truncated);
+    }
+
+    #[test]
+    fn needle_is_escaped_like_stored_json() {
+        assert_eq!(stored_needle("error[E0308]"), "error[E0308]");
+        assert_eq!(stored_needle(r#"expected "u32""#), r#"expected \"u32\""#);
+    }
+
+    #[test]
+    fn long_lines_are_clipped_around_the_match() {
+        let line = format!("{}needle{}", "a".repeat(5000), "b".repeat(5000));
+        let clipped = clip_line(&line, 5000);
+        assert!(clipped.contains("needle"));
+        assert!(clipped.starts_with('…') && clipped.ends_with('…'));
+        assert!(clipped.len() <= MAX_LINE_LENGTH + 2 * '…'.len_utf8());
+    }
+}
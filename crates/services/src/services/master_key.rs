@@ -0,0 +1,128 @@
+//! Server master key for secrets stored in the database.
+//!
+//! Values are sealed with AES-256-GCM under a key kept in the asset directory, separate from
+//! the database, so a copy of the database alone does not reveal them.
+
+use std::path::Path;
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use thiserror::Error;
+use utils::assets::asset_dir;
+
+const KEY_FILE: &str = "master.key";
+const NONCE_SIZE: usize = 12;
+
+#[derive(Debug, Error)]
+#[error("Secret could not be encrypted or decrypted")]
+pub struct CipherError;
+
+pub struct MasterKey([u8; 32]);
+
+impl MasterKey {
+    /// Load the server's master key, creating it on first use
+    pub fn load() -> std::io::Result<Self> {
+        Self::load_from(&asset_dir())
+    }
+
+    /// Load the key kept in `dir`, creating it if there is none
+    fn load_from(dir: &Path) -> std::io::Result<Self> {
+        let path = dir.join(KEY_FILE);
+        match Self::read(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => match Self::create(&path) {
+                // Another caller created the key first
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Self::read(&path),
+                created => created,
+            },
+            loaded => loaded,
+        }
+    }
+
+    fn read(path: &Path) -> std::io::Result<Self> {
+        std::fs::read(path)?.try_into().map(Self).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Malformed key file: {}", path.display()),
+            )
+        })
+    }
+
+    fn create(path: &Path) -> std::io::Result<Self> {
+        use std::io::Write as _;
+
+        let key: [u8; 32] = Aes256Gcm::generate_key(&mut OsRng).into();
+        let mut opts = std::fs::OpenOptions::new();
+        opts.create_new(true).write(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
+        }
+        let mut file = opts.open(path)?;
+        file.write_all(&key)?;
+        file.sync_all()?;
+        Ok(Self(key))
+    }
+
+    /// Encrypt `plaintext`, returning the nonce and ciphertext base64-encoded
+    pub fn seal(&self, plaintext: &str) -> Result<String, CipherError> {
+        let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(self.0));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| CipherError)?;
+
+        let mut combined = nonce.to_vec();
+        combined.extend_from_slice(&ciphertext);
+        Ok(STANDARD.encode(combined))
+    }
+
+    pub fn open(&self, sealed: &str) -> Result<String, CipherError> {
+        let decoded = STANDARD.decode(sealed).map_err(|_| CipherError)?;
+        if decoded.len() < NONCE_SIZE {
+            return Err(CipherError);
+        }
+        let (nonce, ciphertext) = decoded.split_at(NONCE_SIZE);
+        let nonce: [u8; NONCE_SIZE] = nonce.try_into().map_err(|_| CipherError)?;
+
+        let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(self.0));
+        let plaintext = cipher
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| CipherError)?;
+        String::from_utf8(plaintext).map_err(|_| CipherError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_secrets_round_trip_and_reject_tampering() {
+        let key = MasterKey([7; 32]);
+        let sealed = key.seal("ghp_secret").unwrap();
+        assert!(!sealed.contains("ghp_secret"));
+        assert_eq!(key.open(&sealed).unwrap(), "ghp_secret");
+
+        let mut tampered = STANDARD.decode(&sealed).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(key.open(&STANDARD.encode(tampered)).is_err());
+
+        let other = MasterKey([8; 32]);
+        assert!(other.open(&sealed).is_err());
+    }
+
+    #[test]
+    fn creates_a_key_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let sealed = MasterKey::load_from(dir.path())
+            .unwrap()
+            .seal("value")
+            .unwrap();
+        let reloaded = MasterKey::load_from(dir.path()).unwrap();
+        assert_eq!(reloaded.open(&sealed).unwrap(), "value");
+    }
+}
@@ -0,0 +1,172 @@
+//! Descriptions of a workspace's changes for merge commits and pull requests.
+//!
+//! The description is put together from what is already known about the workspace rather
+//! than from a new agent run: the task title, the summary the coding agent gave of its
+//! last turn (falling back to the task description), the diff stats of each repo and the
+//! subjects of the workspace's commits.
+
+use std::fmt::Write;
+
+use git::CommitInfo;
+
+/// Commits listed per repo; the rest are counted
+const MAX_COMMITS: usize = 50;
+
+/// Changes of one repo of the workspace
+pub struct MergeDescriptionRepo<'a> {
+    pub repo_name: &'a str,
+    /// Commits on the workspace branch, newest first
+    pub commits: &'a [CommitInfo],
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{count} {}", if count == 1 { one } else { many })
+}
+
+pub struct MergeDescriptionService;
+
+impl MergeDescriptionService {
+    /// Title and markdown body describing the workspace's changes
+    pub fn generate(
+        task_title: &str,
+        task_description: Option<&str>,
+        agent_summary: Option<&str>,
+        repos: &[MergeDescriptionRepo],
+    ) -> (String, String) {
+        let mut body = String::new();
+        let overview = agent_summary
+            .or(task_description)
+            .map(str::trim)
+            .filter(|text| !text.is_empty());
+        if let Some(overview) = overview {
+            let _ = writeln!(body, "{overview}\n");
+        }
+
+        let changed: Vec<_> = repos.iter().filter(|repo| repo.files_changed > 0).collect();
+        if !changed.is_empty() {
+            body.push_str("## Changes\n\n");
+            for repo in &changed {
+                let _ = writeln!(
+                    body,
+                    "- {}{}, +{} -{}",
+                    if repos.len() > 1 {
+                        format!("**{}**: ", repo.repo_name)
+                    } else {
+                        String::new()
+                    },
+                    plural(repo.files_changed, "file changed", "files changed"),
+                    repo.additions,
+                    repo.deletions
+                );
+            }
+            body.push('\n');
+        }
+
+        if repos.iter().any(|repo| !repo.commits.is_empty()) {
+            body.push_str("## Commits\n\n");
+            for repo in repos.iter().filter(|repo| !repo.commits.is_empty()) {
+                if repos.len() > 1 {
+                    let _ = writeln!(body, "**{}**\n", repo.repo_name);
+                }
+                // Oldest first, in the order they were made
+                for commit in repo.commits.iter().rev().take(MAX_COMMITS) {
+                    let short_sha = commit.commit_sha.get(..7).unwrap_or(&commit.commit_sha);
+                    let _ = writeln!(body, "- `{short_sha}` {}", commit.summary.trim());
+                }
+                if repo.commits.len() > MAX_COMMITS {
+                    let _ = writeln!(
+                        body,
+                        "- and {}",
+                        plural(
+                            repo.commits.len() - MAX_COMMITS,
+                            "more commit",
+                            "more commits"
+                        )
+                    );
+                }
+                body.push('\n');
+            }
+        }
+
+        (task_title.trim().to_string(), body.trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn commit(sha: &str, summary: &str) -> CommitInfo {
+        CommitInfo {
+            commit_sha: sha.to_string(),
+            author: "dev".to_string(),
+            summary: summary.to_string(),
+            message: summary.to_string(),
+            committed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn single_repo_description_lists_stats_and_commits_oldest_first() {
+        let commits = [
+            commit("bbbbbbbbbbbb", "Add tests"),
+            commit("aaaaaaaaaaaa", "Add login form"),
+        ];
+        let (title, body) = MergeDescriptionService::generate(
+            "Add login ",
+            Some("Users sign in with email"),
+            Some("Added a login form with validation.\n"),
+            &[MergeDescriptionRepo {
+                repo_name: "web",
+                commits: &commits,
+                files_changed: 1,
+                additions: 40,
+                deletions: 2,
+            }],
+        );
+        assert_eq!(title, "Add login");
+        assert_eq!(
+            body,
+            "Added a login form with validation.\n\n\
+             ## Changes\n\n- 1 file changed, +40 -2\n\n\
+             ## Commits\n\n- `aaaaaaa` Add login form\n- `bbbbbbb` Add tests"
+        );
+    }
+
+    #[test]
+    fn repos_are_named_and_task_description_is_the_fallback() {
+        let commits = [commit("cccccccccccc", "Add endpoint")];
+        let (_, body) = MergeDescriptionService::generate(
+            "Add login",
+            Some("Users sign in with email"),
+            None,
+            &[
+                MergeDescriptionRepo {
+                    repo_name: "api",
+                    commits: &commits,
+                    files_changed: 3,
+                    additions: 10,
+                    deletions: 0,
+                },
+                MergeDescriptionRepo {
+                    repo_name: "docs",
+                    commits: &[],
+                    files_changed: 0,
+                    additions: 0,
+                    deletions: 0,
+                },
+            ],
+        );
+        assert_eq!(
+            body,
+            "Users sign in with email\n\n\
+             ## Changes\n\n- **api**: 3 files changed, +10 -0\n\n\
+             ## Commits\n\n**api**\n\n- `ccccccc` Add endpoint"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Per-repo queue that serializes merges into the main repository checkout.
+//!
+//! Workspace merges update the target branch in the shared repo rather than in a worktree,
+//! so two workspaces closing into the same repo at once would race; merging a target branch
+//! into a worktree reads the same branch while it may be moving. The merges of
+//! [`WorkspaceManager`](super::workspace_manager::WorkspaceManager) take a slot from
+//! [`MergeQueue::acquire`] and hold it for the duration of the merge.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use tokio::sync::OwnedMutexGuard;
+use tracing::debug;
+
+static MERGE_QUEUES: LazyLock<Mutex<HashMap<PathBuf, Arc<RepoQueue>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Default)]
+struct RepoQueue {
+    lock: Arc<tokio::sync::Mutex<()>>,
+    /// Merges currently running or waiting for this repo
+    depth: AtomicUsize,
+}
+
+/// Counts a merge towards the queue depth until dropped, including while it is still
+/// waiting, so a cancelled waiter does not leave the depth inflated.
+struct QueueEntry(Arc<RepoQueue>);
+
+impl Drop for QueueEntry {
+    fn drop(&mut self) {
+        self.0.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Exclusive right to merge into a repo; released when dropped
+pub struct MergeSlot {
+    _guard: OwnedMutexGuard<()>,
+    _entry: QueueEntry,
+    /// Number of merges into the same repo that were ahead of this one when it joined
+    pub position: usize,
+}
+
+pub struct MergeQueue;
+
+impl MergeQueue {
+    /// Wait for our turn to merge into the repo at `repo_path`. Merges are granted in the
+    /// order they were requested.
+    pub async fn acquire(repo_path: &Path) -> MergeSlot {
+        let queue = Self::queue_for(repo_path);
+        let position = queue.depth.fetch_add(1, Ordering::SeqCst);
+        let entry = QueueEntry(queue.clone());
+        if position > 0 {
+            debug!(
+                "Merge into '{}' queued behind {} other merge(s)",
+                repo_path.display(),
+                position
+            );
+        }
+
+        let guard = queue.lock.clone().lock_owned().await;
+        MergeSlot {
+            _guard: guard,
+            _entry: entry,
+            position,
+        }
+    }
+
+    /// Number of merges currently running or waiting for the repo at `repo_path`
+    pub fn depth(repo_path: &Path) -> usize {
+        MERGE_QUEUES
+            .lock()
+            .unwrap()
+            .get(repo_path)
+            .map(|q| q.depth.load(Ordering::SeqCst))
+            .unwrap_or(0)
+    }
+
+    fn queue_for(repo_path: &Path) -> Arc<RepoQueue> {
+        MERGE_QUEUES
+            .lock()
+            .unwrap()
+            .entry(repo_path.to_path_buf())
+            .or_default()
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn merges_into_same_repo_are_serialized() {
+        let repo = PathBuf::from("/tmp/merge-queue-test-repo");
+        let first = MergeQueue::acquire(&repo).await;
+        assert_eq!(first.position, 0);
+
+        let waiter = tokio::spawn({
+            let repo = repo.clone();
+            async move { MergeQueue::acquire(&repo).await.position }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+        assert_eq!(MergeQueue::depth(&repo), 2);
+
+        // Other repos are not blocked
+        let other = MergeQueue::acquire(Path::new("/tmp/merge-queue-test-other")).await;
+        assert_eq!(other.position, 0);
+
+        drop(first);
+        assert_eq!(waiter.await.unwrap(), 1);
+        assert_eq!(MergeQueue::depth(&repo), 0);
+    }
+}
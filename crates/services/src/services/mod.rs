@@ -1,25 +1,60 @@
+pub mod agent_pipeline;
+pub mod agent_plan;
+pub mod agent_resume;
+pub mod ai_review;
 pub mod analytics;
 pub mod approvals;
+pub mod auto_sync;
 pub mod auth;
 pub mod config;
+pub mod conflict_resolution;
 pub mod container;
+pub mod cost_report;
+pub mod diff_html;
 pub mod diff_stream;
+pub mod env_vars;
 pub mod events;
+pub mod execution_artifacts;
 pub mod file_ranker;
 pub mod file_search;
 pub mod filesystem;
 pub mod filesystem_watcher;
+pub mod git_credentials;
 pub mod git_host;
 pub mod image;
+pub mod log_retention;
+pub mod log_search;
+pub mod master_key;
+pub mod merge_description;
+pub mod merge_queue;
 pub mod notification;
 pub mod oauth_credentials;
+pub mod object_storage;
+pub mod orphan_processes;
+pub mod output_redaction;
 pub mod pr_monitor;
+pub mod process_input;
+pub mod process_reaper;
 pub mod project;
+pub mod project_stats;
 #[cfg(feature = "qa-mode")]
 pub mod qa_repos;
+pub mod quality_gate;
 pub mod queued_message;
 pub mod ralph;
+pub mod recorded_command;
 pub mod remote_client;
 pub mod repo;
+pub mod secrets;
+pub mod settings;
+pub mod target_drift;
+pub mod task_analytics;
+pub mod test_runner;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod workspace_archive;
+pub mod workspace_dir_lock;
+pub mod workspace_integrity;
+pub mod workspace_lock;
 pub mod workspace_manager;
 pub mod worktree_manager;
@@ -1,28 +1,69 @@
-use std::sync::{Arc, OnceLock};
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
-use tokio::sync::RwLock;
+use serde_json::json;
+use tokio::sync::{RwLock, watch};
 use utils;
 
-use crate::services::config::{Config, NotificationConfig, SoundFile};
+use crate::services::{
+    config::{Config, NotificationConfig, SoundFile},
+    settings::Settings,
+};
 
 /// Service for handling cross-platform notifications including sound alerts and push notifications
 #[derive(Debug, Clone)]
 pub struct NotificationService {
     config: Arc<RwLock<Config>>,
+    settings: watch::Receiver<Settings>,
+    client: reqwest::Client,
 }
 
 /// Cache for WSL root path from PowerShell
 static WSL_ROOT_PATH_CACHE: OnceLock<Option<String>> = OnceLock::new();
 
 impl NotificationService {
-    pub fn new(config: Arc<RwLock<Config>>) -> Self {
-        Self { config }
+    pub fn new(config: Arc<RwLock<Config>>, settings: watch::Receiver<Settings>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        Self {
+            config,
+            settings,
+            client,
+        }
     }
 
-    /// Send both sound and push notifications if enabled
+    /// Send sound and push notifications if enabled, and post to any configured webhooks
     pub async fn notify(&self, title: &str, message: &str) {
         let config = self.config.read().await.notifications.clone();
         Self::send_notification(&config, title, message).await;
+
+        let webhooks = self.settings.borrow().notification_webhooks.clone();
+        for url in webhooks {
+            self.send_webhook_notification(url, title, message);
+        }
+    }
+
+    /// POST the notification to a webhook; fire-and-forget like the other targets
+    fn send_webhook_notification(&self, url: String, title: &str, message: &str) {
+        let client = self.client.clone();
+        let payload = json!({ "title": title, "message": message });
+        tokio::spawn(async move {
+            match client.post(&url).json(&payload).send().await {
+                Ok(response) if !response.status().is_success() => {
+                    tracing::warn!(
+                        "Notification webhook {} returned {}",
+                        url,
+                        response.status()
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to send notification webhook {}: {}", url, e),
+            }
+        });
     }
 
     /// Internal method to send notifications with a given config
@@ -0,0 +1,254 @@
+//! Storage for large process outputs that should outlive their workspaces.
+//!
+//! Collected artifacts and archived logs are written to directories on the server by
+//! default. When an S3-compatible bucket is configured they are uploaded to it instead, so
+//! they do not fill the server's disk. A stored object is referred to by its location, a
+//! file path or an `s3://<bucket>/<key>` URL, which is what the database records.
+//! Credentials for the bucket are read from `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`
+//! rather than stored with the settings.
+
+use std::path::{Path, PathBuf};
+
+use aws_credential_types::Credentials;
+use aws_sdk_s3::{
+    Client,
+    config::{Builder as S3ConfigBuilder, Region},
+    primitives::ByteStream,
+};
+use thiserror::Error;
+use tokio::io::AsyncRead;
+
+use super::settings::{ObjectStorageSettings, Settings};
+
+const S3_SCHEME: &str = "s3://";
+
+#[derive(Debug, Error)]
+pub enum ObjectStorageError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(
+        "Object storage credentials are missing; set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY"
+    )]
+    MissingCredentials,
+    #[error("Object storage request failed: {0}")]
+    Request(String),
+    #[error("{0} is in object storage, which is not configured")]
+    NotConfigured(String),
+}
+
+#[derive(Clone)]
+pub struct S3Store {
+    client: Client,
+    bucket: String,
+    /// Prepended to every key, ending in `/`
+    prefix: String,
+}
+
+/// Where the objects of one kind are stored
+#[derive(Clone)]
+pub enum ObjectStore {
+    /// Files under a directory on the server
+    Filesystem(PathBuf),
+    S3(S3Store),
+}
+
+/// A stored object opened for reading
+pub struct StoredObject {
+    pub size: u64,
+    pub reader: Box<dyn AsyncRead + Send + Unpin>,
+}
+
+/// Key prefix of a kind of object: the configured prefix, then the kind
+fn key_prefix(prefix: Option<&str>, kind: &str) -> String {
+    let prefix = prefix.map(|p| p.trim_matches('/'));
+    match prefix.filter(|p| !p.is_empty()) {
+        Some(prefix) => format!("{prefix}/{kind}/"),
+        None => format!("{kind}/"),
+    }
+}
+
+/// Bucket and key of an `s3://` location
+fn parse_s3_location(location: &str) -> Option<(&str, &str)> {
+    location.strip_prefix(S3_SCHEME)?.split_once('/')
+}
+
+impl S3Store {
+    fn new(settings: &ObjectStorageSettings, kind: &str) -> Result<Self, ObjectStorageError> {
+        let (Ok(access_key_id), Ok(secret_access_key)) = (
+            std::env::var("AWS_ACCESS_KEY_ID"),
+            std::env::var("AWS_SECRET_ACCESS_KEY"),
+        ) else {
+            return Err(ObjectStorageError::MissingCredentials);
+        };
+        let credentials = Credentials::new(
+            access_key_id,
+            secret_access_key,
+            std::env::var("AWS_SESSION_TOKEN").ok(),
+            None,
+            "environment",
+        );
+
+        let mut config = S3ConfigBuilder::new()
+            .region(Region::new(settings.region.clone()))
+            .credentials_provider(credentials);
+        if let Some(endpoint) = &settings.endpoint {
+            config = config.endpoint_url(endpoint).force_path_style(true);
+        }
+
+        Ok(Self {
+            client: Client::from_conf(config.build()),
+            bucket: settings.bucket.clone(),
+            prefix: key_prefix(settings.prefix.as_deref(), kind),
+        })
+    }
+
+    async fn put(&self, key: &str, body: ByteStream) -> Result<String, ObjectStorageError> {
+        let key = format!("{}{key}", self.prefix);
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| ObjectStorageError::Request(e.to_string()))?;
+        Ok(format!("{S3_SCHEME}{}/{key}", self.bucket))
+    }
+}
+
+impl ObjectStore {
+    /// Store for collected execution artifacts
+    pub fn artifacts(settings: &Settings) -> Result<Self, ObjectStorageError> {
+        match &settings.object_storage {
+            Some(storage) => Ok(Self::S3(S3Store::new(storage, "artifacts")?)),
+            None => Ok(Self::Filesystem(settings.artifacts_path())),
+        }
+    }
+
+    /// Store for output archived by the log retention policy, if one is configured
+    pub fn log_archives(settings: &Settings) -> Result<Option<Self>, ObjectStorageError> {
+        match (&settings.object_storage, &settings.log_archive_dir) {
+            (Some(storage), _) => Ok(Some(Self::S3(S3Store::new(storage, "logs")?))),
+            (None, Some(dir)) => Ok(Some(Self::Filesystem(utils::path::expand_tilde(dir)))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Store a copy of the file at `source` under `key`, returning its location
+    pub async fn put_file(&self, key: &str, source: &Path) -> Result<String, ObjectStorageError> {
+        match self {
+            Self::Filesystem(root) => {
+                let target = Self::file_target(root, key).await?;
+                tokio::fs::copy(source, &target).await?;
+                Ok(target.to_string_lossy().into_owned())
+            }
+            Self::S3(store) => {
+                let body = ByteStream::from_path(source)
+                    .await
+                    .map_err(|e| ObjectStorageError::Request(e.to_string()))?;
+                store.put(key, body).await
+            }
+        }
+    }
+
+    /// Store `data` under `key`, returning its location
+    pub async fn put_bytes(&self, key: &str, data: Vec<u8>) -> Result<String, ObjectStorageError> {
+        match self {
+            Self::Filesystem(root) => {
+                let target = Self::file_target(root, key).await?;
+                tokio::fs::write(&target, data).await?;
+                Ok(target.to_string_lossy().into_owned())
+            }
+            Self::S3(store) => store.put(key, ByteStream::from(data)).await,
+        }
+    }
+
+    /// Open a stored object. File locations can be read whichever store is configured, so
+    /// objects stored before a bucket was set up stay readable.
+    pub async fn open(&self, location: &str) -> Result<StoredObject, ObjectStorageError> {
+        let Some((bucket, key)) = parse_s3_location(location) else {
+            let file = tokio::fs::File::open(location).await?;
+            let size = file.metadata().await?.len();
+            return Ok(StoredObject {
+                size,
+                reader: Box::new(file),
+            });
+        };
+        let Self::S3(store) = self else {
+            return Err(ObjectStorageError::NotConfigured(location.to_string()));
+        };
+        let output = store
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| ObjectStorageError::Request(e.to_string()))?;
+        Ok(StoredObject {
+            size: output.content_length().unwrap_or_default().max(0) as u64,
+            reader: Box::new(output.body.into_async_read()),
+        })
+    }
+
+    async fn file_target(root: &Path, key: &str) -> std::io::Result<PathBuf> {
+        let target = root.join(key);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+
+    #[test]
+    fn keys_are_namespaced_under_the_prefix() {
+        assert_eq!(key_prefix(None, "logs"), "logs/");
+        assert_eq!(key_prefix(Some(""), "logs"), "logs/");
+        assert_eq!(key_prefix(Some("/vk/prod/"), "logs"), "vk/prod/logs/");
+    }
+
+    #[test]
+    fn s3_locations_are_split_into_bucket_and_key() {
+        assert_eq!(
+            parse_s3_location("s3://outputs/vk/artifacts/1/dist/app.js"),
+            Some(("outputs", "vk/artifacts/1/dist/app.js"))
+        );
+        assert_eq!(parse_s3_location("/srv/artifacts/1/dist/app.js"), None);
+    }
+
+    #[tokio::test]
+    async fn filesystem_objects_round_trip() {
+        let td = TempDir::new().unwrap();
+        let store = ObjectStore::Filesystem(td.path().join("store"));
+        let location = store
+            .put_bytes("run/out.txt", b"hello".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            Path::new(&location),
+            td.path().join("store").join("run/out.txt")
+        );
+
+        let mut object = store.open(&location).await.unwrap();
+        let mut contents = String::new();
+        object.reader.read_to_string(&mut contents).await.unwrap();
+        assert_eq!((object.size, contents.as_str()), (5, "hello"));
+    }
+
+    #[tokio::test]
+    async fn bucket_objects_need_object_storage() {
+        let td = TempDir::new().unwrap();
+        let store = ObjectStore::Filesystem(td.path().to_path_buf());
+        assert!(matches!(
+            store.open("s3://outputs/logs/1.jsonl").await,
+            Err(ObjectStorageError::NotConfigured(_))
+        ));
+    }
+}
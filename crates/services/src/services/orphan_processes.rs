@@ -0,0 +1,100 @@
+//! Execution processes left behind by an earlier server run.
+//!
+//! Every spawned process leads its own process group, and its id is recorded when it starts.
+//! At startup a process still marked running has either died with the server or outlived it;
+//! either way its output can no longer be followed, so a surviving group is stopped.
+
+use std::time::Duration;
+
+/// How long a surviving process group gets to exit after SIGTERM before it is killed
+pub const ORPHAN_KILL_GRACE: Duration = Duration::from_secs(2);
+
+pub struct OrphanProcessService;
+
+impl OrphanProcessService {
+    /// Whether `pid` still runs as the leader of its own process group. A reused pid of an
+    /// unrelated process rarely leads a group of the same id.
+    #[cfg(unix)]
+    pub fn is_running(pid: u32) -> bool {
+        use nix::{
+            sys::signal::kill,
+            unistd::{Pid, getpgid},
+        };
+
+        let Ok(pid) = i32::try_from(pid) else {
+            return false;
+        };
+        let pid = Pid::from_raw(pid);
+        kill(pid, None).is_ok() && getpgid(Some(pid)).is_ok_and(|pgid| pgid == pid)
+    }
+
+    #[cfg(not(unix))]
+    pub fn is_running(_pid: u32) -> bool {
+        false
+    }
+
+    /// Stop the process group led by `pid`, killing it if it is still running after `grace`
+    #[cfg(unix)]
+    pub async fn kill_group(pid: u32, grace: Duration) {
+        use nix::{
+            sys::signal::{Signal, killpg},
+            unistd::Pid,
+        };
+
+        let Ok(raw) = i32::try_from(pid) else {
+            return;
+        };
+        let pgid = Pid::from_raw(raw);
+        if let Err(e) = killpg(pgid, Signal::SIGTERM) {
+            tracing::warn!("Failed to send SIGTERM to process group {}: {}", pgid, e);
+            return;
+        }
+        let deadline = tokio::time::Instant::now() + grace;
+        while tokio::time::Instant::now() < deadline {
+            if !Self::is_running(pid) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        if let Err(e) = killpg(pgid, Signal::SIGKILL) {
+            tracing::warn!("Failed to send SIGKILL to process group {}: {}", pgid, e);
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub async fn kill_group(_pid: u32, _grace: Duration) {}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{
+        os::unix::process::{CommandExt, ExitStatusExt},
+        process::Command,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn surviving_group_is_stopped() {
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        assert!(OrphanProcessService::is_running(pid));
+
+        OrphanProcessService::kill_group(pid, Duration::from_millis(200)).await;
+        let status = child.wait().unwrap();
+        assert!(status.signal().is_some());
+        assert!(!OrphanProcessService::is_running(pid));
+    }
+
+    #[test]
+    fn process_outside_its_own_group_is_not_taken_for_the_orphan() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        assert!(!OrphanProcessService::is_running(child.id()));
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
@@ -0,0 +1,207 @@
+//! Masking secrets in process output.
+//!
+//! Output is redacted as it is read from the process, before it reaches the message store
+//! that persists it and streams it to clients. The values of the secrets a process was given
+//! are masked wherever they appear, as is anything matching the configured patterns. Output
+//! is passed on a line at a time so a secret split across two reads is still masked; a
+//! partial line, such as a prompt, is passed on once the process has been quiet briefly.
+
+use std::{borrow::Cow, mem, sync::Arc, time::Duration};
+
+use futures::{Stream, StreamExt};
+use regex::Regex;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::secrets::{SecretError, SecretService};
+
+/// Text that replaces each masked value
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Secret values shorter than this are not masked, as they would mask ordinary output
+const MIN_SECRET_LEN: usize = 6;
+
+/// Quiet time after which a partial line is passed on
+pub const PARTIAL_LINE_FLUSH: Duration = Duration::from_millis(100);
+
+/// Partial lines longer than this are passed on without waiting for the rest
+const MAX_PENDING_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Default)]
+pub struct OutputRedactor {
+    /// Longest first, so a secret containing another is masked whole
+    secrets: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl OutputRedactor {
+    /// Redactor masking `secrets` and matches of `patterns`. Patterns that are not valid
+    /// regular expressions are skipped; settings reject them before they are stored.
+    pub fn new(secrets: impl IntoIterator<Item = String>, patterns: &[String]) -> Self {
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .filter(|secret| secret.len() >= MIN_SECRET_LEN)
+            .collect();
+        secrets.sort_by(|a, b| b.len().cmp(&a.len()));
+        secrets.dedup();
+        let patterns = patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    tracing::warn!("Skipping output redaction pattern {pattern}: {e}");
+                    None
+                }
+            })
+            .collect();
+        Self { secrets, patterns }
+    }
+
+    /// Redactor for the processes of a workspace, masking the secrets the workspace may be
+    /// given as well as matches of `patterns`
+    pub async fn for_workspace(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        workspace_id: Uuid,
+        patterns: &[String],
+    ) -> Result<Self, SecretError> {
+        let secrets = SecretService::new()?
+            .env_for_workspace(pool, project_id, workspace_id)
+            .await?;
+        Ok(Self::new(secrets.into_values(), patterns))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty() && self.patterns.is_empty()
+    }
+
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for secret in &self.secrets {
+            if text.contains(secret.as_str()) {
+                text = Cow::Owned(text.replace(secret.as_str(), REDACTED));
+            }
+        }
+        for pattern in &self.patterns {
+            let replaced = match pattern.replace_all(&text, REDACTED) {
+                Cow::Owned(replaced) => replaced,
+                Cow::Borrowed(_) => continue,
+            };
+            text = Cow::Owned(replaced);
+        }
+        text
+    }
+
+    /// Redact a stream of output chunks, passing the output on a line at a time and a
+    /// partial line once no more output has arrived for `flush_after`
+    pub fn redact_stream<S, E>(
+        self: Arc<Self>,
+        chunks: S,
+        flush_after: Duration,
+    ) -> impl Stream<Item = Result<String, E>>
+    where
+        S: Stream<Item = Result<String, E>> + Unpin,
+    {
+        futures::stream::unfold(
+            (chunks, String::new(), false),
+            move |(mut chunks, mut pending, finished)| {
+                let redactor = self.clone();
+                async move {
+                    if finished {
+                        return None;
+                    }
+                    loop {
+                        let next = if pending.is_empty() {
+                            chunks.next().await
+                        } else {
+                            match tokio::time::timeout(flush_after, chunks.next()).await {
+                                Ok(next) => next,
+                                Err(_) => {
+                                    let output = redactor.redact(&pending).into_owned();
+                                    pending.clear();
+                                    return Some((Ok(output), (chunks, pending, false)));
+                                }
+                            }
+                        };
+                        match next {
+                            Some(Ok(chunk)) => {
+                                pending.push_str(&chunk);
+                                let complete = match pending.rfind('\n') {
+                                    Some(end) => {
+                                        let rest = pending.split_off(end + 1);
+                                        mem::replace(&mut pending, rest)
+                                    }
+                                    None if pending.len() >= MAX_PENDING_BYTES => {
+                                        mem::take(&mut pending)
+                                    }
+                                    None => continue,
+                                };
+                                let output = redactor.redact(&complete).into_owned();
+                                return Some((Ok(output), (chunks, pending, false)));
+                            }
+                            Some(Err(e)) => return Some((Err(e), (chunks, pending, false))),
+                            None if pending.is_empty() => return None,
+                            None => {
+                                let output = redactor.redact(&pending).into_owned();
+                                return Some((Ok(output), (chunks, String::new(), true)));
+                            }
+                        }
+                    }
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    fn redactor() -> OutputRedactor {
+        OutputRedactor::new(
+            ["hunter2-secret".to_string(), "abc".to_string()],
+            &["sk-[A-Za-z0-9]+".to_string()],
+        )
+    }
+
+    #[test]
+    fn secrets_and_patterns_are_masked() {
+        let redactor = redactor();
+        assert_eq!(
+            redactor.redact("token=hunter2-secret key=sk-Abc123 abc"),
+            "token=[REDACTED] key=[REDACTED] abc"
+        );
+        assert!(matches!(redactor.redact("nothing here"), Cow::Borrowed(_)));
+        assert!(OutputRedactor::new([], &["(".to_string()]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn secrets_split_across_chunks_are_masked() {
+        let chunks = futures::stream::iter(
+            ["export KEY=hunt", "er2-secret\necho ", "done"]
+                .map(|chunk| Ok::<_, Infallible>(chunk.to_string())),
+        );
+        let output: Vec<String> = Arc::new(redactor())
+            .redact_stream(chunks, PARTIAL_LINE_FLUSH)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(output, ["export KEY=[REDACTED]\n", "echo done"]);
+    }
+
+    #[tokio::test]
+    async fn partial_lines_are_passed_on_when_quiet() {
+        let (sender, receiver) =
+            tokio::sync::mpsc::unbounded_channel::<Result<String, Infallible>>();
+        let mut output = Box::pin(Arc::new(redactor()).redact_stream(
+            tokio_stream::wrappers::UnboundedReceiverStream::new(receiver),
+            Duration::from_millis(20),
+        ));
+        sender.send(Ok("Continue? [y/n] ".to_string())).unwrap();
+        assert_eq!(output.next().await.unwrap().unwrap(), "Continue? [y/n] ");
+        drop(sender);
+        assert!(output.next().await.is_none());
+    }
+}
@@ -0,0 +1,170 @@
+//! Input for running execution processes.
+//!
+//! Agents that read input mid-run take it through their executor, which knows how the agent
+//! expects it; a process spawned with its stdin left open gets the input written there. A
+//! running process that accepts input but has been silent for a while is taken to be
+//! waiting for it, so clients can prompt for an answer instead of watching a run that never
+//! finishes.
+
+use std::{sync::Arc, time::Duration};
+
+use executors::executors::ExecutorInputSender;
+use thiserror::Error;
+use tokio::{
+    io::AsyncWriteExt,
+    process::ChildStdin,
+    sync::{Mutex, broadcast, watch},
+    task::JoinHandle,
+};
+use utils::log_msg::LogMsg;
+
+/// Silence after which a process that accepts input is taken to be waiting for it
+pub const INPUT_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum ProcessInputError {
+    #[error("The process no longer accepts input")]
+    Closed,
+}
+
+#[derive(Clone)]
+enum InputSink {
+    Executor(ExecutorInputSender),
+    Stdin(Arc<Mutex<ChildStdin>>),
+}
+
+/// Where input for a running process goes, and whether the process appears to wait for it
+#[derive(Clone)]
+pub struct ProcessInput {
+    sink: InputSink,
+    awaiting: Arc<watch::Sender<bool>>,
+}
+
+impl ProcessInput {
+    /// Input delivered by the process's executor
+    pub fn from_executor(sender: ExecutorInputSender) -> Self {
+        Self::new(InputSink::Executor(sender))
+    }
+
+    /// Input written to the process's stdin, one line per message
+    pub fn from_stdin(stdin: ChildStdin) -> Self {
+        Self::new(InputSink::Stdin(Arc::new(Mutex::new(stdin))))
+    }
+
+    fn new(sink: InputSink) -> Self {
+        Self {
+            sink,
+            awaiting: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    pub async fn send(&self, input: String) -> Result<(), ProcessInputError> {
+        match &self.sink {
+            InputSink::Executor(sender) => {
+                sender.send(input).map_err(|_| ProcessInputError::Closed)?
+            }
+            InputSink::Stdin(stdin) => {
+                let mut line = input;
+                if !line.ends_with('\n') {
+                    line.push('\n');
+                }
+                let mut stdin = stdin.lock().await;
+                stdin
+                    .write_all(line.as_bytes())
+                    .await
+                    .map_err(|_| ProcessInputError::Closed)?;
+                stdin.flush().await.map_err(|_| ProcessInputError::Closed)?;
+            }
+        }
+        set_awaiting(&self.awaiting, false);
+        Ok(())
+    }
+
+    /// Whether the process has been silent for the idle timeout since its last output or input
+    pub fn awaiting(&self) -> bool {
+        *self.awaiting.borrow()
+    }
+
+    /// Changes of [`Self::awaiting`]
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.awaiting.subscribe()
+    }
+
+    /// Track the process's output, marking it awaiting input after `idle` without any and
+    /// not awaiting once output resumes. Ends when the process finishes.
+    pub fn spawn_idle_watch(
+        &self,
+        mut output: broadcast::Receiver<LogMsg>,
+        idle: Duration,
+    ) -> JoinHandle<()> {
+        // Only the flag is kept, so dropping the input still closes the process's stdin
+        let awaiting = self.awaiting.clone();
+        tokio::spawn(async move {
+            loop {
+                match tokio::time::timeout(idle, output.recv()).await {
+                    Err(_) => set_awaiting(&awaiting, true),
+                    Ok(Ok(LogMsg::Finished)) | Ok(Err(broadcast::error::RecvError::Closed)) => {
+                        set_awaiting(&awaiting, false);
+                        break;
+                    }
+                    Ok(Ok(LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::JsonPatch(_)))
+                    | Ok(Err(broadcast::error::RecvError::Lagged(_))) => {
+                        set_awaiting(&awaiting, false)
+                    }
+                    Ok(Ok(_)) => {}
+                }
+            }
+        })
+    }
+}
+
+fn set_awaiting(flag: &watch::Sender<bool>, awaiting: bool) {
+    flag.send_if_modified(|current| {
+        let changed = *current != awaiting;
+        *current = awaiting;
+        changed
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn silence_marks_awaiting_until_output_or_input() {
+        let (sender, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let input = ProcessInput::from_executor(sender);
+        let (output, output_rx) = broadcast::channel(16);
+        let watch = input.spawn_idle_watch(output_rx, IDLE);
+        let mut awaiting = input.subscribe();
+
+        awaiting.changed().await.unwrap();
+        assert!(input.awaiting());
+        output.send(LogMsg::Stdout("working".to_string())).unwrap();
+        awaiting.changed().await.unwrap();
+        assert!(!input.awaiting());
+
+        awaiting.changed().await.unwrap();
+        assert!(input.awaiting());
+        input.send("yes".to_string()).await.unwrap();
+        assert!(!input.awaiting());
+        assert_eq!(received.recv().await.unwrap(), "yes");
+
+        output.send(LogMsg::Finished).unwrap();
+        watch.await.unwrap();
+        assert!(!input.awaiting());
+    }
+
+    #[tokio::test]
+    async fn sending_to_a_finished_executor_fails() {
+        let (sender, received) = tokio::sync::mpsc::unbounded_channel();
+        let input = ProcessInput::from_executor(sender);
+        drop(received);
+        assert!(matches!(
+            input.send("yes".to_string()).await,
+            Err(ProcessInputError::Closed)
+        ));
+    }
+}
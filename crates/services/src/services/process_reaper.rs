@@ -0,0 +1,128 @@
+//! Periodic reconciliation of execution processes with the OS processes running them.
+//!
+//! An execution can be marked running with nothing running it, for example after its exit
+//! was missed, or keep running after its worktree was removed. The other way round, a
+//! spawned process can outlive its execution being marked finished. The reaper fails,
+//! kills or stops these and keeps a report of its last run.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use db::models::execution_process::ExecutionProcessRunReason;
+use serde::Serialize;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// How often the reaper runs
+pub const REAPER_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Executions started or finished more recently than this are left alone, as their process
+/// may still be starting or exiting
+pub const REAPER_GRACE: chrono::Duration = chrono::Duration::minutes(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ReapAction {
+    /// Marked running with no process running it; marked failed
+    MarkedFailed,
+    /// Running although its workspace's worktree was removed; killed
+    KilledMissingWorktree,
+    /// Its process still ran although the execution was no longer marked running; killed
+    KilledUntracked,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ReapedProcess {
+    pub execution_process_id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    pub run_reason: ExecutionProcessRunReason,
+    pub action: ReapAction,
+}
+
+/// What one run of the reaper found and did
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProcessReaperReport {
+    #[ts(type = "Date")]
+    pub ran_at: DateTime<Utc>,
+    /// Executions marked running when the reaper ran
+    pub running: usize,
+    pub reaped: Vec<ReapedProcess>,
+}
+
+impl ProcessReaperReport {
+    pub fn new(running: usize, reaped: Vec<ReapedProcess>) -> Self {
+        Self {
+            ran_at: Utc::now(),
+            running,
+            reaped,
+        }
+    }
+}
+
+pub struct ProcessReaperService;
+
+impl ProcessReaperService {
+    /// What to do about an execution marked running, given whether a spawned process is
+    /// tracked for it and whether its worktree exists
+    pub fn action_for_running(
+        tracked: bool,
+        worktree_exists: bool,
+        started_at: DateTime<Utc>,
+    ) -> Option<ReapAction> {
+        if Utc::now() - started_at < REAPER_GRACE {
+            None
+        } else if !tracked {
+            Some(ReapAction::MarkedFailed)
+        } else if !worktree_exists {
+            Some(ReapAction::KilledMissingWorktree)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the still running process of an execution that is no longer marked running,
+    /// and finished at `completed_at` if it did, should be killed
+    pub fn should_kill_untracked(completed_at: Option<DateTime<Utc>>) -> bool {
+        completed_at.is_none_or(|completed_at| Utc::now() - completed_at >= REAPER_GRACE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_executions_need_a_process_and_a_worktree() {
+        let now = Utc::now();
+        let old = now - chrono::Duration::minutes(10);
+        assert_eq!(
+            ProcessReaperService::action_for_running(false, true, old),
+            Some(ReapAction::MarkedFailed)
+        );
+        assert_eq!(
+            ProcessReaperService::action_for_running(true, false, old),
+            Some(ReapAction::KilledMissingWorktree)
+        );
+        assert_eq!(
+            ProcessReaperService::action_for_running(true, true, old),
+            None
+        );
+        // Just started, so the process may not be tracked yet
+        assert_eq!(
+            ProcessReaperService::action_for_running(false, false, now),
+            None
+        );
+    }
+
+    #[test]
+    fn untracked_processes_get_time_to_exit() {
+        let now = Utc::now();
+        assert!(ProcessReaperService::should_kill_untracked(None));
+        assert!(ProcessReaperService::should_kill_untracked(Some(
+            now - chrono::Duration::minutes(5)
+        )));
+        assert!(!ProcessReaperService::should_kill_untracked(Some(
+            now - chrono::Duration::seconds(10)
+        )));
+    }
+}
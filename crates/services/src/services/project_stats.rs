@@ -0,0 +1,386 @@
+//! Rollup of a project's workspaces: outcomes, time to merge, lines changed and how often
+//! merges and rebases ran into conflicts, per repo and per agent configuration.
+
+use std::collections::{HashMap, HashSet};
+
+use db::models::{
+    execution_process::ExecutionProcess,
+    execution_process_repo_state::{ExecutionProcessRepoState, WorkspaceRepoHeads},
+    merge_conflict::MergeConflict,
+    project_repo::ProjectRepo,
+    workspace::{Workspace, WorkspaceOutcome},
+    workspace_repo::WorkspaceRepo,
+};
+use executors::{
+    actions::{ExecutorAction, ExecutorActionType},
+    profile::ExecutorProfileId,
+};
+use git::GitService;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, TS)]
+pub struct ProjectStats {
+    pub total_workspaces: usize,
+    /// Neither merged nor discarded yet
+    pub open: usize,
+    /// Merged directly or through a merged PR
+    pub merged: usize,
+    /// Archived, or with a closed PR, without ever being merged
+    pub discarded: usize,
+    /// Average hours from workspace creation to its first merge
+    pub avg_hours_to_merge: Option<f64>,
+    /// Lines added across all agent runs, summed over workspaces and repos
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub repos: Vec<RepoStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct RepoStats {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    /// Workspaces that include the repo
+    pub workspaces: usize,
+    /// Workspaces whose merge or rebase in the repo stopped on conflicts at least once
+    pub conflicted_workspaces: usize,
+    /// `conflicted_workspaces / workspaces`, or 0 without workspaces
+    pub conflict_rate: f64,
+}
+
+/// Outcomes of the workspaces started with one agent configuration. The model is part of the
+/// configuration: it is set by the profile variant.
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct AgentStats {
+    pub executor_profile_id: ExecutorProfileId,
+    pub workspaces: usize,
+    pub open: usize,
+    pub merged: usize,
+    pub discarded: usize,
+    /// `merged / (merged + discarded)`; unset while no workspace has an outcome yet
+    pub merge_rate: Option<f64>,
+    /// Average number of follow-up runs per workspace
+    pub avg_follow_ups: f64,
+    /// Share of workspaces whose merge or rebase stopped on conflicts at least once
+    pub conflict_rate: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Open,
+    Merged,
+    Discarded,
+}
+
+fn classify(outcome: &WorkspaceOutcome) -> Outcome {
+    if outcome.merged_at.is_some() {
+        Outcome::Merged
+    } else if outcome.archived || outcome.has_closed_pr {
+        Outcome::Discarded
+    } else {
+        Outcome::Open
+    }
+}
+
+/// Fill in the outcome counts and average time to merge
+fn summarize_outcomes(outcomes: &[WorkspaceOutcome], stats: &mut ProjectStats) {
+    let mut merge_hours = Vec::new();
+    for outcome in outcomes {
+        match (classify(outcome), outcome.merged_at) {
+            (Outcome::Merged, Some(merged_at)) => {
+                stats.merged += 1;
+                merge_hours.push((merged_at - outcome.created_at).num_seconds() as f64 / 3600.0);
+            }
+            (Outcome::Discarded, _) => stats.discarded += 1,
+            _ => stats.open += 1,
+        }
+    }
+    stats.total_workspaces = outcomes.len();
+    stats.avg_hours_to_merge = (!merge_hours.is_empty())
+        .then(|| merge_hours.iter().sum::<f64>() / merge_hours.len() as f64);
+}
+
+/// First recorded "before" and last recorded "after" commit per workspace and repo
+fn commit_ranges(heads: &[WorkspaceRepoHeads]) -> HashMap<(Uuid, Uuid), (String, String)> {
+    let mut bounds: HashMap<(Uuid, Uuid), (Option<&str>, Option<&str>)> = HashMap::new();
+    for head in heads {
+        let entry = bounds.entry((head.workspace_id, head.repo_id)).or_default();
+        if entry.0.is_none() {
+            entry.0 = head.before_head_commit.as_deref();
+        }
+        if let Some(after) = head.after_head_commit.as_deref() {
+            entry.1 = Some(after);
+        }
+    }
+    bounds
+        .into_iter()
+        .filter_map(|(key, bounds)| match bounds {
+            (Some(from), Some(to)) => Some((key, (from.to_string(), to.to_string()))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Agent configuration that started each workspace, and how many follow-ups it took
+fn agent_runs_by_workspace(
+    actions: &[(Uuid, ExecutorAction)],
+) -> HashMap<Uuid, (ExecutorProfileId, usize)> {
+    let mut runs: HashMap<Uuid, (ExecutorProfileId, usize)> = HashMap::new();
+    for (workspace_id, action) in actions {
+        match action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                runs.entry(*workspace_id)
+                    .or_insert_with(|| (request.executor_profile_id.clone(), 0));
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                runs.entry(*workspace_id)
+                    .or_insert_with(|| (request.executor_profile_id.clone(), 0))
+                    .1 += 1;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+fn summarize_agents(
+    outcomes: &[WorkspaceOutcome],
+    runs: &HashMap<Uuid, (ExecutorProfileId, usize)>,
+    conflicted: &HashSet<Uuid>,
+) -> Vec<AgentStats> {
+    let mut by_profile: HashMap<&ExecutorProfileId, AgentStats> = HashMap::new();
+    let mut follow_ups: HashMap<&ExecutorProfileId, usize> = HashMap::new();
+    let mut conflicts: HashMap<&ExecutorProfileId, usize> = HashMap::new();
+    for outcome in outcomes {
+        let Some((profile, workspace_follow_ups)) = runs.get(&outcome.id) else {
+            continue;
+        };
+        let stats = by_profile.entry(profile).or_insert_with(|| AgentStats {
+            executor_profile_id: profile.clone(),
+            workspaces: 0,
+            open: 0,
+            merged: 0,
+            discarded: 0,
+            merge_rate: None,
+            avg_follow_ups: 0.0,
+            conflict_rate: 0.0,
+        });
+        stats.workspaces += 1;
+        match classify(outcome) {
+            Outcome::Open => stats.open += 1,
+            Outcome::Merged => stats.merged += 1,
+            Outcome::Discarded => stats.discarded += 1,
+        }
+        *follow_ups.entry(profile).or_default() += workspace_follow_ups;
+        if conflicted.contains(&outcome.id) {
+            *conflicts.entry(profile).or_default() += 1;
+        }
+    }
+
+    let mut agents: Vec<AgentStats> = by_profile
+        .into_iter()
+        .map(|(profile, mut stats)| {
+            let decided = stats.merged + stats.discarded;
+            stats.merge_rate = (decided > 0).then(|| stats.merged as f64 / decided as f64);
+            let workspaces = stats.workspaces as f64;
+            stats.avg_follow_ups =
+                follow_ups.get(profile).copied().unwrap_or(0) as f64 / workspaces;
+            stats.conflict_rate = conflicts.get(profile).copied().unwrap_or(0) as f64 / workspaces;
+            stats
+        })
+        .collect();
+    agents.sort_by(|a, b| {
+        b.workspaces.cmp(&a.workspaces).then_with(|| {
+            a.executor_profile_id
+                .to_string()
+                .cmp(&b.executor_profile_id.to_string())
+        })
+    });
+    agents
+}
+
+pub struct ProjectStatsService;
+
+impl ProjectStatsService {
+    pub async fn compute(
+        pool: &SqlitePool,
+        git: &GitService,
+        project_id: Uuid,
+    ) -> Result<ProjectStats, sqlx::Error> {
+        let mut stats = ProjectStats::default();
+        summarize_outcomes(
+            &Workspace::find_outcomes_for_project(pool, project_id).await?,
+            &mut stats,
+        );
+
+        let repos = ProjectRepo::find_repos_for_project(pool, project_id).await?;
+        let repo_paths: HashMap<Uuid, _> = repos.iter().map(|r| (r.id, &r.path)).collect();
+        let heads = ExecutionProcessRepoState::find_heads_for_project(pool, project_id).await?;
+        for ((workspace_id, repo_id), (from, to)) in commit_ranges(&heads) {
+            let Some(repo_path) = repo_paths.get(&repo_id) else {
+                continue;
+            };
+            match git.diff_line_counts(repo_path, &from, &to) {
+                Ok((added, removed)) => {
+                    stats.lines_added += added;
+                    stats.lines_removed += removed;
+                }
+                // Commits of cleaned-up branches may have been garbage collected
+                Err(e) => tracing::debug!(
+                    "Skipping line counts for workspace {} in repo {}: {}",
+                    workspace_id,
+                    repo_id,
+                    e
+                ),
+            }
+        }
+
+        let workspaces_by_repo = WorkspaceRepo::count_workspaces_by_repo(pool, project_id).await?;
+        let conflicts_by_repo = MergeConflict::count_workspaces_by_repo(pool, project_id).await?;
+        stats.repos = repos
+            .into_iter()
+            .map(|repo| {
+                let workspaces = workspaces_by_repo.get(&repo.id).copied().unwrap_or(0) as usize;
+                let conflicted_workspaces =
+                    conflicts_by_repo.get(&repo.id).copied().unwrap_or(0) as usize;
+                RepoStats {
+                    repo_id: repo.id,
+                    repo_name: repo.display_name,
+                    workspaces,
+                    conflicted_workspaces,
+                    conflict_rate: if workspaces == 0 {
+                        0.0
+                    } else {
+                        conflicted_workspaces as f64 / workspaces as f64
+                    },
+                }
+            })
+            .collect();
+
+        Ok(stats)
+    }
+
+    /// Outcomes grouped by the agent configuration each workspace was started with
+    pub async fn by_agent(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<AgentStats>, sqlx::Error> {
+        let outcomes = Workspace::find_outcomes_for_project(pool, project_id).await?;
+        let actions =
+            ExecutionProcess::find_coding_agent_actions_for_project(pool, project_id).await?;
+        let conflicted = MergeConflict::find_workspace_ids_for_project(pool, project_id).await?;
+        Ok(summarize_agents(
+            &outcomes,
+            &agent_runs_by_workspace(&actions),
+            &conflicted,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use executors::executors::BaseCodingAgent;
+
+    use super::*;
+
+    fn outcome(
+        archived: bool,
+        merged_after_hours: Option<i64>,
+        closed_pr: bool,
+    ) -> WorkspaceOutcome {
+        let created_at = Utc::now();
+        WorkspaceOutcome {
+            id: Uuid::new_v4(),
+            archived,
+            created_at,
+            merged_at: merged_after_hours.map(|h| created_at + Duration::hours(h)),
+            has_closed_pr: closed_pr,
+        }
+    }
+
+    #[test]
+    fn outcomes_are_classified_and_merge_time_averaged() {
+        let outcomes = [
+            outcome(false, None, false),
+            outcome(true, Some(2), false),
+            outcome(false, Some(4), true),
+            outcome(true, None, false),
+            outcome(false, None, true),
+        ];
+        let mut stats = ProjectStats::default();
+        summarize_outcomes(&outcomes, &mut stats);
+        assert_eq!(
+            (
+                stats.total_workspaces,
+                stats.open,
+                stats.merged,
+                stats.discarded
+            ),
+            (5, 1, 2, 2)
+        );
+        assert_eq!(stats.avg_hours_to_merge, Some(3.0));
+
+        let mut empty = ProjectStats::default();
+        summarize_outcomes(&[], &mut empty);
+        assert_eq!(empty.avg_hours_to_merge, None);
+    }
+
+    #[test]
+    fn commit_ranges_span_first_before_to_last_after() {
+        let (workspace_id, repo_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let head = |before: Option<&str>, after: Option<&str>| WorkspaceRepoHeads {
+            workspace_id,
+            repo_id,
+            before_head_commit: before.map(String::from),
+            after_head_commit: after.map(String::from),
+        };
+        let heads = [
+            head(Some("a"), Some("b")),
+            head(Some("b"), None),
+            head(Some("b"), Some("c")),
+        ];
+        let ranges = commit_ranges(&heads);
+        assert_eq!(
+            ranges.get(&(workspace_id, repo_id)),
+            Some(&("a".to_string(), "c".to_string()))
+        );
+
+        assert!(commit_ranges(&[head(None, Some("c"))]).is_empty());
+    }
+
+    #[test]
+    fn agents_are_compared_by_outcome_follow_ups_and_conflicts() {
+        let claude = ExecutorProfileId::new(BaseCodingAgent::ClaudeCode);
+        let codex = ExecutorProfileId::new(BaseCodingAgent::Codex);
+        let outcomes = [
+            outcome(false, Some(1), false),
+            outcome(true, None, false),
+            outcome(false, None, false),
+            outcome(false, Some(2), false),
+            outcome(false, None, false),
+        ];
+        let runs = HashMap::from([
+            (outcomes[0].id, (claude.clone(), 2)),
+            (outcomes[1].id, (claude.clone(), 0)),
+            (outcomes[2].id, (claude.clone(), 1)),
+            (outcomes[3].id, (codex.clone(), 0)),
+        ]);
+        let conflicted = HashSet::from([outcomes[0].id]);
+
+        let agents = summarize_agents(&outcomes, &runs, &conflicted);
+        assert_eq!(agents.len(), 2);
+        let first = &agents[0];
+        assert_eq!(first.executor_profile_id, claude);
+        assert_eq!(
+            (first.workspaces, first.open, first.merged, first.discarded),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(first.merge_rate, Some(0.5));
+        assert_eq!(first.avg_follow_ups, 1.0);
+        assert!((first.conflict_rate - 1.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(agents[1].merge_rate, Some(1.0));
+    }
+}
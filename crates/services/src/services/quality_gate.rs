@@ -0,0 +1,615 @@
+//! Project quality gates, run in the background before a workspace is merged.
+//!
+//! Every gate of the project runs in each repo worktree of the workspace as a recorded
+//! execution process. A close that finds no current run of them starts one and returns the
+//! processes so they can be followed; a run is current while the repos are still at the HEAD
+//! commits it ran on and no coding agent has run since.
+
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::{
+        ExecutionProcess, ExecutionProcessFilter, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
+    execution_process_repo_state::{CreateExecutionProcessRepoState, ExecutionProcessRepoState},
+    project_quality_gate::ProjectQualityGate,
+    project_repo::ProjectRepo,
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use executors::actions::{ExecutorActionType, script::ScriptContext};
+use git::{GitService, GitServiceError};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tracing::{debug, error, info};
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use super::{
+    output_redaction::OutputRedactor,
+    recorded_command::{DEFAULT_COMMAND_TIMEOUT, RecordedCommandError, RecordedCommandService},
+};
+
+#[derive(Debug, Error)]
+pub enum QualityGateError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error(transparent)]
+    Command(#[from] RecordedCommandError),
+}
+
+/// Details of the first quality gate that failed for a workspace.
+/// The full command output is stored as the logs of `execution_process_id`.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct QualityGateFailure {
+    pub gate_id: Uuid,
+    pub gate_name: String,
+    pub command: String,
+    pub repo_name: String,
+    pub execution_process_id: Uuid,
+    pub exit_code: Option<i64>,
+}
+
+/// Where the quality gates of a workspace stand
+#[derive(Debug, Clone)]
+pub enum QualityGateStatus {
+    /// Every gate passed in every repo on the current HEAD commits, or none are configured
+    Passed,
+    /// A gate failed on the current HEAD commits
+    Failed(QualityGateFailure),
+    /// The gates have not run on the current HEAD commits
+    Outdated,
+}
+
+/// A gate to run in one repo worktree of the workspace
+#[derive(Debug, Clone)]
+struct PlannedGate {
+    gate: ProjectQualityGate,
+    repo_id: Uuid,
+    repo_name: String,
+    /// Where the gate runs, relative to the workspace directory
+    working_dir: String,
+    head_commit: String,
+}
+
+impl PlannedGate {
+    fn timeout(&self) -> Duration {
+        self.gate
+            .timeout_secs
+            .and_then(|secs| u64::try_from(secs).ok())
+            .map_or(DEFAULT_COMMAND_TIMEOUT, Duration::from_secs)
+    }
+
+    fn failure(&self, process: &ExecutionProcess) -> QualityGateFailure {
+        QualityGateFailure {
+            gate_id: self.gate.id,
+            gate_name: self.gate.name.clone(),
+            command: self.gate.command.clone(),
+            repo_name: self.repo_name.clone(),
+            execution_process_id: process.id,
+            exit_code: process.exit_code,
+        }
+    }
+
+    /// Whether `process` ran this gate
+    fn ran_as(&self, process: &ExecutionProcess) -> bool {
+        process
+            .executor_action()
+            .is_ok_and(|action| match action.typ() {
+                ExecutorActionType::ScriptRequest(request) => {
+                    request.script == self.gate.command
+                        && request.working_dir.as_deref() == Some(self.working_dir.as_str())
+                }
+                _ => false,
+            })
+    }
+}
+
+pub struct QualityGateService;
+
+impl QualityGateService {
+    /// Every quality gate configured for `project_id` in each repo worktree of the
+    /// workspace, or in the repo's path scope when the project has one, in the order they run
+    async fn plan(
+        pool: &SqlitePool,
+        git: &GitService,
+        workspace: &Workspace,
+        project_id: Uuid,
+        workspace_dir: &Path,
+    ) -> Result<Vec<PlannedGate>, QualityGateError> {
+        let gates = ProjectQualityGate::find_by_project_id(pool, project_id).await?;
+        if gates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+        let path_scopes: HashMap<Uuid, String> = ProjectRepo::find_by_project_id(pool, project_id)
+            .await?
+            .into_iter()
+            .filter_map(|project_repo| {
+                let scope = project_repo.path_scope?;
+                Some((project_repo.repo_id, scope))
+            })
+            .collect();
+
+        let mut planned = Vec::new();
+        for repo in &repos {
+            let head_commit = git.get_head_info(&workspace_dir.join(&repo.name))?.oid;
+            let working_dir = match path_scopes.get(&repo.id) {
+                Some(scope) => format!("{}/{scope}", repo.name),
+                None => repo.name.clone(),
+            };
+            planned.extend(gates.iter().map(|gate| PlannedGate {
+                gate: gate.clone(),
+                repo_id: repo.id,
+                repo_name: repo.name.clone(),
+                working_dir: working_dir.clone(),
+                head_commit: head_commit.clone(),
+            }));
+        }
+        Ok(planned)
+    }
+
+    /// Quality gate processes of the workspace that are still running, or waiting for an
+    /// earlier gate to finish
+    pub async fn running_for_workspace(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let running = ExecutionProcess::find_filtered(
+            pool,
+            &ExecutionProcessFilter {
+                workspace_id: Some(workspace_id),
+                status: Some(ExecutionProcessStatus::Running),
+                run_reason: Some(ExecutionProcessRunReason::QualityGate),
+                ..Default::default()
+            },
+            None,
+            0,
+        )
+        .await?;
+        Ok(running
+            .into_iter()
+            .map(|summary| summary.process.id)
+            .collect())
+    }
+
+    /// Whether the latest runs of the project's quality gates in the workspace are current
+    /// and passed. Gates still running count as outdated; check
+    /// [`Self::running_for_workspace`] first.
+    pub async fn status_for_workspace(
+        pool: &SqlitePool,
+        git: &GitService,
+        workspace: &Workspace,
+        project_id: Uuid,
+        workspace_dir: &Path,
+    ) -> Result<QualityGateStatus, QualityGateError> {
+        let planned = Self::plan(pool, git, workspace, project_id, workspace_dir).await?;
+        if planned.is_empty() {
+            return Ok(QualityGateStatus::Passed);
+        }
+
+        let runs = ExecutionProcess::find_filtered(
+            pool,
+            &ExecutionProcessFilter {
+                workspace_id: Some(workspace.id),
+                run_reason: Some(ExecutionProcessRunReason::QualityGate),
+                ..Default::default()
+            },
+            None,
+            0,
+        )
+        .await?;
+        let latest_agent_run = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            pool,
+            workspace.id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+        let changed_at: Option<DateTime<Utc>> = latest_agent_run.map(|process| process.started_at);
+
+        for gate in &planned {
+            let Some(process) = runs
+                .iter()
+                .map(|summary| &summary.process)
+                .find(|process| gate.ran_as(process))
+            else {
+                return Ok(QualityGateStatus::Outdated);
+            };
+            let ran_on_head =
+                ExecutionProcessRepoState::find_by_execution_process_id(pool, process.id)
+                    .await?
+                    .iter()
+                    .any(|state| {
+                        state.repo_id == gate.repo_id
+                            && state.before_head_commit.as_deref()
+                                == Some(gate.head_commit.as_str())
+                    });
+            if !ran_on_head
+                || changed_at.is_some_and(|changed_at| process.started_at < changed_at)
+                || process.status == ExecutionProcessStatus::Running
+            {
+                return Ok(QualityGateStatus::Outdated);
+            }
+            if process.status != ExecutionProcessStatus::Completed || process.exit_code != Some(0) {
+                return Ok(QualityGateStatus::Failed(gate.failure(process)));
+            }
+        }
+        Ok(QualityGateStatus::Passed)
+    }
+
+    /// Start every quality gate of the project in the workspace in the background and
+    /// return their execution processes in the order they run. Each gate is recorded up
+    /// front; one that fails stops the run, and the gates after it are marked killed. Their
+    /// output is masked by `redactor` before it is stored.
+    pub async fn start_for_workspace(
+        pool: &SqlitePool,
+        git: &GitService,
+        workspace: &Workspace,
+        project_id: Uuid,
+        workspace_dir: &Path,
+        redactor: OutputRedactor,
+    ) -> Result<Vec<Uuid>, QualityGateError> {
+        let planned = Self::plan(pool, git, workspace, project_id, workspace_dir).await?;
+        let session = RecordedCommandService::session_for_workspace(pool, workspace.id).await?;
+
+        let mut runs = Vec::new();
+        for gate in planned {
+            let process = RecordedCommandService::record(
+                pool,
+                session.id,
+                Some(gate.working_dir.clone()),
+                &gate.gate.command,
+                ScriptContext::QualityGate,
+                ExecutionProcessRunReason::QualityGate,
+                &[CreateExecutionProcessRepoState {
+                    repo_id: gate.repo_id,
+                    before_head_commit: Some(gate.head_commit.clone()),
+                    after_head_commit: None,
+                    merge_commit: None,
+                }],
+            )
+            .await?;
+            runs.push((gate, process.id));
+        }
+        let ids = runs.iter().map(|(_, id)| *id).collect();
+
+        info!(
+            "Starting {} quality gate run(s) for workspace {}",
+            runs.len(),
+            workspace.id
+        );
+        let pool = pool.clone();
+        let workspace_dir = workspace_dir.to_path_buf();
+        tokio::spawn(async move {
+            Self::run_in_order(&pool, &workspace_dir, runs, &redactor).await;
+        });
+        Ok(ids)
+    }
+
+    /// Run recorded gates one after another until one fails
+    async fn run_in_order(
+        pool: &SqlitePool,
+        workspace_dir: &Path,
+        runs: Vec<(PlannedGate, Uuid)>,
+        redactor: &OutputRedactor,
+    ) {
+        let mut runs = runs.into_iter();
+        let mut stopped_by = None;
+        for (gate, execution_process_id) in runs.by_ref() {
+            debug!(
+                "Running quality gate '{}' in repo '{}'",
+                gate.gate.name, gate.repo_name
+            );
+            let result = RecordedCommandService::run(
+                pool,
+                execution_process_id,
+                workspace_dir,
+                Some(&gate.working_dir),
+                &gate.gate.command,
+                gate.timeout(),
+                redactor,
+            )
+            .await;
+            match result {
+                Ok(result) if result.succeeded() => continue,
+                Ok(result) => info!(
+                    "Quality gate '{}' failed in repo '{}' (exit code {:?})",
+                    gate.gate.name, gate.repo_name, result.exit_code
+                ),
+                Err(e) => error!(
+                    "Quality gate '{}' could not run in repo '{}': {}",
+                    gate.gate.name, gate.repo_name, e
+                ),
+            }
+            stopped_by = Some(gate);
+            break;
+        }
+
+        let Some(failed) = stopped_by else {
+            return;
+        };
+        for (_, execution_process_id) in runs {
+            RecordedCommandService::append_log(
+                pool,
+                execution_process_id,
+                LogMsg::Stderr(format!(
+                    "Not run: quality gate '{}' failed in repo '{}'",
+                    failed.gate.name, failed.repo_name
+                )),
+            )
+            .await;
+            if let Err(e) = ExecutionProcess::update_completion(
+                pool,
+                execution_process_id,
+                ExecutionProcessStatus::Killed,
+                None,
+            )
+            .await
+            {
+                error!(
+                    "Failed to mark quality gate process {} as not run: {}",
+                    execution_process_id, e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use db::models::{
+        execution_process_logs::ExecutionProcessLogs,
+        project_quality_gate::CreateProjectQualityGate,
+    };
+
+    use super::*;
+    use crate::services::test_support::{
+        SeededWorkspace, init_repo, run_git, seed_workspace, test_pool,
+    };
+
+    async fn add_gate(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        command: &str,
+        timeout_secs: Option<u32>,
+    ) -> ProjectQualityGate {
+        ProjectQualityGate::create(
+            pool,
+            project_id,
+            &CreateProjectQualityGate {
+                name: "check".to_string(),
+                command: command.to_string(),
+                sort_order: None,
+                timeout_secs,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn logs(pool: &SqlitePool, execution_id: Uuid) -> Vec<LogMsg> {
+        let records = ExecutionProcessLogs::find_by_execution_id(pool, execution_id)
+            .await
+            .unwrap();
+        ExecutionProcessLogs::parse_logs(&records).unwrap()
+    }
+
+    async fn process(pool: &SqlitePool, id: Uuid) -> ExecutionProcess {
+        ExecutionProcess::find_by_id(pool, id)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    async fn seeded(pool: &SqlitePool, dir: &Path) -> SeededWorkspace {
+        init_repo(&dir.join("ws/api"), "README.md", "v1\n");
+        seed_workspace(pool, &dir.join("ws"), &dir.join("api")).await
+    }
+
+    async fn start_gates(
+        pool: &SqlitePool,
+        seeded: &SeededWorkspace,
+        dir: &Path,
+        redactor: OutputRedactor,
+    ) -> Vec<Uuid> {
+        QualityGateService::start_for_workspace(
+            pool,
+            &GitService::new(),
+            &seeded.workspace,
+            seeded.project.id,
+            &dir.join("ws"),
+            redactor,
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn status(pool: &SqlitePool, seeded: &SeededWorkspace, dir: &Path) -> QualityGateStatus {
+        QualityGateService::status_for_workspace(
+            pool,
+            &GitService::new(),
+            &seeded.workspace,
+            seeded.project.id,
+            &dir.join("ws"),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Wait for the gates of the workspace to finish and return where they stand
+    async fn finished_status(
+        pool: &SqlitePool,
+        seeded: &SeededWorkspace,
+        dir: &Path,
+    ) -> QualityGateStatus {
+        let started = std::time::Instant::now();
+        while !QualityGateService::running_for_workspace(pool, seeded.workspace.id)
+            .await
+            .unwrap()
+            .is_empty()
+        {
+            assert!(started.elapsed() < Duration::from_secs(20));
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        status(pool, seeded, dir).await
+    }
+
+    #[tokio::test]
+    async fn passing_gates_let_the_workspace_through_until_it_changes() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let seeded = seeded(&pool, dir.path()).await;
+        assert!(matches!(
+            status(&pool, &seeded, dir.path()).await,
+            QualityGateStatus::Passed
+        ));
+
+        add_gate(&pool, seeded.project.id, "echo gate ok", None).await;
+        assert!(matches!(
+            status(&pool, &seeded, dir.path()).await,
+            QualityGateStatus::Outdated
+        ));
+        let ids = start_gates(&pool, &seeded, dir.path(), OutputRedactor::default()).await;
+        assert_eq!(ids.len(), 1);
+        assert!(matches!(
+            finished_status(&pool, &seeded, dir.path()).await,
+            QualityGateStatus::Passed
+        ));
+
+        let process = process(&pool, ids[0]).await;
+        assert_eq!(process.status, ExecutionProcessStatus::Completed);
+        assert_eq!(process.exit_code, Some(0));
+        assert!(
+            logs(&pool, process.id)
+                .await
+                .iter()
+                .any(|msg| matches!(msg, LogMsg::Stdout(out) if out.contains("gate ok")))
+        );
+
+        let worktree = dir.path().join("ws/api");
+        std::fs::write(worktree.join("README.md"), "v2\n").unwrap();
+        run_git(&worktree, &["commit", "-q", "-am", "Update readme"]);
+        assert!(matches!(
+            status(&pool, &seeded, dir.path()).await,
+            QualityGateStatus::Outdated
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_failing_gate_blocks_with_its_output_recorded() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let seeded = seeded(&pool, dir.path()).await;
+        let gate = add_gate(
+            &pool,
+            seeded.project.id,
+            "echo 'lint failed' >&2; exit 3",
+            None,
+        )
+        .await;
+        add_gate(&pool, seeded.project.id, "echo never runs", None).await;
+
+        let ids = start_gates(&pool, &seeded, dir.path(), OutputRedactor::default()).await;
+        assert_eq!(ids.len(), 2);
+        let QualityGateStatus::Failed(failure) = finished_status(&pool, &seeded, dir.path()).await
+        else {
+            panic!("the gate fails");
+        };
+        assert_eq!(failure.gate_id, gate.id);
+        assert_eq!(failure.repo_name, seeded.repo.name);
+        assert_eq!(failure.exit_code, Some(3));
+        assert_eq!(failure.execution_process_id, ids[0]);
+
+        let failed = process(&pool, ids[0]).await;
+        assert_eq!(failed.status, ExecutionProcessStatus::Failed);
+        assert_eq!(failed.run_reason, ExecutionProcessRunReason::QualityGate);
+        assert!(
+            logs(&pool, failed.id)
+                .await
+                .iter()
+                .any(|msg| matches!(msg, LogMsg::Stderr(err) if err.contains("lint failed")))
+        );
+        let skipped = process(&pool, ids[1]).await;
+        assert_eq!(skipped.status, ExecutionProcessStatus::Killed);
+        assert!(
+            logs(&pool, skipped.id)
+                .await
+                .iter()
+                .any(|msg| matches!(msg, LogMsg::Stderr(err) if err.starts_with("Not run")))
+        );
+    }
+
+    #[tokio::test]
+    async fn a_gate_running_past_its_timeout_is_stopped_and_fails() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let seeded = seeded(&pool, dir.path()).await;
+        add_gate(&pool, seeded.project.id, "echo started; sleep 30", Some(1)).await;
+
+        let ids = start_gates(&pool, &seeded, dir.path(), OutputRedactor::default()).await;
+        assert_eq!(
+            QualityGateService::running_for_workspace(&pool, seeded.workspace.id)
+                .await
+                .unwrap(),
+            ids
+        );
+        let QualityGateStatus::Failed(failure) = finished_status(&pool, &seeded, dir.path()).await
+        else {
+            panic!("the gate times out");
+        };
+        assert_eq!(failure.exit_code, None);
+
+        let process = process(&pool, failure.execution_process_id).await;
+        assert_eq!(process.status, ExecutionProcessStatus::Failed);
+        let logs = logs(&pool, process.id).await;
+        assert!(
+            logs.iter()
+                .any(|msg| matches!(msg, LogMsg::Stdout(out) if out.contains("started")))
+        );
+        assert!(
+            logs.iter()
+                .any(|msg| matches!(msg, LogMsg::Stderr(err) if err.contains("Timed out")))
+        );
+    }
+
+    #[tokio::test]
+    async fn gate_output_is_redacted_before_it_is_stored() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let seeded = seeded(&pool, dir.path()).await;
+        add_gate(
+            &pool,
+            seeded.project.id,
+            "echo 'token ghp_abc123'; echo 'ghp_def456' >&2; exit 1",
+            None,
+        )
+        .await;
+
+        let redactor = OutputRedactor::new([], &["ghp_[a-z0-9]+".to_string()]);
+        let ids = start_gates(&pool, &seeded, dir.path(), redactor).await;
+        assert!(matches!(
+            finished_status(&pool, &seeded, dir.path()).await,
+            QualityGateStatus::Failed(_)
+        ));
+
+        let logs = logs(&pool, ids[0]).await;
+        assert!(
+            logs.iter()
+                .any(|msg| matches!(msg, LogMsg::Stdout(out) if out.contains("token [REDACTED]")))
+        );
+        assert!(
+            logs.iter()
+                .any(|msg| matches!(msg, LogMsg::Stderr(err) if err.contains("[REDACTED]")))
+        );
+        assert!(!logs.iter().any(|msg| match msg {
+            LogMsg::Stdout(text) | LogMsg::Stderr(text) => text.contains("ghp_"),
+            _ => false,
+        }));
+    }
+}
@@ -11,6 +11,7 @@ use uuid::Uuid;
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct QueuedMessage {
+    pub id: Uuid,
     /// The session this message is queued for
     pub session_id: Uuid,
     /// The follow-up data (message + variant)
@@ -31,10 +32,11 @@ pub enum QueueStatus {
 }
 
 /// In-memory service for managing queued follow-up messages.
-/// One queued message per session.
+/// Each session has a queue of messages that run one after another, each once the
+/// execution before it completes.
 #[derive(Clone)]
 pub struct QueuedMessageService {
-    queue: Arc<DashMap<Uuid, QueuedMessage>>,
+    queue: Arc<DashMap<Uuid, Vec<QueuedMessage>>>,
 }
 
 impl QueuedMessageService {
@@ -44,31 +46,96 @@ impl QueuedMessageService {
         }
     }
 
-    /// Queue a message for a session. Replaces any existing queued message.
-    pub fn queue_message(&self, session_id: Uuid, data: DraftFollowUpData) -> QueuedMessage {
-        let queued = QueuedMessage {
+    fn new_message(session_id: Uuid, data: DraftFollowUpData) -> QueuedMessage {
+        QueuedMessage {
+            id: Uuid::new_v4(),
             session_id,
             data,
             queued_at: Utc::now(),
-        };
-        self.queue.insert(session_id, queued.clone());
+        }
+    }
+
+    /// Queue a message for a session. Replaces any existing queued messages.
+    pub fn queue_message(&self, session_id: Uuid, data: DraftFollowUpData) -> QueuedMessage {
+        let queued = Self::new_message(session_id, data);
+        self.queue.insert(session_id, vec![queued.clone()]);
         queued
     }
 
-    /// Cancel/remove a queued message for a session
-    pub fn cancel_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.remove(&session_id).map(|(_, v)| v)
+    /// Add a message to the end of a session's queue
+    pub fn append_message(&self, session_id: Uuid, data: DraftFollowUpData) -> QueuedMessage {
+        let queued = Self::new_message(session_id, data);
+        self.queue
+            .entry(session_id)
+            .or_default()
+            .push(queued.clone());
+        queued
+    }
+
+    /// Cancel/remove all queued messages for a session
+    pub fn cancel_queued(&self, session_id: Uuid) -> Vec<QueuedMessage> {
+        self.queue
+            .remove(&session_id)
+            .map(|(_, v)| v)
+            .unwrap_or_default()
     }
 
-    /// Get the queued message for a session (if any)
+    /// Cancel/remove one queued message of a session
+    pub fn cancel_message(&self, session_id: Uuid, message_id: Uuid) -> Option<QueuedMessage> {
+        let mut messages = self.queue.get_mut(&session_id)?;
+        let index = messages.iter().position(|m| m.id == message_id)?;
+        let cancelled = messages.remove(index);
+        let now_empty = messages.is_empty();
+        drop(messages);
+        if now_empty {
+            self.queue.remove_if(&session_id, |_, v| v.is_empty());
+        }
+        Some(cancelled)
+    }
+
+    /// Get the next queued message for a session (if any)
     pub fn get_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.get(&session_id).map(|r| r.clone())
+        self.queue.get(&session_id).and_then(|r| r.first().cloned())
+    }
+
+    /// All queued messages of a session, in the order they will run
+    pub fn list_queued(&self, session_id: Uuid) -> Vec<QueuedMessage> {
+        self.queue
+            .get(&session_id)
+            .map(|r| r.clone())
+            .unwrap_or_default()
     }
 
-    /// Take (remove and return) the queued message for a session.
+    /// Put a session's queued messages in the order of `message_ids`. Returns `None`, leaving
+    /// the queue as it is, unless `message_ids` lists every queued message exactly once.
+    pub fn reorder(&self, session_id: Uuid, message_ids: &[Uuid]) -> Option<Vec<QueuedMessage>> {
+        let mut messages = self.queue.get_mut(&session_id)?;
+        if message_ids.len() != messages.len() {
+            return None;
+        }
+        let mut reordered: Vec<QueuedMessage> = Vec::with_capacity(messages.len());
+        for id in message_ids {
+            let message = messages.iter().find(|m| m.id == *id)?;
+            if reordered.iter().any(|m| m.id == *id) {
+                return None;
+            }
+            reordered.push(message.clone());
+        }
+        *messages = reordered.clone();
+        Some(reordered)
+    }
+
+    /// Take (remove and return) the next queued message for a session.
     /// Used by finalization flow to consume the queued message.
     pub fn take_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.remove(&session_id).map(|(_, v)| v)
+        let mut messages = self.queue.get_mut(&session_id)?;
+        let next = (!messages.is_empty()).then(|| messages.remove(0));
+        let now_empty = messages.is_empty();
+        drop(messages);
+        if now_empty {
+            self.queue.remove_if(&session_id, |_, v| v.is_empty());
+        }
+        next
     }
 
     /// Check if a session has a queued message
@@ -90,3 +157,90 @@ impl Default for QueuedMessageService {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+
+    use super::*;
+
+    fn data(message: &str) -> DraftFollowUpData {
+        DraftFollowUpData {
+            message: message.to_string(),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+        }
+    }
+
+    fn messages(queued: &[QueuedMessage]) -> Vec<&str> {
+        queued.iter().map(|m| m.data.message.as_str()).collect()
+    }
+
+    #[test]
+    fn appended_messages_are_taken_in_order() {
+        let service = QueuedMessageService::new();
+        let session_id = Uuid::new_v4();
+        service.append_message(session_id, data("first"));
+        service.append_message(session_id, data("second"));
+        assert_eq!(
+            messages(&service.list_queued(session_id)),
+            ["first", "second"]
+        );
+
+        assert_eq!(
+            service.take_queued(session_id).unwrap().data.message,
+            "first"
+        );
+        assert_eq!(
+            service.take_queued(session_id).unwrap().data.message,
+            "second"
+        );
+        assert!(service.take_queued(session_id).is_none());
+        assert!(!service.has_queued(session_id));
+    }
+
+    #[test]
+    fn queueing_replaces_and_cancelling_removes() {
+        let service = QueuedMessageService::new();
+        let session_id = Uuid::new_v4();
+        service.append_message(session_id, data("first"));
+        let second = service.append_message(session_id, data("second"));
+        service.append_message(session_id, data("third"));
+
+        assert_eq!(
+            service.cancel_message(session_id, second.id).unwrap().id,
+            second.id
+        );
+        assert!(service.cancel_message(session_id, second.id).is_none());
+        assert_eq!(
+            messages(&service.list_queued(session_id)),
+            ["first", "third"]
+        );
+
+        service.queue_message(session_id, data("only"));
+        assert_eq!(messages(&service.list_queued(session_id)), ["only"]);
+        assert_eq!(service.cancel_queued(session_id).len(), 1);
+        assert!(!service.has_queued(session_id));
+    }
+
+    #[test]
+    fn reorder_needs_every_message_once() {
+        let service = QueuedMessageService::new();
+        let session_id = Uuid::new_v4();
+        let a = service.append_message(session_id, data("a"));
+        let b = service.append_message(session_id, data("b"));
+        let c = service.append_message(session_id, data("c"));
+
+        assert!(service.reorder(session_id, &[c.id, a.id]).is_none());
+        assert!(service.reorder(session_id, &[c.id, a.id, a.id]).is_none());
+        assert!(
+            service
+                .reorder(session_id, &[c.id, a.id, Uuid::new_v4()])
+                .is_none()
+        );
+        assert_eq!(messages(&service.list_queued(session_id)), ["a", "b", "c"]);
+
+        let reordered = service.reorder(session_id, &[c.id, a.id, b.id]).unwrap();
+        assert_eq!(messages(&reordered), ["c", "a", "b"]);
+        assert_eq!(service.get_queued(session_id).unwrap().id, c.id);
+    }
+}
@@ -0,0 +1,338 @@
+//! Shell commands run to completion inside a workspace and recorded as execution processes,
+//! as used by quality gates and test runs.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    process::{ExitStatus, Stdio},
+    time::Duration,
+};
+
+use db::models::{
+    execution_process::{
+        CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
+    execution_process_logs::ExecutionProcessLogs,
+    execution_process_repo_state::CreateExecutionProcessRepoState,
+    session::{CreateSession, Session, SessionError},
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType,
+    script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    process::{Child, Command},
+    task::JoinHandle,
+};
+use tracing::info;
+use utils::{log_msg::LogMsg, shell::get_shell_command};
+use uuid::Uuid;
+
+use super::{
+    orphan_processes::{ORPHAN_KILL_GRACE, OrphanProcessService},
+    output_redaction::OutputRedactor,
+};
+
+/// How long a recorded command may run when nothing else is configured
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Error)]
+pub enum RecordedCommandError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Session(#[from] SessionError),
+    #[error("Failed to run command '{command}': {source}")]
+    Spawn {
+        command: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Result of running a single command through [`RecordedCommandService::run`]
+#[derive(Debug, Clone)]
+pub struct RecordedCommand {
+    pub execution_process_id: Uuid,
+    pub exit_code: Option<i64>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl RecordedCommand {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// What a command run by [`RecordedCommandService::run`] left behind; no status means it
+/// timed out
+struct CommandOutput {
+    status: Option<ExitStatus>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+/// Read a child's pipe to its end in the background
+fn read_pipe<R: AsyncRead + Unpin + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    tokio::spawn(async move {
+        let mut data = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut data).await;
+        }
+        data
+    })
+}
+
+pub struct RecordedCommandService;
+
+impl RecordedCommandService {
+    /// Record `command` as a running execution process of the session, to be run by
+    /// [`Self::run`]. `repo_states` are the HEAD commits the command is about to run on.
+    pub async fn record(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        working_dir: Option<String>,
+        command: &str,
+        context: ScriptContext,
+        run_reason: ExecutionProcessRunReason,
+        repo_states: &[CreateExecutionProcessRepoState],
+    ) -> Result<ExecutionProcess, sqlx::Error> {
+        let action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: command.to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context,
+                working_dir,
+                env: HashMap::new(),
+                artifacts: Vec::new(),
+            }),
+            None,
+        );
+        ExecutionProcess::create(
+            pool,
+            &CreateExecutionProcess {
+                session_id,
+                executor_action: action,
+                run_reason,
+            },
+            Uuid::new_v4(),
+            repo_states,
+        )
+        .await
+    }
+
+    /// Record a shell command as an execution process and run it to completion, as
+    /// [`Self::record`] and [`Self::run`] do
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run_recorded_command(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        workspace_dir: &Path,
+        working_dir: Option<String>,
+        command: &str,
+        context: ScriptContext,
+        run_reason: ExecutionProcessRunReason,
+        timeout: Duration,
+        redactor: &OutputRedactor,
+    ) -> Result<RecordedCommand, RecordedCommandError> {
+        let execution_process = Self::record(
+            pool,
+            session_id,
+            working_dir.clone(),
+            command,
+            context,
+            run_reason,
+            &[],
+        )
+        .await?;
+        Self::run(
+            pool,
+            execution_process.id,
+            workspace_dir,
+            working_dir.as_deref(),
+            command,
+            timeout,
+            redactor,
+        )
+        .await
+    }
+
+    /// Run the shell command of a recorded execution process to completion inside the
+    /// workspace, with stdout/stderr masked by `redactor` and stored as the process logs. A
+    /// command still running after `timeout` is stopped, along with everything it started,
+    /// and fails.
+    pub async fn run(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        workspace_dir: &Path,
+        working_dir: Option<&str>,
+        command: &str,
+        timeout: Duration,
+        redactor: &OutputRedactor,
+    ) -> Result<RecordedCommand, RecordedCommandError> {
+        let effective_dir = match working_dir {
+            Some(dir) => workspace_dir.join(dir),
+            None => workspace_dir.to_path_buf(),
+        };
+
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let mut cmd = Command::new(shell_cmd);
+        cmd.kill_on_drop(true)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .arg(shell_arg)
+            .arg(command)
+            .current_dir(&effective_dir);
+        // Its own process group, so that a timeout stops everything the command started
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        let output = match cmd.spawn() {
+            Ok(child) => Self::wait_for_output(child, timeout).await,
+            Err(e) => Err(e),
+        };
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                Self::append_log(
+                    pool,
+                    execution_process_id,
+                    LogMsg::Stderr(format!("Failed to start command: {e}")),
+                )
+                .await;
+                ExecutionProcess::update_completion(
+                    pool,
+                    execution_process_id,
+                    ExecutionProcessStatus::Failed,
+                    None,
+                )
+                .await?;
+                return Err(RecordedCommandError::Spawn {
+                    command: command.to_string(),
+                    source: e,
+                });
+            }
+        };
+
+        let stdout = redactor
+            .redact(&String::from_utf8_lossy(&output.stdout))
+            .into_owned();
+        let stderr = redactor
+            .redact(&String::from_utf8_lossy(&output.stderr))
+            .into_owned();
+        if !stdout.is_empty() {
+            Self::append_log(pool, execution_process_id, LogMsg::Stdout(stdout.clone())).await;
+        }
+        if !stderr.is_empty() {
+            Self::append_log(pool, execution_process_id, LogMsg::Stderr(stderr.clone())).await;
+        }
+        if output.status.is_none() {
+            info!(
+                "Command '{}' timed out after {}s and was stopped",
+                command,
+                timeout.as_secs()
+            );
+            Self::append_log(
+                pool,
+                execution_process_id,
+                LogMsg::Stderr(format!(
+                    "Timed out after {}s; the command was stopped",
+                    timeout.as_secs()
+                )),
+            )
+            .await;
+        }
+
+        let exit_code = output
+            .status
+            .and_then(|status| status.code())
+            .map(i64::from);
+        let status = if output.status.is_some_and(|status| status.success()) {
+            ExecutionProcessStatus::Completed
+        } else {
+            ExecutionProcessStatus::Failed
+        };
+        ExecutionProcess::update_completion(pool, execution_process_id, status, exit_code).await?;
+
+        Ok(RecordedCommand {
+            execution_process_id,
+            exit_code,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Wait for `child` to exit and collect its output. Once `timeout` passes the child's
+    /// process group is stopped and the output so far is returned without a status.
+    async fn wait_for_output(
+        mut child: Child,
+        timeout: Duration,
+    ) -> std::io::Result<CommandOutput> {
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+        let status = match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(status) => Some(status?),
+            Err(_) => {
+                if let Some(pid) = child.id() {
+                    OrphanProcessService::kill_group(pid, ORPHAN_KILL_GRACE).await;
+                }
+                let _ = child.kill().await;
+                None
+            }
+        };
+        Ok(CommandOutput {
+            status,
+            stdout: stdout.await.unwrap_or_default(),
+            stderr: stderr.await.unwrap_or_default(),
+        })
+    }
+
+    /// Latest session of the workspace, creating one if it has none yet
+    pub async fn session_for_workspace(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Session, RecordedCommandError> {
+        if let Some(session) = Session::find_latest_by_workspace_id(pool, workspace_id).await? {
+            return Ok(session);
+        }
+        Ok(Session::create(
+            pool,
+            &CreateSession { executor: None },
+            Uuid::new_v4(),
+            workspace_id,
+        )
+        .await?)
+    }
+
+    pub async fn append_log(pool: &SqlitePool, execution_id: Uuid, msg: LogMsg) {
+        match serde_json::to_string(&msg) {
+            Ok(json_line) => {
+                if let Err(e) = ExecutionProcessLogs::append_log_line(
+                    pool,
+                    execution_id,
+                    &format!("{json_line}\n"),
+                )
+                .await
+                {
+                    tracing::error!(
+                        "Failed to append log line for execution {}: {}",
+                        execution_id,
+                        e
+                    );
+                }
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to serialize log message for execution {}: {}",
+                    execution_id,
+                    e
+                );
+            }
+        }
+    }
+}
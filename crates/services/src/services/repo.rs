@@ -1,12 +1,23 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use db::models::repo::Repo as RepoModel;
-use git::{GitService, GitServiceError};
+use git::{GitRemote, GitService, GitServiceError};
+use serde::Serialize;
 use sqlx::SqlitePool;
 use thiserror::Error;
+use ts_rs::TS;
 use utils::path::expand_tilde;
 use uuid::Uuid;
 
+use super::workspace_manager::WorkspaceManager;
+
+/// Below this much free space in the worktree base directory, new workspaces may fail to check out
+const MIN_WORKTREE_FREE_BYTES: u64 = 1024 * 1024 * 1024;
+const REMOTE_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug, Error)]
 pub enum RepoError {
     #[error(transparent)]
@@ -31,6 +42,56 @@ pub enum RepoError {
 
 pub type Result<T> = std::result::Result<T, RepoError>;
 
+/// A git repository found by scanning a directory
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DiscoveredRepo {
+    pub path: PathBuf,
+    pub name: String,
+    /// Branch the default remote's HEAD points at, else the checked-out branch
+    pub default_branch: Option<String>,
+    pub remotes: Vec<GitRemote>,
+    /// Id of the existing registration of this path, if any
+    pub registered_repo_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum RepoIssueKind {
+    PathMissing,
+    NotADirectory,
+    NotAGitRepository,
+    DefaultBranchMissing,
+    RemoteUnreachable,
+    LowDiskSpace,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RepoIssue {
+    pub kind: RepoIssueKind,
+    pub message: String,
+}
+
+/// Result of checking that a registered repo can host workspaces
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RepoValidation {
+    pub repo_id: Uuid,
+    pub healthy: bool,
+    pub issues: Vec<RepoIssue>,
+}
+
+#[cfg(unix)]
+fn available_space(path: &Path) -> Option<u64> {
+    let stat = nix::sys::statvfs::statvfs(path).ok()?;
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
+
 #[derive(Clone, Default)]
 pub struct RepoService;
 
@@ -79,6 +140,106 @@ impl RepoService {
         Ok(repo)
     }
 
+    /// Describe repositories found by a directory scan, noting which are already registered
+    pub async fn describe_discovered(
+        &self,
+        pool: &SqlitePool,
+        git: &GitService,
+        paths: Vec<PathBuf>,
+    ) -> Result<Vec<DiscoveredRepo>> {
+        let registered = RepoModel::list_all(pool).await?;
+        Ok(paths
+            .into_iter()
+            .map(|path| DiscoveredRepo {
+                name: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "unnamed".to_string()),
+                default_branch: git.get_default_branch(&path).ok().flatten(),
+                remotes: git.list_remotes(&path).unwrap_or_default(),
+                registered_repo_id: registered.iter().find(|r| r.path == path).map(|r| r.id),
+                path,
+            })
+            .collect())
+    }
+
+    /// Check that a registered repo can still host workspaces: the path is a git repository,
+    /// its default target branch exists, its remotes answer and the worktree base directory
+    /// has room. Problems are reported as issues rather than errors.
+    pub async fn validate(&self, git: &GitService, repo: &RepoModel) -> RepoValidation {
+        let mut issues = Vec::new();
+        let mut issue = |kind, message: String| issues.push(RepoIssue { kind, message });
+
+        match self.validate_git_repo_path(&repo.path) {
+            Ok(()) => {
+                if let Some(branch) = &repo.default_target_branch
+                    && !git.check_branch_exists(&repo.path, branch).unwrap_or(false)
+                {
+                    issue(
+                        RepoIssueKind::DefaultBranchMissing,
+                        format!("Default target branch '{branch}' does not exist"),
+                    );
+                }
+
+                for remote in git.list_remotes(&repo.path).unwrap_or_default() {
+                    let check = tokio::task::spawn_blocking({
+                        let git = git.clone();
+                        let path = repo.path.clone();
+                        let name = remote.name.clone();
+                        move || git.check_remote_reachable(&path, &name)
+                    });
+                    let error = match tokio::time::timeout(REMOTE_CHECK_TIMEOUT, check).await {
+                        Ok(Ok(Ok(()))) => continue,
+                        Ok(Ok(Err(e))) => e.to_string(),
+                        Ok(Err(e)) => e.to_string(),
+                        Err(_) => format!("timed out after {}s", REMOTE_CHECK_TIMEOUT.as_secs()),
+                    };
+                    issue(
+                        RepoIssueKind::RemoteUnreachable,
+                        format!(
+                            "Remote '{}' ({}) is unreachable: {error}",
+                            remote.name, remote.url
+                        ),
+                    );
+                }
+            }
+            Err(RepoError::PathNotFound(path)) => issue(
+                RepoIssueKind::PathMissing,
+                format!("Path does not exist: {}", path.display()),
+            ),
+            Err(RepoError::PathNotDirectory(path)) => issue(
+                RepoIssueKind::NotADirectory,
+                format!("Path is not a directory: {}", path.display()),
+            ),
+            Err(e) => issue(RepoIssueKind::NotAGitRepository, e.to_string()),
+        }
+
+        let base_dir = WorkspaceManager::get_workspace_base_dir();
+        // The base directory is created lazily; measure the nearest existing ancestor
+        let measured = base_dir
+            .ancestors()
+            .find(|p| p.exists())
+            .unwrap_or(&base_dir);
+        if let Some(free) = available_space(measured)
+            && free < MIN_WORKTREE_FREE_BYTES
+        {
+            issue(
+                RepoIssueKind::LowDiskSpace,
+                format!(
+                    "Only {} MiB free in worktree directory {}",
+                    free / (1024 * 1024),
+                    base_dir.display()
+                ),
+            );
+        }
+
+        RepoValidation {
+            repo_id: repo.id,
+            healthy: issues.is_empty(),
+            issues,
+        }
+    }
+
     pub async fn find_by_id(&self, pool: &SqlitePool, repo_id: Uuid) -> Result<Option<RepoModel>> {
         let repo = RepoModel::find_by_id(pool, repo_id).await?;
         Ok(repo)
@@ -125,3 +286,101 @@ impl RepoService {
         Ok(repo)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::{init_repo, run_git, test_pool};
+
+    #[tokio::test]
+    async fn discovered_repos_report_their_branch_remotes_and_registration() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let upstream = dir.path().join("upstream");
+        init_repo(&upstream, "README.md", "hello\n");
+        run_git(&upstream, &["branch", "-m", "main", "trunk"]);
+        run_git(dir.path(), &["clone", "-q", "upstream", "api"]);
+        let clone = dir.path().join("api");
+        // The remote's HEAD wins over whatever happens to be checked out
+        run_git(&clone, &["checkout", "-q", "-b", "feature"]);
+        let local = dir.path().join("web");
+        init_repo(&local, "README.md", "hello\n");
+        let registered = RepoModel::find_or_create(&pool, &local, "web")
+            .await
+            .unwrap();
+
+        let discovered = RepoService
+            .describe_discovered(&pool, &GitService::new(), vec![clone, local])
+            .await
+            .unwrap();
+
+        assert_eq!(discovered[0].name, "api");
+        assert_eq!(discovered[0].default_branch.as_deref(), Some("trunk"));
+        assert_eq!(discovered[0].remotes.len(), 1);
+        assert_eq!(discovered[0].remotes[0].name, "origin");
+        assert!(discovered[0].remotes[0].url.ends_with("upstream"));
+        assert_eq!(discovered[0].registered_repo_id, None);
+
+        assert_eq!(discovered[1].name, "web");
+        assert_eq!(discovered[1].default_branch.as_deref(), Some("main"));
+        assert!(discovered[1].remotes.is_empty());
+        assert_eq!(discovered[1].registered_repo_id, Some(registered.id));
+    }
+
+    /// Issue kinds found by validating `repo`, ignoring the free space of the test machine
+    async fn issue_kinds(repo: &RepoModel) -> Vec<RepoIssueKind> {
+        let validation = RepoService.validate(&GitService::new(), repo).await;
+        assert_eq!(validation.repo_id, repo.id);
+        validation
+            .issues
+            .into_iter()
+            .map(|issue| issue.kind)
+            .filter(|kind| *kind != RepoIssueKind::LowDiskSpace)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn validation_reports_paths_that_cannot_host_workspaces() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "hello\n").unwrap();
+        let plain = dir.path().join("plain");
+        std::fs::create_dir_all(&plain).unwrap();
+
+        for (path, kind) in [
+            (missing, RepoIssueKind::PathMissing),
+            (file, RepoIssueKind::NotADirectory),
+            (plain, RepoIssueKind::NotAGitRepository),
+        ] {
+            let repo = RepoModel::find_or_create(&pool, &path, "api")
+                .await
+                .unwrap();
+            assert_eq!(issue_kinds(&repo).await, vec![kind], "{}", path.display());
+        }
+    }
+
+    #[tokio::test]
+    async fn validation_reports_missing_default_branches_and_dead_remotes() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api");
+        init_repo(&path, "README.md", "hello\n");
+        let mut repo = RepoModel::find_or_create(&pool, &path, "api")
+            .await
+            .unwrap();
+        assert!(issue_kinds(&repo).await.is_empty());
+
+        repo.default_target_branch = Some("develop".to_string());
+        let gone = dir.path().join("gone");
+        run_git(&path, &["remote", "add", "origin", &gone.to_string_lossy()]);
+        assert_eq!(
+            issue_kinds(&repo).await,
+            vec![
+                RepoIssueKind::DefaultBranchMissing,
+                RepoIssueKind::RemoteUnreachable
+            ]
+        );
+    }
+}
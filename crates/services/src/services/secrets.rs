@@ -0,0 +1,254 @@
+//! Encrypted secrets for execution process environments.
+//!
+//! Values are sealed with the server master key and only decrypted when a process starts.
+//! A process receives a secret only if its name is allowlisted for the task's project or for
+//! one of the workspace's repos.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use db::models::{secret::Secret, secret_allowlist::SecretAllowlist};
+use executors::env::is_user_env_var_name;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::master_key::{CipherError, MasterKey};
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Cipher(#[from] CipherError),
+    #[error("Invalid secret name '{0}': must be an env var name not starting with VK_")]
+    InvalidName(String),
+    #[error("Secret value must not be empty")]
+    EmptyValue,
+    #[error("A secret named '{0}' already exists")]
+    AlreadyExists(String),
+    #[error("Secret {0} not found")]
+    NotFound(Uuid),
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateSecret {
+    /// Environment variable the value is exposed as
+    pub name: String,
+    pub value: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateSecret {
+    /// New value; omitted keeps the current one
+    pub value: Option<String>,
+    /// New description; an empty string clears it
+    pub description: Option<String>,
+}
+
+/// What clients see of a stored secret; values are write-only
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SecretSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Secret> for SecretSummary {
+    fn from(secret: Secret) -> Self {
+        Self {
+            id: secret.id,
+            name: secret.name,
+            description: secret.description,
+            created_at: secret.created_at,
+            updated_at: secret.updated_at,
+        }
+    }
+}
+
+/// Secret names a project or repo may pass to its processes
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SecretAllowlistNames {
+    pub names: Vec<String>,
+}
+
+/// Whether `name` can be used as a secret's environment variable
+pub fn validate_secret_name(name: &str) -> Result<(), SecretError> {
+    if is_user_env_var_name(name) {
+        Ok(())
+    } else {
+        Err(SecretError::InvalidName(name.to_string()))
+    }
+}
+
+/// Validate, trim and deduplicate allowlist names
+fn normalize_allowlist(names: &[String]) -> Result<Vec<String>, SecretError> {
+    let names: BTreeSet<String> = names.iter().map(|n| n.trim().to_string()).collect();
+    for name in &names {
+        validate_secret_name(name)?;
+    }
+    Ok(names.into_iter().collect())
+}
+
+fn normalize_description(description: Option<&str>) -> Option<&str> {
+    description.map(str::trim).filter(|d| !d.is_empty())
+}
+
+pub struct SecretService {
+    key: MasterKey,
+}
+
+impl SecretService {
+    pub fn new() -> Result<Self, SecretError> {
+        Ok(Self {
+            key: MasterKey::load()?,
+        })
+    }
+
+    pub async fn list(pool: &SqlitePool) -> Result<Vec<SecretSummary>, SecretError> {
+        let secrets = Secret::find_all(pool).await?;
+        Ok(secrets.into_iter().map(SecretSummary::from).collect())
+    }
+
+    pub async fn create(
+        &self,
+        pool: &SqlitePool,
+        request: &CreateSecret,
+    ) -> Result<SecretSummary, SecretError> {
+        let name = request.name.trim();
+        validate_secret_name(name)?;
+        if request.value.is_empty() {
+            return Err(SecretError::EmptyValue);
+        }
+        if Secret::find_by_name(pool, name).await?.is_some() {
+            return Err(SecretError::AlreadyExists(name.to_string()));
+        }
+
+        let secret = Secret::create(
+            pool,
+            name,
+            &self.key.seal(&request.value)?,
+            normalize_description(request.description.as_deref()),
+        )
+        .await?;
+        Ok(secret.into())
+    }
+
+    pub async fn update(
+        &self,
+        pool: &SqlitePool,
+        id: Uuid,
+        request: &UpdateSecret,
+    ) -> Result<SecretSummary, SecretError> {
+        let existing = Secret::find_by_id(pool, id)
+            .await?
+            .ok_or(SecretError::NotFound(id))?;
+
+        let value = match &request.value {
+            Some(value) if value.is_empty() => return Err(SecretError::EmptyValue),
+            Some(value) => self.key.seal(value)?,
+            None => existing.value,
+        };
+        let description = match &request.description {
+            Some(description) => normalize_description(Some(description)),
+            None => existing.description.as_deref(),
+        };
+
+        let secret = Secret::update(pool, id, &value, description).await?;
+        Ok(secret.into())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), SecretError> {
+        if Secret::delete(pool, id).await? == 0 {
+            return Err(SecretError::NotFound(id));
+        }
+        Ok(())
+    }
+
+    pub async fn project_allowlist(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<SecretAllowlistNames, SecretError> {
+        let names = SecretAllowlist::find_for_project(pool, project_id).await?;
+        Ok(SecretAllowlistNames { names })
+    }
+
+    pub async fn set_project_allowlist(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        names: &[String],
+    ) -> Result<SecretAllowlistNames, SecretError> {
+        let names = normalize_allowlist(names)?;
+        SecretAllowlist::set_for_project(pool, project_id, &names).await?;
+        Ok(SecretAllowlistNames { names })
+    }
+
+    pub async fn repo_allowlist(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<SecretAllowlistNames, SecretError> {
+        let names = SecretAllowlist::find_for_repo(pool, repo_id).await?;
+        Ok(SecretAllowlistNames { names })
+    }
+
+    pub async fn set_repo_allowlist(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        names: &[String],
+    ) -> Result<SecretAllowlistNames, SecretError> {
+        let names = normalize_allowlist(names)?;
+        SecretAllowlist::set_for_repo(pool, repo_id, &names).await?;
+        Ok(SecretAllowlistNames { names })
+    }
+
+    /// Decrypted secrets allowlisted for the project or any repo of the workspace, keyed by
+    /// environment variable name
+    pub async fn env_for_workspace(
+        &self,
+        pool: &SqlitePool,
+        project_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<HashMap<String, String>, SecretError> {
+        let mut env = HashMap::new();
+        for secret in Secret::find_allowed_for_workspace(pool, project_id, workspace_id).await? {
+            match self.key.open(&secret.value) {
+                Ok(value) => {
+                    env.insert(secret.name, value);
+                }
+                Err(e) => tracing::warn!("Skipping secret {}: {}", secret.name, e),
+            }
+        }
+        Ok(env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_names_must_be_unreserved_env_vars() {
+        for name in ["OPENAI_API_KEY", "_token", "key2"] {
+            assert!(validate_secret_name(name).is_ok(), "{name}");
+        }
+        for name in ["", "2FA", "MY-KEY", "A B", "VK_PROJECT_ID", "vk_token"] {
+            assert!(validate_secret_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn allowlists_are_trimmed_and_deduplicated() {
+        let names = ["B_KEY", " A_KEY ", "B_KEY"].map(String::from);
+        assert_eq!(normalize_allowlist(&names).unwrap(), ["A_KEY", "B_KEY"]);
+        assert!(normalize_allowlist(&["BAD NAME".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,571 @@
+//! Server settings: operational toggles that used to be read from environment variables.
+//!
+//! Settings are stored in the database. On first start they are seeded from `settings.json`
+//! in the asset directory when it exists, otherwise from the legacy environment variables,
+//! and afterwards they are changed at runtime through the settings API.
+//!
+//! Changes take effect without a restart: the service publishes every update on a watch
+//! channel, and long-running tasks that depend on a setting subscribe to it.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use db::models::setting::Setting;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::sync::watch;
+use ts_rs::TS;
+use url::Url;
+use utils::assets::settings_bootstrap_path;
+
+use super::{
+    object_storage::{ObjectStorageError, ObjectStore},
+    worktree_manager::WorktreeManager,
+};
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Malformed settings: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid settings: {0}")]
+    Invalid(String),
+}
+
+const DEFAULT_WORKSPACE_RETENTION_HOURS: u32 = 72;
+const DEFAULT_ARCHIVED_WORKSPACE_RETENTION_HOURS: u32 = 1;
+const DEFAULT_LOG_RETENTION_KEEP_ENTRIES: u32 = 200;
+const DEFAULT_ORPHAN_CLEANUP_INTERVAL_HOURS: u32 = 6;
+const DEFAULT_ORPHAN_CLEANUP_GRACE_HOURS: u32 = 1;
+const DEFAULT_OBJECT_STORAGE_REGION: &str = "us-east-1";
+/// API keys of common providers: OpenAI and Anthropic, GitHub, AWS access keys and Slack
+const DEFAULT_OUTPUT_REDACTION_PATTERNS: &[&str] = &[
+    r"sk-[A-Za-z0-9_-]{20,}",
+    r"gh[pousr]_[A-Za-z0-9]{36,}",
+    r"github_pat_[A-Za-z0-9_]{22,}",
+    r"AKIA[0-9A-Z]{16}",
+    r"xox[abprs]-[A-Za-z0-9-]{10,}",
+];
+
+/// What the log retention job does with a finished process's output once it expires
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRetentionPolicy {
+    /// Remove the output
+    Truncate,
+    /// Keep the first and last entries and remove the rest
+    #[default]
+    HeadTail,
+    /// Move the output to a JSONL file in the archive directory
+    Archive,
+}
+
+impl LogRetentionPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Truncate => "truncate",
+            Self::HeadTail => "head_tail",
+            Self::Archive => "archive",
+        }
+    }
+}
+
+fn default_object_storage_region() -> String {
+    DEFAULT_OBJECT_STORAGE_REGION.to_string()
+}
+
+/// S3-compatible bucket that artifacts and archived logs are uploaded to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct ObjectStorageSettings {
+    pub bucket: String,
+    /// Endpoint of an S3-compatible service such as MinIO or R2; unset uses AWS
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default = "default_object_storage_region")]
+    pub region: String,
+    /// Prepended to the key of every object, to share a bucket between servers
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct Settings {
+    /// Never remove expired or orphaned workspaces; replaces `DISABLE_WORKTREE_CLEANUP`
+    pub disable_worktree_cleanup: bool,
+    /// Keep orphaned workspaces that still hold git worktrees so they can be adopted;
+    /// replaces `RECONCILE_ORPHAN_WORKTREES`
+    pub reconcile_orphan_worktrees: bool,
+    /// Directory new workspaces are created under; unset uses the system temp directory
+    pub worktree_base_dir: Option<String>,
+    /// Hours an idle workspace of an in-progress or in-review task is kept before cleanup
+    pub workspace_retention_hours: u32,
+    /// Hours an idle archived workspace, or one whose task is not active, is kept
+    pub archived_workspace_retention_hours: u32,
+    /// Most coding agent runs allowed at once; unset means no limit
+    pub max_concurrent_agents: Option<u32>,
+    /// URLs that receive a JSON POST for every notification, alongside sound and push
+    pub notification_webhooks: Vec<String>,
+    /// Monthly agent spend in US dollars that triggers a notification once exceeded;
+    /// counts only agents that report their cost
+    pub monthly_budget_usd: Option<u32>,
+    /// Days a finished process's output is stored in full; unset keeps it forever
+    pub log_retention_days: Option<u32>,
+    /// How output older than the retention period is compacted
+    pub log_retention_policy: LogRetentionPolicy,
+    /// Output entries kept at each end by the head and tail policy
+    pub log_retention_keep_entries: u32,
+    /// Directory the archive policy writes output to, such as a mounted object store
+    pub log_archive_dir: Option<String>,
+    /// Hours between sweeps for orphaned workspace directories; the first runs at startup
+    pub orphan_cleanup_interval_hours: u32,
+    /// Orphaned directories modified within this many hours are left for the next sweep
+    pub orphan_cleanup_grace_hours: u32,
+    /// Directory names or absolute paths under the workspace directories that are never
+    /// removed as orphans
+    pub orphan_cleanup_exclusions: Vec<String>,
+    /// Recreate or repair missing and broken worktrees when the status or diff of their
+    /// workspace is requested, recording each repair as a workspace event
+    pub auto_repair_worktrees: bool,
+    /// Directory collected execution artifacts are stored under, such as a mounted object
+    /// store; unset uses `artifacts` in the app's data directory
+    pub artifacts_dir: Option<String>,
+    /// Bucket artifacts and archived logs are stored in instead of the artifacts and log
+    /// archive directories; credentials are read from `AWS_ACCESS_KEY_ID` and
+    /// `AWS_SECRET_ACCESS_KEY`
+    pub object_storage: Option<ObjectStorageSettings>,
+    /// Regular expressions masked in process output before it is stored or streamed,
+    /// alongside the values of the secrets the process was given
+    pub output_redaction_patterns: Vec<String>,
+    /// Commits a workspace's target branch may gain past their merge base before a
+    /// notification suggests rebasing it; unset turns the check off
+    pub target_drift_threshold_commits: Option<u32>,
+    /// Minutes between fetches of the remotes of every registered repo that has not opted
+    /// out; unset turns scheduled fetching off
+    pub repo_fetch_interval_minutes: Option<u32>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            disable_worktree_cleanup: false,
+            reconcile_orphan_worktrees: false,
+            worktree_base_dir: None,
+            workspace_retention_hours: DEFAULT_WORKSPACE_RETENTION_HOURS,
+            archived_workspace_retention_hours: DEFAULT_ARCHIVED_WORKSPACE_RETENTION_HOURS,
+            max_concurrent_agents: None,
+            notification_webhooks: Vec::new(),
+            monthly_budget_usd: None,
+            log_retention_days: None,
+            log_retention_policy: LogRetentionPolicy::default(),
+            log_retention_keep_entries: DEFAULT_LOG_RETENTION_KEEP_ENTRIES,
+            log_archive_dir: None,
+            orphan_cleanup_interval_hours: DEFAULT_ORPHAN_CLEANUP_INTERVAL_HOURS,
+            orphan_cleanup_grace_hours: DEFAULT_ORPHAN_CLEANUP_GRACE_HOURS,
+            orphan_cleanup_exclusions: Vec::new(),
+            auto_repair_worktrees: false,
+            artifacts_dir: None,
+            object_storage: None,
+            output_redaction_patterns: DEFAULT_OUTPUT_REDACTION_PATTERNS
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect(),
+            target_drift_threshold_commits: None,
+            repo_fetch_interval_minutes: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Defaults for a server without stored settings, honouring the legacy environment
+    /// variables and the workspace directory from the config
+    fn from_legacy(workspace_dir: Option<&str>) -> Self {
+        Self {
+            disable_worktree_cleanup: std::env::var("DISABLE_WORKTREE_CLEANUP").is_ok(),
+            reconcile_orphan_worktrees: std::env::var("RECONCILE_ORPHAN_WORKTREES").is_ok(),
+            worktree_base_dir: workspace_dir.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    /// Build settings from stored rows; unknown keys are ignored and missing ones defaulted
+    fn from_rows(rows: Vec<Setting>) -> Result<Self, SettingsError> {
+        let map: Map<String, Value> = rows.into_iter().map(|row| (row.key, row.value)).collect();
+        Ok(serde_json::from_value(Value::Object(map))?)
+    }
+
+    fn to_rows(&self) -> Result<Vec<Setting>, SettingsError> {
+        let Value::Object(map) = serde_json::to_value(self)? else {
+            unreachable!("settings serialize to an object");
+        };
+        Ok(map
+            .into_iter()
+            .map(|(key, value)| Setting { key, value })
+            .collect())
+    }
+
+    fn validate(&self) -> Result<(), SettingsError> {
+        if let Some(dir) = &self.worktree_base_dir {
+            let path = utils::path::expand_tilde(dir);
+            if !path.is_absolute() {
+                return Err(SettingsError::Invalid(format!(
+                    "Worktree base directory must be an absolute path: {dir}"
+                )));
+            }
+        }
+        if self.workspace_retention_hours == 0 || self.archived_workspace_retention_hours == 0 {
+            return Err(SettingsError::Invalid(
+                "Workspace retention must be at least one hour".to_string(),
+            ));
+        }
+        if self.orphan_cleanup_interval_hours == 0 {
+            return Err(SettingsError::Invalid(
+                "Orphan cleanup interval must be at least one hour".to_string(),
+            ));
+        }
+        if self.max_concurrent_agents == Some(0) {
+            return Err(SettingsError::Invalid(
+                "Concurrent agent limit must be at least one; leave it unset for no limit"
+                    .to_string(),
+            ));
+        }
+        if self.monthly_budget_usd == Some(0) {
+            return Err(SettingsError::Invalid(
+                "Monthly budget must be at least one dollar; leave it unset for no budget"
+                    .to_string(),
+            ));
+        }
+        if self.target_drift_threshold_commits == Some(0) {
+            return Err(SettingsError::Invalid(
+                "Target drift threshold must be at least one commit; leave it unset to turn the \
+                 check off"
+                    .to_string(),
+            ));
+        }
+        if self.repo_fetch_interval_minutes == Some(0) {
+            return Err(SettingsError::Invalid(
+                "Repo fetch interval must be at least one minute; leave it unset to turn \
+                 scheduled fetching off"
+                    .to_string(),
+            ));
+        }
+        if self.log_retention_days == Some(0) {
+            return Err(SettingsError::Invalid(
+                "Log retention must be at least one day; leave it unset to keep logs forever"
+                    .to_string(),
+            ));
+        }
+        match (&self.log_archive_dir, self.log_retention_policy) {
+            (Some(dir), _) if !utils::path::expand_tilde(dir).is_absolute() => {
+                return Err(SettingsError::Invalid(format!(
+                    "Log archive directory must be an absolute path: {dir}"
+                )));
+            }
+            (None, LogRetentionPolicy::Archive) if self.object_storage.is_none() => {
+                return Err(SettingsError::Invalid(
+                    "The archive log retention policy needs a log archive directory or object storage"
+                        .to_string(),
+                ));
+            }
+            _ => {}
+        }
+        if let Some(dir) = &self.artifacts_dir
+            && !utils::path::expand_tilde(dir).is_absolute()
+        {
+            return Err(SettingsError::Invalid(format!(
+                "Artifacts directory must be an absolute path: {dir}"
+            )));
+        }
+        if let Some(storage) = &self.object_storage {
+            if storage.bucket.trim().is_empty() || storage.region.trim().is_empty() {
+                return Err(SettingsError::Invalid(
+                    "Object storage needs a bucket and a region".to_string(),
+                ));
+            }
+            if let Some(endpoint) = &storage.endpoint
+                && !Url::parse(endpoint).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+            {
+                return Err(SettingsError::Invalid(format!(
+                    "Object storage endpoint must be an http(s) URL: {endpoint}"
+                )));
+            }
+        }
+        for pattern in &self.output_redaction_patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(SettingsError::Invalid(format!(
+                    "Output redaction pattern is not a valid regular expression: {pattern}: {e}"
+                )));
+            }
+        }
+        for webhook in &self.notification_webhooks {
+            let valid = Url::parse(webhook)
+                .map(|url| matches!(url.scheme(), "http" | "https"))
+                .unwrap_or(false);
+            if !valid {
+                return Err(SettingsError::Invalid(format!(
+                    "Notification webhook must be an http(s) URL: {webhook}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn worktree_base_path(&self) -> Option<PathBuf> {
+        self.worktree_base_dir
+            .as_deref()
+            .map(utils::path::expand_tilde)
+    }
+
+    /// Directory artifacts are stored under when no object storage is configured
+    pub fn artifacts_path(&self) -> PathBuf {
+        self.artifacts_dir
+            .as_deref()
+            .map(utils::path::expand_tilde)
+            .unwrap_or_else(|| utils::assets::asset_dir().join("artifacts"))
+    }
+
+    /// Make the settings take effect in components that read them outside the service
+    fn apply(&self) {
+        WorktreeManager::set_workspace_dir_override(self.worktree_base_path());
+    }
+}
+
+fn read_bootstrap_file(path: &Path) -> Result<Option<Settings>, SettingsError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Clone)]
+pub struct SettingsService {
+    pool: SqlitePool,
+    current: Arc<watch::Sender<Settings>>,
+}
+
+impl SettingsService {
+    /// Load the stored settings, seeding them on first start, and apply them
+    pub async fn load(
+        pool: SqlitePool,
+        legacy_workspace_dir: Option<&str>,
+    ) -> Result<Self, SettingsError> {
+        let rows = Setting::find_all(&pool).await?;
+        let settings = if rows.is_empty() {
+            let bootstrap_path = settings_bootstrap_path();
+            let settings = match read_bootstrap_file(&bootstrap_path)? {
+                Some(settings) => {
+                    tracing::info!("Seeding settings from {}", bootstrap_path.display());
+                    settings
+                }
+                None => Settings::from_legacy(legacy_workspace_dir),
+            };
+            settings.validate()?;
+            Setting::upsert_all(&pool, &settings.to_rows()?).await?;
+            settings
+        } else {
+            Settings::from_rows(rows)?
+        };
+
+        settings.apply();
+        Ok(Self {
+            pool,
+            current: Arc::new(watch::Sender::new(settings)),
+        })
+    }
+
+    pub fn get(&self) -> Settings {
+        self.current.borrow().clone()
+    }
+
+    /// Receiver that observes every settings update, for services that react to changes
+    pub fn subscribe(&self) -> watch::Receiver<Settings> {
+        self.current.subscribe()
+    }
+
+    /// Replace all settings; they take effect immediately and subscribers are notified
+    pub async fn update(&self, settings: Settings) -> Result<Settings, SettingsError> {
+        settings.validate()?;
+        Setting::upsert_all(&self.pool, &settings.to_rows()?).await?;
+        settings.apply();
+        self.current.send_replace(settings.clone());
+        Ok(settings)
+    }
+
+    pub fn disable_worktree_cleanup(&self) -> bool {
+        self.current.borrow().disable_worktree_cleanup
+    }
+
+    pub fn reconcile_orphan_worktrees(&self) -> bool {
+        self.current.borrow().reconcile_orphan_worktrees
+    }
+
+    pub fn worktree_base_dir(&self) -> Option<PathBuf> {
+        self.current.borrow().worktree_base_path()
+    }
+
+    pub fn max_concurrent_agents(&self) -> Option<u32> {
+        self.current.borrow().max_concurrent_agents
+    }
+
+    pub fn monthly_budget_usd(&self) -> Option<u32> {
+        self.current.borrow().monthly_budget_usd
+    }
+
+    pub fn target_drift_threshold_commits(&self) -> Option<u32> {
+        self.current.borrow().target_drift_threshold_commits
+    }
+
+    pub fn repo_fetch_interval_minutes(&self) -> Option<u32> {
+        self.current.borrow().repo_fetch_interval_minutes
+    }
+
+    pub fn auto_repair_worktrees(&self) -> bool {
+        self.current.borrow().auto_repair_worktrees
+    }
+
+    pub fn output_redaction_patterns(&self) -> Vec<String> {
+        self.current.borrow().output_redaction_patterns.clone()
+    }
+
+    pub fn artifact_store(&self) -> Result<ObjectStore, ObjectStorageError> {
+        ObjectStore::artifacts(&self.current.borrow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_round_trip_and_tolerate_unknown_or_missing_keys() {
+        let settings = Settings {
+            disable_worktree_cleanup: true,
+            reconcile_orphan_worktrees: false,
+            worktree_base_dir: Some("/srv/workspaces".to_string()),
+            workspace_retention_hours: 24,
+            max_concurrent_agents: Some(2),
+            ..Default::default()
+        };
+        let rows = settings.to_rows().unwrap();
+        assert_eq!(rows.len(), 19);
+        assert_eq!(Settings::from_rows(rows).unwrap(), settings);
+
+        let rows = vec![
+            Setting {
+                key: "reconcile_orphan_worktrees".to_string(),
+                value: Value::Bool(true),
+            },
+            Setting {
+                key: "removed_setting".to_string(),
+                value: Value::from(1),
+            },
+        ];
+        let settings = Settings::from_rows(rows).unwrap();
+        assert!(settings.reconcile_orphan_worktrees);
+        assert!(!settings.disable_worktree_cleanup);
+        assert_eq!(
+            settings.workspace_retention_hours,
+            DEFAULT_WORKSPACE_RETENTION_HOURS
+        );
+    }
+
+    #[test]
+    fn relative_worktree_base_dir_is_rejected() {
+        let settings = Settings {
+            worktree_base_dir: Some("workspaces".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            settings.validate(),
+            Err(SettingsError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn limits_and_webhooks_are_validated() {
+        let invalid = [
+            Settings {
+                workspace_retention_hours: 0,
+                ..Default::default()
+            },
+            Settings {
+                orphan_cleanup_interval_hours: 0,
+                ..Default::default()
+            },
+            Settings {
+                max_concurrent_agents: Some(0),
+                ..Default::default()
+            },
+            Settings {
+                notification_webhooks: vec!["ftp://example.com/hook".to_string()],
+                ..Default::default()
+            },
+            Settings {
+                monthly_budget_usd: Some(0),
+                ..Default::default()
+            },
+            Settings {
+                target_drift_threshold_commits: Some(0),
+                ..Default::default()
+            },
+            Settings {
+                repo_fetch_interval_minutes: Some(0),
+                ..Default::default()
+            },
+            Settings {
+                log_retention_policy: LogRetentionPolicy::Archive,
+                ..Default::default()
+            },
+            Settings {
+                log_archive_dir: Some("logs".to_string()),
+                ..Default::default()
+            },
+            Settings {
+                artifacts_dir: Some("artifacts".to_string()),
+                ..Default::default()
+            },
+            Settings {
+                object_storage: Some(ObjectStorageSettings {
+                    bucket: "outputs".to_string(),
+                    endpoint: Some("minio:9000".to_string()),
+                    region: default_object_storage_region(),
+                    prefix: None,
+                }),
+                ..Default::default()
+            },
+            Settings {
+                output_redaction_patterns: vec!["sk-[".to_string()],
+                ..Default::default()
+            },
+        ];
+        for settings in invalid {
+            assert!(settings.validate().is_err(), "{settings:?}");
+        }
+
+        let settings = Settings {
+            max_concurrent_agents: Some(3),
+            notification_webhooks: vec!["https://hooks.example.com/vk".to_string()],
+            ..Default::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        let settings: Settings = serde_json::from_value(serde_json::json!({
+            "log_retention_policy": "archive",
+            "object_storage": { "bucket": "outputs", "endpoint": "http://minio:9000" }
+        }))
+        .unwrap();
+        assert_eq!(
+            settings.object_storage.as_ref().unwrap().region,
+            DEFAULT_OBJECT_STORAGE_REGION
+        );
+        assert!(settings.validate().is_ok());
+    }
+}
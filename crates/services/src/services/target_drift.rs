@@ -0,0 +1,193 @@
+//! How far the target branches of open workspaces have moved on.
+//!
+//! A workspace branch is merged into a target branch that keeps gaining commits while the
+//! workspace is worked on. The drift of a repo is the number of commits the target branch has
+//! that the workspace branch does not, i.e. those made since their merge base. A periodic
+//! check records a workspace event once a repo's drift reaches the configured threshold, so
+//! the workspace can be rebased before the gap grows; a repo is reported again only after a
+//! rebase brought it back under the threshold.
+
+use std::collections::HashSet;
+
+use db::models::{
+    workspace::Workspace,
+    workspace_event::{WorkspaceEvent, WorkspaceEventKind},
+    workspace_repo::{RepoWithTargetBranch, WorkspaceRepo},
+};
+use git::GitService;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct RepoTargetDrift {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub target_branch: String,
+    /// Commits on the target branch since its merge base with the workspace branch
+    pub commits_behind: usize,
+}
+
+/// A repo of an open workspace whose drift just reached the threshold
+#[derive(Debug, Clone)]
+pub struct DriftAlert {
+    pub workspace_id: Uuid,
+    pub workspace_branch: String,
+    pub drift: RepoTargetDrift,
+}
+
+/// Drift of each repo of a workspace on `branch`. Repos whose branches cannot be compared,
+/// such as a target branch that was deleted, are left out.
+pub fn workspace_drift(
+    git: &GitService,
+    branch: &str,
+    repos: &[RepoWithTargetBranch],
+) -> Vec<RepoTargetDrift> {
+    repos
+        .iter()
+        .filter_map(|repo| {
+            match git.get_branch_status(&repo.repo.path, branch, &repo.target_branch) {
+                Ok((_, commits_behind)) => Some(RepoTargetDrift {
+                    repo_id: repo.repo.id,
+                    repo_name: repo.repo.name.clone(),
+                    target_branch: repo.target_branch.clone(),
+                    commits_behind,
+                }),
+                Err(e) => {
+                    tracing::debug!(
+                        "Cannot compare '{}' with '{}' in repo '{}': {}",
+                        branch,
+                        repo.target_branch,
+                        repo.repo.name,
+                        e
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Remembers which repos have been reported, so each crossing is reported once
+#[derive(Debug, Default)]
+pub struct TargetDriftTracker {
+    reported: HashSet<(Uuid, Uuid)>,
+}
+
+impl TargetDriftTracker {
+    /// Drifts of a workspace at or over `threshold` that were not reported before. Repos back
+    /// under the threshold are forgotten, so they are reported if they drift again.
+    fn newly_drifted<'a>(
+        &mut self,
+        workspace_id: Uuid,
+        drifts: &'a [RepoTargetDrift],
+        threshold: u32,
+    ) -> Vec<&'a RepoTargetDrift> {
+        let threshold = threshold as usize;
+        drifts
+            .iter()
+            .filter(|drift| {
+                let key = (workspace_id, drift.repo_id);
+                if drift.commits_behind >= threshold {
+                    self.reported.insert(key)
+                } else {
+                    self.reported.remove(&key);
+                    false
+                }
+            })
+            .collect()
+    }
+
+    /// Check every open workspace, recording a workspace event for each repo whose drift
+    /// reached `threshold` since the last check
+    pub async fn check(
+        &mut self,
+        pool: &SqlitePool,
+        git: &GitService,
+        threshold: u32,
+    ) -> Result<Vec<DriftAlert>, sqlx::Error> {
+        let workspaces: Vec<_> = Workspace::find_all_with_status(pool, Some(false), None)
+            .await?
+            .into_iter()
+            .filter(|workspace| workspace.container_ref.is_some())
+            .collect();
+        let open: HashSet<Uuid> = workspaces.iter().map(|workspace| workspace.id).collect();
+        self.reported
+            .retain(|(workspace_id, _)| open.contains(workspace_id));
+
+        let mut alerts = Vec::new();
+        for workspace in workspaces {
+            let repos =
+                WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id)
+                    .await?;
+            let drifts = workspace_drift(git, &workspace.branch, &repos);
+            for drift in self.newly_drifted(workspace.id, &drifts, threshold) {
+                WorkspaceEvent::create(
+                    pool,
+                    workspace.id,
+                    WorkspaceEventKind::TargetBranchDrifted,
+                    &format!(
+                        "Target branch '{}' of repo '{}' has {} commits since the workspace \
+                         branched off it; rebase to keep up",
+                        drift.target_branch, drift.repo_name, drift.commits_behind
+                    ),
+                )
+                .await?;
+                alerts.push(DriftAlert {
+                    workspace_id: workspace.id,
+                    workspace_branch: workspace.branch.clone(),
+                    drift: drift.clone(),
+                });
+            }
+        }
+        Ok(alerts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drift(repo_id: Uuid, commits_behind: usize) -> RepoTargetDrift {
+        RepoTargetDrift {
+            repo_id,
+            repo_name: "api".to_string(),
+            target_branch: "main".to_string(),
+            commits_behind,
+        }
+    }
+
+    #[test]
+    fn each_crossing_is_reported_once() {
+        let mut tracker = TargetDriftTracker::default();
+        let workspace = Uuid::new_v4();
+        let repo = Uuid::new_v4();
+
+        assert!(
+            tracker
+                .newly_drifted(workspace, &[drift(repo, 4)], 5)
+                .is_empty()
+        );
+        assert_eq!(
+            tracker.newly_drifted(workspace, &[drift(repo, 5)], 5).len(),
+            1
+        );
+        assert!(
+            tracker
+                .newly_drifted(workspace, &[drift(repo, 9)], 5)
+                .is_empty()
+        );
+
+        // A rebase brings it back under the threshold, so the next drift is new again
+        assert!(
+            tracker
+                .newly_drifted(workspace, &[drift(repo, 0)], 5)
+                .is_empty()
+        );
+        assert_eq!(
+            tracker.newly_drifted(workspace, &[drift(repo, 6)], 5).len(),
+            1
+        );
+    }
+}
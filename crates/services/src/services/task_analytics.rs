@@ -0,0 +1,269 @@
+//! Flow metrics for a project's board, computed from the task status history.
+//!
+//! Lead time runs from a task's creation to its completion and cycle time from the first move
+//! to in progress to completion. A task is completed when its latest status is done, at the
+//! time it last moved there, so reopened tasks count only once they are done again.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use db::models::{task::TaskStatus, task_status_transition::TaskStatusTransition};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+pub const DEFAULT_THROUGHPUT_WEEKS: u32 = 12;
+pub const MAX_THROUGHPUT_WEEKS: u32 = 104;
+
+/// Distribution of a duration over the project's completed tasks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, TS)]
+pub struct DurationSummary {
+    /// Completed tasks the summary covers
+    pub tasks: usize,
+    pub average_hours: Option<f64>,
+    pub median_hours: Option<f64>,
+    /// 85th percentile; most tasks finish within this
+    pub p85_hours: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct WeeklyThroughput {
+    /// Monday the week starts on
+    #[ts(type = "string")]
+    pub week_start: NaiveDate,
+    /// Tasks completed during the week
+    pub completed: usize,
+}
+
+#[derive(Debug, Default)]
+struct TaskTimeline {
+    created_at: Option<DateTime<Utc>>,
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+/// Reduce transitions, oldest first, to one timeline per task
+fn timelines(transitions: &[TaskStatusTransition]) -> Vec<TaskTimeline> {
+    let mut by_task: HashMap<Uuid, TaskTimeline> = HashMap::new();
+    for transition in transitions {
+        let timeline = by_task.entry(transition.task_id).or_default();
+        if transition.from_status.is_none() {
+            timeline.created_at = Some(transition.changed_at);
+        }
+        if transition.to_status == TaskStatus::InProgress && timeline.started_at.is_none() {
+            timeline.started_at = Some(transition.changed_at);
+        }
+        timeline.completed_at =
+            (transition.to_status == TaskStatus::Done).then_some(transition.changed_at);
+    }
+    by_task.into_values().collect()
+}
+
+fn hours_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_seconds().max(0) as f64 / 3600.0
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn summarize_durations(mut hours: Vec<f64>) -> DurationSummary {
+    if hours.is_empty() {
+        return DurationSummary::default();
+    }
+    hours.sort_by(f64::total_cmp);
+    DurationSummary {
+        tasks: hours.len(),
+        average_hours: Some(hours.iter().sum::<f64>() / hours.len() as f64),
+        median_hours: Some(percentile(&hours, 0.5)),
+        p85_hours: Some(percentile(&hours, 0.85)),
+    }
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday().into())
+}
+
+/// Completions per week for the `weeks` weeks up to and including the one containing `now`,
+/// oldest first
+fn weekly_throughput(
+    completions: impl IntoIterator<Item = DateTime<Utc>>,
+    now: DateTime<Utc>,
+    weeks: u32,
+) -> Vec<WeeklyThroughput> {
+    let current = week_start(now.date_naive());
+    let mut buckets: Vec<WeeklyThroughput> = (0..weeks)
+        .rev()
+        .map(|ago| WeeklyThroughput {
+            week_start: current - Duration::weeks(ago.into()),
+            completed: 0,
+        })
+        .collect();
+    for completed_at in completions {
+        let week = week_start(completed_at.date_naive());
+        if let Some(bucket) = buckets.iter_mut().find(|b| b.week_start == week) {
+            bucket.completed += 1;
+        }
+    }
+    buckets
+}
+
+pub struct TaskAnalyticsService;
+
+impl TaskAnalyticsService {
+    async fn project_timelines(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<TaskTimeline>, sqlx::Error> {
+        let transitions = TaskStatusTransition::find_by_project_id(pool, project_id).await?;
+        Ok(timelines(&transitions))
+    }
+
+    pub async fn cycle_time(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<DurationSummary, sqlx::Error> {
+        let hours = Self::project_timelines(pool, project_id)
+            .await?
+            .into_iter()
+            .filter_map(|t| Some(hours_between(t.started_at?, t.completed_at?)))
+            .collect();
+        Ok(summarize_durations(hours))
+    }
+
+    pub async fn lead_time(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<DurationSummary, sqlx::Error> {
+        let hours = Self::project_timelines(pool, project_id)
+            .await?
+            .into_iter()
+            .filter_map(|t| Some(hours_between(t.created_at?, t.completed_at?)))
+            .collect();
+        Ok(summarize_durations(hours))
+    }
+
+    pub async fn throughput(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        weeks: u32,
+    ) -> Result<Vec<WeeklyThroughput>, sqlx::Error> {
+        let completions = Self::project_timelines(pool, project_id)
+            .await?
+            .into_iter()
+            .filter_map(|t| t.completed_at);
+        Ok(weekly_throughput(
+            completions,
+            Utc::now(),
+            weeks.clamp(1, MAX_THROUGHPUT_WEEKS),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn transition(
+        task_id: Uuid,
+        from: Option<TaskStatus>,
+        to: TaskStatus,
+        changed_at: DateTime<Utc>,
+    ) -> TaskStatusTransition {
+        TaskStatusTransition {
+            task_id,
+            from_status: from,
+            to_status: to,
+            changed_at,
+        }
+    }
+
+    #[test]
+    fn timelines_use_first_start_and_final_completion() {
+        let task = Uuid::new_v4();
+        let transitions = [
+            transition(task, None, TaskStatus::Todo, at(2, 9)),
+            transition(
+                task,
+                Some(TaskStatus::Todo),
+                TaskStatus::InProgress,
+                at(2, 12),
+            ),
+            transition(
+                task,
+                Some(TaskStatus::InProgress),
+                TaskStatus::Done,
+                at(3, 9),
+            ),
+            transition(
+                task,
+                Some(TaskStatus::Done),
+                TaskStatus::InProgress,
+                at(3, 10),
+            ),
+            transition(
+                task,
+                Some(TaskStatus::InProgress),
+                TaskStatus::Done,
+                at(4, 12),
+            ),
+        ];
+        let timeline = timelines(&transitions);
+        assert_eq!(timeline.len(), 1);
+        assert_eq!(timeline[0].created_at, Some(at(2, 9)));
+        assert_eq!(timeline[0].started_at, Some(at(2, 12)));
+        assert_eq!(timeline[0].completed_at, Some(at(4, 12)));
+
+        let reopened = [
+            transition(task, None, TaskStatus::Todo, at(2, 9)),
+            transition(task, Some(TaskStatus::Todo), TaskStatus::Done, at(2, 10)),
+            transition(
+                task,
+                Some(TaskStatus::Done),
+                TaskStatus::InReview,
+                at(2, 11),
+            ),
+        ];
+        assert_eq!(timelines(&reopened)[0].completed_at, None);
+    }
+
+    #[test]
+    fn durations_are_summarized_with_nearest_rank_percentiles() {
+        let summary = summarize_durations(vec![10.0, 1.0, 4.0, 2.0]);
+        assert_eq!(summary.tasks, 4);
+        assert_eq!(summary.average_hours, Some(4.25));
+        assert_eq!(summary.median_hours, Some(2.0));
+        assert_eq!(summary.p85_hours, Some(10.0));
+        assert_eq!(summarize_durations(vec![]), DurationSummary::default());
+    }
+
+    #[test]
+    fn throughput_buckets_completions_by_monday_week() {
+        // 2026-03-04 is a Wednesday; its week starts on Monday 2026-03-02
+        let now = at(4, 12);
+        let completions = [at(2, 0), at(4, 9), at(1, 23), at(20, 0)];
+        let weeks = weekly_throughput(completions, now, 2);
+        assert_eq!(
+            weeks,
+            vec![
+                WeeklyThroughput {
+                    week_start: NaiveDate::from_ymd_opt(2026, 2, 23).unwrap(),
+                    completed: 1,
+                },
+                WeeklyThroughput {
+                    week_start: NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(),
+                    completed: 2,
+                },
+            ]
+        );
+    }
+}
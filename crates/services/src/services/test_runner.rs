@@ -0,0 +1,284 @@
+use std::{collections::HashSet, path::Path, sync::LazyLock};
+
+use db::models::{
+    execution_process::ExecutionProcessRunReason,
+    repo::Repo,
+    workspace::Workspace,
+    workspace_test_run::{RepoTestResult, TestFramework, WorkspaceTestRun},
+};
+use executors::actions::script::ScriptContext;
+use regex::Regex;
+use sqlx::SqlitePool;
+use tracing::info;
+
+use super::{
+    output_redaction::OutputRedactor,
+    recorded_command::{DEFAULT_COMMAND_TIMEOUT, RecordedCommandError, RecordedCommandService},
+};
+
+static ANSI_ESCAPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").unwrap());
+static CARGO_SUMMARY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"test result: \w+\. (\d+) passed; (\d+) failed; (\d+) ignored").unwrap()
+});
+static CARGO_FAILED_CASE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^test (\S+) \.\.\. FAILED$").unwrap());
+static JEST_SUMMARY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^Tests:\s+(.+)$").unwrap());
+static JEST_FAILED_CASE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*● (.+)$").unwrap());
+static PYTEST_SUMMARY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^=+ (.+) in [\d.]+s.*=+$").unwrap());
+static PYTEST_FAILED_CASE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:FAILED|ERROR) (\S+)").unwrap());
+static SUMMARY_COUNT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d+) ([a-z]+)").unwrap());
+
+/// Counts and failing cases extracted from test runner output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedTestOutput {
+    pub framework: TestFramework,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub failed_cases: Vec<String>,
+}
+
+pub struct TestRunnerService;
+
+impl TestRunnerService {
+    /// Run the test script of every repo that has one in the workspace worktrees and
+    /// store the parsed results as a new test run. Their output is masked by `redactor`
+    /// before it is stored or parsed.
+    pub async fn run_for_workspace(
+        pool: &SqlitePool,
+        workspace: &Workspace,
+        repos: &[Repo],
+        workspace_dir: &Path,
+        redactor: &OutputRedactor,
+    ) -> Result<WorkspaceTestRun, RecordedCommandError> {
+        let session = RecordedCommandService::session_for_workspace(pool, workspace.id).await?;
+        let mut results = Vec::new();
+
+        for repo in repos {
+            let Some(command) = repo.test_script.as_deref().filter(|s| !s.trim().is_empty()) else {
+                continue;
+            };
+
+            info!(
+                "Running tests for repo '{}' in workspace {}",
+                repo.name, workspace.id
+            );
+            let recorded = RecordedCommandService::run_recorded_command(
+                pool,
+                session.id,
+                workspace_dir,
+                Some(repo.name.clone()),
+                command,
+                ScriptContext::TestRun,
+                ExecutionProcessRunReason::TestRun,
+                DEFAULT_COMMAND_TIMEOUT,
+                redactor,
+            )
+            .await?;
+
+            let parsed = parse_test_output(&format!("{}\n{}", recorded.stdout, recorded.stderr));
+            // A non-zero exit without any recognised failures still counts as one failure
+            let failed = if recorded.succeeded() {
+                parsed.failed
+            } else {
+                parsed.failed.max(1)
+            };
+
+            results.push(RepoTestResult {
+                repo_id: repo.id,
+                repo_name: repo.name.clone(),
+                command: command.to_string(),
+                execution_process_id: recorded.execution_process_id,
+                exit_code: recorded.exit_code,
+                framework: parsed.framework,
+                passed: parsed.passed,
+                failed,
+                skipped: parsed.skipped,
+                failed_cases: parsed.failed_cases,
+            });
+        }
+
+        Ok(WorkspaceTestRun::create(pool, workspace.id, &results).await?)
+    }
+}
+
+/// Detect cargo, jest or pytest output and extract pass/fail counts and failing case names.
+/// Unrecognised output yields [`TestFramework::Unknown`] with zero counts.
+pub fn parse_test_output(output: &str) -> ParsedTestOutput {
+    let output = ANSI_ESCAPE.replace_all(output, "");
+    let lines: Vec<&str> = output.lines().map(str::trim_end).collect();
+
+    if lines.iter().any(|l| CARGO_SUMMARY.is_match(l)) {
+        return parse_cargo(&lines);
+    }
+    if lines.iter().any(|l| JEST_SUMMARY.is_match(l.trim_start())) {
+        return parse_jest(&lines);
+    }
+    if lines.iter().any(|l| PYTEST_SUMMARY.is_match(l)) {
+        return parse_pytest(&lines);
+    }
+
+    ParsedTestOutput {
+        framework: TestFramework::Unknown,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        failed_cases: Vec::new(),
+    }
+}
+
+fn parse_cargo(lines: &[&str]) -> ParsedTestOutput {
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    for caps in lines.iter().filter_map(|l| CARGO_SUMMARY.captures(l)) {
+        passed += caps[1].parse::<usize>().unwrap_or(0);
+        failed += caps[2].parse::<usize>().unwrap_or(0);
+        skipped += caps[3].parse::<usize>().unwrap_or(0);
+    }
+    ParsedTestOutput {
+        framework: TestFramework::Cargo,
+        passed,
+        failed,
+        skipped,
+        failed_cases: collect_cases(lines, &CARGO_FAILED_CASE),
+    }
+}
+
+fn parse_jest(lines: &[&str]) -> ParsedTestOutput {
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    if let Some(caps) = lines
+        .iter()
+        .rev()
+        .find_map(|l| JEST_SUMMARY.captures(l.trim_start()))
+    {
+        for count in SUMMARY_COUNT.captures_iter(&caps[1]) {
+            let n = count[1].parse::<usize>().unwrap_or(0);
+            match &count[2] {
+                "passed" => passed += n,
+                "failed" => failed += n,
+                "skipped" | "todo" | "pending" => skipped += n,
+                _ => {}
+            }
+        }
+    }
+    let failed_cases = collect_cases(lines, &JEST_FAILED_CASE)
+        .into_iter()
+        .filter(|case| !case.starts_with("Test suite failed to run"))
+        .collect();
+    ParsedTestOutput {
+        framework: TestFramework::Jest,
+        passed,
+        failed,
+        skipped,
+        failed_cases,
+    }
+}
+
+fn parse_pytest(lines: &[&str]) -> ParsedTestOutput {
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    if let Some(caps) = lines.iter().rev().find_map(|l| PYTEST_SUMMARY.captures(l)) {
+        for count in SUMMARY_COUNT.captures_iter(&caps[1]) {
+            let n = count[1].parse::<usize>().unwrap_or(0);
+            match &count[2] {
+                "passed" => passed += n,
+                "failed" | "error" | "errors" => failed += n,
+                "skipped" | "xfailed" => skipped += n,
+                _ => {}
+            }
+        }
+    }
+    ParsedTestOutput {
+        framework: TestFramework::Pytest,
+        passed,
+        failed,
+        skipped,
+        failed_cases: collect_cases(lines, &PYTEST_FAILED_CASE),
+    }
+}
+
+fn collect_cases(lines: &[&str], pattern: &Regex) -> Vec<String> {
+    let mut seen = HashSet::new();
+    lines
+        .iter()
+        .filter_map(|l| pattern.captures(l))
+        .map(|caps| caps[1].trim().to_string())
+        .filter(|case| seen.insert(case.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cargo_output() {
+        let output = "\
+running 3 tests
+test parser::tests::ok ... ok
+test parser::tests::broken ... FAILED
+test parser::tests::slow ... ignored
+
+failures:
+    parser::tests::broken
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+running 2 tests
+test it_works ... ok
+test it_also_works ... ok
+
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+        let parsed = parse_test_output(output);
+        assert_eq!(parsed.framework, TestFramework::Cargo);
+        assert_eq!((parsed.passed, parsed.failed, parsed.skipped), (3, 1, 1));
+        assert_eq!(parsed.failed_cases, vec!["parser::tests::broken"]);
+    }
+
+    #[test]
+    fn parses_jest_output() {
+        let output = "\
+FAIL src/sum.test.ts
+  ● math › adds numbers
+
+    expect(received).toBe(expected)
+
+PASS src/other.test.ts
+
+Test Suites: 1 failed, 1 passed, 2 total
+Tests:       1 failed, 2 skipped, 5 passed, 8 total
+";
+        let parsed = parse_test_output(output);
+        assert_eq!(parsed.framework, TestFramework::Jest);
+        assert_eq!((parsed.passed, parsed.failed, parsed.skipped), (5, 1, 2));
+        assert_eq!(parsed.failed_cases, vec!["math › adds numbers"]);
+    }
+
+    #[test]
+    fn parses_pytest_output() {
+        let output = "\
+\x1b[31mFAILED tests/test_api.py::test_create - AssertionError: boom\x1b[0m
+ERROR tests/test_db.py::test_connect - ConnectionError
+===== 1 failed, 7 passed, 1 skipped, 1 error in 0.52s =====
+";
+        let parsed = parse_test_output(output);
+        assert_eq!(parsed.framework, TestFramework::Pytest);
+        assert_eq!((parsed.passed, parsed.failed, parsed.skipped), (7, 2, 1));
+        assert_eq!(
+            parsed.failed_cases,
+            vec![
+                "tests/test_api.py::test_create",
+                "tests/test_db.py::test_connect"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_output_has_no_counts() {
+        let parsed = parse_test_output("all good\n");
+        assert_eq!(parsed.framework, TestFramework::Unknown);
+        assert_eq!(parsed.passed + parsed.failed + parsed.skipped, 0);
+    }
+}
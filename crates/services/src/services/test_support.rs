@@ -0,0 +1,166 @@
+//! Fixtures shared by the tests of services that work on the database and git repos.
+
+use std::path::Path;
+
+use db::{
+    DBService,
+    models::{
+        execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason},
+        project::{CreateProject, Project},
+        project_repo::ProjectRepo,
+        repo::Repo,
+        session::{CreateSession, Session},
+        task::{CreateTask, Task},
+        workspace::{CreateWorkspace, Workspace},
+        workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
+    },
+};
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_initial::CodingAgentInitialRequest,
+    },
+    executors::BaseCodingAgent,
+    profile::ExecutorProfileId,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// A fresh in-memory database with the schema in place
+pub(crate) async fn test_pool() -> SqlitePool {
+    DBService::new_in_memory()
+        .await
+        .expect("in-memory database")
+        .pool
+}
+
+/// A project with one repo and a task whose workspace has a session
+pub(crate) struct SeededWorkspace {
+    pub project: Project,
+    pub repo: Repo,
+    pub task: Task,
+    pub workspace: Workspace,
+    pub session: Session,
+}
+
+/// Seed a workspace living in `workspace_dir` on branch `feature`, targeting `main` of the
+/// repo at `repo_path`
+pub(crate) async fn seed_workspace(
+    pool: &SqlitePool,
+    workspace_dir: &Path,
+    repo_path: &Path,
+) -> SeededWorkspace {
+    let project = Project::create(
+        pool,
+        &CreateProject {
+            name: "Test project".to_string(),
+            repositories: Vec::new(),
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+    let repo = Repo::find_or_create(pool, repo_path, "api").await.unwrap();
+    ProjectRepo::create(pool, project.id, repo.id)
+        .await
+        .unwrap();
+    let task = Task::create(
+        pool,
+        &CreateTask::from_title_description(project.id, "Add login".to_string(), None),
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+    let workspace = Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch: "feature".to_string(),
+            agent_working_dir: None,
+        },
+        Uuid::new_v4(),
+        task.id,
+    )
+    .await
+    .unwrap();
+    Workspace::update_container_ref(pool, workspace.id, &workspace_dir.to_string_lossy())
+        .await
+        .unwrap();
+    WorkspaceRepo::create_many(
+        pool,
+        workspace.id,
+        &[CreateWorkspaceRepo {
+            repo_id: repo.id,
+            target_branch: "main".to_string(),
+            start_from_ref: None,
+        }],
+    )
+    .await
+    .unwrap();
+    let session = Session::create(
+        pool,
+        &CreateSession { executor: None },
+        Uuid::new_v4(),
+        workspace.id,
+    )
+    .await
+    .unwrap();
+    let workspace = Workspace::find_by_id(pool, workspace.id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    SeededWorkspace {
+        project,
+        repo,
+        task,
+        workspace,
+        session,
+    }
+}
+
+/// Record a running agent process in `session`
+pub(crate) async fn start_agent_process(
+    pool: &SqlitePool,
+    session_id: Uuid,
+    run_reason: ExecutionProcessRunReason,
+) -> ExecutionProcess {
+    let action = ExecutorAction::new(
+        ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt: "Do the task".to_string(),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            working_dir: None,
+        }),
+        None,
+    );
+    ExecutionProcess::create(
+        pool,
+        &CreateExecutionProcess {
+            session_id,
+            executor_action: action,
+            run_reason,
+        },
+        Uuid::new_v4(),
+        &[],
+    )
+    .await
+    .unwrap()
+}
+
+/// Run git in `dir` as a test user, returning its stdout
+pub(crate) fn run_git(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("run git");
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Initialize a repo at `path` on `main` with `file` committed
+pub(crate) fn init_repo(path: &Path, file: &str, content: &str) {
+    std::fs::create_dir_all(path).unwrap();
+    run_git(path, &["init", "-q", "-b", "main"]);
+    std::fs::write(path.join(file), content).unwrap();
+    run_git(path, &["add", "-A"]);
+    run_git(path, &["commit", "-q", "-m", "Initial commit"]);
+}
@@ -0,0 +1,187 @@
+//! Tarballs of the current files of a workspace worktree.
+//!
+//! The archive is written on a blocking thread and handed over in chunks as it is produced,
+//! so a large worktree is streamed to the client rather than held in memory. `.git` is
+//! always left out, since a worktree's `.git` only points into the server's repository;
+//! ignored files such as build outputs and dependencies can be left out as well.
+
+use std::{
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use flate2::{Compression, write::GzEncoder};
+use futures::Stream;
+use ignore::WalkBuilder;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Size of the chunks the archive is sent in
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Chunks buffered before the writer waits for the client
+const BUFFERED_CHUNKS: usize = 8;
+
+/// Sends everything written to it over a channel, failing once the receiver is gone
+struct ChannelWriter {
+    tx: mpsc::Sender<io::Result<Vec<u8>>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .blocking_send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Archive download cancelled"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Write a tar.gz of the files under `root` to `writer`, with paths under `prefix`
+fn write_archive(
+    root: &Path,
+    prefix: &str,
+    exclude_ignored: bool,
+    writer: impl Write,
+) -> io::Result<()> {
+    let mut builder = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+    builder.follow_symlinks(false);
+
+    let walker = WalkBuilder::new(root)
+        .standard_filters(exclude_ignored)
+        .hidden(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_path(|a, b| a.cmp(b))
+        .build();
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::debug!("Skipping unreadable entry under {}: {}", root.display(), e);
+                continue;
+            }
+        };
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        builder.append_path_with_name(entry.path(), Path::new(prefix).join(relative))?;
+    }
+
+    builder.into_inner()?.finish()?.flush()
+}
+
+pub struct WorkspaceArchiveService;
+
+impl WorkspaceArchiveService {
+    /// Stream a tar.gz of the worktree at `root`, its files under a `prefix` directory
+    pub fn stream(
+        root: PathBuf,
+        prefix: String,
+        exclude_ignored: bool,
+    ) -> impl Stream<Item = io::Result<Vec<u8>>> {
+        let (tx, rx) = mpsc::channel(BUFFERED_CHUNKS);
+        tokio::task::spawn_blocking(move || {
+            let writer = BufWriter::with_capacity(CHUNK_SIZE, ChannelWriter { tx: tx.clone() });
+            if let Err(e) = write_archive(&root, &prefix, exclude_ignored, writer) {
+                if e.kind() == io::ErrorKind::BrokenPipe {
+                    return;
+                }
+                tracing::warn!("Failed to archive {}: {}", root.display(), e);
+                let _ = tx.blocking_send(Err(e));
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::read::GzDecoder;
+    use futures::StreamExt;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn worktree() -> TempDir {
+        let td = TempDir::new().unwrap();
+        git2::Repository::init(td.path()).unwrap();
+        write(td.path(), ".gitignore", "target/\n");
+        write(td.path(), "src/main.rs", "fn main() {}");
+        write(td.path(), "target/debug/app", "binary");
+        td
+    }
+
+    fn entries(archive: &[u8]) -> Vec<String> {
+        let mut archive = tar::Archive::new(GzDecoder::new(archive));
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let path = entry
+                    .unwrap()
+                    .path()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned();
+                path.trim_end_matches('/').to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ignored_files_are_optional_and_git_is_never_included() {
+        let td = worktree();
+
+        let mut archive = Vec::new();
+        write_archive(td.path(), "api", true, &mut archive).unwrap();
+        assert_eq!(
+            entries(&archive),
+            ["api/.gitignore", "api/src", "api/src/main.rs"]
+        );
+
+        let mut archive = Vec::new();
+        write_archive(td.path(), "api", false, &mut archive).unwrap();
+        assert_eq!(
+            entries(&archive),
+            [
+                "api/.gitignore",
+                "api/src",
+                "api/src/main.rs",
+                "api/target",
+                "api/target/debug",
+                "api/target/debug/app"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn streamed_archive_is_complete() {
+        let td = worktree();
+        let chunks: Vec<_> =
+            WorkspaceArchiveService::stream(td.path().to_path_buf(), "api".to_string(), true)
+                .collect()
+                .await;
+        let archive: Vec<u8> = chunks.into_iter().flat_map(Result::unwrap).collect();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(archive.as_slice()));
+        let mut main = String::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            if entry.path().unwrap() == Path::new("api/src/main.rs") {
+                io::Read::read_to_string(&mut entry, &mut main).unwrap();
+            }
+        }
+        assert_eq!(main, "fn main() {}");
+    }
+}
@@ -0,0 +1,305 @@
+//! Cross-process locks on workspace directories.
+//!
+//! The worktree base directory may sit on a network filesystem shared by several server
+//! instances, so in-process locks cannot keep orphan sweeps away from a workspace that is
+//! still being created. Each operation that creates, migrates or removes a workspace
+//! directory holds `.<dir name>.lock` next to it. The lock file is created exclusively,
+//! which is atomic on local disks as well as NFS and SMB, and its holder refreshes its
+//! modification time periodically; a lock that has not been refreshed for a while belongs
+//! to a crashed process and is taken over.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, task::JoinHandle};
+use uuid::Uuid;
+
+/// How often a held lock is refreshed
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// A lock not refreshed for this long is considered abandoned; generous enough to absorb
+/// clock skew between the file server and the instances
+const STALE_AFTER: Duration = Duration::from_secs(120);
+/// How long `acquire` waits for another holder before giving up
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(300);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Error)]
+pub enum WorkspaceDirLockError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Workspace directory {path} is locked by {holder}")]
+    Busy { path: String, holder: String },
+}
+
+/// Contents of a lock file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LockOwner {
+    /// Unique per acquisition, so a holder never removes a lock that was taken over
+    token: Uuid,
+    host: String,
+    pid: u32,
+    operation: String,
+}
+
+impl LockOwner {
+    fn new(operation: &str) -> Self {
+        let host = std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .unwrap_or_else(|_| "unknown host".to_string());
+        Self {
+            token: Uuid::new_v4(),
+            host,
+            pid: std::process::id(),
+            operation: operation.to_string(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("{} (pid {} on {})", self.operation, self.pid, self.host)
+    }
+}
+
+/// Lock file guarding `workspace_dir`, kept beside it so removing the directory leaves it
+pub fn lock_path(workspace_dir: &Path) -> PathBuf {
+    let name = workspace_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    workspace_dir.with_file_name(format!(".{name}.lock"))
+}
+
+/// Whether a lock last written at `modified` has outlived its holder
+fn is_stale(modified: SystemTime, now: SystemTime) -> bool {
+    now.duration_since(modified)
+        .is_ok_and(|age| age > STALE_AFTER)
+}
+
+async fn read_owner(path: &Path) -> Option<LockOwner> {
+    let contents = tokio::fs::read(path).await.ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+/// Bump the lock's modification time, which is what other instances judge staleness by
+fn touch(path: &Path) -> std::io::Result<()> {
+    std::fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+/// Held while an operation works on a workspace directory; released when dropped
+#[derive(Debug)]
+pub struct WorkspaceDirLockGuard {
+    path: PathBuf,
+    token: Uuid,
+    heartbeat: JoinHandle<()>,
+}
+
+impl Drop for WorkspaceDirLockGuard {
+    fn drop(&mut self) {
+        self.heartbeat.abort();
+        // Only remove the lock if it is still ours
+        let ours = std::fs::read(&self.path)
+            .ok()
+            .and_then(|contents| serde_json::from_slice::<LockOwner>(&contents).ok())
+            .is_some_and(|owner| owner.token == self.token);
+        if ours && let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to release lock {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+pub struct WorkspaceDirLock;
+
+impl WorkspaceDirLock {
+    /// Lock `workspace_dir` for `operation`, waiting for another holder to finish
+    pub async fn acquire(
+        workspace_dir: &Path,
+        operation: &str,
+    ) -> Result<WorkspaceDirLockGuard, WorkspaceDirLockError> {
+        Self::acquire_within(workspace_dir, operation, ACQUIRE_TIMEOUT).await
+    }
+
+    /// Lock `workspace_dir` for `operation` if nobody holds it
+    pub async fn try_acquire(
+        workspace_dir: &Path,
+        operation: &str,
+    ) -> Result<WorkspaceDirLockGuard, WorkspaceDirLockError> {
+        Self::acquire_within(workspace_dir, operation, Duration::ZERO).await
+    }
+
+    async fn acquire_within(
+        workspace_dir: &Path,
+        operation: &str,
+        timeout: Duration,
+    ) -> Result<WorkspaceDirLockGuard, WorkspaceDirLockError> {
+        let path = lock_path(workspace_dir);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let owner = LockOwner::new(operation);
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            match Self::create(&path, &owner).await {
+                Ok(()) => {
+                    return Ok(WorkspaceDirLockGuard {
+                        heartbeat: Self::spawn_heartbeat(path.clone(), owner.clone()),
+                        path,
+                        token: owner.token,
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            if Self::remove_if_stale(&path).await? {
+                continue;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let holder = read_owner(&path)
+                    .await
+                    .map(|owner| owner.describe())
+                    .unwrap_or_else(|| "another process".to_string());
+                return Err(WorkspaceDirLockError::Busy {
+                    path: workspace_dir.to_string_lossy().into_owned(),
+                    holder,
+                });
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Create the lock file, failing with `AlreadyExists` when it is held
+    async fn create(path: &Path, owner: &LockOwner) -> std::io::Result<()> {
+        let contents = serde_json::to_vec(owner).map_err(std::io::Error::other)?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .await?;
+        file.write_all(&contents).await?;
+        file.sync_all().await
+    }
+
+    /// Remove a lock abandoned by a crashed holder; true when it was removed
+    async fn remove_if_stale(path: &Path) -> std::io::Result<bool> {
+        let modified = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata.modified()?,
+            // Released in the meantime
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+            Err(e) => return Err(e),
+        };
+        if !is_stale(modified, SystemTime::now()) {
+            return Ok(false);
+        }
+        let holder = read_owner(path).await;
+        tracing::warn!(
+            "Taking over stale lock {} held by {}",
+            path.display(),
+            holder.map_or_else(|| "an unknown process".to_string(), |o| o.describe())
+        );
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Keep the lock fresh while it is held so other instances do not take it over
+    fn spawn_heartbeat(path: PathBuf, owner: LockOwner) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if read_owner(&path).await.as_ref() != Some(&owner) {
+                    tracing::warn!("Lock {} was taken over by another process", path.display());
+                    return;
+                }
+                if let Err(e) = touch(&path) {
+                    tracing::warn!("Failed to refresh lock {}: {}", path.display(), e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    async fn write_owner(path: &Path, owner: &LockOwner) {
+        tokio::fs::write(path, serde_json::to_vec(owner).unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn second_holder_is_refused_until_release() {
+        let td = TempDir::new().unwrap();
+        let dir = td.path().join("ab12-task");
+
+        let guard = WorkspaceDirLock::try_acquire(&dir, "create").await.unwrap();
+        assert!(lock_path(&dir).is_file());
+        let err = WorkspaceDirLock::try_acquire(&dir, "orphan cleanup")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, WorkspaceDirLockError::Busy { holder, .. } if holder.starts_with("create"))
+        );
+
+        drop(guard);
+        assert!(!lock_path(&dir).exists());
+        WorkspaceDirLock::try_acquire(&dir, "orphan cleanup")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn abandoned_lock_is_taken_over() {
+        let td = TempDir::new().unwrap();
+        let dir = td.path().join("ab12-task");
+        let path = lock_path(&dir);
+        write_owner(&path, &LockOwner::new("crashed create")).await;
+        let old = SystemTime::now() - STALE_AFTER * 2;
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let guard = WorkspaceDirLock::try_acquire(&dir, "create").await.unwrap();
+        assert_eq!(read_owner(&path).await.unwrap().token, guard.token);
+    }
+
+    #[tokio::test]
+    async fn release_keeps_a_lock_taken_over_by_someone_else() {
+        let td = TempDir::new().unwrap();
+        let dir = td.path().join("ab12-task");
+        let guard = WorkspaceDirLock::try_acquire(&dir, "create").await.unwrap();
+
+        let other = LockOwner::new("cleanup");
+        write_owner(&lock_path(&dir), &other).await;
+        drop(guard);
+        assert_eq!(read_owner(&lock_path(&dir)).await, Some(other));
+    }
+
+    #[test]
+    fn lock_sits_beside_the_workspace_directory() {
+        assert_eq!(
+            lock_path(Path::new("/srv/worktrees/ab12-task")),
+            Path::new("/srv/worktrees/.ab12-task.lock")
+        );
+        let now = SystemTime::now();
+        assert!(!is_stale(now - Duration::from_secs(30), now));
+        assert!(is_stale(now - STALE_AFTER * 2, now));
+    }
+}
@@ -0,0 +1,372 @@
+//! Integrity checks for the worktrees of a workspace.
+//!
+//! Each repo worktree should exist, be a worktree of its repository that git still lists,
+//! have the workspace branch checked out, and be neither locked nor prunable. Problems are
+//! reported per repo with a suggested repair. When enabled, worktrees that are missing or
+//! whose links are broken are repaired on access and the repair is recorded as a workspace
+//! event.
+
+use std::path::{Path, PathBuf};
+
+use db::models::{
+    repo::Repo,
+    workspace::Workspace,
+    workspace_event::{WorkspaceEvent, WorkspaceEventKind},
+    workspace_repo::WorkspaceRepo,
+};
+use git::{GitService, GitServiceError, WorktreeHealth};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::container::{ContainerError, ContainerService};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum WorktreeIssueKind {
+    /// The workspace is closed, so it has no worktrees
+    NotCreated,
+    Missing,
+    /// The directory exists but its `.git` link does not lead to the repository
+    BrokenGitLink,
+    /// The repository does not list the worktree
+    Unregistered,
+    BranchMismatch,
+    DetachedHead,
+    Locked,
+    Prunable,
+}
+
+impl WorktreeIssueKind {
+    /// Fixed by relinking or recreating the worktree, as opposed to needing a decision
+    fn is_repairable(self) -> bool {
+        matches!(
+            self,
+            Self::Missing | Self::BrokenGitLink | Self::Unregistered | Self::Prunable
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct WorktreeIssue {
+    pub kind: WorktreeIssueKind,
+    pub message: String,
+    pub repair: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RepoVerification {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub worktree_path: Option<String>,
+    pub issues: Vec<WorktreeIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorkspaceVerification {
+    pub workspace_id: Uuid,
+    /// No repo has issues
+    pub healthy: bool,
+    pub repos: Vec<RepoVerification>,
+}
+
+fn issue(kind: WorktreeIssueKind, message: String, repair: String) -> WorktreeIssue {
+    WorktreeIssue {
+        kind,
+        message,
+        repair,
+    }
+}
+
+/// Issues with a worktree given what git reports about it
+fn issues_for(
+    health: &WorktreeHealth,
+    expected_branch: &str,
+    repo_path: &Path,
+    worktree_path: &Path,
+) -> Vec<WorktreeIssue> {
+    let repo = repo_path.display();
+    let worktree = worktree_path.display();
+    let mut issues = Vec::new();
+
+    if !health.exists {
+        issues.push(issue(
+            WorktreeIssueKind::Missing,
+            format!("Worktree directory {worktree} does not exist"),
+            "Reopen the workspace to recreate its worktrees".to_string(),
+        ));
+    } else if !health.git_link_valid {
+        issues.push(issue(
+            WorktreeIssueKind::BrokenGitLink,
+            format!("{worktree} is not a git worktree of {repo}"),
+            format!("Run `git -C {repo} worktree repair {worktree}`"),
+        ));
+    }
+    if !health.registered {
+        issues.push(issue(
+            WorktreeIssueKind::Unregistered,
+            format!("{repo} does not list {worktree} as a worktree"),
+            format!("Run `git -C {repo} worktree repair {worktree}`"),
+        ));
+    }
+    if health.git_link_valid {
+        match health.branch.as_deref() {
+            Some(branch) if branch == expected_branch => {}
+            Some(branch) => issues.push(issue(
+                WorktreeIssueKind::BranchMismatch,
+                format!("Branch {branch} is checked out instead of {expected_branch}"),
+                format!("Run `git -C {worktree} checkout {expected_branch}`"),
+            )),
+            None => issues.push(issue(
+                WorktreeIssueKind::DetachedHead,
+                format!("HEAD is detached instead of on {expected_branch}"),
+                format!("Run `git -C {worktree} checkout {expected_branch}`"),
+            )),
+        }
+    }
+    if health.locked {
+        issues.push(issue(
+            WorktreeIssueKind::Locked,
+            format!("Worktree {worktree} is locked"),
+            format!("Run `git -C {repo} worktree unlock {worktree}`"),
+        ));
+    }
+    if health.prunable {
+        issues.push(issue(
+            WorktreeIssueKind::Prunable,
+            format!("Git considers the metadata of {worktree} stale"),
+            format!("Run `git -C {repo} worktree prune`, then reopen the workspace to recreate it"),
+        ));
+    }
+    issues
+}
+
+/// Event message naming each repaired repo and what was wrong with it
+fn repair_message(repos: &[&RepoVerification]) -> String {
+    let repos: Vec<String> = repos
+        .iter()
+        .map(|repo| {
+            let issues: Vec<&str> = repo
+                .issues
+                .iter()
+                .map(|issue| issue.message.as_str())
+                .collect();
+            format!("{} ({})", repo.repo_name, issues.join("; "))
+        })
+        .collect();
+    format!("Repaired worktrees: {}", repos.join(", "))
+}
+
+pub struct WorkspaceIntegrityService;
+
+impl WorkspaceIntegrityService {
+    pub fn verify(
+        git: &GitService,
+        workspace: &Workspace,
+        repos: &[Repo],
+    ) -> Result<WorkspaceVerification, GitServiceError> {
+        let container = workspace.container_ref.as_deref().map(PathBuf::from);
+        let mut verifications = Vec::with_capacity(repos.len());
+
+        for repo in repos {
+            let (worktree_path, issues) = match &container {
+                Some(container) => {
+                    let worktree_path = container.join(&repo.name);
+                    let health = git.inspect_worktree(&repo.path, &worktree_path)?;
+                    let issues = issues_for(&health, &workspace.branch, &repo.path, &worktree_path);
+                    (Some(worktree_path.to_string_lossy().into_owned()), issues)
+                }
+                None => (
+                    None,
+                    vec![issue(
+                        WorktreeIssueKind::NotCreated,
+                        "The workspace is closed and has no worktrees".to_string(),
+                        "Reopen the workspace to create its worktrees".to_string(),
+                    )],
+                ),
+            };
+            verifications.push(RepoVerification {
+                repo_id: repo.id,
+                repo_name: repo.name.clone(),
+                worktree_path,
+                issues,
+            });
+        }
+
+        Ok(WorkspaceVerification {
+            workspace_id: workspace.id,
+            healthy: verifications.iter().all(|r| r.issues.is_empty()),
+            repos: verifications,
+        })
+    }
+
+    /// Relink and recreate the worktrees of an open workspace that are missing or broken.
+    /// Returns the recorded event, or None when nothing needed repairing.
+    pub async fn repair(
+        pool: &SqlitePool,
+        git: &GitService,
+        container: &(impl ContainerService + Sync),
+        workspace: &Workspace,
+    ) -> Result<Option<WorkspaceEvent>, ContainerError> {
+        let Some(container_ref) = workspace.container_ref.clone() else {
+            return Ok(None);
+        };
+        let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+        let verification = tokio::task::spawn_blocking({
+            let git = git.clone();
+            let workspace = workspace.clone();
+            let repos = repos.clone();
+            move || Self::verify(&git, &workspace, &repos)
+        })
+        .await
+        .map_err(|e| ContainerError::Other(e.into()))??;
+
+        let broken: Vec<&RepoVerification> = verification
+            .repos
+            .iter()
+            .filter(|repo| repo.issues.iter().any(|issue| issue.kind.is_repairable()))
+            .collect();
+        if broken.is_empty() {
+            return Ok(None);
+        }
+
+        // Relink worktrees that still exist; ones that cannot be relinked are recreated below
+        for repo in &repos {
+            if !broken.iter().any(|b| b.repo_id == repo.id) {
+                continue;
+            }
+            let worktree_path = PathBuf::from(&container_ref).join(&repo.name);
+            if worktree_path.exists()
+                && let Err(e) = git.repair_worktree(&repo.path, &worktree_path)
+            {
+                tracing::warn!(
+                    "git worktree repair failed for {}: {}",
+                    worktree_path.display(),
+                    e
+                );
+            }
+        }
+        container.ensure_container_exists(workspace).await?;
+
+        let message = repair_message(&broken);
+        tracing::info!("Workspace {}: {}", workspace.id, message);
+        let event = WorkspaceEvent::create(
+            pool,
+            workspace.id,
+            WorkspaceEventKind::WorktreeRepaired,
+            &message,
+        )
+        .await?;
+        Ok(Some(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn healthy(branch: &str) -> WorktreeHealth {
+        WorktreeHealth {
+            exists: true,
+            git_link_valid: true,
+            registered: true,
+            branch: Some(branch.to_string()),
+            locked: false,
+            prunable: false,
+        }
+    }
+
+    fn kinds(health: &WorktreeHealth) -> Vec<WorktreeIssueKind> {
+        issues_for(
+            health,
+            "vk/feature",
+            Path::new("/repo"),
+            Path::new("/wt/repo"),
+        )
+        .into_iter()
+        .map(|issue| issue.kind)
+        .collect()
+    }
+
+    #[test]
+    fn healthy_worktree_has_no_issues() {
+        assert!(kinds(&healthy("vk/feature")).is_empty());
+    }
+
+    #[test]
+    fn wrong_or_detached_branch_is_reported() {
+        assert_eq!(kinds(&healthy("main")), [WorktreeIssueKind::BranchMismatch]);
+        let detached = WorktreeHealth {
+            branch: None,
+            ..healthy("vk/feature")
+        };
+        assert_eq!(kinds(&detached), [WorktreeIssueKind::DetachedHead]);
+    }
+
+    #[test]
+    fn deleted_worktree_is_missing_and_prunable() {
+        let deleted = WorktreeHealth {
+            exists: false,
+            git_link_valid: false,
+            branch: None,
+            prunable: true,
+            ..healthy("vk/feature")
+        };
+        assert_eq!(
+            kinds(&deleted),
+            [WorktreeIssueKind::Missing, WorktreeIssueKind::Prunable]
+        );
+    }
+
+    #[test]
+    fn only_broken_links_and_missing_worktrees_are_repaired() {
+        assert!(WorktreeIssueKind::Missing.is_repairable());
+        assert!(WorktreeIssueKind::Prunable.is_repairable());
+        assert!(!WorktreeIssueKind::BranchMismatch.is_repairable());
+        assert!(!WorktreeIssueKind::Locked.is_repairable());
+    }
+
+    #[test]
+    fn repair_message_names_repos_and_issues() {
+        let repo = RepoVerification {
+            repo_id: Uuid::new_v4(),
+            repo_name: "api".to_string(),
+            worktree_path: Some("/wt/api".to_string()),
+            issues: issues_for(
+                &WorktreeHealth {
+                    exists: false,
+                    git_link_valid: false,
+                    branch: None,
+                    ..healthy("vk/feature")
+                },
+                "vk/feature",
+                Path::new("/repo"),
+                Path::new("/wt/api"),
+            ),
+        };
+        assert_eq!(
+            repair_message(&[&repo]),
+            "Repaired worktrees: api (Worktree directory /wt/api does not exist)"
+        );
+    }
+
+    #[test]
+    fn locked_worktree_suggests_unlock() {
+        let locked = WorktreeHealth {
+            locked: true,
+            ..healthy("vk/feature")
+        };
+        let issues = issues_for(
+            &locked,
+            "vk/feature",
+            Path::new("/repo"),
+            Path::new("/wt/repo"),
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].repair,
+            "Run `git -C /repo worktree unlock /wt/repo`"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Advisory per-workspace locks for mutating operations.
+//!
+//! Closing, merging, rebasing, pushing and starting follow-ups all touch the same worktrees
+//! and branches, so only one of them may run for a workspace at a time. Locks are not
+//! waited on: a second caller is refused straight away with the operation holding the lock.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{LazyLock, Mutex},
+};
+
+use thiserror::Error;
+use uuid::Uuid;
+
+static WORKSPACE_OPERATIONS: LazyLock<Mutex<HashMap<Uuid, WorkspaceOperation>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceOperation {
+    Close,
+    Merge,
+    Rebase,
+    Push,
+    FollowUp,
+    ApplyPatch,
+    Upload,
+    ApplySuggestion,
+    ResolveConflicts,
+    Plan,
+    Pipeline,
+    Resume,
+    Retry,
+    AddRepo,
+    RemoveRepo,
+    Stage,
+    Commit,
+    DiscardFiles,
+    AutoSync,
+}
+
+impl fmt::Display for WorkspaceOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WorkspaceOperation::Close => "close",
+            WorkspaceOperation::Merge => "merge",
+            WorkspaceOperation::Rebase => "rebase",
+            WorkspaceOperation::Push => "push",
+            WorkspaceOperation::FollowUp => "follow-up",
+            WorkspaceOperation::ApplyPatch => "patch apply",
+            WorkspaceOperation::Upload => "file upload",
+            WorkspaceOperation::ApplySuggestion => "suggestion apply",
+            WorkspaceOperation::ResolveConflicts => "conflict resolution",
+            WorkspaceOperation::Plan => "plan",
+            WorkspaceOperation::Pipeline => "pipeline",
+            WorkspaceOperation::Resume => "resume",
+            WorkspaceOperation::Retry => "retry",
+            WorkspaceOperation::AddRepo => "repo addition",
+            WorkspaceOperation::RemoveRepo => "repo removal",
+            WorkspaceOperation::Stage => "staging",
+            WorkspaceOperation::Commit => "commit",
+            WorkspaceOperation::DiscardFiles => "file discard",
+            WorkspaceOperation::AutoSync => "automatic sync",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Workspace {workspace_id} is busy: a {holder} is already in progress")]
+pub struct WorkspaceBusy {
+    pub workspace_id: Uuid,
+    pub holder: WorkspaceOperation,
+}
+
+/// Held while an operation runs; releases the workspace when dropped
+#[derive(Debug)]
+pub struct WorkspaceOperationGuard {
+    workspace_id: Uuid,
+}
+
+impl Drop for WorkspaceOperationGuard {
+    fn drop(&mut self) {
+        WORKSPACE_OPERATIONS
+            .lock()
+            .unwrap()
+            .remove(&self.workspace_id);
+    }
+}
+
+pub struct WorkspaceLock;
+
+impl WorkspaceLock {
+    /// Claim the workspace for `operation`, or report which operation already holds it
+    pub fn try_acquire(
+        workspace_id: Uuid,
+        operation: WorkspaceOperation,
+    ) -> Result<WorkspaceOperationGuard, WorkspaceBusy> {
+        let mut operations = WORKSPACE_OPERATIONS.lock().unwrap();
+        if let Some(holder) = operations.get(&workspace_id) {
+            return Err(WorkspaceBusy {
+                workspace_id,
+                holder: *holder,
+            });
+        }
+        operations.insert(workspace_id, operation);
+        Ok(WorkspaceOperationGuard { workspace_id })
+    }
+
+    /// Operation currently holding the workspace, if any
+    pub fn holder(workspace_id: Uuid) -> Option<WorkspaceOperation> {
+        WORKSPACE_OPERATIONS
+            .lock()
+            .unwrap()
+            .get(&workspace_id)
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_operation_is_refused_until_release() {
+        let workspace_id = Uuid::new_v4();
+        let guard = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Close).unwrap();
+
+        let busy =
+            WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Rebase).unwrap_err();
+        assert_eq!(busy.holder, WorkspaceOperation::Close);
+        // Other workspaces are unaffected
+        let _other = WorkspaceLock::try_acquire(Uuid::new_v4(), WorkspaceOperation::Push).unwrap();
+
+        drop(guard);
+        assert_eq!(WorkspaceLock::holder(workspace_id), None);
+        let _guard = WorkspaceLock::try_acquire(workspace_id, WorkspaceOperation::Merge).unwrap();
+    }
+}
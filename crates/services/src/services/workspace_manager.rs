@@ -1,13 +1,26 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
 use db::models::{repo::Repo, workspace::Workspace as DbWorkspace};
-use git::{GitService, GitServiceError};
+use git::{GitService, GitServiceError, PathMergeResult};
+use git2::Repository;
+use serde::Serialize;
 use sqlx::{Pool, Sqlite};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
+use ts_rs::TS;
+use utils::path::{path_key, paths_equal, remove_dir_all_with_retry};
 use uuid::Uuid;
 
-use super::worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager};
+use super::{
+    merge_queue::MergeQueue,
+    settings::{Settings, SettingsService},
+    workspace_dir_lock::{WorkspaceDirLock, WorkspaceDirLockError},
+    worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager},
+};
 
 #[derive(Debug, Clone)]
 pub struct RepoWorkspaceInput {
@@ -16,6 +29,9 @@ pub struct RepoWorkspaceInput {
     /// Optional git ref (commit, tag, branch) to start the worktree from
     /// instead of the default target_branch HEAD
     pub start_from_ref: Option<String>,
+    /// Directory the worktree's sparse checkout is limited to, along with the files at the
+    /// root of the repo
+    pub sparse_checkout_dir: Option<String>,
 }
 
 impl RepoWorkspaceInput {
@@ -24,6 +40,7 @@ impl RepoWorkspaceInput {
             repo,
             target_branch,
             start_from_ref: None,
+            sparse_checkout_dir: None,
         }
     }
 
@@ -32,8 +49,56 @@ impl RepoWorkspaceInput {
             repo,
             target_branch,
             start_from_ref,
+            sparse_checkout_dir: None,
         }
     }
+
+    pub fn with_sparse_checkout(mut self, dir: Option<String>) -> Self {
+        self.sparse_checkout_dir = dir;
+        self
+    }
+}
+
+/// Which orphaned workspace directories a cleanup sweep may remove
+#[derive(Debug, Clone)]
+pub struct OrphanCleanupPolicy {
+    /// Keep orphans that still hold git worktrees so they can be adopted
+    pub reconcile: bool,
+    /// Keep orphans modified more recently than this
+    pub grace_period: Duration,
+    /// Directory names or absolute paths that are never removed
+    pub exclusions: Vec<String>,
+}
+
+impl OrphanCleanupPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            reconcile: settings.reconcile_orphan_worktrees,
+            grace_period: Duration::from_secs(
+                u64::from(settings.orphan_cleanup_grace_hours) * 3600,
+            ),
+            exclusions: settings.orphan_cleanup_exclusions.clone(),
+        }
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclusions.iter().any(|exclusion| {
+            let exclusion = utils::path::expand_tilde(exclusion);
+            if exclusion.is_absolute() {
+                paths_equal(&exclusion, path)
+            } else {
+                path.file_name()
+                    .is_some_and(|name| path_key(name) == path_key(&exclusion))
+            }
+        })
+    }
+
+    /// Whether a directory last modified at `modified` is still within the grace period
+    fn is_recent(&self, modified: SystemTime, now: SystemTime) -> bool {
+        now.duration_since(modified)
+            .map(|age| age < self.grace_period)
+            .unwrap_or(true)
+    }
 }
 
 #[derive(Debug, Error)]
@@ -44,12 +109,20 @@ pub enum WorkspaceError {
     Io(#[from] std::io::Error),
     #[error("No repositories provided")]
     NoRepositories,
+    #[error(
+        "Repositories '{0}' and '{1}' would share a worktree directory on case-insensitive filesystems; rename one of them"
+    )]
+    RepoNameCollision(String, String),
     #[error("Partial workspace creation failed: {0}")]
     PartialCreation(String),
     #[error("Merge conflicts in repo '{repo_name}': {message}")]
     MergeConflicts { repo_name: String, message: String },
     #[error("Git error: {0}")]
     Git(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    DirLock(#[from] WorkspaceDirLockError),
 }
 
 /// Result of a workspace merge operation for a single repo
@@ -59,6 +132,8 @@ pub struct RepoMergeResult {
     pub repo_name: String,
     pub merge_commit_sha: String,
     pub target_branch: String,
+    /// Number of merges into the same repo that had to finish before this one could start
+    pub queue_position: usize,
 }
 
 /// Info about a single repo's worktree within a workspace
@@ -77,6 +152,61 @@ pub struct WorktreeContainer {
     pub worktrees: Vec<RepoWorktree>,
 }
 
+/// A git worktree found inside a workspace directory that has no database row
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct OrphanWorktree {
+    /// Directory name inside the workspace; matches the repo name for tool-created workspaces
+    pub dir_name: String,
+    /// Main repository the worktree belongs to
+    #[ts(type = "string")]
+    pub repo_path: PathBuf,
+    /// Registered repo at `repo_path`, if any
+    pub repo_id: Option<Uuid>,
+    /// Checked-out branch; unset for a detached HEAD
+    pub branch: Option<String>,
+    pub has_uncommitted_changes: bool,
+}
+
+/// A workspace directory with no database row, and the worktrees left in it
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct OrphanWorkspace {
+    #[ts(type = "string")]
+    pub path: PathBuf,
+    pub worktrees: Vec<OrphanWorktree>,
+}
+
+/// Two repo names that map to the same worktree directory on a case-insensitive filesystem,
+/// as used on Windows and by default on macOS
+fn find_repo_name_collision<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+) -> Option<(&'a str, &'a str)> {
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    for name in names {
+        if let Some(existing) = seen.insert(name.to_lowercase(), name) {
+            return Some((existing, name));
+        }
+    }
+    None
+}
+
+fn check_repo_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<(), WorkspaceError> {
+    match find_repo_name_collision(names) {
+        Some((a, b)) => Err(WorkspaceError::RepoNameCollision(
+            a.to_string(),
+            b.to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Remove a directory tree off the async runtime, retrying files Windows keeps busy
+async fn remove_dir_all(path: &Path) -> std::io::Result<()> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || remove_dir_all_with_retry(path))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
 pub struct WorkspaceManager;
 
 impl WorkspaceManager {
@@ -90,6 +220,8 @@ impl WorkspaceManager {
         if repos.is_empty() {
             return Err(WorkspaceError::NoRepositories);
         }
+        check_repo_names(repos.iter().map(|input| input.repo.name.as_str()))?;
+        let _lock = WorkspaceDirLock::acquire(workspace_dir, "workspace creation").await?;
 
         info!(
             "Creating workspace at {} with {} repositories",
@@ -121,6 +253,9 @@ impl WorkspaceManager {
             .await
             {
                 Ok(()) => {
+                    if let Some(dir) = &input.sparse_checkout_dir {
+                        Self::apply_sparse_checkout(&worktree_path, dir).await;
+                    }
                     created_worktrees.push(RepoWorktree {
                         repo_id: input.repo.id,
                         repo_name: input.repo.name.clone(),
@@ -164,6 +299,74 @@ impl WorkspaceManager {
         })
     }
 
+    /// Add a worktree for one more repo to an existing workspace, on a new branch named like
+    /// the workspace's other branches
+    pub async fn add_repo_worktree(
+        workspace_dir: &Path,
+        existing_repos: &[Repo],
+        input: &RepoWorkspaceInput,
+        branch_name: &str,
+    ) -> Result<RepoWorktree, WorkspaceError> {
+        check_repo_names(
+            existing_repos
+                .iter()
+                .map(|repo| repo.name.as_str())
+                .chain([input.repo.name.as_str()]),
+        )?;
+        let _lock = WorkspaceDirLock::acquire(workspace_dir, "repo addition").await?;
+
+        let worktree_path = workspace_dir.join(&input.repo.name);
+        info!(
+            "Adding worktree for repo '{}' at {}",
+            input.repo.name,
+            worktree_path.display()
+        );
+        WorktreeManager::create_worktree_with_start_ref(
+            &input.repo.path,
+            branch_name,
+            &worktree_path,
+            &input.target_branch,
+            true,
+            input.start_from_ref.as_deref(),
+        )
+        .await?;
+        if let Some(dir) = &input.sparse_checkout_dir {
+            Self::apply_sparse_checkout(&worktree_path, dir).await;
+        }
+
+        Ok(RepoWorktree {
+            repo_id: input.repo.id,
+            repo_name: input.repo.name.clone(),
+            source_repo_path: input.repo.path.clone(),
+            worktree_path,
+        })
+    }
+
+    /// Limit a new worktree's checkout to `dir`. A failure leaves the full checkout in place
+    /// rather than failing the workspace.
+    async fn apply_sparse_checkout(worktree_path: &Path, dir: &str) {
+        let result = tokio::task::spawn_blocking({
+            let worktree_path = worktree_path.to_path_buf();
+            let dir = dir.to_string();
+            move || GitService::new().set_sparse_checkout(&worktree_path, &dir)
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => debug!(
+                "Limited checkout of {} to '{}'",
+                worktree_path.display(),
+                dir
+            ),
+            Ok(Err(e)) => warn!(
+                "Failed to limit checkout of {} to '{}': {}",
+                worktree_path.display(),
+                dir,
+                e
+            ),
+            Err(e) => warn!("Sparse checkout task failed: {}", e),
+        }
+    }
+
     /// Ensure all worktrees in a workspace exist (for cold restart scenarios)
     pub async fn ensure_workspace_exists(
         workspace_dir: &Path,
@@ -173,10 +376,12 @@ impl WorkspaceManager {
         if repos.is_empty() {
             return Err(WorkspaceError::NoRepositories);
         }
+        check_repo_names(repos.iter().map(|repo| repo.name.as_str()))?;
+        let _lock = WorkspaceDirLock::acquire(workspace_dir, "workspace setup").await?;
 
         // Try legacy migration first (single repo projects only)
         // Old layout had worktree directly at workspace_dir; new layout has it at workspace_dir/{repo_name}
-        if repos.len() == 1 && Self::migrate_legacy_worktree(workspace_dir, &repos[0]).await? {
+        if repos.len() == 1 && Self::migrate_legacy_layout(workspace_dir, &repos[0]).await? {
             return Ok(());
         }
 
@@ -206,6 +411,7 @@ impl WorkspaceManager {
         repos: &[Repo],
     ) -> Result<(), WorkspaceError> {
         info!("Cleaning up workspace at {}", workspace_dir.display());
+        let _lock = WorkspaceDirLock::acquire(workspace_dir, "workspace cleanup").await?;
 
         let cleanup_data: Vec<WorktreeCleanup> = repos
             .iter()
@@ -219,7 +425,7 @@ impl WorkspaceManager {
 
         // Remove the workspace directory itself
         if workspace_dir.exists()
-            && let Err(e) = tokio::fs::remove_dir_all(workspace_dir).await
+            && let Err(e) = remove_dir_all(workspace_dir).await
         {
             debug!(
                 "Could not remove workspace directory {}: {}",
@@ -244,6 +450,15 @@ impl WorkspaceManager {
     pub async fn migrate_legacy_worktree(
         workspace_dir: &Path,
         repo: &Repo,
+    ) -> Result<bool, WorkspaceError> {
+        let _lock = WorkspaceDirLock::acquire(workspace_dir, "workspace migration").await?;
+        Self::migrate_legacy_layout(workspace_dir, repo).await
+    }
+
+    /// [`Self::migrate_legacy_worktree`] for callers already holding the directory lock
+    async fn migrate_legacy_layout(
+        workspace_dir: &Path,
+        repo: &Repo,
     ) -> Result<bool, WorkspaceError> {
         let expected_worktree_path = workspace_dir.join(&repo.name);
 
@@ -273,6 +488,8 @@ impl WorkspaceManager {
                 .unwrap_or_default()
         );
         let temp_path = workspace_dir.with_file_name(temp_name);
+        // The temporary directory has no database row, so keep orphan sweeps away from it
+        let _temp_lock = WorkspaceDirLock::acquire(&temp_path, "workspace migration").await?;
 
         WorktreeManager::move_worktree(&repo.path, workspace_dir, &temp_path).await?;
 
@@ -283,7 +500,7 @@ impl WorkspaceManager {
         WorktreeManager::move_worktree(&repo.path, &temp_path, &expected_worktree_path).await?;
 
         if temp_path.exists() {
-            let _ = tokio::fs::remove_dir_all(&temp_path).await;
+            let _ = remove_dir_all(&temp_path).await;
         }
 
         info!(
@@ -311,26 +528,126 @@ impl WorkspaceManager {
         }
     }
 
-    pub async fn cleanup_orphan_workspaces(db: &Pool<Sqlite>) {
-        if std::env::var("DISABLE_WORKTREE_CLEANUP").is_ok() {
-            info!(
-                "Orphan workspace cleanup is disabled via DISABLE_WORKTREE_CLEANUP environment variable"
-            );
+    /// Remove workspace directories that have no database row.
+    ///
+    /// With the `reconcile_orphan_worktrees` setting, orphans that still hold git worktrees
+    /// are kept so they can be inspected and re-registered through
+    /// [`Self::find_orphan_workspaces`]; only directories without any worktree are removed.
+    pub async fn cleanup_orphan_workspaces(db: &Pool<Sqlite>, settings: &SettingsService) {
+        if settings.disable_worktree_cleanup() {
+            info!("Orphan workspace cleanup is disabled by the disable_worktree_cleanup setting");
             return;
         }
+        let policy = OrphanCleanupPolicy::from_settings(&settings.get());
 
-        // Always clean up the default directory
-        let default_dir = WorktreeManager::get_default_worktree_base_dir();
-        Self::cleanup_orphans_in_directory(db, &default_dir).await;
+        for dir in Self::workspace_base_dirs() {
+            Self::cleanup_orphans_in_directory(db, &dir, &policy).await;
+        }
+    }
 
-        // Also clean up custom directory if it's different from the default
+    /// The default workspace directory, plus the configured one when it differs
+    fn workspace_base_dirs() -> Vec<PathBuf> {
+        let default_dir = WorktreeManager::get_default_worktree_base_dir();
         let current_dir = Self::get_workspace_base_dir();
         if current_dir != default_dir {
-            Self::cleanup_orphans_in_directory(db, &current_dir).await;
+            vec![default_dir, current_dir]
+        } else {
+            vec![default_dir]
         }
     }
 
-    async fn cleanup_orphans_in_directory(db: &Pool<Sqlite>, workspace_base_dir: &Path) {
+    /// List workspace directories with no database row that still contain worktrees,
+    /// matched back to their repositories and branches
+    pub async fn find_orphan_workspaces(
+        db: &Pool<Sqlite>,
+    ) -> Result<Vec<OrphanWorkspace>, WorkspaceError> {
+        let repos = Repo::list_all(db).await?;
+
+        let mut orphans = Vec::new();
+        for base_dir in Self::workspace_base_dirs() {
+            if !base_dir.exists() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&base_dir)?.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                if let Ok(false) =
+                    DbWorkspace::container_ref_exists(db, &path.to_string_lossy()).await
+                {
+                    let worktrees = Self::inspect_orphan_worktrees(path.clone(), &repos).await;
+                    if !worktrees.is_empty() {
+                        orphans.push(OrphanWorkspace { path, worktrees });
+                    }
+                }
+            }
+        }
+        Ok(orphans)
+    }
+
+    async fn inspect_orphan_worktrees(
+        workspace_dir: PathBuf,
+        repos: &[Repo],
+    ) -> Vec<OrphanWorktree> {
+        let repos: Vec<(Uuid, PathBuf)> = repos
+            .iter()
+            .map(|r| {
+                (
+                    r.id,
+                    r.path.canonicalize().unwrap_or_else(|_| r.path.clone()),
+                )
+            })
+            .collect();
+
+        tokio::task::spawn_blocking(move || {
+            let git = GitService::new();
+            let Ok(entries) = std::fs::read_dir(&workspace_dir) else {
+                return Vec::new();
+            };
+
+            let mut worktrees = Vec::new();
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                let Ok(repo) = Repository::open(&path) else {
+                    continue;
+                };
+                if !repo.is_worktree() {
+                    continue;
+                }
+                // commondir is the main repository's .git directory
+                let Some(repo_path) = repo.commondir().parent().map(Path::to_path_buf) else {
+                    continue;
+                };
+                let repo_path = repo_path.canonicalize().unwrap_or(repo_path);
+                let branch = repo
+                    .head()
+                    .ok()
+                    .filter(|head| head.is_branch())
+                    .and_then(|head| head.shorthand().map(str::to_string));
+
+                worktrees.push(OrphanWorktree {
+                    dir_name: entry.file_name().to_string_lossy().to_string(),
+                    repo_id: repos
+                        .iter()
+                        .find(|(_, p)| *p == repo_path)
+                        .map(|(id, _)| *id),
+                    repo_path,
+                    branch,
+                    has_uncommitted_changes: !git.is_worktree_clean(&path).unwrap_or(true),
+                });
+            }
+            worktrees
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn cleanup_orphans_in_directory(
+        db: &Pool<Sqlite>,
+        workspace_base_dir: &Path,
+        policy: &OrphanCleanupPolicy,
+    ) {
         if !workspace_base_dir.exists() {
             debug!(
                 "Workspace base directory {} does not exist, skipping orphan cleanup",
@@ -368,6 +685,47 @@ impl WorkspaceManager {
             let workspace_path_str = path.to_string_lossy().to_string();
             if let Ok(false) = DbWorkspace::container_ref_exists(db, &workspace_path_str).await {
                 info!("Found orphaned workspace: {}", workspace_path_str);
+                if policy.is_excluded(&path) {
+                    info!(
+                        "Keeping orphaned workspace {}; it is excluded from cleanup",
+                        workspace_path_str
+                    );
+                    continue;
+                }
+                if let Some(modified) = Self::last_modified(&path)
+                    && policy.is_recent(modified, SystemTime::now())
+                {
+                    info!(
+                        "Keeping orphaned workspace {} until the next sweep; it was modified recently",
+                        workspace_path_str
+                    );
+                    continue;
+                }
+                if policy.reconcile
+                    && !Self::inspect_orphan_worktrees(path.clone(), &[])
+                        .await
+                        .is_empty()
+                {
+                    info!(
+                        "Keeping orphaned workspace {} for adoption; it still contains worktrees",
+                        workspace_path_str
+                    );
+                    continue;
+                }
+                // Another instance may be creating this workspace and not have saved it yet
+                let _lock = match WorkspaceDirLock::try_acquire(&path, "orphan cleanup").await {
+                    Ok(lock) => lock,
+                    Err(e) => {
+                        info!("Skipping orphaned workspace {}: {}", workspace_path_str, e);
+                        continue;
+                    }
+                };
+                if !matches!(
+                    DbWorkspace::container_ref_exists(db, &workspace_path_str).await,
+                    Ok(false)
+                ) {
+                    continue;
+                }
                 if let Err(e) = Self::cleanup_workspace_without_repos(&path).await {
                     error!(
                         "Failed to remove orphaned workspace {}: {}",
@@ -383,6 +741,16 @@ impl WorkspaceManager {
         }
     }
 
+    /// Newest modification time of the directory and its direct entries
+    fn last_modified(dir: &Path) -> Option<SystemTime> {
+        let own = std::fs::metadata(dir).and_then(|m| m.modified()).ok();
+        let entries = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok()?.metadata().and_then(|m| m.modified()).ok());
+        own.into_iter().chain(entries).max()
+    }
+
     async fn cleanup_workspace_without_repos(workspace_dir: &Path) -> Result<(), WorkspaceError> {
         info!(
             "Cleaning up orphaned workspace at {}",
@@ -397,7 +765,7 @@ impl WorkspaceManager {
                     workspace_dir.display(),
                     e
                 );
-                return tokio::fs::remove_dir_all(workspace_dir)
+                return remove_dir_all(workspace_dir)
                     .await
                     .map_err(WorkspaceError::Io);
             }
@@ -413,7 +781,7 @@ impl WorkspaceManager {
         }
 
         if workspace_dir.exists()
-            && let Err(e) = tokio::fs::remove_dir_all(workspace_dir).await
+            && let Err(e) = remove_dir_all(workspace_dir).await
         {
             debug!(
                 "Could not remove workspace directory {}: {}",
@@ -433,10 +801,13 @@ impl WorkspaceManager {
     /// * `workspace_dir` - The container_ref path where worktrees are located
     /// * `repos` - List of repositories in the workspace
     /// * `branch_name` - The workspace branch to delete from each repo
+    /// * `keep_branch_repo_ids` - Repos whose workspace branch is kept, e.g. because a PR
+    ///   was opened from it
     pub async fn close_workspace_discard(
         workspace_dir: &Path,
         repos: &[Repo],
         branch_name: &str,
+        keep_branch_repo_ids: &[Uuid],
     ) -> Result<(), WorkspaceError> {
         info!(
             "Closing workspace (discard): {} with branch '{}'",
@@ -449,7 +820,10 @@ impl WorkspaceManager {
 
         // Step 2: Delete the workspace branch from each repository
         let git = GitService::new();
-        for repo in repos {
+        for repo in repos
+            .iter()
+            .filter(|repo| !keep_branch_repo_ids.contains(&repo.id))
+        {
             debug!(
                 "Deleting branch '{}' from repo '{}'",
                 branch_name, repo.name
@@ -470,6 +844,7 @@ impl WorkspaceManager {
     /// Close workspace with merge: merge workspace branch into target branch for each repo.
     /// Returns the merge commit SHA for each repo.
     /// Does NOT cleanup the workspace - caller should call close_workspace_discard after recording merges.
+    /// Merges into the same repo are serialized through [`MergeQueue`].
     ///
     /// # Arguments
     /// * `repos_with_targets` - List of (Repo, target_branch) pairs
@@ -495,7 +870,11 @@ impl WorkspaceManager {
                 workspace_branch, target_branch, repo.name
             );
 
-            // Perform the merge in the main repo (not the worktree)
+            // Wait for other merges into this repo, then perform the merge in the main repo
+            // (not the worktree). The slot moves into the blocking task so it stays held
+            // until the merge finishes even if this future is dropped.
+            let slot = MergeQueue::acquire(&repo.path).await;
+            let queue_position = slot.position;
             let merge_commit_sha = tokio::task::spawn_blocking({
                 let git = git.clone();
                 let repo_path = repo.path.clone();
@@ -503,6 +882,7 @@ impl WorkspaceManager {
                 let workspace_branch = workspace_branch.to_string();
                 let commit_message = commit_message.to_string();
                 move || {
+                    let _slot = slot;
                     git.merge_into_branch(
                         &repo_path,
                         &target_branch,
@@ -529,6 +909,7 @@ impl WorkspaceManager {
                 repo_name: repo.name.clone(),
                 merge_commit_sha,
                 target_branch: target_branch.clone(),
+                queue_position,
             });
         }
 
@@ -538,4 +919,355 @@ impl WorkspaceManager {
         );
         Ok(results)
     }
+
+    /// Merge a workspace branch into its target branch, as [`GitService::merge_changes`]
+    /// does, once it is the repo's turn in the [`MergeQueue`]
+    pub async fn merge_changes(
+        git: &GitService,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+        target_branch: &str,
+        commit_message: &str,
+    ) -> Result<String, GitServiceError> {
+        let git = git.clone();
+        let (repo, worktree) = (repo_path.to_path_buf(), worktree_path.to_path_buf());
+        let (branch, target_branch) = (branch.to_string(), target_branch.to_string());
+        let commit_message = commit_message.to_string();
+        Self::queued_merge(repo_path, move || {
+            git.merge_changes(&repo, &worktree, &branch, &target_branch, &commit_message)
+        })
+        .await
+    }
+
+    /// Merge a selection of a workspace branch's files into its target branch, as
+    /// [`GitService::merge_paths`] does, once it is the repo's turn in the [`MergeQueue`]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn merge_paths(
+        git: &GitService,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+        target_branch: &str,
+        paths: &[String],
+        commit_message: &str,
+        remainder_message: &str,
+    ) -> Result<PathMergeResult, GitServiceError> {
+        let git = git.clone();
+        let (repo, worktree) = (repo_path.to_path_buf(), worktree_path.to_path_buf());
+        let (branch, target_branch) = (branch.to_string(), target_branch.to_string());
+        let paths = paths.to_vec();
+        let (commit_message, remainder_message) =
+            (commit_message.to_string(), remainder_message.to_string());
+        Self::queued_merge(repo_path, move || {
+            git.merge_paths(
+                &repo,
+                &worktree,
+                &branch,
+                &target_branch,
+                &paths,
+                &commit_message,
+                &remainder_message,
+            )
+        })
+        .await
+    }
+
+    /// Merge a target branch into a workspace's worktree of the repo at `repo_path`, as
+    /// [`GitService::merge_target_into_worktree`] does, once it is the repo's turn in the
+    /// [`MergeQueue`]
+    pub async fn merge_target_into_worktree(
+        git: &GitService,
+        repo_path: &Path,
+        worktree_path: &Path,
+        target_branch: &str,
+        message: &str,
+    ) -> Result<Vec<String>, GitServiceError> {
+        let git = git.clone();
+        let worktree = worktree_path.to_path_buf();
+        let (target_branch, message) = (target_branch.to_string(), message.to_string());
+        Self::queued_merge(repo_path, move || {
+            git.merge_target_into_worktree(&worktree, &target_branch, &message)
+        })
+        .await
+    }
+
+    /// Run `merge` on a blocking thread once it is the turn of the repo at `repo_path` in
+    /// the [`MergeQueue`]. The slot moves into the blocking task so it stays held until the
+    /// merge finishes even if this future is dropped.
+    async fn queued_merge<T: Send + 'static>(
+        repo_path: &Path,
+        merge: impl FnOnce() -> Result<T, GitServiceError> + Send + 'static,
+    ) -> Result<T, GitServiceError> {
+        let slot = MergeQueue::acquire(repo_path).await;
+        tokio::task::spawn_blocking(move || {
+            let _slot = slot;
+            merge()
+        })
+        .await
+        .map_err(|e| GitServiceError::IoError(std::io::Error::other(e)))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(exclusions: &[&str]) -> OrphanCleanupPolicy {
+        OrphanCleanupPolicy {
+            reconcile: false,
+            grace_period: Duration::from_secs(3600),
+            exclusions: exclusions.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn exclusions_match_names_and_absolute_paths() {
+        let policy = policy(&["keep-me", "/srv/workspaces/pinned"]);
+        assert!(policy.is_excluded(Path::new("/tmp/vibe-kanban/keep-me")));
+        assert!(policy.is_excluded(Path::new("/srv/workspaces/pinned")));
+        assert!(!policy.is_excluded(Path::new("/tmp/vibe-kanban/pinned")));
+        assert!(!policy.is_excluded(Path::new("/tmp/vibe-kanban/keep-me-not")));
+    }
+
+    #[test]
+    fn repo_names_differing_only_in_case_collide() {
+        assert_eq!(
+            find_repo_name_collision(["api", "web", "API"]),
+            Some(("api", "API"))
+        );
+        assert_eq!(find_repo_name_collision(["api", "web", "api-client"]), None);
+        assert!(matches!(
+            check_repo_names(["Docs", "docs"]),
+            Err(WorkspaceError::RepoNameCollision(..))
+        ));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn exclusions_ignore_case_and_separators_on_windows() {
+        let policy = policy(&["Keep-Me", r"C:\Workspaces\Pinned"]);
+        assert!(policy.is_excluded(Path::new(r"C:\Temp\vibe-kanban\keep-me")));
+        assert!(policy.is_excluded(Path::new("c:/workspaces/pinned")));
+    }
+
+    #[test]
+    fn recently_modified_directories_are_within_the_grace_period() {
+        let policy = policy(&[]);
+        let now = SystemTime::now();
+        assert!(policy.is_recent(now - Duration::from_secs(600), now));
+        assert!(!policy.is_recent(now - Duration::from_secs(7200), now));
+        // Clock skew puts the modification in the future; treat it as recent
+        assert!(policy.is_recent(now + Duration::from_secs(60), now));
+    }
+
+    #[tokio::test]
+    async fn orphan_worktrees_are_matched_to_their_repos() {
+        use crate::services::test_support::{init_repo, run_git, test_pool};
+
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path().join("api");
+        init_repo(&repo_path, "README.md", "# api\n");
+        let repo = Repo::find_or_create(&pool, &repo_path, "api")
+            .await
+            .unwrap();
+        let unregistered_path = dir.path().join("web");
+        init_repo(&unregistered_path, "README.md", "# web\n");
+
+        let workspace_dir = dir.path().join("workspace");
+        std::fs::create_dir_all(workspace_dir.join("notes")).unwrap();
+        let api_worktree = workspace_dir.join("api");
+        run_git(
+            &repo_path,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "vk/login",
+                &api_worktree.to_string_lossy(),
+            ],
+        );
+        std::fs::write(api_worktree.join("README.md"), "# changed\n").unwrap();
+        run_git(
+            &unregistered_path,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "--detach",
+                &workspace_dir.join("web").to_string_lossy(),
+            ],
+        );
+
+        let mut worktrees =
+            WorkspaceManager::inspect_orphan_worktrees(workspace_dir, &[repo.clone()]).await;
+        worktrees.sort_by(|a, b| a.dir_name.cmp(&b.dir_name));
+
+        assert_eq!(worktrees.len(), 2);
+        let (api, web) = (&worktrees[0], &worktrees[1]);
+        assert_eq!(api.dir_name, "api");
+        assert_eq!(api.repo_id, Some(repo.id));
+        assert_eq!(api.branch.as_deref(), Some("vk/login"));
+        assert!(api.has_uncommitted_changes);
+        assert_eq!(web.repo_id, None);
+        assert_eq!(web.repo_path, unregistered_path.canonicalize().unwrap());
+        assert_eq!(web.branch, None);
+        assert!(!web.has_uncommitted_changes);
+    }
+
+    #[tokio::test]
+    async fn repos_are_added_to_an_open_workspace_on_its_branch() {
+        use crate::services::test_support::{init_repo, run_git, test_pool};
+
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let api_path = dir.path().join("api");
+        init_repo(&api_path, "README.md", "# api\n");
+        let api = Repo::find_or_create(&pool, &api_path, "api").await.unwrap();
+        let web_path = dir.path().join("web");
+        init_repo(&web_path, "README.md", "# web\n");
+        std::fs::create_dir_all(web_path.join("src")).unwrap();
+        std::fs::write(web_path.join("src/app.ts"), "export {};\n").unwrap();
+        std::fs::create_dir_all(web_path.join("docs")).unwrap();
+        std::fs::write(web_path.join("docs/guide.md"), "# Guide\n").unwrap();
+        run_git(&web_path, &["add", "-A"]);
+        run_git(&web_path, &["commit", "-q", "-m", "Add app and docs"]);
+        let web = Repo::find_or_create(&pool, &web_path, "web").await.unwrap();
+
+        let workspace_dir = dir.path().join("workspace");
+        std::fs::create_dir_all(&workspace_dir).unwrap();
+        let input = RepoWorkspaceInput::new(web.clone(), "main".to_string())
+            .with_sparse_checkout(Some("src".to_string()));
+        let worktree = WorkspaceManager::add_repo_worktree(
+            &workspace_dir,
+            std::slice::from_ref(&api),
+            &input,
+            "vk/login",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(worktree.repo_id, web.id);
+        assert_eq!(worktree.worktree_path, workspace_dir.join("web"));
+        assert_eq!(
+            run_git(&worktree.worktree_path, &["branch", "--show-current"]).trim(),
+            "vk/login"
+        );
+        assert!(worktree.worktree_path.join("README.md").is_file());
+        assert!(worktree.worktree_path.join("src/app.ts").is_file());
+        assert!(!worktree.worktree_path.join("docs").exists());
+
+        // Worktree directories are named after the repos, so names must differ beyond case
+        let shouting_path = dir.path().join("other").join("API");
+        init_repo(&shouting_path, "README.md", "# API\n");
+        let shouting = Repo::find_or_create(&pool, &shouting_path, "API")
+            .await
+            .unwrap();
+        let result = WorkspaceManager::add_repo_worktree(
+            &workspace_dir,
+            &[api, web],
+            &RepoWorkspaceInput::new(shouting, "main".to_string()),
+            "vk/login",
+        )
+        .await;
+        assert!(matches!(result, Err(WorkspaceError::RepoNameCollision(..))));
+        assert!(!workspace_dir.join("API").exists());
+    }
+
+    #[tokio::test]
+    async fn discarding_keeps_the_branches_prs_were_opened_from() {
+        use crate::services::test_support::{init_repo, run_git, test_pool};
+
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let workspace_dir = dir.path().join("workspace");
+        let mut repos = Vec::new();
+        for name in ["api", "web"] {
+            let path = dir.path().join("repos").join(name);
+            init_repo(&path, "README.md", &format!("# {name}\n"));
+            let worktree = workspace_dir.join(name);
+            run_git(
+                &path,
+                &[
+                    "worktree",
+                    "add",
+                    "-q",
+                    "-b",
+                    "vk/login",
+                    &worktree.to_string_lossy(),
+                ],
+            );
+            repos.push(Repo::find_or_create(&pool, &path, name).await.unwrap());
+        }
+
+        WorkspaceManager::close_workspace_discard(
+            &workspace_dir,
+            &repos,
+            "vk/login",
+            &[repos[1].id],
+        )
+        .await
+        .unwrap();
+
+        assert!(!workspace_dir.exists());
+        let git = GitService::new();
+        assert!(!git.check_branch_exists(&repos[0].path, "vk/login").unwrap());
+        assert!(git.check_branch_exists(&repos[1].path, "vk/login").unwrap());
+    }
+
+    #[tokio::test]
+    async fn merges_into_a_repo_wait_for_its_queue() {
+        use crate::services::test_support::{init_repo, run_git};
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path().join("api");
+        init_repo(&repo_path, "README.md", "# api\n");
+        run_git(&repo_path, &["config", "user.name", "Test"]);
+        run_git(&repo_path, &["config", "user.email", "test@example.com"]);
+        let worktree = dir.path().join("workspace").join("api");
+        run_git(
+            &repo_path,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                &worktree.to_string_lossy(),
+            ],
+        );
+        std::fs::write(worktree.join("login.rs"), "fn login() {}\n").unwrap();
+        run_git(&worktree, &["add", "-A"]);
+        run_git(&worktree, &["commit", "-q", "-m", "Add login"]);
+
+        let slot = MergeQueue::acquire(&repo_path).await;
+        let merge = tokio::spawn({
+            let (repo_path, worktree) = (repo_path.clone(), worktree.clone());
+            async move {
+                WorkspaceManager::merge_changes(
+                    &GitService::new(),
+                    &repo_path,
+                    &worktree,
+                    "feature",
+                    "main",
+                    "Add login",
+                )
+                .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!merge.is_finished());
+        assert_eq!(MergeQueue::depth(&repo_path), 2);
+        assert_eq!(run_git(&repo_path, &["show", "main:login.rs"]), "");
+
+        drop(slot);
+        merge.await.unwrap().unwrap();
+        assert_eq!(MergeQueue::depth(&repo_path), 0);
+        assert_eq!(
+            run_git(&repo_path, &["show", "main:login.rs"]),
+            "fn login() {}\n"
+        );
+    }
 }
@@ -2,18 +2,22 @@ use std::{
     collections::HashMap,
     fs,
     path::{Path, PathBuf},
-    sync::{Arc, LazyLock, Mutex, OnceLock},
+    sync::{Arc, LazyLock, Mutex, RwLock},
 };
 
-static WORKSPACE_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+static WORKSPACE_DIR_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
 
 use git::{GitService, GitServiceError};
 use git2::{Error as GitError, Repository};
 use thiserror::Error;
 use tracing::{debug, info, trace};
-use utils::{path::normalize_macos_private_alias, shell::resolve_executable_path};
+use utils::{
+    path::{normalize_macos_private_alias, path_key, remove_dir_all_with_retry},
+    shell::resolve_executable_path,
+};
 
-// Global synchronization for worktree creation to prevent race conditions
+// Global synchronization for worktree creation to prevent race conditions.
+// Keyed by `path_key` so differently spelled paths to one worktree share a lock on Windows.
 static WORKTREE_CREATION_LOCKS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
@@ -55,8 +59,9 @@ pub enum WorktreeError {
 pub struct WorktreeManager;
 
 impl WorktreeManager {
-    pub fn set_workspace_dir_override(path: PathBuf) {
-        let _ = WORKSPACE_DIR_OVERRIDE.set(path);
+    /// Create new workspaces under `path`, or under the default directory with `None`
+    pub fn set_workspace_dir_override(path: Option<PathBuf>) {
+        *WORKSPACE_DIR_OVERRIDE.write().unwrap() = path;
     }
 
     /// Create a worktree with a new branch
@@ -135,7 +140,7 @@ impl WorktreeManager {
         let lock = {
             let mut locks = WORKTREE_CREATION_LOCKS.lock().unwrap();
             locks
-                .entry(path_str.clone())
+                .entry(path_key(worktree_path))
                 .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
                 .clone()
         };
@@ -228,8 +233,9 @@ impl WorktreeManager {
         git_repo_path: &Path,
         worktree_path: &Path,
     ) -> Result<Option<String>, WorktreeError> {
-        fn canonicalize_for_compare(path: &Path) -> PathBuf {
-            dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+        // git writes forward slashes on Windows, so compare keys rather than paths
+        fn canonicalize_for_compare(path: &Path) -> String {
+            path_key(dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
         }
 
         let worktree_root = canonicalize_for_compare(&normalize_macos_private_alias(worktree_path));
@@ -296,7 +302,7 @@ impl WorktreeManager {
                 "Removing existing worktree directory: {}",
                 worktree_path.display()
             );
-            std::fs::remove_dir_all(worktree_path).map_err(WorktreeError::Io)?;
+            remove_dir_all_with_retry(worktree_path).map_err(WorktreeError::Io)?;
         }
 
         // Step 4: Good-practice to clean up any other stale admin entries
@@ -385,7 +391,7 @@ impl WorktreeManager {
                     // Clean up physical directory if it exists
                     // Needed if previous attempt failed after directory creation
                     if worktree_path.exists() {
-                        std::fs::remove_dir_all(&worktree_path).map_err(WorktreeError::Io)?;
+                        remove_dir_all_with_retry(&worktree_path).map_err(WorktreeError::Io)?;
                     }
                     if let Err(e2) = git_service.add_worktree(
                         &git_repo_path,
@@ -441,7 +447,7 @@ impl WorktreeManager {
                     "Force removing git worktree metadata: {}",
                     git_worktree_metadata_path.display()
                 );
-                std::fs::remove_dir_all(&git_worktree_metadata_path)?;
+                remove_dir_all_with_retry(&git_worktree_metadata_path)?;
             }
         }
 
@@ -469,7 +475,7 @@ impl WorktreeManager {
         let lock = {
             let mut locks = WORKTREE_CREATION_LOCKS.lock().unwrap();
             locks
-                .entry(path_str.clone())
+                .entry(path_key(&worktree.worktree_path))
                 .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
                 .clone()
         };
@@ -534,7 +540,7 @@ impl WorktreeManager {
 
         tokio::task::spawn_blocking(move || -> Result<(), WorktreeError> {
             if worktree_path_owned.exists() {
-                std::fs::remove_dir_all(&worktree_path_owned).map_err(WorktreeError::Io)?;
+                remove_dir_all_with_retry(&worktree_path_owned).map_err(WorktreeError::Io)?;
                 info!(
                     "Removed worktree directory: {}",
                     worktree_path_owned.display()
@@ -568,7 +574,7 @@ impl WorktreeManager {
 
     /// Get the base directory for vibe-kanban worktrees
     pub fn get_worktree_base_dir() -> std::path::PathBuf {
-        if let Some(override_path) = WORKSPACE_DIR_OVERRIDE.get() {
+        if let Some(override_path) = WORKSPACE_DIR_OVERRIDE.read().unwrap().as_ref() {
             // Always use app-owned subdirectory within custom path for safety.
             // This ensures orphan cleanup never touches user's existing folders.
             return override_path.join(".vibe-kanban-workspaces");
@@ -638,3 +644,53 @@ async fn create_worktree_when_repo_path_is_a_worktree() {
     .await
     .unwrap();
 }
+
+#[cfg(windows)]
+#[tokio::test]
+async fn worktree_lifecycle_handles_long_paths_and_respelled_paths_on_windows() {
+    use tempfile::TempDir;
+    let td = TempDir::new().unwrap();
+
+    let repo_path = td.path().join("repo");
+    GitService::new()
+        .initialize_repo_with_main_branch(&repo_path)
+        .unwrap();
+
+    // Deep enough that files git writes under the worktree exceed MAX_PATH
+    let workspace_dir = (0..8).fold(td.path().to_path_buf(), |dir, i| {
+        dir.join(format!("nested-workspace-directory-{i:02}"))
+    });
+    let worktree_path = workspace_dir.join("repo");
+    WorktreeManager::create_worktree(&repo_path, "vk/long-path", &worktree_path, "main", true)
+        .await
+        .unwrap();
+    assert!(worktree_path.join(".git").is_file());
+
+    // git reports worktree paths with forward slashes, and drive letter case varies
+    let respelled = PathBuf::from(
+        worktree_path
+            .to_string_lossy()
+            .replace('\\', "/")
+            .to_uppercase(),
+    );
+    assert!(
+        WorktreeManager::is_worktree_properly_set_up(&repo_path, &respelled)
+            .await
+            .unwrap()
+    );
+
+    // Read-only files, like the ones git writes for objects, must not block cleanup
+    let readonly = worktree_path.join("readonly.txt");
+    std::fs::write(&readonly, "locked").unwrap();
+    let mut permissions = std::fs::metadata(&readonly).unwrap().permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(&readonly, permissions).unwrap();
+
+    WorktreeManager::cleanup_worktree(&WorktreeCleanup::new(
+        worktree_path.clone(),
+        Some(repo_path.clone()),
+    ))
+    .await
+    .unwrap();
+    assert!(!worktree_path.exists());
+}
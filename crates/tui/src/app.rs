@@ -0,0 +1,126 @@
+use cli::api::Task;
+use uuid::Uuid;
+
+/// Board columns: task status as sent by the server, and its heading
+pub const COLUMNS: [(&str, &str); 5] = [
+    ("todo", "To do"),
+    ("inprogress", "In progress"),
+    ("inreview", "In review"),
+    ("done", "Done"),
+    ("cancelled", "Cancelled"),
+];
+
+pub enum Mode {
+    Board,
+    Diff {
+        title: String,
+        lines: Vec<String>,
+        scroll: u16,
+    },
+    ConfirmClose {
+        workspace_id: Uuid,
+        title: String,
+    },
+}
+
+pub struct App {
+    pub columns: Vec<Vec<Task>>,
+    pub selected_column: usize,
+    pub selected_rows: [usize; COLUMNS.len()],
+    pub mode: Mode,
+    pub status: String,
+    pub connected: bool,
+    pub should_quit: bool,
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self {
+            columns: COLUMNS.iter().map(|_| Vec::new()).collect(),
+            selected_column: 0,
+            selected_rows: [0; COLUMNS.len()],
+            mode: Mode::Board,
+            status: "Loading…".to_string(),
+            connected: false,
+            should_quit: false,
+        }
+    }
+
+    /// Replace the board contents, keeping the cursor on the same task when it still exists
+    pub fn set_tasks(&mut self, tasks: Vec<Task>) {
+        let selected = self.selected_task().map(|task| task.id);
+        for column in &mut self.columns {
+            column.clear();
+        }
+        for task in tasks {
+            if let Some(index) = COLUMNS
+                .iter()
+                .position(|(status, _)| *status == task.status)
+            {
+                self.columns[index].push(task);
+            }
+        }
+
+        for (index, column) in self.columns.iter().enumerate() {
+            let row = &mut self.selected_rows[index];
+            *row = (*row).min(column.len().saturating_sub(1));
+            if let Some(id) = selected
+                && let Some(position) = column.iter().position(|task| task.id == id)
+            {
+                *row = position;
+                self.selected_column = index;
+            }
+        }
+    }
+
+    pub fn selected_task(&self) -> Option<&Task> {
+        self.columns[self.selected_column].get(self.selected_rows[self.selected_column])
+    }
+
+    pub fn move_column(&mut self, delta: isize) {
+        self.selected_column = self
+            .selected_column
+            .saturating_add_signed(delta)
+            .min(COLUMNS.len() - 1);
+    }
+
+    pub fn move_row(&mut self, delta: isize) {
+        let len = self.columns[self.selected_column].len();
+        let row = &mut self.selected_rows[self.selected_column];
+        *row = row.saturating_add_signed(delta).min(len.saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(title: &str, status: &str) -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            status: status.to_string(),
+            priority: None,
+            labels: Vec::new(),
+            assignee: None,
+            has_in_progress_attempt: false,
+            last_attempt_failed: false,
+        }
+    }
+
+    #[test]
+    fn cursor_follows_a_task_that_changes_column() {
+        let mut app = App::new();
+        let moving = task("moving", "todo");
+        let id = moving.id;
+        app.set_tasks(vec![task("other", "todo"), moving]);
+        app.move_row(1);
+        assert_eq!(app.selected_task().unwrap().id, id);
+
+        let mut moved = task("moving", "inreview");
+        moved.id = id;
+        app.set_tasks(vec![task("other", "todo"), moved]);
+        assert_eq!(app.selected_column, 2);
+        assert_eq!(app.selected_task().unwrap().id, id);
+    }
+}
@@ -0,0 +1,200 @@
+mod app;
+mod sse;
+mod ui;
+
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use app::{App, Mode};
+use clap::Parser;
+use cli::{api::ApiClient, resolve_server_url};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind};
+use futures_util::StreamExt;
+use ratatui::DefaultTerminal;
+use sse::BoardUpdate;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Lines moved by PgUp/PgDn in the diff view
+const DIFF_PAGE: u16 = 20;
+
+#[derive(Parser, Debug)]
+#[command(name = "ralph-tui")]
+#[command(about = "Terminal kanban board for a running ralph-kanban server")]
+#[command(version)]
+struct Args {
+    /// Project to show
+    #[arg(long, env = "RALPH_PROJECT_ID")]
+    project: Uuid,
+
+    /// Server URL; defaults to the locally running server
+    #[arg(long, env = "RALPH_SERVER_URL")]
+    server: Option<String>,
+}
+
+async fn refresh(app: &mut App, client: &ApiClient, project_id: Uuid) {
+    match client.list_tasks(project_id, None).await {
+        Ok(tasks) => {
+            app.status = format!("{} tasks", tasks.len());
+            app.set_tasks(tasks);
+        }
+        Err(e) => app.status = format!("Refresh failed: {e}"),
+    }
+}
+
+/// Latest workspace of the selected task that has not been archived
+async fn selected_workspace(app: &App, client: &ApiClient) -> Result<(Uuid, String)> {
+    let task = app
+        .selected_task()
+        .ok_or_else(|| anyhow!("No task selected"))?;
+    let workspace = client
+        .list_workspaces(task.id)
+        .await?
+        .into_iter()
+        .find(|workspace| !workspace.archived)
+        .ok_or_else(|| anyhow!("\"{}\" has no open workspace", task.title))?;
+    Ok((workspace.id, task.title.clone()))
+}
+
+async fn handle_key(app: &mut App, client: &ApiClient, project_id: Uuid, key: KeyEvent) {
+    match &mut app.mode {
+        Mode::Board => match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
+            KeyCode::Left | KeyCode::Char('h') => app.move_column(-1),
+            KeyCode::Right | KeyCode::Char('l') => app.move_column(1),
+            KeyCode::Up | KeyCode::Char('k') => app.move_row(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.move_row(1),
+            KeyCode::Char('r') => refresh(app, client, project_id).await,
+            KeyCode::Char('d') => {
+                let diff = async {
+                    let (workspace_id, title) = selected_workspace(app, client).await?;
+                    let diff = client.workspace_diff(workspace_id).await?;
+                    let lines = diff
+                        .files
+                        .iter()
+                        .flat_map(|file| file.diff_content.lines().map(str::to_string))
+                        .collect::<Vec<_>>();
+                    Ok::<_, anyhow::Error>((title, lines))
+                };
+                let diff = diff.await;
+                match diff {
+                    Ok((title, lines)) if lines.is_empty() => {
+                        app.status = format!("\"{title}\" has no changes");
+                    }
+                    Ok((title, lines)) => {
+                        app.mode = Mode::Diff {
+                            title,
+                            lines,
+                            scroll: 0,
+                        };
+                    }
+                    Err(e) => app.status = e.to_string(),
+                }
+            }
+            KeyCode::Char('c') => match selected_workspace(app, client).await {
+                Ok((workspace_id, title)) => {
+                    app.mode = Mode::ConfirmClose {
+                        workspace_id,
+                        title,
+                    }
+                }
+                Err(e) => app.status = e.to_string(),
+            },
+            _ => {}
+        },
+        Mode::Diff { lines, scroll, .. } => {
+            let max_scroll = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => app.mode = Mode::Board,
+                KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => *scroll = (*scroll + 1).min(max_scroll),
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(DIFF_PAGE),
+                KeyCode::PageDown => *scroll = scroll.saturating_add(DIFF_PAGE).min(max_scroll),
+                _ => {}
+            }
+        }
+        Mode::ConfirmClose { workspace_id, .. } => {
+            let workspace_id = *workspace_id;
+            let strategy = match key.code {
+                KeyCode::Char('m') => "merge",
+                KeyCode::Char('x') => "discard",
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    app.mode = Mode::Board;
+                    return;
+                }
+                _ => return,
+            };
+            app.mode = Mode::Board;
+            app.status = match client.close_workspace(workspace_id, strategy, false).await {
+                Ok(result) => result.message,
+                Err(e) => format!("Close failed: {e}"),
+            };
+        }
+    }
+}
+
+async fn run(
+    terminal: &mut DefaultTerminal,
+    client: Arc<ApiClient>,
+    project_id: Uuid,
+) -> Result<()> {
+    let mut app = App::new();
+    let (updates_tx, mut updates) = mpsc::unbounded_channel();
+    sse::spawn(client.clone(), project_id, updates_tx);
+    refresh(&mut app, &client, project_id).await;
+
+    let mut keys = EventStream::new();
+    while !app.should_quit {
+        terminal.draw(|frame| ui::render(frame, &app))?;
+
+        tokio::select! {
+            event = keys.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                    handle_key(&mut app, &client, project_id, key).await;
+                }
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+                _ => {}
+            },
+            Some(update) = updates.recv() => {
+                let mut changed = false;
+                let mut next = Some(update);
+                // Coalesce bursts of events into a single refetch
+                while let Some(update) = next {
+                    match update {
+                        BoardUpdate::Connected => {
+                            app.connected = true;
+                            changed = true;
+                        }
+                        BoardUpdate::Changed => changed = true,
+                        BoardUpdate::Disconnected(reason) => {
+                            app.connected = false;
+                            app.status = format!("Event stream lost: {reason}");
+                        }
+                    }
+                    next = updates.try_recv().ok();
+                }
+                if changed {
+                    refresh(&mut app, &client, project_id).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Install rustls crypto provider before any TLS operations
+    rustls::crypto::aws_lc_rs::default_provider()
+        .install_default()
+        .expect("Failed to install rustls crypto provider");
+
+    let args = Args::parse();
+    let client = Arc::new(ApiClient::new(resolve_server_url(args.server)?));
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, client, args.project).await;
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,146 @@
+//! Follows the project event stream and tells the board when to refresh.
+
+use std::{sync::Arc, time::Duration};
+
+use cli::api::ApiClient;
+use futures_util::StreamExt;
+use tokio::sync::mpsc::UnboundedSender;
+use uuid::Uuid;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+#[derive(Debug)]
+pub enum BoardUpdate {
+    Connected,
+    /// A task, workspace or process of the project changed
+    Changed,
+    Disconnected(String),
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    pub id: Option<u64>,
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Incremental `text/event-stream` parser; feed it response chunks as they arrive
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    current: SseEvent,
+    has_fields: bool,
+}
+
+impl SseParser {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if self.has_fields {
+                    events.push(std::mem::take(&mut self.current));
+                    self.has_fields = false;
+                }
+                continue;
+            }
+            // Lines starting with ':' are comments, used for keep-alives
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "id" => self.current.id = value.parse().ok(),
+                "event" => self.current.event = Some(value.to_string()),
+                "data" => {
+                    if !self.current.data.is_empty() {
+                        self.current.data.push('\n');
+                    }
+                    self.current.data.push_str(value);
+                }
+                _ => continue,
+            }
+            self.has_fields = true;
+        }
+        events
+    }
+}
+
+/// Keep a connection to the project's event stream open, reconnecting with
+/// `Last-Event-ID` so no change is missed, until the receiver is dropped.
+pub fn spawn(client: Arc<ApiClient>, project_id: Uuid, updates: UnboundedSender<BoardUpdate>) {
+    tokio::spawn(async move {
+        let mut last_event_id = None;
+        loop {
+            let reason = match client.project_events(project_id, last_event_id).await {
+                Ok(response) => {
+                    if updates.send(BoardUpdate::Connected).is_err() {
+                        return;
+                    }
+                    let mut parser = SseParser::default();
+                    let mut body = response.bytes_stream();
+                    loop {
+                        match body.next().await {
+                            Some(Ok(chunk)) => {
+                                for event in parser.push(&chunk) {
+                                    if event.id.is_some() {
+                                        last_event_id = event.id;
+                                    }
+                                    if updates.send(BoardUpdate::Changed).is_err() {
+                                        return;
+                                    }
+                                }
+                            }
+                            Some(Err(e)) => break e.to_string(),
+                            None => break "stream closed".to_string(),
+                        }
+                    }
+                }
+                Err(e) => e.to_string(),
+            };
+
+            if updates.send(BoardUpdate::Disconnected(reason)).is_err() {
+                return;
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_events_split_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(
+            parser
+                .push(b": keep-alive\n\nid: 7\nevent: task.up")
+                .is_empty()
+        );
+
+        let events = parser.push(b"dated\ndata: {\"a\":1}\r\n\nid: 8\ndata: x\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    id: Some(7),
+                    event: Some("task.updated".to_string()),
+                    data: "{\"a\":1}".to_string(),
+                },
+                SseEvent {
+                    id: Some(8),
+                    event: None,
+                    data: "x".to_string(),
+                },
+            ]
+        );
+    }
+}
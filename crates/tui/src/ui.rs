@@ -0,0 +1,134 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::app::{App, COLUMNS, Mode};
+
+const BOARD_HELP: &str = "←/→ column  ↑/↓ task  d diff  c close  r refresh  q quit";
+const DIFF_HELP: &str = "↑/↓ scroll  PgUp/PgDn page  q back";
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+    let help = match &app.mode {
+        Mode::Diff {
+            title,
+            lines,
+            scroll,
+        } => {
+            render_diff(frame, main, title, lines, *scroll);
+            DIFF_HELP
+        }
+        Mode::Board | Mode::ConfirmClose { .. } => {
+            render_board(frame, main, app);
+            BOARD_HELP
+        }
+    };
+    if let Mode::ConfirmClose { title, .. } = &app.mode {
+        render_confirm_close(frame, main, title);
+    }
+
+    let connection = if app.connected {
+        Span::styled("● live ", Style::default().fg(Color::Green))
+    } else {
+        Span::styled("● offline ", Style::default().fg(Color::Red))
+    };
+    let status_line = Line::from(vec![
+        connection,
+        Span::raw(&app.status),
+        Span::styled(format!("  {help}"), Style::default().fg(Color::DarkGray)),
+    ]);
+    frame.render_widget(Paragraph::new(status_line), status);
+}
+
+fn render_board(frame: &mut Frame, area: Rect, app: &App) {
+    let areas =
+        Layout::horizontal([Constraint::Ratio(1, COLUMNS.len() as u32); COLUMNS.len()]).split(area);
+
+    for (index, ((_, heading), tasks)) in COLUMNS.iter().zip(&app.columns).enumerate() {
+        let focused = index == app.selected_column;
+        let items: Vec<ListItem> = tasks
+            .iter()
+            .map(|task| {
+                let marker = if task.has_in_progress_attempt {
+                    Span::styled("▶ ", Style::default().fg(Color::Yellow))
+                } else if task.last_attempt_failed {
+                    Span::styled("✗ ", Style::default().fg(Color::Red))
+                } else {
+                    Span::raw("  ")
+                };
+                ListItem::new(Line::from(vec![marker, Span::raw(task.title.as_str())]))
+            })
+            .collect();
+
+        let border_style = if focused {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        };
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(format!(" {heading} ({}) ", tasks.len()))
+                    .border_style(border_style),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state =
+            ListState::default().with_selected(focused.then_some(app.selected_rows[index]));
+        frame.render_stateful_widget(list, areas[index], &mut state);
+    }
+}
+
+fn render_diff(frame: &mut Frame, area: Rect, title: &str, lines: &[String], scroll: u16) {
+    let lines: Vec<Line> = lines
+        .iter()
+        .map(|line| {
+            let style = if line.starts_with("+++") || line.starts_with("---") {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else if line.starts_with('+') {
+                Style::default().fg(Color::Green)
+            } else if line.starts_with('-') {
+                Style::default().fg(Color::Red)
+            } else if line.starts_with("@@") {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            Line::styled(line.as_str(), style)
+        })
+        .collect();
+
+    let diff = Paragraph::new(lines)
+        .block(Block::bordered().title(format!(" {title} ")))
+        .scroll((scroll, 0));
+    frame.render_widget(diff, area);
+}
+
+fn render_confirm_close(frame: &mut Frame, area: Rect, title: &str) {
+    let width = area.width.min(60);
+    let height = area.height.min(5);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let text = vec![
+        Line::from(format!("Close the latest workspace of \"{title}\"?")),
+        Line::from("m merge   x discard   Esc cancel"),
+    ];
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(text)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().title(" Close workspace ")),
+        popup,
+    );
+}
@@ -36,6 +36,11 @@ pub fn credentials_path() -> std::path::PathBuf {
     asset_dir().join("credentials.json")
 }
 
+/// Settings seeded into the database on first start
+pub fn settings_bootstrap_path() -> std::path::PathBuf {
+    asset_dir().join("settings.json")
+}
+
 #[derive(RustEmbed)]
 #[folder = "../../assets/sounds"]
 pub struct SoundAssets;
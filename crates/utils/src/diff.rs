@@ -30,6 +30,10 @@ pub struct Diff {
     pub additions: Option<usize>,
     pub deletions: Option<usize>,
     pub repo_id: Option<Uuid>,
+    /// Whether a worktree change is committed yet; None for diffs between commits
+    pub state: Option<DiffChangeState>,
+    /// How similar the old and new file of a rename or copy are, in percent
+    pub similarity: Option<u8>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -44,22 +48,166 @@ pub enum DiffChangeKind {
     PermissionChange,
 }
 
+/// How far a worktree change has made it into the branch. A file with both staged and
+/// unstaged edits is unstaged, as its current contents are not what would be committed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffChangeState {
+    Committed,
+    Staged,
+    Unstaged,
+    Untracked,
+}
+
+/// Whitespace differences to ignore when comparing lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitespaceMode {
+    #[default]
+    None,
+    /// All whitespace, like `git diff -w`
+    IgnoreAll,
+    /// Changes in the amount of whitespace, like `git diff -b`
+    IgnoreChange,
+    /// Whitespace at the end of lines
+    IgnoreEol,
+}
+
+/// Shape in which diff endpoints return each file's changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffView {
+    /// The unified diff text alone
+    #[default]
+    Unified,
+    /// The unified diff with its lines also aligned into old/new pairs
+    Split,
+}
+
+/// How diffs are computed and rendered, as passed to the diff endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DiffFormatOptions {
+    /// Unchanged lines shown around each change
+    pub context_lines: u32,
+    pub whitespace: WhitespaceMode,
+    /// Similarity percentage from which a deleted and an added file count as a rename
+    pub rename_threshold: Option<u16>,
+    /// Similarity percentage from which an added file counts as a copy of another;
+    /// copies are only detected when this is set
+    pub copy_threshold: Option<u16>,
+    pub view: DiffView,
+}
+
+impl Default for DiffFormatOptions {
+    fn default() -> Self {
+        Self {
+            context_lines: 3,
+            whitespace: WhitespaceMode::None,
+            rename_threshold: None,
+            copy_threshold: None,
+            view: DiffView::Unified,
+        }
+    }
+}
+
+impl DiffFormatOptions {
+    /// Check the thresholds are percentages
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, threshold) in [
+            ("rename_threshold", self.rename_threshold),
+            ("copy_threshold", self.copy_threshold),
+        ] {
+            if threshold.is_some_and(|t| t > 100) {
+                return Err(format!("{name} must be a percentage between 0 and 100"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the context and whitespace options on libgit2 diff options
+    pub fn apply_to(&self, opts: &mut DiffOptions) {
+        opts.context_lines(self.context_lines);
+        match self.whitespace {
+            WhitespaceMode::None => {}
+            WhitespaceMode::IgnoreAll => {
+                opts.ignore_whitespace(true);
+            }
+            WhitespaceMode::IgnoreChange => {
+                opts.ignore_whitespace_change(true);
+            }
+            WhitespaceMode::IgnoreEol => {
+                opts.ignore_whitespace_eol(true);
+            }
+        }
+    }
+}
+
+/// What a row of a side-by-side diff shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitDiffRowKind {
+    /// The same line on both sides
+    Context,
+    /// A removed line next to the line that replaced it
+    Modified,
+    /// A removed line with nothing opposite
+    Deleted,
+    /// An added line with nothing opposite
+    Added,
+}
+
+/// A row of a side-by-side diff; a side without a line has no number or text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct SplitDiffRow {
+    pub kind: SplitDiffRowKind,
+    pub old_line: Option<u32>,
+    pub old_text: Option<String>,
+    pub new_line: Option<u32>,
+    pub new_text: Option<String>,
+}
+
+/// A hunk of a side-by-side diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct SplitDiffHunk {
+    /// The `@@` line of the unified hunk
+    pub header: String,
+    pub rows: Vec<SplitDiffRow>,
+}
+
 // ==============================
 // Unified diff utility functions
 // ==============================
 
 /// Converts a replace diff to a list of unified diff hunks.
-/// Uses a context limit of 3 lines.
-fn create_unified_diff_hunks(old: &str, new: &str) -> Vec<String> {
+pub fn create_unified_diff_hunks(old: &str, new: &str, options: &DiffFormatOptions) -> Vec<String> {
     let old = ensure_newline(old);
     let new = ensure_newline(new);
 
+    // similar only compares lines exactly, so leave ignoring whitespace to libgit2
+    if options.whitespace != WhitespaceMode::None {
+        let mut opts = DiffOptions::new();
+        options.apply_to(&mut opts);
+        match Patch::from_buffers(old.as_bytes(), None, new.as_bytes(), None, Some(&mut opts))
+            .and_then(|mut patch| {
+                if patch.num_hunks() == 0 {
+                    return Ok(String::new());
+                }
+                let buf = patch.to_buf()?;
+                Ok(String::from_utf8_lossy(&buf).into_owned())
+            }) {
+            Ok(unified_diff) => return extract_unified_diff_hunks(&unified_diff),
+            Err(e) => tracing::error!("git2 diff failed: {}", e),
+        }
+    }
+
     let diff = TextDiff::from_lines(&old, &new);
 
     // Generate unified diff with context
     let unified_diff = diff
         .unified_diff()
-        .context_radius(3)
+        .context_radius(options.context_lines as usize)
         .header("a", "b")
         .to_string();
 
@@ -67,17 +215,47 @@ fn create_unified_diff_hunks(old: &str, new: &str) -> Vec<String> {
 }
 
 /// Creates a full unified diff with the file path in the header.
+/// Uses a context limit of 3 lines.
 pub fn create_unified_diff(file_path: &str, old: &str, new: &str) -> String {
-    let hunks = create_unified_diff_hunks(old, new);
-    concatenate_diff_hunks(file_path, &hunks)
+    create_unified_diff_with_options(
+        file_path,
+        file_path,
+        old,
+        new,
+        &DiffFormatOptions::default(),
+    )
+}
+
+/// Creates a full unified diff with the old and new file paths in the header, with the
+/// given context and whitespace handling.
+pub fn create_unified_diff_with_options(
+    old_path: &str,
+    new_path: &str,
+    old: &str,
+    new: &str,
+    options: &DiffFormatOptions,
+) -> String {
+    let hunks = create_unified_diff_hunks(old, new, options);
+    concatenate_diff_hunks_between(old_path, new_path, &hunks)
 }
 
 /// Compute addition/deletion counts between two text snapshots.
 pub fn compute_line_change_counts(old: &str, new: &str) -> (usize, usize) {
+    compute_line_change_counts_with_options(old, new, &DiffFormatOptions::default())
+}
+
+/// Compute addition/deletion counts between two text snapshots, ignoring whitespace as
+/// the options say.
+pub fn compute_line_change_counts_with_options(
+    old: &str,
+    new: &str,
+    options: &DiffFormatOptions,
+) -> (usize, usize) {
     let old = ensure_newline(old);
     let new = ensure_newline(new);
 
     let mut opts = DiffOptions::new();
+    options.apply_to(&mut opts);
     opts.context_lines(0);
 
     match Patch::from_buffers(old.as_bytes(), None, new.as_bytes(), None, Some(&mut opts))
@@ -220,9 +398,13 @@ fn fix_hunk_headers(hunks: Vec<String>) -> Vec<String> {
 
 /// Creates a full unified diff with the file path in the header,
 pub fn concatenate_diff_hunks(file_path: &str, hunks: &[String]) -> String {
+    concatenate_diff_hunks_between(file_path, file_path, hunks)
+}
+
+fn concatenate_diff_hunks_between(old_path: &str, new_path: &str, hunks: &[String]) -> String {
     let mut unified_diff = String::new();
 
-    let header = format!("--- a/{file_path}\n+++ b/{file_path}\n");
+    let header = format!("--- a/{old_path}\n+++ b/{new_path}\n");
 
     unified_diff.push_str(&header);
 
@@ -246,3 +428,171 @@ pub fn normalize_unified_diff(file_path: &str, unified_diff: &str) -> String {
     let hunks = extract_unified_diff_hunks(unified_diff);
     concatenate_diff_hunks(file_path, &hunks)
 }
+
+/// Start lines of the old and new side from a `@@ -a,b +c,d @@` header
+fn parse_hunk_starts(header: &str) -> (u32, u32) {
+    let mut starts = header.split_whitespace().skip(1).take(2).map(|range| {
+        range
+            .get(1..)
+            .and_then(|range| range.split(',').next())
+            .and_then(|start| start.parse().ok())
+            .unwrap_or(1)
+    });
+    (starts.next().unwrap_or(1), starts.next().unwrap_or(1))
+}
+
+/// Aligns the lines of a unified diff into side-by-side rows. Each run of removed lines is
+/// paired in order with the added lines that directly follow it; whatever is left of the
+/// longer run gets a row of its own.
+pub fn split_unified_diff(unified_diff: &str) -> Vec<SplitDiffHunk> {
+    fn flush(
+        rows: &mut Vec<SplitDiffRow>,
+        deleted: &mut Vec<(u32, String)>,
+        added: &mut Vec<(u32, String)>,
+    ) {
+        let paired = deleted.len().max(added.len());
+        let mut deleted = deleted.drain(..);
+        let mut added = added.drain(..);
+        for _ in 0..paired {
+            let (old_line, old_text) = deleted.next().unzip();
+            let (new_line, new_text) = added.next().unzip();
+            let kind = match (&old_text, &new_text) {
+                (Some(_), Some(_)) => SplitDiffRowKind::Modified,
+                (Some(_), None) => SplitDiffRowKind::Deleted,
+                _ => SplitDiffRowKind::Added,
+            };
+            rows.push(SplitDiffRow {
+                kind,
+                old_line,
+                old_text,
+                new_line,
+                new_text,
+            });
+        }
+    }
+
+    let mut hunks = Vec::new();
+    for hunk in extract_unified_diff_hunks(unified_diff) {
+        let mut lines = hunk.lines();
+        let Some(header) = lines.next() else {
+            continue;
+        };
+        let (mut old_line, mut new_line) = parse_hunk_starts(header);
+        let mut rows = Vec::new();
+        let (mut deleted, mut added) = (Vec::new(), Vec::new());
+        for line in lines {
+            let Some((marker, text)) = line.split_at_checked(1) else {
+                continue;
+            };
+            match marker {
+                "-" => {
+                    if !added.is_empty() {
+                        flush(&mut rows, &mut deleted, &mut added);
+                    }
+                    deleted.push((old_line, text.to_string()));
+                    old_line += 1;
+                }
+                "+" => {
+                    added.push((new_line, text.to_string()));
+                    new_line += 1;
+                }
+                _ => {
+                    flush(&mut rows, &mut deleted, &mut added);
+                    rows.push(SplitDiffRow {
+                        kind: SplitDiffRowKind::Context,
+                        old_line: Some(old_line),
+                        old_text: Some(text.to_string()),
+                        new_line: Some(new_line),
+                        new_text: Some(text.to_string()),
+                    });
+                    old_line += 1;
+                    new_line += 1;
+                }
+            }
+        }
+        flush(&mut rows, &mut deleted, &mut added);
+        hunks.push(SplitDiffHunk {
+            header: header.to_string(),
+            rows,
+        });
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_lines_are_paired_and_the_rest_stand_alone() {
+        let diff = create_unified_diff(
+            "src/lib.rs",
+            "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n",
+            "fn a() {}\nfn b2() {}\nfn d() {}\nfn e() {}\n",
+        );
+        let hunks = split_unified_diff(&diff);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].header, "@@ -1,4 +1,4 @@");
+
+        let rows: Vec<_> = hunks[0]
+            .rows
+            .iter()
+            .map(|row| {
+                (
+                    row.kind,
+                    row.old_line,
+                    row.old_text.as_deref(),
+                    row.new_line,
+                    row.new_text.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (
+                    SplitDiffRowKind::Context,
+                    Some(1),
+                    Some("fn a() {}"),
+                    Some(1),
+                    Some("fn a() {}")
+                ),
+                (
+                    SplitDiffRowKind::Modified,
+                    Some(2),
+                    Some("fn b() {}"),
+                    Some(2),
+                    Some("fn b2() {}")
+                ),
+                (
+                    SplitDiffRowKind::Deleted,
+                    Some(3),
+                    Some("fn c() {}"),
+                    None,
+                    None
+                ),
+                (
+                    SplitDiffRowKind::Context,
+                    Some(4),
+                    Some("fn d() {}"),
+                    Some(3),
+                    Some("fn d() {}")
+                ),
+                (
+                    SplitDiffRowKind::Added,
+                    None,
+                    None,
+                    Some(4),
+                    Some("fn e() {}")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn hunk_starts_follow_the_header() {
+        assert_eq!(parse_hunk_starts("@@ -10,3 +12,4 @@ fn main()"), (10, 12));
+        assert_eq!(parse_hunk_starts("@@ -0,0 +1 @@"), (0, 1));
+        assert!(split_unified_diff("[Content omitted - file too large]").is_empty());
+    }
+}
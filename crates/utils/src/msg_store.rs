@@ -13,6 +13,21 @@ use crate::{log_msg::LogMsg, stream_lines::LinesStreamExt};
 // 100 MB Limit
 const HISTORY_BYTES: usize = 100000 * 1024;
 
+/// Keep the most recent messages whose combined size fits in `max_bytes`.
+pub fn tail_within_bytes(messages: Vec<LogMsg>, max_bytes: usize) -> Vec<LogMsg> {
+    let mut total = 0usize;
+    let mut tail: Vec<LogMsg> = messages
+        .into_iter()
+        .rev()
+        .take_while(|msg| {
+            total = total.saturating_add(msg.approx_bytes());
+            total <= max_bytes
+        })
+        .collect();
+    tail.reverse();
+    tail
+}
+
 #[derive(Clone)]
 struct StoredMsg {
     msg: LogMsg,
@@ -110,6 +125,32 @@ impl MsgStore {
         Box::pin(hist.chain(live))
     }
 
+    /// Stdout/stderr only: at most `max_bytes` of the most recent history, then live.
+    /// Lets late subscribers catch up without replaying the whole history.
+    pub fn raw_output_tail_plus_stream(
+        &self,
+        max_bytes: usize,
+    ) -> futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>> {
+        let is_raw_output = |msg: &LogMsg| {
+            matches!(
+                msg,
+                LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::Finished
+            )
+        };
+        let (history, rx) = (self.get_history(), self.get_receiver());
+
+        let history = tail_within_bytes(
+            history.into_iter().filter(is_raw_output).collect(),
+            max_bytes,
+        );
+        let hist = futures::stream::iter(history.into_iter().map(Ok::<_, std::io::Error>));
+        let live = BroadcastStream::new(rx).filter_map(move |res| async move {
+            res.ok().filter(is_raw_output).map(Ok::<_, std::io::Error>)
+        });
+
+        Box::pin(hist.chain(live))
+    }
+
     pub fn stdout_chunked_stream(
         &self,
     ) -> futures::stream::BoxStream<'static, Result<String, std::io::Error>> {
@@ -175,3 +216,68 @@ impl MsgStore {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(msg: &LogMsg) -> String {
+        match msg {
+            LogMsg::Stdout(s) => format!("stdout: {s}"),
+            LogMsg::Stderr(s) => format!("stderr: {s}"),
+            LogMsg::Finished => "finished".to_string(),
+            other => format!("other: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn tails_keep_the_most_recent_messages_that_fit() {
+        let messages = || {
+            vec![
+                LogMsg::Stdout("a".to_string()),
+                LogMsg::Stdout("bb".to_string()),
+                LogMsg::Stderr("ccc".to_string()),
+            ]
+        };
+        let last_two: usize = messages()[1..].iter().map(LogMsg::approx_bytes).sum();
+
+        let tail = tail_within_bytes(messages(), last_two);
+        assert_eq!(
+            tail.iter().map(describe).collect::<Vec<_>>(),
+            vec!["stdout: bb", "stderr: ccc"]
+        );
+        // A message that does not fit whole is left out
+        let tail = tail_within_bytes(messages(), last_two - 1);
+        assert_eq!(
+            tail.iter().map(describe).collect::<Vec<_>>(),
+            vec!["stderr: ccc"]
+        );
+        assert!(tail_within_bytes(messages(), 0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn late_subscribers_replay_the_output_tail_then_follow_live_output() {
+        let store = MsgStore::new();
+        store.push_stdout("one");
+        store.push_session_id("session".to_string());
+        store.push_stdout("two");
+        store.push_stderr("three");
+        let tail_bytes = LogMsg::Stdout("two".to_string()).approx_bytes()
+            + LogMsg::Stderr("three".to_string()).approx_bytes();
+
+        let stream = store.raw_output_tail_plus_stream(tail_bytes);
+        store.push_session_id("resumed".to_string());
+        store.push_stdout("four");
+        store.push_finished();
+
+        let received: Vec<String> = stream
+            .take(4)
+            .map(|msg| describe(&msg.unwrap()))
+            .collect()
+            .await;
+        assert_eq!(
+            received,
+            vec!["stdout: two", "stderr: three", "stdout: four", "finished"]
+        );
+    }
+}
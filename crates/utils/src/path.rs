@@ -1,4 +1,8 @@
-use std::path::{Path, PathBuf};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 /// Directory name for storing images in worktrees
 pub const VIBE_IMAGES_DIR: &str = ".vibe-images";
@@ -105,6 +109,110 @@ pub fn normalize_macos_private_alias<P: AsRef<Path>>(p: P) -> PathBuf {
     p.to_path_buf()
 }
 
+/// Key for comparing paths that git, the database and the filesystem may spell differently.
+/// On Windows, verbatim prefixes are dropped, separators unified and case folded; elsewhere
+/// only trailing separators are ignored.
+pub fn path_key<P: AsRef<Path>>(path: P) -> String {
+    let path = path.as_ref().to_string_lossy();
+    if cfg!(windows) {
+        windows_path_key(&path)
+    } else {
+        let trimmed = path.trim_end_matches('/');
+        if trimmed.is_empty() && path.starts_with('/') {
+            "/".to_string()
+        } else {
+            trimmed.to_string()
+        }
+    }
+}
+
+fn windows_path_key(path: &str) -> String {
+    let path = if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{rest}")
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_string()
+    };
+    path.replace('/', "\\")
+        .trim_end_matches('\\')
+        .to_lowercase()
+}
+
+/// Whether two paths name the same location, compared by [`path_key`]
+pub fn paths_equal<A: AsRef<Path>, B: AsRef<Path>>(a: A, b: B) -> bool {
+    path_key(a) == path_key(b)
+}
+
+/// Removal attempts made by [`remove_dir_all_with_retry`] before giving up
+const REMOVE_ATTEMPTS: u32 = 5;
+
+/// `remove_dir_all` that tolerates Windows file semantics: read-only files, which git
+/// creates for its object files, are made writable, and files briefly held open by editors,
+/// indexers or antivirus scanners are retried with a short backoff. A missing directory
+/// counts as removed.
+pub fn remove_dir_all_with_retry<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut attempt = 1;
+    loop {
+        match std::fs::remove_dir_all(path) {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) if attempt < REMOVE_ATTEMPTS && is_transient_removal_error(&e) => {
+                tracing::debug!(
+                    "Removing {} failed (attempt {}): {}; retrying",
+                    path.display(),
+                    attempt,
+                    e
+                );
+                if e.kind() == io::ErrorKind::PermissionDenied {
+                    clear_readonly(path);
+                }
+                std::thread::sleep(Duration::from_millis(50 << attempt));
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Errors from files that are read-only, in use, or still being released
+fn is_transient_removal_error(e: &io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+    const WINDOWS_FILE_IN_USE: [i32; 2] = [32, 33];
+    let in_use = cfg!(windows)
+        && e.raw_os_error()
+            .is_some_and(|code| WINDOWS_FILE_IN_USE.contains(&code));
+    in_use
+        || matches!(
+            e.kind(),
+            io::ErrorKind::PermissionDenied
+                | io::ErrorKind::ResourceBusy
+                | io::ErrorKind::DirectoryNotEmpty
+        )
+}
+
+/// Clear the read-only attribute of everything under `path`
+#[cfg(windows)]
+#[allow(clippy::permissions_set_readonly_false)] // Windows has no world-writable bit to set
+fn clear_readonly(path: &Path) {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return;
+    };
+    if metadata.is_dir() {
+        for entry in std::fs::read_dir(path).into_iter().flatten().flatten() {
+            clear_readonly(&entry.path());
+        }
+    }
+    let mut permissions = metadata.permissions();
+    if permissions.readonly() {
+        permissions.set_readonly(false);
+        let _ = std::fs::set_permissions(path, permissions);
+    }
+}
+
+/// Unix removes read-only files from writable directories, so there is nothing to clear
+#[cfg(not(windows))]
+fn clear_readonly(_path: &Path) {}
+
 pub fn get_vibe_kanban_temp_dir() -> std::path::PathBuf {
     let dir_name = if cfg!(debug_assertions) {
         "vibe-kanban-dev"
@@ -154,6 +262,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn windows_path_keys_ignore_prefix_separators_and_case() {
+        let key = windows_path_key(r"C:\Users\Dev\vibe-kanban\worktrees\ab12-task\");
+        assert_eq!(
+            windows_path_key(r"\\?\C:\Users\dev\Vibe-Kanban\worktrees\AB12-task"),
+            key
+        );
+        assert_eq!(
+            windows_path_key("c:/users/dev/vibe-kanban/worktrees/ab12-task"),
+            key
+        );
+        assert_eq!(
+            windows_path_key(r"\\?\UNC\server\share\repo"),
+            windows_path_key(r"\\SERVER\share\repo")
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn path_keys_ignore_trailing_separators() {
+        assert!(paths_equal("/tmp/worktrees/ab12/", "/tmp/worktrees/ab12"));
+        assert!(!paths_equal("/tmp/worktrees/AB12", "/tmp/worktrees/ab12"));
+        assert_eq!(path_key("/"), "/");
+    }
+
+    #[test]
+    fn remove_dir_all_with_retry_removes_read_only_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("worktree");
+        std::fs::create_dir_all(root.join(".git/objects/pack")).unwrap();
+        let pack = root.join(".git/objects/pack/pack-1.pack");
+        std::fs::write(&pack, b"pack").unwrap();
+        let mut permissions = std::fs::metadata(&pack).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&pack, permissions).unwrap();
+
+        remove_dir_all_with_retry(&root).unwrap();
+        assert!(!root.exists());
+        remove_dir_all_with_retry(&root).unwrap();
+    }
+
     #[cfg(target_os = "macos")]
     #[test]
     fn test_make_path_relative_macos_private_alias() {
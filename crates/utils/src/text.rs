@@ -23,6 +23,25 @@ pub fn short_uuid(u: &Uuid) -> String {
     full.chars().take(4).collect() // grab the first 4 chars
 }
 
+/// Branch name from a template with `{prefix}`, `{short_id}` and `{title}` placeholders. An
+/// empty prefix drops the `/` (or `-`) that separates it from the rest of the name.
+pub fn render_branch_template(
+    template: &str,
+    prefix: &str,
+    workspace_id: &Uuid,
+    task_title: &str,
+) -> String {
+    let template = if prefix.is_empty() {
+        template.replace("{prefix}/", "").replace("{prefix}-", "")
+    } else {
+        template.to_string()
+    };
+    template
+        .replace("{prefix}", prefix)
+        .replace("{short_id}", &short_uuid(workspace_id))
+        .replace("{title}", &git_branch_id(task_title))
+}
+
 pub fn truncate_to_char_boundary(content: &str, max_len: usize) -> &str {
     if content.len() <= max_len {
         return content;
@@ -40,6 +59,36 @@ pub fn truncate_to_char_boundary(content: &str, max_len: usize) -> &str {
     &content[..cutoff]
 }
 
+/// Replace lines `first..=last` (1-based) of `content` with the lines of `replacement`,
+/// keeping the content's line endings. None if the lines are not all in the content.
+pub fn replace_lines(
+    content: &str,
+    first: usize,
+    last: usize,
+    replacement: &str,
+) -> Option<String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    if first == 0 || last < first || last > lines.len() {
+        return None;
+    }
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let mut replaced = String::with_capacity(content.len() + replacement.len());
+    replaced.extend(lines[..first - 1].iter().copied());
+    let new_lines: Vec<&str> = replacement.lines().collect();
+    replaced.push_str(&new_lines.join(newline));
+    // Only the last line of the content can lack a line ending
+    if !new_lines.is_empty() && lines[last - 1].ends_with('\n') {
+        replaced.push_str(newline);
+    }
+    replaced.extend(lines[last..].iter().copied());
+    Some(replaced)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -57,4 +106,38 @@ mod tests {
         assert_eq!(truncate_to_char_boundary(input, 5), "🔥");
         assert_eq!(truncate_to_char_boundary(input, 3), "");
     }
+
+    #[test]
+    fn branch_templates_fill_placeholders() {
+        use super::render_branch_template;
+
+        let id = uuid::Uuid::parse_str("1a2b3c4d-0000-0000-0000-000000000000").unwrap();
+        assert_eq!(
+            render_branch_template("{prefix}/{short_id}-{title}", "vk", &id, "Fix the Login!"),
+            "vk/1a2b-fix-the-login"
+        );
+        assert_eq!(
+            render_branch_template("{prefix}/feature/{title}-{short_id}", "", &id, "Add API"),
+            "feature/add-api-1a2b"
+        );
+    }
+
+    #[test]
+    fn replace_lines_keeps_line_endings() {
+        use super::replace_lines;
+
+        let content = "a\nb\nc\nd";
+        assert_eq!(
+            replace_lines(content, 2, 3, "x\ny\nz").unwrap(),
+            "a\nx\ny\nz\nd"
+        );
+        assert_eq!(replace_lines(content, 4, 4, "e\n").unwrap(), "a\nb\nc\ne");
+        assert_eq!(replace_lines(content, 1, 2, "").unwrap(), "c\nd");
+        assert_eq!(
+            replace_lines("a\r\nb\r\n", 1, 1, "x\ny").unwrap(),
+            "x\r\ny\r\nb\r\n"
+        );
+        assert!(replace_lines(content, 3, 5, "x").is_none());
+        assert!(replace_lines(content, 0, 1, "x").is_none());
+    }
 }
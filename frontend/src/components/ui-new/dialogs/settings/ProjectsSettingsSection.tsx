@@ -20,6 +20,7 @@ import {
 import { IconButton } from '../../primitives/IconButton';
 import {
   SettingsCard,
+  SettingsCheckbox,
   SettingsField,
   SettingsInput,
   SettingsSaveBar,
@@ -27,11 +28,13 @@ import {
 
 interface ProjectFormState {
   name: string;
+  require_merge_approval: boolean;
 }
 
 function projectToFormState(project: Project): ProjectFormState {
   return {
     name: project.name,
+    require_merge_approval: project.require_merge_approval,
   };
 }
 
@@ -226,6 +229,7 @@ export function ProjectsSettingsSection() {
     try {
       const updateData: UpdateProject = {
         name: draft.name.trim(),
+        require_merge_approval: draft.require_merge_approval,
       };
 
       updateProject.mutate({
@@ -349,6 +353,18 @@ export function ProjectsSettingsSection() {
                 placeholder={t('settings.projects.general.name.placeholder')}
               />
             </SettingsField>
+
+            <SettingsCheckbox
+              id="require-merge-approval"
+              label={t('settings.projects.general.requireMergeApproval.label')}
+              description={t(
+                'settings.projects.general.requireMergeApproval.helper'
+              )}
+              checked={draft.require_merge_approval}
+              onChange={(checked) =>
+                updateDraft({ require_merge_approval: checked })
+              }
+            />
           </SettingsCard>
 
           {/* Repositories */}
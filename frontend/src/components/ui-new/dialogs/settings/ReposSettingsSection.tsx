@@ -27,22 +27,26 @@ import {
 interface RepoScriptsFormState {
   display_name: string;
   default_target_branch: string;
+  require_ci_pass: boolean;
   setup_script: string;
   parallel_setup_script: boolean;
   cleanup_script: string;
   copy_files: string;
   dev_server_script: string;
+  test_script: string;
 }
 
 function repoToFormState(repo: Repo): RepoScriptsFormState {
   return {
     display_name: repo.display_name,
     default_target_branch: repo.default_target_branch ?? '',
+    require_ci_pass: repo.require_ci_pass,
     setup_script: repo.setup_script ?? '',
     parallel_setup_script: repo.parallel_setup_script,
     cleanup_script: repo.cleanup_script ?? '',
     copy_files: repo.copy_files ?? '',
     dev_server_script: repo.dev_server_script ?? '',
+    test_script: repo.test_script ?? '',
   };
 }
 
@@ -151,11 +155,13 @@ export function ReposSettingsSection() {
       const updateData: UpdateRepo = {
         display_name: draft.display_name.trim() || null,
         default_target_branch: draft.default_target_branch.trim() || null,
+        require_ci_pass: draft.require_ci_pass,
         setup_script: draft.setup_script.trim() || null,
         cleanup_script: draft.cleanup_script.trim() || null,
         copy_files: draft.copy_files.trim() || null,
         parallel_setup_script: draft.parallel_setup_script,
         dev_server_script: draft.dev_server_script.trim() || null,
+        test_script: draft.test_script.trim() || null,
       };
 
       const updatedRepo = await repoApi.update(selectedRepo.id, updateData);
@@ -343,6 +349,14 @@ export function ReposSettingsSection() {
                 }
               />
             </SettingsField>
+
+            <SettingsCheckbox
+              id="require-ci-pass"
+              label={t('settings.repos.general.requireCiPass.label')}
+              description={t('settings.repos.general.requireCiPass.helper')}
+              checked={draft.require_ci_pass}
+              onChange={(checked) => updateDraft({ require_ci_pass: checked })}
+            />
           </SettingsCard>
 
           {/* Scripts settings */}
@@ -362,6 +376,18 @@ export function ReposSettingsSection() {
               />
             </SettingsField>
 
+            <SettingsField
+              label={t('settings.repos.scripts.test.label')}
+              description={t('settings.repos.scripts.test.helper')}
+            >
+              <SettingsTextarea
+                value={draft.test_script}
+                onChange={(value) => updateDraft({ test_script: value })}
+                placeholder={t('settings.repos.scripts.test.placeholder')}
+                monospace
+              />
+            </SettingsField>
+
             <SettingsField
               label={t('settings.repos.scripts.setup.label')}
               description={t('settings.repos.scripts.setup.helper')}
@@ -289,6 +289,18 @@ export const useConversationHistory = ({
               case 'ToolInstallScript':
                 toolName = 'Tool Install Script';
                 break;
+              case 'QualityGate':
+                toolName = 'Quality Gate';
+                break;
+              case 'TestRun':
+                toolName = 'Test Run';
+                break;
+              case 'AdHocCommand':
+                toolName = 'Command';
+                break;
+              case 'ProjectScript':
+                toolName = 'Project Script';
+                break;
               default:
                 return [];
             }
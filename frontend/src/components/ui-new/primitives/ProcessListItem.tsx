@@ -26,6 +26,10 @@ const RUN_REASON_LABELS: Record<ExecutionProcessRunReason, string> = {
   setupscript: 'Setup Script',
   cleanupscript: 'Cleanup Script',
   devserver: 'Dev Server',
+  qualitygate: 'Quality Gate',
+  testrun: 'Test Run',
+  adhoccommand: 'Command',
+  projectscript: 'Project Script',
 };
 
 const RUN_REASON_ICONS: Record<ExecutionProcessRunReason, typeof TerminalIcon> =
@@ -34,6 +38,10 @@ const RUN_REASON_ICONS: Record<ExecutionProcessRunReason, typeof TerminalIcon> =
     setupscript: GearIcon,
     cleanupscript: GearIcon,
     devserver: GlobeIcon,
+    qualitygate: GearIcon,
+    testrun: GearIcon,
+    adhoccommand: TerminalIcon,
+    projectscript: TerminalIcon,
   };
 
 const STATUS_COLORS: Record<ExecutionProcessStatus, string> = {
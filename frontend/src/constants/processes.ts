@@ -6,6 +6,10 @@ export const PROCESS_RUN_REASONS = {
   CLEANUP_SCRIPT: 'cleanupscript' as ExecutionProcessRunReason,
   CODING_AGENT: 'codingagent' as ExecutionProcessRunReason,
   DEV_SERVER: 'devserver' as ExecutionProcessRunReason,
+  QUALITY_GATE: 'qualitygate' as ExecutionProcessRunReason,
+  TEST_RUN: 'testrun' as ExecutionProcessRunReason,
+  AD_HOC_COMMAND: 'adhoccommand' as ExecutionProcessRunReason,
+  PROJECT_SCRIPT: 'projectscript' as ExecutionProcessRunReason,
 } as const;
 
 export const isCodingAgent = (
@@ -263,6 +263,18 @@ export const useConversationHistoryOld = ({
               case 'ToolInstallScript':
                 toolName = 'Tool Install Script';
                 break;
+              case 'QualityGate':
+                toolName = 'Quality Gate';
+                break;
+              case 'TestRun':
+                toolName = 'Test Run';
+                break;
+              case 'AdHocCommand':
+                toolName = 'Command';
+                break;
+              case 'ProjectScript':
+                toolName = 'Project Script';
+                break;
               default:
                 return [];
             }
@@ -24,6 +24,7 @@ import {
   UpdateRepo,
   SearchMode,
   SearchResult,
+  StaleVersion,
   Task,
   TaskRelationships,
   Tag,
@@ -361,12 +362,21 @@ export const tasksApi = {
     return handleApiResponse<TaskWithAttemptStatus>(response);
   },
 
-  update: async (taskId: string, data: UpdateTask): Promise<Task> => {
+  /**
+   * Pass the task's `updated_at` as `ifMatch` to fail with a 409 instead of
+   * overwriting a change made since the task was loaded.
+   */
+  update: async (
+    taskId: string,
+    data: UpdateTask,
+    ifMatch?: string
+  ): Promise<Task> => {
     const response = await makeRequest(`/api/tasks/${taskId}`, {
       method: 'PUT',
+      headers: ifMatch ? { 'If-Match': `"${ifMatch}"` } : undefined,
       body: JSON.stringify(data),
     });
-    return handleApiResponse<Task>(response);
+    return handleApiResponse<Task, StaleVersion<Task>>(response);
   },
 
   delete: async (taskId: string): Promise<void> => {
@@ -484,13 +494,15 @@ export const attemptsApi = {
 
   update: async (
     attemptId: string,
-    data: { archived?: boolean; pinned?: boolean; name?: string }
+    data: { archived?: boolean; pinned?: boolean; name?: string },
+    ifMatch?: string
   ): Promise<Workspace> => {
     const response = await makeRequest(`/api/task-attempts/${attemptId}`, {
       method: 'PUT',
+      headers: ifMatch ? { 'If-Match': `"${ifMatch}"` } : undefined,
       body: JSON.stringify(data),
     });
-    return handleApiResponse<Workspace>(response);
+    return handleApiResponse<Workspace, StaleVersion<Workspace>>(response);
   },
 
   /** Get workspace with latest session */
@@ -702,13 +702,19 @@ export function ProjectTasks() {
       if (!task || task.status === newStatus) return;
 
       try {
-        await tasksApi.update(draggedTaskId, {
-          title: task.title,
-          description: task.description,
-          status: newStatus,
-          parent_workspace_id: task.parent_workspace_id,
-          image_ids: null,
-        });
+        // Guard on the version we rendered so a card someone else already moved
+        // is not silently moved back
+        await tasksApi.update(
+          draggedTaskId,
+          {
+            title: task.title,
+            description: task.description,
+            status: newStatus,
+            parent_workspace_id: task.parent_workspace_id,
+            image_ids: null,
+          },
+          task.updated_at
+        );
       } catch (err) {
         console.error('Failed to update task status:', err);
       }
@@ -4,11 +4,15 @@
 
 // If you are an AI, and you absolutely have to edit this file, please confirm with the user first.
 
-export type Project = { id: string, name: string, default_agent_working_dir: string | null, remote_project_id: string | null, created_at: Date, updated_at: Date, };
+export type Project = { id: string, name: string, default_agent_working_dir: string | null, remote_project_id: string | null, 
+/**
+ * Refuse to merge a workspace until its latest review approves it
+ */
+require_merge_approval: boolean, created_at: Date, updated_at: Date, };
 
 export type CreateProject = { name: string, repositories: Array<CreateProjectRepo>, };
 
-export type UpdateProject = { name: string | null, };
+export type UpdateProject = { name: string | null, require_merge_approval?: boolean, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, 
 /**
@@ -18,19 +22,359 @@ score: bigint, };
 
 export type SearchMatchType = "FileName" | "DirectoryName" | "FullPath";
 
-export type Repo = { id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, default_target_branch: string | null, created_at: Date, updated_at: Date, };
+export type Repo = { id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, 
+/**
+ * Command run by the workspace test endpoint; output is parsed into a test report
+ */
+test_script: string | null, default_target_branch: string | null, 
+/**
+ * Block merges until CI on the pushed workspace branch head has passed. Azure DevOps CI
+ * status is not read, so repos hosted there only need the branch pushed
+ */
+require_ci_pass: boolean, 
+/**
+ * Remote workspace branches are pushed to and PRs opened from; `None` uses the git default
+ */
+push_remote: string | null, 
+/**
+ * Fetch the repo's remotes on the server's fetch schedule
+ */
+auto_fetch: boolean, created_at: Date, updated_at: Date, };
 
-export type UpdateRepo = { display_name?: string | null, setup_script?: string | null, cleanup_script?: string | null, copy_files?: string | null, parallel_setup_script?: boolean | null, dev_server_script?: string | null, default_target_branch?: string | null, };
+export type UpdateRepo = { display_name?: string | null, setup_script?: string | null, cleanup_script?: string | null, copy_files?: string | null, parallel_setup_script?: boolean | null, dev_server_script?: string | null, test_script?: string | null, default_target_branch?: string | null, require_ci_pass?: boolean | null, push_remote?: string | null, auto_fetch?: boolean | null, };
 
-export type ProjectRepo = { id: string, project_id: string, repo_id: string, };
+export type RepoCredentialKind = "https_token" | "ssh_key";
+
+export type ProjectRepo = { id: string, project_id: string, repo_id: string, 
+/**
+ * Branch new workspaces of the project target in this repo when none is given;
+ * unset falls back to the repo's default target branch
+ */
+default_target_branch: string | null, 
+/**
+ * Directory of the repo the project is limited to, such as `services/billing` in a
+ * monorepo; diffs, diff stats and quality gates of the project's workspaces only cover it
+ */
+path_scope: string | null, 
+/**
+ * Whether workspaces only check out the path scope and the files at the repo root
+ */
+sparse_checkout: boolean, };
 
 export type CreateProjectRepo = { display_name: string, git_repo_path: string, };
 
-export type WorkspaceRepo = { id: string, workspace_id: string, repo_id: string, target_branch: string, start_from_ref: string | null, created_at: Date, updated_at: Date, };
+export type UpdateProjectRepo = { default_target_branch: string | null, path_scope: string | null, sparse_checkout: boolean, };
+
+export type ProjectQualityGate = { id: string, project_id: string, name: string, command: string, sort_order: bigint, 
+/**
+ * Seconds the command may run before it is stopped and the gate fails; unset allows 30
+ * minutes
+ */
+timeout_secs: bigint | null, created_at: Date, updated_at: Date, };
+
+export type CreateProjectQualityGate = { name: string, command: string, sort_order: bigint | null, timeout_secs: number | null, };
+
+export type UpdateProjectQualityGate = { name: string | null, command: string | null, sort_order: bigint | null, timeout_secs: number | null, };
+
+export type ProjectRemoteHost = { project_id: string, host: string, 
+/**
+ * Defaults to the SSH configuration for the host
+ */
+user: string | null, port: bigint | null, identity_file: string | null, 
+/**
+ * Directory on the host under which workspaces are synced
+ */
+remote_dir: string, created_at: Date, updated_at: Date, };
+
+export type SetProjectRemoteHost = { host: string, user: string | null, port: bigint | null, identity_file: string | null, remote_dir: string, };
+
+export type ProjectAiReviewPolicy = { project_id: string, 
+/**
+ * The agent that does the review
+ */
+executor_profile_id: ExecutorProfileId, 
+/**
+ * Unresolved findings at or above this severity block the merge; lower ones are
+ * reported as warnings. Unset, no finding blocks.
+ */
+block_severity: ReviewSeverity | null, created_at: Date, updated_at: Date, };
+
+export type UpsertProjectAiReviewPolicy = { executor_profile_id: ExecutorProfileId, block_severity: ReviewSeverity | null, };
+
+export type AgentPipelineStage = { name: string, 
+/**
+ * The agent that runs the stage
+ */
+executor_profile_id: ExecutorProfileId, 
+/**
+ * Prompt of the stage. `{{task_title}}`, `{{task_description}}`, `{{stage}}` and
+ * `{{previous_output}}`, the last message of the stage before, are filled in when it
+ * starts.
+ */
+prompt_template: string, };
+
+export type ProjectAgentPipeline = { project_id: string, 
+/**
+ * The stages, in the order they run
+ */
+stages: Array<AgentPipelineStage>, created_at: Date, updated_at: Date, };
+
+export type UpsertProjectAgentPipeline = { stages: Array<AgentPipelineStage>, };
+
+export type ProjectAgentPolicy = { project_id: string, 
+/**
+ * Whether agents may reach the network
+ */
+allow_network: boolean, 
+/**
+ * Programs agents may run in their shell; unset allows any
+ */
+allowed_commands: Array<string> | null, 
+/**
+ * Whether agents may only write files inside the workspace
+ */
+restrict_writes_to_worktree: boolean, created_at: Date, updated_at: Date, };
+
+export type UpsertProjectAgentPolicy = { allow_network: boolean, allowed_commands: Array<string> | null, restrict_writes_to_worktree: boolean, };
+
+/**
+ * How a workspace ends when it is closed
+ */
+export type MergeStrategy = "merge" | "discard";
+
+/**
+ * How open workspaces are brought up to date when their target branch gains commits
+ */
+export type AutoSyncStrategy = "rebase" | "merge";
+
+/**
+ * Per-project overrides of server-wide behavior; unset fields use the server's settings
+ */
+export type ProjectSettings = { project_id: string, 
+/**
+ * Agent new workspaces of the project start with when none is chosen
+ */
+default_executor_profile_id: ExecutorProfileId | null, 
+/**
+ * Branch name of new workspaces, with `{prefix}`, `{short_id}` and `{title}`
+ * placeholders; unset uses `{prefix}/{short_id}-{title}`
+ */
+branch_template: string | null, 
+/**
+ * Strategy a close uses when the request does not name one
+ */
+default_merge_strategy: MergeStrategy | null, 
+/**
+ * Whether the project's quality gates run before a workspace is merged
+ */
+run_quality_gates: boolean, 
+/**
+ * Hours an idle workspace of an in-progress or in-review task is kept before cleanup
+ */
+workspace_retention_hours: bigint | null, 
+/**
+ * Hours an idle archived workspace, or one whose task is not active, is kept
+ */
+archived_workspace_retention_hours: bigint | null, 
+/**
+ * Keep open workspaces up with their target branches this way; unset leaves them alone
+ */
+auto_sync_strategy: AutoSyncStrategy | null, created_at: Date, updated_at: Date, };
+
+export type UpsertProjectSettings = { default_executor_profile_id: ExecutorProfileId | null, branch_template: string | null, default_merge_strategy: MergeStrategy | null, run_quality_gates: boolean, workspace_retention_hours: number | null, archived_workspace_retention_hours: number | null, auto_sync_strategy: AutoSyncStrategy | null, };
+
+export type ProjectScript = { id: string, project_id: string, name: string, command: string, 
+/**
+ * Repo whose worktree the command runs in. Unset runs it in the only repo of
+ * single-repo workspaces, otherwise in the workspace directory.
+ */
+repo_id: string | null, 
+/**
+ * Extra environment variables for the command
+ */
+env: Record<string, string>, 
+/**
+ * Glob patterns, relative to where the command runs, of files kept after each run
+ */
+artifacts: Array<string>, created_at: Date, updated_at: Date, };
+
+export type CreateProjectScript = { name: string, command: string, repo_id: string | null, env: { [key in string]?: string }, artifacts: Array<string>, };
+
+export type UpdateProjectScript = { name: string | null, command: string | null, repo_id?: string | null, env: { [key in string]?: string } | null, artifacts: Array<string> | null, };
+
+/**
+ * Named group of environment variables injected into every execution process of the
+ * project's workspaces. Enabled sets are applied in name order, so a later set wins when
+ * two define the same variable.
+ */
+export type ProjectEnvSet = { id: string, project_id: string, name: string, vars: Record<string, string>, enabled: boolean, created_at: Date, updated_at: Date, };
+
+export type CreateProjectEnvSet = { name: string, vars: { [key in string]?: string }, enabled?: boolean, };
+
+export type UpdateProjectEnvSet = { name: string | null, vars: { [key in string]?: string } | null, enabled: boolean | null, };
+
+export type WorkspaceRepo = { id: string, workspace_id: string, repo_id: string, target_branch: string, start_from_ref: string | null, 
+/**
+ * Remote this workspace pushes to, overriding the repo's `push_remote`
+ */
+push_remote: string | null, created_at: Date, updated_at: Date, };
 
 export type CreateWorkspaceRepo = { repo_id: string, target_branch: string, start_from_ref: string | null, };
 
-export type RepoWithTargetBranch = { target_branch: string, id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, default_target_branch: string | null, created_at: Date, updated_at: Date, };
+export type RepoWithTargetBranch = { target_branch: string, 
+/**
+ * Directory of the repo the workspace's project is limited to
+ */
+path_scope: string | null, id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, 
+/**
+ * Command run by the workspace test endpoint; output is parsed into a test report
+ */
+test_script: string | null, default_target_branch: string | null, 
+/**
+ * Block merges until CI on the pushed workspace branch head has passed. Azure DevOps CI
+ * status is not read, so repos hosted there only need the branch pushed
+ */
+require_ci_pass: boolean, 
+/**
+ * Remote workspace branches are pushed to and PRs opened from; `None` uses the git default
+ */
+push_remote: string | null, 
+/**
+ * Fetch the repo's remotes on the server's fetch schedule
+ */
+auto_fetch: boolean, created_at: Date, updated_at: Date, };
+
+export type TestFramework = "cargo" | "jest" | "pytest" | "unknown";
+
+export type RepoTestResult = { repo_id: string, repo_name: string, command: string, 
+/**
+ * Execution process holding the raw test output
+ */
+execution_process_id: string, exit_code: bigint | null, framework: TestFramework, passed: number, failed: number, skipped: number, 
+/**
+ * Names of the failing test cases, as reported by the framework
+ */
+failed_cases: Array<string>, };
+
+export type WorkspaceTestRun = { id: string, workspace_id: string, 
+/**
+ * True when every repo's tests passed
+ */
+passed: boolean, results: Array<RepoTestResult>, created_at: Date, };
+
+export type WorkspaceEventKind = "worktree_repaired" | "process_interrupted" | "files_discarded" | "target_branch_drifted" | "auto_synced" | "auto_sync_conflicted" | "patch_applied" | "patch_failed";
+
+export type WorkspaceEvent = { id: string, workspace_id: string, kind: WorkspaceEventKind, message: string, created_at: Date, };
+
+export type ReviewCommentSide = "old" | "new";
+
+export type ReviewSeverity = "info" | "warning" | "error";
+
+export type ReviewComment = { id: string, workspace_id: string, repo_id: string, 
+/**
+ * Path of the file in the diff, relative to the repo root
+ */
+path: string, line: bigint, 
+/**
+ * Last line of a comment on several lines
+ */
+end_line: bigint | null, side: ReviewCommentSide, body: string, 
+/**
+ * Replacement text for the commented lines, which can be applied to the worktree
+ */
+suggestion: string | null, severity: ReviewSeverity | null, 
+/**
+ * The agent review that reported the comment as a finding; unset for comments by people
+ */
+ai_review_id: string | null, 
+/**
+ * When the comment was resolved; unset while it is open
+ */
+resolved_at: Date | null, created_at: Date, updated_at: Date, };
+
+export type CreateReviewComment = { repo_id: string, path: string, line: bigint, end_line?: bigint, side: ReviewCommentSide, body: string, suggestion?: string, severity?: ReviewSeverity, };
+
+export type UpdateReviewComment = { body: string | null, resolved: boolean | null, };
+
+export type ReviewDecision = "approved" | "changes_requested";
+
+export type WorkspaceReview = { id: string, workspace_id: string, 
+/**
+ * Who made the decision, as given by the client
+ */
+reviewer: string, decision: ReviewDecision, comment: string | null, created_at: Date, };
+
+export type CreateWorkspaceReview = { reviewer: string, decision: ReviewDecision, comment: string | null, };
+
+export type WorkspaceMergeDescription = { workspace_id: string, title: string, body: string, 
+/**
+ * Whether the description was edited since it was generated
+ */
+edited: boolean, created_at: Date, updated_at: Date, };
+
+export type SetWorkspaceMergeDescription = { title: string, body: string, };
+
+export type PipelineStageStatus = "pending" | "running" | "completed" | "failed" | "skipped";
+
+export type WorkspacePipelineStage = { id: string, workspace_id: string, 
+/**
+ * Zero-based place of the stage in the pipeline
+ */
+position: bigint, name: string, executor_profile_id: ExecutorProfileId, prompt_template: string, status: PipelineStageStatus, 
+/**
+ * The coding agent process of the stage, once it started
+ */
+execution_process_id: string | null, created_at: Date, updated_at: Date, };
+
+export type AiReviewStatus = "running" | "completed" | "failed";
+
+export type AiReview = { id: string, workspace_id: string, 
+/**
+ * The coding agent process doing the review
+ */
+execution_process_id: string, status: AiReviewStatus, 
+/**
+ * Why the review failed, e.g. the agent's reply had no findings block
+ */
+error: string | null, created_at: Date, completed_at: Date | null, };
+
+export type ConflictResolutionStatus = "running" | "proposed" | "unresolved" | "accepted" | "rejected";
+
+export type ConflictResolution = { id: string, workspace_id: string, repo_id: string, 
+/**
+ * The coding agent process resolving the conflicts
+ */
+execution_process_id: string, 
+/**
+ * Path of the conflicted file, relative to the repo
+ */
+path: string, status: ConflictResolutionStatus, created_at: Date, updated_at: Date, };
+
+export type PlanTurnStatus = "running" | "proposed" | "failed" | "approved" | "rejected";
+
+export type PlanTurn = { id: string, workspace_id: string, 
+/**
+ * The coding agent process that made the plan
+ */
+execution_process_id: string, 
+/**
+ * The proposed plan, the last message of the plan run
+ */
+plan: string | null, status: PlanTurnStatus, 
+/**
+ * The coding agent run started when the plan was approved
+ */
+approved_execution_process_id: string | null, created_at: Date, updated_at: Date, };
+
+export type TerminalSession = { id: string, workspace_id: string, 
+/**
+ * Repo whose worktree the shell was started in, if one was chosen
+ */
+repo_id: string | null, shell: string, working_dir: string, started_at: Date, 
+/**
+ * Unset while the terminal is still open
+ */
+ended_at: Date | null, };
 
 export type Tag = { id: string, tag_name: string, content: string, created_at: string, updated_at: string, };
 
@@ -42,15 +386,36 @@ export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelle
 
 export type TaskType = "default" | "ralph";
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, task_type: TaskType, parent_workspace_id: string | null, ralph_current_story_index: bigint | null, ralph_auto_continue: boolean, ralph_max_iterations: bigint, created_at: string, updated_at: string, };
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, task_type: TaskType, parent_workspace_id: string | null, ralph_current_story_index: bigint | null, ralph_auto_continue: boolean, ralph_max_iterations: bigint, priority: TaskPriority | null, labels: Array<string>, assignee: string | null, created_at: string, updated_at: string, };
 
-export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, task_type: TaskType, parent_workspace_id: string | null, ralph_current_story_index: bigint | null, ralph_auto_continue: boolean, ralph_max_iterations: bigint, created_at: string, updated_at: string, };
+export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, task_type: TaskType, parent_workspace_id: string | null, ralph_current_story_index: bigint | null, ralph_auto_continue: boolean, ralph_max_iterations: bigint, priority: TaskPriority | null, labels: Array<string>, assignee: string | null, created_at: string, updated_at: string, };
 
 export type TaskRelationships = { parent_task: Task | null, current_workspace: Workspace, children: Array<Task>, };
 
-export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, task_type: TaskType | null, parent_workspace_id: string | null, image_ids: Array<string> | null, ralph_auto_continue: boolean | null, ralph_max_iterations: bigint | null, };
+export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, task_type: TaskType | null, parent_workspace_id: string | null, image_ids: Array<string> | null, ralph_auto_continue: boolean | null, ralph_max_iterations: bigint | null, priority?: TaskPriority, labels?: Array<string>, assignee?: string, };
+
+export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, priority?: TaskPriority | null, labels?: Array<string>, assignee?: string | null, };
+
+export type TaskPriority = "low" | "medium" | "high" | "urgent";
 
-export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, };
+/**
+ * One change of a task's status; rows are written by database triggers
+ */
+export type TaskStatusTransition = { task_id: string, 
+/**
+ * Unset for the transition recorded when the task was created
+ */
+from_status: TaskStatus | null, to_status: TaskStatus, changed_at: Date, };
+
+export type TaskTemplate = { id: string, 
+/**
+ * Project the template belongs to; unset makes it available in every project
+ */
+project_id: string | null, name: string, title_pattern: string, description_skeleton: string | null, labels: Array<string>, priority: TaskPriority | null, checklist: Array<string>, created_at: Date, updated_at: Date, };
+
+export type CreateTaskTemplate = { project_id: string | null, name: string, title_pattern: string, description_skeleton: string | null, labels: Array<string>, priority: TaskPriority | null, checklist: Array<string>, };
+
+export type UpdateTaskTemplate = { name: string | null, title_pattern: string | null, description_skeleton?: string | null, labels: Array<string> | null, priority?: TaskPriority | null, checklist: Array<string> | null, };
 
 export type DraftFollowUpData = { message: string, executor_profile_id: ExecutorProfileId, };
 
@@ -88,14 +453,53 @@ export type ExecutionProcess = { id: string, session_id: string, run_reason: Exe
  * history view (due to restore/trimming). Hidden from logs/timeline;
  * still listed in the Processes tab.
  */
-dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, 
+/**
+ * The failed or killed process this one re-runs, if it is a retry
+ */
+retried_from: string | null, };
 
 export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
 
-export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
+export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver" | "qualitygate" | "testrun" | "adhoccommand" | "projectscript" | "plan" | "aireview" | "conflictresolution";
+
+export type ExecutionProcessSummary = { workspace_id: string, 
+/**
+ * Milliseconds from start to completion; unset while the process runs
+ */
+duration_ms: number | null, id: string, session_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, status: ExecutionProcessStatus, exit_code: bigint | null, 
+/**
+ * dropped: true if this process is excluded from the current
+ * history view (due to restore/trimming). Hidden from logs/timeline;
+ * still listed in the Processes tab.
+ */
+dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, 
+/**
+ * The failed or killed process this one re-runs, if it is a retry
+ */
+retried_from: string | null, };
 
 export type ExecutionProcessRepoState = { id: string, execution_process_id: string, repo_id: string, before_head_commit: string | null, after_head_commit: string | null, merge_commit: string | null, created_at: Date, updated_at: Date, };
 
+export type ExecutionProcessArtifact = { id: string, execution_process_id: string, 
+/**
+ * Path relative to the directory the process ran in
+ */
+path: string, size_bytes: bigint, created_at: Date, };
+
+export type ExecutionProcessPolicy = { execution_process_id: string, policy: AgentPolicy, 
+/**
+ * Sandbox the process ran in to enforce the policy; unset for processes started before
+ * policies were sandboxed
+ */
+sandbox: Sandbox | null, created_at: Date, };
+
+export type InterruptedExecution = { execution_process_id: string, workspace_id: string, 
+/**
+ * The coding agent run that resumed the interrupted one
+ */
+resumed_execution_process_id: string | null, interrupted_at: Date, };
+
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
 export type DirectMerge = { id: string, workspace_id: string, repo_id: string, merge_commit: string, target_branch_name: string, created_at: string, };
@@ -120,10 +524,24 @@ contentOmitted: boolean,
 /**
  * Optional precomputed stats for omitted content
  */
-additions: number | null, deletions: number | null, repoId: string | null, };
+additions: number | null, deletions: number | null, repoId: string | null, 
+/**
+ * Whether a worktree change is committed yet; None for diffs between commits
+ */
+state: DiffChangeState | null, 
+/**
+ * How similar the old and new file of a rename or copy are, in percent
+ */
+similarity: number | null, };
 
 export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
 
+/**
+ * How far a worktree change has made it into the branch. A file with both staged and
+ * unstaged edits is unstaged, as its current contents are not what would be committed.
+ */
+export type DiffChangeState = "committed" | "staged" | "unstaged" | "untracked";
+
 export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
 
 export type LoginStatus = { "status": "loggedout" } | { "status": "loggedin", profile: ProfileResponse, };
@@ -182,10 +600,50 @@ export type ListProjectsResponse = { projects: Array<RemoteProject>, };
 
 export type RemoteProjectMembersResponse = { organization_id: string, members: Array<OrganizationMemberWithProfile>, };
 
+export type StaleVersion<T> = { 
+/**
+ * Version the client sent in `If-Match`
+ */
+expected_version: string, 
+/**
+ * The entity as it is now, so the client can reconcile
+ */
+current: T, };
+
+export type Page<T> = { items: Array<T>, 
+/**
+ * Rows matching the request across all pages
+ */
+total: number, limit: number, offset: number, has_more: boolean, };
+
+export type Listing<T> = Array<T> | Page<T>;
+
 export type RegisterRepoRequest = { path: string, display_name: string | null, };
 
 export type InitRepoRequest = { parent_path: string, folder_name: string, };
 
+export type DiscoverReposRequest = { 
+/**
+ * Directory to scan for git repositories
+ */
+path: string, 
+/**
+ * How many directory levels below `path` to search; defaults to 3
+ */
+max_depth: number | null, 
+/**
+ * Paths of discovered repos to register
+ */
+register: Array<string>, };
+
+export type DiscoverReposResponse = { repos: Array<DiscoveredRepo>, 
+/**
+ * Repos registered from `register`, in request order
+ */
+registered: Array<Repo>, };
+
+export type AddRepoRemoteRequest = { name: string, url: string, };
+
 export type RalphCheckResponse = { 
 /**
  * Whether the repo has .ralph/prompt.md
@@ -228,10 +686,22 @@ export type ChangeTargetBranchRequest = { repo_id: string, new_target_branch: st
 
 export type ChangeTargetBranchResponse = { repo_id: string, new_target_branch: string, status: [number, number], };
 
-export type MergeTaskAttemptRequest = { repo_id: string, };
+export type MergeTaskAttemptRequest = { repo_id: string, 
+/**
+ * Merge only these changed files, given relative to the repo root. The other changes
+ * are committed back onto the workspace branch on top of the merge, and the workspace
+ * stays open for them.
+ */
+paths?: Array<string>, };
 
 export type PushTaskAttemptRequest = { repo_id: string, };
 
+export type SetPushRemoteRequest = { repo_id: string, 
+/**
+ * Remote to push this workspace's branch to; `null` falls back to the repo's push remote
+ */
+remote_name: string | null, };
+
 export type RenameBranchRequest = { new_branch_name: string, };
 
 export type RenameBranchResponse = { branch: string, };
@@ -240,11 +710,108 @@ export type StartReviewRequest = { executor_profile_id: ExecutorProfileId, addit
 
 export type ReviewError = { "type": "process_already_running" };
 
+export type LogStream = "stdout" | "stderr";
+
+export type LogChunkEntry = { 
+/**
+ * Cursor of the stored record this output came from
+ */
+cursor: number, stream: LogStream, content: string, };
+
+export type ExecutionProcessLogChunkResponse = { entries: Array<LogChunkEntry>, 
+/**
+ * Pass as `after_cursor` to continue; echoes the request's cursor when nothing new was read
+ */
+next_cursor: number | null, 
+/**
+ * More records were stored after this chunk at the time of the read
+ */
+has_more: boolean, };
+
+export type ProcessInputRequest = { 
+/**
+ * Text for the agent; written to stdin as a line for plain processes
+ */
+input: string, };
+
+export type LogSearchMatch = { 
+/**
+ * 1-based line of the process output
+ */
+line_number: number, line: string, context_before: Array<string>, context_after: Array<string>, };
+
+export type LogSearchResult = { execution_process_id: string, session_id: string, workspace_id: string, run_reason: ExecutionProcessRunReason, created_at: Date, matches: Array<LogSearchMatch>, 
+/**
+ * More lines matched than are returned for the process
+ */
+truncated: boolean, };
+
+export type ReapAction = "marked_failed" | "killed_missing_worktree" | "killed_untracked";
+
+export type ReapedProcess = { execution_process_id: string, workspace_id: string | null, run_reason: ExecutionProcessRunReason, action: ReapAction, };
+
+export type ProcessReaperReport = { ran_at: Date, 
+/**
+ * Executions marked running when the reaper ran
+ */
+running: number, reaped: Array<ReapedProcess>, };
+
 export type OpenEditorRequest = { editor_type: string | null, file_path: string | null, };
 
 export type OpenEditorResponse = { url: string | null, };
 
-export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
+export type TaskGroupBy = "assignee" | "label" | "repo" | "priority";
+
+export type TaskGroup = { 
+/**
+ * Assignee, label, repo name or priority; unset for tasks that have none
+ */
+key: string | null, count: number, tasks: Array<TaskWithAttemptStatus>, };
+
+export type TaskList = Array<TaskWithAttemptStatus> | Page<TaskWithAttemptStatus> | Array<TaskGroup>;
+
+export type CreateTaskFromTemplateRequest = { project_id: string, 
+/**
+ * Values for the template's `{{name}}` placeholders
+ */
+variables: { [key in string]?: string }, status: TaskStatus | null, };
+
+export type DuplicateTaskRequest = { 
+/**
+ * Project to create the copy in; unset copies into the task's own project
+ */
+project_id?: string, };
+
+export type MoveTaskRequest = { project_id: string, 
+/**
+ * Keep the task's closed workspaces with it; otherwise their records are deleted
+ */
+include_workspaces: boolean, };
+
+export type MoveTaskResponse = { task: Task, 
+/**
+ * Labels renamed to the spelling the target project already uses, as `[from, to]`
+ */
+remapped_labels: Array<[string, string]>, 
+/**
+ * Closed workspaces whose records were deleted rather than moved
+ */
+dropped_workspaces: Array<string>, 
+/**
+ * Parent workspace the task no longer belongs to, as it is in another project
+ */
+cleared_parent_workspace_id: string | null, 
+/**
+ * Repos of the moved workspaces that the target project does not have
+ */
+missing_repos: Array<string>, };
+
+export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, 
+/**
+ * Repos the workspace provisions worktrees for, a subset of the project's; empty uses
+ * all of them
+ */
+repos: Array<WorkspaceRepoInput>, };
 
 export type RalphStatusResponse = { total_stories: number, completed_count: number, stories: Array<RalphStory>, current_story: RalphStory | null, 
 /**
@@ -268,15 +835,58 @@ export type UpdateRalphAutoContinueResponse = { auto_continue: boolean, };
 
 export type RalphStoryCommitsResponse = { commits: { [key in string]?: StoryCommit }, };
 
+export type Overview = { 
+/**
+ * Projects with in-progress tasks or active workspaces, in project order
+ */
+projects: Array<ProjectOverview>, };
+
+export type ProjectOverview = { project_id: string, project_name: string, 
+/**
+ * Tasks in progress or in review, or with an agent run in progress, newest first
+ */
+tasks: Array<TaskWithAttemptStatus>, 
+/**
+ * Workspaces that are not archived and are open or running processes, most recently
+ * updated first
+ */
+workspaces: Array<OverviewWorkspace>, };
+
+export type OverviewWorkspace = { workspace: WorkspaceWithStatus, task_title: string, task_status: TaskStatus, running_processes: Array<ExecutionProcess>, };
+
 export type CreatePrApiRequest = { title: string, body: string | null, target_branch: string | null, draft: boolean | null, repo_id: string, auto_generate_description: boolean, };
 
 export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
 
 export type ImageMetadata = { exists: boolean, file_name: string | null, path: string | null, size_bytes: bigint | null, format: string | null, proxy_url: string | null, };
 
-export type CreateTaskAttemptBody = { task_id: string, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
+export type CreateTaskAttemptBody = { task_id: string, 
+/**
+ * Agent to start; unset uses the project's default agent
+ */
+executor_profile_id?: ExecutorProfileId, 
+/**
+ * Repos the workspace provisions worktrees for, a subset of the project's; empty uses
+ * all of them, or the parent workspace's repos when stacking
+ */
+repos: Array<WorkspaceRepoInput>, 
+/**
+ * Stack the new workspace on this one: each repo starts from the parent workspace's
+ * branch and targets it instead of `target_branch`
+ */
+parent_workspace_id?: string, 
+/**
+ * Adopt this existing local branch instead of creating one: worktrees are checked out
+ * from it in every repo and no coding agent is started
+ */
+existing_branch?: string, };
 
-export type WorkspaceRepoInput = { repo_id: string, target_branch: string, };
+export type WorkspaceRepoInput = { repo_id: string, 
+/**
+ * Branch the workspace targets in this repo; unset uses the project's default target
+ * branch for the repo, then the repo's own, then the branch checked out in the repo
+ */
+target_branch?: string, };
 
 export type RunAgentSetupRequest = { executor_profile_id: ExecutorProfileId, };
 
@@ -392,11 +1002,159 @@ export type DiffStats = { files_changed: number, lines_added: number, lines_remo
 
 export type DirectoryEntry = { name: string, path: string, is_directory: boolean, is_git_repo: boolean, last_modified: bigint | null, };
 
+export type DiscoveredRepo = { path: string, name: string, 
+/**
+ * Branch the default remote's HEAD points at, else the checked-out branch
+ */
+default_branch: string | null, remotes: Array<GitRemote>, 
+/**
+ * Id of the existing registration of this path, if any
+ */
+registered_repo_id: string | null, };
+
+export type RepoIssueKind = "path_missing" | "not_a_directory" | "not_a_git_repository" | "default_branch_missing" | "remote_unreachable" | "low_disk_space";
+
+export type RepoIssue = { kind: RepoIssueKind, message: string, };
+
+/**
+ * Result of checking that a registered repo can host workspaces
+ */
+export type RepoValidation = { repo_id: string, healthy: boolean, issues: Array<RepoIssue>, };
+
+export type SetRepoCredential = { "type": "https_token", username: string | null, token: string, } | { "type": "ssh_key", 
+/**
+ * Path to the private key on the server
+ */
+path: string, };
+
+/**
+ * What clients see of a stored credential; tokens are never returned
+ */
+export type RepoCredentialSummary = { repo_id: string, kind: RepoCredentialKind, username: string | null, ssh_key_path: string | null, updated_at: Date, };
+
+export type CreateSecret = { 
+/**
+ * Environment variable the value is exposed as
+ */
+name: string, value: string, description: string | null, };
+
+export type UpdateSecret = { 
+/**
+ * New value; omitted keeps the current one
+ */
+value: string | null, 
+/**
+ * New description; an empty string clears it
+ */
+description: string | null, };
+
+/**
+ * What clients see of a stored secret; values are write-only
+ */
+export type SecretSummary = { id: string, name: string, description: string | null, created_at: Date, updated_at: Date, };
+
+/**
+ * Secret names a project or repo may pass to its processes
+ */
+export type SecretAllowlistNames = { names: Array<string>, };
+
+export type ProjectStats = { total_workspaces: number, 
+/**
+ * Neither merged nor discarded yet
+ */
+open: number, 
+/**
+ * Merged directly or through a merged PR
+ */
+merged: number, 
+/**
+ * Archived, or with a closed PR, without ever being merged
+ */
+discarded: number, 
+/**
+ * Average hours from workspace creation to its first merge
+ */
+avg_hours_to_merge: number | null, 
+/**
+ * Lines added across all agent runs, summed over workspaces and repos
+ */
+lines_added: number, lines_removed: number, repos: Array<RepoStats>, };
+
+export type RepoStats = { repo_id: string, repo_name: string, 
+/**
+ * Workspaces that include the repo
+ */
+workspaces: number, 
+/**
+ * Workspaces whose merge or rebase in the repo stopped on conflicts at least once
+ */
+conflicted_workspaces: number, 
+/**
+ * `conflicted_workspaces / workspaces`, or 0 without workspaces
+ */
+conflict_rate: number, };
+
+/**
+ * Outcomes of the workspaces started with one agent configuration. The model is part of the
+ * configuration: it is set by the profile variant.
+ */
+export type AgentStats = { executor_profile_id: ExecutorProfileId, workspaces: number, open: number, merged: number, discarded: number, 
+/**
+ * `merged / (merged + discarded)`; unset while no workspace has an outcome yet
+ */
+merge_rate: number | null, 
+/**
+ * Average number of follow-up runs per workspace
+ */
+avg_follow_ups: number, 
+/**
+ * Share of workspaces whose merge or rebase stopped on conflicts at least once
+ */
+conflict_rate: number, };
+
+/**
+ * Distribution of a duration over the project's completed tasks
+ */
+export type DurationSummary = { 
+/**
+ * Completed tasks the summary covers
+ */
+tasks: number, average_hours: number | null, median_hours: number | null, 
+/**
+ * 85th percentile; most tasks finish within this
+ */
+p85_hours: number | null, };
+
+export type WeeklyThroughput = { 
+/**
+ * Monday the week starts on
+ */
+week_start: string, 
+/**
+ * Tasks completed during the week
+ */
+completed: number, };
+
+export type CostPeriod = "day" | "week" | "month";
+
+export type PeriodCost = { 
+/**
+ * First day of the period; weeks start on Monday
+ */
+period_start: string, turns: number, input_tokens: number, output_tokens: number, cost_usd: number, };
+
+export type ProjectCost = { project_id: string, project_name: string, turns: number, input_tokens: number, output_tokens: number, cost_usd: number, };
+
 export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
 export type SearchMode = "taskform" | "settings";
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, beta_workspaces: boolean, beta_workspaces_invitation_sent: boolean, commit_reminder: boolean, send_message_shortcut: SendMessageShortcut, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, beta_workspaces: boolean, beta_workspaces_invitation_sent: boolean, commit_reminder: boolean, send_message_shortcut: SendMessageShortcut, 
+/**
+ * Environment variables for every execution process; project env sets and workspace
+ * overrides take precedence
+ */
+env: { [key in string]?: string }, };
 
 export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
 
@@ -418,9 +1176,145 @@ export type ShowcaseState = { seen_features: Array<string>, };
 
 export type SendMessageShortcut = "ModifierEnter" | "Enter";
 
+export type Settings = { 
+/**
+ * Never remove expired or orphaned workspaces; replaces `DISABLE_WORKTREE_CLEANUP`
+ */
+disable_worktree_cleanup: boolean, 
+/**
+ * Keep orphaned workspaces that still hold git worktrees so they can be adopted;
+ * replaces `RECONCILE_ORPHAN_WORKTREES`
+ */
+reconcile_orphan_worktrees: boolean, 
+/**
+ * Directory new workspaces are created under; unset uses the system temp directory
+ */
+worktree_base_dir: string | null, 
+/**
+ * Hours an idle workspace of an in-progress or in-review task is kept before cleanup
+ */
+workspace_retention_hours: number, 
+/**
+ * Hours an idle archived workspace, or one whose task is not active, is kept
+ */
+archived_workspace_retention_hours: number, 
+/**
+ * Most coding agent runs allowed at once; unset means no limit
+ */
+max_concurrent_agents: number | null, 
+/**
+ * URLs that receive a JSON POST for every notification, alongside sound and push
+ */
+notification_webhooks: Array<string>, 
+/**
+ * Monthly agent spend in US dollars that triggers a notification once exceeded;
+ * counts only agents that report their cost
+ */
+monthly_budget_usd: number | null, 
+/**
+ * Days a finished process's output is stored in full; unset keeps it forever
+ */
+log_retention_days: number | null, 
+/**
+ * How output older than the retention period is compacted
+ */
+log_retention_policy: LogRetentionPolicy, 
+/**
+ * Output entries kept at each end by the head and tail policy
+ */
+log_retention_keep_entries: number, 
+/**
+ * Directory the archive policy writes output to, such as a mounted object store
+ */
+log_archive_dir: string | null, 
+/**
+ * Hours between sweeps for orphaned workspace directories; the first runs at startup
+ */
+orphan_cleanup_interval_hours: number, 
+/**
+ * Orphaned directories modified within this many hours are left for the next sweep
+ */
+orphan_cleanup_grace_hours: number, 
+/**
+ * Directory names or absolute paths under the workspace directories that are never
+ * removed as orphans
+ */
+orphan_cleanup_exclusions: Array<string>, 
+/**
+ * Recreate or repair missing and broken worktrees when the status or diff of their
+ * workspace is requested, recording each repair as a workspace event
+ */
+auto_repair_worktrees: boolean, 
+/**
+ * Directory collected execution artifacts are stored under, such as a mounted object
+ * store; unset uses `artifacts` in the app's data directory
+ */
+artifacts_dir: string | null, 
+/**
+ * Bucket artifacts and archived logs are stored in instead of the artifacts and log
+ * archive directories; credentials are read from `AWS_ACCESS_KEY_ID` and
+ * `AWS_SECRET_ACCESS_KEY`
+ */
+object_storage: ObjectStorageSettings | null, 
+/**
+ * Regular expressions masked in process output before it is stored or streamed,
+ * alongside the values of the secrets the process was given
+ */
+output_redaction_patterns: Array<string>, 
+/**
+ * Commits a workspace's target branch may gain past their merge base before a
+ * notification suggests rebasing it; unset turns the check off
+ */
+target_drift_threshold_commits: number | null, 
+/**
+ * Minutes between fetches of the remotes of every registered repo that has not opted
+ * out; unset turns scheduled fetching off
+ */
+repo_fetch_interval_minutes: number | null, };
+
+/**
+ * What the log retention job does with a finished process's output once it expires
+ */
+export type LogRetentionPolicy = "truncate" | "head_tail" | "archive";
+
+/**
+ * S3-compatible bucket that artifacts and archived logs are uploaded to
+ */
+export type ObjectStorageSettings = { bucket: string, 
+/**
+ * Endpoint of an S3-compatible service such as MinIO or R2; unset uses AWS
+ */
+endpoint: string | null, region: string, 
+/**
+ * Prepended to the key of every object, to share a bucket between servers
+ */
+prefix: string | null, };
+
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 
-export type QueuedMessage = { 
+export type BlameLineOrigin = "base" | "branch" | "uncommitted";
+
+export type BlameLine = { 
+/**
+ * 1-based line number in the worktree version of the file
+ */
+line_number: number, content: string, origin: BlameLineOrigin, 
+/**
+ * Commit that last changed the line; unset for uncommitted lines
+ */
+commit_sha: string | null, author: string | null, summary: string | null, committed_at: Date | null, };
+
+export type FileHistoryEntry = { commit_sha: string, author: string, summary: string, committed_at: Date, 
+/**
+ * Path of the file in this commit, which differs from the current path across renames
+ */
+path: string, 
+/**
+ * True if the commit was made on the workspace branch rather than inherited from the base
+ */
+on_branch: boolean, };
+
+export type QueuedMessage = { id: string, 
 /**
  * The session this message is queued for
  */
@@ -436,6 +1330,59 @@ queued_at: string, };
 
 export type QueueStatus = { "status": "empty" } | { "status": "queued", message: QueuedMessage, };
 
+export type ProjectEventEntity = "task" | "workspace" | "execution_process";
+
+export type ProjectEventOp = "created" | "updated" | "deleted";
+
+export type ProjectEvent = { 
+/**
+ * Monotonic id shared by all projects; sent as the SSE event id
+ */
+id: number, project_id: string, entity: ProjectEventEntity, entity_id: string, op: ProjectEventOp, 
+/**
+ * The record after the change (`TaskWithAttemptStatus`, `WorkspaceWithStatus` or
+ * `ExecutionProcess`); absent for deletions
+ */
+data: unknown, };
+
+export type OrphanWorktree = { 
+/**
+ * Directory name inside the workspace; matches the repo name for tool-created workspaces
+ */
+dir_name: string, 
+/**
+ * Main repository the worktree belongs to
+ */
+repo_path: string, 
+/**
+ * Registered repo at `repo_path`, if any
+ */
+repo_id: string | null, 
+/**
+ * Checked-out branch; unset for a detached HEAD
+ */
+branch: string | null, has_uncommitted_changes: boolean, };
+
+export type OrphanWorkspace = { path: string, worktrees: Array<OrphanWorktree>, };
+
+export type WorktreeIssueKind = "not_created" | "missing" | "broken_git_link" | "unregistered" | "branch_mismatch" | "detached_head" | "locked" | "prunable";
+
+export type WorktreeIssue = { kind: WorktreeIssueKind, message: string, repair: string, };
+
+export type RepoVerification = { repo_id: string, repo_name: string, worktree_path: string | null, issues: Array<WorktreeIssue>, };
+
+export type WorkspaceVerification = { workspace_id: string, 
+/**
+ * No repo has issues
+ */
+healthy: boolean, repos: Array<RepoVerification>, };
+
+export type RepoTargetDrift = { repo_id: string, repo_name: string, target_branch: string, 
+/**
+ * Commits on the target branch since its merge base with the workspace branch
+ */
+commits_behind: number, };
+
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
 export type RalphStory = { id: string, title: string, passes: boolean, 
@@ -466,16 +1413,40 @@ export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAct
 
 export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path: Array<string>, template: JsonValue, preconfigured: JsonValue, is_toml_config: boolean, };
 
+export type AgentPolicy = { 
+/**
+ * Whether the agent may reach the network 
+ */
+allow_network: boolean, 
+/**
+ * Programs the agent may run in its shell; unset allows any
+ */
+allowed_commands: Array<string> | null, 
+/**
+ * Whether the agent may only write files inside the workspace
+ */
+restrict_writes_to_worktree: boolean, };
+
+export type Sandbox = "bubblewrap" | "sandbox_exec";
+
 export type ExecutorActionType = { "type": "CodingAgentInitialRequest" } & CodingAgentInitialRequest | { "type": "CodingAgentFollowUpRequest" } & CodingAgentFollowUpRequest | { "type": "ScriptRequest" } & ScriptRequest | { "type": "ReviewRequest" } & ReviewRequest;
 
-export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer" | "ToolInstallScript";
+export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer" | "ToolInstallScript" | "QualityGate" | "TestRun" | "AdHocCommand" | "ProjectScript";
 
 export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, 
 /**
  * Optional relative path to execute the script in (relative to container_ref).
  * If None, uses the container_ref directory directly.
  */
-working_dir: string | null, };
+working_dir: string | null, 
+/**
+ * Extra environment variables for the script, applied over the execution environment
+ */
+env: { [key in string]?: string }, 
+/**
+ * Glob patterns, relative to the working directory, of files kept after the run
+ */
+artifacts: Array<string>, };
 
 export type ScriptRequestLanguage = "Bash";
 
@@ -517,13 +1488,13 @@ export type ExecutorConfigs = { executors: { [key in BaseCodingAgent]?: Executor
 
 export enum BaseAgentCapability { SESSION_FORK = "SESSION_FORK", SETUP_HELPER = "SETUP_HELPER", CONTEXT_USAGE = "CONTEXT_USAGE" }
 
-export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
-export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
-export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
-export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_apply_patch_tool?: boolean | null, model_provider?: string | null, compact_prompt?: string | null, developer_instructions?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_apply_patch_tool?: boolean | null, model_provider?: string | null, compact_prompt?: string | null, developer_instructions?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
 export type SandboxMode = "auto" | "read-only" | "workspace-write" | "danger-full-access";
 
@@ -535,9 +1506,9 @@ export type ReasoningSummary = "auto" | "concise" | "detailed" | "none";
 
 export type ReasoningSummaryFormat = "none" | "experimental";
 
-export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
-export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
 export type Opencode = { append_prompt: AppendPrompt, model?: string | null, variant?: string | null, mode?: string | null, 
 /**
@@ -547,11 +1518,11 @@ auto_approve: boolean,
 /**
  * Enable auto-compaction when the context length approaches the model's context window limit
  */
-auto_compact: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+auto_compact: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
-export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
-export type Droid = { append_prompt: AppendPrompt, autonomy: Autonomy, model?: string | null, reasoning_effort?: DroidReasoningEffort | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Droid = { append_prompt: AppendPrompt, autonomy: Autonomy, model?: string | null, reasoning_effort?: DroidReasoningEffort | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, artifacts?: Array<string> | null, };
 
 export type Autonomy = "normal" | "low" | "medium" | "high" | "skip-permissions-unsafe";
 
@@ -601,7 +1572,20 @@ export type NormalizedEntry = { timestamp: string | null, entry_type: Normalized
 
 export type NormalizedEntryType = { "type": "user_message" } | { "type": "user_feedback", denied_tool: string, } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, status: ToolStatus, } | { "type": "system_message" } | { "type": "error_message", error_type: NormalizedEntryError, } | { "type": "thinking" } | { "type": "loading" } | { "type": "next_action", failed: boolean, execution_processes: number, needs_setup: boolean, } | { "type": "token_usage_info" } & TokenUsageInfo;
 
-export type TokenUsageInfo = { total_tokens: number, model_context_window: number, };
+export type TokenUsageInfo = { total_tokens: number, model_context_window: number, 
+/**
+ * Totals for the whole turn, reported by agents that track billing when the turn ends
+ */
+turn_usage?: TurnUsage, };
+
+/**
+ * Tokens and cost of a complete coding agent turn
+ */
+export type TurnUsage = { 
+/**
+ * Input tokens including cache reads and writes
+ */
+input_tokens: number, output_tokens: number, cost_usd: number | null, };
 
 export type FileChange = { "action": "write", content: string, } | { "action": "delete" } | { "action": "rename", new_path: string, } | { "action": "edit", 
 /**